@@ -0,0 +1,375 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use crate::operator_selection::Bandit;
+use crate::{ConfigError, Stagnation};
+
+/// Rechenberg's 1/5 success rule for the probability of mutation: after every generation pm is increased
+/// if more than one fifth of the mutations were successful (the mutated agent is better than it was before
+/// the mutation), and decreased if fewer were
+///
+/// Measuring the success needs the fitness of the agents before and after the mutation, so every mutation
+/// costs two evaluations (they are counted in the evaluations of the result).
+/// To adapt the step size of the mutation instead of its probability, use the SelfAdaptive genome.
+///
+/// # Examples:
+/// ```
+/// use rand::prelude::*;
+/// use bbte_optim_tzim1773_genetic::{Genetic, OneFifthRule};
+///
+/// fn main() {
+///     let test = Genetic::builder()
+///         .max_generation(50)
+///         .random_agent(|rng| rng.gen_range(-5.0, 5.0))
+///         .fitness(|a: &f64| 5.0 - a * a)
+///         .mutate(|a: &f64, rng| *a + rng.gen_range(-0.1, 0.1))
+///         .offspring(|a: &f64, b: &f64, _rng| (*a + *b) / 2.0)
+///         .pm_adaptation(OneFifthRule::default())
+///         .build()
+///         .unwrap();
+///
+///     println!("{}", test.run().best); // should be a number close to 0
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OneFifthRule {
+    /// pm is multiplied by this value when it decreases and divided by it when it increases (0.0 < factor < 1.0)
+    /// Suggested value: 0.85
+    pub factor: f64,
+    /// pm doesn't go under this value
+    pub min: f64,
+    /// pm doesn't go over this value
+    pub max: f64,
+}
+
+impl Default for OneFifthRule {
+    fn default() -> Self {
+        OneFifthRule { factor: 0.85, min: 0.01, max: 1.0 }
+    }
+}
+
+impl OneFifthRule {
+    /// Returns the adapted probability of mutation
+    ///
+    /// # Arguments:
+    ///
+    /// * `pm` the current probability of mutation
+    /// * `successes` number of successful mutations of the generation
+    /// * `mutations` number of mutations of the generation (pm doesn't change if it's 0)
+    pub fn adapt(&self, pm: f64, successes: usize, mutations: usize) -> f64 {
+        if mutations == 0 {
+            return pm;
+        }
+        let ratio = successes as f64 / mutations as f64;
+        let pm = if ratio > 0.2 {
+            pm / self.factor
+        } else if ratio < 0.2 {
+            pm * self.factor
+        } else {
+            pm
+        };
+        pm.max(self.min).min(self.max)
+    }
+
+    /// Checks whether the parameters are usable
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if !(self.factor > 0.0 && self.factor < 1.0) {
+            return Err(ConfigError::InvalidAdaptation(format!("factor must be between 0.0 and 1.0, got {}", self.factor)));
+        }
+        if !(0.0 <= self.min && self.min <= self.max && self.max <= 1.0) {
+            return Err(ConfigError::InvalidAdaptation(format!("0.0 <= min <= max <= 1.0 must hold, got {} and {}", self.min, self.max)));
+        }
+
+        Ok(())
+    }
+}
+
+/// Sets pc and pm after every generation from the diversity of the population, relative to the diversity of
+/// the first generation: with full diversity pc is pc_max and pm is pm_min, when the population converges
+/// pc goes down to pc_min and pm goes up to pm_max (linearly)
+///
+/// The diversity is the standard deviation of the fitness, or the average distance of the agents if a
+/// distance function is given with distance().
+///
+/// # Examples:
+/// ```
+/// use rand::prelude::*;
+/// use bbte_optim_tzim1773_genetic::{DiversityAdaptation, Genetic};
+///
+/// fn main() {
+///     let test = Genetic::builder()
+///         .max_generation(50)
+///         .random_agent(|rng| rng.gen_range(-5.0, 5.0))
+///         .fitness(|a: &f64| 5.0 - a * a)
+///         .mutate(|a: &f64, rng| *a + rng.gen_range(-0.1, 0.1))
+///         .offspring(|a: &f64, b: &f64, _rng| (*a + *b) / 2.0)
+///         .distance(|a: &f64, b: &f64| (a - b).abs())
+///         .diversity_adaptation(DiversityAdaptation::default())
+///         .build()
+///         .unwrap();
+///
+///     println!("{}", test.run().best); // should be a number close to 0
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DiversityAdaptation {
+    /// pc of a converged population
+    pub pc_min: f64,
+    /// pc of a population as diverse as the first generation
+    pub pc_max: f64,
+    /// pm of a population as diverse as the first generation
+    pub pm_min: f64,
+    /// pm of a converged population
+    pub pm_max: f64,
+}
+
+impl Default for DiversityAdaptation {
+    fn default() -> Self {
+        DiversityAdaptation { pc_min: 0.3, pc_max: 0.9, pm_min: 0.05, pm_max: 0.5 }
+    }
+}
+
+impl DiversityAdaptation {
+    /// Returns (pc, pm) for the given diversity
+    ///
+    /// # Arguments:
+    ///
+    /// * `diversity` the diversity of the current generation
+    /// * `initial` the diversity of the first generation (it's taken as full diversity)
+    pub fn adapt(&self, diversity: f64, initial: f64) -> (f64, f64) {
+        let ratio = if initial > 0.0 && diversity.is_finite() { (diversity / initial).clamp(0.0, 1.0) } else { 0.0 };
+
+        (self.pc_min + (self.pc_max - self.pc_min) * ratio, self.pm_max - (self.pm_max - self.pm_min) * ratio)
+    }
+
+    /// Checks whether the parameters are usable
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if !(0.0 <= self.pc_min && self.pc_min <= self.pc_max && self.pc_max <= 1.0) {
+            return Err(ConfigError::InvalidAdaptation(format!("0.0 <= pc_min <= pc_max <= 1.0 must hold, got {} and {}", self.pc_min, self.pc_max)));
+        }
+        if !(0.0 <= self.pm_min && self.pm_min <= self.pm_max && self.pm_max <= 1.0) {
+            return Err(ConfigError::InvalidAdaptation(format!("0.0 <= pm_min <= pm_max <= 1.0 must hold, got {} and {}", self.pm_min, self.pm_max)));
+        }
+
+        Ok(())
+    }
+}
+
+/// Signal that the fitness landscape changed (in dynamic optimization), it starts a burst of hypermutation in the
+/// runs using it
+///
+/// The signal is a shared flag, so it can be given from the fitness function or from another thread, the run
+/// clears it when it starts the burst (after the current generation).
+#[derive(Debug, Clone, Default)]
+pub struct LandscapeChange(Arc<AtomicBool>);
+
+impl LandscapeChange {
+    /// Creates a signal that wasn't given
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Tells the runs using this signal that the fitness landscape changed
+    pub fn signal(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    /// Returns true (and clears the signal) if signal() was called since the last time
+    pub(crate) fn take(&self) -> bool {
+        self.0.swap(false, Ordering::SeqCst)
+    }
+}
+
+/// Triggered hypermutation: when the best fitness stagnates or the fitness landscape changes, pm is multiplied by
+/// `factor` (at most 1.0), and the multiplier decays back to 1.0 linearly over `generations` generations
+///
+/// The burst works on top of the other ways of setting pm (schedules or adaptation), it doesn't change their
+/// value. A new burst doesn't start on stagnation while one is going on, and the stagnation is measured from the
+/// start of the last burst; a landscape change always starts a new one.
+///
+/// # Examples:
+/// ```
+/// use rand::prelude::*;
+/// use bbte_optim_tzim1773_genetic::{Genetic, Hypermutation, LandscapeChange, Stagnation};
+///
+/// fn main() {
+///     let change = LandscapeChange::new();
+///     let test = Genetic::builder()
+///         .max_generation(100)
+///         .pm(0.05)
+///         .random_agent(|rng| rng.gen_range(-5.0, 5.0))
+///         .fitness(|a: &f64| 5.0 - a * a)
+///         .mutate(|a: &f64, rng| *a + rng.gen_range(-0.5, 0.5))
+///         .offspring(|a: &f64, b: &f64, _rng| (*a + *b) / 2.0)
+///         .hypermutation(Hypermutation::new(10.0, 5).on_stagnation(Stagnation::new(10)).on_change(change.clone()))
+///         .build()
+///         .unwrap();
+///
+///     // change.signal() can be called when the optimum moves
+///     println!("{}", test.run().best); // should be a number close to 0
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Hypermutation {
+    /// pm is multiplied by this value in the first generation of a burst (at least 1.0)
+    /// Suggested value: 10.0
+    pub factor: f64,
+    /// The number of generations of a burst (at least 1)
+    /// Suggested value: 10
+    pub generations: usize,
+    /// If it's given, a burst starts when the best fitness doesn't improve for a number of generations
+    pub stagnation: Option<Stagnation>,
+    /// If it's given, a burst starts when the landscape change is signalled
+    pub change: Option<LandscapeChange>,
+}
+
+impl Hypermutation {
+    /// Bursts multiplying pm by `factor` and lasting `generations` generations (a trigger has to be given)
+    pub fn new(factor: f64, generations: usize) -> Self {
+        Hypermutation { factor, generations, stagnation: None, change: None }
+    }
+
+    /// Starts a burst when the best fitness stagnates
+    pub fn on_stagnation(mut self, stagnation: Stagnation) -> Self {
+        self.stagnation = Some(stagnation);
+        self
+    }
+
+    /// Starts a burst when the landscape change is signalled
+    pub fn on_change(mut self, change: LandscapeChange) -> Self {
+        self.change = Some(change);
+        self
+    }
+
+    /// Returns the multiplier of pm with the given number of generations left from the burst
+    pub fn multiplier(&self, left: usize) -> f64 {
+        1.0 + (self.factor - 1.0) * left.min(self.generations) as f64 / self.generations as f64
+    }
+
+    /// Checks whether the parameters are usable
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if !(self.factor >= 1.0 && self.factor.is_finite()) {
+            return Err(ConfigError::InvalidHypermutation(format!("factor must be at least 1.0, got {}", self.factor)));
+        }
+        if self.generations == 0 {
+            return Err(ConfigError::InvalidHypermutation("a burst needs at least 1 generation".to_string()));
+        }
+        if self.stagnation.is_none() && self.change.is_none() {
+            return Err(ConfigError::InvalidHypermutation("a stagnation or a landscape change has to trigger it".to_string()));
+        }
+        if let Some(stagnation) = &self.stagnation {
+            if stagnation.generations == 0 || stagnation.epsilon.is_nan() || stagnation.epsilon < 0.0 {
+                return Err(ConfigError::InvalidHypermutation(format!("the stagnation needs at least 1 generation and a non-negative epsilon, got {:?}", stagnation)));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The probabilities and the penalty coefficients used by a run (they can change during the run) and the statistics
+/// they are adapted from
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct Rates {
+    pub pc: f64,
+    pub pm: f64,
+    pub mutations: usize,
+    pub successes: usize,
+    pub initial_diversity: Option<f64>,
+    pub mutation_bandit: Bandit,
+    pub crossover_bandit: Bandit,
+    pub penalties: Vec<f64>,
+    pub penalty_streaks: Vec<isize>,
+    pub burst: usize,
+    pub burst_start: usize,
+    pub pm_multiplier: f64,
+}
+
+impl Rates {
+    pub fn new(pc: f64, pm: f64, mutation_bandit: Bandit, crossover_bandit: Bandit) -> Self {
+        Rates { pc, pm, mutations: 0, successes: 0, initial_diversity: None, mutation_bandit, crossover_bandit, penalties: Vec::new(), penalty_streaks: Vec::new(), burst: 0, burst_start: 0, pm_multiplier: 1.0 }
+    }
+
+    /// The probability of mutation of the generation (pm raised by the hypermutation)
+    pub fn mutation_probability(&self) -> f64 {
+        (self.pm * self.pm_multiplier).min(1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Genetic;
+    use rand::prelude::*;
+
+    #[test]
+    fn one_fifth_rule() {
+        let rule = OneFifthRule { factor: 0.5, min: 0.1, max: 0.8 };
+
+        assert_eq!(rule.adapt(0.2, 5, 10), 0.4);
+        assert_eq!(rule.adapt(0.4, 1, 10), 0.2);
+        assert_eq!(rule.adapt(0.4, 2, 10), 0.4);
+        assert_eq!(rule.adapt(0.4, 0, 0), 0.4);
+        assert_eq!(rule.adapt(0.6, 10, 10), 0.8);
+        assert_eq!(rule.adapt(0.15, 0, 10), 0.1);
+
+        assert!(rule.validate().is_ok());
+        assert!(OneFifthRule { factor: 1.0, ..rule }.validate().is_err());
+        assert!(OneFifthRule { min: 0.9, ..rule }.validate().is_err());
+    }
+
+    #[test]
+    fn diversity_adaptation() {
+        let adaptation = DiversityAdaptation { pc_min: 0.2, pc_max: 0.8, pm_min: 0.1, pm_max: 0.5 };
+
+        let close = |(pc, pm): (f64, f64), expected: (f64, f64)| (pc - expected.0).abs() < 1e-12 && (pm - expected.1).abs() < 1e-12;
+
+        assert!(close(adaptation.adapt(4.0, 4.0), (0.8, 0.1)));
+        assert!(close(adaptation.adapt(8.0, 4.0), (0.8, 0.1)));
+        assert!(close(adaptation.adapt(0.0, 4.0), (0.2, 0.5)));
+        assert!(close(adaptation.adapt(1.0, 0.0), (0.2, 0.5)));
+        assert!(close(adaptation.adapt(2.0, 4.0), (0.5, 0.3)));
+
+        assert!(adaptation.validate().is_ok());
+        assert!(DiversityAdaptation { pm_min: 0.6, ..adaptation }.validate().is_err());
+    }
+
+    #[test]
+    fn hypermutation() {
+        let stagnation = Stagnation::new(3);
+        assert_eq!(Hypermutation::new(5.0, 4).multiplier(2), 3.0);
+        assert!(Hypermutation::new(5.0, 4).validate().is_err());
+        assert!(Hypermutation::new(0.5, 4).on_stagnation(stagnation).validate().is_err());
+        assert!(Hypermutation::new(5.0, 0).on_stagnation(stagnation).validate().is_err());
+
+        // nothing ever improves: a burst starts after every 4th generation and decays in 4 generations
+        let change = LandscapeChange::new();
+        let test = Genetic::builder()
+            .population(10)
+            .max_generation(20)
+            .pm(0.05)
+            .random_agent(|rng| rng.gen_range(0, 1000))
+            .fitness(|_a: &i32| 1.0)
+            .mutate(|a: &i32, _rng| *a)
+            .offspring(|a: &i32, _b: &i32, _rng| *a)
+            .hypermutation(Hypermutation::new(5.0, 4).on_stagnation(stagnation).on_change(change.clone()))
+            .build()
+            .unwrap();
+
+        let mut state = test.start_with_rng(StdRng::seed_from_u64(1)).unwrap();
+        let mut pms = Vec::new();
+        for _ in 0..9 {
+            test.step(&mut state).unwrap();
+            pms.push(state.mutation_probability());
+        }
+        let expected = [0.05, 0.05, 0.05, 0.25, 0.2, 0.15, 0.1, 0.25, 0.2];
+        assert!(pms.iter().zip(&expected).all(|(pm, expected)| (pm - expected).abs() < 1e-12), "{:?}", pms);
+
+        // the landscape change restarts the burst
+        test.step(&mut state).unwrap();
+        change.signal();
+        test.step(&mut state).unwrap();
+        assert!((state.mutation_probability() - 0.25).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,245 @@
+use rand::prelude::*;
+
+use crate::error::EvaluationBudget;
+use crate::{ConfigError, Fitness, Genetic, MaybeSync, Objective, RunError, Termination};
+
+/// How the temperature of simulated annealing decreases with the iterations (T0 is the starting temperature,
+/// k the index of the iteration and n the number of iterations)
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Cooling {
+    /// T0 * rate^k, the rate is between 0.0 and 1.0 (usually very close to 1.0, e.g. 0.999)
+    Exponential {
+        /// Multiplier of an iteration
+        rate: f64,
+    },
+    /// T0 * (1 - k / n), reaching 0 at the end of the run
+    Linear,
+    /// T0 / (1 + ln(1 + k)), slow cooling
+    Logarithmic,
+    /// T0 / (1 + k), fast (Cauchy) cooling
+    Fast,
+}
+
+impl Cooling {
+    /// Returns the temperature in the given iteration
+    ///
+    /// # Arguments:
+    ///
+    /// * `temperature` the starting temperature
+    /// * `iteration` the index of the iteration (starting from 0)
+    /// * `iterations` the number of iterations of the run
+    pub fn temperature(&self, temperature: f64, iteration: usize, iterations: usize) -> f64 {
+        let k = iteration as f64;
+        match self {
+            Cooling::Exponential { rate } => temperature * rate.powf(k),
+            Cooling::Linear => temperature * (1.0 - k / iterations.max(1) as f64).max(0.0),
+            Cooling::Logarithmic => temperature / (1.0 + k.ln_1p()),
+            Cooling::Fast => temperature / (1.0 + k),
+        }
+    }
+}
+
+/// Simulated annealing on the problem of a Genetic: a single agent is mutated again and again, the mutants that are
+/// not worse are always accepted, the worse ones with probability exp(-difference / temperature), and the
+/// temperature decreases according to the cooling schedule
+///
+/// The starting agent, the fitness and the neighbours come from the get_random_agent, f_fitness and f_mutate (or the
+/// registered mutation operators) of the Genetic, so the same problem can be compared with both algorithms (the
+/// Genetic stays accessible in the `genetic` field). The differences are measured with Fitness::to_f64(), so the
+/// temperature is on the scale of the fitness. The run makes as many iterations as the evaluations of the Genetic
+/// (max_generation * population, every iteration evaluates a mutant) and it stops early if max_evaluations is
+/// reached; the fitness cache, the fitness_error_policy and repair() are used, the other settings are not.
+///
+/// # Examples:
+/// ```
+/// use rand::prelude::*;
+/// use bbte_optim_tzim1773_genetic::{Cooling, Genetic, SimulatedAnnealing};
+///
+/// fn main() {
+///     let genetic = Genetic::builder()
+///         .max_generation(50)
+///         .random_agent(|rng| rng.gen_range(-5.0, 5.0))
+///         .fitness(|a: &f64| 5.0 - a * a)
+///         .mutate(|a: &f64, rng| *a + rng.gen_range(-0.1, 0.1))
+///         .offspring(|a: &f64, b: &f64, _rng| (*a + *b) / 2.0)
+///         .build()
+///         .unwrap();
+///
+///     let annealing = SimulatedAnnealing::new(genetic, 1.0, Cooling::Exponential { rate: 0.995 }).unwrap();
+///     println!("{}", annealing.run().best); // should be a number close to 0
+///     println!("{}", annealing.genetic.run().best); // the same problem solved by the genetic algorithm
+/// }
+/// ```
+pub struct SimulatedAnnealing<'a, T, F = f64> {
+    /// The Genetic giving the agents, the fitness, the neighbours and the number of evaluations
+    pub genetic: Genetic<'a, T, F>,
+    /// The starting temperature (on the scale of the fitness differences, 0.0 accepts only the mutants that are not
+    /// worse)
+    /// Suggested value: about the fitness difference of two random agents
+    pub temperature: f64,
+    /// How the temperature decreases
+    /// Suggested value: Cooling::Exponential with a rate that brings the temperature near 0 by the end of the run
+    pub cooling: Cooling,
+}
+
+/// The outcome of simulated annealing
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnnealingResult<T, F = f64> {
+    /// The best agent found
+    pub best: T,
+    /// The fitness of the best agent
+    pub best_fitness: F,
+    /// The agent the annealing ended with
+    pub current: T,
+    /// The number of iterations executed (fewer than max_generation * population if max_evaluations was reached)
+    pub iterations: usize,
+    /// The number of accepted mutants (including the better ones)
+    pub accepted: usize,
+    /// The number of times the fitness function was called
+    pub evaluations: usize,
+    /// The best fitness after every iteration (for plotting the convergence, using Fitness::to_f64())
+    pub history: Vec<f64>,
+    /// Why the annealing stopped (MaxGeneration if every iteration was executed, EvaluationLimit if max_evaluations
+    /// was reached)
+    pub termination: Termination,
+}
+
+impl<'a, T, F> SimulatedAnnealing<'a, T, F>
+    where T: Clone + MaybeSync, F: Fitness + MaybeSync {
+    /// Creates simulated annealing on the problem of the Genetic, checking the temperature and the cooling
+    ///
+    /// # Arguments:
+    ///
+    /// * `genetic` the agents, the fitness, the neighbours and the number of evaluations
+    /// * `temperature` the starting temperature
+    /// * `cooling` the cooling schedule
+    pub fn new(genetic: Genetic<'a, T, F>, temperature: f64, cooling: Cooling) -> Result<Self, ConfigError> {
+        if !(temperature >= 0.0 && temperature.is_finite()) {
+            return Err(ConfigError::InvalidAnnealing(format!("the temperature must be finite and not negative, got {}", temperature)));
+        }
+        if let Cooling::Exponential { rate } = cooling {
+            if !(rate > 0.0 && rate <= 1.0) {
+                return Err(ConfigError::InvalidAnnealing(format!("the rate of the exponential cooling must be bigger than 0.0 and at most 1.0, got {}", rate)));
+            }
+        }
+
+        Ok(SimulatedAnnealing { genetic, temperature, cooling })
+    }
+
+    /// Runs the annealing until the iterations (or the evaluations) run out
+    ///
+    /// # Panics:
+    ///  If the fitness function fails and the fitness_error_policy gives up on the agent
+    ///  (use try_run() to get the error instead)
+    pub fn run(&self) -> AnnealingResult<T, F> {
+        self.run_with_rng(&mut thread_rng())
+    }
+
+    /// Same as run(), but returns the error of the fitness function instead of panicking
+    pub fn try_run(&self) -> Result<AnnealingResult<T, F>, RunError> {
+        self.try_run_with_rng(&mut thread_rng())
+    }
+
+    /// Same as run(), but uses the given random number generator
+    pub fn run_with_rng(&self, rng: &mut dyn RngCore) -> AnnealingResult<T, F> {
+        match self.try_run_with_rng(rng) {
+            Ok(result) => result,
+            Err(error) => panic!("{}", error),
+        }
+    }
+
+    /// Same as run(), but uses a random number generator seeded with the given seed, so the runs can be reproduced
+    pub fn run_with_seed(&self, seed: u64) -> AnnealingResult<T, F> {
+        self.run_with_rng(&mut StdRng::seed_from_u64(seed))
+    }
+
+    /// Same as run_with_rng(), but returns the error of the fitness function instead of panicking
+    pub fn try_run_with_rng(&self, rng: &mut dyn RngCore) -> Result<AnnealingResult<T, F>, RunError> {
+        let genetic = &self.genetic;
+        let iterations = genetic.max_generation * genetic.population;
+        let mut evaluations = 0;
+
+        let mut current = genetic.repair((genetic.get_random_agent)(rng));
+        let mut f_current = genetic.evaluate_unlimited(&current, &mut evaluations)?;
+        let (mut best, mut f_best) = (current.clone(), f_current.clone());
+        let (mut iteration, mut accepted) = (0, 0);
+        let mut history = Vec::with_capacity(iterations);
+        let mut termination = Termination::MaxGeneration;
+
+        while iteration < iterations {
+            let operator = rng.gen_range(0, genetic.mutation_operators.len().max(1));
+            let neighbour = genetic.apply_mutation(operator, &current, rng);
+            let f_neighbour = match genetic.evaluate(&neighbour, &mut evaluations) {
+                Ok(f_neighbour) => f_neighbour,
+                Err(error) if error.is::<EvaluationBudget>() => {
+                    termination = Termination::EvaluationLimit;
+                    break;
+                }
+                Err(error) => return Err(error.into()),
+            };
+
+            let temperature = self.cooling.temperature(self.temperature, iteration, iterations);
+            let worsening = match genetic.objective {
+                Objective::Maximize => f_current.to_f64() - f_neighbour.to_f64(),
+                Objective::Minimize => f_neighbour.to_f64() - f_current.to_f64(),
+            };
+            let accept = !genetic.objective.is_better(&f_current, &f_neighbour)
+                || (temperature > 0.0 && rng.gen::<f64>() < (-worsening / temperature).exp());
+            if accept {
+                current = neighbour;
+                f_current = f_neighbour;
+                accepted += 1;
+                if genetic.objective.is_better(&f_current, &f_best) {
+                    best = current.clone();
+                    f_best = f_current.clone();
+                }
+            }
+            history.push(f_best.to_f64());
+            iteration += 1;
+        }
+
+        Ok(AnnealingResult { best, best_fitness: f_best, current, iterations: iteration, accepted, evaluations, history, termination })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cooling() {
+        assert_eq!(Cooling::Exponential { rate: 0.5 }.temperature(8.0, 2, 10), 2.0);
+        assert_eq!(Cooling::Linear.temperature(8.0, 5, 10), 4.0);
+        assert_eq!(Cooling::Linear.temperature(8.0, 10, 10), 0.0);
+        assert_eq!(Cooling::Logarithmic.temperature(8.0, 0, 10), 8.0);
+        assert_eq!(Cooling::Fast.temperature(8.0, 3, 10), 2.0);
+    }
+
+    #[test]
+    fn annealing() {
+        let builder = || Genetic::builder()
+            .population(10)
+            .max_generation(50)
+            .random_agent(|rng| rng.gen_range(-100, 100))
+            .fitness(|a: &i32| (a * a) as f64)
+            .minimize()
+            .mutate(|a: &i32, rng| *a + rng.gen_range(-2, 3))
+            .offspring(|a: &i32, b: &i32, _rng| (*a + *b) / 2);
+        assert!(SimulatedAnnealing::new(builder().build().unwrap(), -1.0, Cooling::Linear).is_err());
+        assert!(SimulatedAnnealing::new(builder().build().unwrap(), 1.0, Cooling::Exponential { rate: 1.5 }).is_err());
+
+        let annealing = SimulatedAnnealing::new(builder().build().unwrap(), 10.0, Cooling::Exponential { rate: 0.99 }).unwrap();
+        let result = annealing.run_with_seed(1);
+        assert_eq!(result.best, 0);
+        assert_eq!((result.iterations, result.evaluations, result.history.len()), (500, 501, 500));
+        assert_eq!(result.termination, Termination::MaxGeneration);
+        assert!(result.history.windows(2).all(|w| w[1] <= w[0]));
+
+        // without temperature only the mutants that are not worse are accepted, and the evaluations are limited
+        let greedy = SimulatedAnnealing::new(builder().max_evaluations(101).build().unwrap(), 0.0, Cooling::Fast).unwrap();
+        let result = greedy.run_with_seed(1);
+        assert_eq!((result.iterations, result.evaluations, result.termination), (100, 101, Termination::EvaluationLimit));
+        assert_eq!((result.current * result.current) as f64, result.best_fitness);
+    }
+}
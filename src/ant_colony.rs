@@ -0,0 +1,336 @@
+use rand::prelude::*;
+use std::time::Instant;
+
+use crate::engine::Engine;
+use crate::genome::Permutation;
+use crate::{ConfigError, Fitness, Genetic, MaybeSync, RunError, RunResult, Termination};
+
+/// How the pheromones of an ant colony are updated
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AcoVariant {
+    /// Ant System: every ant deposits 1 / (the cost of its tour) on the edges of its tour
+    /// Suggested evaporation: 0.5
+    AntSystem {
+        /// The part of the pheromones evaporating in every generation (0.0 < evaporation <= 1.0)
+        evaporation: f64,
+    },
+    /// MAX-MIN Ant System: only the best tour found deposits, and the pheromones are kept between
+    /// tau_max = 1 / (evaporation * the cost of the best tour) and tau_min = tau_max / (2 * n), starting from tau_max
+    /// Suggested evaporation: 0.02
+    MaxMin {
+        /// The part of the pheromones evaporating in every generation (0.0 < evaporation <= 1.0)
+        evaporation: f64,
+    },
+}
+
+impl AcoVariant {
+    fn evaporation(&self) -> f64 {
+        match *self {
+            AcoVariant::AntSystem { evaporation } | AcoVariant::MaxMin { evaporation } => evaporation,
+        }
+    }
+}
+
+/// Ant colony optimisation for permutation problems given by a cost matrix (e.g. the distances of the cities of a
+/// route): every ant builds a Permutation step by step, choosing the next element j after i with a probability
+/// proportional to pheromone(i, j)^alpha * (1 / cost(i, j))^beta, and the pheromones of the edges of the good tours
+/// are reinforced
+///
+/// The number of ants (the population), the fitness and the stopping criteria come from the Genetic: the run stops
+/// at max_generation, max_evaluations, the target fitness, the stagnation, the time limit, the stop condition, the
+/// cancellation or the callbacks, and the result is a RunResult with the statistics of every generation. The tours
+/// are evaluated like the offspring of the Genetic and repaired with its repair(), the best tour is chosen by the
+/// fitness, while the deposits are computed from the cost of the closed tours (returning to their first element) in
+/// the matrix, so the fitness is usually the cost of the route. The best of the result is the best tour found
+/// during the run, the population is the last generation of tours.
+///
+/// # Examples:
+/// ```
+/// use rand::prelude::*;
+/// use bbte_optim_tzim1773_genetic::{AcoVariant, AntColony, Genetic};
+/// use bbte_optim_tzim1773_genetic::genome::Permutation;
+///
+/// fn main() {
+///     // 12 cities on a circle, the shortest route goes around it
+///     let cities = (0..12).map(|i| (i as f64 * 0.5236).sin_cos()).collect::<Vec<(f64, f64)>>();
+///     let distances = cities.iter()
+///         .map(|a| cities.iter().map(|b| ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt()).collect())
+///         .collect::<Vec<Vec<f64>>>();
+///     let tour = distances.clone();
+///     let genetic = Genetic::builder()
+///         .population(12)
+///         .max_generation(100)
+///         .random_agent(|rng| Permutation::random(12, rng))
+///         .fitness(move |a: &Permutation| (0..12).map(|i| tour[a[i]][a[(i + 1) % 12]]).sum::<f64>())
+///         .minimize()
+///         .mutate(|a: &Permutation, _rng| a.clone())
+///         .offspring(|a: &Permutation, _b: &Permutation, _rng| a.clone())
+///         .build()
+///         .unwrap();
+///
+///     let colony = AntColony::new(genetic, distances, AcoVariant::MaxMin { evaporation: 0.02 }).unwrap();
+///     println!("{:?}", colony.run().best); // should go around the circle
+/// }
+/// ```
+pub struct AntColony<'a, F = f64> {
+    /// The Genetic giving the number of ants, the fitness and the stopping criteria
+    pub genetic: Genetic<'a, Permutation, F>,
+    /// The cost of going from i to j (a square matrix, the diagonal is not used)
+    pub costs: Vec<Vec<f64>>,
+    /// How the pheromones are updated
+    pub variant: AcoVariant,
+    /// The weight of the pheromones in the choices
+    /// Suggested value: 1.0
+    pub alpha: f64,
+    /// The weight of the costs in the choices
+    /// Suggested value: 2.0 - 5.0
+    pub beta: f64,
+}
+
+impl<'a, F> AntColony<'a, F>
+    where F: Fitness + MaybeSync {
+    /// Creates an ant colony on the cost matrix, checking the parameters (alpha is 1.0 and beta is 3.0)
+    ///
+    /// # Arguments:
+    ///
+    /// * `genetic` the number of ants, the fitness and the stopping criteria
+    /// * `costs` the cost of going from i to j (at least 2 elements, no negative costs)
+    /// * `variant` Ant System or MAX-MIN Ant System
+    pub fn new(genetic: Genetic<'a, Permutation, F>, costs: Vec<Vec<f64>>, variant: AcoVariant) -> Result<Self, ConfigError> {
+        if costs.len() < 2 || costs.iter().any(|row| row.len() != costs.len()) {
+            return Err(ConfigError::InvalidAntColony("the costs must be a square matrix of at least 2 elements".to_string()));
+        }
+        if costs.iter().flatten().any(|cost| cost.is_nan() || *cost < 0.0) {
+            return Err(ConfigError::InvalidAntColony("the costs can't be negative".to_string()));
+        }
+        let evaporation = variant.evaporation();
+        if !(evaporation > 0.0 && evaporation <= 1.0) {
+            return Err(ConfigError::InvalidAntColony(format!("the evaporation must be bigger than 0.0 and at most 1.0, got {}", evaporation)));
+        }
+
+        Ok(AntColony { genetic, costs, variant, alpha: 1.0, beta: 3.0 })
+    }
+
+    /// Sets the weights of the pheromones and the costs in the choices
+    pub fn weights(mut self, alpha: f64, beta: f64) -> Self {
+        self.alpha = alpha;
+        self.beta = beta;
+        self
+    }
+
+    /// Runs the optimisation until one of the stopping criteria of the Genetic is met
+    ///
+    /// # Panics:
+    ///  If the fitness function fails and the fitness_error_policy gives up on the agent
+    ///  (use try_run() to get the error instead)
+    pub fn run(&self) -> RunResult<Permutation, F> {
+        self.run_with_rng(&mut thread_rng())
+    }
+
+    /// Same as run(), but returns the error instead of panicking
+    pub fn try_run(&self) -> Result<RunResult<Permutation, F>, RunError> {
+        self.try_run_with_rng(&mut thread_rng())
+    }
+
+    /// Same as run(), but uses the given random number generator
+    pub fn run_with_rng(&self, rng: &mut dyn RngCore) -> RunResult<Permutation, F> {
+        match self.try_run_with_rng(rng) {
+            Ok(result) => result,
+            Err(error) => panic!("{}", error),
+        }
+    }
+
+    /// Same as run(), but uses a random number generator seeded with the given seed, so the runs can be reproduced
+    pub fn run_with_seed(&self, seed: u64) -> RunResult<Permutation, F> {
+        self.run_with_rng(&mut StdRng::seed_from_u64(seed))
+    }
+
+    /// Same as run_with_rng(), but returns the error instead of panicking
+    pub fn try_run_with_rng(&self, rng: &mut dyn RngCore) -> Result<RunResult<Permutation, F>, RunError> {
+        let start = Instant::now();
+        let genetic = &self.genetic;
+        let n = self.costs.len();
+        let evaporation = self.variant.evaporation();
+        // the attractiveness of the edges by their costs, it doesn't change during the run
+        let visibility = self.costs.iter()
+            .map(|row| row.iter().map(|cost| (1.0 / cost.max(1e-12)).powf(self.beta)).collect())
+            .collect::<Vec<Vec<f64>>>();
+        let nearest = self.tour_cost(&self.nearest_neighbour());
+        let initial = match self.variant {
+            AcoVariant::AntSystem { .. } => genetic.population as f64 / nearest,
+            AcoVariant::MaxMin { .. } => 1.0 / (evaporation * nearest),
+        };
+        let mut pheromones = vec![vec![initial; n]; n];
+
+        let tours = (0..genetic.population).map(|_| (self.construct(&pheromones, &visibility, rng), None)).collect();
+        let (mut engine, mut population) = Engine::start(genetic, start, tours)?;
+        let mut best = population[genetic.best_index(&population)].clone();
+
+        let termination = loop {
+            for row in &mut pheromones {
+                for tau in row.iter_mut() {
+                    *tau *= 1.0 - evaporation;
+                }
+            }
+            match self.variant {
+                AcoVariant::AntSystem { .. } => {
+                    for x in &population {
+                        self.deposit(&mut pheromones, &x.agent, 1.0 / self.tour_cost(&x.agent));
+                    }
+                }
+                AcoVariant::MaxMin { .. } => {
+                    let best_cost = self.tour_cost(&best.agent);
+                    self.deposit(&mut pheromones, &best.agent, 1.0 / best_cost);
+                    let tau_max = 1.0 / (evaporation * best_cost);
+                    let tau_min = tau_max / (2.0 * n as f64);
+                    for tau in pheromones.iter_mut().flatten() {
+                        *tau = tau.clamp(tau_min, tau_max);
+                    }
+                }
+            }
+            if let Some(termination) = engine.check_bound() {
+                break termination;
+            }
+
+            let tours = (0..genetic.population).map(|_| (self.construct(&pheromones, &visibility, rng), None)).collect();
+            population = match engine.evaluate(tours)? {
+                Some(tours) => tours,
+                None => break Termination::EvaluationLimit,
+            };
+            let generation_best = &population[genetic.best_index(&population)];
+            if genetic.is_fitter(generation_best, &best) {
+                best = generation_best.clone();
+            }
+            if let Some(termination) = engine.finish_generation(&population)? {
+                break termination;
+            }
+        };
+
+        Ok(engine.result(best, population.into_iter().map(|x| x.agent).collect(), termination))
+    }
+
+    /// Builds the tour of an ant from a random first element
+    fn construct(&self, pheromones: &[Vec<f64>], visibility: &[Vec<f64>], rng: &mut dyn RngCore) -> Permutation {
+        let n = self.costs.len();
+        let mut visited = vec![false; n];
+        let mut order = Vec::with_capacity(n);
+        let mut current = rng.gen_range(0, n);
+        visited[current] = true;
+        order.push(current);
+
+        while order.len() < n {
+            let weights = (0..n)
+                .map(|j| if visited[j] { 0.0 } else { pheromones[current][j].powf(self.alpha) * visibility[current][j] })
+                .collect::<Vec<f64>>();
+            let total = weights.iter().sum::<f64>();
+            let next = if total > 0.0 && total.is_finite() {
+                let mut pick = rng.gen::<f64>() * total;
+                let mut chosen = None;
+                for (j, weight) in weights.iter().enumerate() {
+                    if *weight > 0.0 {
+                        chosen = Some(j);
+                        pick -= weight;
+                        if pick <= 0.0 {
+                            break;
+                        }
+                    }
+                }
+                chosen
+            } else {
+                None
+            };
+            // when every weight vanishes the next element is chosen uniformly
+            current = next.unwrap_or_else(|| {
+                let left = (0..n).filter(|j| !visited[*j]).collect::<Vec<usize>>();
+                left[rng.gen_range(0, left.len())]
+            });
+            visited[current] = true;
+            order.push(current);
+        }
+
+        self.genetic.repair(Permutation::from_vec(order).expect("an ant visits every element once"))
+    }
+
+    /// Adds the amount to the pheromones of the edges of the closed tour (in both directions)
+    fn deposit(&self, pheromones: &mut [Vec<f64>], tour: &Permutation, amount: f64) {
+        let n = tour.len();
+        for i in 0..n {
+            let (a, b) = (tour[i], tour[(i + 1) % n]);
+            pheromones[a][b] += amount;
+            pheromones[b][a] += amount;
+        }
+    }
+
+    /// Returns the cost of the closed tour in the matrix (it's never 0, so it can be divided by)
+    fn tour_cost(&self, tour: &Permutation) -> f64 {
+        let n = tour.len();
+        (0..n).map(|i| self.costs[tour[i]][tour[(i + 1) % n]]).sum::<f64>().max(1e-12)
+    }
+
+    /// Returns the greedy tour from the first element, always going to the cheapest unvisited element
+    fn nearest_neighbour(&self) -> Permutation {
+        let n = self.costs.len();
+        let mut visited = vec![false; n];
+        let mut order = vec![0];
+        visited[0] = true;
+        while order.len() < n {
+            let current = order[order.len() - 1];
+            let next = (0..n)
+                .filter(|j| !visited[*j])
+                .min_by(|a, b| self.costs[current][*a].total_cmp(&self.costs[current][*b]))
+                .unwrap_or(0);
+            visited[next] = true;
+            order.push(next);
+        }
+
+        Permutation::from_vec(order).expect("the greedy tour visits every element once")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn circle(n: usize) -> Vec<Vec<f64>> {
+        let cities = (0..n).map(|i| (i as f64 * 2.0 * std::f64::consts::PI / n as f64).sin_cos()).collect::<Vec<(f64, f64)>>();
+        cities.iter().map(|a| cities.iter().map(|b| ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt()).collect()).collect()
+    }
+
+    fn genetic<'a>(costs: Vec<Vec<f64>>, max_generation: usize) -> Genetic<'a, Permutation> {
+        let n = costs.len();
+        Genetic::builder()
+            .population(10)
+            .max_generation(max_generation)
+            .random_agent(move |rng| Permutation::random(n, rng))
+            .fitness(move |a: &Permutation| (0..n).map(|i| costs[a[i]][a[(i + 1) % n]]).sum::<f64>())
+            .minimize()
+            .mutate(|a: &Permutation, _rng| a.clone())
+            .offspring(|a: &Permutation, _b: &Permutation, _rng| a.clone())
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn ant_colony() {
+        assert!(AntColony::new(genetic(circle(5), 10), vec![vec![0.0; 4]; 5], AcoVariant::AntSystem { evaporation: 0.5 }).is_err());
+        assert!(AntColony::new(genetic(circle(5), 10), circle(5), AcoVariant::MaxMin { evaporation: 0.0 }).is_err());
+        assert!(AntColony::new(genetic(circle(2), 10), vec![vec![0.0, -1.0], vec![1.0, 0.0]], AcoVariant::AntSystem { evaporation: 0.5 }).is_err());
+
+        // the shortest route of the cities on a circle goes around it
+        let optimum = 20.0 * (std::f64::consts::PI / 20.0).sin() * 2.0;
+        for variant in &[AcoVariant::AntSystem { evaporation: 0.5 }, AcoVariant::MaxMin { evaporation: 0.02 }] {
+            let colony = AntColony::new(genetic(circle(20), 100), circle(20), *variant).unwrap();
+            assert!((colony.tour_cost(&colony.nearest_neighbour()) - optimum).abs() < 1e-9);
+            let result = colony.run_with_seed(1);
+            assert!((result.best_fitness - optimum).abs() < 1e-9, "{:?} {}", variant, result.best_fitness);
+            assert_eq!((result.population.len(), result.evaluations), (10, 10 * 101));
+        }
+
+        // the choices don't use the pheromones without alpha, and only the pheromones without beta
+        let colony = AntColony::new(genetic(circle(6), 10), circle(6), AcoVariant::AntSystem { evaporation: 0.5 }).unwrap().weights(0.0, 0.0);
+        let mut rng = StdRng::seed_from_u64(1);
+        let tour = colony.construct(&vec![vec![1.0; 6]; 6], &vec![vec![1.0; 6]; 6], &mut rng);
+        assert_eq!(Permutation::from_vec(tour.to_vec()), Some(tour));
+    }
+}
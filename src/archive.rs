@@ -0,0 +1,98 @@
+use crate::{ConfigError, Evaluated, Objective};
+
+/// Function that tells whether two agents are the same
+pub type SameFn<'a, T> = Box<dyn Fn(&T, &T) -> bool + Send + Sync + 'a>;
+
+/// Hall of fame of a run: keeps the best `capacity` different agents ever evaluated during the run
+/// (not only the ones of the last generation), they are returned in RunResult::archive
+///
+/// Without elitism (or with a selection that can lose the best agents) a later generation can be worse
+/// than an earlier one, the archive keeps the best agents found anyway.
+///
+/// # Examples:
+/// ```
+/// use rand::prelude::*;
+/// use bbte_optim_tzim1773_genetic::{Genetic, Selection};
+///
+/// fn main() {
+///     let test = Genetic::builder()
+///         .max_generation(50)
+///         .random_agent(|rng| rng.gen_range(-50, 50))
+///         .fitness(|a: &i32| 2500 - a * a)
+///         .mutate(|a: &i32, rng| *a + rng.gen_range(-3, 4))
+///         .offspring(|a: &i32, b: &i32, _rng| (*a + *b) / 2)
+///         .selection(Selection::Roulette)
+///         .archive(5)
+///         .build()
+///         .unwrap();
+///
+///     let result = test.run();
+///     println!("{:?}", result.archive); // the best 5 agents found, the best one first
+/// }
+/// ```
+pub struct Archive<'a, T> {
+    /// The number of agents kept (at least 1)
+    pub capacity: usize,
+    f_same: SameFn<'a, T>,
+}
+
+impl<'a, T> Archive<'a, T> {
+    /// Keeps the best `capacity` agents, the equal agents are kept once
+    pub fn new(capacity: usize) -> Self
+        where T: PartialEq {
+        Self::with_same(capacity, |a: &T, b: &T| a == b)
+    }
+
+    /// Keeps the best `capacity` agents, the agents the function considers the same are kept once
+    pub fn with_same(capacity: usize, f_same: impl Fn(&T, &T) -> bool + Send + Sync + 'a) -> Self {
+        Archive { capacity, f_same: Box::new(f_same) }
+    }
+
+    /// Checks whether the capacity is usable
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.capacity == 0 {
+            return Err(ConfigError::InvalidArchive("the capacity must be at least 1".to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// Puts the candidates that are better than the archived agents into the archive (the best one first)
+    pub(crate) fn update<F: Clone + PartialOrd>(&self, archive: &mut Vec<Evaluated<T, F>>, candidates: &[Evaluated<T, F>], objective: Objective)
+        where T: Clone {
+        for candidate in candidates {
+            let full = archive.len() >= self.capacity;
+            if full && !objective.is_better(&candidate.fitness, &archive[archive.len() - 1].fitness) {
+                continue;
+            }
+            if archive.iter().any(|x| (self.f_same)(&x.agent, &candidate.agent)) {
+                continue;
+            }
+            let position = archive.iter().position(|x| objective.is_better(&candidate.fitness, &x.fitness)).unwrap_or(archive.len());
+            archive.insert(position, candidate.clone());
+            archive.truncate(self.capacity);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn update() {
+        let evaluated = |xs: &[i32]| xs.iter().map(|x| Evaluated::new(*x, -(x * x))).collect::<Vec<_>>();
+        let archive = Archive::new(3);
+        let mut kept = Vec::new();
+
+        archive.update(&mut kept, &evaluated(&[5, 4, 4, 7]), Objective::Maximize);
+        assert_eq!(kept, evaluated(&[4, 5, 7]));
+        archive.update(&mut kept, &evaluated(&[9, 1, 4, 6, 0]), Objective::Maximize);
+        assert_eq!(kept, evaluated(&[0, 1, 4]));
+
+        let mut kept = Vec::new();
+        archive.update(&mut kept, &evaluated(&[5, 4, 1]), Objective::Minimize);
+        assert_eq!(kept, evaluated(&[5, 4, 1]));
+        assert!(Archive::<i32>::new(0).validate().is_err());
+    }
+}
@@ -0,0 +1,209 @@
+use futures::stream::{self, StreamExt};
+use rand::prelude::*;
+use std::time::Instant;
+
+use crate::{AsyncFitnessFn, Evaluated, Fitness, FitnessError, Genetic, GeneticState, MaybeSync, Offspring, RunError, RunResult, Termination};
+
+impl<'a, T, F> Genetic<'a, T, F>
+    where T: Clone + MaybeSync, F: Fitness + MaybeSync {
+    /// Same as try_run(), but the new agents are evaluated by awaiting f_async_fitness, at most concurrency
+    /// of them at once (f_fitness is used if there is no f_async_fitness)
+    ///
+    /// The run doesn't depend on the order the evaluations finish in, so seeded runs give the same result
+    /// as run_with_seed().
+    ///
+    /// # Examples:
+    /// ```
+    /// use rand::prelude::*;
+    /// use bbte_optim_tzim1773_genetic::Genetic;
+    ///
+    /// async fn remote_fitness(a: f64) -> f64 {
+    ///     // e.g. a request to the service that measures the agent
+    ///     5.0 - a * a
+    /// }
+    ///
+    /// fn main() {
+    ///     let test = Genetic::builder()
+    ///         .max_generation(20)
+    ///         .random_agent(|rng| rng.gen_range(-5.0, 5.0))
+    ///         .async_fitness(|a: &f64| remote_fitness(*a))
+    ///         .concurrency(8)
+    ///         .mutate(|a: &f64, rng| *a + rng.gen_range(-0.01, 0.01))
+    ///         .offspring(|a: &f64, b: &f64, _rng| (*a + *b) / 2.0)
+    ///         .build()
+    ///         .unwrap();
+    ///
+    ///     // or .await it in your async runtime
+    ///     let result = futures::executor::block_on(test.run_async()).unwrap();
+    ///
+    ///     println!("{}", result.best); // should be a number close to 0
+    /// }
+    /// ```
+    pub async fn run_async(&self) -> Result<RunResult<T, F>, RunError> {
+        self.run_async_with_rng(StdRng::from_entropy()).await
+    }
+
+    /// Same as run_async(), but the run uses the given random number generator
+    ///
+    /// # Arguments:
+    ///
+    /// * `rng` the random number generator used throughout the run
+    pub async fn run_async_with_rng<R: RngCore>(&self, rng: R) -> Result<RunResult<T, F>, RunError> {
+        let mut state = self.start_async_with_rng(rng).await?;
+        while self.step_async(&mut state).await?.is_none() {}
+
+        Ok(self.finish(state))
+    }
+
+    /// Same as start_with_rng(), but the first generation is evaluated by awaiting f_async_fitness
+    ///
+    /// # Arguments:
+    ///
+    /// * `rng` the random number generator used throughout the run (it's owned by the state)
+    pub async fn start_async_with_rng<R: RngCore>(&self, mut rng: R) -> Result<GeneticState<T, F, R>, RunError> {
+        let start = Instant::now();
+        let xg = self.with_opposites(self.get_population(&mut rng));
+        let mut evaluations = 0;
+        let (population, exhausted) = self.evaluate_all_async(xg.into_iter().map(|x| (x, None)).collect(), &mut evaluations).await?;
+        let mut state = self.initial_state(self.fitter_of_pairs(population), evaluations, start, rng);
+        // the first generation was cut short by max_evaluations, the run is over
+        if exhausted {
+            state.termination = Some(Termination::EvaluationLimit);
+        }
+
+        Ok(state)
+    }
+
+    /// Same as step(), but the new agents are evaluated by awaiting f_async_fitness
+    ///
+    /// # Arguments:
+    ///
+    /// * `state` the state of the run, created by start_async_with_rng() (or start()) of this Genetic
+    pub async fn step_async<R: RngCore>(&self, state: &mut GeneticState<T, F, R>) -> Result<Option<Termination>, RunError> {
+        if let Some(termination) = self.check_bound(state) {
+            return Ok(Some(termination));
+        }
+        let candidates = match self.offspring(state) {
+            Ok(offspring) => {
+                let evaluation = self.evaluate_all_async(offspring, &mut state.evaluations);
+                // an entered span can't be held across an await, the future is instrumented instead
+                #[cfg(feature = "tracing")]
+                let evaluation = tracing::Instrument::instrument(evaluation, tracing::debug_span!("evaluation"));
+                evaluation.await
+            }
+            Err(error) => Err(error),
+        };
+        let termination = self.advance(state, candidates)?;
+        #[cfg(feature = "serde")]
+        self.checkpoint(state)?;
+
+        Ok(termination)
+    }
+
+    /// Evaluates the agents whose fitness isn't known yet with f_async_fitness (with f_fitness if it's None), returns
+    /// the ones that fit into max_evaluations and whether it was used up before every agent was evaluated
+    async fn evaluate_all_async(&self, offspring: Vec<Offspring<T, F>>, evaluations: &mut usize) -> Result<(Vec<Evaluated<T, F>>, bool), FitnessError> {
+        match &self.f_async_fitness {
+            Some(f_async_fitness) => self.await_fitness(f_async_fitness, offspring, evaluations).await,
+            None => self.evaluate_in_budget(offspring, evaluations),
+        }
+    }
+
+    /// Awaits the fitness of the agents whose fitness isn't known yet (and isn't cached), at most concurrency
+    /// of them at once; only the ones fitting into max_evaluations are evaluated, returns them with whether
+    /// the others had to be left out
+    async fn await_fitness(&self, f_async_fitness: &AsyncFitnessFn<'a, T, F>, offspring: Vec<Offspring<T, F>>, evaluations: &mut usize) -> Result<(Vec<Evaluated<T, F>>, bool), FitnessError> {
+        let offspring = offspring.into_iter()
+            .map(|(x, f_x)| {
+                let f_x = f_x.or_else(|| self.fitness_cache.as_ref().and_then(|cache| cache.get(&x)));
+                (x, f_x)
+            })
+            .collect::<Vec<Offspring<T, F>>>();
+        let unknown = offspring.iter()
+            .enumerate()
+            .filter(|(_, (_, f_x))| f_x.is_none())
+            .map(|(i, _)| i)
+            .collect::<Vec<usize>>();
+        let budget = match self.max_evaluations {
+            Some(max_evaluations) => max_evaluations.saturating_sub(*evaluations).min(unknown.len()),
+            None => unknown.len(),
+        };
+
+        let fitness = stream::iter(&unknown[..budget])
+            .map(|&i| f_async_fitness(&offspring[i].0))
+            .buffered(self.concurrency)
+            .collect::<Vec<F>>()
+            .await;
+        *evaluations += budget;
+
+        let mut offspring = offspring;
+        for (&i, f_x) in unknown.iter().zip(fitness) {
+            if let Some(cache) = &self.fitness_cache {
+                cache.insert(&offspring[i].0, f_x.clone());
+            }
+            offspring[i].1 = Some(f_x);
+        }
+
+        // the agents left out by max_evaluations are still without a fitness
+        let evaluated = offspring.into_iter()
+            .filter_map(|(agent, fitness)| fitness.map(|fitness| Evaluated { agent, fitness }))
+            .collect();
+        Ok((evaluated, budget < unknown.len()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ConfigError;
+    use futures::executor::block_on;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn run_async() {
+        let calls = AtomicUsize::new(0);
+        let test = Genetic::builder()
+            .population(20)
+            .max_generation(30)
+            .random_agent(|rng| rng.gen_range(-5.0, 5.0))
+            .async_fitness(|a: &f64| {
+                calls.fetch_add(1, Ordering::SeqCst);
+                let a = *a;
+                async move { 5.0 - a * a }
+            })
+            .concurrency(4)
+            .mutate(|a: &f64, rng| *a + rng.gen_range(-0.1, 0.1))
+            .offspring(|a: &f64, b: &f64, _rng| (*a + *b) / 2.0)
+            .build()
+            .unwrap();
+
+        let result = block_on(test.run_async_with_rng(StdRng::seed_from_u64(4))).unwrap();
+        assert_eq!(result.evaluations, calls.load(Ordering::SeqCst));
+        assert!(result.best.abs() < 0.1);
+        // the blocking run gives the same result
+        assert_eq!(result, test.run_with_seed(4));
+
+        let limited = Genetic::builder()
+            .population(10)
+            .max_generation(100000)
+            .random_agent(|rng| rng.gen_range(-5.0, 5.0))
+            .async_fitness(|a: &f64| futures::future::ready(5.0 - a * a))
+            .mutate(|a: &f64, rng| *a + rng.gen_range(-0.1, 0.1))
+            .offspring(|a: &f64, b: &f64, _rng| (*a + *b) / 2.0)
+            .max_evaluations(1000)
+            .build()
+            .unwrap();
+        let result = block_on(limited.run_async()).unwrap();
+        assert_eq!(result.termination, Termination::EvaluationLimit);
+        assert_eq!(result.evaluations, 1000);
+
+        let invalid = Genetic::builder()
+            .random_agent(|rng| rng.gen_range(-5.0, 5.0))
+            .async_fitness(|a: &f64| futures::future::ready(5.0 - a * a))
+            .mutate(|a: &f64, rng| *a + rng.gen_range(-0.1, 0.1))
+            .offspring(|a: &f64, b: &f64, _rng| (*a + *b) / 2.0)
+            .concurrency(0)
+            .build();
+        assert!(matches!(invalid, Err(ConfigError::InvalidParallelism(_))));
+    }
+}
@@ -0,0 +1,379 @@
+use rand::prelude::*;
+
+use crate::error::EvaluationBudget;
+use crate::{ConfigError, Fitness, FitnessError, Genetic, MaybeSync, RunError, Termination};
+
+/// How a hill climber chooses the next agent from the neighbours (the mutants) of the current one
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Ascent {
+    /// Moves to the first neighbour that is better than the current agent, at most `tries` neighbours are tried
+    First {
+        /// The number of neighbours tried before the current agent is considered a local optimum (at least 1)
+        tries: usize,
+    },
+    /// Creates `neighbours` neighbours and moves to the best one if it's better than the current agent
+    Steepest {
+        /// The number of neighbours compared in a step (at least 1)
+        neighbours: usize,
+    },
+}
+
+/// The outcome of a baseline run (hill climbing, random search or tabu search)
+#[derive(Debug, Clone, PartialEq)]
+pub struct BaselineResult<T, F = f64> {
+    /// The best agent found
+    pub best: T,
+    /// The fitness of the best agent
+    pub best_fitness: F,
+    /// The number of agents created (the starting agents and the neighbours, or the random samples)
+    pub samples: usize,
+    /// The number of times the fitness function was called
+    pub evaluations: usize,
+    /// The number of random restarts of the hill climber (always 0 for random search and tabu search)
+    pub restarts: usize,
+    /// The best fitness after every sample (for plotting the convergence, using Fitness::to_f64())
+    pub history: Vec<f64>,
+    /// Why the run stopped: MaxGeneration if every agent was created, EvaluationLimit if max_evaluations was reached,
+    /// Stagnation if the hill climber ran out of restarts or every neighbour of tabu search was tabu
+    pub termination: Termination,
+}
+
+/// Hill climbing on the problem of a Genetic: the neighbours of the current agent are its mutants, and the climber
+/// moves to a better neighbour (the first one found or the best one of a step, see Ascent) until it gets stuck in a
+/// local optimum, where it restarts from a new random agent (at most `max_restarts` times, then the run stops)
+///
+/// The starting agents, the fitness and the neighbours come from the get_random_agent, f_fitness and f_mutate (or the
+/// registered mutation operators) of the Genetic, so it's a baseline for the genetic algorithm on the same problem.
+/// The run creates as many agents as the Genetic evaluates (max_generation * population) and it stops early if
+/// max_evaluations is reached; the fitness cache, the fitness_error_policy and repair() are used, the other settings
+/// are not.
+///
+/// # Examples:
+/// ```
+/// use rand::prelude::*;
+/// use bbte_optim_tzim1773_genetic::{Ascent, Genetic, HillClimber, RandomSearch};
+///
+/// fn main() {
+///     let genetic = || Genetic::builder()
+///         .max_generation(50)
+///         .random_agent(|rng| rng.gen_range(-5.0, 5.0))
+///         .fitness(|a: &f64| 5.0 - a * a)
+///         .mutate(|a: &f64, rng| *a + rng.gen_range(-0.1, 0.1))
+///         .offspring(|a: &f64, b: &f64, _rng| (*a + *b) / 2.0)
+///         .build()
+///         .unwrap();
+///
+///     let climber = HillClimber::new(genetic(), Ascent::First { tries: 50 }, 10).unwrap();
+///     println!("{}", climber.run().best); // should be a number close to 0
+///     println!("{}", RandomSearch::new(genetic()).run().best); // a worse one
+/// }
+/// ```
+pub struct HillClimber<'a, T, F = f64> {
+    /// The Genetic giving the agents, the fitness, the neighbours and the number of agents created
+    pub genetic: Genetic<'a, T, F>,
+    /// How the next agent is chosen
+    /// Suggested value: Ascent::First { tries: 50 }
+    pub ascent: Ascent,
+    /// The most random restarts in a run (usize::MAX restarts until the run ends)
+    /// Suggested value: usize::MAX
+    pub max_restarts: usize,
+}
+
+impl<'a, T, F> HillClimber<'a, T, F>
+    where T: Clone + MaybeSync, F: Fitness + MaybeSync {
+    /// Creates a hill climber on the problem of the Genetic, checking the ascent
+    ///
+    /// # Arguments:
+    ///
+    /// * `genetic` the agents, the fitness, the neighbours and the number of agents created
+    /// * `ascent` first or steepest ascent
+    /// * `max_restarts` the most random restarts from a local optimum (0 stops in the first local optimum)
+    pub fn new(genetic: Genetic<'a, T, F>, ascent: Ascent, max_restarts: usize) -> Result<Self, ConfigError> {
+        if let Ascent::First { tries: 0 } | Ascent::Steepest { neighbours: 0 } = ascent {
+            return Err(ConfigError::InvalidHillClimbing("at least 1 neighbour has to be tried in a step".to_string()));
+        }
+
+        Ok(HillClimber { genetic, ascent, max_restarts })
+    }
+
+    /// Climbs until the agents (or the evaluations, or the restarts) run out
+    ///
+    /// # Panics:
+    ///  If the fitness function fails and the fitness_error_policy gives up on the agent
+    ///  (use try_run() to get the error instead)
+    pub fn run(&self) -> BaselineResult<T, F> {
+        self.run_with_rng(&mut thread_rng())
+    }
+
+    /// Same as run(), but returns the error of the fitness function instead of panicking
+    pub fn try_run(&self) -> Result<BaselineResult<T, F>, RunError> {
+        self.try_run_with_rng(&mut thread_rng())
+    }
+
+    /// Same as run(), but uses the given random number generator
+    pub fn run_with_rng(&self, rng: &mut dyn RngCore) -> BaselineResult<T, F> {
+        match self.try_run_with_rng(rng) {
+            Ok(result) => result,
+            Err(error) => panic!("{}", error),
+        }
+    }
+
+    /// Same as run(), but uses a random number generator seeded with the given seed, so the runs can be reproduced
+    pub fn run_with_seed(&self, seed: u64) -> BaselineResult<T, F> {
+        self.run_with_rng(&mut StdRng::seed_from_u64(seed))
+    }
+
+    /// Same as run_with_rng(), but returns the error of the fitness function instead of panicking
+    pub fn try_run_with_rng(&self, rng: &mut dyn RngCore) -> Result<BaselineResult<T, F>, RunError> {
+        let genetic = &self.genetic;
+        let mut tracker = Tracker::new(genetic);
+
+        let mut current = genetic.repair((genetic.get_random_agent)(rng));
+        let mut f_current = genetic.evaluate_unlimited(&current, &mut tracker.evaluations)?;
+        tracker.record(&f_current);
+        let mut best = (current.clone(), f_current.clone());
+
+        while !tracker.is_done() {
+            let (tries, first) = match self.ascent {
+                Ascent::First { tries } => (tries, true),
+                Ascent::Steepest { neighbours } => (neighbours, false),
+            };
+            let mut next: Option<(T, F)> = None;
+            for _ in 0..tries {
+                if tracker.is_done() {
+                    break;
+                }
+                let operator = rng.gen_range(0, genetic.mutation_operators.len().max(1));
+                let neighbour = genetic.apply_mutation(operator, &current, rng);
+                let f_neighbour = match tracker.evaluate(&neighbour)? {
+                    Some(f_neighbour) => f_neighbour,
+                    None => break,
+                };
+                let f_next = next.as_ref().map_or(&f_current, |(_, f_next)| f_next);
+                if genetic.objective.is_better(&f_neighbour, f_next) {
+                    next = Some((neighbour, f_neighbour));
+                    if first {
+                        break;
+                    }
+                }
+            }
+
+            match next {
+                Some((x, f_x)) => {
+                    current = x;
+                    f_current = f_x;
+                    if genetic.objective.is_better(&f_current, &best.1) {
+                        best = (current.clone(), f_current.clone());
+                    }
+                }
+                // stuck in a local optimum
+                None if !tracker.is_done() && tracker.restarts < self.max_restarts => {
+                    current = genetic.repair((genetic.get_random_agent)(rng));
+                    f_current = match tracker.evaluate(&current)? {
+                        Some(f_current) => f_current,
+                        None => break,
+                    };
+                    tracker.restarts += 1;
+                    if genetic.objective.is_better(&f_current, &best.1) {
+                        best = (current.clone(), f_current.clone());
+                    }
+                }
+                None => break,
+            }
+        }
+
+        Ok(tracker.finish(best))
+    }
+}
+
+/// Random search on the problem of a Genetic: max_generation * population random agents are created with its
+/// get_random_agent (and repaired), and the best one is kept
+///
+/// It's the simplest baseline: an algorithm that doesn't beat it doesn't learn anything about the problem. The run
+/// stops early if max_evaluations is reached, the fitness cache and the fitness_error_policy are used.
+///
+/// # Examples:
+///  See at HillClimber
+pub struct RandomSearch<'a, T, F = f64> {
+    /// The Genetic giving the agents, the fitness and the number of agents created
+    pub genetic: Genetic<'a, T, F>,
+}
+
+impl<'a, T, F> RandomSearch<'a, T, F>
+    where T: Clone + MaybeSync, F: Fitness + MaybeSync {
+    /// Creates a random search on the problem of the Genetic
+    pub fn new(genetic: Genetic<'a, T, F>) -> Self {
+        RandomSearch { genetic }
+    }
+
+    /// Samples until the agents (or the evaluations) run out
+    ///
+    /// # Panics:
+    ///  If the fitness function fails and the fitness_error_policy gives up on the agent
+    ///  (use try_run() to get the error instead)
+    pub fn run(&self) -> BaselineResult<T, F> {
+        self.run_with_rng(&mut thread_rng())
+    }
+
+    /// Same as run(), but returns the error of the fitness function instead of panicking
+    pub fn try_run(&self) -> Result<BaselineResult<T, F>, RunError> {
+        self.try_run_with_rng(&mut thread_rng())
+    }
+
+    /// Same as run(), but uses the given random number generator
+    pub fn run_with_rng(&self, rng: &mut dyn RngCore) -> BaselineResult<T, F> {
+        match self.try_run_with_rng(rng) {
+            Ok(result) => result,
+            Err(error) => panic!("{}", error),
+        }
+    }
+
+    /// Same as run(), but uses a random number generator seeded with the given seed, so the runs can be reproduced
+    pub fn run_with_seed(&self, seed: u64) -> BaselineResult<T, F> {
+        self.run_with_rng(&mut StdRng::seed_from_u64(seed))
+    }
+
+    /// Same as run_with_rng(), but returns the error of the fitness function instead of panicking
+    pub fn try_run_with_rng(&self, rng: &mut dyn RngCore) -> Result<BaselineResult<T, F>, RunError> {
+        let genetic = &self.genetic;
+        let mut tracker = Tracker::new(genetic);
+
+        let first = genetic.repair((genetic.get_random_agent)(rng));
+        let f_first = genetic.evaluate_unlimited(&first, &mut tracker.evaluations)?;
+        tracker.record(&f_first);
+        let mut best = (first, f_first);
+
+        while !tracker.is_done() {
+            let x = genetic.repair((genetic.get_random_agent)(rng));
+            let f_x = match tracker.evaluate(&x)? {
+                Some(f_x) => f_x,
+                None => break,
+            };
+            if genetic.objective.is_better(&f_x, &best.1) {
+                best = (x, f_x);
+            }
+        }
+
+        Ok(tracker.finish(best))
+    }
+}
+
+/// Counts the agents and the evaluations of a baseline run and records its history
+pub(crate) struct Tracker<'g, 'a, T, F> {
+    genetic: &'g Genetic<'a, T, F>,
+    samples: usize,
+    pub evaluations: usize,
+    pub restarts: usize,
+    best: Option<F>,
+    history: Vec<f64>,
+    exhausted: bool,
+}
+
+impl<'g, 'a, T, F> Tracker<'g, 'a, T, F>
+    where T: Clone + MaybeSync, F: Fitness + MaybeSync {
+    pub fn new(genetic: &'g Genetic<'a, T, F>) -> Self {
+        let samples = genetic.max_generation * genetic.population;
+        Tracker { genetic, samples: 0, evaluations: 0, restarts: 0, best: None, history: Vec::with_capacity(samples), exhausted: false }
+    }
+
+    /// Returns true if the run created all of its agents
+    pub fn is_done(&self) -> bool {
+        self.samples >= self.genetic.max_generation * self.genetic.population
+    }
+
+    /// Evaluates a new agent, returns None if max_evaluations is reached
+    pub fn evaluate(&mut self, x: &T) -> Result<Option<F>, FitnessError> {
+        match self.genetic.evaluate(x, &mut self.evaluations) {
+            Ok(f_x) => {
+                self.record(&f_x);
+                Ok(Some(f_x))
+            }
+            Err(error) if error.is::<EvaluationBudget>() => {
+                self.exhausted = true;
+                Ok(None)
+            }
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Counts an evaluated agent and updates the history
+    pub fn record(&mut self, f_x: &F) {
+        if self.best.as_ref().is_none_or(|f_best| self.genetic.objective.is_better(f_x, f_best)) {
+            self.best = Some(f_x.clone());
+        }
+        self.samples += 1;
+        self.history.push(self.best.as_ref().map_or(f64::NAN, Fitness::to_f64));
+    }
+
+    /// Creates the result, the run stopped early (Stagnation) if it didn't create every agent or use up the budget
+    pub fn finish(self, (best, best_fitness): (T, F)) -> BaselineResult<T, F> {
+        let termination = if self.exhausted {
+            Termination::EvaluationLimit
+        } else if self.is_done() {
+            Termination::MaxGeneration
+        } else {
+            Termination::Stagnation
+        };
+        BaselineResult { best, best_fitness, samples: self.samples, evaluations: self.evaluations, restarts: self.restarts, history: self.history, termination }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn genetic<'a>() -> Genetic<'a, i32> {
+        // a local optimum at -50 (fitness 0) and the global one at 50 (fitness 10), with steps of 1
+        Genetic::builder()
+            .population(10)
+            .max_generation(100)
+            .random_agent(|rng| rng.gen_range(-100, 100))
+            .fitness(|a: &i32| (10 - (a - 50).abs()).max(-(a + 50).abs()) as f64)
+            .mutate(|a: &i32, rng| *a + if rng.gen::<bool>() { 1 } else { -1 })
+            .offspring(|a: &i32, b: &i32, _rng| (*a + *b) / 2)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn hill_climbing() {
+        assert!(HillClimber::new(genetic(), Ascent::Steepest { neighbours: 0 }, 0).is_err());
+
+        // the climbers without restarts stop in one of the optima
+        for ascent in &[Ascent::First { tries: 20 }, Ascent::Steepest { neighbours: 4 }] {
+            let result = HillClimber::new(genetic(), *ascent, 0).unwrap().run_with_seed(1);
+            assert!(result.best == 50 || result.best == -50);
+            assert_eq!(result.restarts, 0);
+            assert!(result.samples < 1000);
+            assert_eq!(result.termination, Termination::Stagnation);
+        }
+
+        // with restarts the whole budget is used and the global optimum is found
+        let result = HillClimber::new(genetic(), Ascent::First { tries: 20 }, usize::MAX).unwrap().run_with_seed(1);
+        assert_eq!((result.best, result.samples, result.history.len()), (50, 1000, 1000));
+        assert!(result.restarts > 0);
+        assert_eq!(result.termination, Termination::MaxGeneration);
+        assert!(result.history.windows(2).all(|w| w[1] >= w[0]));
+    }
+
+    #[test]
+    fn random_search() {
+        let result = RandomSearch::new(genetic()).run_with_seed(1);
+        assert_eq!((result.samples, result.history.len()), (1000, 1000));
+        assert!(result.best_fitness > 5.0);
+        assert_eq!(result.history.last(), Some(&result.best_fitness));
+
+        let limited = Genetic::builder()
+            .max_generation(100)
+            .max_evaluations(10)
+            .random_agent(|rng| rng.gen_range(-100, 100))
+            .fitness(|a: &i32| *a as f64)
+            .mutate(|a: &i32, _rng| *a)
+            .offspring(|a: &i32, _b: &i32, _rng| *a)
+            .build()
+            .unwrap();
+        let result = RandomSearch::new(limited).run_with_seed(1);
+        assert_eq!((result.evaluations, result.termination), (10, Termination::EvaluationLimit));
+    }
+}
@@ -0,0 +1,262 @@
+use rand::prelude::*;
+use std::time::Instant;
+
+use crate::engine::Engine;
+use crate::{ConfigError, Evaluated, Fitness, Genetic, MaybeSync, RunError, RunResult, Termination};
+
+/// Artificial bee colony on the problem of a Genetic (usually created from a Problem with Genetic::from_problem()):
+/// the population is the set of food sources, and in every generation
+///  - every employed bee tries a neighbour of its source,
+///  - as many onlooker bees try the neighbours of the sources chosen by their rank (the better sources get more bees),
+///  - the source that wasn't improved for more than `limit` tries is abandoned, and a scout bee replaces it with a
+///    random agent.
+///
+/// A neighbour of a source is the mutant of its crossover with another random source (using the registered
+/// operators, or the crossover and the mutation of the Problem), and it replaces the source only if it's fitter. The
+/// stopping criteria, the statistics and the callbacks come from the Genetic like in a run of the genetic algorithm,
+/// and the result is a RunResult: the best is the best agent found during the run, the population is the last set of
+/// food sources.
+///
+/// # Examples:
+/// ```
+/// use rand::prelude::*;
+/// use bbte_optim_tzim1773_genetic::{BeeColony, Genetic, Objective, Problem};
+///
+/// struct Sphere;
+///
+/// impl Problem<Vec<f64>> for Sphere {
+///     fn random_agent(&self, rng: &mut dyn RngCore) -> Vec<f64> {
+///         (0..5).map(|_| rng.gen_range(-5.0, 5.0)).collect()
+///     }
+///     fn fitness(&self, agent: &Vec<f64>) -> f64 {
+///         agent.iter().map(|x| x * x).sum()
+///     }
+///     fn mutate(&self, agent: &Vec<f64>, rng: &mut dyn RngCore) -> Vec<f64> {
+///         agent.iter().map(|x| x + rng.gen_range(-0.1, 0.1)).collect()
+///     }
+///     fn crossover(&self, a: &Vec<f64>, b: &Vec<f64>, rng: &mut dyn RngCore) -> Vec<f64> {
+///         let phi = rng.gen_range(-1.0, 1.0);
+///         a.iter().zip(b).map(|(x, y)| x + phi * (x - y)).collect()
+///     }
+///     fn objective(&self) -> Objective {
+///         Objective::Minimize
+///     }
+/// }
+///
+/// fn main() {
+///     let colony = BeeColony::new(Genetic::from_problem(20, 100, 0.5, 0.5, Sphere), 50).unwrap();
+///     println!("{:?}", colony.run().best); // should be close to 0
+/// }
+/// ```
+pub struct BeeColony<'a, T, F = f64> {
+    /// The Genetic giving the food sources, the neighbours and the stopping criteria
+    pub genetic: Genetic<'a, T, F>,
+    /// The number of failed improvements after which a source is abandoned
+    /// Suggested value: population * the number of dimensions (or more for bigger search spaces)
+    pub limit: usize,
+}
+
+impl<'a, T, F> BeeColony<'a, T, F>
+    where T: Clone + MaybeSync, F: Fitness + MaybeSync {
+    /// Creates a bee colony on the problem of the Genetic, checking the parameters
+    ///
+    /// # Arguments:
+    ///
+    /// * `genetic` the food sources (at least 2), the neighbours and the stopping criteria
+    /// * `limit` the number of failed improvements after which a source is abandoned (at least 1)
+    pub fn new(genetic: Genetic<'a, T, F>, limit: usize) -> Result<Self, ConfigError> {
+        if genetic.population < 2 {
+            return Err(ConfigError::InvalidBeeColony(format!("the colony needs at least 2 food sources, got {}", genetic.population)));
+        }
+        if limit == 0 {
+            return Err(ConfigError::InvalidBeeColony("the limit must be at least 1".to_string()));
+        }
+
+        Ok(BeeColony { genetic, limit })
+    }
+
+    /// Runs the optimisation until one of the stopping criteria of the Genetic is met
+    ///
+    /// # Panics:
+    ///  If the fitness function fails and the fitness_error_policy gives up on the agent
+    ///  (use try_run() to get the error instead)
+    pub fn run(&self) -> RunResult<T, F> {
+        self.run_with_rng(&mut thread_rng())
+    }
+
+    /// Same as run(), but returns the error instead of panicking
+    pub fn try_run(&self) -> Result<RunResult<T, F>, RunError> {
+        self.try_run_with_rng(&mut thread_rng())
+    }
+
+    /// Same as run(), but uses the given random number generator
+    pub fn run_with_rng(&self, rng: &mut dyn RngCore) -> RunResult<T, F> {
+        match self.try_run_with_rng(rng) {
+            Ok(result) => result,
+            Err(error) => panic!("{}", error),
+        }
+    }
+
+    /// Same as run(), but uses a random number generator seeded with the given seed, so the runs can be reproduced
+    pub fn run_with_seed(&self, seed: u64) -> RunResult<T, F> {
+        self.run_with_rng(&mut StdRng::seed_from_u64(seed))
+    }
+
+    /// Same as run_with_rng(), but returns the error instead of panicking
+    pub fn try_run_with_rng(&self, rng: &mut dyn RngCore) -> Result<RunResult<T, F>, RunError> {
+        let start = Instant::now();
+        let genetic = &self.genetic;
+        let initial = genetic.get_population(rng).into_iter().map(|x| (genetic.repair(x), None)).collect();
+        let (mut engine, mut sources) = Engine::start(genetic, start, initial)?;
+        let mut trials = vec![0; sources.len()];
+        let mut best = sources[genetic.best_index(&sources)].clone();
+
+        let termination = loop {
+            if let Some(termination) = engine.check_bound() {
+                break termination;
+            }
+
+            // employed bees
+            let employed = (0..sources.len()).collect::<Vec<usize>>();
+            if !self.improve(&mut engine, &mut sources, &mut trials, &employed, rng)? {
+                break Termination::EvaluationLimit;
+            }
+
+            // onlooker bees
+            let order = genetic.best_first(&sources);
+            let n = sources.len();
+            let total = n * (n + 1) / 2;
+            let onlookers = (0..n)
+                .map(|_| {
+                    let mut pick = rng.gen_range(0, total);
+                    let mut rank = 0;
+                    while pick >= n - rank {
+                        pick -= n - rank;
+                        rank += 1;
+                    }
+                    order[rank]
+                })
+                .collect::<Vec<usize>>();
+            if !self.improve(&mut engine, &mut sources, &mut trials, &onlookers, rng)? {
+                break Termination::EvaluationLimit;
+            }
+
+            let generation_best = &sources[genetic.best_index(&sources)];
+            if genetic.is_fitter(generation_best, &best) {
+                best = generation_best.clone();
+            }
+
+            // scout bee
+            let abandoned = (0..n).filter(|i| trials[*i] > self.limit).max_by_key(|i| trials[*i]);
+            if let Some(i) = abandoned {
+                let scout = vec![(genetic.repair((genetic.get_random_agent)(rng)), None)];
+                match engine.evaluate(scout)? {
+                    Some(mut scout) => sources[i] = scout.remove(0),
+                    None => break Termination::EvaluationLimit,
+                }
+                trials[i] = 0;
+                if genetic.is_fitter(&sources[i], &best) {
+                    best = sources[i].clone();
+                }
+            }
+
+            if let Some(termination) = engine.finish_generation(&sources)? {
+                break termination;
+            }
+        };
+
+        Ok(engine.result(best, sources.into_iter().map(|x| x.agent).collect(), termination))
+    }
+
+    /// Tries a neighbour of every chosen source and keeps the fitter ones, returns false if the neighbours don't fit
+    /// into max_evaluations
+    fn improve(&self, engine: &mut Engine<T, F>, sources: &mut [Evaluated<T, F>], trials: &mut [usize], chosen: &[usize], rng: &mut dyn RngCore) -> Result<bool, RunError> {
+        let neighbours = chosen.iter().map(|i| (self.neighbour(sources, *i, rng), None)).collect();
+        let neighbours = match engine.evaluate(neighbours)? {
+            Some(neighbours) => neighbours,
+            None => return Ok(false),
+        };
+        for (i, neighbour) in chosen.iter().zip(neighbours) {
+            if self.genetic.is_fitter(&neighbour, &sources[*i]) {
+                sources[*i] = neighbour;
+                trials[*i] = 0;
+            } else {
+                trials[*i] += 1;
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Returns the mutant of the crossover of the i-th source with another random source
+    fn neighbour(&self, sources: &[Evaluated<T, F>], i: usize, rng: &mut dyn RngCore) -> T {
+        let genetic = &self.genetic;
+        let k = (i + rng.gen_range(1, sources.len())) % sources.len();
+        let crossover = rng.gen_range(0, genetic.crossover_operators.len().max(1));
+        let child = genetic.apply_crossover(crossover, &sources[i].agent, &sources[k].agent, rng)
+            .into_iter()
+            .next()
+            .unwrap_or_else(|| sources[i].agent.clone());
+        let mutation = rng.gen_range(0, genetic.mutation_operators.len().max(1));
+        genetic.apply_mutation(mutation, &child, rng)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Objective, Problem};
+
+    struct Sphere;
+
+    impl Problem<Vec<f64>> for Sphere {
+        fn random_agent(&self, rng: &mut dyn RngCore) -> Vec<f64> {
+            (0..3).map(|_| rng.gen_range(-5.0, 5.0)).collect()
+        }
+        fn fitness(&self, agent: &Vec<f64>) -> f64 {
+            agent.iter().map(|x| x * x).sum()
+        }
+        fn mutate(&self, agent: &Vec<f64>, rng: &mut dyn RngCore) -> Vec<f64> {
+            let mut agent = agent.clone();
+            agent[rng.gen_range(0, 3)] += rng.gen_range(-0.01, 0.01);
+            agent
+        }
+        fn crossover(&self, a: &Vec<f64>, b: &Vec<f64>, rng: &mut dyn RngCore) -> Vec<f64> {
+            let (j, phi) = (rng.gen_range(0, 3), rng.gen_range(-1.0, 1.0));
+            let mut child = a.clone();
+            child[j] += phi * (a[j] - b[j]);
+            child
+        }
+        fn objective(&self) -> Objective {
+            Objective::Minimize
+        }
+    }
+
+    #[test]
+    fn bee_colony() {
+        let mut one_source = Genetic::from_problem(10, 10, 0.5, 0.5, Sphere);
+        one_source.population = 1;
+        assert!(BeeColony::new(one_source, 10).is_err());
+        assert!(BeeColony::new(Genetic::from_problem(10, 10, 0.5, 0.5, Sphere), 0).is_err());
+
+        let colony = BeeColony::new(Genetic::from_problem(10, 200, 0.5, 0.5, Sphere), 30).unwrap();
+        let result = colony.run_with_seed(1);
+        assert!(result.best_fitness < 1e-3, "{}", result.best_fitness);
+        assert_eq!((result.generations, result.population.len(), result.history.len()), (200, 10, 200));
+        // the first sources, the employed and the onlooker bees, and at most one scout in every generation
+        assert!(result.evaluations >= 10 + 200 * 20 && result.evaluations <= 10 + 200 * 21);
+
+        // the scouts abandon the sources that can't be improved
+        let stuck = Genetic::builder()
+            .population(4)
+            .max_generation(10)
+            .random_agent(|rng| rng.gen_range(0, 100))
+            .fitness(|a: &i32| *a as f64)
+            .mutate(|a: &i32, _rng| *a)
+            .offspring(|a: &i32, _b: &i32, _rng| *a)
+            .build()
+            .unwrap();
+        let result = BeeColony::new(stuck, 1).unwrap().run_with_seed(1);
+        assert_eq!(result.evaluations, 4 + 10 * 9);
+    }
+}
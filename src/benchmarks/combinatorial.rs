@@ -0,0 +1,495 @@
+use rand::prelude::*;
+use std::f64::consts::PI;
+use std::fs;
+use std::path::Path;
+
+use crate::genome::{BitString, InversionMutation, OrderCrossover, Permutation};
+use crate::operators::{BitFlip, Crossover, Mutation, Uniform};
+use crate::{ConfigError, Objective, Problem, TsplibError};
+
+/// A travelling salesman problem: the agents are the orders the cities are visited in, and the fitness is the length
+/// of the closed tour (minimised)
+///
+/// The mutation reverses a random segment of the tour (2-opt move), the crossover is the order crossover.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tsp {
+    /// The name of the instance (the NAME of the TSPLIB file, empty if it's not given)
+    pub name: String,
+    distances: Vec<Vec<f64>>,
+}
+
+/// The part of a TSPLIB file a data line belongs to
+enum Section {
+    None,
+    Coordinates,
+    Weights,
+}
+
+impl Tsp {
+    /// Creates the problem of cities in the plane, with their Euclidean distances (not rounded)
+    ///
+    /// Returns an error if there are less than 2 cities or a coordinate is not finite.
+    pub fn from_coordinates(cities: &[(f64, f64)]) -> Result<Self, ConfigError> {
+        let distances = cities.iter()
+            .map(|(x1, y1)| cities.iter().map(|(x2, y2)| ((x1 - x2).powi(2) + (y1 - y2).powi(2)).sqrt()).collect())
+            .collect();
+        Tsp::from_distances(distances)
+    }
+
+    /// Creates the problem from the distance matrix (the distance from i to j is distances[i][j], so it can be
+    /// asymmetric)
+    ///
+    /// Returns an error if there are less than 2 cities, the matrix is not square or a distance is not finite.
+    pub fn from_distances(distances: Vec<Vec<f64>>) -> Result<Self, ConfigError> {
+        if distances.len() < 2 {
+            return Err(ConfigError::InvalidBenchmark("the travelling salesman problem needs at least 2 cities".to_string()));
+        }
+        if distances.iter().any(|row| row.len() != distances.len()) {
+            return Err(ConfigError::InvalidBenchmark("the distance matrix must be square".to_string()));
+        }
+        if let Some(distance) = distances.iter().flatten().find(|d| !d.is_finite()) {
+            return Err(ConfigError::InvalidBenchmark(format!("the distances must be finite, got {}", distance)));
+        }
+        Ok(Tsp { name: String::new(), distances })
+    }
+
+    /// Reads a TSPLIB file (see parse())
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, TsplibError> {
+        Tsp::parse(&fs::read_to_string(path)?)
+    }
+
+    /// Reads a TSPLIB instance of the TSP or ATSP type
+    ///
+    /// The supported edge weight types are EUC_2D, CEIL_2D, ATT and GEO (from the NODE_COORD_SECTION, rounded as
+    /// TSPLIB defines them, so the known optimal tour lengths can be reproduced) and EXPLICIT (from the
+    /// EDGE_WEIGHT_SECTION, in the FULL_MATRIX, UPPER_ROW, LOWER_ROW, UPPER_DIAG_ROW or LOWER_DIAG_ROW format).
+    pub fn parse(text: &str) -> Result<Self, TsplibError> {
+        let mut name = String::new();
+        let mut dimension = None;
+        let mut weight_type = None;
+        let mut weight_format = None;
+        let mut coordinates = Vec::new();
+        let mut weights = Vec::new();
+        let mut section = Section::None;
+
+        for line in text.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            // the data lines are numbers, everything else is a keyword (which ends the section)
+            if line.starts_with(|c: char| c.is_ascii_alphabetic()) {
+                let (key, value) = match line.find(':') {
+                    Some(i) => (line[..i].trim(), line[i + 1..].trim()),
+                    None => (line, ""),
+                };
+                section = Section::None;
+                match key {
+                    "EOF" => break,
+                    "NAME" => name = value.to_string(),
+                    "TYPE" if value != "TSP" && value != "ATSP" => return Err(TsplibError::Format(format!("the type {} is not a travelling salesman problem", value))),
+                    "DIMENSION" => dimension = Some(value.parse::<usize>().map_err(|_| TsplibError::Format(format!("invalid DIMENSION: {}", value)))?),
+                    "EDGE_WEIGHT_TYPE" => weight_type = Some(value.to_string()),
+                    "EDGE_WEIGHT_FORMAT" => weight_format = Some(value.to_string()),
+                    "NODE_COORD_SECTION" => section = Section::Coordinates,
+                    "EDGE_WEIGHT_SECTION" => section = Section::Weights,
+                    // comments, display data, fixed edges, ...
+                    _ => {}
+                }
+                continue;
+            }
+
+            let numbers = line.split_whitespace()
+                .map(|token| token.parse::<f64>().map_err(|_| TsplibError::Format(format!("not a number: {}", token))))
+                .collect::<Result<Vec<f64>, TsplibError>>()?;
+            match section {
+                Section::Coordinates if numbers.len() >= 3 => coordinates.push((numbers[0] as usize, numbers[1], numbers[2])),
+                Section::Coordinates => return Err(TsplibError::Format(format!("a coordinate line needs a number and 2 coordinates: {}", line))),
+                Section::Weights => weights.extend(numbers),
+                Section::None => {}
+            }
+        }
+
+        let n = dimension.ok_or_else(|| TsplibError::Format("DIMENSION is missing".to_string()))?;
+        if n < 2 {
+            return Err(TsplibError::Format("the problem needs at least 2 cities".to_string()));
+        }
+        let weight_type = weight_type.ok_or_else(|| TsplibError::Format("EDGE_WEIGHT_TYPE is missing".to_string()))?;
+        let distances = match weight_type.as_str() {
+            "EXPLICIT" => {
+                let format = weight_format.ok_or_else(|| TsplibError::Format("EDGE_WEIGHT_FORMAT is missing".to_string()))?;
+                explicit_distances(n, &format, &weights)?
+            }
+            "EUC_2D" | "CEIL_2D" | "ATT" | "GEO" => {
+                let mut cities = vec![None; n];
+                for (id, x, y) in coordinates {
+                    match cities.get_mut(id.wrapping_sub(1)) {
+                        Some(city @ None) => *city = Some((x, y)),
+                        _ => return Err(TsplibError::Format(format!("invalid or repeated city number: {}", id))),
+                    }
+                }
+                let cities = cities.into_iter()
+                    .collect::<Option<Vec<(f64, f64)>>>()
+                    .ok_or_else(|| TsplibError::Format(format!("the coordinates of {} cities are needed", n)))?;
+                cities.iter()
+                    .map(|a| cities.iter().map(|b| coordinate_distance(&weight_type, *a, *b)).collect())
+                    .collect()
+            }
+            _ => return Err(TsplibError::Format(format!("unsupported EDGE_WEIGHT_TYPE: {}", weight_type))),
+        };
+
+        Ok(Tsp { name, distances })
+    }
+
+    /// Returns the number of cities
+    pub fn cities(&self) -> usize {
+        self.distances.len()
+    }
+
+    /// Returns the distance matrix (e.g. for AntColony)
+    pub fn distances(&self) -> &[Vec<f64>] {
+        &self.distances
+    }
+
+    /// Returns the length of the closed tour visiting the cities in the given order
+    pub fn tour_length(&self, tour: &[usize]) -> f64 {
+        (0..tour.len())
+            .map(|i| self.distances[tour[i]][tour[(i + 1) % tour.len()]])
+            .sum()
+    }
+}
+
+/// Returns the distance of two cities by the TSPLIB definition of the edge weight type
+fn coordinate_distance(weight_type: &str, (x1, y1): (f64, f64), (x2, y2): (f64, f64)) -> f64 {
+    let euclidean = ((x1 - x2).powi(2) + (y1 - y2).powi(2)).sqrt();
+    match weight_type {
+        "EUC_2D" => euclidean.round(),
+        "CEIL_2D" => euclidean.ceil(),
+        "ATT" => {
+            let r = (((x1 - x2).powi(2) + (y1 - y2).powi(2)) / 10.0).sqrt();
+            if r.round() < r { r.round() + 1.0 } else { r.round() }
+        }
+        _ => {
+            // the coordinates are latitudes and longitudes in DDD.MM format
+            let radians = |x: f64| PI * (x.trunc() + 5.0 * (x - x.trunc()) / 3.0) / 180.0;
+            let (latitude1, longitude1, latitude2, longitude2) = (radians(x1), radians(y1), radians(x2), radians(y2));
+            let q1 = (longitude1 - longitude2).cos();
+            let q2 = (latitude1 - latitude2).cos();
+            let q3 = (latitude1 + latitude2).cos();
+            (6378.388 * (0.5 * ((1.0 + q1) * q2 - (1.0 - q1) * q3)).acos() + 1.0).trunc()
+        }
+    }
+}
+
+/// Builds the distance matrix from the numbers of the EDGE_WEIGHT_SECTION
+fn explicit_distances(n: usize, format: &str, weights: &[f64]) -> Result<Vec<Vec<f64>>, TsplibError> {
+    // the cells of the matrix in the order they are listed in
+    let cells = match format {
+        "FULL_MATRIX" => (0..n).flat_map(|i| (0..n).map(move |j| (i, j))).collect::<Vec<(usize, usize)>>(),
+        "UPPER_ROW" => (0..n).flat_map(|i| (i + 1..n).map(move |j| (i, j))).collect(),
+        "UPPER_DIAG_ROW" => (0..n).flat_map(|i| (i..n).map(move |j| (i, j))).collect(),
+        "LOWER_ROW" => (0..n).flat_map(|i| (0..i).map(move |j| (i, j))).collect(),
+        "LOWER_DIAG_ROW" => (0..n).flat_map(|i| (0..=i).map(move |j| (i, j))).collect(),
+        _ => return Err(TsplibError::Format(format!("unsupported EDGE_WEIGHT_FORMAT: {}", format))),
+    };
+    if cells.len() != weights.len() {
+        return Err(TsplibError::Format(format!("{} edge weights are needed, got {}", cells.len(), weights.len())));
+    }
+
+    let mut distances = vec![vec![0.0; n]; n];
+    for ((i, j), weight) in cells.into_iter().zip(weights) {
+        distances[i][j] = *weight;
+        if format != "FULL_MATRIX" {
+            distances[j][i] = *weight;
+        }
+    }
+    Ok(distances)
+}
+
+impl Problem<Permutation> for Tsp {
+    fn random_agent(&self, rng: &mut dyn RngCore) -> Permutation {
+        Permutation::random(self.cities(), rng)
+    }
+
+    fn fitness(&self, agent: &Permutation) -> f64 {
+        self.tour_length(agent)
+    }
+
+    fn mutate(&self, agent: &Permutation, rng: &mut dyn RngCore) -> Permutation {
+        InversionMutation.mutate(agent, rng)
+    }
+
+    fn crossover(&self, a: &Permutation, b: &Permutation, rng: &mut dyn RngCore) -> Permutation {
+        OrderCrossover.cross(a, b, rng).swap_remove(0)
+    }
+
+    fn objective(&self) -> Objective {
+        Objective::Minimize
+    }
+}
+
+/// A 0/1 knapsack problem: the agents are the chosen items (bit i is set if item i is packed), and the fitness is
+/// their total value (maximised)
+///
+/// Every agent made by the problem fits into the knapsack: after the random agents, the mutation (flipping one bit on
+/// average) and the uniform crossover the agents are repaired by repair().
+#[derive(Debug, Clone, PartialEq)]
+pub struct Knapsack {
+    /// The values of the items
+    pub values: Vec<f64>,
+    /// The weights of the items
+    pub weights: Vec<f64>,
+    /// The biggest total weight that fits into the knapsack
+    pub capacity: f64,
+}
+
+impl Knapsack {
+    /// Creates the problem from the values and the weights of the items
+    ///
+    /// Returns an error if there are no items, the number of values and weights differ, a value is not finite, or a
+    /// weight or the capacity is negative or not finite.
+    pub fn new(values: Vec<f64>, weights: Vec<f64>, capacity: f64) -> Result<Self, ConfigError> {
+        if values.is_empty() {
+            return Err(ConfigError::InvalidBenchmark("the knapsack problem needs at least 1 item".to_string()));
+        }
+        if values.len() != weights.len() {
+            return Err(ConfigError::InvalidBenchmark(format!("every item needs a value and a weight, got {} values and {} weights", values.len(), weights.len())));
+        }
+        if let Some(value) = values.iter().find(|v| !v.is_finite()) {
+            return Err(ConfigError::InvalidBenchmark(format!("the values must be finite, got {}", value)));
+        }
+        if let Some(weight) = weights.iter().find(|w| !w.is_finite() || **w < 0.0) {
+            return Err(ConfigError::InvalidBenchmark(format!("the weights must be finite and not negative, got {}", weight)));
+        }
+        if !capacity.is_finite() || capacity < 0.0 {
+            return Err(ConfigError::InvalidBenchmark(format!("the capacity must be finite and not negative, got {}", capacity)));
+        }
+        Ok(Knapsack { values, weights, capacity })
+    }
+
+    /// Returns the total weight of the chosen items
+    pub fn weight(&self, agent: &BitString) -> f64 {
+        (0..agent.len()).filter(|i| agent.get(*i)).map(|i| self.weights[i]).sum()
+    }
+
+    /// Returns the total value of the chosen items
+    pub fn value(&self, agent: &BitString) -> f64 {
+        (0..agent.len()).filter(|i| agent.get(*i)).map(|i| self.values[i]).sum()
+    }
+
+    /// Removes the chosen items with the smallest value per weight until the rest fits into the knapsack
+    pub fn repair(&self, agent: &BitString) -> BitString {
+        let mut agent = agent.clone();
+        let mut weight = self.weight(&agent);
+        if weight <= self.capacity {
+            return agent;
+        }
+
+        let ratio = |i: usize| if self.weights[i] > 0.0 { self.values[i] / self.weights[i] } else { f64::INFINITY };
+        let mut chosen = (0..agent.len()).filter(|i| agent.get(*i)).collect::<Vec<usize>>();
+        chosen.sort_by(|a, b| ratio(*a).partial_cmp(&ratio(*b)).unwrap_or(std::cmp::Ordering::Equal));
+        for i in chosen {
+            if weight <= self.capacity {
+                break;
+            }
+            agent.set(i, false);
+            weight -= self.weights[i];
+        }
+        agent
+    }
+}
+
+impl Problem<BitString> for Knapsack {
+    fn random_agent(&self, rng: &mut dyn RngCore) -> BitString {
+        self.repair(&BitString::random(self.values.len(), rng))
+    }
+
+    /// The total value of the items, or 0 if they don't fit into the knapsack (the agents made by the problem always fit)
+    fn fitness(&self, agent: &BitString) -> f64 {
+        if self.weight(agent) <= self.capacity { self.value(agent) } else { 0.0 }
+    }
+
+    fn mutate(&self, agent: &BitString, rng: &mut dyn RngCore) -> BitString {
+        self.repair(&BitFlip::per_bit_of(agent.len()).mutate(agent, rng))
+    }
+
+    fn crossover(&self, a: &BitString, b: &BitString, rng: &mut dyn RngCore) -> BitString {
+        self.repair(&Uniform::default().cross(a, b, rng).swap_remove(0))
+    }
+}
+
+/// A maximum cut problem of a weighted undirected graph: the agents split the vertices into two sets (by bit i of
+/// vertex i), and the fitness is the total weight of the edges between the sets (maximised)
+///
+/// The mutation flips one bit on average, the crossover is uniform.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MaxCut {
+    vertices: usize,
+    edges: Vec<(usize, usize, f64)>,
+}
+
+impl MaxCut {
+    /// Creates the problem of the graph with the given number of vertices and weighted edges (the vertices are
+    /// numbered from 0, the edges are (vertex, vertex, weight) triples)
+    ///
+    /// Returns an error if there are no vertices, an edge has a vertex that is not in the graph or a weight is not
+    /// finite.
+    pub fn new(vertices: usize, edges: Vec<(usize, usize, f64)>) -> Result<Self, ConfigError> {
+        if vertices == 0 {
+            return Err(ConfigError::InvalidBenchmark("the graph needs at least 1 vertex".to_string()));
+        }
+        if let Some((a, b, _)) = edges.iter().find(|(a, b, _)| *a >= vertices || *b >= vertices) {
+            return Err(ConfigError::InvalidBenchmark(format!("the edges must connect the vertices 0 to {}, got ({}, {})", vertices - 1, a, b)));
+        }
+        if let Some((_, _, weight)) = edges.iter().find(|(_, _, w)| !w.is_finite()) {
+            return Err(ConfigError::InvalidBenchmark(format!("the weights must be finite, got {}", weight)));
+        }
+        Ok(MaxCut { vertices, edges })
+    }
+
+    /// Creates the problem of the graph with the given number of vertices and unweighted edges (every weight is 1)
+    ///
+    /// Returns the same errors as new().
+    pub fn unweighted(vertices: usize, edges: &[(usize, usize)]) -> Result<Self, ConfigError> {
+        MaxCut::new(vertices, edges.iter().map(|(a, b)| (*a, *b, 1.0)).collect())
+    }
+
+    /// Returns the number of vertices
+    pub fn vertices(&self) -> usize {
+        self.vertices
+    }
+
+    /// Returns the weighted edges
+    pub fn edges(&self) -> &[(usize, usize, f64)] {
+        &self.edges
+    }
+
+    /// Returns the total weight of the edges between the two sets of vertices
+    pub fn cut(&self, agent: &BitString) -> f64 {
+        self.edges.iter()
+            .filter(|(a, b, _)| agent.get(*a) != agent.get(*b))
+            .map(|(_, _, weight)| weight)
+            .sum()
+    }
+}
+
+impl Problem<BitString> for MaxCut {
+    fn random_agent(&self, rng: &mut dyn RngCore) -> BitString {
+        BitString::random(self.vertices, rng)
+    }
+
+    fn fitness(&self, agent: &BitString) -> f64 {
+        self.cut(agent)
+    }
+
+    fn mutate(&self, agent: &BitString, rng: &mut dyn RngCore) -> BitString {
+        BitFlip::per_bit_of(agent.len()).mutate(agent, rng)
+    }
+
+    fn crossover(&self, a: &BitString, b: &BitString, rng: &mut dyn RngCore) -> BitString {
+        Uniform::default().cross(a, b, rng).swap_remove(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Genetic;
+
+    #[test]
+    fn tsplib() {
+        let square = "NAME : square\nCOMMENT : 4 corners\nTYPE : TSP\nDIMENSION : 4\nEDGE_WEIGHT_TYPE : EUC_2D\nNODE_COORD_SECTION\n1 0 0\n2 0 3.2\n3 4 3.2\n4 4 0\nEOF\n";
+        let tsp = Tsp::parse(square).unwrap();
+        assert_eq!(tsp.name, "square");
+        assert_eq!(tsp.cities(), 4);
+        assert_eq!(tsp.distances()[0][2], 5.0);
+        assert_eq!(tsp.tour_length(&[0, 1, 2, 3]), 14.0);
+        assert_eq!(tsp.tour_length(&[0, 2, 1, 3]), 18.0);
+
+        let ceil = Tsp::parse(&square.replace("EUC_2D", "CEIL_2D")).unwrap();
+        assert_eq!(ceil.distances()[0][1], 4.0);
+        let att = Tsp::parse(&square.replace("EUC_2D", "ATT")).unwrap();
+        assert_eq!(att.distances()[0][3], 2.0);
+
+        let upper = "TYPE: TSP\nDIMENSION: 3\nEDGE_WEIGHT_TYPE: EXPLICIT\nEDGE_WEIGHT_FORMAT: UPPER_ROW\nEDGE_WEIGHT_SECTION\n1 2\n3\nEOF";
+        let lower = "TYPE: TSP\nDIMENSION: 3\nEDGE_WEIGHT_TYPE: EXPLICIT\nEDGE_WEIGHT_FORMAT: LOWER_DIAG_ROW\nEDGE_WEIGHT_SECTION\n0 1 0 2 3 0\nEOF";
+        let full = "TYPE: ATSP\nDIMENSION: 3\nEDGE_WEIGHT_TYPE: EXPLICIT\nEDGE_WEIGHT_FORMAT: FULL_MATRIX\nEDGE_WEIGHT_SECTION\n0 1 2\n1 0 3\n2 3 0\nEOF";
+        assert_eq!(Tsp::parse(upper).unwrap().distances(), Tsp::parse(full).unwrap().distances());
+        assert_eq!(Tsp::parse(lower).unwrap().distances(), Tsp::parse(full).unwrap().distances());
+
+        // the distance of two cities of burma14 from TSPLIB
+        let geo = "TYPE: TSP\nDIMENSION: 2\nEDGE_WEIGHT_TYPE: GEO\nNODE_COORD_SECTION\n1 16.47 96.10\n2 16.47 94.44\nEOF";
+        assert_eq!(Tsp::parse(geo).unwrap().distances()[0][1], 153.0);
+
+        assert!(matches!(Tsp::parse("TYPE: CVRP\nDIMENSION: 3"), Err(TsplibError::Format(_))));
+        assert!(Tsp::parse(&square.replace("DIMENSION : 4", "DIMENSION : 5")).is_err());
+        assert!(Tsp::parse(&square.replace("3 4 3.2", "2 4 3.2")).is_err());
+        assert!(Tsp::parse(&square.replace("EUC_2D", "EUC_3D")).is_err());
+        assert!(Tsp::parse(&upper.replace("\n3\n", "\n")).is_err());
+        assert!(matches!(Tsp::load("does/not/exist.tsp"), Err(TsplibError::Io(_))));
+    }
+
+    #[test]
+    fn tsp() {
+        // the cities on a circle are visited in their order by the shortest tour
+        let cities = (0..10).map(|i| (2.0 * PI * i as f64 / 10.0).sin_cos()).collect::<Vec<(f64, f64)>>();
+        let tsp = Tsp::from_coordinates(&cities).unwrap();
+        let optimum = tsp.tour_length(&(0..10).collect::<Vec<usize>>());
+        let result = Genetic::from_problem(50, 200, 0.7, 0.5, tsp.clone()).run_with_seed(1);
+        assert!((result.best_fitness - optimum).abs() < 1e-9, "{} {}", result.best_fitness, optimum);
+        assert_eq!(result.best_fitness, tsp.tour_length(&result.best));
+
+        assert!(matches!(Tsp::from_coordinates(&[(0.0, 0.0)]), Err(ConfigError::InvalidBenchmark(_))));
+        assert!(Tsp::from_coordinates(&[(0.0, 0.0), (f64::NAN, 1.0)]).is_err());
+        assert!(Tsp::from_distances(vec![vec![0.0, 1.0], vec![1.0]]).is_err());
+        assert!(Tsp::from_distances(vec![vec![0.0, f64::INFINITY], vec![1.0, 0.0]]).is_err());
+    }
+
+    #[test]
+    fn knapsack() {
+        let knapsack = Knapsack::new(vec![10.0, 7.0, 5.0, 1.0, 4.0], vec![5.0, 4.0, 3.0, 2.0, 1.0], 9.0).unwrap();
+        let all = BitString::from_bools(&[true; 5]);
+        assert_eq!(knapsack.weight(&all), 15.0);
+        assert_eq!(knapsack.fitness(&all), 0.0);
+        // item 3 (0.5 value per weight), then item 2 (1.67) and item 1 (1.75) are removed
+        let repaired = knapsack.repair(&all);
+        assert_eq!(repaired.iter().collect::<Vec<bool>>(), vec![true, false, false, false, true]);
+        assert_eq!(knapsack.fitness(&repaired), 14.0);
+
+        let mut rng = StdRng::seed_from_u64(1);
+        for _ in 0..100 {
+            let a = knapsack.random_agent(&mut rng);
+            let b = knapsack.mutate(&a, &mut rng);
+            assert!(knapsack.weight(&a) <= 9.0 && knapsack.weight(&b) <= 9.0);
+            assert!(knapsack.weight(&knapsack.crossover(&a, &b, &mut rng)) <= 9.0);
+        }
+
+        // the best is 10 + 5 + 4 with a weight of 9
+        let result = Genetic::from_problem(20, 50, 0.7, 0.5, knapsack).run_with_seed(1);
+        assert_eq!(result.best_fitness, 19.0);
+
+        assert!(matches!(Knapsack::new(vec![], vec![], 1.0), Err(ConfigError::InvalidBenchmark(_))));
+        assert!(Knapsack::new(vec![1.0, 2.0], vec![1.0], 1.0).is_err());
+        assert!(Knapsack::new(vec![f64::NAN], vec![1.0], 1.0).is_err());
+        assert!(Knapsack::new(vec![1.0], vec![-1.0], 1.0).is_err());
+        assert!(Knapsack::new(vec![1.0], vec![f64::INFINITY], 1.0).is_err());
+        assert!(Knapsack::new(vec![1.0], vec![1.0], f64::NAN).is_err());
+        assert!(Knapsack::new(vec![1.0], vec![1.0], -1.0).is_err());
+        assert!(Knapsack::new(vec![1.0], vec![0.0], 0.0).is_ok());
+    }
+
+    #[test]
+    fn max_cut() {
+        // the cycle of 6 vertices is cut at every edge by the alternating sets
+        let cycle = MaxCut::unweighted(6, &[(0, 1), (1, 2), (2, 3), (3, 4), (4, 5), (5, 0)]).unwrap();
+        assert_eq!(cycle.cut(&BitString::from_bools(&[true, false, true, false, true, false])), 6.0);
+        assert_eq!(cycle.cut(&BitString::zeros(6)), 0.0);
+        let result = Genetic::from_problem(20, 50, 0.7, 0.5, cycle).run_with_seed(1);
+        assert_eq!(result.best_fitness, 6.0);
+
+        let weighted = MaxCut::new(3, vec![(0, 1, 2.0), (1, 2, 0.5), (0, 2, 1.0)]).unwrap();
+        assert_eq!(weighted.cut(&BitString::from_bools(&[true, false, false])), 3.0);
+        assert_eq!(weighted.vertices(), 3);
+        assert_eq!(weighted.edges().len(), 3);
+
+        assert!(matches!(MaxCut::unweighted(0, &[]), Err(ConfigError::InvalidBenchmark(_))));
+        assert!(MaxCut::unweighted(3, &[(0, 3)]).is_err());
+        assert!(MaxCut::new(3, vec![(0, 1, f64::NAN)]).is_err());
+    }
+}
@@ -0,0 +1,265 @@
+use rand::prelude::*;
+use std::f64::consts::{E, PI};
+use std::sync::Arc;
+
+use crate::genome::Bounds;
+use crate::operators::standard_normal;
+use crate::{Objective, Problem};
+
+/// A classic test function (minimised, the optimal value is 0)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Function {
+    /// The sum of the squares (unimodal, separable), bounds: [-5.12, 5.12], optimum: 0
+    Sphere,
+    /// The sphere with a cosine wave (highly multimodal, separable), bounds: [-5.12, 5.12], optimum: 0
+    Rastrigin,
+    /// A narrow curved valley (unimodal for few dimensions, non-separable), bounds: [-5, 10], optimum: 1
+    Rosenbrock,
+    /// A nearly flat surface with a deep hole in the middle (multimodal), bounds: [-32.768, 32.768], optimum: 0
+    Ackley,
+    /// Many small local optima on a parabola (multimodal, non-separable), bounds: [-600, 600], optimum: 0
+    Griewank,
+    /// The best local optimum is far from the second best one (deceptive, multimodal),
+    /// bounds: [-500, 500], optimum: 420.9687 (outside of the bounds the values are mirrored back and penalised, as in
+    /// CEC 2014, so a shifted or rotated Schwefel isn't better anywhere than at its optimum)
+    Schwefel,
+}
+
+impl Function {
+    /// All of the functions
+    pub const ALL: [Function; 6] = [Function::Sphere, Function::Rastrigin, Function::Rosenbrock, Function::Ackley, Function::Griewank, Function::Schwefel];
+
+    /// Returns the value of the function at x
+    pub fn evaluate(&self, x: &[f64]) -> f64 {
+        let n = x.len() as f64;
+        match self {
+            Function::Sphere => x.iter().map(|x| x * x).sum(),
+            Function::Rastrigin => 10.0 * n + x.iter().map(|x| x * x - 10.0 * (2.0 * PI * x).cos()).sum::<f64>(),
+            Function::Rosenbrock => x.windows(2).map(|w| 100.0 * (w[1] - w[0] * w[0]).powi(2) + (1.0 - w[0]).powi(2)).sum(),
+            Function::Ackley => {
+                let squares = x.iter().map(|x| x * x).sum::<f64>() / n;
+                let cosines = x.iter().map(|x| (2.0 * PI * x).cos()).sum::<f64>() / n;
+                -20.0 * (-0.2 * squares.sqrt()).exp() - cosines.exp() + 20.0 + E
+            }
+            Function::Griewank => {
+                let product = x.iter().enumerate().map(|(i, x)| (x / ((i + 1) as f64).sqrt()).cos()).product::<f64>();
+                1.0 + x.iter().map(|x| x * x).sum::<f64>() / 4000.0 - product
+            }
+            // the constant is the value of the sum at the optimum, so the optimal value is 0 (within 1e-9 per dimension)
+            Function::Schwefel => 418.982887272433 * n - x.iter().map(|x| schwefel_term(*x, n)).sum::<f64>(),
+        }
+    }
+
+    /// Returns the usual bounds of the function in the given number of dimensions
+    pub fn bounds(&self, dimensions: usize) -> Arc<Bounds> {
+        let (lower, upper) = match self {
+            Function::Sphere | Function::Rastrigin => (-5.12, 5.12),
+            Function::Rosenbrock => (-5.0, 10.0),
+            Function::Ackley => (-32.768, 32.768),
+            Function::Griewank => (-600.0, 600.0),
+            Function::Schwefel => (-500.0, 500.0),
+        };
+        Bounds::uniform(dimensions, lower, upper)
+    }
+
+    /// Returns the place of the optimum in the given number of dimensions
+    pub fn optimum(&self, dimensions: usize) -> Vec<f64> {
+        let value = match self {
+            Function::Rosenbrock => 1.0,
+            Function::Schwefel => 420.968746359982,
+            _ => 0.0,
+        };
+        vec![value; dimensions]
+    }
+}
+
+/// Returns the term of a coordinate in the sum of the Schwefel function of n dimensions, the coordinates outside of
+/// [-500, 500] are mirrored back into it and get a quadratic penalty
+fn schwefel_term(x: f64, n: f64) -> f64 {
+    if x > 500.0 {
+        let y = 500.0 - x % 500.0;
+        y * y.abs().sqrt().sin() - (x - 500.0).powi(2) / (10000.0 * n)
+    } else if x < -500.0 {
+        let y = x.abs() % 500.0 - 500.0;
+        y * y.abs().sqrt().sin() - (x + 500.0).powi(2) / (10000.0 * n)
+    } else {
+        x * x.abs().sqrt().sin()
+    }
+}
+
+/// A test function in a given number of dimensions, optionally shifted and rotated
+#[derive(Debug, Clone, PartialEq)]
+pub struct Benchmark {
+    /// The test function
+    pub function: Function,
+    /// The search space (the usual bounds of the function)
+    pub bounds: Arc<Bounds>,
+    /// The place of the optimum (the optimum of the function if it's not shifted)
+    shift: Vec<f64>,
+    /// The orthogonal matrix mixing the coordinates (None if it's not rotated)
+    rotation: Option<Vec<Vec<f64>>>,
+}
+
+impl Benchmark {
+    /// Creates the benchmark of the function in the given number of dimensions
+    ///
+    /// # Panics:
+    ///  If there are no dimensions
+    pub fn new(function: Function, dimensions: usize) -> Self {
+        assert!(dimensions > 0, "the benchmark needs at least 1 dimension");
+        Benchmark { function, bounds: function.bounds(dimensions), shift: function.optimum(dimensions), rotation: None }
+    }
+
+    /// Moves the optimum to a random point of the inner 80% of the bounds
+    pub fn shifted(mut self, rng: &mut dyn RngCore) -> Self {
+        self.shift = (0..self.dimensions())
+            .map(|i| self.bounds.lower[i] + self.bounds.range(i) * rng.gen_range(0.1, 0.9))
+            .collect();
+        self
+    }
+
+    /// Mixes the coordinates by a random orthogonal matrix (around the optimum, so it stays in the same place)
+    pub fn rotated(mut self, rng: &mut dyn RngCore) -> Self {
+        let n = self.dimensions();
+        // Gram-Schmidt orthonormalisation of a random Gaussian matrix
+        let mut rows: Vec<Vec<f64>> = Vec::with_capacity(n);
+        while rows.len() < n {
+            let mut row = (0..n).map(|_| standard_normal(rng)).collect::<Vec<f64>>();
+            for other in &rows {
+                let dot = row.iter().zip(other).map(|(a, b)| a * b).sum::<f64>();
+                for (a, b) in row.iter_mut().zip(other) {
+                    *a -= dot * b;
+                }
+            }
+            let norm = row.iter().map(|a| a * a).sum::<f64>().sqrt();
+            // a nearly dependent row is drawn again
+            if norm > 1e-6 {
+                rows.push(row.into_iter().map(|a| a / norm).collect());
+            }
+        }
+        self.rotation = Some(rows);
+        self
+    }
+
+    /// Returns the number of dimensions
+    pub fn dimensions(&self) -> usize {
+        self.bounds.len()
+    }
+
+    /// Returns the place of the optimum (its value is 0)
+    pub fn optimum(&self) -> Vec<f64> {
+        self.shift.clone()
+    }
+
+    /// Returns the value of the benchmark at x
+    ///
+    /// # Panics:
+    ///  If x doesn't have a value for every dimension
+    pub fn evaluate(&self, x: &[f64]) -> f64 {
+        assert_eq!(x.len(), self.dimensions(), "the agent must have a value for every dimension");
+        let base = self.function.optimum(x.len());
+        let moved = x.iter().zip(&self.shift).map(|(x, o)| x - o).collect::<Vec<f64>>();
+        let z = match &self.rotation {
+            Some(rotation) => rotation.iter().zip(&base).map(|(row, c)| c + row.iter().zip(&moved).map(|(r, d)| r * d).sum::<f64>()).collect::<Vec<f64>>(),
+            None => moved.iter().zip(&base).map(|(d, c)| c + d).collect(),
+        };
+        self.function.evaluate(&z)
+    }
+
+    /// Returns the value x clamped into the bounds of the i-th dimension
+    fn clamped(&self, i: usize, x: f64) -> f64 {
+        x.clamp(self.bounds.lower[i], self.bounds.upper[i])
+    }
+}
+
+impl Problem<Vec<f64>> for Benchmark {
+    fn random_agent(&self, rng: &mut dyn RngCore) -> Vec<f64> {
+        (0..self.dimensions()).map(|i| self.bounds.lower[i] + self.bounds.range(i) * rng.gen::<f64>()).collect()
+    }
+
+    fn fitness(&self, agent: &Vec<f64>) -> f64 {
+        self.evaluate(agent)
+    }
+
+    /// Moves a random coordinate by a normal step of 1% of its interval
+    fn mutate(&self, agent: &Vec<f64>, rng: &mut dyn RngCore) -> Vec<f64> {
+        let mut agent = agent.clone();
+        let i = rng.gen_range(0, agent.len());
+        agent[i] = self.clamped(i, agent[i] + 0.01 * self.bounds.range(i) * standard_normal(rng));
+        agent
+    }
+
+    /// Every coordinate is a random point of the interval of the parents, extended by a quarter on both sides (BLX-0.25)
+    fn crossover(&self, a: &Vec<f64>, b: &Vec<f64>, rng: &mut dyn RngCore) -> Vec<f64> {
+        (0..a.len())
+            .map(|i| {
+                let (low, high) = (a[i].min(b[i]), a[i].max(b[i]));
+                let extension = 0.25 * (high - low);
+                self.clamped(i, low - extension + rng.gen::<f64>() * (high - low + 2.0 * extension))
+            })
+            .collect()
+    }
+
+    fn objective(&self) -> Objective {
+        Objective::Minimize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Genetic;
+
+    #[test]
+    fn functions() {
+        for function in &Function::ALL {
+            let optimum = function.optimum(10);
+            assert!(function.evaluate(&optimum).abs() < 1e-8, "{:?} {}", function, function.evaluate(&optimum));
+            let bounds = function.bounds(10);
+            assert!(optimum.iter().enumerate().all(|(i, x)| bounds.lower[i] <= *x && *x <= bounds.upper[i]));
+            // the optimum is the best of some random points
+            let mut rng = StdRng::seed_from_u64(1);
+            let benchmark = Benchmark::new(*function, 10);
+            assert!((0..100).all(|_| benchmark.evaluate(&benchmark.random_agent(&mut rng)) > 1e-3));
+        }
+
+        assert_eq!(Function::Sphere.evaluate(&[1.0, 2.0]), 5.0);
+        assert!((Function::Rastrigin.evaluate(&[1.0, 1.0]) - 2.0).abs() < 1e-9);
+        assert_eq!(Function::Rosenbrock.evaluate(&[0.0, 0.0]), 1.0);
+        assert!((Function::Griewank.evaluate(&[PI * 2.0, 0.0]) - (1.0 + PI * PI / 1000.0 - 1.0)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn benchmark() {
+        let mut rng = StdRng::seed_from_u64(1);
+        for function in &Function::ALL {
+            let benchmark = Benchmark::new(*function, 4).shifted(&mut rng).rotated(&mut rng);
+            assert!(benchmark.evaluate(&benchmark.optimum()).abs() < 1e-8, "{:?}", function);
+            assert_ne!(benchmark.optimum(), function.optimum(4));
+        }
+
+        // the shifted and rotated Schwefel leaves the bounds of the function, but it isn't better than its optimum there
+        for _ in 0..10 {
+            let schwefel = Benchmark::new(Function::Schwefel, 4).shifted(&mut rng);
+            let rotated = schwefel.clone().rotated(&mut rng);
+            for _ in 0..1000 {
+                let x = schwefel.random_agent(&mut rng);
+                assert!(schwefel.evaluate(&x) >= 0.0 && rotated.evaluate(&x) >= 0.0, "{:?}", x);
+            }
+        }
+        assert!(Function::Schwefel.evaluate(&[1092.0]) > 0.0);
+        assert!(Function::Schwefel.evaluate(&[-700.0, 420.968746359982]) > 0.0);
+
+        // the rotation is orthogonal, so it doesn't change the sphere around the origin
+        let sphere = Benchmark::new(Function::Sphere, 3).rotated(&mut rng);
+        assert!((sphere.evaluate(&[1.0, 2.0, 3.0]) - 14.0).abs() < 1e-9);
+
+        // the shifted sphere can be solved by the genetic algorithm
+        let shifted = Benchmark::new(Function::Sphere, 3).shifted(&mut rng);
+        let agent = shifted.random_agent(&mut rng);
+        assert!(shifted.mutate(&agent, &mut rng).iter().zip(&agent).filter(|(a, b)| a != b).count() <= 1);
+        let result = Genetic::from_problem(50, 100, 0.7, 0.5, shifted.clone()).run_with_seed(1);
+        assert!(result.best_fitness < 1e-2, "{}", result.best_fitness);
+    }
+}
@@ -0,0 +1,38 @@
+//! Ready-made test problems with known optima, for validating the algorithms and sanity-checking configurations
+//!
+//! The continuous ones are the classic test functions: every Function is minimised and its optimal value is 0. A
+//! Benchmark is a Function in a given number of dimensions, optionally shifted (the optimum is moved to a random
+//! point) and rotated (the coordinates are mixed by a random orthogonal matrix, so the separable functions become
+//! non-separable). A Benchmark is a Problem of Vec<f64> agents (with a Gaussian mutation and a blend crossover kept
+//! inside the bounds), so it can be given to Genetic::from_problem() directly.
+//!
+//! The combinatorial ones are the travelling salesman problem (Tsp, read from TSPLIB files, a Problem of
+//! Permutation agents), the 0/1 knapsack problem (Knapsack, a Problem of BitString agents repaired to fit the
+//! capacity) and the maximum cut of a weighted graph (MaxCut, a Problem of BitString agents).
+//!
+//! # Examples:
+//! ```
+//! use rand::prelude::*;
+//! use bbte_optim_tzim1773_genetic::Genetic;
+//! use bbte_optim_tzim1773_genetic::benchmarks::{Benchmark, Function, Tsp};
+//!
+//! fn main() {
+//!     let mut rng = StdRng::seed_from_u64(1);
+//!     let benchmark = Benchmark::new(Function::Rastrigin, 5).shifted(&mut rng).rotated(&mut rng);
+//!     let optimum = benchmark.optimum();
+//!     assert!(benchmark.evaluate(&optimum).abs() < 1e-9);
+//!
+//!     let result = Genetic::from_problem(100, 200, 0.7, 0.3, benchmark).run();
+//!     println!("{} (the optimum is 0 at {:?})", result.best_fitness, optimum);
+//!
+//!     let tsp = Tsp::parse("TYPE: TSP\nDIMENSION: 4\nEDGE_WEIGHT_TYPE: EUC_2D\nNODE_COORD_SECTION\n1 0 0\n2 0 3\n3 4 3\n4 4 0\nEOF\n").unwrap();
+//!     let result = Genetic::from_problem(20, 50, 0.7, 0.3, tsp).run();
+//!     assert_eq!(result.best_fitness, 14.0);
+//! }
+//! ```
+
+mod combinatorial;
+mod continuous;
+
+pub use combinatorial::{Knapsack, MaxCut, Tsp};
+pub use continuous::{Benchmark, Function};
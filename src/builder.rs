@@ -0,0 +1,805 @@
+#[cfg(feature = "async")]
+use futures::future::{BoxFuture, Future, FutureExt};
+use rand::RngCore;
+#[cfg(feature = "serde")]
+use serde::Serialize;
+use std::hash::Hash;
+use std::ops::ControlFlow;
+#[cfg(feature = "serde")]
+use std::path::PathBuf;
+use std::str::FromStr;
+#[cfg(any(feature = "rayon", feature = "async"))]
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::operators::{Crossover, Mutation};
+#[cfg(feature = "async")]
+use crate::AsyncFitnessFn;
+#[cfg(feature = "serde")]
+use crate::{Checkpointing, GeneticConfig};
+#[cfg(feature = "distributed")]
+use crate::DistributedFitness;
+use crate::{
+    dedup, problem, AgentFn, Archive, CancellationToken, CaseFitnessFn, Cellular, Clearing, ConfigError, ConstraintHandling, ConstraintsFn, DistanceFn, DiversityAdaptation, DuplicatesFn, DynamicEnvironment, EvolutionStrategy, ExternalFitness, Fitness, FitnessCache, FitnessError, FitnessErrorPolicy, FitnessFn, GenerationFn, GenerationInfo, Genetic, Hypermutation, LocalSearch, LruCache, MutateFn, Novelty, Objective,
+    OffspringFn, OneFifthRule, OperatorSelection, Opposition, PenaltyFn, Problem, ProgressFn, RepairFn, Restart, RunLog, Schedule, Selection, Sharing, Speciation, Stagnation, SteadyState, StopCondition, Surrogate,
+};
+
+/// Builds a Genetic, checking its parameters before it's used
+///
+/// Parameters that are not set keep their suggested values
+/// (population: 100, max_generation: 1000, pc: 0.5, pm: 0.4),
+/// the four functions (or a Problem) have to be given.
+///
+/// # Examples:
+///  See at Genetic::builder()
+pub struct GeneticBuilder<'a, T, F = f64> {
+    population: usize,
+    max_generation: usize,
+    pc: f64,
+    pm: f64,
+    get_random_agent: Option<AgentFn<'a, T>>,
+    initial_population: Vec<T>,
+    opposition: Option<Opposition<'a, T>>,
+    f_fitness: Option<FitnessFn<'a, T, F>>,
+    f_mutate: Option<MutateFn<'a, T>>,
+    f_offspring: Option<OffspringFn<'a, T>>,
+    fitness_error_policy: FitnessErrorPolicy,
+    objective: Objective,
+    elitism: usize,
+    random_immigrants: f64,
+    selection: Selection,
+    parent_selection: Option<Selection>,
+    steady_state: Option<SteadyState>,
+    evolution_strategy: Option<EvolutionStrategy>,
+    cellular: Option<Cellular>,
+    f_length_penalty: Option<PenaltyFn<'a, T, F>>,
+    f_constraints: Option<ConstraintsFn<'a, T>>,
+    constraint_handling: ConstraintHandling,
+    f_repair: Option<RepairFn<'a, T>>,
+    local_search: Option<LocalSearch<'a, T>>,
+    f_case_fitness: Option<CaseFitnessFn<'a, T>>,
+    pm_adaptation: Option<OneFifthRule>,
+    diversity_adaptation: Option<DiversityAdaptation>,
+    hypermutation: Option<Hypermutation>,
+    dynamic_environment: Option<DynamicEnvironment>,
+    f_distance: Option<DistanceFn<'a, T>>,
+    sharing: Option<Sharing>,
+    clearing: Option<Clearing>,
+    speciation: Option<Speciation>,
+    novelty: Option<Novelty<'a, T>>,
+    surrogate: Option<Surrogate<'a, T>>,
+    pc_schedule: Option<Schedule<'a>>,
+    pm_schedule: Option<Schedule<'a>>,
+    mutation_operators: Vec<(String, MutateFn<'a, T>)>,
+    crossover_operators: Vec<(String, OffspringFn<'a, T>)>,
+    operator_selection: Option<OperatorSelection>,
+    stagnation: Option<Stagnation>,
+    restart: Option<Restart>,
+    target_fitness: Option<F>,
+    max_duration: Option<Duration>,
+    max_evaluations: Option<usize>,
+    stop_condition: Option<StopCondition<'a, F>>,
+    on_generation: Option<GenerationFn<'a, T, F>>,
+    on_progress: Option<ProgressFn<'a, F>>,
+    cancellation: Option<CancellationToken>,
+    run_log: Option<RunLog<T>>,
+    archive: Option<Archive<'a, T>>,
+    f_duplicates: Option<DuplicatesFn<'a, T>>,
+    #[cfg(feature = "serde")]
+    checkpoint: Option<Checkpointing<T, F>>,
+    fitness_cache: Option<Box<dyn FitnessCache<T, F> + 'a>>,
+    #[cfg(feature = "rayon")]
+    thread_pool: Option<Arc<rayon::ThreadPool>>,
+    #[cfg(feature = "rayon")]
+    threads: Option<usize>,
+    #[cfg(feature = "rayon")]
+    chunk_size: usize,
+    #[cfg(feature = "async")]
+    f_async_fitness: Option<AsyncFitnessFn<'a, T, F>>,
+    #[cfg(feature = "async")]
+    concurrency: usize,
+}
+
+impl<'a, T, F> Default for GeneticBuilder<'a, T, F> {
+    fn default() -> Self {
+        GeneticBuilder {
+            population: 100,
+            max_generation: 1000,
+            pc: 0.5,
+            pm: 0.4,
+            get_random_agent: None,
+            initial_population: Vec::new(),
+            opposition: None,
+            f_fitness: None,
+            f_mutate: None,
+            f_offspring: None,
+            fitness_error_policy: FitnessErrorPolicy::Abort,
+            objective: Objective::Maximize,
+            elitism: 0,
+            random_immigrants: 0.0,
+            selection: Selection::Truncation,
+            parent_selection: None,
+            steady_state: None,
+            evolution_strategy: None,
+            cellular: None,
+            f_length_penalty: None,
+            f_constraints: None,
+            constraint_handling: ConstraintHandling::Feasibility,
+            f_repair: None,
+            local_search: None,
+            f_case_fitness: None,
+            pm_adaptation: None,
+            diversity_adaptation: None,
+            hypermutation: None,
+            dynamic_environment: None,
+            f_distance: None,
+            sharing: None,
+            clearing: None,
+            speciation: None,
+            novelty: None,
+            surrogate: None,
+            pc_schedule: None,
+            pm_schedule: None,
+            mutation_operators: Vec::new(),
+            crossover_operators: Vec::new(),
+            operator_selection: None,
+            stagnation: None,
+            restart: None,
+            target_fitness: None,
+            max_duration: None,
+            max_evaluations: None,
+            stop_condition: None,
+            on_generation: None,
+            on_progress: None,
+            cancellation: None,
+            run_log: None,
+            archive: None,
+            f_duplicates: None,
+            #[cfg(feature = "serde")]
+            checkpoint: None,
+            fitness_cache: None,
+            #[cfg(feature = "rayon")]
+            thread_pool: None,
+            #[cfg(feature = "rayon")]
+            threads: None,
+            #[cfg(feature = "rayon")]
+            chunk_size: 1,
+            #[cfg(feature = "async")]
+            f_async_fitness: None,
+            #[cfg(feature = "async")]
+            concurrency: 16,
+        }
+    }
+}
+
+impl<'a, T, F> GeneticBuilder<'a, T, F> {
+    /// Creates a builder with the suggested parameters and no functions
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the parameters read from a configuration file (the stopping criteria that are None there are removed)
+    #[cfg(feature = "serde")]
+    pub fn config(mut self, config: GeneticConfig<F>) -> Self {
+        self.population = config.population;
+        self.max_generation = config.max_generation;
+        self.pc = config.pc;
+        self.pm = config.pm;
+        self.elitism = config.elitism;
+        self.random_immigrants = config.random_immigrants;
+        self.objective = config.objective;
+        self.selection = config.selection;
+        self.parent_selection = config.parent_selection;
+        self.stagnation = config.stagnation;
+        self.target_fitness = config.target_fitness;
+        self.max_duration = config.max_seconds.map(|seconds| Duration::try_from_secs_f64(seconds.max(0.0)).unwrap_or(Duration::MAX));
+        self.max_evaluations = config.max_evaluations;
+        self
+    }
+
+    /// Sets the population size
+    pub fn population(mut self, population: usize) -> Self {
+        self.population = population;
+        self
+    }
+
+    /// Sets the number of generations
+    pub fn max_generation(mut self, max_generation: usize) -> Self {
+        self.max_generation = max_generation;
+        self
+    }
+
+    /// Sets the probability of crossover
+    pub fn pc(mut self, pc: f64) -> Self {
+        self.pc = pc;
+        self
+    }
+
+    /// Sets the probability of mutation
+    pub fn pm(mut self, pm: f64) -> Self {
+        self.pm = pm;
+        self
+    }
+
+    /// Sets the function that returns the agents of the 0th generation
+    pub fn random_agent(mut self, f: impl Fn(&mut dyn RngCore) -> T + Send + Sync + 'a) -> Self {
+        self.get_random_agent = Some(Box::new(f));
+        self
+    }
+
+    /// Sets the function that evaluates an agent (this algorithm maximises this function, unless minimize() is called)
+    pub fn fitness(mut self, f: impl Fn(&T) -> F + Send + Sync + 'a) -> Self {
+        self.f_fitness = Some(Box::new(move |a| Ok(f(a))));
+        self
+    }
+
+    /// Sets a fitness function that can fail, its errors are handled according to the fitness_error_policy
+    pub fn try_fitness<E>(mut self, f: impl Fn(&T) -> Result<F, E> + Send + Sync + 'a) -> Self
+        where E: Into<FitnessError> {
+        self.f_fitness = Some(Box::new(move |a| f(a).map_err(Into::into)));
+        self
+    }
+
+    /// The fitness is computed by an external program, its failures are handled according to the fitness_error_policy
+    pub fn external_fitness(mut self, external: ExternalFitness<T, F>) -> Self
+        where T: 'a, F: FromStr + 'a {
+        self.f_fitness = Some(Box::new(move |a| external.evaluate(a).map_err(Into::into)));
+        self
+    }
+
+    /// The fitness is computed by remote workers over TCP, its failures are handled according to the fitness_error_policy
+    #[cfg(feature = "distributed")]
+    pub fn distributed_fitness(mut self, distributed: DistributedFitness<T, F>) -> Self
+        where T: 'a, F: FromStr + 'a {
+        self.f_fitness = Some(Box::new(move |a| distributed.evaluate(a).map_err(Into::into)));
+        self
+    }
+
+    /// Identical agents are evaluated only once, the fitness of the last `capacity` agents used is kept
+    pub fn cache(self, capacity: usize) -> Self
+        where T: Hash + Eq + Clone + Send + 'a, F: Clone + Send + 'a {
+        self.fitness_cache(LruCache::new(capacity))
+    }
+
+    /// Stores the fitness of the evaluated agents in the given cache
+    pub fn fitness_cache(mut self, cache: impl FitnessCache<T, F> + 'a) -> Self {
+        self.fitness_cache = Some(Box::new(cache));
+        self
+    }
+
+    /// The parallel parts of the run are executed in the given thread pool (e.g. the one of your application)
+    #[cfg(feature = "rayon")]
+    pub fn thread_pool(mut self, thread_pool: Arc<rayon::ThreadPool>) -> Self {
+        self.thread_pool = Some(thread_pool);
+        self.threads = None;
+        self
+    }
+
+    /// The parallel parts of the run are executed in a new thread pool with the given number of threads
+    #[cfg(feature = "rayon")]
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = Some(threads);
+        self.thread_pool = None;
+        self
+    }
+
+    /// Sets the smallest number of agents a thread works on at once
+    #[cfg(feature = "rayon")]
+    pub fn chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = chunk_size;
+        self
+    }
+
+    /// Sets an asynchronous fitness function, run_async() awaits it for many agents at once
+    /// (if no fitness() is given, f_fitness blocks on it, so run() can be used too)
+    #[cfg(feature = "async")]
+    pub fn async_fitness<Fut>(mut self, f: impl Fn(&T) -> Fut + Send + Sync + 'a) -> Self
+        where Fut: Future<Output = F> + Send + 'a, T: 'a, F: 'a {
+        let f = Arc::new(f);
+        let f_async = Arc::clone(&f);
+        self.f_async_fitness = Some(Box::new(move |a| -> BoxFuture<'a, F> { f_async(a).boxed() }));
+        if self.f_fitness.is_none() {
+            self.f_fitness = Some(Box::new(move |a| Ok(futures::executor::block_on(f(a)))));
+        }
+        self
+    }
+
+    /// Sets the number of evaluations run_async() awaits at the same time
+    #[cfg(feature = "async")]
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency;
+        self
+    }
+
+    /// Sets what happens when the fitness function fails
+    pub fn fitness_error_policy(mut self, policy: FitnessErrorPolicy) -> Self {
+        self.fitness_error_policy = policy;
+        self
+    }
+
+    /// Sets the function that mutates an agent
+    pub fn mutate(mut self, f: impl Fn(&T, &mut dyn RngCore) -> T + Send + Sync + 'a) -> Self {
+        self.f_mutate = Some(Box::new(f));
+        self
+    }
+
+    /// Sets the function that crossovers two agents
+    pub fn offspring(mut self, f: impl Fn(&T, &T, &mut dyn RngCore) -> T + Send + Sync + 'a) -> Self {
+        self.f_offspring = Some(Box::new(move |a, b, rng| vec![f(a, b, rng)]));
+        self
+    }
+
+    /// Sets a function that crossovers two agents and creates more than one offspring
+    /// (e.g. both children of a two-point crossover)
+    pub fn multi_offspring(mut self, f: impl Fn(&T, &T, &mut dyn RngCore) -> Vec<T> + Send + Sync + 'a) -> Self {
+        self.f_offspring = Some(Box::new(f));
+        self
+    }
+
+    /// Sets the crossover from an operator (e.g. one from the operators module)
+    pub fn crossover<C>(mut self, crossover: C) -> Self
+        where C: Crossover<T> + Send + Sync + 'a {
+        self.f_offspring = Some(Box::new(move |a, b, rng| crossover.cross(a, b, rng)));
+        self
+    }
+
+    /// Sets the mutation from an operator (e.g. one from the operators module)
+    pub fn mutation<M>(mut self, mutation: M) -> Self
+        where M: Mutation<T> + Send + Sync + 'a {
+        self.f_mutate = Some(Box::new(move |a, rng| mutation.mutate(a, rng)));
+        self
+    }
+
+    /// Sets whether the fitness function should be maximised or minimised
+    pub fn objective(mut self, objective: Objective) -> Self {
+        self.objective = objective;
+        self
+    }
+
+    /// The fitness function will be minimised (same as objective(Objective::Minimize))
+    pub fn minimize(self) -> Self {
+        self.objective(Objective::Minimize)
+    }
+
+    /// Sets the number of the best agents that are copied unchanged into the next generation
+    pub fn elitism(mut self, elitism: usize) -> Self {
+        self.elitism = elitism;
+        self
+    }
+
+    /// Sets the known agents that are put into the 0th generation (the rest of it is random)
+    pub fn initial_population(mut self, agents: Vec<T>) -> Self {
+        self.initial_population = agents;
+        self
+    }
+
+    /// Uses opposition-based learning: the first generation is opposed, and later ones with the jumping rate
+    pub fn opposition(mut self, opposition: Opposition<'a, T>) -> Self {
+        self.opposition = Some(opposition);
+        self
+    }
+
+    /// Sets the fraction of the population that is replaced with new random agents in every generation
+    pub fn random_immigrants(mut self, random_immigrants: f64) -> Self {
+        self.random_immigrants = random_immigrants;
+        self
+    }
+
+    /// Sets the way the agents of the next generation are chosen
+    pub fn selection(mut self, selection: Selection) -> Self {
+        self.selection = selection;
+        self
+    }
+
+    /// Sets the way the parents are chosen (instead of pairing every agent with a random partner)
+    pub fn parent_selection(mut self, selection: Selection) -> Self {
+        self.parent_selection = Some(selection);
+        self
+    }
+
+    /// Switches to the steady-state mode, where every generation only creates a few offspring
+    pub fn steady_state(mut self, steady_state: SteadyState) -> Self {
+        self.steady_state = Some(steady_state);
+        self
+    }
+
+    /// Switches to (mu, lambda) or (mu + lambda) replacement (the population is not changed, mu can't be bigger than it)
+    pub fn evolution_strategy(mut self, evolution_strategy: EvolutionStrategy) -> Self {
+        self.evolution_strategy = Some(evolution_strategy);
+        self
+    }
+
+    /// Switches to the cellular mode (the population is not changed, it has to be the number of cells)
+    pub fn cellular(mut self, cellular: Cellular) -> Self {
+        self.cellular = Some(cellular);
+        self
+    }
+
+    /// Sets the function that adjusts the fitness before selection (e.g. to penalise long genomes)
+    pub fn length_penalty(mut self, f: impl Fn(&T, F) -> F + Send + Sync + 'a) -> Self {
+        self.f_length_penalty = Some(Box::new(f));
+        self
+    }
+
+    /// Sets the function that returns the violations of the constraints (0.0 or less if a constraint is satisfied),
+    /// the agents are compared by Deb's feasibility rules
+    pub fn constraints(mut self, f: impl Fn(&T) -> Vec<f64> + Send + Sync + 'a) -> Self {
+        self.f_constraints = Some(Box::new(f));
+        self
+    }
+
+    /// Sets the way the violations of the constraints are handled (penalties instead of the feasibility rules)
+    pub fn constraint_handling(mut self, constraint_handling: ConstraintHandling) -> Self {
+        self.constraint_handling = constraint_handling;
+        self
+    }
+
+    /// Sets the function that fixes every offspring and mutant before it's evaluated
+    pub fn repair(mut self, f: impl Fn(&T) -> T + Send + Sync + 'a) -> Self {
+        self.f_repair = Some(Box::new(f));
+        self
+    }
+
+    /// Improves the best candidates with a local search before the next generation is selected (memetic algorithm)
+    pub fn local_search(mut self, local_search: LocalSearch<'a, T>) -> Self {
+        self.local_search = Some(local_search);
+        self
+    }
+
+    /// Sets the function that evaluates an agent on every test case (needed by the lexicase selections)
+    pub fn case_fitness(mut self, f: impl Fn(&T) -> Vec<f64> + Send + Sync + 'a) -> Self {
+        self.f_case_fitness = Some(Box::new(f));
+        self
+    }
+
+    /// Adapts pm after every generation with the 1/5 success rule (pm() sets the starting value)
+    pub fn pm_adaptation(mut self, rule: OneFifthRule) -> Self {
+        self.pm_adaptation = Some(rule);
+        self
+    }
+
+    /// Sets pc and pm after every generation from the diversity of the population
+    pub fn diversity_adaptation(mut self, adaptation: DiversityAdaptation) -> Self {
+        self.diversity_adaptation = Some(adaptation);
+        self
+    }
+
+    /// Multiplies pm for a few generations when the best fitness stagnates or the fitness landscape changes
+    pub fn hypermutation(mut self, hypermutation: Hypermutation) -> Self {
+        self.hypermutation = Some(hypermutation);
+        self
+    }
+
+    /// Lets the fitness function change during the run: the changes are detected and the population responds to them
+    pub fn dynamic_environment(mut self, dynamic_environment: DynamicEnvironment) -> Self {
+        self.dynamic_environment = Some(dynamic_environment);
+        self
+    }
+
+    /// Sets the function that returns the distance of two agents (used to measure diversity, by fitness sharing and clearing)
+    pub fn distance(mut self, f: impl Fn(&T, &T) -> f64 + Send + Sync + 'a) -> Self {
+        self.f_distance = Some(Box::new(f));
+        self
+    }
+
+    /// The selection strategies use the fitness shared between the close agents (needs distance())
+    pub fn sharing(mut self, sharing: Sharing) -> Self {
+        self.sharing = Some(sharing);
+        self
+    }
+
+    /// The selection strategies use the fitness cleared in every niche except for its best agents (needs distance())
+    pub fn clearing(mut self, clearing: Clearing) -> Self {
+        self.clearing = Some(clearing);
+        self
+    }
+
+    /// The population is partitioned into species that evolve separately (needs distance())
+    pub fn speciation(mut self, speciation: Speciation) -> Self {
+        self.speciation = Some(speciation);
+        self
+    }
+
+    /// The next generation is selected by the novelty of the agents (novelty search)
+    pub fn novelty(mut self, novelty: Novelty<'a, T>) -> Self {
+        self.novelty = Some(novelty);
+        self
+    }
+
+    /// Pre-screens the offspring with a surrogate model, only the most promising ones are evaluated
+    pub fn surrogate(mut self, surrogate: Surrogate<'a, T>) -> Self {
+        self.surrogate = Some(surrogate);
+        self
+    }
+
+    /// pc follows the given schedule across the generations
+    pub fn pc_schedule(mut self, schedule: Schedule<'a>) -> Self {
+        self.pc_schedule = Some(schedule);
+        self
+    }
+
+    /// pm follows the given schedule across the generations
+    pub fn pm_schedule(mut self, schedule: Schedule<'a>) -> Self {
+        self.pm_schedule = Some(schedule);
+        self
+    }
+
+    /// Registers a named mutation operator, if there are any, they are used instead of f_mutate
+    /// (which doesn't have to be given then)
+    pub fn mutation_operator<M>(mut self, name: &str, mutation: M) -> Self
+        where M: Mutation<T> + Send + Sync + 'a {
+        self.mutation_operators.push((name.to_string(), Box::new(move |a, rng| mutation.mutate(a, rng))));
+        self
+    }
+
+    /// Registers a named crossover operator, if there are any, they are used instead of f_offspring
+    /// (which doesn't have to be given then)
+    pub fn crossover_operator<C>(mut self, name: &str, crossover: C) -> Self
+        where C: Crossover<T> + Send + Sync + 'a {
+        self.crossover_operators.push((name.to_string(), Box::new(move |a, b, rng| crossover.cross(a, b, rng))));
+        self
+    }
+
+    /// The registered operators are chosen with a multi-armed bandit learning from their successes
+    pub fn operator_selection(mut self, operator_selection: OperatorSelection) -> Self {
+        self.operator_selection = Some(operator_selection);
+        self
+    }
+
+    /// Stops the run early when the best fitness doesn't improve for a number of generations
+    pub fn stagnation(mut self, stagnation: Stagnation) -> Self {
+        self.stagnation = Some(stagnation);
+        self
+    }
+
+    /// Restarts the population (keeping its best agents) when the best fitness doesn't improve for a number
+    /// of generations
+    pub fn restart(mut self, restart: Restart) -> Self {
+        self.restart = Some(restart);
+        self
+    }
+
+    /// Stops the run as soon as the best agent reaches the given fitness
+    pub fn target_fitness(mut self, target: F) -> Self {
+        self.target_fitness = Some(target);
+        self
+    }
+
+    /// Stops the run when the given time is up
+    pub fn max_duration(mut self, max_duration: Duration) -> Self {
+        self.max_duration = Some(max_duration);
+        self
+    }
+
+    /// Stops the run when f_fitness was called the given number of times
+    pub fn max_evaluations(mut self, max_evaluations: usize) -> Self {
+        self.max_evaluations = Some(max_evaluations);
+        self
+    }
+
+    /// The run goes on until the given stopping criteria are met (instead of max_generation)
+    pub fn stop_condition(mut self, stop_condition: StopCondition<'a, F>) -> Self {
+        self.stop_condition = Some(stop_condition);
+        self
+    }
+
+    /// Sets the function called after every generation, it can stop the run by returning ControlFlow::Break
+    pub fn on_generation(mut self, f: impl Fn(&GenerationInfo<T, F>) -> ControlFlow<()> + Send + Sync + 'a) -> Self {
+        self.on_generation = Some(Box::new(f));
+        self
+    }
+
+    /// Sets the function called after every generation with the generations executed, max_generation and the best fitness
+    pub fn on_progress(mut self, f: impl Fn(usize, usize, &F) + Send + Sync + 'a) -> Self {
+        self.on_progress = Some(Box::new(f));
+        self
+    }
+
+    /// Shows the progress of the run on the bar: its length is max_generation, its message is the best fitness
+    /// (keep a clone of it to finish it after the run)
+    #[cfg(feature = "indicatif")]
+    pub fn progress_bar(self, bar: indicatif::ProgressBar) -> Self
+        where F: std::fmt::Display {
+        self.on_progress(move |generation, max_generation, best_fitness| {
+            bar.set_length(max_generation as u64);
+            bar.set_position(generation as u64);
+            bar.set_message(format!("best: {}", best_fitness));
+        })
+    }
+
+    /// Stops the run when the token is cancelled (keep a clone of it to cancel the run)
+    pub fn cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = Some(token);
+        self
+    }
+
+    /// Keeps the best `capacity` different agents evaluated during the run (they are returned in RunResult::archive)
+    pub fn archive(self, capacity: usize) -> Self
+        where T: PartialEq {
+        self.best_archive(Archive::new(capacity))
+    }
+
+    /// Keeps the best agents evaluated during the run in the given archive (e.g. with a custom test of sameness)
+    pub fn best_archive(mut self, archive: Archive<'a, T>) -> Self {
+        self.archive = Some(archive);
+        self
+    }
+
+    /// Replaces the copies among the agents the next generation is selected from with new random agents
+    pub fn dedup(mut self) -> Self
+        where T: Hash + Eq {
+        self.f_duplicates = Some(Box::new(|agents: &[&T]| dedup::duplicates(agents.iter())));
+        self
+    }
+
+    /// Same as dedup(), but the agents with the same key are considered copies
+    pub fn dedup_by_key<K: Hash + Eq>(mut self, key: impl Fn(&T) -> K + Send + Sync + 'a) -> Self {
+        self.f_duplicates = Some(Box::new(move |agents: &[&T]| dedup::duplicates(agents.iter().map(|agent| key(agent)))));
+        self
+    }
+
+    /// Writes the statistics of every generation to the log during the run
+    pub fn run_log(mut self, run_log: RunLog<T>) -> Self {
+        self.run_log = Some(run_log);
+        self
+    }
+
+    /// Writes the state of the run to the file every `interval` generations (and at the end of the run),
+    /// so it can be continued with resume_from()
+    #[cfg(feature = "serde")]
+    pub fn checkpoint(mut self, path: impl Into<PathBuf>, interval: usize) -> Self
+        where T: Serialize, F: Serialize {
+        self.checkpoint = Some(Checkpointing::new(path, interval));
+        self
+    }
+
+    /// Sets all four functions and the objective from a Problem
+    pub fn problem<P>(mut self, problem: P) -> Self
+        where P: Problem<T, F> + Send + Sync + 'a {
+        self.objective = problem.objective();
+        let (get_random_agent, f_fitness, f_mutate, f_offspring) = problem::into_functions(problem);
+        self.get_random_agent = Some(get_random_agent);
+        self.f_fitness = Some(f_fitness);
+        self.f_mutate = Some(f_mutate);
+        self.f_offspring = Some(f_offspring);
+        self
+    }
+
+    /// Creates the Genetic if every function is given and the parameters are valid
+    pub fn build(self) -> Result<Genetic<'a, T, F>, ConfigError>
+        where T: Clone, F: Fitness {
+        let genetic = self.assemble()?;
+        genetic.validate()?;
+
+        Ok(genetic)
+    }
+
+    /// Creates the Genetic if every function is given, without checking the parameters
+    pub(crate) fn assemble(mut self) -> Result<Genetic<'a, T, F>, ConfigError>
+        where T: Clone {
+        // the registered operators replace the functions, they don't have to be given
+        if self.f_mutate.is_none() && !self.mutation_operators.is_empty() {
+            self.f_mutate = Some(Box::new(|a: &T, _: &mut dyn RngCore| a.clone()));
+        }
+        if self.f_offspring.is_none() && !self.crossover_operators.is_empty() {
+            self.f_offspring = Some(Box::new(|a: &T, _: &T, _: &mut dyn RngCore| vec![a.clone()]));
+        }
+
+        #[cfg(feature = "rayon")]
+        let thread_pool = match self.threads {
+            Some(threads) => {
+                let thread_pool = rayon::ThreadPoolBuilder::new()
+                    .num_threads(threads)
+                    .build()
+                    .map_err(|error| ConfigError::InvalidParallelism(error.to_string()))?;
+                Some(Arc::new(thread_pool))
+            }
+            None => self.thread_pool,
+        };
+
+        Ok(Genetic {
+            population: self.population,
+            max_generation: self.max_generation,
+            pc: self.pc,
+            pm: self.pm,
+            get_random_agent: self.get_random_agent.ok_or(ConfigError::MissingFunction("get_random_agent"))?,
+            initial_population: self.initial_population,
+            opposition: self.opposition,
+            f_fitness: self.f_fitness.ok_or(ConfigError::MissingFunction("f_fitness"))?,
+            f_mutate: self.f_mutate.ok_or(ConfigError::MissingFunction("f_mutate"))?,
+            f_offspring: self.f_offspring.ok_or(ConfigError::MissingFunction("f_offspring"))?,
+            fitness_error_policy: self.fitness_error_policy,
+            objective: self.objective,
+            elitism: self.elitism,
+            random_immigrants: self.random_immigrants,
+            selection: self.selection,
+            parent_selection: self.parent_selection,
+            steady_state: self.steady_state,
+            evolution_strategy: self.evolution_strategy,
+            cellular: self.cellular,
+            f_length_penalty: self.f_length_penalty,
+            f_constraints: self.f_constraints,
+            constraint_handling: self.constraint_handling,
+            f_repair: self.f_repair,
+            local_search: self.local_search,
+            f_case_fitness: self.f_case_fitness,
+            pm_adaptation: self.pm_adaptation,
+            diversity_adaptation: self.diversity_adaptation,
+            hypermutation: self.hypermutation,
+            dynamic_environment: self.dynamic_environment,
+            f_distance: self.f_distance,
+            sharing: self.sharing,
+            clearing: self.clearing,
+            speciation: self.speciation,
+            novelty: self.novelty,
+            surrogate: self.surrogate,
+            pc_schedule: self.pc_schedule,
+            pm_schedule: self.pm_schedule,
+            mutation_operators: self.mutation_operators,
+            crossover_operators: self.crossover_operators,
+            operator_selection: self.operator_selection,
+            stagnation: self.stagnation,
+            restart: self.restart,
+            target_fitness: self.target_fitness,
+            max_duration: self.max_duration,
+            max_evaluations: self.max_evaluations,
+            stop_condition: self.stop_condition,
+            on_generation: self.on_generation,
+            on_progress: self.on_progress,
+            cancellation: self.cancellation,
+            run_log: self.run_log,
+            archive: self.archive,
+            f_duplicates: self.f_duplicates,
+            #[cfg(feature = "serde")]
+            checkpoint: self.checkpoint,
+            fitness_cache: self.fitness_cache,
+            #[cfg(feature = "rayon")]
+            thread_pool,
+            #[cfg(feature = "rayon")]
+            chunk_size: self.chunk_size,
+            #[cfg(feature = "async")]
+            f_async_fitness: self.f_async_fitness,
+            #[cfg(feature = "async")]
+            concurrency: self.concurrency,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usize_builder<'a>() -> GeneticBuilder<'a, usize> {
+        GeneticBuilder::new()
+            .population(10)
+            .max_generation(1)
+            .random_agent(|_| 123)
+            .fitness(|_a: &usize| 1.0)
+            .mutate(|a: &usize, _| *a + 1)
+            .offspring(|a: &usize, b: &usize, _| (*a + *b) / 2)
+    }
+
+    #[test]
+    fn valid_config() {
+        let test = usize_builder().pm(1.0).build().unwrap();
+
+        let pop = test.run().population;
+        assert_eq!(pop[0], 124);
+    }
+
+    #[test]
+    fn invalid_config() {
+        assert_eq!(usize_builder().population(1).build().err(), Some(ConfigError::PopulationTooSmall(1)));
+        assert_eq!(usize_builder().max_generation(0).build().err(), Some(ConfigError::ZeroGenerations));
+        assert_eq!(usize_builder().max_evaluations(0).build().err(), Some(ConfigError::ZeroEvaluations));
+        assert_eq!(usize_builder().pc(1.5).build().err(), Some(ConfigError::InvalidCrossoverProbability(1.5)));
+        assert_eq!(usize_builder().pm(-0.1).build().err(), Some(ConfigError::InvalidMutationProbability(-0.1)));
+        assert!(usize_builder().pm(f64::NAN).build().is_err());
+    }
+
+    #[test]
+    fn missing_function() {
+        let test = GeneticBuilder::<usize>::new().random_agent(|_| 1).build();
+        assert_eq!(test.err(), Some(ConfigError::MissingFunction("f_fitness")));
+    }
+}
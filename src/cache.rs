@@ -0,0 +1,170 @@
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hash;
+use std::sync::Mutex;
+
+/// Stores the fitness of agents that were already evaluated, so the fitness function isn't called again
+/// for identical agents (the agents found in the cache don't count as evaluations)
+///
+/// With the rayon feature the agents of a generation are evaluated in parallel, so identical agents created
+/// in the same generation can both be evaluated.
+///
+/// It's shared by the runs of a Genetic (and by the threads running them), so it needs interior mutability.
+pub trait FitnessCache<T, F>: Send + Sync {
+    /// Returns the stored fitness of the agent, if there is one
+    fn get(&self, agent: &T) -> Option<F>;
+    /// Stores the fitness of an agent
+    fn insert(&self, agent: &T, fitness: F);
+    /// Returns the number of hits and misses until now
+    fn stats(&self) -> CacheStats;
+    /// Removes the stored fitness values (e.g. when the fitness function changed in a dynamic environment)
+    fn clear(&self);
+}
+
+/// How many times the fitness of an agent was found in the cache
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CacheStats {
+    /// Number of agents found in the cache
+    pub hits: usize,
+    /// Number of agents that had to be evaluated
+    pub misses: usize,
+    /// Number of agents stored in the cache
+    pub len: usize,
+}
+
+impl CacheStats {
+    /// The ratio of the lookups that were found in the cache (0.0 if there were no lookups)
+    pub fn hit_rate(&self) -> f64 {
+        if self.hits + self.misses == 0 {
+            return 0.0;
+        }
+        self.hits as f64 / (self.hits + self.misses) as f64
+    }
+}
+
+/// Fitness cache that holds at most `capacity` agents, dropping the least recently used one when it's full
+///
+/// # Examples:
+/// ```
+/// use rand::prelude::*;
+/// use bbte_optim_tzim1773_genetic::Genetic;
+///
+/// fn main() {
+///     let test = Genetic::builder()
+///         .max_generation(50)
+///         .random_agent(|rng| rng.gen_range(-50, 50))
+///         .fitness(|a: &i32| {
+///             // an expensive simulation
+///             -(a * a)
+///         })
+///         .mutate(|a: &i32, rng| *a + rng.gen_range(-1, 2))
+///         .offspring(|a: &i32, b: &i32, _rng| (*a + *b) / 2)
+///         .cache(1000)
+///         .build()
+///         .unwrap();
+///
+///     let result = test.run();
+///     let stats = test.cache_stats().unwrap();
+///     println!("{} evaluations, {} hits", result.evaluations, stats.hits);
+/// }
+/// ```
+#[derive(Debug)]
+pub struct LruCache<T, F> {
+    capacity: usize,
+    inner: Mutex<Lru<T, F>>,
+}
+
+#[derive(Debug)]
+struct Lru<T, F> {
+    values: HashMap<T, (F, u64)>,
+    /// The agents by the time of their last use
+    order: BTreeMap<u64, T>,
+    time: u64,
+    hits: usize,
+    misses: usize,
+}
+
+impl<T, F> LruCache<T, F>
+    where T: Hash + Eq {
+    /// Creates a cache that holds at most `capacity` agents (with 0 nothing is stored)
+    pub fn new(capacity: usize) -> Self {
+        let lru = Lru { values: HashMap::new(), order: BTreeMap::new(), time: 0, hits: 0, misses: 0 };
+        LruCache { capacity, inner: Mutex::new(lru) }
+    }
+}
+
+impl<T, F> FitnessCache<T, F> for LruCache<T, F>
+    where T: Hash + Eq + Clone + Send, F: Clone + Send {
+    fn get(&self, agent: &T) -> Option<F> {
+        let mut lru = self.inner.lock().unwrap();
+        let lru = &mut *lru;
+        lru.time += 1;
+        match lru.values.get_mut(agent) {
+            Some((fitness, used)) => {
+                let x = lru.order.remove(used)?;
+                *used = lru.time;
+                lru.order.insert(lru.time, x);
+                lru.hits += 1;
+                Some(fitness.clone())
+            }
+            None => {
+                lru.misses += 1;
+                None
+            }
+        }
+    }
+
+    fn insert(&self, agent: &T, fitness: F) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut lru = self.inner.lock().unwrap();
+        let lru = &mut *lru;
+        lru.time += 1;
+        if let Some((_, used)) = lru.values.insert(agent.clone(), (fitness, lru.time)) {
+            lru.order.remove(&used);
+        }
+        lru.order.insert(lru.time, agent.clone());
+        while lru.values.len() > self.capacity {
+            let oldest = *lru.order.keys().next().unwrap();
+            let x = lru.order.remove(&oldest).unwrap();
+            lru.values.remove(&x);
+        }
+    }
+
+    fn stats(&self) -> CacheStats {
+        let lru = self.inner.lock().unwrap();
+        CacheStats { hits: lru.hits, misses: lru.misses, len: lru.values.len() }
+    }
+
+    fn clear(&self) {
+        let mut lru = self.inner.lock().unwrap();
+        lru.values.clear();
+        lru.order.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn least_recently_used() {
+        let cache = LruCache::new(2);
+        cache.insert(&1, 1.0);
+        cache.insert(&2, 2.0);
+        assert_eq!(cache.get(&1), Some(1.0));
+        // 2 is dropped, 1 was used later
+        cache.insert(&3, 3.0);
+        assert_eq!(cache.get(&2), None);
+        assert_eq!(cache.get(&3), Some(3.0));
+        assert_eq!(cache.get(&1), Some(1.0));
+        assert_eq!(cache.stats(), CacheStats { hits: 3, misses: 1, len: 2 });
+        assert_eq!(cache.stats().hit_rate(), 0.75);
+        cache.clear();
+        assert_eq!(cache.get(&1), None);
+
+        let cache = LruCache::new(0);
+        cache.insert(&1, 1.0);
+        assert_eq!(cache.get(&1), None);
+    }
+}
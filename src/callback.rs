@@ -0,0 +1,102 @@
+use std::ops::ControlFlow;
+
+use crate::{Evaluated, Fitness, Objective};
+
+/// Function called after every generation, the run stops if it returns ControlFlow::Break
+pub type GenerationFn<'a, T, F = f64> = Box<dyn Fn(&GenerationInfo<T, F>) -> ControlFlow<()> + Send + Sync + 'a>;
+
+/// Function called after every generation with the number of generations executed, max_generation and
+/// the best fitness of the generation (e.g. to drive a progress bar)
+pub type ProgressFn<'a, F = f64> = Box<dyn Fn(usize, usize, &F) + Send + Sync + 'a>;
+
+/// The state of a run after a generation, given to the on_generation callback
+///
+/// # Examples:
+/// ```
+/// use rand::prelude::*;
+/// use std::ops::ControlFlow;
+/// use bbte_optim_tzim1773_genetic::{Genetic, Termination};
+///
+/// fn main() {
+///     let test = Genetic::builder()
+///         .max_generation(100)
+///         .random_agent(|rng| rng.gen_range(-5.0, 5.0))
+///         .fitness(|a: &f64| 5.0 - a * a)
+///         .mutate(|a: &f64, rng| *a + rng.gen_range(-0.1, 0.1))
+///         .offspring(|a: &f64, b: &f64, _rng| (*a + *b) / 2.0)
+///         .on_generation(|info| {
+///             println!("{}: {} (mean {})", info.generation, info.best_fitness, info.mean_fitness);
+///             if *info.best_fitness > 4.99 { ControlFlow::Break(()) } else { ControlFlow::Continue(()) }
+///         })
+///         .build()
+///         .unwrap();
+///
+///     let result = test.run();
+///     if result.termination == Termination::Callback {
+///         println!("stopped after {} generations", result.generations);
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct GenerationInfo<'i, T, F = f64> {
+    /// The index of the generation (starting from 0)
+    pub generation: usize,
+    /// The agents of the generation with their fitness
+    pub population: &'i [Evaluated<T, F>],
+    /// The best agent of the generation
+    pub best: &'i T,
+    /// The fitness of the best agent
+    pub best_fitness: &'i F,
+    /// The average fitness of the generation (using Fitness::to_f64())
+    pub mean_fitness: f64,
+    /// The fitness of the worst agent
+    pub worst_fitness: &'i F,
+    /// The number of times the fitness function was called until now
+    pub evaluations: usize,
+}
+
+impl<'i, T, F: Fitness> GenerationInfo<'i, T, F> {
+    /// Collects the statistics of an evaluated, non-empty generation
+    pub(crate) fn new(generation: usize, population: &'i [Evaluated<T, F>], evaluations: usize, objective: Objective) -> Self {
+        let (mut best_i, mut worst_i) = (0, 0);
+        for i in 1..population.len() {
+            if objective.is_better(&population[i].fitness, &population[best_i].fitness) {
+                best_i = i;
+            }
+            if objective.is_better(&population[worst_i].fitness, &population[i].fitness) {
+                worst_i = i;
+            }
+        }
+        let mean_fitness = population.iter().map(|x| x.fitness.to_f64()).sum::<f64>() / population.len() as f64;
+
+        GenerationInfo {
+            generation,
+            population,
+            best: &population[best_i].agent,
+            best_fitness: &population[best_i].fitness,
+            mean_fitness,
+            worst_fitness: &population[worst_i].fitness,
+            evaluations,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn statistics() {
+        let population = ["a", "b", "c", "d"].iter()
+            .zip([2.0, 4.0, 1.0, 3.0].iter())
+            .map(|(x, f_x)| Evaluated::new(*x, *f_x))
+            .collect::<Vec<_>>();
+
+        let info = GenerationInfo::new(3, &population, 12, Objective::Maximize);
+        assert_eq!((info.best, info.best_fitness, info.worst_fitness), (&"b", &4.0, &1.0));
+        assert_eq!(info.mean_fitness, 2.5);
+
+        let info = GenerationInfo::new(3, &population, 12, Objective::Minimize);
+        assert_eq!((info.best, info.best_fitness, info.worst_fitness), (&"c", &1.0, &4.0));
+    }
+}
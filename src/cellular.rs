@@ -0,0 +1,142 @@
+use crate::ConfigError;
+
+/// The cells an agent of the cellular mode can mate with, around its own cell
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Neighborhood {
+    /// The cells at most this many steps away horizontally plus vertically (1: the 4 cells next to it, L5)
+    VonNeumann(usize),
+    /// The cells at most this many steps away in both directions (1: the 8 cells around it, C9)
+    Moore(usize),
+    /// The cells at the given (column, row) offsets
+    Custom(Vec<(isize, isize)>),
+}
+
+impl Default for Neighborhood {
+    fn default() -> Self {
+        Neighborhood::VonNeumann(1)
+    }
+}
+
+impl Neighborhood {
+    /// Returns the (column, row) offsets of the neighbours (without the cell itself)
+    pub fn offsets(&self) -> Vec<(isize, isize)> {
+        let square = |radius: usize| {
+            let r = radius as isize;
+            (-r..=r).flat_map(move |dy| (-r..=r).map(move |dx| (dx, dy)))
+        };
+        match self {
+            Neighborhood::VonNeumann(radius) => square(*radius)
+                .filter(|(dx, dy)| (dx.abs() + dy.abs()) as usize <= *radius && (*dx, *dy) != (0, 0))
+                .collect(),
+            Neighborhood::Moore(radius) => square(*radius).filter(|offset| *offset != (0, 0)).collect(),
+            Neighborhood::Custom(offsets) => offsets.iter().copied().filter(|offset| *offset != (0, 0)).collect(),
+        }
+    }
+}
+
+/// Settings of the cellular mode: the agents live on a `width` x `height` grid (whose edges wrap around),
+/// and every agent mates only with one of its neighbours
+///
+/// In every generation each agent creates one offspring with a neighbour (chosen with parent_selection
+/// among the neighbours, or with a binary tournament), which takes its place if it's not worse.
+/// Good agents spread slowly across the grid, so the population keeps its diversity longer, which helps
+/// on deceptive problems. The population is width * height, the agents are kept in the order of the cells
+/// (row by row), selection and elitism are not used.
+///
+/// # Examples:
+/// ```
+/// use rand::prelude::*;
+/// use bbte_optim_tzim1773_genetic::{Cellular, Genetic, Neighborhood};
+///
+/// fn main() {
+///     let test = Genetic::builder()
+///         .population(64)
+///         .max_generation(50)
+///         .random_agent(|rng| rng.gen_range(-5.0, 5.0))
+///         .fitness(|a: &f64| 5.0 - a * a)
+///         .mutate(|a: &f64, rng| *a + rng.gen_range(-0.1, 0.1))
+///         .offspring(|a: &f64, b: &f64, _rng| (*a + *b) / 2.0)
+///         .cellular(Cellular { width: 8, height: 8, neighborhood: Neighborhood::Moore(1) })
+///         .build()
+///         .unwrap();
+///
+///     println!("{}", test.run().best); // should be a number close to 0
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cellular {
+    /// Number of columns of the grid (at least 1)
+    pub width: usize,
+    /// Number of rows of the grid (at least 1)
+    pub height: usize,
+    /// The cells an agent can mate with
+    pub neighborhood: Neighborhood,
+}
+
+impl Cellular {
+    /// Grid with the von Neumann neighbourhood of radius 1
+    pub fn new(width: usize, height: usize) -> Self {
+        Cellular { width, height, neighborhood: Neighborhood::default() }
+    }
+
+    /// Returns the indices of the neighbours of the cell (without the cell itself, every cell once)
+    pub fn neighbors(&self, cell: usize) -> Vec<usize> {
+        let (x, y) = ((cell % self.width) as isize, (cell / self.width) as isize);
+        let mut neighbors = Vec::new();
+        for (dx, dy) in self.neighborhood.offsets() {
+            let nx = (x + dx).rem_euclid(self.width as isize) as usize;
+            let ny = (y + dy).rem_euclid(self.height as isize) as usize;
+            let neighbor = ny * self.width + nx;
+            // the edges of small grids wrap around to the same cells
+            if neighbor != cell && !neighbors.contains(&neighbor) {
+                neighbors.push(neighbor);
+            }
+        }
+
+        neighbors
+    }
+
+    /// Checks whether the grid holds the given population and every cell has a neighbour
+    pub fn validate(&self, population: usize) -> Result<(), ConfigError> {
+        if self.width * self.height != population {
+            return Err(ConfigError::InvalidCellular(format!("the grid has {} cells, the population is {}", self.width * self.height, population)));
+        }
+        if self.neighborhood.offsets().is_empty() || self.neighbors(0).is_empty() {
+            return Err(ConfigError::InvalidCellular("the cells have no neighbours".to_string()));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn neighbors() {
+        let grid = Cellular::new(4, 3);
+        let mut neighbors = grid.neighbors(5);
+        neighbors.sort_unstable();
+        assert_eq!(neighbors, vec![1, 4, 6, 9]);
+        // the edges wrap around
+        let mut neighbors = grid.neighbors(0);
+        neighbors.sort_unstable();
+        assert_eq!(neighbors, vec![1, 3, 4, 8]);
+
+        let moore = Cellular { width: 4, height: 3, neighborhood: Neighborhood::Moore(1) };
+        assert_eq!(moore.neighbors(5).len(), 8);
+        assert_eq!(Neighborhood::VonNeumann(2).offsets().len(), 12);
+        assert_eq!(Neighborhood::Moore(2).offsets().len(), 24);
+        // the cells of a 2 x 1 grid are each other's only neighbours
+        assert_eq!(Cellular { width: 2, height: 1, neighborhood: Neighborhood::Moore(1) }.neighbors(0), vec![1]);
+    }
+
+    #[test]
+    fn invalid_cellular() {
+        assert!(Cellular::new(4, 3).validate(12).is_ok());
+        assert!(Cellular::new(4, 3).validate(10).is_err());
+        assert!(Cellular { width: 4, height: 3, neighborhood: Neighborhood::Custom(vec![(0, 0)]) }.validate(12).is_err());
+        assert!(Cellular { width: 1, height: 1, neighborhood: Neighborhood::Moore(1) }.validate(1).is_err());
+    }
+}
@@ -0,0 +1,303 @@
+use rand::prelude::*;
+use rand_chacha::ChaCha20Rng;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::adaptation::Rates;
+use crate::state::RunRng;
+use crate::termination::Progress;
+use crate::{CheckpointError, ConfigError, Evaluated, Fitness, GenerationStats, Genetic, GeneticState, MaybeSync, RunError, RunResult, Species};
+
+/// Function that writes a checkpoint to a file
+type WriteFn<T, F> = fn(&Checkpoint<T, F>, &Path) -> Result<(), CheckpointError>;
+
+/// Settings of the checkpoints of a run: every `interval` generations (and at the end of the run) the state of
+/// the run is written to the file at `path`, so the run can be continued with resume_from() after a crash
+///
+/// The checkpoint holds the population with the fitness values, the generation, the number of evaluations, the
+/// (adapted) probabilities, the statistics and the state of the random number generator, so the resumed run goes
+/// on exactly as the interrupted one would have (a run with checkpoints draws its random numbers from a ChaCha20
+/// generator seeded once from the generator given to the run). The functions of the Genetic can't be saved, so the
+/// run has to be resumed with a Genetic built the same way.
+///
+/// # Examples:
+/// ```no_run
+/// use rand::prelude::*;
+/// use bbte_optim_tzim1773_genetic::Genetic;
+///
+/// fn main() {
+///     let test = Genetic::builder()
+///         .max_generation(100000)
+///         .random_agent(|rng| rng.gen_range(-5.0, 5.0))
+///         .fitness(|a: &f64| 5.0 - a * a)
+///         .mutate(|a: &f64, rng| *a + rng.gen_range(-0.01, 0.01))
+///         .offspring(|a: &f64, b: &f64, _rng| (*a + *b) / 2.0)
+///         .checkpoint("run.checkpoint", 100)
+///         .build()
+///         .unwrap();
+///
+///     // continues the interrupted run if there's a checkpoint
+///     let result = match test.resume_from("run.checkpoint") {
+///         Ok(result) => result,
+///         Err(_) => test.run(),
+///     };
+///     println!("{}", result.best);
+/// }
+/// ```
+pub struct Checkpointing<T, F> {
+    /// The file the checkpoints are written to (the previous checkpoint is replaced)
+    pub path: PathBuf,
+    /// The number of generations between two checkpoints (at least 1)
+    pub interval: usize,
+    f_write: WriteFn<T, F>,
+}
+
+impl<T, F> Checkpointing<T, F> {
+    /// Writes the checkpoints to the given file every `interval` generations
+    pub fn new(path: impl Into<PathBuf>, interval: usize) -> Self
+        where T: Serialize, F: Serialize {
+        Checkpointing { path: path.into(), interval, f_write: write_checkpoint::<T, F> }
+    }
+
+    /// Checks whether the interval is usable
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.interval == 0 {
+            return Err(ConfigError::InvalidCheckpoint("the interval must be at least 1".to_string()));
+        }
+
+        Ok(())
+    }
+}
+
+/// The random number generator of a run with checkpoints, seeded from the generator given to the run
+#[derive(Debug, Clone)]
+pub(crate) struct SavedRng {
+    seed: [u8; 32],
+    pub(crate) rng: ChaCha20Rng,
+}
+
+impl SavedRng {
+    /// Seeds the generator from the given one
+    pub fn new(rng: &mut impl RngCore) -> Self {
+        let mut seed = [0; 32];
+        rng.fill_bytes(&mut seed);
+        SavedRng { seed, rng: ChaCha20Rng::from_seed(seed) }
+    }
+
+    /// Returns the seed and the position of the generator (without drawing from it)
+    fn position(&self) -> RngPosition {
+        RngPosition { seed: self.seed, word_pos: self.rng.get_word_pos() }
+    }
+
+    /// Continues the generator from the saved position
+    fn restore(position: RngPosition) -> Self {
+        let mut rng = ChaCha20Rng::from_seed(position.seed);
+        rng.set_word_pos(position.word_pos);
+        SavedRng { seed: position.seed, rng }
+    }
+}
+
+/// The saved state of the random number generator of a run
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+struct RngPosition {
+    seed: [u8; 32],
+    word_pos: u128,
+}
+
+/// The parameters a checkpoint was created with
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+struct Parameters {
+    population: usize,
+    max_generation: usize,
+}
+
+/// The saved state of a run
+#[derive(Serialize, Deserialize)]
+struct Checkpoint<T, F> {
+    parameters: Parameters,
+    population: Vec<Evaluated<T, F>>,
+    generation: usize,
+    evaluations: usize,
+    rates: Rates,
+    best_history: Vec<f64>,
+    elapsed: Duration,
+    history: Vec<GenerationStats>,
+    archive: Vec<Evaluated<T, F>>,
+    species: Vec<Species<T, F>>,
+    novelty_archive: Vec<Vec<f64>>,
+    restarts: usize,
+    last_restart: usize,
+    sentinels: Vec<Evaluated<T, F>>,
+    optima: Vec<T>,
+    changes: usize,
+    surrogate_points: Vec<(Vec<f64>, f64)>,
+    rng: RngPosition,
+}
+
+/// Writes the checkpoint next to the file first, so a crash while it's written doesn't destroy the previous one
+fn write_checkpoint<T: Serialize, F: Serialize>(checkpoint: &Checkpoint<T, F>, path: &Path) -> Result<(), CheckpointError> {
+    let mut temporary = path.as_os_str().to_owned();
+    temporary.push(".tmp");
+    let mut file = BufWriter::new(File::create(&temporary)?);
+    bincode::serialize_into(&mut file, checkpoint)?;
+    file.flush()?;
+    file.get_ref().sync_all()?;
+    fs::rename(&temporary, path)?;
+
+    Ok(())
+}
+
+impl<'a, T, F> Genetic<'a, T, F>
+    where T: Clone + MaybeSync, F: Fitness + MaybeSync {
+    /// Writes the checkpoint of the run if it's due (every interval generations and at the end of the run)
+    pub(crate) fn checkpoint<R>(&self, state: &GeneticState<T, F, R>) -> Result<(), CheckpointError> {
+        let checkpointing = match &self.checkpoint {
+            Some(checkpointing) if state.generation.is_multiple_of(checkpointing.interval) || state.termination.is_some() => checkpointing,
+            _ => return Ok(()),
+        };
+        let rng = match &state.rng.saved {
+            Some(saved) => saved.position(),
+            None => return Err(CheckpointError::Mismatch("the run was started by a Genetic without checkpoints".to_string())),
+        };
+        let checkpoint = Checkpoint {
+            parameters: self.parameters(),
+            population: state.population.clone(),
+            generation: state.generation,
+            evaluations: state.evaluations,
+            rates: state.rates.clone(),
+            best_history: state.progress.history().to_vec(),
+            elapsed: state.progress.elapsed(),
+            history: state.history.clone(),
+            archive: state.archive.clone(),
+            species: state.species.clone(),
+            novelty_archive: state.novelty_archive.clone(),
+            restarts: state.restarts,
+            last_restart: state.last_restart,
+            sentinels: state.sentinels.clone(),
+            optima: state.optima.clone(),
+            changes: state.changes,
+            surrogate_points: state.surrogate_points.clone(),
+            rng,
+        };
+
+        (checkpointing.f_write)(&checkpoint, &checkpointing.path)
+    }
+
+    /// Reads the state of a run from a checkpoint, it can be continued with step()
+    ///
+    /// # Arguments:
+    ///
+    /// * `path` the file written by the checkpoints of a run of a Genetic built the same way
+    ///
+    /// # Errors:
+    ///  If the file can't be read, it's not a checkpoint of these agents or it was created with a different population
+    pub fn load_checkpoint(&self, path: impl AsRef<Path>) -> Result<GeneticState<T, F>, CheckpointError>
+        where T: DeserializeOwned, F: DeserializeOwned {
+        let file = BufReader::new(File::open(path)?);
+        let checkpoint: Checkpoint<T, F> = bincode::deserialize_from(file)?;
+        if checkpoint.parameters.population != self.population {
+            return Err(CheckpointError::Mismatch(format!("the checkpoint has a population of {}, the Genetic has {}", checkpoint.parameters.population, self.population)));
+        }
+
+        Ok(GeneticState {
+            population: checkpoint.population,
+            generation: checkpoint.generation,
+            evaluations: checkpoint.evaluations,
+            rates: checkpoint.rates,
+            progress: Progress::resume(checkpoint.best_history, checkpoint.elapsed),
+            termination: None,
+            history: checkpoint.history,
+            archive: checkpoint.archive,
+            species: checkpoint.species,
+            novelty_archive: checkpoint.novelty_archive,
+            restarts: checkpoint.restarts,
+            last_restart: checkpoint.last_restart,
+            sentinels: checkpoint.sentinels,
+            optima: checkpoint.optima,
+            changes: checkpoint.changes,
+            surrogate_points: checkpoint.surrogate_points,
+            // the generator given to the run isn't used after the start, only the saved one
+            rng: RunRng { rng: StdRng::from_seed(checkpoint.rng.seed), saved: Some(SavedRng::restore(checkpoint.rng)) },
+        })
+    }
+
+    /// Continues the run saved in a checkpoint until it stops (max_generation or the other stopping criteria
+    /// of this Genetic, so a finished run can be extended)
+    ///
+    /// # Arguments:
+    ///
+    /// * `path` the file written by the checkpoints of a run of a Genetic built the same way
+    ///
+    /// # Errors:
+    ///  If the checkpoint can't be loaded (RunError::Checkpoint), or the fitness function fails
+    pub fn resume_from(&self, path: impl AsRef<Path>) -> Result<RunResult<T, F>, RunError>
+        where T: DeserializeOwned, F: DeserializeOwned {
+        let mut state = self.load_checkpoint(path)?;
+        while self.step(&mut state)?.is_none() {}
+
+        Ok(self.finish(state))
+    }
+
+    /// The parameters that have to match when a run is resumed
+    fn parameters(&self) -> Parameters {
+        Parameters { population: self.population, max_generation: self.max_generation }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn genetic<'a>(max_generation: usize, interval: usize, path: &Path) -> Genetic<'a, f64> {
+        Genetic::builder()
+            .population(20)
+            .max_generation(max_generation)
+            .random_agent(|rng| rng.gen_range(-5.0, 5.0))
+            .fitness(|a: &f64| if a.abs() < 4.0 { 5.0 - a * a } else { f64::NEG_INFINITY })
+            .mutate(|a: &f64, rng| *a + rng.gen_range(-0.1, 0.1))
+            .offspring(|a: &f64, b: &f64, _rng| (*a + *b) / 2.0)
+            .checkpoint(path, interval)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn resume_from() {
+        let path = std::env::temp_dir().join(format!("genetic-{}.checkpoint", std::process::id()));
+        let first = genetic(25, 10, &path).run_with_seed(5);
+        let state = genetic(25, 10, &path).load_checkpoint(&path).unwrap();
+        // the last checkpoint is written at the end of the run
+        assert_eq!(state.generation(), 25);
+        assert_eq!(state.evaluations(), first.evaluations);
+        assert_eq!(state.history(), &first.history[..]);
+
+        let resumed = genetic(50, 10, &path).resume_from(&path).unwrap();
+        assert_eq!(resumed.generations, 50);
+        assert_eq!(resumed.history[..25], first.history[..]);
+        assert!(resumed.evaluations > first.evaluations);
+        assert!((resumed.best).abs() < 0.1);
+
+        // writing the checkpoints doesn't draw random numbers, and the resumed run goes on as the uninterrupted one
+        let other_path = std::env::temp_dir().join(format!("genetic-{}-other.checkpoint", std::process::id()));
+        let uninterrupted = genetic(50, 7, &other_path).run_with_seed(5);
+        assert_eq!(uninterrupted.history, resumed.history);
+        assert_eq!((uninterrupted.best, uninterrupted.evaluations), (resumed.best, resumed.evaluations));
+        fs::remove_file(&other_path).unwrap();
+
+        let bigger = Genetic::builder()
+            .population(30)
+            .random_agent(|rng| rng.gen_range(-5.0, 5.0))
+            .fitness(|a: &f64| 5.0 - a * a)
+            .mutate(|a: &f64, _rng| *a)
+            .offspring(|a: &f64, _b: &f64, _rng| *a)
+            .build()
+            .unwrap();
+        assert!(matches!(bigger.load_checkpoint(&path), Err(CheckpointError::Mismatch(_))));
+        fs::remove_file(&path).unwrap();
+        assert!(matches!(bigger.load_checkpoint(&path), Err(CheckpointError::Io(_))));
+    }
+}
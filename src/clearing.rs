@@ -0,0 +1,130 @@
+use crate::{ConfigError, Fitness, Objective};
+
+/// Clearing (niching): only the best `capacity` agents of every niche (the agents closer than `radius` to the
+/// niche's best agent by f_distance) keep their fitness, the fitness of the others is cleared to the worst value
+/// before selection, so the population spreads over several optima of a multimodal function
+///
+/// The niches are formed greedily: the best agent not cleared yet becomes the centre of a new niche. Clearing is
+/// stronger than fitness sharing (the cleared agents are rarely selected) and works with any fitness type. It's used
+/// by the selection strategies (the result still holds the raw fitness), it needs f_distance and at most n * n
+/// distances per selection.
+///
+/// # Examples:
+/// ```
+/// use rand::prelude::*;
+/// use bbte_optim_tzim1773_genetic::{Clearing, Genetic, Selection};
+///
+/// fn main() {
+///     // two peaks of the same height at -2 and 2
+///     let test = Genetic::builder()
+///         .max_generation(100)
+///         .random_agent(|rng| rng.gen_range(-3.0, 3.0))
+///         .fitness(|a: &f64| (4.0 - (a * a - 4.0).abs()).max(0.0))
+///         .mutate(|a: &f64, rng| *a + rng.gen_range(-0.05, 0.05))
+///         .offspring(|a: &f64, _b: &f64, _rng| *a)
+///         .selection(Selection::tournament(2))
+///         .distance(|a: &f64, b: &f64| (a - b).abs())
+///         .clearing(Clearing::new(1.0, 10))
+///         .build()
+///         .unwrap();
+///
+///     let result = test.run();
+///     println!("{:?}", result.population); // agents around both peaks
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Clearing {
+    /// The radius of a niche (bigger than 0.0)
+    pub radius: f64,
+    /// The number of agents of a niche that keep their fitness (at least 1)
+    pub capacity: usize,
+}
+
+impl Clearing {
+    /// Keeps the best `capacity` agents in every niche of the given radius
+    pub fn new(radius: f64, capacity: usize) -> Self {
+        Clearing { radius, capacity }
+    }
+
+    /// Checks whether the parameters are usable
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if !(self.radius > 0.0 && self.radius.is_finite()) {
+            return Err(ConfigError::InvalidClearing(format!("radius must be a positive number, got {}", self.radius)));
+        }
+        if self.capacity == 0 {
+            return Err(ConfigError::InvalidClearing("capacity must be at least 1".to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// Clears the fitness of the agents that don't fit into the capacity of their niche
+    pub(crate) fn clear<F: Fitness>(&self, fitness: &mut [F], distance: impl Fn(usize, usize) -> f64, objective: Objective) {
+        let mut order = (0..fitness.len()).collect::<Vec<usize>>();
+        order.sort_by(|a, b| objective.compare(&fitness[*a], &fitness[*b]));
+        let mut cleared = vec![false; fitness.len()];
+        for (k, &centre) in order.iter().enumerate() {
+            if cleared[centre] {
+                continue;
+            }
+            let mut winners = 1;
+            for &other in &order[k + 1..] {
+                if cleared[other] || distance(centre, other) >= self.radius {
+                    continue;
+                }
+                if winners < self.capacity {
+                    winners += 1;
+                } else {
+                    cleared[other] = true;
+                }
+            }
+        }
+        for (f, _) in fitness.iter_mut().zip(cleared).filter(|(_, cleared)| *cleared) {
+            *f = objective.worst();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Genetic, Selection};
+    use rand::Rng;
+
+    #[test]
+    fn clear() {
+        let positions = [0.0, 0.5, 5.0, 0.7, 5.2, 10.0];
+        let distance = |i: usize, j: usize| f64::abs(positions[i] - positions[j]);
+        let mut fitness = vec![6, 5, 4, 3, 2, 1];
+
+        // the niches are {0, 1, 3}, {2, 4} and {5}
+        Clearing::new(1.0, 2).clear(&mut fitness, distance, Objective::Maximize);
+        assert_eq!(fitness, vec![6, 5, 4, i32::MIN, 2, 1]);
+        let mut fitness = vec![6.0, 5.0, 4.0, 3.0, 2.0, 1.0];
+        Clearing::new(1.0, 1).clear(&mut fitness, distance, Objective::Minimize);
+        assert_eq!(fitness, vec![f64::INFINITY, f64::INFINITY, f64::INFINITY, 3.0, 2.0, 1.0]);
+        assert!(Clearing::new(1.0, 0).validate().is_err());
+    }
+
+    #[test]
+    fn niches() {
+        // two peaks of the same height at -2 and 2
+        let test = Genetic::builder()
+            .population(40)
+            .max_generation(100)
+            .random_agent(|rng| rng.gen_range(-3.0, 3.0))
+            .fitness(|a: &f64| (4.0 - (a * a - 4.0).abs()).max(0.0))
+            .mutate(|a: &f64, rng| *a + rng.gen_range(-0.05, 0.05))
+            .offspring(|a: &f64, _b: &f64, _rng| *a)
+            .selection(Selection::tournament(2))
+            .distance(|a: &f64, b: &f64| (a - b).abs())
+            .clearing(Clearing::new(1.0, 10))
+            .build()
+            .unwrap();
+
+        let population = test.run_with_seed(1).population;
+        let left = population.iter().filter(|a| (**a + 2.0).abs() < 0.5).count();
+        let right = population.iter().filter(|a| (**a - 2.0).abs() < 0.5).count();
+        assert!(left >= 5 && right >= 5);
+    }
+}
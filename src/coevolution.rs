@@ -0,0 +1,361 @@
+use rand::prelude::*;
+use std::collections::VecDeque;
+use std::time::Instant;
+
+use crate::{ConfigError, Evaluated, Genetic, GeneticBuilder, GeneticState, MaybeSync, RunError, RunResult};
+
+/// Function that returns the score of an agent of the given population against an agent of another population
+pub type CompetitionFn<'a, T> = Box<dyn Fn(usize, &T, &T) -> f64 + Send + Sync + 'a>;
+/// Function that returns the score of a team (one agent from every population, in the order of the populations)
+pub type TeamFn<'a, T> = Box<dyn Fn(&[&T]) -> f64 + Send + Sync + 'a>;
+
+/// The way the fitness of a coevolving agent is measured
+pub enum Interaction<'a, T> {
+    /// The agents play against the opponents sampled from the other populations (and from their hall of fame),
+    /// the fitness is the average score
+    Competitive(CompetitionFn<'a, T>),
+    /// The agents form teams with collaborators sampled from the other populations, the fitness is the score of
+    /// the best team the agent was part of
+    Cooperative(TeamFn<'a, T>),
+}
+
+/// The way the opponents (or collaborators) of an agent are chosen from another population
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sampling {
+    /// This many random agents of the population
+    Random(usize),
+    /// The best agents of the population (according to their last fitness)
+    Best(usize),
+    /// Every agent of the population
+    All,
+}
+
+impl Default for Sampling {
+    fn default() -> Self {
+        Sampling::Random(5)
+    }
+}
+
+impl Sampling {
+    /// Returns the indices of the chosen agents
+    fn choose<T>(&self, xg: &[Evaluated<T>], genetic: &Genetic<T>, rng: &mut dyn RngCore) -> Vec<usize> {
+        match self {
+            Sampling::Random(count) => (0..*count).map(|_| rng.gen_range(0, xg.len())).collect(),
+            Sampling::Best(count) => {
+                let mut order = (0..xg.len()).collect::<Vec<usize>>();
+                order.sort_by(|a, b| genetic.objective.compare(&xg[*a].fitness, &xg[*b].fitness));
+                order.truncate(*count);
+                order
+            }
+            Sampling::All => (0..xg.len()).collect(),
+        }
+    }
+
+    /// Checks whether the parameters are usable
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        match self {
+            Sampling::Random(0) | Sampling::Best(0) => Err(ConfigError::InvalidCoevolution("at least one opponent has to be sampled".to_string())),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Archive of the best agents of the past generations of every population, the competitive agents also play
+/// against `samples` random members of the halls of the other populations (so they don't forget how to beat
+/// earlier opponents)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HallOfFame {
+    /// Number of the last generations whose best agent is kept (at least 1)
+    pub capacity: usize,
+    /// Number of opponents sampled from the hall of every other population (at least 1)
+    pub samples: usize,
+}
+
+impl HallOfFame {
+    /// Checks whether the parameters are usable
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.capacity == 0 || self.samples == 0 {
+            return Err(ConfigError::InvalidCoevolution("the capacity and the samples of the hall of fame must be at least 1".to_string()));
+        }
+
+        Ok(())
+    }
+}
+
+/// Evolves two or more populations whose fitness depends on each other, e.g. strategies against test cases
+/// (competitive) or the parts of a solution (cooperative)
+///
+/// The populations are Genetics given by their builders (without fitness functions: the fitness comes from the
+/// interaction). They can have different parameters, but their agents have the same type (use an enum or a struct
+/// if they differ). In every generation every population creates its offspring, then every agent (the old ones too)
+/// is evaluated against the previous generation of the other populations. Each population stops according to its own
+/// stopping criteria (and keeps serving as opponents), the run is over when all of them stopped.
+///
+/// The fitness changes as the other populations evolve, so pm_adaptation, operator_selection and fitness_cache
+/// can't be used by the populations.
+///
+/// # Examples:
+/// ```
+/// use rand::prelude::*;
+/// use bbte_optim_tzim1773_genetic::{Coevolution, Genetic, GeneticBuilder, HallOfFame, Interaction, Sampling};
+///
+/// fn main() {
+///     let population = || -> GeneticBuilder<f64> {
+///         Genetic::builder()
+///             .population(20)
+///             .max_generation(50)
+///             .random_agent(|rng| rng.gen_range(-5.0, 5.0))
+///             .mutate(|a: &f64, rng| (*a + rng.gen_range(-0.5, 0.5)).clamp(-5.0, 5.0))
+///             .offspring(|a: &f64, b: &f64, _rng| (*a + *b) / 2.0)
+///     };
+///
+///     // both populations try to be bigger than their opponents
+///     let arms_race = Interaction::Competitive(Box::new(|_population, a: &f64, opponent: &f64| if a > opponent { 1.0 } else { 0.0 }));
+///     let hall_of_fame = HallOfFame { capacity: 10, samples: 2 };
+///     let model = Coevolution::new(vec![population(), population()], arms_race, Sampling::Random(5), Some(hall_of_fame)).unwrap();
+///
+///     let result = model.run();
+///     println!("{} {}", result.populations[0].best, result.populations[1].best); // should be numbers close to 5
+/// }
+/// ```
+pub struct Coevolution<'a, T> {
+    /// The populations (at least two)
+    pub populations: Vec<Genetic<'a, T>>,
+    /// The way the fitness is measured
+    pub interaction: Interaction<'a, T>,
+    /// The way the opponents (or collaborators) are chosen from the other populations
+    pub sampling: Sampling,
+    /// If it's given, the best agents of the past generations are kept and played against (competitive only)
+    pub hall_of_fame: Option<HallOfFame>,
+}
+
+/// The outcome of a coevolution run
+#[derive(Debug, Clone, PartialEq)]
+pub struct CoevolutionResult<T> {
+    /// The results of the populations (the fitness is the one measured in their last generation)
+    pub populations: Vec<RunResult<T>>,
+    /// The hall of fame of every population, the oldest agent first (empty if there's no hall of fame)
+    pub hall_of_fame: Vec<Vec<T>>,
+    /// The number of generations executed until the last population stopped
+    pub generations: usize,
+}
+
+impl<'a, T> Coevolution<'a, T>
+    where T: Clone + MaybeSync {
+    /// Creates a coevolution from the builders of the populations, checking their parameters
+    pub fn new(populations: Vec<GeneticBuilder<'a, T>>, interaction: Interaction<'a, T>, sampling: Sampling, hall_of_fame: Option<HallOfFame>) -> Result<Self, ConfigError> {
+        let populations = populations.into_iter()
+            .map(|builder| builder.try_fitness(|_: &T| Err("the fitness of coevolving agents depends on the other populations")).build())
+            .collect::<Result<Vec<Genetic<'a, T>>, ConfigError>>()?;
+        let model = Coevolution { populations, interaction, sampling, hall_of_fame };
+        model.validate()?;
+
+        Ok(model)
+    }
+
+    /// Checks whether there are at least two populations and none of them needs a fixed fitness
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.populations.len() < 2 {
+            return Err(ConfigError::InvalidCoevolution("at least two populations are needed".to_string()));
+        }
+        let fixed_fitness = self.populations.iter()
+            .any(|genetic| genetic.pm_adaptation.is_some() || genetic.operator_selection.is_some() || genetic.fitness_cache.is_some());
+        if fixed_fitness {
+            return Err(ConfigError::InvalidCoevolution("pm_adaptation, operator_selection and fitness_cache can't be used".to_string()));
+        }
+        self.sampling.validate()?;
+        if let Some(hall_of_fame) = &self.hall_of_fame {
+            hall_of_fame.validate()?;
+        }
+
+        Ok(())
+    }
+
+    /// Evolves the populations until all of them stop
+    ///
+    /// # Panics:
+    ///  If the populations can't create their offspring (use try_run() to get the error instead)
+    pub fn run(&self) -> CoevolutionResult<T> {
+        self.run_with_rng(&mut thread_rng())
+    }
+
+    /// Same as run(), but returns the error instead of panicking
+    pub fn try_run(&self) -> Result<CoevolutionResult<T>, RunError> {
+        self.try_run_with_rng(&mut thread_rng())
+    }
+
+    /// Same as run(), but every random decision is made with the given random number generator
+    ///
+    /// # Arguments:
+    ///
+    /// * `rng` the random number generator seeding the populations and sampling the opponents
+    pub fn run_with_rng<R: Rng>(&self, rng: &mut R) -> CoevolutionResult<T> {
+        match self.try_run_with_rng(rng) {
+            Ok(result) => result,
+            Err(error) => panic!("{}", error),
+        }
+    }
+
+    /// Same as run(), but the random number generator is seeded with the given seed
+    pub fn run_with_seed(&self, seed: u64) -> CoevolutionResult<T> {
+        self.run_with_rng(&mut StdRng::seed_from_u64(seed))
+    }
+
+    /// Same as run_with_rng(), but returns the error instead of panicking
+    ///
+    /// # Arguments:
+    ///
+    /// * `rng` the random number generator seeding the populations and sampling the opponents
+    pub fn try_run_with_rng<R: Rng>(&self, rng: &mut R) -> Result<CoevolutionResult<T>, RunError> {
+        let start = Instant::now();
+        let mut halls = vec![VecDeque::new(); self.populations.len()];
+        let first = self.populations.iter()
+            .map(|genetic| genetic.get_population(rng).into_iter().map(|agent| Evaluated { agent, fitness: 0.0 }).collect())
+            .collect::<Vec<Vec<Evaluated<T>>>>();
+        // the first generations are evaluated against each other's random agents
+        let mut states = Vec::with_capacity(first.len());
+        for (p, genetic) in self.populations.iter().enumerate() {
+            let mut evaluations = 0;
+            let agents = first[p].iter().map(|x| x.agent.clone()).collect();
+            let population = self.evaluate(p, agents, &first, &halls, &mut evaluations, rng);
+            states.push(genetic.initial_state(population, evaluations, start, StdRng::seed_from_u64(rng.gen())));
+        }
+        self.record(&mut halls, &states);
+
+        let mut generations = 0;
+        loop {
+            let opponents = states.iter().map(|state| state.population.clone()).collect::<Vec<Vec<Evaluated<T>>>>();
+            let mut running = false;
+            for (p, (genetic, state)) in self.populations.iter().zip(states.iter_mut()).enumerate() {
+                if genetic.check_bound(state).is_some() {
+                    continue;
+                }
+                running = true;
+                // the old agents are measured against the current opponents too
+                let agents = state.population.drain(..).map(|x| x.agent).collect();
+                state.population = self.evaluate(p, agents, &opponents, &halls, &mut state.evaluations, rng);
+                let candidates = genetic.offspring(state).map(|offspring| {
+                    let agents = offspring.into_iter().map(|(agent, _)| agent).collect();
+                    (self.evaluate(p, agents, &opponents, &halls, &mut state.evaluations, rng), false)
+                });
+                genetic.advance(state, candidates)?;
+            }
+            if !running {
+                break;
+            }
+            generations += 1;
+            self.record(&mut halls, &states);
+        }
+
+        Ok(CoevolutionResult {
+            populations: self.populations.iter().zip(states).map(|(genetic, state)| genetic.finish(state)).collect(),
+            hall_of_fame: halls.into_iter().map(Vec::from).collect(),
+            generations,
+        })
+    }
+
+    /// Measures the fitness of the agents of population `p` against the other populations
+    fn evaluate(&self, p: usize, agents: Vec<T>, opponents: &[Vec<Evaluated<T>>], halls: &[VecDeque<T>], evaluations: &mut usize, rng: &mut dyn RngCore) -> Vec<Evaluated<T>> {
+        agents.into_iter()
+            .map(|agent| {
+                let fitness = match &self.interaction {
+                    Interaction::Competitive(f_competition) => {
+                        let mut scores = Vec::new();
+                        for q in (0..opponents.len()).filter(|q| *q != p) {
+                            for i in self.sampling.choose(&opponents[q], &self.populations[q], rng) {
+                                scores.push(f_competition(p, &agent, &opponents[q][i].agent));
+                            }
+                            if let (Some(hall_of_fame), false) = (&self.hall_of_fame, halls[q].is_empty()) {
+                                for _ in 0..hall_of_fame.samples {
+                                    scores.push(f_competition(p, &agent, &halls[q][rng.gen_range(0, halls[q].len())]));
+                                }
+                            }
+                        }
+                        *evaluations += scores.len();
+                        scores.iter().sum::<f64>() / scores.len() as f64
+                    }
+                    Interaction::Cooperative(f_team) => {
+                        let collaborators = (0..opponents.len())
+                            .map(|q| if q == p { Vec::new() } else { self.sampling.choose(&opponents[q], &self.populations[q], rng) })
+                            .collect::<Vec<Vec<usize>>>();
+                        let teams = collaborators.iter().filter(|c| !c.is_empty()).map(Vec::len).max().unwrap_or(0);
+                        let mut best = f64::NEG_INFINITY;
+                        for k in 0..teams {
+                            let team = (0..opponents.len())
+                                .map(|q| if q == p { &agent } else { &opponents[q][collaborators[q][k % collaborators[q].len()]].agent })
+                                .collect::<Vec<&T>>();
+                            let score = f_team(&team);
+                            if best == f64::NEG_INFINITY || self.populations[p].objective.is_better(&score, &best) {
+                                best = score;
+                            }
+                        }
+                        *evaluations += teams;
+                        best
+                    }
+                };
+                Evaluated { agent, fitness }
+            })
+            .collect()
+    }
+
+    /// Puts the best agent of every population into its hall of fame (dropping the oldest one if it's full)
+    fn record<R>(&self, halls: &mut [VecDeque<T>], states: &[GeneticState<T, f64, R>]) {
+        let hall_of_fame = match &self.hall_of_fame {
+            Some(hall_of_fame) => hall_of_fame,
+            None => return,
+        };
+        for ((hall, genetic), state) in halls.iter_mut().zip(self.populations.iter()).zip(states) {
+            hall.push_back(state.population[genetic.best_index(&state.population)].agent.clone());
+            if hall.len() > hall_of_fame.capacity {
+                hall.pop_front();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn population<'a>(max_generation: usize) -> GeneticBuilder<'a, f64> {
+        Genetic::builder()
+            .population(20)
+            .max_generation(max_generation)
+            .random_agent(|rng| rng.gen_range(-5.0, 5.0))
+            .mutate(|a: &f64, rng| (*a + rng.gen_range(-0.5, 0.5)).clamp(-5.0, 5.0))
+            .offspring(|a: &f64, b: &f64, _rng| (*a + *b) / 2.0)
+    }
+
+    #[test]
+    fn competitive() {
+        let arms_race = Interaction::Competitive(Box::new(|_p, a: &f64, opponent: &f64| if a > opponent { 1.0 } else { 0.0 }));
+        let model = Coevolution::new(vec![population(60), population(40)], arms_race, Sampling::Random(5), Some(HallOfFame { capacity: 10, samples: 2 })).unwrap();
+
+        let result = model.run_with_seed(3);
+        assert_eq!(result, model.run_with_seed(3));
+        assert_eq!(result.generations, 60);
+        assert_eq!((result.populations[0].generations, result.populations[1].generations), (60, 40));
+        assert!(result.populations.iter().all(|x| x.best > 4.0));
+        assert!(result.hall_of_fame.iter().all(|hall| hall.len() == 10));
+    }
+
+    #[test]
+    fn cooperative() {
+        let team = Interaction::Cooperative(Box::new(|team: &[&f64]| -(team[0] - 1.0).powi(2) - (team[1] + 2.0).powi(2)));
+        let model = Coevolution::new(vec![population(50), population(50)], team, Sampling::Best(2), None).unwrap();
+
+        let result = model.run_with_seed(3);
+        assert!((result.populations[0].best - 1.0).abs() < 0.1);
+        assert!((result.populations[1].best + 2.0).abs() < 0.1);
+        assert!(result.hall_of_fame.iter().all(Vec::is_empty));
+    }
+
+    #[test]
+    fn invalid_coevolution() {
+        let arms_race = || Interaction::Competitive(Box::new(|_p, a: &f64, opponent: &f64| a - opponent));
+        assert!(Coevolution::new(vec![population(10)], arms_race(), Sampling::All, None).is_err());
+        assert!(Coevolution::new(vec![population(10), population(10)], arms_race(), Sampling::Random(0), None).is_err());
+        assert!(Coevolution::new(vec![population(10), population(10).pm_adaptation(crate::OneFifthRule::default())], arms_race(), Sampling::All, None).is_err());
+        assert!(Coevolution::new(vec![population(10), population(10)], arms_race(), Sampling::All, Some(HallOfFame { capacity: 0, samples: 1 })).is_err());
+    }
+}
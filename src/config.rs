@@ -0,0 +1,157 @@
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+use crate::{ConfigFileError, Objective, Selection, Stagnation};
+
+/// The parameters of a Genetic that can be read from a TOML or JSON file, so experiments can be changed
+/// without recompiling (the functions are still given to the builder)
+///
+/// The missing fields get the default values of the builder, unknown fields are errors. The selection
+/// strategies are written in snake_case: `selection = "roulette"` or
+/// `selection = { tournament = { size = 3, probability = 0.9 } }` in TOML.
+///
+/// # Examples:
+/// ```
+/// use rand::prelude::*;
+/// use bbte_optim_tzim1773_genetic::{Genetic, GeneticConfig, Selection};
+///
+/// fn main() {
+///     let config: GeneticConfig = GeneticConfig::from_toml(r#"
+///         population = 50
+///         max_generation = 100
+///         pm = 0.2
+///         selection = { tournament = { size = 3, probability = 1.0 } }
+///         stagnation = { generations = 20, epsilon = 0.0001 }
+///     "#).unwrap();
+///     assert_eq!(config.selection, Selection::Tournament { size: 3, probability: 1.0 });
+///
+///     let test = Genetic::builder()
+///         .config(config)
+///         .random_agent(|rng| rng.gen_range(-5.0, 5.0))
+///         .fitness(|a: &f64| 5.0 - a * a)
+///         .mutate(|a: &f64, rng| *a + rng.gen_range(-0.1, 0.1))
+///         .offspring(|a: &f64, b: &f64, _rng| (*a + *b) / 2.0)
+///         .build()
+///         .unwrap();
+///
+///     println!("{}", test.run().best); // should be a number close to 0
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GeneticConfig<F = f64> {
+    /// Number of agents in a generation
+    pub population: usize,
+    /// Number of generations
+    pub max_generation: usize,
+    /// Probability of crossover
+    pub pc: f64,
+    /// Probability of mutation
+    pub pm: f64,
+    /// Number of the best agents kept in the next generation
+    pub elitism: usize,
+    /// The fraction of the population replaced with random agents in every generation
+    pub random_immigrants: f64,
+    /// Whether bigger or smaller fitness is better
+    pub objective: Objective,
+    /// The way the next generation is selected
+    pub selection: Selection,
+    /// The way the parents are chosen (randomly if it's None)
+    pub parent_selection: Option<Selection>,
+    /// Stops the run if the best fitness doesn't improve
+    pub stagnation: Option<Stagnation>,
+    /// Stops the run when an agent at least this good is found
+    pub target_fitness: Option<F>,
+    /// Stops the run after this many seconds
+    pub max_seconds: Option<f64>,
+    /// Stops the run after this many evaluations
+    pub max_evaluations: Option<usize>,
+}
+
+impl<F> Default for GeneticConfig<F> {
+    fn default() -> Self {
+        GeneticConfig {
+            population: 100,
+            max_generation: 1000,
+            pc: 0.5,
+            pm: 0.4,
+            elitism: 0,
+            random_immigrants: 0.0,
+            objective: Objective::Maximize,
+            selection: Selection::Truncation,
+            parent_selection: None,
+            stagnation: None,
+            target_fitness: None,
+            max_seconds: None,
+            max_evaluations: None,
+        }
+    }
+}
+
+impl<F: DeserializeOwned> GeneticConfig<F> {
+    /// Reads the parameters from a TOML document
+    pub fn from_toml(toml: &str) -> Result<Self, ConfigFileError> {
+        Ok(toml::from_str(toml)?)
+    }
+
+    /// Reads the parameters from a JSON document
+    pub fn from_json(json: &str) -> Result<Self, ConfigFileError> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Reads the parameters from a .toml or .json file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ConfigFileError> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)?;
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("toml") => Self::from_toml(&content),
+            Some("json") => Self::from_json(&content),
+            _ => Err(ConfigFileError::UnknownFormat(path.display().to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Genetic;
+    use rand::Rng;
+
+    #[test]
+    fn genetic_config() {
+        let from_json = GeneticConfig::<f64>::from_json(r#"{
+            "population": 30,
+            "pc": 0.9,
+            "objective": "minimize",
+            "selection": { "linear_rank": { "selection_pressure": 1.5 } },
+            "target_fitness": 0.001,
+            "max_seconds": 10.0
+        }"#).unwrap();
+        let from_toml = GeneticConfig::from_toml(r#"
+            population = 30
+            pc = 0.9
+            objective = "minimize"
+            selection = { linear_rank = { selection_pressure = 1.5 } }
+            target_fitness = 0.001
+            max_seconds = 10.0
+        "#).unwrap();
+        assert_eq!(from_json, from_toml);
+        assert_eq!((from_json.max_generation, from_json.pm), (1000, 0.4));
+
+        let test = Genetic::builder()
+            .config(from_toml)
+            .random_agent(|rng| rng.gen_range(-5.0, 5.0))
+            .fitness(|a: &f64| a * a)
+            .mutate(|a: &f64, rng| *a + rng.gen_range(-0.1, 0.1))
+            .offspring(|a: &f64, b: &f64, _rng| (*a + *b) / 2.0)
+            .build()
+            .unwrap();
+        assert_eq!((test.population, test.pc, test.objective), (30, 0.9, Objective::Minimize));
+        assert!(test.run_with_seed(1).best_fitness <= 0.001);
+
+        assert!(GeneticConfig::<f64>::from_toml("populaton = 30").is_err());
+        assert!(matches!(GeneticConfig::<f64>::load("config.yaml"), Err(ConfigFileError::Io(_))));
+    }
+}
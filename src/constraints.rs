@@ -0,0 +1,419 @@
+use rand::prelude::*;
+use std::cmp::Ordering;
+
+use crate::adaptation::Rates;
+use crate::{ConfigError, Evaluated, Fitness, Genetic, MaybeSync, Objective, Selection};
+
+/// Function that returns how much an agent violates each constraint (0.0 or less if it's satisfied)
+pub type ConstraintsFn<'a, T> = Box<dyn Fn(&T) -> Vec<f64> + Send + Sync + 'a>;
+
+/// Returns the total violation of the constraints (the sum of the positive amounts, NaN counts as infinite)
+pub(crate) fn total_violation(amounts: &[f64]) -> f64 {
+    amounts.iter().map(|amount| if amount.is_nan() { f64::INFINITY } else { amount.max(0.0) }).sum()
+}
+
+/// The way the agents violating the constraints are compared
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum ConstraintHandling {
+    /// Deb's feasibility rules: a feasible agent beats an infeasible one, a smaller total violation beats a bigger
+    /// one, and the fitness only decides between feasible agents
+    #[default]
+    Feasibility,
+    /// The selections compare the fitness (its to_f64() value) minus the weighted violations of the constraints
+    /// (plus when the objective is Minimize), so a good infeasible agent can beat a bad feasible one. The penalties
+    /// belong to the constraints in order, the constraints without one use the last penalty.
+    Penalty(Vec<Penalty>),
+    /// Stochastic ranking (Runarsson and Yao): the agents are ranked by a bubble sort comparing two neighbours by
+    /// their fitness if both are feasible or with the given probability, otherwise by their total violation, and
+    /// the selections use the ranks (the best rank is the best agent)
+    /// Suggested value: StochasticRanking { probability: 0.45 }
+    StochasticRanking {
+        /// The probability of comparing by the fitness when an agent is infeasible (between 0.0 and 1.0, below 0.5
+        /// the ranking ends up mostly feasible)
+        probability: f64,
+    },
+}
+
+impl ConstraintHandling {
+    /// Checks whether the penalties are usable
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        match self {
+            ConstraintHandling::Feasibility => Ok(()),
+            ConstraintHandling::Penalty(penalties) if penalties.is_empty() => Err(ConfigError::InvalidConstraintHandling("at least one penalty is needed".to_string())),
+            ConstraintHandling::Penalty(penalties) => penalties.iter().try_for_each(Penalty::validate),
+            ConstraintHandling::StochasticRanking { probability } if !(0.0..=1.0).contains(probability) => {
+                Err(ConfigError::InvalidConstraintHandling(format!("the probability must be between 0.0 and 1.0, got {}", probability)))
+            }
+            ConstraintHandling::StochasticRanking { .. } => Ok(()),
+        }
+    }
+}
+
+/// The coefficient the violation of a constraint is multiplied by in the Penalty constraint handling
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Penalty {
+    /// The same coefficient in every generation
+    Static {
+        /// The coefficient (more than 0.0)
+        coefficient: f64,
+    },
+    /// A coefficient growing with the generations: coefficient * (generation + 1)^exponent, the infeasible agents
+    /// are tolerated at the start of the run but not at its end
+    /// Suggested exponent: 2.0
+    Dynamic {
+        /// The coefficient of the first generation (more than 0.0)
+        coefficient: f64,
+        /// The exponent of the growth (at least 0.0)
+        exponent: f64,
+    },
+    /// A coefficient adapted to the best agent (by the penalized fitness): it's multiplied by `factor` if the best
+    /// agent violated the constraint in each of the last `generations` generations and divided by it if the best
+    /// agent satisfied it in each of them
+    /// Suggested values: generations = 5, factor = 2.0
+    Adaptive {
+        /// The starting coefficient (more than 0.0)
+        coefficient: f64,
+        /// The number of generations in a row needed for a change (at least 1)
+        generations: usize,
+        /// The change of the coefficient (more than 1.0)
+        factor: f64,
+    },
+}
+
+impl Penalty {
+    /// Checks whether the parameters are usable
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let coefficient = match *self {
+            Penalty::Static { coefficient } | Penalty::Dynamic { coefficient, .. } | Penalty::Adaptive { coefficient, .. } => coefficient,
+        };
+        if !(coefficient > 0.0 && coefficient.is_finite()) {
+            return Err(ConfigError::InvalidConstraintHandling(format!("the coefficient must be positive, got {}", coefficient)));
+        }
+        match *self {
+            Penalty::Dynamic { exponent, .. } if !(exponent >= 0.0 && exponent.is_finite()) => {
+                Err(ConfigError::InvalidConstraintHandling(format!("the exponent must be at least 0.0, got {}", exponent)))
+            }
+            Penalty::Adaptive { generations: 0, .. } => Err(ConfigError::InvalidConstraintHandling("generations must be at least 1".to_string())),
+            Penalty::Adaptive { factor, .. } if !(factor > 1.0 && factor.is_finite()) => {
+                Err(ConfigError::InvalidConstraintHandling(format!("the factor must be more than 1.0, got {}", factor)))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the coefficient of the generation from the coefficient of the previous one (None in the first
+    /// generation), `streak` counts the generations in a row the best agent satisfied (positive) or violated
+    /// (negative) the constraint
+    fn coefficient(&self, previous: Option<f64>, streak: &mut isize, violated: bool, generation: usize) -> f64 {
+        match *self {
+            Penalty::Static { coefficient } => coefficient,
+            Penalty::Dynamic { coefficient, exponent } => coefficient * ((generation + 1) as f64).powf(exponent),
+            Penalty::Adaptive { coefficient, generations, factor } => {
+                let previous = match previous {
+                    Some(previous) => previous,
+                    None => return coefficient,
+                };
+                *streak = match (violated, *streak) {
+                    (true, streak) if streak < 0 => streak - 1,
+                    (true, _) => -1,
+                    (false, streak) if streak > 0 => streak + 1,
+                    (false, _) => 1,
+                };
+                if streak.unsigned_abs() < generations {
+                    return previous;
+                }
+                let violated = *streak < 0;
+                *streak = 0;
+                if violated { previous * factor } else { previous / factor }
+            }
+        }
+    }
+}
+
+/// Returns the fitness worsened by the violations multiplied by their coefficients (the violations without a
+/// coefficient use the last one, NaN counts as infinite)
+pub(crate) fn penalty_score(fitness: f64, amounts: &[f64], coefficients: &[f64], objective: Objective) -> f64 {
+    let penalty = amounts.iter()
+        .enumerate()
+        .filter(|(_, amount)| **amount > 0.0 || amount.is_nan())
+        .map(|(i, amount)| {
+            let coefficient = coefficients.get(i).or_else(|| coefficients.last()).cloned().unwrap_or(0.0);
+            if amount.is_nan() { f64::INFINITY } else { coefficient * amount }
+        })
+        .sum::<f64>();
+
+    match objective {
+        Objective::Maximize => fitness - penalty,
+        Objective::Minimize => fitness + penalty,
+    }
+}
+
+/// Returns the indices of the agents ranked by stochastic ranking (the best one first): bubble sort sweeps swapping
+/// two neighbours if the second one is better by the fitness (if both are feasible or with the given probability)
+/// or by the total violation (otherwise), until a sweep doesn't swap or there were as many sweeps as agents
+pub(crate) fn stochastic_ranking<G: Fitness>(fitness: &[G], violations: &[f64], probability: f64, objective: Objective, rng: &mut dyn RngCore) -> Vec<usize> {
+    let mut order = (0..fitness.len()).collect::<Vec<usize>>();
+    for _ in 0..fitness.len() {
+        let mut swapped = false;
+        for k in 1..order.len() {
+            let (a, b) = (order[k - 1], order[k]);
+            let feasible = violations[a] == 0.0 && violations[b] == 0.0;
+            let better = if feasible || rng.gen::<f64>() < probability {
+                objective.is_better(&fitness[b], &fitness[a])
+            } else {
+                violations[b] < violations[a]
+            };
+            if better {
+                order.swap(k - 1, k);
+                swapped = true;
+            }
+        }
+        if !swapped {
+            break;
+        }
+    }
+
+    order
+}
+
+/// The fitness of an agent with its violation of the constraints, compared by Deb's feasibility rules:
+/// a feasible agent is better than an infeasible one, from two infeasible agents the one with the smaller
+/// violation is better, from two feasible agents the one with the better fitness
+///
+/// The violation is stored negated when bigger fitness is better, so the derived lexicographic order follows
+/// the objective.
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub(crate) struct Feasibility<F> {
+    violation: f64,
+    fitness: F,
+}
+
+impl<F: Fitness> Feasibility<F> {
+    fn new(violation: f64, fitness: F, objective: Objective) -> Self {
+        let violation = match objective {
+            Objective::Maximize => -violation,
+            Objective::Minimize => violation,
+        };
+        Feasibility { violation, fitness }
+    }
+}
+
+impl<F: Fitness> Fitness for Feasibility<F> {
+    fn lowest() -> Self {
+        Feasibility { violation: f64::NEG_INFINITY, fitness: F::lowest() }
+    }
+
+    fn highest() -> Self {
+        Feasibility { violation: f64::INFINITY, fitness: F::highest() }
+    }
+
+    /// The fitness of the feasible agents, NaN for the infeasible ones (so the roulette doesn't choose them)
+    fn to_f64(&self) -> f64 {
+        if self.violation == 0.0 { self.fitness.to_f64() } else { f64::NAN }
+    }
+}
+
+impl<'a, T, F> Genetic<'a, T, F>
+    where T: Clone + MaybeSync, F: Fitness + MaybeSync {
+    /// Returns the total violation of the constraints of every agent (0.0 for every agent if there are no
+    /// constraints)
+    pub(crate) fn violations(&self, xg: &[Evaluated<T, F>]) -> Vec<f64> {
+        match &self.f_constraints {
+            Some(f_constraints) => xg.iter().map(|x| total_violation(&f_constraints(&x.agent))).collect(),
+            None => vec![0.0; xg.len()],
+        }
+    }
+
+    /// Compares two agents (the better one is Less) by their violations first if there are constraints
+    pub(crate) fn compare_feasible(&self, violations: &[f64], xg: &[Evaluated<T, F>], a: usize, b: usize) -> Ordering {
+        violations[a].total_cmp(&violations[b]).then_with(|| self.objective.compare(&xg[a].fitness, &xg[b].fitness))
+    }
+
+    /// Tells whether agent a is better than agent b (by Deb's feasibility rules if there are constraints)
+    pub(crate) fn is_fitter(&self, a: &Evaluated<T, F>, b: &Evaluated<T, F>) -> bool {
+        match &self.f_constraints {
+            Some(f_constraints) => {
+                let (violation_a, violation_b) = (total_violation(&f_constraints(&a.agent)), total_violation(&f_constraints(&b.agent)));
+                violation_a < violation_b || (violation_a == violation_b && self.objective.is_better(&a.fitness, &b.fitness))
+            }
+            None => self.objective.is_better(&a.fitness, &b.fitness),
+        }
+    }
+
+    /// Returns the indices of the agents, the best one first (by Deb's feasibility rules if there are constraints)
+    pub(crate) fn best_first(&self, xg: &[Evaluated<T, F>]) -> Vec<usize> {
+        let violations = self.violations(xg);
+        let mut order = (0..xg.len()).collect::<Vec<usize>>();
+        order.sort_by(|a, b| self.compare_feasible(&violations, xg, *a, *b));
+        order
+    }
+
+    /// Sets the penalty coefficients of the generation (if the constraints are handled with penalties), the
+    /// adaptive ones are adapted to the best agent of the population
+    pub(crate) fn update_penalties(&self, rates: &mut Rates, generation: usize, xg: &[Evaluated<T, F>]) {
+        let (penalties, f_constraints) = match (&self.constraint_handling, &self.f_constraints) {
+            (ConstraintHandling::Penalty(penalties), Some(f_constraints)) if !xg.is_empty() => (penalties, f_constraints),
+            _ => return,
+        };
+        let amounts = xg.iter().map(|x| f_constraints(&x.agent)).collect::<Vec<Vec<f64>>>();
+        let scores = self.penalized(xg)
+            .iter()
+            .zip(&amounts)
+            .map(|(fitness, amounts)| penalty_score(fitness.to_f64(), amounts, &rates.penalties, self.objective))
+            .collect::<Vec<f64>>();
+        let best = (0..xg.len())
+            .min_by(|a, b| self.objective.compare(&scores[*a], &scores[*b]))
+            .expect("the population is not empty");
+
+        let count = amounts.iter().map(Vec::len).max().unwrap_or(0);
+        rates.penalty_streaks.resize(count, 0);
+        rates.penalties = (0..count)
+            .map(|i| {
+                let penalty = penalties.get(i).or_else(|| penalties.last()).expect("validated to be not empty");
+                let violated = amounts[best].get(i).is_some_and(|amount| *amount > 0.0 || amount.is_nan());
+                penalty.coefficient(rates.penalties.get(i).cloned(), &mut rates.penalty_streaks[i], violated, generation)
+            })
+            .collect();
+    }
+
+    /// Chooses agents with the selection strategy from the (adjusted) fitness, by Deb's feasibility rules, by the
+    /// fitness worsened with the penalty coefficients or by the stochastic ranking if there are constraints
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn select_feasible<G: Fitness>(&self, selection: &Selection, xg: &[Evaluated<T, F>], fitness: Vec<G>, penalties: &[f64], cases: Option<&[Vec<f64>]>, count: usize, rng: &mut dyn RngCore) -> Vec<usize> {
+        let f_constraints = match &self.f_constraints {
+            Some(f_constraints) => f_constraints,
+            None => return selection.select(&fitness, cases, count, self.objective, rng),
+        };
+        match self.constraint_handling {
+            ConstraintHandling::Feasibility => {}
+            ConstraintHandling::Penalty(_) => {
+                let scores = xg.iter()
+                    .zip(&fitness)
+                    .map(|(x, fitness)| penalty_score(fitness.to_f64(), &f_constraints(&x.agent), penalties, self.objective))
+                    .collect::<Vec<f64>>();
+                return selection.select(&scores, cases, count, self.objective, rng);
+            }
+            ConstraintHandling::StochasticRanking { probability } => {
+                let ranking = stochastic_ranking(&fitness, &self.violations(xg), probability, self.objective, rng);
+                // the best agent gets the biggest score
+                let mut scores = vec![0.0; xg.len()];
+                for (rank, i) in ranking.into_iter().enumerate() {
+                    scores[i] = (xg.len() - rank) as f64;
+                }
+                return selection.select(&scores, cases, count, Objective::Maximize, rng);
+            }
+        }
+        let feasibility = self.violations(xg)
+            .into_iter()
+            .zip(fitness)
+            .map(|(violation, fitness)| Feasibility::new(violation, fitness, self.objective))
+            .collect::<Vec<Feasibility<G>>>();
+
+        selection.select(&feasibility, cases, count, self.objective, rng)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn feasibility_rules() {
+        assert_eq!(total_violation(&[-1.0, 0.5, 2.0]), 2.5);
+        let feasible = Feasibility::new(0.0, 10.0, Objective::Minimize);
+        let slightly = Feasibility::new(0.5, 1.0, Objective::Minimize);
+        let badly = Feasibility::new(3.0, 0.0, Objective::Minimize);
+        assert!(feasible < slightly && slightly < badly);
+        assert!(Feasibility::new(0.0, 5.0, Objective::Minimize) < feasible);
+        assert!(Feasibility::new(0.0, 1.0, Objective::Maximize) > Feasibility::new(0.1, 10.0, Objective::Maximize));
+        assert!(slightly.to_f64().is_nan());
+    }
+
+    #[test]
+    fn constraints() {
+        // the unconstrained optimum is 0, the agents have to be at least 3
+        let test = Genetic::builder()
+            .population(30)
+            .max_generation(60)
+            .random_agent(|rng| rng.gen_range(-10.0, 10.0))
+            .fitness(|a: &f64| -a * a)
+            .mutate(|a: &f64, rng| *a + rng.gen_range(-0.2, 0.2))
+            .offspring(|a: &f64, b: &f64, _rng| (*a + *b) / 2.0)
+            .constraints(|a: &f64| vec![3.0 - a])
+            .build()
+            .unwrap();
+
+        let result = test.run_with_seed(2);
+        assert!(result.best >= 3.0 && result.best < 3.1);
+    }
+
+    #[test]
+    fn penalties() {
+        assert_eq!(penalty_score(1.0, &[2.0, -1.0, 1.0], &[3.0, 1.0], Objective::Maximize), -6.0);
+        assert_eq!(penalty_score(1.0, &[f64::NAN], &[3.0], Objective::Minimize), f64::INFINITY);
+
+        let mut streak = 0;
+        assert_eq!(Penalty::Dynamic { coefficient: 2.0, exponent: 2.0 }.coefficient(None, &mut streak, true, 2), 18.0);
+        let adaptive = Penalty::Adaptive { coefficient: 1.0, generations: 2, factor: 2.0 };
+        assert_eq!(adaptive.coefficient(None, &mut streak, true, 0), 1.0);
+        assert_eq!(adaptive.coefficient(Some(1.0), &mut streak, true, 1), 1.0);
+        assert_eq!(adaptive.coefficient(Some(1.0), &mut streak, true, 2), 2.0);
+        assert_eq!(adaptive.coefficient(Some(2.0), &mut streak, false, 3), 2.0);
+        assert_eq!(adaptive.coefficient(Some(2.0), &mut streak, false, 4), 1.0);
+        assert_eq!(streak, 0);
+
+        assert!(ConstraintHandling::Penalty(Vec::new()).validate().is_err());
+        assert!(Penalty::Adaptive { coefficient: 1.0, generations: 2, factor: 1.0 }.validate().is_err());
+        assert!(Penalty::Static { coefficient: 0.0 }.validate().is_err());
+
+        let penalties = [
+            Penalty::Static { coefficient: 100.0 },
+            Penalty::Dynamic { coefficient: 1.0, exponent: 2.0 },
+            Penalty::Adaptive { coefficient: 1.0, generations: 3, factor: 2.0 },
+        ];
+        for penalty in penalties {
+            let test = Genetic::builder()
+                .population(30)
+                .max_generation(60)
+                .random_agent(|rng| rng.gen_range(-10.0, 10.0))
+                .fitness(|a: &f64| -a * a)
+                .mutate(|a: &f64, rng| *a + rng.gen_range(-0.2, 0.2))
+                .offspring(|a: &f64, b: &f64, _rng| (*a + *b) / 2.0)
+                .constraints(|a: &f64| vec![3.0 - a])
+                .constraint_handling(ConstraintHandling::Penalty(vec![penalty]))
+                .build()
+                .unwrap();
+
+            // the best agent is still the best feasible one
+            let result = test.run_with_seed(2);
+            assert!(result.best >= 3.0 && result.best < 3.2, "{:?}: {}", penalty, result.best);
+        }
+    }
+
+    #[test]
+    fn stochastic_ranking_run() {
+        let rng = &mut StdRng::seed_from_u64(1);
+        let (fitness, violations) = ([5.0, 1.0, 4.0, 3.0], [1.0, 0.0, 0.0, 2.0]);
+        // only the violations matter between the infeasible agents, only the fitness with probability 1.0
+        assert_eq!(stochastic_ranking(&fitness, &violations, 0.0, Objective::Maximize, rng), vec![2, 1, 0, 3]);
+        assert_eq!(stochastic_ranking(&fitness, &violations, 1.0, Objective::Maximize, rng), vec![0, 2, 3, 1]);
+        assert!(ConstraintHandling::StochasticRanking { probability: 1.5 }.validate().is_err());
+
+        let test = Genetic::builder()
+            .population(30)
+            .max_generation(60)
+            .selection(Selection::Tournament { size: 2, probability: 1.0 })
+            .elitism(1)
+            .random_agent(|rng| rng.gen_range(-10.0, 10.0))
+            .fitness(|a: &f64| -a * a)
+            .mutate(|a: &f64, rng| *a + rng.gen_range(-0.2, 0.2))
+            .offspring(|a: &f64, b: &f64, _rng| (*a + *b) / 2.0)
+            .constraints(|a: &f64| vec![3.0 - a])
+            .constraint_handling(ConstraintHandling::StochasticRanking { probability: 0.45 })
+            .build()
+            .unwrap();
+
+        let result = test.run_with_seed(2);
+        assert!(result.best >= 3.0 && result.best < 3.2);
+    }
+}
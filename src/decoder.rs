@@ -0,0 +1,125 @@
+use std::hash::Hash;
+use std::sync::Arc;
+
+use crate::{CacheStats, FitnessCache, LruCache};
+
+/// Function that decodes a genotype (the evolved agent) into its phenotype (the solution that is evaluated)
+pub type DecodeFn<'a, G, P> = Box<dyn Fn(&G) -> P + Send + Sync + 'a>;
+
+/// Decoder of the genotypes into phenotypes, keeping the recently decoded phenotypes so that they are not decoded
+/// again (e.g. when the best agent is decoded after the run, or a genotype comes back in a later generation)
+///
+/// The operators work on the genotypes, the fitness is defined on the phenotypes: fitness() turns a fitness
+/// function of the phenotypes into one of the genotypes, it can be given to the builder with fitness(). The decoder
+/// is a handle, its clones share the phenotypes.
+///
+/// # Examples:
+/// ```
+/// use rand::prelude::*;
+/// use bbte_optim_tzim1773_genetic::{Decoder, Genetic};
+///
+/// fn main() {
+///     // the genotype is a vector of bits, the phenotype is the number they encode
+///     let decoder = Decoder::new(1000, |bits: &Vec<bool>| bits.iter().fold(0u32, |n, bit| 2 * n + *bit as u32));
+///     let test = Genetic::builder()
+///         .max_generation(50)
+///         .random_agent(|rng| (0..8).map(|_| rng.gen::<bool>()).collect())
+///         .fitness(decoder.fitness(|n: &u32| -((*n as f64) - 100.0).abs()))
+///         .mutate(|a: &Vec<bool>, rng| {
+///             let mut a = a.clone();
+///             let i = rng.gen_range(0, a.len());
+///             a[i] = !a[i];
+///             a
+///         })
+///         .offspring(|a: &Vec<bool>, b: &Vec<bool>, rng| {
+///             let cut = rng.gen_range(0, a.len());
+///             a[..cut].iter().chain(&b[cut..]).cloned().collect()
+///         })
+///         .build()
+///         .unwrap();
+///
+///     let result = test.run();
+///     println!("{}", decoder.decode(&result.best)); // probably 100, without decoding it again
+/// }
+/// ```
+pub struct Decoder<'a, G, P> {
+    inner: Arc<Inner<'a, G, P>>,
+}
+
+struct Inner<'a, G, P> {
+    f_decode: DecodeFn<'a, G, P>,
+    phenotypes: LruCache<G, P>,
+}
+
+impl<'a, G, P> Clone for Decoder<'a, G, P> {
+    fn clone(&self) -> Self {
+        Decoder { inner: Arc::clone(&self.inner) }
+    }
+}
+
+impl<'a, G, P> Decoder<'a, G, P>
+    where G: Hash + Eq + Clone + Send + 'a, P: Clone + Send + 'a {
+    /// Creates a decoder keeping at most `capacity` phenotypes (with 0 every genotype is decoded every time)
+    pub fn new(capacity: usize, f_decode: impl Fn(&G) -> P + Send + Sync + 'a) -> Self {
+        let inner = Inner { f_decode: Box::new(f_decode), phenotypes: LruCache::new(capacity) };
+        Decoder { inner: Arc::new(inner) }
+    }
+
+    /// Returns the phenotype of the genotype (the kept one if it was decoded recently)
+    pub fn decode(&self, genotype: &G) -> P {
+        if let Some(phenotype) = self.inner.phenotypes.get(genotype) {
+            return phenotype;
+        }
+        let phenotype = (self.inner.f_decode)(genotype);
+        self.inner.phenotypes.insert(genotype, phenotype.clone());
+        phenotype
+    }
+
+    /// Returns a fitness function of the genotypes: the fitness of their decoded phenotype
+    pub fn fitness<F>(&self, f_fitness: impl Fn(&P) -> F + Send + Sync + 'a) -> impl Fn(&G) -> F + Send + Sync + 'a {
+        let decoder = self.clone();
+        move |genotype| f_fitness(&decoder.decode(genotype))
+    }
+
+    /// Returns how many decodings were saved until now
+    pub fn stats(&self) -> CacheStats {
+        self.inner.phenotypes.stats()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Genetic;
+    use rand::Rng;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn decoded_fitness() {
+        let decodings = AtomicUsize::new(0);
+        let decoder = Decoder::new(100, |a: &i32| {
+            decodings.fetch_add(1, Ordering::Relaxed);
+            *a as f64 / 10.0
+        });
+        let fitness = decoder.fitness(|x: &f64| -x * x);
+        assert_eq!(fitness(&20), -4.0);
+        assert_eq!(decoder.decode(&20), 2.0);
+        assert_eq!(decodings.load(Ordering::Relaxed), 1);
+        assert_eq!(decoder.stats(), CacheStats { hits: 1, misses: 1, len: 1 });
+
+        let test = Genetic::builder()
+            .population(20)
+            .max_generation(30)
+            .random_agent(|rng| rng.gen_range(-100, 100))
+            .fitness(decoder.fitness(|x: &f64| -(x - 3.0).abs()))
+            .mutate(|a: &i32, rng| *a + rng.gen_range(-3, 4))
+            .offspring(|a: &i32, b: &i32, _rng| (*a + *b) / 2)
+            .build()
+            .unwrap();
+
+        let result = test.run_with_seed(1);
+        assert_eq!(decoder.decode(&result.best), 3.0);
+        // the kept phenotypes of the repeated genotypes were not decoded again
+        assert!(decodings.load(Ordering::Relaxed) < result.evaluations);
+    }
+}
@@ -0,0 +1,55 @@
+use rand::RngCore;
+use std::collections::HashSet;
+use std::hash::Hash;
+
+use crate::{Fitness, Genetic, MaybeSync, Offspring};
+
+/// Function that returns the indices of the agents that are the same as an agent before them
+pub type DuplicatesFn<'a, T> = Box<dyn Fn(&[&T]) -> Vec<usize> + Send + Sync + 'a>;
+
+/// Returns the indices of the keys that were already seen before them
+pub(crate) fn duplicates<K: Hash + Eq>(keys: impl Iterator<Item = K>) -> Vec<usize> {
+    let mut seen = HashSet::new();
+    keys.enumerate()
+        .filter_map(|(i, key)| if seen.insert(key) { None } else { Some(i) })
+        .collect()
+}
+
+impl<'a, T, F> Genetic<'a, T, F>
+    where T: Clone + MaybeSync, F: Fitness + MaybeSync {
+    /// Replaces the copies among the agents the next generation is selected from with new random agents
+    /// (the first one of the same agents is kept)
+    pub(crate) fn replace_duplicates(&self, f_duplicates: &DuplicatesFn<'a, T>, offspring: &mut [Offspring<T, F>], rng: &mut dyn RngCore) {
+        let agents = offspring.iter().map(|(agent, _)| agent).collect::<Vec<&T>>();
+        for i in f_duplicates(&agents) {
+            offspring[i] = ((self.get_random_agent)(rng), None);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+
+    #[test]
+    fn dedup() {
+        assert_eq!(duplicates([3, 1, 3, 2, 1, 3].iter()), vec![2, 4, 5]);
+
+        // the offspring are copies of the first parent, so the population collapses without dedup
+        let build = |dedup: bool| {
+            let builder = Genetic::builder()
+                .population(20)
+                .max_generation(30)
+                .random_agent(|rng| rng.gen_range(-1000, 1000))
+                .fitness(|a: &i32| -(a * a))
+                .mutate(|a: &i32, _rng| *a)
+                .offspring(|a: &i32, _b: &i32, _rng| *a);
+            if dedup { builder.dedup() } else { builder }.build().unwrap()
+        };
+        let distinct = |population: &[i32]| duplicates(population.iter()).is_empty();
+
+        assert!(!distinct(&build(false).run_with_seed(3).population));
+        assert!(distinct(&build(true).run_with_seed(3).population));
+    }
+}
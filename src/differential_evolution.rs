@@ -0,0 +1,259 @@
+use rand::prelude::*;
+use std::sync::Arc;
+use std::time::Instant;
+
+use crate::engine::Engine;
+use crate::genome::{BoundHandling, Bounds};
+use crate::{ConfigError, Evaluated, Fitness, Genetic, MaybeSync, RunError, RunResult, Termination};
+
+/// How the mutant vector of differential evolution is created (r1, r2, r3 are distinct random agents different
+/// from the target agent, the mutant is crossed with the target by binomial crossover)
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DeStrategy {
+    /// DE/rand/1/bin: x_r1 + F * (x_r2 - x_r3), robust, explores well
+    Rand1Bin,
+    /// DE/best/1/bin: x_best + F * (x_r1 - x_r2), converges fast, can get stuck in a local optimum
+    Best1Bin,
+    /// DE/current-to-best/1/bin: x_i + F * (x_best - x_i) + F * (x_r1 - x_r2), between the two
+    CurrentToBest1,
+}
+
+/// Differential evolution on real vector (Vec<f64>) genomes: in every generation each agent (the target) competes
+/// with a trial vector, made by crossing it with a mutant built from the differences of other agents, and the
+/// trial replaces the target if it's not worse
+///
+/// The agents, the fitness, the population size and the stopping criteria come from the Genetic: the run stops at
+/// max_generation, max_evaluations, the target fitness, the stagnation, the time limit, the stop condition, the
+/// cancellation or the callbacks, and the result is a RunResult with the statistics of every generation (the run
+/// log and the archive are kept too). Its operators, selection and modes are not used, the trials are repaired with
+/// repair(). The values that leave the bounds (in the first generation too) are moved back by the bound handling
+/// (without bounds they are kept).
+///
+/// # Examples:
+/// ```
+/// use rand::prelude::*;
+/// use bbte_optim_tzim1773_genetic::{DeStrategy, DifferentialEvolution, Genetic};
+/// use bbte_optim_tzim1773_genetic::genome::{BoundHandling, Bounds};
+///
+/// fn main() {
+///     let bounds = Bounds::uniform(5, -5.0, 5.0);
+///     let genetic = Genetic::builder()
+///         .population(30)
+///         .max_generation(200)
+///         .random_agent(|rng| (0..5).map(|_| rng.gen_range(-5.0, 5.0)).collect())
+///         .fitness(|a: &Vec<f64>| a.iter().map(|x| x * x).sum::<f64>())
+///         .minimize()
+///         .mutate(|a: &Vec<f64>, _rng| a.clone())
+///         .offspring(|a: &Vec<f64>, _b: &Vec<f64>, _rng| a.clone())
+///         .build()
+///         .unwrap();
+///
+///     let de = DifferentialEvolution::new(genetic, DeStrategy::Rand1Bin, 0.5, 0.9)
+///         .unwrap()
+///         .bounds(bounds, BoundHandling::Reflect);
+///     println!("{:?}", de.run().best); // should be close to [0, 0, 0, 0, 0]
+/// }
+/// ```
+pub struct DifferentialEvolution<'a, F = f64> {
+    /// The Genetic giving the agents, the fitness, the population and the stopping criteria
+    pub genetic: Genetic<'a, Vec<f64>, F>,
+    /// How the mutant vectors are created
+    /// Suggested value: DeStrategy::Rand1Bin
+    pub strategy: DeStrategy,
+    /// The differential weight F, the scale of the differences (0.0 < weight <= 2.0)
+    /// Suggested value: 0.5
+    pub weight: f64,
+    /// The crossover rate CR, the probability of taking a value from the mutant (0.0 - 1.0, one value is always taken)
+    /// Suggested value: 0.9 (0.1 for separable problems)
+    pub cr: f64,
+    /// The bounds of the values (the trials are not moved if there are none)
+    pub bounds: Option<Arc<Bounds>>,
+    /// What happens with the values that leave their bounds
+    pub handling: BoundHandling,
+}
+
+impl<'a, F> DifferentialEvolution<'a, F>
+    where F: Fitness + MaybeSync {
+    /// Creates differential evolution on the agents of the Genetic, checking the parameters
+    ///
+    /// # Arguments:
+    ///
+    /// * `genetic` the agents, the fitness, the population (at least 4) and the stopping criteria
+    /// * `strategy` how the mutant vectors are created
+    /// * `weight` the differential weight F
+    /// * `cr` the crossover rate CR
+    pub fn new(genetic: Genetic<'a, Vec<f64>, F>, strategy: DeStrategy, weight: f64, cr: f64) -> Result<Self, ConfigError> {
+        if genetic.population < 4 {
+            return Err(ConfigError::InvalidDifferentialEvolution(format!("the population must be at least 4, got {}", genetic.population)));
+        }
+        if !(weight > 0.0 && weight <= 2.0) {
+            return Err(ConfigError::InvalidDifferentialEvolution(format!("the weight must be bigger than 0.0 and at most 2.0, got {}", weight)));
+        }
+        if !(0.0..=1.0).contains(&cr) {
+            return Err(ConfigError::InvalidDifferentialEvolution(format!("cr must be between 0.0 and 1.0, got {}", cr)));
+        }
+
+        Ok(DifferentialEvolution { genetic, strategy, weight, cr, bounds: None, handling: BoundHandling::Clamp })
+    }
+
+    /// Keeps the values of the trials between the bounds with the given bound handling
+    pub fn bounds(mut self, bounds: Arc<Bounds>, handling: BoundHandling) -> Self {
+        self.bounds = Some(bounds);
+        self.handling = handling;
+        self
+    }
+
+    /// Runs the optimisation until one of the stopping criteria of the Genetic is met
+    ///
+    /// # Panics:
+    ///  If the fitness function fails and the fitness_error_policy gives up on the agent
+    ///  (use try_run() to get the error instead)
+    pub fn run(&self) -> RunResult<Vec<f64>, F> {
+        self.run_with_rng(&mut thread_rng())
+    }
+
+    /// Same as run(), but returns the error instead of panicking
+    pub fn try_run(&self) -> Result<RunResult<Vec<f64>, F>, RunError> {
+        self.try_run_with_rng(&mut thread_rng())
+    }
+
+    /// Same as run(), but uses the given random number generator
+    pub fn run_with_rng(&self, rng: &mut dyn RngCore) -> RunResult<Vec<f64>, F> {
+        match self.try_run_with_rng(rng) {
+            Ok(result) => result,
+            Err(error) => panic!("{}", error),
+        }
+    }
+
+    /// Same as run(), but uses a random number generator seeded with the given seed, so the runs can be reproduced
+    pub fn run_with_seed(&self, seed: u64) -> RunResult<Vec<f64>, F> {
+        self.run_with_rng(&mut StdRng::seed_from_u64(seed))
+    }
+
+    /// Same as run_with_rng(), but returns the error instead of panicking
+    pub fn try_run_with_rng(&self, rng: &mut dyn RngCore) -> Result<RunResult<Vec<f64>, F>, RunError> {
+        let start = Instant::now();
+        let genetic = &self.genetic;
+        let initial = genetic.get_population(rng).into_iter().map(|x| (self.bounded(x), None)).collect();
+        let (mut engine, mut population) = Engine::start(genetic, start, initial)?;
+
+        let termination = loop {
+            if let Some(termination) = engine.check_bound() {
+                break termination;
+            }
+            let best = genetic.best_index(&population);
+            let trials = (0..population.len()).map(|i| (self.trial(&population, i, best, rng), None)).collect();
+            let trials = match engine.evaluate(trials)? {
+                Some(trials) => trials,
+                None => break Termination::EvaluationLimit,
+            };
+            for (target, trial) in population.iter_mut().zip(trials) {
+                if !genetic.objective.is_better(&target.fitness, &trial.fitness) {
+                    *target = trial;
+                }
+            }
+            if let Some(termination) = engine.finish_generation(&population)? {
+                break termination;
+            }
+        };
+
+        let best = population[genetic.best_index(&population)].clone();
+        Ok(engine.result(best, population.into_iter().map(|x| x.agent).collect(), termination))
+    }
+
+    /// Creates the trial vector of the i-th agent (`best` is the index of the best agent)
+    fn trial(&self, xg: &[Evaluated<Vec<f64>, F>], i: usize, best: usize, rng: &mut dyn RngCore) -> Vec<f64> {
+        let r = distinct(xg.len(), i, rng);
+        let (x, x1, x2, x3, x_best) = (&xg[i].agent, &xg[r[0]].agent, &xg[r[1]].agent, &xg[r[2]].agent, &xg[best].agent);
+        let w = self.weight;
+        let j_rand = rng.gen_range(0, x.len().max(1));
+
+        let trial = (0..x.len())
+            .map(|j| {
+                if j != j_rand && rng.gen::<f64>() >= self.cr {
+                    return x[j];
+                }
+                match self.strategy {
+                    DeStrategy::Rand1Bin => x1[j] + w * (x2[j] - x3[j]),
+                    DeStrategy::Best1Bin => x_best[j] + w * (x1[j] - x2[j]),
+                    DeStrategy::CurrentToBest1 => x[j] + w * (x_best[j] - x[j]) + w * (x1[j] - x2[j]),
+                }
+            })
+            .collect();
+
+        self.bounded(trial)
+    }
+
+    /// Moves the values into their bounds and repairs the agent
+    fn bounded(&self, mut x: Vec<f64>) -> Vec<f64> {
+        if let Some(bounds) = &self.bounds {
+            for (j, value) in x.iter_mut().enumerate().take(bounds.len()) {
+                *value = self.handling.apply(*value, bounds.lower[j], bounds.upper[j]);
+            }
+        }
+        self.genetic.repair(x)
+    }
+}
+
+/// Returns three distinct random indices below n that are different from `exclude` (n is at least 4)
+fn distinct(n: usize, exclude: usize, rng: &mut dyn RngCore) -> [usize; 3] {
+    let mut r = [exclude; 3];
+    for k in 0..3 {
+        while r[..k].contains(&r[k]) || r[k] == exclude {
+            r[k] = rng.gen_range(0, n);
+        }
+    }
+    r
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Stagnation;
+
+    fn genetic<'a>() -> crate::GeneticBuilder<'a, Vec<f64>> {
+        // a sphere with its minimum at [1, 1, 1]
+        Genetic::builder()
+            .population(20)
+            .max_generation(300)
+            .random_agent(|rng| (0..3).map(|_| rng.gen_range(-10.0, 10.0)).collect())
+            .fitness(|a: &Vec<f64>| a.iter().map(|x| (x - 1.0) * (x - 1.0)).sum::<f64>())
+            .minimize()
+            .mutate(|a: &Vec<f64>, _rng| a.clone())
+            .offspring(|a: &Vec<f64>, _b: &Vec<f64>, _rng| a.clone())
+    }
+
+    #[test]
+    fn differential_evolution() {
+        assert!(DifferentialEvolution::new(genetic().population(3).build().unwrap(), DeStrategy::Rand1Bin, 0.5, 0.9).is_err());
+        assert!(DifferentialEvolution::new(genetic().build().unwrap(), DeStrategy::Rand1Bin, 0.0, 0.9).is_err());
+        assert!(DifferentialEvolution::new(genetic().build().unwrap(), DeStrategy::Rand1Bin, 0.5, 1.5).is_err());
+
+        let mut rng = StdRng::seed_from_u64(1);
+        for _ in 0..100 {
+            let r = distinct(4, 2, &mut rng);
+            assert!(!r.contains(&2) && r[0] != r[1] && r[1] != r[2] && r[0] != r[2]);
+        }
+
+        for strategy in &[DeStrategy::Rand1Bin, DeStrategy::Best1Bin, DeStrategy::CurrentToBest1] {
+            let result = DifferentialEvolution::new(genetic().build().unwrap(), *strategy, 0.5, 0.9).unwrap().run_with_seed(1);
+            assert!(result.best.iter().all(|x| (x - 1.0).abs() < 1e-3), "{:?} {:?}", strategy, result.best);
+            assert_eq!(result.history.len(), result.generations);
+            assert!(result.history.windows(2).all(|w| w[1].best <= w[0].best));
+        }
+
+        // the stopping criteria of the Genetic are used, and the values stay in the bounds
+        let test = genetic().max_evaluations(200).build().unwrap();
+        let result = DifferentialEvolution::new(test, DeStrategy::Best1Bin, 1.0, 1.0).unwrap().bounds(Bounds::uniform(3, 2.0, 3.0), BoundHandling::Clamp).run_with_seed(1);
+        assert_eq!((result.termination, result.evaluations, result.generations), (Termination::EvaluationLimit, 200, 9));
+        // the budget is used up in the middle of a generation
+        let test = genetic().max_evaluations(210).build().unwrap();
+        let result = DifferentialEvolution::new(test, DeStrategy::Best1Bin, 1.0, 1.0).unwrap().run_with_seed(1);
+        assert_eq!((result.termination, result.evaluations, result.generations), (Termination::EvaluationLimit, 210, 9));
+        let test = genetic().stagnation(Stagnation::new(5)).build().unwrap();
+        let result = DifferentialEvolution::new(test, DeStrategy::Rand1Bin, 0.5, 0.9).unwrap().bounds(Bounds::uniform(3, 2.0, 3.0), BoundHandling::Clamp).run_with_seed(1);
+        assert_eq!(result.termination, Termination::Stagnation);
+        assert!(result.population.iter().all(|x| x.iter().all(|v| (2.0..=3.0).contains(v))));
+    }
+}
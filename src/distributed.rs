@@ -0,0 +1,338 @@
+use std::fmt::Display;
+use std::io::{self, BufRead, BufReader, ErrorKind, Write};
+use std::marker::PhantomData;
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::str::FromStr;
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::external::EncodeFn;
+use crate::{DistributedError, LineEncode, LineFormat};
+
+/// Evaluates the agents on remote worker processes over TCP
+///
+/// The agents are sent as CSV or JSON lines (like to an ExternalFitness) to the workers, which answer with
+/// a line holding the fitness. A worker evaluates one agent at a time, so the agents are evaluated in parallel
+/// only if the Genetic evaluates them in parallel (rayon feature). The connections are kept open between the
+/// agents. If a worker can't be reached, its connection breaks or it doesn't answer within the timeout
+/// (a straggler), the agent is sent to another worker (at most attempts() times), and the worker is reconnected
+/// later. Unreachable workers and stragglers are skipped for retry_delay().
+///
+/// The workers can be written in any language, or with serve() of this module.
+///
+/// # Examples:
+/// ```no_run
+/// use rand::prelude::*;
+/// use std::time::Duration;
+/// use bbte_optim_tzim1773_genetic::{DistributedFitness, Genetic};
+///
+/// fn main() {
+///     let fitness = DistributedFitness::<Vec<f64>>::new(vec!["10.0.0.2:7878", "10.0.0.3:7878"])
+///         .unwrap()
+///         .timeout(Duration::from_secs(30));
+///
+///     let test = Genetic::builder()
+///         .max_generation(20)
+///         .random_agent(|rng| vec![rng.gen_range(-5.0, 5.0); 3])
+///         .distributed_fitness(fitness)
+///         .mutate(|a: &Vec<f64>, rng| a.iter().map(|x| x + rng.gen_range(-0.1, 0.1)).collect())
+///         .offspring(|a: &Vec<f64>, b: &Vec<f64>, _rng| a.iter().zip(b).map(|(x, y)| (x + y) / 2.0).collect())
+///         .build()
+///         .unwrap();
+///
+///     println!("{:?}", test.run().best);
+/// }
+/// ```
+pub struct DistributedFitness<T, F = f64> {
+    format: LineFormat,
+    encode: EncodeFn<T>,
+    timeout: Option<Duration>,
+    connect_timeout: Duration,
+    retry_delay: Duration,
+    attempts: usize,
+    workers: Mutex<Vec<Worker>>,
+    available: Condvar,
+    fitness: PhantomData<fn() -> F>,
+}
+
+/// A remote worker and its connection (None if it's not connected)
+struct Worker {
+    address: SocketAddr,
+    connection: Option<Connection>,
+    busy: bool,
+    skipped_until: Option<Instant>,
+}
+
+/// An open connection to a worker
+struct Connection {
+    writer: TcpStream,
+    reader: BufReader<TcpStream>,
+}
+
+impl<T, F> DistributedFitness<T, F> {
+    /// Creates an evaluator using the workers at the given addresses, the agents are written as CSV lines
+    ///
+    /// # Errors:
+    ///  If an address can't be resolved
+    pub fn new<A: ToSocketAddrs>(addresses: impl IntoIterator<Item = A>) -> io::Result<Self>
+        where T: LineEncode {
+        Self::with_encoder(addresses, |agent: &T, format: LineFormat| format.encode(agent))
+    }
+
+    /// Creates an evaluator using the workers at the given addresses, the agents are written with the given
+    /// function (it gets the agent and the format, its result shouldn't contain line breaks)
+    ///
+    /// # Errors:
+    ///  If an address can't be resolved
+    pub fn with_encoder<A: ToSocketAddrs>(addresses: impl IntoIterator<Item = A>, encode: impl Fn(&T, LineFormat) -> String + Send + Sync + 'static) -> io::Result<Self> {
+        let mut workers = Vec::new();
+        for address in addresses {
+            let address = address.to_socket_addrs()?
+                .next()
+                .ok_or_else(|| io::Error::new(ErrorKind::InvalidInput, "the address couldn't be resolved"))?;
+            workers.push(Worker { address, connection: None, busy: false, skipped_until: None });
+        }
+
+        Ok(DistributedFitness {
+            format: LineFormat::Csv,
+            encode: Box::new(encode),
+            timeout: None,
+            connect_timeout: Duration::from_secs(5),
+            retry_delay: Duration::from_secs(1),
+            attempts: 3,
+            workers: Mutex::new(workers),
+            available: Condvar::new(),
+            fitness: PhantomData,
+        })
+    }
+
+    /// Sets the way the agents are written
+    pub fn format(mut self, format: LineFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Sets how long sending an agent and waiting for its fitness can take, before it's sent to another worker
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Sets how long connecting to a worker is tried (default: 5 seconds)
+    pub fn connect_timeout(mut self, connect_timeout: Duration) -> Self {
+        self.connect_timeout = connect_timeout;
+        self
+    }
+
+    /// Sets how long unreachable workers and stragglers are skipped (default: 1 second)
+    pub fn retry_delay(mut self, retry_delay: Duration) -> Self {
+        self.retry_delay = retry_delay;
+        self
+    }
+
+    /// Sets how many times an agent is sent to a worker before the evaluation fails (default: 3)
+    ///
+    /// # Panics:
+    ///  If attempts is 0
+    pub fn attempts(mut self, attempts: usize) -> Self {
+        assert!(attempts > 0, "at least one attempt is needed");
+        self.attempts = attempts;
+        self
+    }
+
+    /// Evaluates an agent on one of the workers (waits for an idle one if all of them are busy)
+    ///
+    /// # Errors:
+    ///  The error of the last attempt if none of the attempts succeeded, or NoWorkers if there are no workers
+    pub fn evaluate(&self, agent: &T) -> Result<F, DistributedError>
+        where F: FromStr {
+        let line = (self.encode)(agent, self.format);
+        let mut error = DistributedError::NoWorkers;
+        for _ in 0..self.attempts {
+            let (index, address, connection) = match self.acquire() {
+                Some(worker) => worker,
+                None => break,
+            };
+            let connection = match connection {
+                Some(connection) => Ok(connection),
+                None => Connection::open(address, self.connect_timeout, self.timeout),
+            };
+            match connection.and_then(|mut connection| connection.evaluate(&line, self.timeout).map(|answer| (connection, answer))) {
+                Ok((connection, answer)) => {
+                    self.release(index, Some(connection), false);
+                    return answer.trim().parse().map_err(|_| DistributedError::Parse(answer));
+                }
+                // the connection can't be trusted to answer the next agent, it's reopened later
+                Err(failure) => {
+                    let skip = matches!(failure, DistributedError::Connect(_) | DistributedError::Timeout(_));
+                    self.release(index, None, skip);
+                    error = failure;
+                }
+            }
+        }
+
+        Err(error)
+    }
+
+    /// Takes an idle worker that isn't skipped, or waits for one (None if there are no workers)
+    fn acquire(&self) -> Option<(usize, SocketAddr, Option<Connection>)> {
+        let mut workers = self.workers.lock().expect("a thread panicked while using the worker pool");
+        if workers.is_empty() {
+            return None;
+        }
+        loop {
+            let now = Instant::now();
+            let idle = workers.iter().position(|worker| !worker.busy && worker.skipped_until.is_none_or(|until| until <= now));
+            if let Some(index) = idle {
+                let worker = &mut workers[index];
+                worker.busy = true;
+                return Some((index, worker.address, worker.connection.take()));
+            }
+            // if every idle worker is skipped, the first one that can be retried is waited for
+            let retry = workers.iter().filter(|worker| !worker.busy).filter_map(|worker| worker.skipped_until).min();
+            workers = match retry {
+                Some(until) => self.available.wait_timeout(workers, until - now).expect("a thread panicked while using the worker pool").0,
+                None => self.available.wait(workers).expect("a thread panicked while using the worker pool"),
+            };
+        }
+    }
+
+    /// Puts the worker back into the pool (with None if its connection was dropped)
+    fn release(&self, index: usize, connection: Option<Connection>, skip: bool) {
+        let mut workers = self.workers.lock().expect("a thread panicked while using the worker pool");
+        let worker = &mut workers[index];
+        worker.busy = false;
+        worker.connection = connection;
+        worker.skipped_until = if skip { Some(Instant::now() + self.retry_delay) } else { None };
+        self.available.notify_one();
+    }
+}
+
+impl Connection {
+    /// Connects to the worker
+    fn open(address: SocketAddr, connect_timeout: Duration, timeout: Option<Duration>) -> Result<Connection, DistributedError> {
+        let writer = TcpStream::connect_timeout(&address, connect_timeout).map_err(DistributedError::Connect)?;
+        writer.set_nodelay(true)
+            .and_then(|_| writer.set_read_timeout(timeout))
+            .and_then(|_| writer.set_write_timeout(timeout))
+            .map_err(DistributedError::Connect)?;
+        let reader = BufReader::new(writer.try_clone().map_err(DistributedError::Connect)?);
+
+        Ok(Connection { writer, reader })
+    }
+
+    /// Writes the line of an agent and returns the answer of the worker
+    fn evaluate(&mut self, line: &str, timeout: Option<Duration>) -> Result<String, DistributedError> {
+        let failed = |error: io::Error| match error.kind() {
+            ErrorKind::WouldBlock | ErrorKind::TimedOut => DistributedError::Timeout(timeout.unwrap_or_default()),
+            _ => DistributedError::Io(error),
+        };
+        writeln!(self.writer, "{}", line).and_then(|_| self.writer.flush()).map_err(failed)?;
+        let mut answer = String::new();
+        match self.reader.read_line(&mut answer) {
+            Ok(0) => Err(DistributedError::Disconnected),
+            Ok(_) => Ok(answer),
+            Err(error) => Err(failed(error)),
+        }
+    }
+}
+
+/// Runs a worker: accepts the connections of DistributedFitness evaluators, and answers every line (agent)
+/// with the result of the function on its own line (every connection is served on its own thread)
+///
+/// It returns only if accepting a connection fails.
+///
+/// # Arguments:
+///
+/// * `listener` the socket the worker listens on
+/// * `evaluate` the fitness function, it gets the line of the agent (without the line break)
+///
+/// # Examples:
+/// ```no_run
+/// use std::net::TcpListener;
+/// use bbte_optim_tzim1773_genetic::distributed;
+///
+/// fn main() -> std::io::Result<()> {
+///     let listener = TcpListener::bind("0.0.0.0:7878")?;
+///     distributed::serve(listener, |line| {
+///         let agent = line.split(',').map(|x| x.parse::<f64>().unwrap()).collect::<Vec<f64>>();
+///         -agent.iter().map(|x| x * x).sum::<f64>()
+///     })
+/// }
+/// ```
+pub fn serve<F: Display>(listener: TcpListener, evaluate: impl Fn(&str) -> F + Send + Sync + 'static) -> io::Result<()> {
+    let evaluate = Arc::new(evaluate);
+    loop {
+        let (stream, _) = listener.accept()?;
+        let evaluate = Arc::clone(&evaluate);
+        thread::spawn(move || -> io::Result<()> {
+            stream.set_nodelay(true)?;
+            let mut writer = stream.try_clone()?;
+            for line in BufReader::new(stream).lines() {
+                writeln!(writer, "{}", evaluate(&line?))?;
+            }
+            Ok(())
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Genetic;
+    use rand::Rng;
+
+    fn worker(delay: Duration) -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        thread::spawn(move || {
+            serve(listener, move |line| {
+                thread::sleep(delay);
+                let x = line.parse::<i64>().unwrap();
+                2500 - x * x
+            })
+        });
+        address
+    }
+
+    fn unreachable() -> SocketAddr {
+        // the port is free again after the listener is dropped
+        TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap()
+    }
+
+    #[test]
+    fn distributed_fitness() {
+        let fitness = DistributedFitness::<i64>::new(vec![unreachable(), worker(Duration::ZERO)])
+            .unwrap()
+            .timeout(Duration::from_secs(10));
+        assert_eq!(fitness.evaluate(&20).unwrap(), 2100.0);
+
+        let test = Genetic::builder()
+            .population(20)
+            .max_generation(50)
+            .random_agent(|rng| rng.gen_range(-50, 50))
+            .distributed_fitness(fitness)
+            .mutate(|a: &i64, rng| *a + rng.gen_range(-2, 3))
+            .offspring(|a: &i64, b: &i64, _rng| (*a + *b) / 2)
+            .build()
+            .unwrap();
+        assert_eq!(test.run_with_seed(1).best, 0);
+    }
+
+    #[test]
+    fn stragglers() {
+        let fitness = DistributedFitness::<i64>::new(vec![worker(Duration::from_secs(5)), worker(Duration::ZERO)])
+            .unwrap()
+            .timeout(Duration::from_millis(200))
+            .retry_delay(Duration::from_secs(60));
+        // the slow worker is tried first, then it's skipped
+        assert_eq!(fitness.evaluate(&10).unwrap(), 2400.0);
+        assert_eq!(fitness.evaluate(&30).unwrap(), 1600.0);
+
+        let nobody = DistributedFitness::<i64>::new(vec![unreachable()]).unwrap().retry_delay(Duration::from_millis(10));
+        assert!(matches!(nobody.evaluate(&10), Err(DistributedError::Connect(_))));
+        let none = DistributedFitness::<i64>::new(Vec::<SocketAddr>::new()).unwrap();
+        assert!(matches!(none.evaluate(&10), Err(DistributedError::NoWorkers)));
+    }
+}
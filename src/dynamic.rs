@@ -0,0 +1,224 @@
+use rand::prelude::*;
+
+use crate::error::EvaluationBudget;
+use crate::{ConfigError, Fitness, FitnessError, Genetic, GeneticState, LandscapeChange, MaybeSync};
+
+/// Support of time-varying fitness functions: the changes of the environment are detected by re-evaluating a few
+/// sentinel agents after every generation (or they are signalled with a LandscapeChange), and the run responds to
+/// them
+///
+/// After a change the fitness cache is cleared and the population is re-evaluated, then the worst agents are
+/// replaced by the remembered optima of the earlier environments (the best agent before every change, at most
+/// `memory` of them) and by `randomize` part of the population of random agents, and a burst of the hypermutation
+/// can be started. The sentinels are agents of the population, taken again after every change, and their
+/// re-evaluations count as evaluations (they are skipped when they don't fit into max_evaluations).
+///
+/// # Examples:
+/// ```
+/// use rand::prelude::*;
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+/// use bbte_optim_tzim1773_genetic::{DynamicEnvironment, Genetic};
+///
+/// fn main() {
+///     // the optimum moves every 500 evaluations
+///     let evaluations = AtomicUsize::new(0);
+///     let test = Genetic::builder()
+///         .max_generation(100)
+///         .random_agent(|rng| rng.gen_range(-10.0, 10.0))
+///         .fitness(|a: &f64| {
+///             let optimum = (evaluations.fetch_add(1, Ordering::Relaxed) / 500) as f64;
+///             -(a - optimum) * (a - optimum)
+///         })
+///         .mutate(|a: &f64, rng| *a + rng.gen_range(-0.1, 0.1))
+///         .offspring(|a: &f64, b: &f64, _rng| (*a + *b) / 2.0)
+///         .dynamic_environment(DynamicEnvironment::new(3).randomize(0.3).memory(5))
+///         .build()
+///         .unwrap();
+///
+///     println!("{}", test.run().best); // close to the last optimum
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct DynamicEnvironment {
+    /// The number of sentinel agents re-evaluated after every generation, a change is detected if the fitness of
+    /// one of them changed (with 0 the changes have to be signalled)
+    /// Suggested value: 3
+    pub sentinels: usize,
+    /// If it's given, a change is detected when it's signalled too
+    pub change: Option<LandscapeChange>,
+    /// The part of the population (0.0 - 1.0) replaced by random agents after a change
+    /// Suggested value: 0.3
+    pub randomize: f64,
+    /// The number of optima of the earlier environments remembered and put back into the population after a change
+    /// Suggested value: 0 (or the number of environments that come back in a cyclic problem)
+    pub memory: usize,
+    /// Whether a change starts a burst of the hypermutation of the Genetic
+    pub hypermutation: bool,
+}
+
+impl DynamicEnvironment {
+    /// Detects the changes with the given number of sentinels (the only response is re-evaluating the population)
+    pub fn new(sentinels: usize) -> Self {
+        DynamicEnvironment { sentinels, change: None, randomize: 0.0, memory: 0, hypermutation: false }
+    }
+
+    /// Detects a change when it's signalled too
+    pub fn on_change(mut self, change: LandscapeChange) -> Self {
+        self.change = Some(change);
+        self
+    }
+
+    /// Sets the part of the population replaced by random agents after a change
+    pub fn randomize(mut self, randomize: f64) -> Self {
+        self.randomize = randomize;
+        self
+    }
+
+    /// Sets the number of remembered optima
+    pub fn memory(mut self, memory: usize) -> Self {
+        self.memory = memory;
+        self
+    }
+
+    /// Starts a burst of the hypermutation after a change
+    pub fn hypermutation(mut self) -> Self {
+        self.hypermutation = true;
+        self
+    }
+
+    /// Checks whether the parameters are usable (`hypermutation` tells whether the Genetic has one)
+    pub fn validate(&self, hypermutation: bool) -> Result<(), ConfigError> {
+        if self.sentinels == 0 && self.change.is_none() {
+            return Err(ConfigError::InvalidDynamicEnvironment("the changes need sentinels or a signal to be detected".to_string()));
+        }
+        if !(0.0..=1.0).contains(&self.randomize) {
+            return Err(ConfigError::InvalidDynamicEnvironment(format!("randomize must be between 0.0 and 1.0, got {}", self.randomize)));
+        }
+        if self.hypermutation && !hypermutation {
+            return Err(ConfigError::InvalidDynamicEnvironment("the hypermutation response needs the hypermutation of the Genetic".to_string()));
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a, T, F> Genetic<'a, T, F>
+    where T: Clone + MaybeSync, F: Fitness + MaybeSync {
+    /// Detects whether the environment changed and responds to it, returns true if it changed (at the end of a
+    /// generation)
+    pub(crate) fn track_environment<R: RngCore>(&self, state: &mut GeneticState<T, F, R>) -> Result<bool, FitnessError> {
+        let dynamic = match &self.dynamic_environment {
+            Some(dynamic) if !state.population.is_empty() => dynamic,
+            _ => return Ok(false),
+        };
+        let signalled = dynamic.change.as_ref().is_some_and(LandscapeChange::take);
+        if !(self.sentinels_changed(dynamic, state)? || signalled) {
+            return Ok(false);
+        }
+
+        self.respond(dynamic, state)
+    }
+
+    /// Re-evaluates the sentinels (bypassing the cache), returns true if the fitness of one of them changed
+    fn sentinels_changed<R>(&self, dynamic: &DynamicEnvironment, state: &mut GeneticState<T, F, R>) -> Result<bool, FitnessError> {
+        if state.sentinels.is_empty() {
+            state.sentinels = state.population.iter().take(dynamic.sentinels).cloned().collect();
+            return Ok(false);
+        }
+        if self.max_evaluations.is_some_and(|max_evaluations| state.evaluations + state.sentinels.len() > max_evaluations) {
+            return Ok(false);
+        }
+        let mut changed = false;
+        for sentinel in &mut state.sentinels {
+            let fitness = self.call_fitness(&sentinel.agent, &mut state.evaluations)?;
+            changed |= fitness != sentinel.fitness;
+            sentinel.fitness = fitness;
+        }
+
+        Ok(changed)
+    }
+
+    /// Re-evaluates the population and replaces its worst agents by the remembered optima and by random agents,
+    /// returns false if the evaluations don't fit into max_evaluations (the population is kept then)
+    fn respond<R: RngCore>(&self, dynamic: &DynamicEnvironment, state: &mut GeneticState<T, F, R>) -> Result<bool, FitnessError> {
+        if let Some(cache) = &self.fitness_cache {
+            cache.clear();
+        }
+        let rng: &mut dyn RngCore = &mut state.rng;
+        let randomized = (dynamic.randomize * state.population.len() as f64).round() as usize;
+        let agents = state.population.iter()
+            .map(|x| x.agent.clone())
+            .chain(state.optima.iter().cloned())
+            .chain((0..randomized).map(|_| (self.get_random_agent)(rng)))
+            .map(|x| (x, None))
+            .collect();
+        let mut evaluated = match self.evaluate_offspring(agents, &mut state.evaluations) {
+            Ok(evaluated) => evaluated,
+            Err(error) if error.is::<EvaluationBudget>() => return Ok(false),
+            Err(error) => return Err(error),
+        };
+        let newcomers = evaluated.split_off(state.population.len());
+        if dynamic.memory > 0 {
+            state.optima.push(state.population[self.best_index(&state.population)].agent.clone());
+            if state.optima.len() > dynamic.memory {
+                state.optima.remove(0);
+            }
+        }
+        self.replace_worst(&mut evaluated, newcomers);
+        state.population = evaluated;
+        state.sentinels = state.population.iter().take(dynamic.sentinels).cloned().collect();
+        state.changes += 1;
+        #[cfg(feature = "tracing")]
+        tracing::info!(generation = state.generation, changes = state.changes, "environment changed");
+
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    #[test]
+    fn environment_changes() {
+        assert!(DynamicEnvironment::new(0).validate(false).is_err());
+        assert!(DynamicEnvironment::new(3).randomize(1.5).validate(false).is_err());
+        assert!(DynamicEnvironment::new(3).hypermutation().validate(false).is_err());
+
+        // the optimum jumps from 0 to 100 when the flag is set
+        let moved = AtomicBool::new(false);
+        let test = Genetic::builder()
+            .population(20)
+            .max_generation(10)
+            .random_agent(|rng| rng.gen_range(-10, 10))
+            .fitness(|a: &i32| {
+                let optimum = if moved.load(Ordering::Relaxed) { 100 } else { 0 };
+                -(a - optimum).abs() as f64
+            })
+            .mutate(|a: &i32, rng| *a + rng.gen_range(-1, 2))
+            .offspring(|a: &i32, b: &i32, _rng| (*a + *b) / 2)
+            .cache(1000)
+            .dynamic_environment(DynamicEnvironment::new(2).memory(3))
+            .build()
+            .unwrap();
+
+        let mut state = test.start_with_rng(StdRng::seed_from_u64(1)).unwrap();
+        test.step(&mut state).unwrap();
+        test.step(&mut state).unwrap();
+        assert_eq!(state.changes(), 0);
+        moved.store(true, Ordering::Relaxed);
+        test.step(&mut state).unwrap();
+        assert_eq!(state.changes(), 1);
+        // the population was re-evaluated (not read from the cache)
+        assert!(state.population().iter().all(|x| x.fitness < -80.0));
+        assert_eq!(state.optima.len(), 1);
+
+        // the remembered optimum comes back when the environment does
+        moved.store(false, Ordering::Relaxed);
+        test.step(&mut state).unwrap();
+        assert_eq!(state.changes(), 2);
+        let best = &state.population()[test.best_index(state.population())];
+        assert!(best.fitness > -10.0);
+    }
+}
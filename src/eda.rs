@@ -0,0 +1,473 @@
+//! Estimation-of-distribution algorithms: instead of crossing and mutating agents, a probabilistic model is learned
+//! from the best agents of every generation, and the next generation is sampled from it
+//!
+//! Pbil, Umda and CompactGa learn the probability of every bit of BitString genomes, GaussianUmda a normal
+//! distribution of every value of real vector (Vec<f64>) genomes. An Eda runs a model on the problem of a Genetic:
+//! the first generation, the fitness, the population and the stopping criteria come from the Genetic (the run stops
+//! at max_generation, max_evaluations, the target fitness, the stagnation, the time limit, the stop condition, the
+//! cancellation or the callbacks), the samples are evaluated like its offspring (in parallel with the rayon feature)
+//! and repaired with its repair(), and the result is a RunResult with the statistics of every generation. The best
+//! of the result is the best agent sampled during the run, the population is the last generation.
+//!
+//! # Examples:
+//! ```
+//! use rand::prelude::*;
+//! use bbte_optim_tzim1773_genetic::Genetic;
+//! use bbte_optim_tzim1773_genetic::eda::{Eda, Umda};
+//! use bbte_optim_tzim1773_genetic::genome::BitString;
+//!
+//! fn main() {
+//!     // OneMax: the number of ones is maximised
+//!     let genetic = Genetic::builder()
+//!         .population(50)
+//!         .max_generation(100)
+//!         .random_agent(|rng| BitString::random(40, rng))
+//!         .fitness(|a: &BitString| a.count_ones() as f64)
+//!         .mutate(|a: &BitString, _rng| a.clone())
+//!         .offspring(|a: &BitString, _b: &BitString, _rng| a.clone())
+//!         .build()
+//!         .unwrap();
+//!
+//!     let result = Eda::new(genetic, Umda::new(40, 0.5)).unwrap().run();
+//!     println!("{}", result.best_fitness); // should be 40
+//! }
+//! ```
+use rand::prelude::*;
+use std::sync::Arc;
+use std::time::Instant;
+
+use crate::engine::Engine;
+use crate::genome::{BitString, Bounds};
+use crate::operators::standard_normal;
+use crate::{ConfigError, Fitness, Genetic, MaybeSync, RunError, RunResult, Termination};
+
+/// A probabilistic model of the good agents of type T
+pub trait Model<T>: Clone {
+    /// Returns the number of agents sampled in a generation with the given population (the population by default)
+    fn samples(&self, population: usize) -> usize {
+        population
+    }
+    /// Samples a new agent from the model
+    fn sample(&self, rng: &mut dyn RngCore) -> T;
+    /// Learns from the agents of a generation (the best one first)
+    fn update(&mut self, ranked: &[&T], rng: &mut dyn RngCore);
+    /// Checks whether the parameters are usable
+    fn validate(&self) -> Result<(), ConfigError> {
+        Ok(())
+    }
+}
+
+/// Samples a bit string from the probabilities of its bits
+fn sample_bits(probabilities: &[f64], rng: &mut dyn RngCore) -> BitString {
+    let mut x = BitString::zeros(probabilities.len());
+    for (i, p) in probabilities.iter().enumerate() {
+        x.set(i, rng.gen::<f64>() < *p);
+    }
+    x
+}
+
+/// Returns the number of the best agents a model learns from (at least 1)
+fn truncation(selected: f64, count: usize) -> usize {
+    ((selected * count as f64).ceil() as usize).clamp(1, count.max(1))
+}
+
+/// Population-based incremental learning: the probability vector of the bits is moved towards the best agents of
+/// every generation by the learning rate, and it's mutated a little to keep the diversity
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Pbil {
+    /// The probability of every bit being 1
+    pub probabilities: Vec<f64>,
+    /// The step towards the best agents (0.0 < learning_rate <= 1.0)
+    /// Suggested value: 0.1
+    pub learning_rate: f64,
+    /// The number of the best agents of a generation learned from (at least 1)
+    /// Suggested value: 1
+    pub selected: usize,
+    /// The probability of mutating a probability (0.0 - 1.0)
+    /// Suggested value: 0.02
+    pub mutation: f64,
+    /// The step of a mutation towards 0 or 1 (0.0 - 1.0)
+    /// Suggested value: 0.05
+    pub shift: f64,
+}
+
+impl Pbil {
+    /// Starts from 0.5 for every bit, learns from the best agent with a learning rate of 0.1, mutates 2% of the
+    /// probabilities by 0.05
+    pub fn new(length: usize) -> Self {
+        Pbil { probabilities: vec![0.5; length], learning_rate: 0.1, selected: 1, mutation: 0.02, shift: 0.05 }
+    }
+
+    /// Sets the step towards the best agents
+    pub fn learning_rate(mut self, learning_rate: f64) -> Self {
+        self.learning_rate = learning_rate;
+        self
+    }
+
+    /// Sets the number of the best agents learned from
+    pub fn selected(mut self, selected: usize) -> Self {
+        self.selected = selected;
+        self
+    }
+
+    /// Sets the probability and the step of the mutation of the probabilities
+    pub fn mutation(mut self, mutation: f64, shift: f64) -> Self {
+        self.mutation = mutation;
+        self.shift = shift;
+        self
+    }
+}
+
+impl Model<BitString> for Pbil {
+    fn sample(&self, rng: &mut dyn RngCore) -> BitString {
+        sample_bits(&self.probabilities, rng)
+    }
+
+    fn update(&mut self, ranked: &[&BitString], rng: &mut dyn RngCore) {
+        for x in ranked.iter().take(self.selected) {
+            for (i, p) in self.probabilities.iter_mut().enumerate() {
+                let bit = if i < x.len() && x.get(i) { 1.0 } else { 0.0 };
+                *p += self.learning_rate * (bit - *p);
+            }
+        }
+        for p in &mut self.probabilities {
+            if rng.gen::<f64>() < self.mutation {
+                let bit = if rng.gen::<bool>() { 1.0 } else { 0.0 };
+                *p += self.shift * (bit - *p);
+            }
+        }
+    }
+
+    fn validate(&self) -> Result<(), ConfigError> {
+        if !(self.learning_rate > 0.0 && self.learning_rate <= 1.0) {
+            return Err(ConfigError::InvalidEda(format!("the learning rate must be bigger than 0.0 and at most 1.0, got {}", self.learning_rate)));
+        }
+        if self.selected == 0 {
+            return Err(ConfigError::InvalidEda("PBIL has to learn from at least 1 agent".to_string()));
+        }
+        if !(0.0..=1.0).contains(&self.mutation) || !(0.0..=1.0).contains(&self.shift) {
+            return Err(ConfigError::InvalidEda(format!("the mutation and the shift must be between 0.0 and 1.0, got {} and {}", self.mutation, self.shift)));
+        }
+
+        Ok(())
+    }
+}
+
+/// Univariate marginal distribution algorithm: the probability of every bit is its frequency in the best
+/// `selected` part of the generation, kept between 1 / length and 1 - 1 / length so that no bit is lost for good
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Umda {
+    /// The probability of every bit being 1
+    pub probabilities: Vec<f64>,
+    /// The part of the generation learned from (0.0 < selected <= 1.0)
+    /// Suggested value: 0.5
+    pub selected: f64,
+}
+
+impl Umda {
+    /// Starts from 0.5 for every bit and learns from the best `selected` part of the generations
+    pub fn new(length: usize, selected: f64) -> Self {
+        Umda { probabilities: vec![0.5; length], selected }
+    }
+}
+
+impl Model<BitString> for Umda {
+    fn sample(&self, rng: &mut dyn RngCore) -> BitString {
+        sample_bits(&self.probabilities, rng)
+    }
+
+    fn update(&mut self, ranked: &[&BitString], _rng: &mut dyn RngCore) {
+        let best = &ranked[..truncation(self.selected, ranked.len()).min(ranked.len())];
+        let margin = 1.0 / self.probabilities.len().max(2) as f64;
+        for (i, p) in self.probabilities.iter_mut().enumerate() {
+            let ones = best.iter().filter(|x| i < x.len() && x.get(i)).count();
+            *p = (ones as f64 / best.len().max(1) as f64).clamp(margin, 1.0 - margin);
+        }
+    }
+
+    fn validate(&self) -> Result<(), ConfigError> {
+        if !(self.selected > 0.0 && self.selected <= 1.0) {
+            return Err(ConfigError::InvalidEda(format!("selected must be bigger than 0.0 and at most 1.0, got {}", self.selected)));
+        }
+
+        Ok(())
+    }
+}
+
+/// Compact genetic algorithm: simulates a GA with a population of `population` agents by a probability vector, two
+/// agents are sampled in every generation and the probabilities of the bits where they differ move by
+/// 1 / population towards the winner
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CompactGa {
+    /// The probability of every bit being 1
+    pub probabilities: Vec<f64>,
+    /// The size of the simulated population (at least 2), bigger is slower but more reliable
+    /// Suggested value: about the length of the genome
+    pub population: usize,
+}
+
+impl CompactGa {
+    /// Starts from 0.5 for every bit and simulates the given population size
+    pub fn new(length: usize, population: usize) -> Self {
+        CompactGa { probabilities: vec![0.5; length], population }
+    }
+}
+
+impl Model<BitString> for CompactGa {
+    fn samples(&self, _population: usize) -> usize {
+        2
+    }
+
+    fn sample(&self, rng: &mut dyn RngCore) -> BitString {
+        sample_bits(&self.probabilities, rng)
+    }
+
+    fn update(&mut self, ranked: &[&BitString], _rng: &mut dyn RngCore) {
+        let (winner, loser) = match ranked {
+            [winner, .., loser] => (winner, loser),
+            _ => return,
+        };
+        let step = 1.0 / self.population as f64;
+        for (i, p) in self.probabilities.iter_mut().enumerate().take(winner.len().min(loser.len())) {
+            match (winner.get(i), loser.get(i)) {
+                (true, false) => *p = (*p + step).min(1.0),
+                (false, true) => *p = (*p - step).max(0.0),
+                _ => {}
+            }
+        }
+    }
+
+    fn validate(&self) -> Result<(), ConfigError> {
+        if self.population < 2 {
+            return Err(ConfigError::InvalidEda(format!("the compact GA has to simulate at least 2 agents, got {}", self.population)));
+        }
+
+        Ok(())
+    }
+}
+
+/// Gaussian univariate marginal distribution algorithm: every value is sampled from a normal distribution, whose
+/// mean and standard deviation are estimated from the best `selected` part of the generation (the values are
+/// clamped to their bounds)
+#[derive(Debug, Clone, PartialEq)]
+pub struct GaussianUmda {
+    /// The bounds of the values
+    pub bounds: Arc<Bounds>,
+    /// The mean of every value
+    pub means: Vec<f64>,
+    /// The standard deviation of every value
+    pub sigmas: Vec<f64>,
+    /// The part of the generation learned from (0.0 < selected <= 1.0)
+    /// Suggested value: 0.5
+    pub selected: f64,
+    /// The smallest standard deviation relative to the interval of the dimension, it keeps the search from
+    /// collapsing too early
+    /// Suggested value: 1e-6
+    pub min_sigma: f64,
+}
+
+impl GaussianUmda {
+    /// Starts from the middle of the bounds with a quarter of the intervals as standard deviations
+    pub fn new(bounds: Arc<Bounds>, selected: f64) -> Self {
+        let means = (0..bounds.len()).map(|i| (bounds.lower[i] + bounds.upper[i]) / 2.0).collect();
+        let sigmas = (0..bounds.len()).map(|i| bounds.range(i) / 4.0).collect();
+        GaussianUmda { bounds, means, sigmas, selected, min_sigma: 1e-6 }
+    }
+
+    /// Sets the smallest standard deviation
+    pub fn min_sigma(mut self, min_sigma: f64) -> Self {
+        self.min_sigma = min_sigma;
+        self
+    }
+}
+
+impl Model<Vec<f64>> for GaussianUmda {
+    fn sample(&self, rng: &mut dyn RngCore) -> Vec<f64> {
+        (0..self.bounds.len())
+            .map(|i| (self.means[i] + self.sigmas[i] * standard_normal(rng)).clamp(self.bounds.lower[i], self.bounds.upper[i]))
+            .collect()
+    }
+
+    fn update(&mut self, ranked: &[&Vec<f64>], _rng: &mut dyn RngCore) {
+        let best = &ranked[..truncation(self.selected, ranked.len()).min(ranked.len())];
+        if best.is_empty() {
+            return;
+        }
+        let n = best.len() as f64;
+        for i in 0..self.bounds.len() {
+            let mean = best.iter().map(|x| x[i]).sum::<f64>() / n;
+            let variance = best.iter().map(|x| (x[i] - mean) * (x[i] - mean)).sum::<f64>() / n;
+            self.means[i] = mean;
+            self.sigmas[i] = variance.sqrt().max(self.min_sigma * self.bounds.range(i));
+        }
+    }
+
+    fn validate(&self) -> Result<(), ConfigError> {
+        if !(self.selected > 0.0 && self.selected <= 1.0) {
+            return Err(ConfigError::InvalidEda(format!("selected must be bigger than 0.0 and at most 1.0, got {}", self.selected)));
+        }
+        if self.min_sigma.is_nan() || self.min_sigma < 0.0 {
+            return Err(ConfigError::InvalidEda(format!("min_sigma can't be negative, got {}", self.min_sigma)));
+        }
+
+        Ok(())
+    }
+}
+
+/// An estimation-of-distribution algorithm running a model on the problem of a Genetic (see the module)
+///
+/// The first generation is created by the Genetic (get_random_agent and initial_population) and the model learns
+/// from it, then every generation is sampled from the model. Its operators, selection and modes are not used.
+///
+/// # Examples:
+///  See at the module
+pub struct Eda<'a, T, M, F = f64> {
+    /// The Genetic giving the first generation, the fitness, the population and the stopping criteria
+    pub genetic: Genetic<'a, T, F>,
+    /// The model at the start of the runs (every run learns its own copy)
+    pub model: M,
+}
+
+impl<'a, T, M, F> Eda<'a, T, M, F>
+    where T: Clone + MaybeSync, M: Model<T>, F: Fitness + MaybeSync {
+    /// Creates an estimation-of-distribution algorithm, checking the model
+    ///
+    /// # Arguments:
+    ///
+    /// * `genetic` the first generation, the fitness, the population and the stopping criteria
+    /// * `model` the probabilistic model (Pbil, Umda, CompactGa, GaussianUmda or your own)
+    pub fn new(genetic: Genetic<'a, T, F>, model: M) -> Result<Self, ConfigError> {
+        model.validate()?;
+
+        Ok(Eda { genetic, model })
+    }
+
+    /// Runs the optimisation until one of the stopping criteria of the Genetic is met
+    ///
+    /// # Panics:
+    ///  If the fitness function fails and the fitness_error_policy gives up on the agent
+    ///  (use try_run() to get the error instead)
+    pub fn run(&self) -> RunResult<T, F> {
+        self.run_with_rng(&mut thread_rng())
+    }
+
+    /// Same as run(), but returns the error instead of panicking
+    pub fn try_run(&self) -> Result<RunResult<T, F>, RunError> {
+        self.try_run_with_rng(&mut thread_rng())
+    }
+
+    /// Same as run(), but uses the given random number generator
+    pub fn run_with_rng(&self, rng: &mut dyn RngCore) -> RunResult<T, F> {
+        match self.try_run_with_rng(rng) {
+            Ok(result) => result,
+            Err(error) => panic!("{}", error),
+        }
+    }
+
+    /// Same as run(), but uses a random number generator seeded with the given seed, so the runs can be reproduced
+    pub fn run_with_seed(&self, seed: u64) -> RunResult<T, F> {
+        self.run_with_rng(&mut StdRng::seed_from_u64(seed))
+    }
+
+    /// Same as run_with_rng(), but returns the error instead of panicking
+    pub fn try_run_with_rng(&self, rng: &mut dyn RngCore) -> Result<RunResult<T, F>, RunError> {
+        let start = Instant::now();
+        let genetic = &self.genetic;
+        let mut model = self.model.clone();
+        let initial = genetic.get_population(rng).into_iter().map(|x| (genetic.repair(x), None)).collect();
+        let (mut engine, mut population) = Engine::start(genetic, start, initial)?;
+        let mut best = population[genetic.best_index(&population)].clone();
+
+        let termination = loop {
+            let ranked = genetic.best_first(&population).into_iter().map(|i| &population[i].agent).collect::<Vec<&T>>();
+            model.update(&ranked, rng);
+            if let Some(termination) = engine.check_bound() {
+                break termination;
+            }
+
+            let samples = (0..model.samples(genetic.population)).map(|_| (genetic.repair(model.sample(rng)), None)).collect();
+            population = match engine.evaluate(samples)? {
+                Some(samples) => samples,
+                None => break Termination::EvaluationLimit,
+            };
+            let generation_best = &population[genetic.best_index(&population)];
+            if genetic.is_fitter(generation_best, &best) {
+                best = generation_best.clone();
+            }
+            if let Some(termination) = engine.finish_generation(&population)? {
+                break termination;
+            }
+        };
+
+        Ok(engine.result(best, population.into_iter().map(|x| x.agent).collect(), termination))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Objective;
+
+    fn one_max<'a>(population: usize) -> Genetic<'a, BitString> {
+        Genetic::builder()
+            .population(population)
+            .max_generation(200)
+            .random_agent(|rng| BitString::random(30, rng))
+            .fitness(|a: &BitString| a.count_ones() as f64)
+            .mutate(|a: &BitString, _rng| a.clone())
+            .offspring(|a: &BitString, _b: &BitString, _rng| a.clone())
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn models() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let (a, b) = (BitString::from_bools(&[true, true, false, false]), BitString::from_bools(&[true, false, true, false]));
+
+        let mut umda = Umda::new(4, 0.5);
+        umda.update(&[&a, &b, &b, &b], &mut rng);
+        assert_eq!(umda.probabilities, vec![0.75, 0.5, 0.5, 0.25]);
+        let mut pbil = Pbil::new(4).learning_rate(0.5).mutation(0.0, 0.0);
+        pbil.update(&[&a, &b], &mut rng);
+        assert_eq!(pbil.probabilities, vec![0.75, 0.75, 0.25, 0.25]);
+        let mut cga = CompactGa::new(4, 10);
+        cga.update(&[&a, &b], &mut rng);
+        assert_eq!(cga.probabilities, vec![0.5, 0.6, 0.4, 0.5]);
+        let mut gaussian = GaussianUmda::new(Bounds::uniform(1, -10.0, 10.0), 1.0);
+        gaussian.update(&[&vec![1.0], &vec![3.0]], &mut rng);
+        assert_eq!((gaussian.means[0], gaussian.sigmas[0]), (2.0, 1.0));
+
+        assert!(Umda::new(4, 0.0).validate().is_err());
+        assert!(Pbil::new(4).learning_rate(0.0).validate().is_err());
+        assert!(CompactGa::new(4, 1).validate().is_err());
+        assert!(GaussianUmda::new(Bounds::uniform(1, 0.0, 1.0), 0.5).min_sigma(-1.0).validate().is_err());
+        assert!(Eda::new(one_max(10), CompactGa::new(30, 0)).is_err());
+    }
+
+    #[test]
+    fn estimation_of_distribution() {
+        assert_eq!(Eda::new(one_max(50), Umda::new(30, 0.5)).unwrap().run_with_seed(1).best_fitness, 30.0);
+        assert_eq!(Eda::new(one_max(50), Pbil::new(30)).unwrap().run_with_seed(1).best_fitness, 30.0);
+        let cga = Eda::new(one_max(50), CompactGa::new(30, 30)).unwrap().run_with_seed(1);
+        assert_eq!(cga.best_fitness, 30.0);
+        assert_eq!((cga.population.len(), cga.evaluations), (2, 50 + 2 * 200));
+
+        let genetic = Genetic::builder()
+            .population(50)
+            .max_generation(100)
+            .objective(Objective::Minimize)
+            .random_agent(|rng| (0..3).map(|_| rng.gen_range(-10.0, 10.0)).collect())
+            .fitness(|a: &Vec<f64>| a.iter().map(|x| (x - 1.0) * (x - 1.0)).sum::<f64>())
+            .mutate(|a: &Vec<f64>, _rng| a.clone())
+            .offspring(|a: &Vec<f64>, _b: &Vec<f64>, _rng| a.clone())
+            .build()
+            .unwrap();
+        let result = Eda::new(genetic, GaussianUmda::new(Bounds::uniform(3, -10.0, 10.0), 0.3)).unwrap().run_with_seed(1);
+        assert!(result.best.iter().all(|x| (x - 1.0).abs() < 1e-3), "{:?}", result.best);
+        assert_eq!(result.history.len(), 100);
+    }
+}
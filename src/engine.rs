@@ -0,0 +1,126 @@
+use std::time::Instant;
+
+use crate::termination::Progress;
+use crate::{CancellationToken, Evaluated, Fitness, GenerationStats, Genetic, MaybeSync, Offspring, RunError, RunResult, Termination};
+
+/// The bookkeeping of the optimisers that run on the problem of a Genetic instead of its generations (differential
+/// evolution, particle swarm, estimation of distribution): the evaluations, the stopping criteria, the statistics,
+/// the run log, the callbacks and the archive of the Genetic
+pub(crate) struct Engine<'g, 'a, T, F> {
+    genetic: &'g Genetic<'a, T, F>,
+    /// The number of times the fitness function was called
+    pub evaluations: usize,
+    /// The number of finished generations
+    pub generation: usize,
+    progress: Progress,
+    history: Vec<GenerationStats>,
+    archive: Vec<Evaluated<T, F>>,
+    /// Whether max_evaluations was used up in the middle of a generation
+    exhausted: bool,
+    /// The best agent of the generation that was cut short by max_evaluations
+    unfinished_best: Option<Evaluated<T, F>>,
+}
+
+impl<'g, 'a, T, F> Engine<'g, 'a, T, F>
+    where T: Clone + MaybeSync, F: Fitness + MaybeSync {
+    /// Evaluates the first generation and starts the run, its time is measured from `start` (before the first
+    /// generation was created); if the first generation doesn't fit into max_evaluations, it's cut short and the
+    /// run stops at the next check_bound()
+    pub fn start(genetic: &'g Genetic<'a, T, F>, start: Instant, initial: Vec<Offspring<T, F>>) -> Result<(Self, Vec<Evaluated<T, F>>), RunError> {
+        let mut engine = Engine {
+            genetic,
+            evaluations: 0,
+            generation: 0,
+            progress: Progress::new(start),
+            history: Vec::new(),
+            archive: Vec::new(),
+            exhausted: false,
+            unfinished_best: None,
+        };
+        let (population, exhausted) = genetic.evaluate_in_budget(initial, &mut engine.evaluations)?;
+        engine.exhausted = exhausted;
+        engine.archive(&population);
+
+        Ok((engine, population))
+    }
+
+    /// Returns the reason of stopping if max_evaluations was used up, the run was cancelled or it reached the last
+    /// generation
+    pub fn check_bound(&self) -> Option<Termination> {
+        if self.exhausted {
+            return Some(Termination::EvaluationLimit);
+        }
+        if self.genetic.cancellation.as_ref().is_some_and(CancellationToken::is_cancelled) {
+            return Some(Termination::Cancelled);
+        }
+        if self.generation >= self.genetic.generation_bound() {
+            return Some(Termination::MaxGeneration);
+        }
+        None
+    }
+
+    /// Evaluates the new agents of a generation, returns None if they don't fit into max_evaluations (the best of
+    /// the ones evaluated before it was used up can still become the best agent of the result)
+    pub fn evaluate(&mut self, offspring: Vec<Offspring<T, F>>) -> Result<Option<Vec<Evaluated<T, F>>>, RunError> {
+        let (evaluated, exhausted) = self.genetic.evaluate_in_budget(offspring, &mut self.evaluations)?;
+        self.archive(&evaluated);
+        if !exhausted {
+            return Ok(Some(evaluated));
+        }
+        self.exhausted = true;
+        if !evaluated.is_empty() {
+            self.unfinished_best = Some(evaluated[self.genetic.best_index(&evaluated)].clone());
+        }
+
+        Ok(None)
+    }
+
+    /// Finishes a generation: records the statistics of the population, then returns the reason of stopping if the
+    /// run has to stop
+    pub fn finish_generation(&mut self, population: &[Evaluated<T, F>]) -> Result<Option<Termination>, RunError> {
+        let genetic = self.genetic;
+        let stats = GenerationStats::new(population.iter().map(|x| &x.fitness), genetic.objective);
+        if let (Some(run_log), false) = (&genetic.run_log, population.is_empty()) {
+            run_log.record(self.generation, self.evaluations, &stats, &population[genetic.best_index(population)].agent).map_err(RunError::Log)?;
+        }
+        self.history.push(stats);
+        let g = self.generation;
+        self.generation += 1;
+        if genetic.notify(g, population, self.evaluations) {
+            return Ok(Some(Termination::Callback));
+        }
+        if population.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(self.progress.update(genetic, &population[genetic.best_index(population)].fitness, self.evaluations))
+    }
+
+    /// Returns the result of the run (the best agent of the generation cut short by max_evaluations replaces `best`
+    /// if it's fitter)
+    pub fn result(self, best: Evaluated<T, F>, population: Vec<T>, termination: Termination) -> RunResult<T, F> {
+        let best = match self.unfinished_best {
+            Some(unfinished_best) if self.genetic.is_fitter(&unfinished_best, &best) => unfinished_best,
+            _ => best,
+        };
+        RunResult {
+            best: best.agent,
+            best_fitness: best.fitness,
+            population,
+            generations: self.generation,
+            evaluations: self.evaluations,
+            mutation_stats: Vec::new(),
+            crossover_stats: Vec::new(),
+            termination,
+            history: self.history,
+            archive: self.archive,
+            restarts: 0,
+        }
+    }
+
+    fn archive(&mut self, candidates: &[Evaluated<T, F>]) {
+        if let Some(archive) = &self.genetic.archive {
+            archive.update(&mut self.archive, candidates, self.genetic.objective);
+        }
+    }
+}
@@ -0,0 +1,469 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::time::Duration;
+
+/// Error returned when the parameters of a Genetic are not usable
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigError {
+    /// The population has to hold at least two agents, so that parents can be paired
+    PopulationTooSmall(usize),
+    /// There has to be at least one generation
+    ZeroGenerations,
+    /// max_evaluations has to allow at least one evaluation
+    ZeroEvaluations,
+    /// The probability of crossover is not between 0.0 and 1.0
+    InvalidCrossoverProbability(f64),
+    /// The probability of mutation is not between 0.0 and 1.0
+    InvalidMutationProbability(f64),
+    /// More elites are kept than the size of the population
+    TooManyElites(usize),
+    /// More agents are given for the 0th generation than the size of the population
+    TooManyInitialAgents(usize),
+    /// The fraction of random immigrants is not at least 0.0 and less than 1.0
+    InvalidImmigrants(f64),
+    /// The parameters of the selection strategy are not usable (contains the reason)
+    InvalidSelection(String),
+    /// The parameters of the steady-state mode are not usable (contains the reason)
+    InvalidSteadyState(String),
+    /// The parameters of the evolution strategy are not usable (contains the reason)
+    InvalidEvolutionStrategy(String),
+    /// The grid of the cellular mode is not usable (contains the reason)
+    InvalidCellular(String),
+    /// The parameters of the adaptation of the probabilities are not usable (contains the reason)
+    InvalidAdaptation(String),
+    /// The parameters of a schedule are not usable (contains the reason)
+    InvalidSchedule(String),
+    /// The parameters of the operator selection are not usable (contains the reason)
+    InvalidOperatorSelection(String),
+    /// The parameters of a stopping criterion are not usable (contains the reason)
+    InvalidStopCondition(String),
+    /// The thread pool can't be created or the chunk size is not usable (contains the reason)
+    InvalidParallelism(String),
+    /// The parameters of the migration between islands are not usable (contains the reason)
+    InvalidMigration(String),
+    /// The populations or the opponent sampling of a coevolution are not usable (contains the reason)
+    InvalidCoevolution(String),
+    /// The radius or the shape of the fitness sharing is not usable (contains the reason)
+    InvalidSharing(String),
+    /// The radius or the niche capacity of the clearing is not usable (contains the reason)
+    InvalidClearing(String),
+    /// The parameters of the speciation are not usable (contains the reason)
+    InvalidSpeciation(String),
+    /// The parameters of the novelty search are not usable (contains the reason)
+    InvalidNovelty(String),
+    /// The grid or the batch of MAP-Elites is not usable (contains the reason)
+    InvalidMapElites(String),
+    /// The parameters of the multi-objective algorithm are not usable (contains the reason)
+    InvalidMultiObjective(String),
+    /// The interval or the portion of the local search is not usable (contains the reason)
+    InvalidLocalSearch(String),
+    /// The jumping rate of the opposition-based learning is not usable (contains the reason)
+    InvalidOpposition(String),
+    /// The model, the ratio or the capacity of the surrogate is not usable (contains the reason)
+    InvalidSurrogate(String),
+    /// The detection or the response of the dynamic environment is not usable (contains the reason)
+    InvalidDynamicEnvironment(String),
+    /// The factor, the length or the triggers of the hypermutation are not usable (contains the reason)
+    InvalidHypermutation(String),
+    /// The stagnation or the elites of the warm restarts are not usable (contains the reason)
+    InvalidRestart(String),
+    /// The penalties or the stochastic ranking of the constraints are not usable (contains the reason)
+    InvalidConstraintHandling(String),
+    /// The capacity of the archive of the best agents is not usable (contains the reason)
+    InvalidArchive(String),
+    /// The temperature or the cooling schedule of the simulated annealing is not usable (contains the reason)
+    InvalidAnnealing(String),
+    /// The neighbours of a hill climbing step are not usable (contains the reason)
+    InvalidHillClimbing(String),
+    /// The population, the weight or the crossover rate of the differential evolution is not usable (contains the reason)
+    InvalidDifferentialEvolution(String),
+    /// The swarm, the topology or the velocity update of the particle swarm is not usable (contains the reason)
+    InvalidParticleSwarm(String),
+    /// The model of the estimation-of-distribution algorithm is not usable (contains the reason)
+    InvalidEda(String),
+    /// The cost matrix or the evaporation of the ant colony is not usable (contains the reason)
+    InvalidAntColony(String),
+    /// The tenure of the tabu search is not usable (contains the reason)
+    InvalidTabuSearch(String),
+    /// The optimizers or the budget of the portfolio is not usable (contains the reason)
+    InvalidPortfolio(String),
+    /// The budget, the ranges or the outer genetic algorithm of the tuner is not usable (contains the reason)
+    InvalidTuning(String),
+    /// The settings or the number of runs of the experiment is not usable (contains the reason)
+    InvalidExperiment(String),
+    /// The data or the settings of the symbolic regression is not usable (contains the reason)
+    InvalidRegression(String),
+    /// The jobs, the machines or the processing times of a scheduling problem are not usable (contains the reason)
+    InvalidScheduling(String),
+    /// The cities, the items or the graph of a combinatorial benchmark problem is not usable (contains the reason)
+    InvalidBenchmark(String),
+    /// The food sources or the limit of the bee colony is not usable (contains the reason)
+    InvalidBeeColony(String),
+    /// The interval of the checkpoints is not usable (contains the reason)
+    #[cfg(feature = "serde")]
+    InvalidCheckpoint(String),
+    /// One of the functions was not given to the builder (contains the name of the function)
+    MissingFunction(&'static str),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::PopulationTooSmall(p) => write!(f, "population must be at least 2, got {}", p),
+            ConfigError::ZeroGenerations => write!(f, "max_generation must be at least 1"),
+            ConfigError::ZeroEvaluations => write!(f, "max_evaluations must be at least 1"),
+            ConfigError::InvalidCrossoverProbability(pc) => write!(f, "pc must be between 0.0 and 1.0, got {}", pc),
+            ConfigError::InvalidMutationProbability(pm) => write!(f, "pm must be between 0.0 and 1.0, got {}", pm),
+            ConfigError::TooManyElites(e) => write!(f, "elitism can't be bigger than the population, got {}", e),
+            ConfigError::TooManyInitialAgents(n) => write!(f, "initial_population can't be bigger than the population, got {}", n),
+            ConfigError::InvalidImmigrants(r) => write!(f, "random_immigrants must be at least 0.0 and less than 1.0, got {}", r),
+            ConfigError::InvalidSelection(reason) => write!(f, "invalid selection: {}", reason),
+            ConfigError::InvalidSteadyState(reason) => write!(f, "invalid steady state: {}", reason),
+            ConfigError::InvalidEvolutionStrategy(reason) => write!(f, "invalid evolution strategy: {}", reason),
+            ConfigError::InvalidCellular(reason) => write!(f, "invalid cellular grid: {}", reason),
+            ConfigError::InvalidAdaptation(reason) => write!(f, "invalid adaptation: {}", reason),
+            ConfigError::InvalidSchedule(reason) => write!(f, "invalid schedule: {}", reason),
+            ConfigError::InvalidOperatorSelection(reason) => write!(f, "invalid operator selection: {}", reason),
+            ConfigError::InvalidStopCondition(reason) => write!(f, "invalid stopping criterion: {}", reason),
+            ConfigError::InvalidParallelism(reason) => write!(f, "invalid parallelism: {}", reason),
+            ConfigError::InvalidMigration(reason) => write!(f, "invalid migration: {}", reason),
+            ConfigError::InvalidCoevolution(reason) => write!(f, "invalid coevolution: {}", reason),
+            ConfigError::InvalidSharing(reason) => write!(f, "invalid fitness sharing: {}", reason),
+            ConfigError::InvalidClearing(reason) => write!(f, "invalid clearing: {}", reason),
+            ConfigError::InvalidSpeciation(reason) => write!(f, "invalid speciation: {}", reason),
+            ConfigError::InvalidNovelty(reason) => write!(f, "invalid novelty search: {}", reason),
+            ConfigError::InvalidMapElites(reason) => write!(f, "invalid MAP-Elites: {}", reason),
+            ConfigError::InvalidMultiObjective(reason) => write!(f, "invalid multi-objective algorithm: {}", reason),
+            ConfigError::InvalidLocalSearch(reason) => write!(f, "invalid local search: {}", reason),
+            ConfigError::InvalidOpposition(reason) => write!(f, "invalid opposition-based learning: {}", reason),
+            ConfigError::InvalidSurrogate(reason) => write!(f, "invalid surrogate: {}", reason),
+            ConfigError::InvalidDynamicEnvironment(reason) => write!(f, "invalid dynamic environment: {}", reason),
+            ConfigError::InvalidHypermutation(reason) => write!(f, "invalid hypermutation: {}", reason),
+            ConfigError::InvalidRestart(reason) => write!(f, "invalid restart: {}", reason),
+            ConfigError::InvalidConstraintHandling(reason) => write!(f, "invalid constraint handling: {}", reason),
+            ConfigError::InvalidArchive(reason) => write!(f, "invalid archive: {}", reason),
+            ConfigError::InvalidAnnealing(reason) => write!(f, "invalid simulated annealing: {}", reason),
+            ConfigError::InvalidHillClimbing(reason) => write!(f, "invalid hill climbing: {}", reason),
+            ConfigError::InvalidDifferentialEvolution(reason) => write!(f, "invalid differential evolution: {}", reason),
+            ConfigError::InvalidParticleSwarm(reason) => write!(f, "invalid particle swarm: {}", reason),
+            ConfigError::InvalidEda(reason) => write!(f, "invalid estimation-of-distribution algorithm: {}", reason),
+            ConfigError::InvalidAntColony(reason) => write!(f, "invalid ant colony: {}", reason),
+            ConfigError::InvalidTabuSearch(reason) => write!(f, "invalid tabu search: {}", reason),
+            ConfigError::InvalidPortfolio(reason) => write!(f, "invalid portfolio: {}", reason),
+            ConfigError::InvalidTuning(reason) => write!(f, "invalid tuning: {}", reason),
+            ConfigError::InvalidExperiment(reason) => write!(f, "invalid experiment: {}", reason),
+            ConfigError::InvalidRegression(reason) => write!(f, "invalid symbolic regression: {}", reason),
+            ConfigError::InvalidScheduling(reason) => write!(f, "invalid scheduling problem: {}", reason),
+            ConfigError::InvalidBenchmark(reason) => write!(f, "invalid benchmark problem: {}", reason),
+            ConfigError::InvalidBeeColony(reason) => write!(f, "invalid bee colony: {}", reason),
+            #[cfg(feature = "serde")]
+            ConfigError::InvalidCheckpoint(reason) => write!(f, "invalid checkpoint: {}", reason),
+            ConfigError::MissingFunction(name) => write!(f, "missing function: {}", name),
+        }
+    }
+}
+
+impl Error for ConfigError {}
+
+/// Error returned by a fitness function that couldn't evaluate an agent
+pub type FitnessError = Box<dyn Error + Send + Sync>;
+
+/// What to do when the fitness function returns an error
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum FitnessErrorPolicy {
+    /// Stop the run and return the error
+    #[default]
+    Abort,
+    /// Give the agent the worst possible fitness, so it won't be selected
+    AssignWorst,
+    /// Call the fitness function again (at most the given number of times), then abort
+    Retry(usize),
+}
+
+/// Error that stopped a run
+#[derive(Debug)]
+pub enum RunError {
+    /// The fitness function failed and the FitnessErrorPolicy gave up on the agent
+    Fitness(FitnessError),
+    /// The statistics of a generation couldn't be written to the run log
+    Log(io::Error),
+    /// The checkpoint couldn't be written or read
+    #[cfg(feature = "serde")]
+    Checkpoint(CheckpointError),
+    /// The settings checked when the run starts are not valid (e.g. the budget of a Portfolio)
+    Config(ConfigError),
+}
+
+impl fmt::Display for RunError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RunError::Fitness(error) => write!(f, "fitness function failed: {}", error),
+            RunError::Log(error) => write!(f, "the run log couldn't be written: {}", error),
+            #[cfg(feature = "serde")]
+            RunError::Checkpoint(error) => write!(f, "checkpoint failed: {}", error),
+            RunError::Config(error) => write!(f, "invalid settings: {}", error),
+        }
+    }
+}
+
+impl Error for RunError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            RunError::Fitness(error) => Some(error.as_ref()),
+            RunError::Log(error) => Some(error),
+            #[cfg(feature = "serde")]
+            RunError::Checkpoint(error) => Some(error),
+            RunError::Config(error) => Some(error),
+        }
+    }
+}
+
+impl From<FitnessError> for RunError {
+    fn from(error: FitnessError) -> Self {
+        RunError::Fitness(error)
+    }
+}
+
+impl From<ConfigError> for RunError {
+    fn from(error: ConfigError) -> Self {
+        RunError::Config(error)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<CheckpointError> for RunError {
+    fn from(error: CheckpointError) -> Self {
+        RunError::Checkpoint(error)
+    }
+}
+
+/// Error of writing or reading a checkpoint
+#[cfg(feature = "serde")]
+#[derive(Debug)]
+pub enum CheckpointError {
+    /// The file couldn't be written or read
+    Io(io::Error),
+    /// The state couldn't be encoded, or the file is not a checkpoint of these agents
+    Format(bincode::Error),
+    /// The checkpoint was created with different parameters (contains the reason)
+    Mismatch(String),
+}
+
+#[cfg(feature = "serde")]
+impl fmt::Display for CheckpointError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CheckpointError::Io(error) => write!(f, "the checkpoint file couldn't be used: {}", error),
+            CheckpointError::Format(error) => write!(f, "the checkpoint couldn't be encoded or decoded: {}", error),
+            CheckpointError::Mismatch(reason) => write!(f, "the checkpoint belongs to a different run: {}", reason),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Error for CheckpointError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            CheckpointError::Io(error) => Some(error),
+            CheckpointError::Format(error) => Some(error),
+            CheckpointError::Mismatch(_) => None,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<io::Error> for CheckpointError {
+    fn from(error: io::Error) -> Self {
+        CheckpointError::Io(error)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<bincode::Error> for CheckpointError {
+    fn from(error: bincode::Error) -> Self {
+        CheckpointError::Format(error)
+    }
+}
+
+/// Error of reading a GeneticConfig
+#[cfg(feature = "serde")]
+#[derive(Debug)]
+pub enum ConfigFileError {
+    /// The file couldn't be read
+    Io(io::Error),
+    /// The TOML document is not a valid configuration
+    Toml(toml::de::Error),
+    /// The JSON document is not a valid configuration
+    Json(serde_json::Error),
+    /// The extension of the file is not .toml or .json (contains the path)
+    UnknownFormat(String),
+}
+
+#[cfg(feature = "serde")]
+impl fmt::Display for ConfigFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigFileError::Io(error) => write!(f, "the configuration file couldn't be read: {}", error),
+            ConfigFileError::Toml(error) => write!(f, "invalid TOML configuration: {}", error),
+            ConfigFileError::Json(error) => write!(f, "invalid JSON configuration: {}", error),
+            ConfigFileError::UnknownFormat(path) => write!(f, "the configuration file should be .toml or .json, got {}", path),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Error for ConfigFileError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ConfigFileError::Io(error) => Some(error),
+            ConfigFileError::Toml(error) => Some(error),
+            ConfigFileError::Json(error) => Some(error),
+            ConfigFileError::UnknownFormat(_) => None,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<io::Error> for ConfigFileError {
+    fn from(error: io::Error) -> Self {
+        ConfigFileError::Io(error)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<toml::de::Error> for ConfigFileError {
+    fn from(error: toml::de::Error) -> Self {
+        ConfigFileError::Toml(error)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<serde_json::Error> for ConfigFileError {
+    fn from(error: serde_json::Error) -> Self {
+        ConfigFileError::Json(error)
+    }
+}
+
+/// Error of an external fitness process
+#[derive(Debug)]
+pub enum ExternalError {
+    /// The program couldn't be started
+    Spawn(io::Error),
+    /// The agent couldn't be written or the answer couldn't be read
+    Io(io::Error),
+    /// The process didn't answer in time (it's killed)
+    Timeout(Duration),
+    /// The process stopped before answering
+    Exited,
+    /// The answer is not a fitness value (contains the answer)
+    Parse(String),
+}
+
+impl fmt::Display for ExternalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExternalError::Spawn(error) => write!(f, "the fitness process couldn't be started: {}", error),
+            ExternalError::Io(error) => write!(f, "communication with the fitness process failed: {}", error),
+            ExternalError::Timeout(timeout) => write!(f, "the fitness process didn't answer in {:?}", timeout),
+            ExternalError::Exited => write!(f, "the fitness process exited"),
+            ExternalError::Parse(answer) => write!(f, "the answer of the fitness process is not a fitness: {:?}", answer),
+        }
+    }
+}
+
+impl Error for ExternalError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ExternalError::Spawn(error) | ExternalError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// Error of reading a TSPLIB file
+#[derive(Debug)]
+pub enum TsplibError {
+    /// The file couldn't be read
+    Io(io::Error),
+    /// The file is not a supported TSPLIB instance (contains the reason)
+    Format(String),
+}
+
+impl fmt::Display for TsplibError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TsplibError::Io(error) => write!(f, "the TSPLIB file couldn't be read: {}", error),
+            TsplibError::Format(reason) => write!(f, "invalid TSPLIB instance: {}", reason),
+        }
+    }
+}
+
+impl Error for TsplibError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            TsplibError::Io(error) => Some(error),
+            TsplibError::Format(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for TsplibError {
+    fn from(error: io::Error) -> Self {
+        TsplibError::Io(error)
+    }
+}
+
+/// Error of a DistributedFitness evaluation (the one of its last attempt)
+#[cfg(feature = "distributed")]
+#[derive(Debug)]
+pub enum DistributedError {
+    /// There are no workers
+    NoWorkers,
+    /// The worker couldn't be reached
+    Connect(io::Error),
+    /// The agent couldn't be sent or the answer couldn't be read
+    Io(io::Error),
+    /// The worker didn't answer in time
+    Timeout(Duration),
+    /// The worker closed the connection before answering
+    Disconnected,
+    /// The answer is not a fitness value (contains the answer)
+    Parse(String),
+}
+
+#[cfg(feature = "distributed")]
+impl fmt::Display for DistributedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DistributedError::NoWorkers => write!(f, "there are no fitness workers"),
+            DistributedError::Connect(error) => write!(f, "the fitness worker couldn't be reached: {}", error),
+            DistributedError::Io(error) => write!(f, "communication with the fitness worker failed: {}", error),
+            DistributedError::Timeout(timeout) => write!(f, "the fitness worker didn't answer in {:?}", timeout),
+            DistributedError::Disconnected => write!(f, "the fitness worker closed the connection"),
+            DistributedError::Parse(answer) => write!(f, "the answer of the fitness worker is not a fitness: {:?}", answer),
+        }
+    }
+}
+
+#[cfg(feature = "distributed")]
+impl Error for DistributedError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            DistributedError::Connect(error) | DistributedError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// Error of the evaluations after max_evaluations is used up (it stops the run, it's never returned)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct EvaluationBudget;
+
+impl fmt::Display for EvaluationBudget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "max_evaluations is used up")
+    }
+}
+
+impl Error for EvaluationBudget {}
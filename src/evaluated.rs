@@ -0,0 +1,16 @@
+/// An agent together with its fitness, so the fitness function is called only once for every new agent
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Evaluated<T, F = f64> {
+    /// The agent
+    pub agent: T,
+    /// The fitness of the agent
+    pub fitness: F,
+}
+
+impl<T, F> Evaluated<T, F> {
+    /// Pairs an agent with its fitness
+    pub fn new(agent: T, fitness: F) -> Self {
+        Evaluated { agent, fitness }
+    }
+}
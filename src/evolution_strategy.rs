@@ -0,0 +1,82 @@
+use crate::ConfigError;
+
+/// Evolution strategy style replacement: the `mu` best agents are the parents, they create `lambda` offspring
+/// and the next generation is chosen (with the selection strategy) from the offspring, (mu, lambda),
+/// or from the parents and the offspring together, (mu + lambda)
+///
+/// Parents are mutated only through their offspring (pc decides whether an offspring is a crossover
+/// or the copy of a parent, pm whether it is mutated).
+///
+/// # Examples:
+/// ```
+/// use rand::prelude::*;
+/// use bbte_optim_tzim1773_genetic::{EvolutionStrategy, Genetic};
+///
+/// fn main() {
+///     let test = Genetic::builder()
+///         .population(10)
+///         .max_generation(50)
+///         .pm(1.0)
+///         .random_agent(|rng| rng.gen_range(-5.0, 5.0))
+///         .fitness(|a: &f64| 5.0 - a * a)
+///         .mutate(|a: &f64, rng| *a + rng.gen_range(-0.1, 0.1))
+///         .offspring(|a: &f64, b: &f64, _rng| (*a + *b) / 2.0)
+///         .evolution_strategy(EvolutionStrategy::comma(10, 70))
+///         .build()
+///         .unwrap();
+///
+///     println!("{}", test.run().best); // should be a number close to 0
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EvolutionStrategy {
+    /// Number of parents (the best agents of the generation) (1 <= mu <= population)
+    pub mu: usize,
+    /// Number of offspring created in every generation
+    /// (with (mu, lambda) it has to be at least the population, usually it's 5-7 times mu)
+    pub lambda: usize,
+    /// Whether the parents compete with the offspring, (mu + lambda), or only the offspring survive, (mu, lambda)
+    pub plus: bool,
+}
+
+impl EvolutionStrategy {
+    /// (mu, lambda) replacement: the next generation is chosen from the offspring only
+    pub fn comma(mu: usize, lambda: usize) -> Self {
+        EvolutionStrategy { mu, lambda, plus: false }
+    }
+
+    /// (mu + lambda) replacement: the next generation is chosen from the parents and the offspring
+    pub fn plus(mu: usize, lambda: usize) -> Self {
+        EvolutionStrategy { mu, lambda, plus: true }
+    }
+
+    /// Checks whether the parameters are usable with the given population size
+    pub fn validate(&self, population: usize) -> Result<(), ConfigError> {
+        if self.mu == 0 || self.mu > population {
+            return Err(ConfigError::InvalidEvolutionStrategy(format!("mu must be between 1 and the population, got {}", self.mu)));
+        }
+        if self.lambda == 0 {
+            return Err(ConfigError::InvalidEvolutionStrategy("lambda must be at least 1".to_string()));
+        }
+        if !self.plus && self.lambda < population {
+            return Err(ConfigError::InvalidEvolutionStrategy(format!("lambda must be at least the population with (mu, lambda), got {}", self.lambda)));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invalid_evolution_strategy() {
+        assert!(EvolutionStrategy::comma(10, 70).validate(10).is_ok());
+        assert!(EvolutionStrategy::plus(10, 5).validate(10).is_ok());
+        assert!(EvolutionStrategy::comma(10, 5).validate(10).is_err());
+        assert!(EvolutionStrategy::plus(0, 5).validate(10).is_err());
+        assert!(EvolutionStrategy::plus(11, 5).validate(10).is_err());
+        assert!(EvolutionStrategy::plus(10, 0).validate(10).is_err());
+    }
+}
@@ -0,0 +1,385 @@
+//! Experiments comparing settings of the genetic algorithm: a grid (or a random part of it) of Hyperparameters is run
+//! with the same seeds, and the best fitness of the runs is summarised for every setting, with the Mann-Whitney U
+//! test telling whether a setting is significantly worse than the best one
+//!
+//! # Examples:
+//! ```
+//! use rand::prelude::*;
+//! use bbte_optim_tzim1773_genetic::{Genetic, Selection};
+//! use bbte_optim_tzim1773_genetic::experiment::{Experiment, Grid};
+//!
+//! fn main() {
+//!     let problem = || Genetic::builder()
+//!         .max_generation(20)
+//!         .random_agent(|rng| rng.gen_range(-100.0, 100.0))
+//!         .fitness(|a: &f64| -(a - 7.0).abs())
+//!         .mutate(|a: &f64, rng| a + rng.gen_range(-1.0, 1.0))
+//!         .offspring(|a: &f64, b: &f64, _rng| (a + b) / 2.0)
+//!         .build()
+//!         .unwrap();
+//!
+//!     let grid = Grid::new()
+//!         .populations(vec![10, 50])
+//!         .pms(vec![0.1, 0.5])
+//!         .selections(vec![Selection::Truncation, Selection::tournament(3)]);
+//!     let result = Experiment::new(problem, grid.settings(), 10).unwrap().run();
+//!     println!("{}", result); // a table of the 8 settings
+//!     println!("{:?}", result.rows[result.best].setting);
+//! }
+//! ```
+
+use rand::prelude::*;
+use std::cmp::Ordering;
+use std::fmt;
+
+use crate::{ConfigError, Fitness, GenerationStats, Genetic, Hyperparameters, MaybeSync, Objective, RunError, Selection};
+
+/// The values of the hyperparameters an experiment tries, every combination of them is a setting
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Grid {
+    /// The population sizes
+    pub populations: Vec<usize>,
+    /// The probabilities of crossover
+    pub pcs: Vec<f64>,
+    /// The probabilities of mutation
+    pub pms: Vec<f64>,
+    /// The selection strategies
+    pub selections: Vec<Selection>,
+}
+
+impl Default for Grid {
+    fn default() -> Self {
+        Grid { populations: vec![100], pcs: vec![0.5], pms: vec![0.4], selections: vec![Selection::Truncation] }
+    }
+}
+
+impl Grid {
+    /// Creates a grid with a single setting (the defaults of Genetic), the lists of values replace its values
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the population sizes tried
+    pub fn populations(mut self, populations: Vec<usize>) -> Self {
+        self.populations = populations;
+        self
+    }
+
+    /// Sets the probabilities of crossover tried
+    pub fn pcs(mut self, pcs: Vec<f64>) -> Self {
+        self.pcs = pcs;
+        self
+    }
+
+    /// Sets the probabilities of mutation tried
+    pub fn pms(mut self, pms: Vec<f64>) -> Self {
+        self.pms = pms;
+        self
+    }
+
+    /// Sets the selection strategies tried
+    pub fn selections(mut self, selections: Vec<Selection>) -> Self {
+        self.selections = selections;
+        self
+    }
+
+    /// Returns the number of settings of the grid
+    pub fn len(&self) -> usize {
+        self.populations.len() * self.pcs.len() * self.pms.len() * self.selections.len()
+    }
+
+    /// Returns true if one of the lists is empty
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns every setting of the grid (the cartesian product of the values)
+    pub fn settings(&self) -> Vec<Hyperparameters> {
+        (0..self.len()).map(|i| self.setting(i)).collect()
+    }
+
+    /// Returns `count` different random settings of the grid (all of them if there are fewer)
+    pub fn sample(&self, count: usize, rng: &mut dyn RngCore) -> Vec<Hyperparameters> {
+        let mut chosen = rand::seq::index::sample(rng, self.len(), count.min(self.len())).into_vec();
+        chosen.sort_unstable();
+        chosen.into_iter().map(|i| self.setting(i)).collect()
+    }
+
+    /// Returns the i-th setting of the cartesian product
+    fn setting(&self, i: usize) -> Hyperparameters {
+        let (i, selection) = (i / self.selections.len(), i % self.selections.len());
+        let (i, pm) = (i / self.pms.len(), i % self.pms.len());
+        let (population, pc) = (i / self.pcs.len(), i % self.pcs.len());
+        Hyperparameters {
+            population: self.populations[population],
+            pc: self.pcs[pc],
+            pm: self.pms[pm],
+            selection: self.selections[selection].clone(),
+        }
+    }
+}
+
+/// The results of a setting in an experiment
+#[derive(Debug, Clone, PartialEq)]
+pub struct SettingStats {
+    /// The setting
+    pub setting: Hyperparameters,
+    /// The best fitness of every run, in the order of the seeds (using Fitness::to_f64())
+    pub fitness: Vec<f64>,
+    /// The statistics of the best fitness of the runs (best, mean, median, worst, standard deviation)
+    pub stats: GenerationStats,
+    /// The p-value of the two-sided Mann-Whitney U test comparing the runs with the runs of the best setting
+    /// (1.0 for the best setting), a small value (e.g. below 0.05) means the difference is significant
+    pub p_value: f64,
+}
+
+/// The outcome of an experiment
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExperimentResult {
+    /// The results of the settings, in the order they were given
+    pub rows: Vec<SettingStats>,
+    /// The index of the setting with the best mean fitness
+    pub best: usize,
+    /// Whether the fitness was maximised or minimised
+    pub objective: Objective,
+}
+
+impl ExperimentResult {
+    /// Returns the indices of the settings that are not significantly worse than the best one at the given level
+    /// (including the best one)
+    pub fn indistinguishable(&self, alpha: f64) -> Vec<usize> {
+        (0..self.rows.len()).filter(|i| self.rows[*i].p_value >= alpha).collect()
+    }
+}
+
+impl fmt::Display for ExperimentResult {
+    /// Writes a table of the settings (the best one is marked with a *)
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "  {:>10} {:>6} {:>6} {:<40} {:>12} {:>12} {:>12} {:>12} {:>8}", "population", "pc", "pm", "selection", "mean", "median", "std_dev", "best", "p")?;
+        for (i, row) in self.rows.iter().enumerate() {
+            let setting = &row.setting;
+            writeln!(
+                f,
+                "{} {:>10} {:>6.3} {:>6.3} {:<40} {:>12.6} {:>12.6} {:>12.6} {:>12.6} {:>8.4}",
+                if i == self.best { "*" } else { " " },
+                setting.population,
+                setting.pc,
+                setting.pm,
+                format!("{:?}", setting.selection),
+                row.stats.mean,
+                row.stats.median,
+                row.stats.std_dev,
+                row.stats.best,
+                row.p_value,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Runs every setting on the problem with the same seeds and compares the best fitness of the runs
+///
+/// The Genetics are created by the given function (with everything else set, e.g. the operators and the stopping
+/// criteria), then their hyperparameters are overwritten by the setting. The r-th run of every setting is seeded
+/// with r, so the settings are compared on the same random numbers. With a budget, max_evaluations of the runs is
+/// set to it, so the settings with bigger populations don't get more evaluations.
+///
+/// # Examples:
+///  See at the module
+pub struct Experiment<'a, T, F = f64> {
+    /// The settings compared
+    pub settings: Vec<Hyperparameters>,
+    /// The number of runs of every setting
+    /// Suggested value: 30
+    pub seeds: usize,
+    /// The number of evaluations of a run (None uses the stopping criteria of the created Genetics)
+    /// Suggested value: None
+    pub budget: Option<usize>,
+    f_genetic: Box<dyn Fn() -> Genetic<'a, T, F> + 'a>,
+}
+
+impl<'a, T, F> Experiment<'a, T, F>
+    where T: Clone + MaybeSync, F: Fitness + MaybeSync {
+    /// Creates an experiment, checking that there is something to compare and that every setting is valid for the
+    /// Genetics created by the function
+    ///
+    /// # Arguments:
+    ///
+    /// * `f_genetic` creates the Genetics solving the problem
+    /// * `settings` the settings compared (e.g. Grid::settings() or Grid::sample())
+    /// * `seeds` the number of runs of every setting (at least 2, so they can be compared)
+    pub fn new(f_genetic: impl Fn() -> Genetic<'a, T, F> + 'a, settings: Vec<Hyperparameters>, seeds: usize) -> Result<Self, ConfigError> {
+        if settings.is_empty() {
+            return Err(ConfigError::InvalidExperiment("there are no settings to compare".to_string()));
+        }
+        if seeds < 2 {
+            return Err(ConfigError::InvalidExperiment(format!("every setting needs at least 2 runs, got {}", seeds)));
+        }
+        let mut probe = f_genetic();
+        for setting in &settings {
+            checked(setting, &mut probe)?;
+        }
+
+        Ok(Experiment { settings, seeds, budget: None, f_genetic: Box::new(f_genetic) })
+    }
+
+    /// Sets the number of evaluations of a run
+    pub fn budget(mut self, budget: usize) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+
+    /// Runs every setting with every seed
+    ///
+    /// # Panics:
+    ///  If a setting is not valid for the created Genetic with the budget, or a run fails (use try_run() to get the
+    ///  error instead)
+    pub fn run(&self) -> ExperimentResult {
+        match self.try_run() {
+            Ok(result) => result,
+            Err(error) => panic!("{}", error),
+        }
+    }
+
+    /// Same as run(), but returns the error of the failed run (or RunError::Config if a setting is not valid for the
+    /// created Genetic with the budget) instead of panicking
+    pub fn try_run(&self) -> Result<ExperimentResult, RunError> {
+        let mut objective = Objective::Maximize;
+        let mut rows = Vec::with_capacity(self.settings.len());
+        for setting in &self.settings {
+            let mut genetic = (self.f_genetic)();
+            if let Some(budget) = self.budget {
+                genetic.max_evaluations = Some(budget);
+            }
+            checked(setting, &mut genetic)?;
+            objective = genetic.objective;
+            let fitness = (0..self.seeds)
+                .map(|seed| Ok(genetic.try_run_with_rng(&mut StdRng::seed_from_u64(seed as u64))?.best_fitness.to_f64()))
+                .collect::<Result<Vec<f64>, RunError>>()?;
+            let stats = GenerationStats::new(fitness.iter(), objective);
+            rows.push(SettingStats { setting: setting.clone(), fitness, stats, p_value: 1.0 });
+        }
+
+        let best = (1..rows.len()).fold(0, |best, i| if objective.compare(&rows[i].stats.mean, &rows[best].stats.mean) == Ordering::Less { i } else { best });
+        let best_fitness = rows[best].fitness.clone();
+        for (i, row) in rows.iter_mut().enumerate() {
+            if i != best {
+                row.p_value = mann_whitney(&row.fitness, &best_fitness);
+            }
+        }
+
+        Ok(ExperimentResult { rows, best, objective })
+    }
+}
+
+/// Applies the setting to the Genetic and checks the parameters
+fn checked<T, F>(setting: &Hyperparameters, genetic: &mut Genetic<T, F>) -> Result<(), ConfigError>
+    where T: Clone, F: Fitness {
+    setting.apply(genetic);
+    genetic.validate().map_err(|error| ConfigError::InvalidExperiment(format!("the setting {:?} is not valid: {}", setting, error)))
+}
+
+/// Returns the p-value of the two-sided Mann-Whitney U test (with the normal approximation, so it's accurate from
+/// about 10 values per sample): the probability of samples this different if they came from the same distribution
+pub fn mann_whitney(a: &[f64], b: &[f64]) -> f64 {
+    let (n1, n2) = (a.len() as f64, b.len() as f64);
+    if a.is_empty() || b.is_empty() {
+        return 1.0;
+    }
+    let mut values = a.iter().map(|x| (*x, true)).chain(b.iter().map(|x| (*x, false))).collect::<Vec<(f64, bool)>>();
+    values.sort_by(|x, y| x.0.total_cmp(&y.0));
+
+    // the sum of the ranks of the first sample (the tied values get the average of their ranks)
+    let mut rank_sum = 0.0;
+    let mut i = 0;
+    while i < values.len() {
+        let mut j = i;
+        while j + 1 < values.len() && values[j + 1].0 == values[i].0 {
+            j += 1;
+        }
+        let rank = (i + j) as f64 / 2.0 + 1.0;
+        rank_sum += rank * values[i..=j].iter().filter(|x| x.1).count() as f64;
+        i = j + 1;
+    }
+
+    let u = rank_sum - n1 * (n1 + 1.0) / 2.0;
+    let mean = n1 * n2 / 2.0;
+    let sigma = (n1 * n2 * (n1 + n2 + 1.0) / 12.0).sqrt();
+    let z = ((u - mean).abs() - 0.5).max(0.0) / sigma;
+    erfc(z / std::f64::consts::SQRT_2).min(1.0)
+}
+
+/// The complementary error function (Abramowitz and Stegun 7.1.26, the error is below 1.5e-7) for x >= 0
+fn erfc(x: f64) -> f64 {
+    let t = 1.0 / (1.0 + 0.3275911 * x);
+    let polynomial = t * (0.254829592 + t * (-0.284496736 + t * (1.421413741 + t * (-1.453152027 + t * 1.061405429))));
+    polynomial * (-x * x).exp()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grid() {
+        let grid = Grid::new().populations(vec![10, 20]).pms(vec![0.1, 0.2, 0.3]).selections(vec![Selection::Truncation, Selection::tournament(2)]);
+        assert_eq!(grid.len(), 12);
+        let settings = grid.settings();
+        assert_eq!(settings.len(), 12);
+        assert_eq!((settings[0].population, settings[0].pm, settings[1].selection.clone()), (10, 0.1, Selection::tournament(2)));
+        assert_eq!((settings[11].population, settings[11].pc, settings[11].pm), (20, 0.5, 0.3));
+        assert!(settings.iter().enumerate().all(|(i, a)| settings[..i].iter().all(|b| a != b)));
+
+        let sample = grid.sample(5, &mut StdRng::seed_from_u64(1));
+        assert_eq!(sample.len(), 5);
+        assert!(sample.iter().all(|setting| settings.contains(setting)));
+        assert_eq!(grid.sample(100, &mut StdRng::seed_from_u64(1)), settings);
+        assert!(Grid::new().pcs(Vec::new()).is_empty());
+    }
+
+    #[test]
+    fn mann_whitney_u() {
+        let a = (0..20).map(f64::from).collect::<Vec<f64>>();
+        let b = (10..30).map(f64::from).collect::<Vec<f64>>();
+        let c = (100..120).map(f64::from).collect::<Vec<f64>>();
+        assert!((mann_whitney(&a, &a) - 1.0).abs() < 1e-6);
+        // U = 50 (with the ties), the normal approximation gives z = 4.04
+        assert!((mann_whitney(&a, &b) - 5.26e-5).abs() < 1e-6, "{}", mann_whitney(&a, &b));
+        assert!(mann_whitney(&a, &c) < 1e-6);
+        assert_eq!(mann_whitney(&a, &c), mann_whitney(&c, &a));
+        assert!((erfc(0.0) - 1.0).abs() < 1e-6 && (erfc(1.0) - 0.157299).abs() < 1e-6);
+    }
+
+    #[test]
+    fn experiment() {
+        let problem = || Genetic::builder()
+            .max_generation(20)
+            .random_agent(|rng| rng.gen_range(-100.0, 100.0))
+            .fitness(|a: &f64| -(a - 7.0).abs())
+            .mutate(|a: &f64, rng| a + rng.gen_range(-1.0, 1.0))
+            .offspring(|a: &f64, b: &f64, _rng| (a + b) / 2.0)
+            .build()
+            .unwrap();
+        assert!(Experiment::new(problem, Vec::new(), 10).is_err());
+        assert!(Experiment::new(problem, Grid::new().settings(), 1).is_err());
+        assert!(Experiment::new(problem, Grid::new().populations(vec![10, 0]).settings(), 10).is_err());
+        let no_budget = Experiment::new(problem, Grid::new().settings(), 10).unwrap().budget(0);
+        assert!(matches!(no_budget.try_run(), Err(RunError::Config(ConfigError::InvalidExperiment(_)))));
+
+        // the tiny populations can't search as well as the bigger ones
+        let grid = Grid::new().populations(vec![2, 50]).pcs(vec![0.0]).pms(vec![0.0, 0.5]);
+        let result = Experiment::new(problem, grid.settings(), 12).unwrap().budget(500).run();
+        assert_eq!(result.rows.len(), 4);
+        assert!(result.rows.iter().all(|row| row.fitness.len() == 12));
+        assert!(result.best >= 2, "{}", result);
+        assert!(result.rows[0].p_value < 0.01, "{}", result);
+        assert!(result.indistinguishable(0.01).contains(&result.best));
+        assert!(!result.indistinguishable(0.01).contains(&0));
+        assert_eq!(result.to_string().lines().count(), 5);
+
+        // the runs are seeded
+        let again = Experiment::new(problem, grid.settings(), 12).unwrap().budget(500).run();
+        assert_eq!(again, result);
+    }
+}
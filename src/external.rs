@@ -0,0 +1,410 @@
+use std::io::{BufRead, BufReader, Write};
+use std::marker::PhantomData;
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::str::FromStr;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::{Condvar, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use crate::genome::{Permutation, RealVector};
+use crate::ExternalError;
+
+/// The way the agents are written to the standard input of the external process (one agent per line)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LineFormat {
+    /// Comma separated values: 1.5,2,0.25
+    #[default]
+    Csv,
+    /// A JSON value: [1.5,2.0,0.25]
+    Json,
+}
+
+impl LineFormat {
+    /// Returns the line that represents the agent
+    pub fn encode<T: LineEncode + ?Sized>(&self, agent: &T) -> String {
+        let mut line = String::new();
+        match self {
+            LineFormat::Csv => agent.write_csv(&mut line),
+            LineFormat::Json => agent.write_json(&mut line),
+        }
+        line
+    }
+}
+
+/// Agents that can be written to an external process as a line of CSV or JSON
+///
+/// It's implemented for numbers, bools, Vecs and arrays of them, RealVector and Permutation.
+/// Other types can be written with ExternalFitness::with_encoder().
+pub trait LineEncode {
+    /// Appends the agent as comma separated values (without a line break)
+    fn write_csv(&self, out: &mut String);
+    /// Appends the agent as a JSON value (without a line break)
+    fn write_json(&self, out: &mut String);
+}
+
+macro_rules! encode_integer {
+    ($($t:ty),*) => {
+        $(impl LineEncode for $t {
+            fn write_csv(&self, out: &mut String) {
+                out.push_str(&self.to_string());
+            }
+
+            fn write_json(&self, out: &mut String) {
+                out.push_str(&self.to_string());
+            }
+        })*
+    };
+}
+
+encode_integer!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+macro_rules! encode_float {
+    ($($t:ty),*) => {
+        $(impl LineEncode for $t {
+            fn write_csv(&self, out: &mut String) {
+                out.push_str(&self.to_string());
+            }
+
+            // JSON has no NaN and infinity
+            fn write_json(&self, out: &mut String) {
+                if self.is_finite() {
+                    out.push_str(&format!("{:?}", self));
+                } else {
+                    out.push_str("null");
+                }
+            }
+        })*
+    };
+}
+
+encode_float!(f32, f64);
+
+impl LineEncode for bool {
+    fn write_csv(&self, out: &mut String) {
+        out.push(if *self { '1' } else { '0' });
+    }
+
+    fn write_json(&self, out: &mut String) {
+        out.push_str(if *self { "true" } else { "false" });
+    }
+}
+
+impl<E: LineEncode> LineEncode for [E] {
+    fn write_csv(&self, out: &mut String) {
+        for (i, x) in self.iter().enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            x.write_csv(out);
+        }
+    }
+
+    fn write_json(&self, out: &mut String) {
+        out.push('[');
+        for (i, x) in self.iter().enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            x.write_json(out);
+        }
+        out.push(']');
+    }
+}
+
+impl<E: LineEncode> LineEncode for Vec<E> {
+    fn write_csv(&self, out: &mut String) {
+        self[..].write_csv(out);
+    }
+
+    fn write_json(&self, out: &mut String) {
+        self[..].write_json(out);
+    }
+}
+
+impl<E: LineEncode, const N: usize> LineEncode for [E; N] {
+    fn write_csv(&self, out: &mut String) {
+        self[..].write_csv(out);
+    }
+
+    fn write_json(&self, out: &mut String) {
+        self[..].write_json(out);
+    }
+}
+
+impl LineEncode for RealVector {
+    fn write_csv(&self, out: &mut String) {
+        self.values.write_csv(out);
+    }
+
+    fn write_json(&self, out: &mut String) {
+        self.values.write_json(out);
+    }
+}
+
+impl LineEncode for Permutation {
+    fn write_csv(&self, out: &mut String) {
+        self[..].write_csv(out);
+    }
+
+    fn write_json(&self, out: &mut String) {
+        self[..].write_json(out);
+    }
+}
+
+/// Function that writes an agent in the given format
+pub(crate) type EncodeFn<T> = Box<dyn Fn(&T, LineFormat) -> String + Send + Sync>;
+
+/// Fitness function computed by an external program (e.g. a Python script or a simulator)
+///
+/// Every agent is written to the standard input of the program as one line, and its fitness is read from
+/// the next line of the standard output. The processes are started when they are first needed and are kept
+/// running for the next agents; at most `processes` of them run at once (more than one is only useful with
+/// the rayon feature). A process that doesn't answer in time is killed, its agent gets a Timeout error
+/// (which is handled according to the fitness_error_policy).
+///
+/// # Examples:
+/// ```no_run
+/// use rand::prelude::*;
+/// use std::time::Duration;
+/// use bbte_optim_tzim1773_genetic::{ExternalFitness, FitnessErrorPolicy, Genetic, LineFormat};
+///
+/// fn main() {
+///     // fitness.py: for line in sys.stdin: print(evaluate(json.loads(line)), flush=True)
+///     let fitness = ExternalFitness::<Vec<f64>>::new("python3")
+///         .arg("fitness.py")
+///         .format(LineFormat::Json)
+///         .timeout(Duration::from_secs(10));
+///
+///     let test = Genetic::builder()
+///         .max_generation(20)
+///         .random_agent(|rng| vec![rng.gen_range(-5.0, 5.0); 3])
+///         .external_fitness(fitness)
+///         .fitness_error_policy(FitnessErrorPolicy::Retry(2))
+///         .mutate(|a: &Vec<f64>, rng| a.iter().map(|x| x + rng.gen_range(-0.1, 0.1)).collect())
+///         .offspring(|a: &Vec<f64>, b: &Vec<f64>, _rng| a.iter().zip(b).map(|(x, y)| (x + y) / 2.0).collect())
+///         .build()
+///         .unwrap();
+///
+///     println!("{:?}", test.run().best);
+/// }
+/// ```
+pub struct ExternalFitness<T, F = f64> {
+    program: String,
+    args: Vec<String>,
+    format: LineFormat,
+    encode: EncodeFn<T>,
+    processes: usize,
+    timeout: Option<Duration>,
+    pool: Mutex<Pool>,
+    available: Condvar,
+    fitness: PhantomData<fn() -> F>,
+}
+
+/// The idle processes and the number of all running processes
+struct Pool {
+    idle: Vec<Worker>,
+    running: usize,
+}
+
+/// A running external process (it's killed when it's dropped)
+struct Worker {
+    child: Child,
+    stdin: ChildStdin,
+    lines: Receiver<std::io::Result<String>>,
+}
+
+impl<T, F> ExternalFitness<T, F> {
+    /// Creates an evaluator running the program, the agents are written as CSV lines
+    pub fn new(program: impl Into<String>) -> Self
+        where T: LineEncode {
+        Self::with_encoder(program, |agent: &T, format: LineFormat| format.encode(agent))
+    }
+
+    /// Creates an evaluator running the program, the agents are written with the given function
+    /// (it gets the agent and the format, its result shouldn't contain line breaks)
+    pub fn with_encoder(program: impl Into<String>, encode: impl Fn(&T, LineFormat) -> String + Send + Sync + 'static) -> Self {
+        ExternalFitness {
+            program: program.into(),
+            args: Vec::new(),
+            format: LineFormat::Csv,
+            encode: Box::new(encode),
+            processes: 1,
+            timeout: None,
+            pool: Mutex::new(Pool { idle: Vec::new(), running: 0 }),
+            available: Condvar::new(),
+            fitness: PhantomData,
+        }
+    }
+
+    /// Adds an argument of the program
+    pub fn arg(mut self, arg: impl Into<String>) -> Self {
+        self.args.push(arg.into());
+        self
+    }
+
+    /// Adds arguments of the program
+    pub fn args<S: Into<String>>(mut self, args: impl IntoIterator<Item = S>) -> Self {
+        self.args.extend(args.into_iter().map(Into::into));
+        self
+    }
+
+    /// Sets the way the agents are written
+    pub fn format(mut self, format: LineFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Sets the maximal number of processes running at once
+    ///
+    /// # Panics:
+    ///  If processes is 0
+    pub fn processes(mut self, processes: usize) -> Self {
+        assert!(processes > 0, "at least one process is needed");
+        self.processes = processes;
+        self
+    }
+
+    /// Sets how long the fitness of an agent is waited for
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Evaluates an agent with one of the processes (waits for an idle one if all of them are busy)
+    pub fn evaluate(&self, agent: &T) -> Result<F, ExternalError>
+        where F: FromStr {
+        let line = (self.encode)(agent, self.format);
+        let mut worker = self.acquire()?;
+
+        match worker.evaluate(&line, self.timeout) {
+            Ok(output) => {
+                self.release(Some(worker));
+                output.trim().parse().map_err(|_| ExternalError::Parse(output))
+            }
+            // the process can't be trusted to answer the next agent, it's killed
+            Err(error) => {
+                self.release(None);
+                Err(error)
+            }
+        }
+    }
+
+    /// Takes an idle process, starts a new one or waits for one to be released
+    fn acquire(&self) -> Result<Worker, ExternalError> {
+        let mut pool = self.pool.lock().expect("a thread panicked while using the process pool");
+        loop {
+            if let Some(worker) = pool.idle.pop() {
+                return Ok(worker);
+            }
+            if pool.running < self.processes {
+                pool.running += 1;
+                drop(pool);
+                return Worker::spawn(&self.program, &self.args).inspect_err(|_| self.release(None));
+            }
+            pool = self.available.wait(pool).expect("a thread panicked while using the process pool");
+        }
+    }
+
+    /// Puts the process back into the pool (None if it was stopped)
+    fn release(&self, worker: Option<Worker>) {
+        let mut pool = self.pool.lock().expect("a thread panicked while using the process pool");
+        match worker {
+            Some(worker) => pool.idle.push(worker),
+            None => pool.running -= 1,
+        }
+        self.available.notify_one();
+    }
+}
+
+impl Worker {
+    /// Starts the program, its output is read line by line on its own thread
+    fn spawn(program: &str, args: &[String]) -> Result<Worker, ExternalError> {
+        let mut child = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(ExternalError::Spawn)?;
+        let stdin = child.stdin.take().expect("stdin is piped");
+        let stdout = child.stdout.take().expect("stdout is piped");
+
+        let (sender, lines) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok(Worker { child, stdin, lines })
+    }
+
+    /// Writes the line of an agent and returns the answer of the process
+    fn evaluate(&mut self, line: &str, timeout: Option<Duration>) -> Result<String, ExternalError> {
+        writeln!(self.stdin, "{}", line).and_then(|_| self.stdin.flush()).map_err(ExternalError::Io)?;
+
+        let answer = match timeout {
+            Some(timeout) => self.lines.recv_timeout(timeout).map_err(|error| match error {
+                RecvTimeoutError::Timeout => ExternalError::Timeout(timeout),
+                RecvTimeoutError::Disconnected => ExternalError::Exited,
+            })?,
+            None => self.lines.recv().map_err(|_| ExternalError::Exited)?,
+        };
+
+        answer.map_err(ExternalError::Io)
+    }
+}
+
+impl Drop for Worker {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::Genetic;
+    use rand::Rng;
+
+    #[test]
+    fn encode() {
+        assert_eq!(LineFormat::Csv.encode(&vec![1.5, -2.0]), "1.5,-2");
+        assert_eq!(LineFormat::Json.encode(&vec![1.5, -2.0]), "[1.5,-2.0]");
+        assert_eq!(LineFormat::Json.encode(&[f64::NAN]), "[null]");
+        assert_eq!(LineFormat::Csv.encode(&[true, false]), "1,0");
+        assert_eq!(LineFormat::Json.encode(&Permutation::identity(3)), "[0,1,2]");
+    }
+
+    #[test]
+    fn external_fitness() {
+        let fitness = ExternalFitness::<i64>::new("sh")
+            .args(vec!["-c", "while read x; do echo $((2500 - x * x)); done"])
+            .processes(2)
+            .timeout(Duration::from_secs(10));
+        assert_eq!(fitness.evaluate(&20).unwrap(), 2100.0);
+
+        let test = Genetic::builder()
+            .population(20)
+            .max_generation(50)
+            .random_agent(|rng| rng.gen_range(-50, 50))
+            .external_fitness(fitness)
+            .mutate(|a: &i64, rng| *a + rng.gen_range(-2, 3))
+            .offspring(|a: &i64, b: &i64, _rng| (*a + *b) / 2)
+            .build()
+            .unwrap();
+        assert_eq!(test.run().best, 0);
+
+        let silent = ExternalFitness::<f64>::new("sh")
+            .args(vec!["-c", "read line; sleep 10"])
+            .timeout(Duration::from_millis(100));
+        assert!(matches!(silent.evaluate(&1.0), Err(ExternalError::Timeout(_))));
+
+        let wrong = ExternalFitness::<f64>::new("sh").args(vec!["-c", "read line; echo nothing"]);
+        assert!(matches!(wrong.evaluate(&1.0), Err(ExternalError::Parse(_))));
+        assert!(matches!(ExternalFitness::<f64>::new("/nonexistent/program").evaluate(&1.0), Err(ExternalError::Spawn(_))));
+    }
+}
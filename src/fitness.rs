@@ -0,0 +1,152 @@
+/// A type that can be used as fitness
+///
+/// Fitness values are compared with PartialOrd (so tuples are compared lexicographically).
+/// It's implemented for the primitive number types, for tuples of them and for Vec<f64> (the objective values of
+/// a multi-objective run), for your own struct you have to implement it yourself.
+///
+/// # Examples:
+/// ```
+/// use bbte_optim_tzim1773_genetic::Fitness;
+///
+/// #[derive(Clone, PartialEq, PartialOrd)]
+/// struct Score {
+///     solved: u32,
+///     time: f64,
+/// }
+///
+/// impl Fitness for Score {
+///     fn lowest() -> Self { Score { solved: 0, time: f64::NEG_INFINITY } }
+///     fn highest() -> Self { Score { solved: u32::MAX, time: f64::INFINITY } }
+///     fn to_f64(&self) -> f64 { self.solved as f64 }
+/// }
+/// ```
+pub trait Fitness: PartialOrd + Clone {
+    /// The smallest possible value
+    fn lowest() -> Self;
+    /// The biggest possible value
+    fn highest() -> Self;
+    /// A number representing the value, used for statistics
+    /// (for tuples it's the value of the first element)
+    fn to_f64(&self) -> f64;
+    /// The values of the objectives, used by the Pareto utilities
+    /// (for tuples and Vec<f64> every element is an objective, otherwise it's the value of to_f64())
+    fn objectives(&self) -> Vec<f64> {
+        vec![self.to_f64()]
+    }
+}
+
+macro_rules! impl_fitness_float {
+    ($($t:ty),*) => {$(
+        impl Fitness for $t {
+            fn lowest() -> Self {
+                <$t>::NEG_INFINITY
+            }
+
+            fn highest() -> Self {
+                <$t>::INFINITY
+            }
+
+            fn to_f64(&self) -> f64 {
+                *self as f64
+            }
+        }
+    )*};
+}
+
+macro_rules! impl_fitness_integer {
+    ($($t:ty),*) => {$(
+        impl Fitness for $t {
+            fn lowest() -> Self {
+                <$t>::MIN
+            }
+
+            fn highest() -> Self {
+                <$t>::MAX
+            }
+
+            fn to_f64(&self) -> f64 {
+                *self as f64
+            }
+        }
+    )*};
+}
+
+impl_fitness_float!(f32, f64);
+impl_fitness_integer!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+impl<A: Fitness, B: Fitness> Fitness for (A, B) {
+    fn lowest() -> Self {
+        (A::lowest(), B::lowest())
+    }
+
+    fn highest() -> Self {
+        (A::highest(), B::highest())
+    }
+
+    fn to_f64(&self) -> f64 {
+        self.0.to_f64()
+    }
+
+    fn objectives(&self) -> Vec<f64> {
+        let mut objectives = self.0.objectives();
+        objectives.extend(self.1.objectives());
+        objectives
+    }
+}
+
+impl<A: Fitness, B: Fitness, C: Fitness> Fitness for (A, B, C) {
+    fn lowest() -> Self {
+        (A::lowest(), B::lowest(), C::lowest())
+    }
+
+    fn highest() -> Self {
+        (A::highest(), B::highest(), C::highest())
+    }
+
+    fn to_f64(&self) -> f64 {
+        self.0.to_f64()
+    }
+
+    fn objectives(&self) -> Vec<f64> {
+        let mut objectives = self.0.objectives();
+        objectives.extend(self.1.objectives());
+        objectives.extend(self.2.objectives());
+        objectives
+    }
+}
+
+impl Fitness for Vec<f64> {
+    fn lowest() -> Self {
+        Vec::new()
+    }
+
+    fn highest() -> Self {
+        vec![f64::INFINITY]
+    }
+
+    fn to_f64(&self) -> f64 {
+        self.first().cloned().unwrap_or(f64::NEG_INFINITY)
+    }
+
+    fn objectives(&self) -> Vec<f64> {
+        self.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bounds() {
+        assert!(i64::lowest() < -1_000_000);
+        assert!(f64::highest() > 1e300);
+        assert!((1, 0.0) > (0, f64::highest()));
+        assert!(<(u8, f64)>::lowest() < (0, 0.0));
+        assert_eq!((3, 2.5).to_f64(), 3.0);
+        assert!(<Vec<f64>>::lowest() < vec![f64::NEG_INFINITY]);
+        assert_eq!(vec![1.5, -2.0].to_f64(), 1.5);
+        assert_eq!((3, (2.5, 1u8)).objectives(), vec![3.0, 2.5, 1.0]);
+        assert_eq!(7i32.objectives(), vec![7.0]);
+    }
+}
@@ -0,0 +1,314 @@
+use rand::prelude::*;
+
+use crate::operators::{BitFlip, Crossover, Mutation, OnePoint, TwoPoint, Uniform};
+
+const WORD: usize = 64;
+
+/// Genome of bits, stored in 64 bit words
+///
+/// The operators of the operators module (OnePoint, TwoPoint, Uniform, BitFlip) work on it.
+/// Numeric parameters can be decoded from parts of it with decode() and decode_real().
+///
+/// # Examples:
+/// ```
+/// use rand::prelude::*;
+/// use bbte_optim_tzim1773_genetic::Genetic;
+/// use bbte_optim_tzim1773_genetic::genome::BitString;
+/// use bbte_optim_tzim1773_genetic::operators::{BitFlip, TwoPoint};
+///
+/// fn main() {
+///     let test = Genetic::builder()
+///         .population(50)
+///         .max_generation(50)
+///         .random_agent(|rng| BitString::random(16, rng))
+///         // 16 Gray coded bits are mapped to [-5, 5]
+///         .fitness(|a: &BitString| {
+///             let x = a.decode_real(0, 16, -5.0, 5.0, true);
+///             5.0 - x * x
+///         })
+///         .crossover(TwoPoint)
+///         .mutation(BitFlip::per_bit_of(16))
+///         .build()
+///         .unwrap();
+///
+///     println!("{}", test.run().best.decode_real(0, 16, -5.0, 5.0, true)); // should be close to 0
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BitString {
+    words: Vec<u64>,
+    len: usize,
+}
+
+/// Returns the Gray code of a number
+pub fn binary_to_gray(x: u64) -> u64 {
+    x ^ (x >> 1)
+}
+
+/// Returns the number whose Gray code is given
+pub fn gray_to_binary(mut x: u64) -> u64 {
+    let mut shift = 1;
+    while shift < WORD {
+        x ^= x >> shift;
+        shift <<= 1;
+    }
+    x
+}
+
+/// Mask of the bits [from, to) of a word
+fn mask(from: usize, to: usize) -> u64 {
+    let high = if to >= WORD { !0 } else { (1u64 << to) - 1 };
+    let low = (1u64 << from) - 1;
+    high & !low
+}
+
+impl BitString {
+    /// Creates a bit string of `len` zeros
+    pub fn zeros(len: usize) -> Self {
+        BitString { words: vec![0; len.div_ceil(WORD)], len }
+    }
+
+    /// Creates a bit string of `len` random bits
+    pub fn random(len: usize, rng: &mut dyn RngCore) -> Self {
+        let mut x = BitString { words: (0..len.div_ceil(WORD)).map(|_| rng.next_u64()).collect(), len };
+        x.clear_unused();
+        x
+    }
+
+    /// Creates a bit string from booleans
+    pub fn from_bools(bits: &[bool]) -> Self {
+        let mut x = BitString::zeros(bits.len());
+        for (i, b) in bits.iter().enumerate() {
+            x.set(i, *b);
+        }
+        x
+    }
+
+    /// Number of bits
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if there are no bits
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the i-th bit
+    ///
+    /// # Panics:
+    ///  If i is not smaller than the length
+    pub fn get(&self, i: usize) -> bool {
+        assert!(i < self.len, "bit index out of range");
+        (self.words[i / WORD] >> (i % WORD)) & 1 == 1
+    }
+
+    /// Sets the i-th bit
+    ///
+    /// # Panics:
+    ///  If i is not smaller than the length
+    pub fn set(&mut self, i: usize, value: bool) {
+        assert!(i < self.len, "bit index out of range");
+        if value {
+            self.words[i / WORD] |= 1 << (i % WORD);
+        } else {
+            self.words[i / WORD] &= !(1 << (i % WORD));
+        }
+    }
+
+    /// Flips the i-th bit
+    ///
+    /// # Panics:
+    ///  If i is not smaller than the length
+    pub fn flip(&mut self, i: usize) {
+        assert!(i < self.len, "bit index out of range");
+        self.words[i / WORD] ^= 1 << (i % WORD);
+    }
+
+    /// Number of bits set to 1
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    /// Returns the bits as booleans
+    pub fn iter(&self) -> impl Iterator<Item = bool> + '_ {
+        (0..self.len).map(move |i| self.get(i))
+    }
+
+    /// Returns the number stored on the bits [start, start + bits) (the first bit is the least significant one)
+    ///
+    /// # Arguments:
+    ///
+    /// * `start` index of the first bit
+    /// * `bits` number of bits (at most 64)
+    /// * `gray` whether the bits are Gray coded (neighbouring numbers differ in one bit)
+    ///
+    /// # Panics:
+    ///  If the bits are out of range or there are more than 64 of them
+    pub fn decode(&self, start: usize, bits: usize, gray: bool) -> u64 {
+        assert!(bits <= WORD && start + bits <= self.len, "bit range out of range");
+        let x = (0..bits)
+            .filter(|i| self.get(start + i))
+            .fold(0u64, |x, i| x | 1 << i);
+
+        if gray {
+            return gray_to_binary(x);
+        }
+        x
+    }
+
+    /// Maps the number stored on the bits [start, start + bits) uniformly to [lower, upper]
+    ///
+    /// # Panics:
+    ///  Same as decode()
+    pub fn decode_real(&self, start: usize, bits: usize, lower: f64, upper: f64, gray: bool) -> f64 {
+        let max = if bits >= WORD { u64::MAX } else { (1u64 << bits) - 1 };
+        if max == 0 {
+            return lower;
+        }
+        lower + (upper - lower) * self.decode(start, bits, gray) as f64 / max as f64
+    }
+
+    /// Stores a number on the bits [start, start + bits), the inverse of decode()
+    ///
+    /// # Panics:
+    ///  Same as decode()
+    pub fn encode(&mut self, start: usize, bits: usize, value: u64, gray: bool) {
+        assert!(bits <= WORD && start + bits <= self.len, "bit range out of range");
+        let x = if gray { binary_to_gray(value) } else { value };
+        for i in 0..bits {
+            self.set(start + i, (x >> i) & 1 == 1);
+        }
+    }
+
+    fn clear_unused(&mut self) {
+        if !self.len.is_multiple_of(WORD) {
+            if let Some(last) = self.words.last_mut() {
+                *last &= mask(0, self.len % WORD);
+            }
+        }
+    }
+
+    /// Returns both strings with the bits [from, to) swapped
+    fn swap_range(a: &BitString, b: &BitString, from: usize, to: usize) -> (BitString, BitString) {
+        let (mut x, mut y) = (a.clone(), b.clone());
+        for w in (from / WORD)..to.div_ceil(WORD).min(x.words.len()).min(y.words.len()) {
+            let start = from.saturating_sub(w * WORD).min(WORD);
+            let end = (to - w * WORD).min(WORD);
+            let m = mask(start, end);
+            x.words[w] = (a.words[w] & !m) | (b.words[w] & m);
+            y.words[w] = (b.words[w] & !m) | (a.words[w] & m);
+        }
+        x.clear_unused();
+        y.clear_unused();
+        (x, y)
+    }
+}
+
+impl Crossover<BitString> for OnePoint {
+    fn cross(&self, a: &BitString, b: &BitString, rng: &mut dyn RngCore) -> Vec<BitString> {
+        let len = a.len().min(b.len());
+        let cut = rng.gen_range(0, len + 1);
+        let (x, y) = BitString::swap_range(a, b, cut, len);
+        vec![x, y]
+    }
+}
+
+impl Crossover<BitString> for TwoPoint {
+    fn cross(&self, a: &BitString, b: &BitString, rng: &mut dyn RngCore) -> Vec<BitString> {
+        let len = a.len().min(b.len());
+        let mut first = rng.gen_range(0, len + 1);
+        let mut second = rng.gen_range(0, len + 1);
+        if first > second {
+            std::mem::swap(&mut first, &mut second);
+        }
+        let (x, y) = BitString::swap_range(a, b, first, second);
+        vec![x, y]
+    }
+}
+
+impl Crossover<BitString> for Uniform {
+    fn cross(&self, a: &BitString, b: &BitString, rng: &mut dyn RngCore) -> Vec<BitString> {
+        let (mut x, mut y) = (a.clone(), b.clone());
+        for w in 0..x.words.len().min(y.words.len()) {
+            let m = if self.probability == 0.5 {
+                rng.next_u64()
+            } else {
+                (0..WORD)
+                    .filter(|_| rng.gen_range(0.0, 1.0) < self.probability)
+                    .fold(0u64, |m, i| m | 1 << i)
+            };
+            x.words[w] = (a.words[w] & !m) | (b.words[w] & m);
+            y.words[w] = (b.words[w] & !m) | (a.words[w] & m);
+        }
+        x.clear_unused();
+        y.clear_unused();
+        vec![x, y]
+    }
+}
+
+impl Mutation<BitString> for BitFlip {
+    fn mutate(&self, agent: &BitString, rng: &mut dyn RngCore) -> BitString {
+        let mut x = agent.clone();
+        for i in 0..x.len() {
+            if rng.gen_range(0.0, 1.0) < self.probability {
+                x.flip(i);
+            }
+        }
+        x
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bits() {
+        let mut x = BitString::zeros(100);
+        x.set(3, true);
+        x.set(70, true);
+        x.flip(99);
+        assert!(x.get(3) && x.get(70) && x.get(99) && !x.get(4));
+        assert_eq!(x.count_ones(), 3);
+        assert_eq!(BitString::from_bools(&[true, false, true]).iter().collect::<Vec<bool>>(), vec![true, false, true]);
+        assert!(BitString::random(70, &mut thread_rng()).count_ones() <= 70);
+    }
+
+    #[test]
+    fn gray_code() {
+        for x in 0..1000 {
+            assert_eq!(gray_to_binary(binary_to_gray(x)), x);
+            // neighbouring numbers differ in one bit
+            assert_eq!((binary_to_gray(x) ^ binary_to_gray(x + 1)).count_ones(), 1);
+        }
+
+        let mut x = BitString::zeros(20);
+        x.encode(4, 10, 613, true);
+        assert_eq!(x.decode(4, 10, true), 613);
+        assert_eq!(x.decode_real(4, 10, 0.0, 1023.0, true), 613.0);
+    }
+
+    #[test]
+    fn crossovers_keep_bits() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let a = BitString::zeros(150);
+        let b = BitString::from_bools(&[true; 150]);
+        let operators: Vec<Box<dyn Crossover<BitString>>> = vec![Box::new(OnePoint), Box::new(TwoPoint), Box::new(Uniform::default()), Box::new(Uniform { probability: 0.2 })];
+
+        for operator in operators.iter() {
+            for _ in 0..20 {
+                let children = operator.cross(&a, &b, &mut rng);
+                assert_eq!(children[0].count_ones() + children[1].count_ones(), 150);
+                assert!(children[0].iter().zip(children[1].iter()).all(|(x, y)| x != y));
+            }
+        }
+    }
+
+    #[test]
+    fn bit_flip() {
+        let x = BitString::zeros(130);
+        assert_eq!(BitFlip::new(1.0).mutate(&x, &mut thread_rng()).count_ones(), 130);
+        assert_eq!(BitFlip::new(0.0).mutate(&x, &mut thread_rng()), x);
+    }
+}
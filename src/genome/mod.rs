@@ -0,0 +1,15 @@
+//! Ready-made genome types with operators that fit them
+
+mod bit_string;
+mod nelder_mead;
+mod permutation;
+mod real_vector;
+mod self_adaptive;
+mod variable_length;
+
+pub use bit_string::{binary_to_gray, gray_to_binary, BitString};
+pub use nelder_mead::NelderMead;
+pub use permutation::{CycleCrossover, EdgeRecombination, InsertMutation, InversionMutation, OrderCrossover, Permutation, Pmx, ScrambleMutation};
+pub use real_vector::{BoundHandling, Bounds, Blx, GaussianMutation, PolynomialMutation, RealVector, Sbx};
+pub use self_adaptive::{SelfAdaptive, SelfAdaptiveCrossover, SelfAdaptiveMutation};
+pub use variable_length::{CutAndSplice, DeleteGene, InsertGene};
@@ -0,0 +1,188 @@
+use std::cmp::Ordering;
+
+use super::RealVector;
+use crate::{ConfigError, Objective};
+
+/// Nelder–Mead simplex local search for RealVector genomes, usable as the local search of a memetic algorithm
+/// (`LocalSearch::new(nelder_mead.local_search(fitness, objective))`) or to polish the best agent of a finished run
+///
+/// The starting simplex is made of the agent and one point for every dimension moved by `step` part of the
+/// interval of the dimension, then the usual reflection, expansion, contraction and shrink steps are made until the
+/// fitness of the best and the worst point of the simplex differ by at most `tolerance`, or for at most
+/// `max_iterations` iterations. The points are clamped to their bounds, and the refined agent is never worse than the
+/// original one. Every iteration calls the fitness function a few times, these calls are not counted as evaluations.
+///
+/// # Examples:
+/// ```
+/// use bbte_optim_tzim1773_genetic::{Genetic, LocalSearch, Objective};
+/// use bbte_optim_tzim1773_genetic::genome::{Bounds, NelderMead, PolynomialMutation, RealVector, Sbx};
+///
+/// fn main() {
+///     let bounds = Bounds::uniform(3, -5.0, 5.0);
+///     let fitness = |a: &RealVector| a.iter().map(|x| x * x).sum::<f64>();
+///     let test = Genetic::builder()
+///         .max_generation(20)
+///         .random_agent(move |rng| RealVector::random(&bounds, rng))
+///         .fitness(fitness)
+///         .minimize()
+///         .crossover(Sbx::new(15.0))
+///         .mutation(PolynomialMutation::new(20.0))
+///         .local_search(LocalSearch::new(NelderMead::new().max_iterations(50).local_search(fitness, Objective::Minimize)).interval(5))
+///         .build()
+///         .unwrap();
+///
+///     let best = NelderMead::new().refine(&test.run().best, fitness, Objective::Minimize);
+///     println!("{:?}", best.values); // should be very close to [0, 0, 0]
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NelderMead {
+    /// The most iterations of a refinement
+    /// Suggested value: 200 * dimensions (fewer inside a memetic algorithm)
+    pub max_iterations: usize,
+    /// The refinement stops when the fitness of the best and the worst point of the simplex differ by at most this
+    /// Suggested value: 1e-8
+    pub tolerance: f64,
+    /// The size of the starting simplex relative to the interval of every dimension (0.0 < step <= 1.0)
+    /// Suggested value: 0.05
+    pub step: f64,
+}
+
+impl Default for NelderMead {
+    fn default() -> Self {
+        NelderMead::new()
+    }
+}
+
+impl NelderMead {
+    /// At most 1000 iterations with a tolerance of 1e-8, starting from a simplex of 5% of the intervals
+    pub fn new() -> Self {
+        NelderMead { max_iterations: 1000, tolerance: 1e-8, step: 0.05 }
+    }
+
+    /// Sets the most iterations of a refinement
+    pub fn max_iterations(mut self, max_iterations: usize) -> Self {
+        self.max_iterations = max_iterations;
+        self
+    }
+
+    /// Sets the difference of the fitness values where the refinement stops
+    pub fn tolerance(mut self, tolerance: f64) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Sets the size of the starting simplex
+    pub fn step(mut self, step: f64) -> Self {
+        self.step = step;
+        self
+    }
+
+    /// Checks whether the parameters are usable
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.tolerance.is_nan() || self.tolerance < 0.0 {
+            return Err(ConfigError::InvalidLocalSearch(format!("the tolerance of Nelder-Mead can't be negative, got {}", self.tolerance)));
+        }
+        if !(self.step > 0.0 && self.step <= 1.0) {
+            return Err(ConfigError::InvalidLocalSearch(format!("the step of Nelder-Mead must be bigger than 0.0 and at most 1.0, got {}", self.step)));
+        }
+
+        Ok(())
+    }
+
+    /// Refines the agent with the simplex method, returns the best point found (the agent itself if nothing better
+    /// was found)
+    pub fn refine(&self, x: &RealVector, f_fitness: impl Fn(&RealVector) -> f64, objective: Objective) -> RealVector {
+        // the simplex is minimized, a maximised fitness is negated
+        let cost = |values: &[f64]| {
+            let fitness = f_fitness(&RealVector::new(values.to_vec(), x.bounds.clone()));
+            match objective {
+                Objective::Maximize => -fitness,
+                Objective::Minimize => fitness,
+            }
+        };
+        let n = x.len();
+        let start = RealVector::new(x.values.clone(), x.bounds.clone());
+        let mut simplex = vec![(start.values.clone(), cost(&start.values))];
+        for i in 0..n {
+            let mut values = start.values.clone();
+            let step = self.step * x.bounds.range(i);
+            // the point goes the other way if it would leave the bounds
+            values[i] = if values[i] + step <= x.bounds.upper[i] { values[i] + step } else { values[i] - step };
+            let c = cost(&values);
+            simplex.push((values, c));
+        }
+        let clamp = |values: Vec<f64>| RealVector::new(values, x.bounds.clone()).values;
+        let by_cost = |a: &(Vec<f64>, f64), b: &(Vec<f64>, f64)| a.1.partial_cmp(&b.1).unwrap_or(Ordering::Equal);
+
+        for _ in 0..self.max_iterations {
+            simplex.sort_by(by_cost);
+            if n == 0 || simplex[n].1 - simplex[0].1 <= self.tolerance {
+                break;
+            }
+            let centroid = (0..n).map(|i| simplex[..n].iter().map(|p| p.0[i]).sum::<f64>() / n as f64).collect::<Vec<f64>>();
+            let towards = |t: f64| clamp(centroid.iter().zip(&simplex[n].0).map(|(c, w)| c + t * (c - w)).collect());
+
+            let reflected = towards(1.0);
+            let reflected_cost = cost(&reflected);
+            if reflected_cost < simplex[0].1 {
+                let expanded = towards(2.0);
+                let expanded_cost = cost(&expanded);
+                simplex[n] = if expanded_cost < reflected_cost { (expanded, expanded_cost) } else { (reflected, reflected_cost) };
+            } else if reflected_cost < simplex[n - 1].1 {
+                simplex[n] = (reflected, reflected_cost);
+            } else {
+                let (contracted, limit) = if reflected_cost < simplex[n].1 {
+                    (towards(0.5), reflected_cost)
+                } else {
+                    (towards(-0.5), simplex[n].1)
+                };
+                let contracted_cost = cost(&contracted);
+                if contracted_cost < limit {
+                    simplex[n] = (contracted, contracted_cost);
+                } else {
+                    let best = simplex[0].0.clone();
+                    for point in &mut simplex[1..] {
+                        let values = best.iter().zip(&point.0).map(|(b, p)| b + 0.5 * (p - b)).collect::<Vec<f64>>();
+                        *point = (values.clone(), cost(&values));
+                    }
+                }
+            }
+        }
+
+        let best = simplex.into_iter().min_by(by_cost).map(|p| p.0).unwrap_or(start.values);
+        RealVector { values: best, bounds: x.bounds.clone() }
+    }
+
+    /// Returns a local search function that refines the agents with the given fitness function
+    pub fn local_search<'a>(self, f_fitness: impl Fn(&RealVector) -> f64 + Send + Sync + 'a, objective: Objective) -> impl Fn(&RealVector) -> RealVector + Send + Sync + 'a {
+        move |x| self.refine(x, &f_fitness, objective)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::genome::Bounds;
+
+    #[test]
+    fn nelder_mead() {
+        assert!(NelderMead::new().step(0.0).validate().is_err());
+        assert!(NelderMead::new().tolerance(-1.0).validate().is_err());
+
+        // the Rosenbrock function has its minimum at [1, 1]
+        let rosenbrock = |a: &RealVector| (1.0 - a[0]).powi(2) + 100.0 * (a[1] - a[0] * a[0]).powi(2);
+        let x = RealVector::new(vec![-1.0, 2.0], Bounds::uniform(2, -3.0, 3.0));
+        let refined = NelderMead::new().refine(&x, rosenbrock, Objective::Minimize);
+        assert!(refined.iter().all(|v| (v - 1.0).abs() < 1e-3));
+
+        // the optimum is outside of the bounds, the refined point stays on the bound
+        let bounds = Bounds::uniform(2, 0.0, 1.0);
+        let refined = NelderMead::new().refine(&RealVector::new(vec![0.2, 0.9], bounds), |a: &RealVector| -(a[0] - 5.0).powi(2) - a[1] * a[1], Objective::Maximize);
+        assert!((refined[0] - 1.0).abs() < 1e-6 && refined[1].abs() < 1e-3);
+
+        // without iterations the agent is returned
+        let refine = NelderMead::new().max_iterations(0).local_search(rosenbrock, Objective::Minimize);
+        assert_eq!(refine(&x), x);
+    }
+}
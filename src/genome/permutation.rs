@@ -0,0 +1,343 @@
+use rand::prelude::*;
+use std::ops::Deref;
+
+use crate::operators::{Crossover, Mutation, Swap};
+
+/// Genome holding an order of the numbers 0..n (e.g. the order of the cities of a route or of the jobs of a schedule)
+///
+/// Every operator of this module keeps it a valid permutation.
+///
+/// # Examples:
+/// ```
+/// use rand::prelude::*;
+/// use bbte_optim_tzim1773_genetic::Genetic;
+/// use bbte_optim_tzim1773_genetic::genome::{InversionMutation, OrderCrossover, Permutation};
+///
+/// fn main() {
+///     // cities on a line, the shortest route visits them in order
+///     let test = Genetic::builder()
+///         .population(50)
+///         .max_generation(100)
+///         .random_agent(|rng| Permutation::random(8, rng))
+///         .fitness(|a: &Permutation| a.windows(2).map(|w| (w[0] as i64 - w[1] as i64).abs()).sum::<i64>())
+///         .minimize()
+///         .crossover(OrderCrossover)
+///         .mutation(InversionMutation)
+///         .build()
+///         .unwrap();
+///
+///     println!("{:?}", test.run().best); // should be 0..8 or 8..0
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Permutation {
+    order: Vec<usize>,
+}
+
+impl Permutation {
+    /// The permutation 0, 1, ..., n - 1
+    pub fn identity(n: usize) -> Self {
+        Permutation { order: (0..n).collect() }
+    }
+
+    /// A uniformly random permutation of 0..n
+    pub fn random(n: usize, rng: &mut dyn RngCore) -> Self {
+        let mut x = Permutation::identity(n);
+        x.order.shuffle(rng);
+        x
+    }
+
+    /// Creates a permutation from a vector, returns None if it doesn't contain every number of 0..n exactly once
+    pub fn from_vec(order: Vec<usize>) -> Option<Self> {
+        let mut seen = vec![false; order.len()];
+        for x in order.iter() {
+            if *x >= order.len() || seen[*x] {
+                return None;
+            }
+            seen[*x] = true;
+        }
+
+        Some(Permutation { order })
+    }
+
+    /// Returns the order as a vector
+    pub fn into_vec(self) -> Vec<usize> {
+        self.order
+    }
+
+    /// Returns the position of every number (the inverse permutation)
+    pub fn positions(&self) -> Vec<usize> {
+        let mut positions = vec![0; self.order.len()];
+        for (i, x) in self.order.iter().enumerate() {
+            positions[*x] = i;
+        }
+        positions
+    }
+}
+
+impl Deref for Permutation {
+    type Target = [usize];
+
+    fn deref(&self) -> &[usize] {
+        &self.order
+    }
+}
+
+/// Returns two random positions i <= j (j is exclusive, at most `len`)
+fn cut_points(len: usize, rng: &mut dyn RngCore) -> (usize, usize) {
+    let mut i = rng.gen_range(0, len + 1);
+    let mut j = rng.gen_range(0, len + 1);
+    if i > j {
+        std::mem::swap(&mut i, &mut j);
+    }
+    (i, j)
+}
+
+/// Order crossover (OX): the child keeps a segment of the first parent, the other numbers follow
+/// in the order of the second parent (creates two children)
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct OrderCrossover;
+
+fn order_child(a: &[usize], b: &[usize], i: usize, j: usize) -> Vec<usize> {
+    let n = a.len();
+    let mut used = vec![false; n];
+    for x in a[i..j].iter() {
+        used[*x] = true;
+    }
+
+    let mut child = a.to_vec();
+    let mut rest = (0..n)
+        .map(|k| b[(j + k) % n])
+        .filter(|x| !used[*x]);
+    for k in 0..(n - (j - i)) {
+        child[(j + k) % n] = rest.next().unwrap();
+    }
+    child
+}
+
+impl Crossover<Permutation> for OrderCrossover {
+    fn cross(&self, a: &Permutation, b: &Permutation, rng: &mut dyn RngCore) -> Vec<Permutation> {
+        let (i, j) = cut_points(a.len(), rng);
+
+        vec![
+            Permutation { order: order_child(a, b, i, j) },
+            Permutation { order: order_child(b, a, i, j) },
+        ]
+    }
+}
+
+/// Partially mapped crossover (PMX): the child keeps a segment of the first parent, the other positions
+/// come from the second parent, conflicts are resolved through the mapping of the segment (creates two children)
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Pmx;
+
+fn pmx_child(a: &Permutation, b: &Permutation, i: usize, j: usize) -> Vec<usize> {
+    let a_positions = a.positions();
+    let mut child = b.to_vec();
+    child[i..j].copy_from_slice(&a[i..j]);
+
+    for k in (0..i).chain(j..a.len()) {
+        let mut x = b[k];
+        // x is already in the segment, follow the mapping until a free number is found
+        while (i..j).contains(&a_positions[x]) {
+            x = b[a_positions[x]];
+        }
+        child[k] = x;
+    }
+    child
+}
+
+impl Crossover<Permutation> for Pmx {
+    fn cross(&self, a: &Permutation, b: &Permutation, rng: &mut dyn RngCore) -> Vec<Permutation> {
+        let (i, j) = cut_points(a.len(), rng);
+
+        vec![
+            Permutation { order: pmx_child(a, b, i, j) },
+            Permutation { order: pmx_child(b, a, i, j) },
+        ]
+    }
+}
+
+/// Cycle crossover (CX): the positions are split into cycles, the children take the cycles alternately
+/// from the two parents, so every number keeps the position it had in one of the parents (creates two children)
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct CycleCrossover;
+
+impl Crossover<Permutation> for CycleCrossover {
+    fn cross(&self, a: &Permutation, b: &Permutation, _rng: &mut dyn RngCore) -> Vec<Permutation> {
+        let a_positions = a.positions();
+        let (mut x, mut y) = (a.to_vec(), b.to_vec());
+        let mut visited = vec![false; a.len()];
+        let mut cycle = 0;
+
+        for start in 0..a.len() {
+            if visited[start] {
+                continue;
+            }
+            let mut k = start;
+            while !visited[k] {
+                visited[k] = true;
+                if cycle % 2 == 1 {
+                    x[k] = b[k];
+                    y[k] = a[k];
+                }
+                k = a_positions[b[k]];
+            }
+            cycle += 1;
+        }
+
+        vec![Permutation { order: x }, Permutation { order: y }]
+    }
+}
+
+/// Edge recombination (ERX): the child is built from the edges (neighbours) of the parents, always moving to
+/// the neighbour that has the fewest remaining neighbours, good for routing problems (creates one child)
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct EdgeRecombination;
+
+impl Crossover<Permutation> for EdgeRecombination {
+    fn cross(&self, a: &Permutation, b: &Permutation, rng: &mut dyn RngCore) -> Vec<Permutation> {
+        let n = a.len();
+        if n == 0 {
+            return vec![a.clone()];
+        }
+
+        let mut neighbours = vec![Vec::with_capacity(4); n];
+        for parent in [a, b].iter() {
+            for k in 0..n {
+                let x = parent[k];
+                for y in [parent[(k + n - 1) % n], parent[(k + 1) % n]].iter() {
+                    if *y != x && !neighbours[x].contains(y) {
+                        neighbours[x].push(*y);
+                    }
+                }
+            }
+        }
+
+        let mut used = vec![false; n];
+        let mut child = Vec::with_capacity(n);
+        let mut current = a[0];
+        loop {
+            child.push(current);
+            used[current] = true;
+            for list in neighbours.iter_mut() {
+                list.retain(|y| *y != current);
+            }
+            if child.len() == n {
+                break;
+            }
+
+            let fewest = neighbours[current].iter().map(|y| neighbours[*y].len()).min();
+            current = match fewest {
+                Some(fewest) => {
+                    let candidates = neighbours[current].iter()
+                        .filter(|y| neighbours[**y].len() == fewest)
+                        .cloned()
+                        .collect::<Vec<usize>>();
+                    candidates[rng.gen_range(0, candidates.len())]
+                }
+                None => {
+                    let unused = (0..n).filter(|y| !used[*y]).collect::<Vec<usize>>();
+                    unused[rng.gen_range(0, unused.len())]
+                }
+            };
+        }
+
+        vec![Permutation { order: child }]
+    }
+}
+
+impl Mutation<Permutation> for Swap {
+    fn mutate(&self, agent: &Permutation, rng: &mut dyn RngCore) -> Permutation {
+        Permutation { order: self.mutate(&agent.order, rng) }
+    }
+}
+
+/// A random number is moved to a random position
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct InsertMutation;
+
+impl Mutation<Permutation> for InsertMutation {
+    fn mutate(&self, agent: &Permutation, rng: &mut dyn RngCore) -> Permutation {
+        let mut x = agent.clone();
+        if x.len() > 1 {
+            let from = rng.gen_range(0, x.len());
+            let to = rng.gen_range(0, x.len());
+            let moved = x.order.remove(from);
+            x.order.insert(to, moved);
+        }
+        x
+    }
+}
+
+/// A random segment is reversed (2-opt move for routes)
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct InversionMutation;
+
+impl Mutation<Permutation> for InversionMutation {
+    fn mutate(&self, agent: &Permutation, rng: &mut dyn RngCore) -> Permutation {
+        let mut x = agent.clone();
+        let (i, j) = cut_points(x.len(), rng);
+        x.order[i..j].reverse();
+        x
+    }
+}
+
+/// A random segment is shuffled
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct ScrambleMutation;
+
+impl Mutation<Permutation> for ScrambleMutation {
+    fn mutate(&self, agent: &Permutation, rng: &mut dyn RngCore) -> Permutation {
+        let mut x = agent.clone();
+        let (i, j) = cut_points(x.len(), rng);
+        x.order[i..j].shuffle(rng);
+        x
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_valid(x: &Permutation) -> bool {
+        Permutation::from_vec(x.to_vec()).is_some()
+    }
+
+    #[test]
+    fn from_vec() {
+        assert!(Permutation::from_vec(vec![2, 0, 1]).is_some());
+        assert!(Permutation::from_vec(vec![2, 0, 2]).is_none());
+        assert!(Permutation::from_vec(vec![3, 0, 1]).is_none());
+    }
+
+    #[test]
+    fn known_children() {
+        let a = Permutation::from_vec(vec![0, 1, 2, 3, 4, 5, 6, 7]).unwrap();
+        let b = Permutation::from_vec(vec![3, 7, 5, 1, 6, 0, 2, 4]).unwrap();
+
+        assert_eq!(order_child(&a, &b, 3, 6), vec![1, 6, 0, 3, 4, 5, 2, 7]);
+        assert_eq!(pmx_child(&a, &b, 3, 6), vec![1, 7, 0, 3, 4, 5, 2, 6]);
+        // the cycles are (0 3 1 7 4 6 2 5), so CX keeps the parents
+        let children = CycleCrossover.cross(&a, &b, &mut thread_rng());
+        assert_eq!(children, vec![a, b]);
+    }
+
+    #[test]
+    fn operators_keep_permutations() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let crossovers: Vec<Box<dyn Crossover<Permutation>>> = vec![Box::new(OrderCrossover), Box::new(Pmx), Box::new(CycleCrossover), Box::new(EdgeRecombination)];
+        let mutations: Vec<Box<dyn Mutation<Permutation>>> = vec![Box::new(Swap), Box::new(InsertMutation), Box::new(InversionMutation), Box::new(ScrambleMutation)];
+
+        for _ in 0..50 {
+            let a = Permutation::random(12, &mut rng);
+            let b = Permutation::random(12, &mut rng);
+            for crossover in crossovers.iter() {
+                assert!(crossover.cross(&a, &b, &mut rng).iter().all(is_valid));
+            }
+            for mutation in mutations.iter() {
+                assert!(is_valid(&mutation.mutate(&a, &mut rng)));
+            }
+        }
+    }
+}
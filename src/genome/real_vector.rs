@@ -0,0 +1,614 @@
+use rand::prelude::*;
+use std::ops::Deref;
+use std::sync::Arc;
+
+use crate::operators::{standard_normal, Crossover, Mutation};
+
+/// Lower and upper bounds of every dimension of a RealVector
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bounds {
+    /// Smallest allowed value of every dimension
+    pub lower: Vec<f64>,
+    /// Biggest allowed value of every dimension
+    pub upper: Vec<f64>,
+}
+
+impl Bounds {
+    /// Creates the bounds of the dimensions
+    ///
+    /// # Panics:
+    ///  If the two vectors have different lengths or a lower bound is bigger than the upper one
+    pub fn new(lower: Vec<f64>, upper: Vec<f64>) -> Arc<Self> {
+        assert_eq!(lower.len(), upper.len(), "lower and upper bounds must have the same length");
+        assert!(lower.iter().zip(upper.iter()).all(|(l, u)| l <= u), "lower bounds must not be bigger than the upper bounds");
+
+        Arc::new(Bounds { lower, upper })
+    }
+
+    /// Every one of the `dimensions` dimensions is between lower and upper
+    pub fn uniform(dimensions: usize, lower: f64, upper: f64) -> Arc<Self> {
+        Bounds::new(vec![lower; dimensions], vec![upper; dimensions])
+    }
+
+    /// Number of dimensions
+    pub fn len(&self) -> usize {
+        self.lower.len()
+    }
+
+    /// Returns true if there are no dimensions
+    pub fn is_empty(&self) -> bool {
+        self.lower.is_empty()
+    }
+
+    /// Returns the length of the interval of the i-th dimension
+    pub fn range(&self, i: usize) -> f64 {
+        self.upper[i] - self.lower[i]
+    }
+}
+
+/// What happens with the values that leave their bounds
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum BoundHandling {
+    /// The value is set to the bound it crossed
+    #[default]
+    Clamp,
+    /// The value is mirrored back from the bound it crossed
+    Reflect,
+}
+
+impl BoundHandling {
+    /// Returns the value moved into [lower, upper]
+    pub fn apply(&self, x: f64, lower: f64, upper: f64) -> f64 {
+        match self {
+            BoundHandling::Clamp => x.max(lower).min(upper),
+            BoundHandling::Reflect => {
+                let range = upper - lower;
+                if range <= 0.0 || !x.is_finite() {
+                    return x.max(lower).min(upper);
+                }
+                let mut t = (x - lower) % (2.0 * range);
+                if t < 0.0 {
+                    t += 2.0 * range;
+                }
+                if t > range {
+                    t = 2.0 * range - t;
+                }
+                lower + t
+            }
+        }
+    }
+}
+
+/// Genome of real numbers, each of them between the bounds of its dimension
+///
+/// The bounds are shared between the agents (cloning an agent only copies the values).
+///
+/// # Examples:
+/// ```
+/// use rand::prelude::*;
+/// use bbte_optim_tzim1773_genetic::Genetic;
+/// use bbte_optim_tzim1773_genetic::genome::{Bounds, PolynomialMutation, RealVector, Sbx};
+///
+/// fn main() {
+///     let bounds = Bounds::uniform(3, -5.0, 5.0);
+///     let test = Genetic::builder()
+///         .population(50)
+///         .max_generation(100)
+///         .random_agent(move |rng| RealVector::random(&bounds, rng))
+///         .fitness(|a: &RealVector| a.iter().map(|x| x * x).sum::<f64>())
+///         .minimize()
+///         .crossover(Sbx::new(15.0))
+///         .mutation(PolynomialMutation::new(20.0))
+///         .build()
+///         .unwrap();
+///
+///     println!("{:?}", test.run().best.values); // should be close to [0, 0, 0]
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct RealVector {
+    /// The value of every dimension
+    pub values: Vec<f64>,
+    /// The bounds of every dimension
+    pub bounds: Arc<Bounds>,
+}
+
+impl RealVector {
+    /// Creates a vector, the values outside of the bounds are clamped
+    ///
+    /// # Panics:
+    ///  If the number of values and bounds differ
+    pub fn new(values: Vec<f64>, bounds: Arc<Bounds>) -> Self {
+        assert_eq!(values.len(), bounds.len(), "the vector must have a value for every dimension");
+
+        let mut x = RealVector { values, bounds };
+        x.repair(BoundHandling::Clamp);
+        x
+    }
+
+    /// Creates a vector with uniformly distributed values between the bounds
+    pub fn random(bounds: &Arc<Bounds>, rng: &mut dyn RngCore) -> Self {
+        let values = bounds.lower.iter()
+            .zip(bounds.upper.iter())
+            .map(|(l, u)| if l < u { rng.gen_range(*l, *u) } else { *l })
+            .collect();
+
+        RealVector { values, bounds: Arc::clone(bounds) }
+    }
+
+    /// Creates `count` vectors by Latin hypercube sampling: the interval of every dimension is cut into `count`
+    /// equal strata, and every stratum of every dimension holds exactly one vector (at a random point of it)
+    ///
+    /// The space-filling initializations are meant for the initial_population of the builder:
+    /// .initial_population(RealVector::latin_hypercube(&bounds, 100, &mut rng))
+    pub fn latin_hypercube(bounds: &Arc<Bounds>, count: usize, rng: &mut dyn RngCore) -> Vec<Self> {
+        let strata = (0..bounds.len())
+            .map(|_| {
+                let mut strata = (0..count).collect::<Vec<usize>>();
+                strata.shuffle(rng);
+                strata
+            })
+            .collect::<Vec<Vec<usize>>>();
+
+        (0..count)
+            .map(|k| {
+                let values = strata.iter()
+                    .enumerate()
+                    .map(|(i, strata)| bounds.lower[i] + bounds.range(i) * (strata[k] as f64 + rng.gen::<f64>()) / count as f64)
+                    .collect();
+                RealVector { values, bounds: Arc::clone(bounds) }
+            })
+            .collect()
+    }
+
+    /// Creates `count` vectors from the scrambled Halton sequence (a low-discrepancy sequence: the points fill
+    /// the space evenly, without the clusters and gaps of random points), the digits of every dimension are
+    /// permuted randomly to break the correlation of the dimensions with big prime bases
+    pub fn halton(bounds: &Arc<Bounds>, count: usize, rng: &mut dyn RngCore) -> Vec<Self> {
+        let bases = primes(bounds.len());
+        // the digit 0 isn't moved, so the trailing zeros of the index stay zeros
+        let permutations = bases.iter()
+            .map(|base| {
+                let mut digits = (1..*base).collect::<Vec<usize>>();
+                digits.shuffle(rng);
+                digits.insert(0, 0);
+                digits
+            })
+            .collect::<Vec<Vec<usize>>>();
+
+        (1..=count)
+            .map(|k| {
+                let values = bases.iter()
+                    .zip(&permutations)
+                    .enumerate()
+                    .map(|(i, (base, digits))| bounds.lower[i] + bounds.range(i) * radical_inverse(k, *base, digits))
+                    .collect();
+                RealVector { values, bounds: Arc::clone(bounds) }
+            })
+            .collect()
+    }
+
+    /// Creates `count` vectors from the Sobol sequence (a low-discrepancy sequence built from binary digits: every
+    /// dimension of the first 2^k points has exactly one point in each of the 2^k equal parts of its interval),
+    /// with a random digital shift of every dimension (the points are XORed with a random number, which keeps
+    /// this property)
+    ///
+    /// The first 21 dimensions use the direction numbers of Joe and Kuo, the further ones random initial direction
+    /// numbers (on the next primitive polynomials)
+    pub fn sobol(bounds: &Arc<Bounds>, count: usize, rng: &mut dyn RngCore) -> Vec<Self> {
+        let directions = (0..bounds.len()).map(|i| sobol_directions(i, rng)).collect::<Vec<[u32; 32]>>();
+        let shifts = (0..bounds.len()).map(|_| rng.gen::<u32>()).collect::<Vec<u32>>();
+        let mut x = vec![0u32; bounds.len()];
+
+        (0..count)
+            .map(|k| {
+                // the Gray code order: the next point differs in the direction of the lowest zero bit of k - 1
+                if k > 0 {
+                    let bit = (k - 1).trailing_ones() as usize;
+                    for (x, directions) in x.iter_mut().zip(&directions) {
+                        *x ^= directions[bit];
+                    }
+                }
+                let values = x.iter()
+                    .zip(&shifts)
+                    .enumerate()
+                    .map(|(i, (x, shift))| bounds.lower[i] + bounds.range(i) * (x ^ shift) as f64 / 2f64.powi(32))
+                    .collect();
+                RealVector { values, bounds: Arc::clone(bounds) }
+            })
+            .collect()
+    }
+
+    /// Creates the centres of the cells of a uniform grid: the interval of every dimension is cut into the same
+    /// number of parts, as many as possible with at most `count` cells (so in many dimensions the grid can be a
+    /// single cell, the rest of the population has to come from elsewhere)
+    pub fn grid(bounds: &Arc<Bounds>, count: usize) -> Vec<Self> {
+        if bounds.is_empty() || count == 0 {
+            return Vec::new();
+        }
+        let dimensions = bounds.len() as u32;
+        let mut parts = 1usize;
+        while (parts + 1).checked_pow(dimensions).is_some_and(|cells| cells <= count) {
+            parts += 1;
+        }
+        let cells = parts.pow(dimensions);
+
+        (0..cells)
+            .map(|mut cell| {
+                let values = (0..bounds.len())
+                    .map(|i| {
+                        let part = cell % parts;
+                        cell /= parts;
+                        bounds.lower[i] + bounds.range(i) * (part as f64 + 0.5) / parts as f64
+                    })
+                    .collect();
+                RealVector { values, bounds: Arc::clone(bounds) }
+            })
+            .collect()
+    }
+
+    /// Returns the opposite vector: every value mirrored to the other side of the middle of its interval
+    /// (lower + upper - x), used by opposition-based learning: Opposition::new(RealVector::opposite)
+    pub fn opposite(&self) -> Self {
+        let values = self.values.iter()
+            .enumerate()
+            .map(|(i, x)| self.bounds.lower[i] + self.bounds.upper[i] - x)
+            .collect();
+
+        RealVector { values, bounds: Arc::clone(&self.bounds) }
+    }
+
+    /// Moves every value back between its bounds
+    pub fn repair(&mut self, handling: BoundHandling) {
+        for (i, x) in self.values.iter_mut().enumerate() {
+            *x = handling.apply(*x, self.bounds.lower[i], self.bounds.upper[i]);
+        }
+    }
+
+    fn with_values(&self, values: Vec<f64>, handling: BoundHandling) -> Self {
+        let mut x = RealVector { values, bounds: Arc::clone(&self.bounds) };
+        x.repair(handling);
+        x
+    }
+}
+
+/// Returns the first `count` prime numbers
+fn primes(count: usize) -> Vec<usize> {
+    let mut primes = Vec::with_capacity(count);
+    let mut n = 2;
+    while primes.len() < count {
+        if primes.iter().take_while(|p| *p * *p <= n).all(|p| n % p != 0) {
+            primes.push(n);
+        }
+        n += 1;
+    }
+    primes
+}
+
+/// The degrees, the inner coefficients and the initial direction numbers of the primitive polynomials of the 2nd to the
+/// 21st dimension of the Sobol sequence (new-joe-kuo-6.21201 by S. Joe and F. Y. Kuo)
+const JOE_KUO: [(u32, u32, &[u32]); 20] = [
+    (1, 0, &[1]),
+    (2, 1, &[1, 3]),
+    (3, 1, &[1, 3, 1]),
+    (3, 2, &[1, 1, 1]),
+    (4, 1, &[1, 1, 3, 3]),
+    (4, 4, &[1, 3, 5, 13]),
+    (5, 2, &[1, 1, 5, 5, 17]),
+    (5, 4, &[1, 1, 5, 5, 5]),
+    (5, 7, &[1, 1, 7, 11, 19]),
+    (5, 11, &[1, 1, 5, 1, 1]),
+    (5, 13, &[1, 1, 1, 3, 11]),
+    (5, 14, &[1, 3, 5, 5, 31]),
+    (6, 1, &[1, 3, 3, 9, 7, 49]),
+    (6, 13, &[1, 1, 1, 15, 21, 21]),
+    (6, 16, &[1, 3, 1, 13, 27, 49]),
+    (6, 19, &[1, 1, 1, 15, 7, 5]),
+    (6, 22, &[1, 3, 1, 15, 13, 25]),
+    (6, 25, &[1, 1, 5, 5, 19, 61]),
+    (7, 1, &[1, 3, 7, 11, 23, 15, 103]),
+    (7, 4, &[1, 3, 7, 13, 13, 15, 69]),
+];
+
+/// Returns the primitive polynomials over GF(2) after the first `skip` ones (ordered by the degree, then by the
+/// coefficients) as their degrees and inner coefficients (without the highest and the constant term)
+fn primitive_polynomials(skip: usize) -> impl Iterator<Item = (u32, u32)> {
+    (1..32u32)
+        .flat_map(|degree| (0..1u32 << (degree - 1)).map(move |a| (degree, a)))
+        .filter(|(degree, a)| {
+            // x has the order 2^degree - 1 modulo the polynomial
+            let polynomial = (1u64 << degree) | ((*a as u64) << 1) | 1;
+            let order = (1u64 << degree) - 1;
+            let mut power = 1u64;
+            for k in 1..=order {
+                power <<= 1;
+                if power & (1 << degree) != 0 {
+                    power ^= polynomial;
+                }
+                if power == 1 {
+                    return k == order;
+                }
+            }
+            false
+        })
+        .skip(skip)
+}
+
+/// Returns the 32 direction numbers (as the bits after the binary point) of the i-th dimension of the Sobol sequence
+fn sobol_directions(i: usize, rng: &mut dyn RngCore) -> [u32; 32] {
+    let mut v = [0u32; 32];
+    if i == 0 {
+        for (j, v) in v.iter_mut().enumerate() {
+            *v = 1 << (31 - j);
+        }
+        return v;
+    }
+    let (degree, a, initial) = match JOE_KUO.get(i - 1) {
+        Some((degree, a, initial)) => (*degree, *a, initial.to_vec()),
+        None => {
+            let (degree, a) = primitive_polynomials(i - 1).next().expect("there are enough primitive polynomials");
+            // odd numbers below 2^j
+            (degree, a, (1..=degree).map(|j| 2 * rng.gen_range(0, 1u32 << (j - 1)) + 1).collect())
+        }
+    };
+
+    let s = degree as usize;
+    for j in 0..32 {
+        v[j] = if j < s {
+            initial[j] << (31 - j)
+        } else {
+            let mut next = v[j - s] ^ (v[j - s] >> s);
+            for k in 1..s {
+                if (a >> (s - 1 - k)) & 1 == 1 {
+                    next ^= v[j - k];
+                }
+            }
+            next
+        };
+    }
+    v
+}
+
+/// Returns the digits of the index in the base, mirrored to the other side of the point (with the digits permuted)
+fn radical_inverse(mut index: usize, base: usize, digits: &[usize]) -> f64 {
+    let (mut value, mut scale) = (0.0, 1.0 / base as f64);
+    while index > 0 {
+        value += digits[index % base] as f64 * scale;
+        index /= base;
+        scale /= base as f64;
+    }
+    value
+}
+
+impl Deref for RealVector {
+    type Target = [f64];
+
+    fn deref(&self) -> &[f64] {
+        &self.values
+    }
+}
+
+/// Blend crossover (BLX-alpha): every value of the children is chosen uniformly from the interval of
+/// the parents' values, extended by alpha times its length on both sides (creates two children)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Blx {
+    /// How much the interval is extended (0.5 is usual)
+    pub alpha: f64,
+    /// What happens with the values that leave their bounds
+    pub handling: BoundHandling,
+}
+
+impl Blx {
+    /// BLX-alpha crossover clamping the values
+    pub fn new(alpha: f64) -> Self {
+        Blx { alpha, handling: BoundHandling::Clamp }
+    }
+}
+
+impl Crossover<RealVector> for Blx {
+    fn cross(&self, a: &RealVector, b: &RealVector, rng: &mut dyn RngCore) -> Vec<RealVector> {
+        let mut child = || a.iter()
+            .zip(b.iter())
+            .map(|(x, y)| {
+                let d = (x - y).abs();
+                let low = x.min(*y) - self.alpha * d;
+                let high = x.max(*y) + self.alpha * d;
+                if low < high { rng.gen_range(low, high) } else { low }
+            })
+            .collect::<Vec<f64>>();
+
+        let (x, y) = (child(), child());
+        vec![a.with_values(x, self.handling), a.with_values(y, self.handling)]
+    }
+}
+
+/// Simulated binary crossover (SBX): the children are spread around the parents like the children of a
+/// one-point crossover of binary strings, bigger eta keeps them closer to the parents (creates two children)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sbx {
+    /// Distribution index (2-20 is usual)
+    pub eta: f64,
+    /// What happens with the values that leave their bounds
+    pub handling: BoundHandling,
+}
+
+impl Sbx {
+    /// SBX crossover clamping the values
+    pub fn new(eta: f64) -> Self {
+        Sbx { eta, handling: BoundHandling::Clamp }
+    }
+}
+
+impl Crossover<RealVector> for Sbx {
+    fn cross(&self, a: &RealVector, b: &RealVector, rng: &mut dyn RngCore) -> Vec<RealVector> {
+        let (mut x, mut y) = (Vec::with_capacity(a.len()), Vec::with_capacity(a.len()));
+        for (p, q) in a.iter().zip(b.iter()) {
+            let u: f64 = rng.gen_range(0.0, 1.0);
+            let beta = if u <= 0.5 {
+                (2.0 * u).powf(1.0 / (self.eta + 1.0))
+            } else {
+                (1.0 / (2.0 * (1.0 - u))).powf(1.0 / (self.eta + 1.0))
+            };
+            x.push(0.5 * ((1.0 + beta) * p + (1.0 - beta) * q));
+            y.push(0.5 * ((1.0 - beta) * p + (1.0 + beta) * q));
+        }
+
+        vec![a.with_values(x, self.handling), a.with_values(y, self.handling)]
+    }
+}
+
+/// Normally distributed noise is added to the values, its standard deviation is relative to the
+/// length of the dimension's interval
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GaussianMutation {
+    /// Standard deviation of the noise relative to the length of the interval (0.1 is 10% of the interval)
+    pub sigma: f64,
+    /// Probability of changing a value (1.0 changes every value)
+    pub probability: f64,
+    /// What happens with the values that leave their bounds
+    pub handling: BoundHandling,
+}
+
+impl GaussianMutation {
+    /// Changes every value, clamping them to their bounds
+    pub fn new(sigma: f64) -> Self {
+        GaussianMutation { sigma, probability: 1.0, handling: BoundHandling::Clamp }
+    }
+}
+
+impl Mutation<RealVector> for GaussianMutation {
+    fn mutate(&self, agent: &RealVector, rng: &mut dyn RngCore) -> RealVector {
+        let values = agent.iter()
+            .enumerate()
+            .map(|(i, x)| {
+                if rng.gen_range(0.0, 1.0) < self.probability {
+                    return x + self.sigma * agent.bounds.range(i) * standard_normal(rng);
+                }
+                *x
+            })
+            .collect();
+
+        agent.with_values(values, self.handling)
+    }
+}
+
+/// Polynomial mutation: the values are moved by a polynomially distributed fraction of their interval,
+/// bigger eta means smaller steps
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PolynomialMutation {
+    /// Distribution index (20 is usual)
+    pub eta: f64,
+    /// Probability of changing a value (usually 1 / dimensions)
+    pub probability: f64,
+    /// What happens with the values that leave their bounds
+    pub handling: BoundHandling,
+}
+
+impl PolynomialMutation {
+    /// Changes every value, clamping them to their bounds
+    pub fn new(eta: f64) -> Self {
+        PolynomialMutation { eta, probability: 1.0, handling: BoundHandling::Clamp }
+    }
+}
+
+impl Mutation<RealVector> for PolynomialMutation {
+    fn mutate(&self, agent: &RealVector, rng: &mut dyn RngCore) -> RealVector {
+        let values = agent.iter()
+            .enumerate()
+            .map(|(i, x)| {
+                if rng.gen_range(0.0, 1.0) >= self.probability {
+                    return *x;
+                }
+                let u: f64 = rng.gen_range(0.0, 1.0);
+                let delta = if u < 0.5 {
+                    (2.0 * u).powf(1.0 / (self.eta + 1.0)) - 1.0
+                } else {
+                    1.0 - (2.0 * (1.0 - u)).powf(1.0 / (self.eta + 1.0))
+                };
+                x + delta * agent.bounds.range(i)
+            })
+            .collect();
+
+        agent.with_values(values, self.handling)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bound_handling() {
+        assert_eq!(BoundHandling::Clamp.apply(7.0, 0.0, 5.0), 5.0);
+        assert_eq!(BoundHandling::Clamp.apply(-1.0, 0.0, 5.0), 0.0);
+        assert_eq!(BoundHandling::Reflect.apply(7.0, 0.0, 5.0), 3.0);
+        assert_eq!(BoundHandling::Reflect.apply(-1.0, 0.0, 5.0), 1.0);
+        assert_eq!(BoundHandling::Reflect.apply(12.0, 0.0, 5.0), 2.0);
+        assert_eq!(BoundHandling::Reflect.apply(2.0, 0.0, 5.0), 2.0);
+    }
+
+    #[test]
+    fn operators_stay_in_bounds() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let bounds = Bounds::new(vec![0.0, -1.0], vec![1.0, 1.0]);
+        let a = RealVector::new(vec![0.0, 1.0], Arc::clone(&bounds));
+        let b = RealVector::new(vec![1.0, -1.0], Arc::clone(&bounds));
+        let in_bounds = |x: &RealVector| x.iter().enumerate().all(|(i, v)| bounds.lower[i] <= *v && *v <= bounds.upper[i]);
+
+        for _ in 0..100 {
+            assert!(Blx::new(0.5).cross(&a, &b, &mut rng).iter().all(in_bounds));
+            assert!(Sbx { eta: 2.0, handling: BoundHandling::Reflect }.cross(&a, &b, &mut rng).iter().all(in_bounds));
+            assert!(in_bounds(&GaussianMutation::new(1.0).mutate(&a, &mut rng)));
+            assert!(in_bounds(&PolynomialMutation::new(1.0).mutate(&b, &mut rng)));
+        }
+    }
+
+    #[test]
+    fn space_filling() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let bounds = Bounds::new(vec![0.0, -10.0, 5.0], vec![1.0, 10.0, 5.0]);
+        let samples = RealVector::latin_hypercube(&bounds, 8, &mut rng);
+        for i in 0..2 {
+            let mut strata = samples.iter().map(|x| ((x[i] - bounds.lower[i]) / bounds.range(i) * 8.0) as usize).collect::<Vec<usize>>();
+            strata.sort_unstable();
+            assert_eq!(strata, (0..8).collect::<Vec<usize>>());
+        }
+        assert!(samples.iter().all(|x| x[2] == 5.0));
+
+        assert_eq!(primes(6), vec![2, 3, 5, 7, 11, 13]);
+        assert_eq!((1..4).map(|k| radical_inverse(k, 2, &[0, 1])).collect::<Vec<f64>>(), vec![0.5, 0.25, 0.75]);
+        assert_eq!(radical_inverse(5, 3, &[0, 2, 1]), 1.0 / 3.0 + 2.0 / 9.0);
+        let halton = RealVector::halton(&Bounds::uniform(20, -1.0, 1.0), 50, &mut rng);
+        assert_eq!(halton.len(), 50);
+        assert!(halton.iter().all(|x| x.iter().all(|v| (-1.0..1.0).contains(v))));
+
+        // the table follows the primitive polynomials, and every dimension of 2^k points fills the 2^k parts
+        assert!(primitive_polynomials(0).take(20).zip(JOE_KUO.iter()).all(|(polynomial, (degree, a, _))| polynomial == (*degree, *a)));
+        let sobol = RealVector::sobol(&Bounds::uniform(30, 0.0, 1.0), 64, &mut rng);
+        assert_eq!(sobol.len(), 64);
+        for i in 0..30 {
+            let mut parts = sobol.iter().map(|x| (x[i] * 64.0) as usize).collect::<Vec<usize>>();
+            parts.sort_unstable();
+            assert_eq!(parts, (0..64).collect::<Vec<usize>>(), "dimension {}", i);
+        }
+        // the first two dimensions of 16 points put one point into every cell of a 4x4 grid
+        let mut cells = sobol[..16].iter().map(|x| (x[0] * 4.0) as usize * 4 + (x[1] * 4.0) as usize).collect::<Vec<usize>>();
+        cells.sort_unstable();
+        assert_eq!(cells, (0..16).collect::<Vec<usize>>());
+
+        let grid = RealVector::grid(&Bounds::uniform(2, 0.0, 6.0), 10);
+        assert_eq!(grid.iter().map(|x| x.values.clone()).collect::<Vec<Vec<f64>>>()[..4], [vec![1.0, 1.0], vec![3.0, 1.0], vec![5.0, 1.0], vec![1.0, 3.0]]);
+        assert_eq!(grid.len(), 9);
+        assert_eq!(RealVector::grid(&Bounds::uniform(20, 0.0, 2.0), 100)[0].values, vec![1.0; 20]);
+        assert!(RealVector::grid(&Bounds::new(Vec::new(), Vec::new()), 10).is_empty());
+        assert!(RealVector::grid(&Bounds::uniform(2, 0.0, 1.0), 0).is_empty());
+
+        assert_eq!(RealVector::new(vec![0.5, 4.0], Bounds::new(vec![0.0, 2.0], vec![1.0, 10.0])).opposite().values, vec![0.5, 8.0]);
+    }
+
+    #[test]
+    fn new_clamps() {
+        let x = RealVector::new(vec![-3.0, 3.0], Bounds::uniform(2, -1.0, 1.0));
+        assert_eq!(x.values, vec![-1.0, 1.0]);
+    }
+}
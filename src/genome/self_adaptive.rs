@@ -0,0 +1,188 @@
+use rand::prelude::*;
+use std::ops::Deref;
+
+use crate::operators::{standard_normal, Crossover, Mutation};
+
+/// Genome of real numbers that carries its own mutation strengths (evolution strategy style
+/// self-adaptation): the strengths are mutated together with the genome, so the scale of the mutation
+/// tunes itself during the run
+///
+/// There is either one strength for the whole individual or one for every gene.
+///
+/// # Examples:
+/// ```
+/// use rand::prelude::*;
+/// use bbte_optim_tzim1773_genetic::{EvolutionStrategy, Genetic};
+/// use bbte_optim_tzim1773_genetic::genome::{SelfAdaptive, SelfAdaptiveCrossover, SelfAdaptiveMutation};
+/// use bbte_optim_tzim1773_genetic::operators::Arithmetic;
+///
+/// fn main() {
+///     let test = Genetic::builder()
+///         .population(10)
+///         .max_generation(100)
+///         .pm(1.0)
+///         .random_agent(|rng| {
+///             let values = (0..3).map(|_| rng.gen_range(-5.0, 5.0)).collect::<Vec<f64>>();
+///             SelfAdaptive::per_gene(values, 1.0)
+///         })
+///         .fitness(|a: &SelfAdaptive<Vec<f64>>| a.iter().map(|x| x * x).sum::<f64>())
+///         .minimize()
+///         .crossover(SelfAdaptiveCrossover(Arithmetic::default()))
+///         .mutation(SelfAdaptiveMutation::default())
+///         .evolution_strategy(EvolutionStrategy::comma(10, 70))
+///         .build()
+///         .unwrap();
+///
+///     let best = test.run().best;
+///     println!("{:?} {:?}", best.genome, best.sigmas); // both should be close to 0
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct SelfAdaptive<T> {
+    /// The genes
+    pub genome: T,
+    /// The mutation strengths (standard deviations), one or one for every gene
+    pub sigmas: Vec<f64>,
+}
+
+impl<T: AsRef<[f64]>> SelfAdaptive<T> {
+    /// The individual has a single mutation strength
+    pub fn uniform(genome: T, sigma: f64) -> Self {
+        SelfAdaptive { genome, sigmas: vec![sigma] }
+    }
+
+    /// Every gene has its own mutation strength (all of them start from sigma)
+    pub fn per_gene(genome: T, sigma: f64) -> Self {
+        let n = genome.as_ref().len();
+        SelfAdaptive { genome, sigmas: vec![sigma; n] }
+    }
+
+    /// Mutation strength of the i-th gene
+    pub fn sigma(&self, i: usize) -> f64 {
+        if self.sigmas.len() == 1 {
+            return self.sigmas[0];
+        }
+        self.sigmas[i]
+    }
+}
+
+impl<T: AsRef<[f64]>> Deref for SelfAdaptive<T> {
+    type Target = [f64];
+
+    fn deref(&self) -> &[f64] {
+        self.genome.as_ref()
+    }
+}
+
+/// Log-normal self-adaptive mutation: first the strengths are multiplied by exp(tau' * N(0, 1) + tau * N_i(0, 1)),
+/// then every gene gets Gaussian noise with its new strength
+///
+/// The learning rates default to the usual values: tau' = 1 / sqrt(2n), tau = 1 / sqrt(2 sqrt(n))
+/// (with a single strength tau = 1 / sqrt(n) and there is no tau').
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SelfAdaptiveMutation {
+    /// Learning rate of the individual strengths (None: computed from the length of the genome)
+    pub tau: Option<f64>,
+    /// Learning rate shared by all strengths of an individual (None: computed from the length of the genome)
+    pub tau_prime: Option<f64>,
+    /// The strengths don't go under this value (keeps the search from freezing)
+    pub min_sigma: f64,
+}
+
+impl Default for SelfAdaptiveMutation {
+    fn default() -> Self {
+        SelfAdaptiveMutation { tau: None, tau_prime: None, min_sigma: 1e-12 }
+    }
+}
+
+impl<T> Mutation<SelfAdaptive<T>> for SelfAdaptiveMutation
+    where T: AsRef<[f64]> + AsMut<[f64]> + Clone {
+    fn mutate(&self, agent: &SelfAdaptive<T>, rng: &mut dyn RngCore) -> SelfAdaptive<T> {
+        let mut x = agent.clone();
+        let n = (x.genome.as_ref().len() as f64).max(1.0);
+
+        if x.sigmas.len() == 1 {
+            let tau = self.tau.unwrap_or_else(|| 1.0 / n.sqrt());
+            x.sigmas[0] = (x.sigmas[0] * (tau * standard_normal(rng)).exp()).max(self.min_sigma);
+        } else {
+            let tau = self.tau.unwrap_or_else(|| 1.0 / (2.0 * n.sqrt()).sqrt());
+            let tau_prime = self.tau_prime.unwrap_or_else(|| 1.0 / (2.0 * n).sqrt());
+            let shared = tau_prime * standard_normal(rng);
+            for sigma in x.sigmas.iter_mut() {
+                *sigma = (*sigma * (shared + tau * standard_normal(rng)).exp()).max(self.min_sigma);
+            }
+        }
+
+        for i in 0..x.genome.as_ref().len() {
+            let sigma = x.sigma(i);
+            x.genome.as_mut()[i] += sigma * standard_normal(rng);
+        }
+        x
+    }
+}
+
+/// Crossover of self-adaptive genomes: the genes are crossed with the given crossover, the strengths of the
+/// children are the averages of the parents' strengths (intermediate recombination)
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct SelfAdaptiveCrossover<C>(pub C);
+
+impl<T, C> Crossover<SelfAdaptive<T>> for SelfAdaptiveCrossover<C>
+    where C: Crossover<T>, T: AsRef<[f64]> {
+    fn cross(&self, a: &SelfAdaptive<T>, b: &SelfAdaptive<T>, rng: &mut dyn RngCore) -> Vec<SelfAdaptive<T>> {
+        let sigmas = a.sigmas.iter()
+            .zip(b.sigmas.iter())
+            .map(|(x, y)| (x + y) / 2.0)
+            .collect::<Vec<f64>>();
+
+        self.0.cross(&a.genome, &b.genome, rng)
+            .into_iter()
+            .map(|genome| SelfAdaptive { genome, sigmas: sigmas.clone() })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::operators::Arithmetic;
+    use crate::{EvolutionStrategy, Genetic};
+
+    #[test]
+    fn mutation() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let single = SelfAdaptive::uniform([0.0; 4], 1.0);
+        let per_gene = SelfAdaptive::per_gene(vec![0.0; 4], 1.0);
+        let mutation = SelfAdaptiveMutation::default();
+
+        let x = mutation.mutate(&single, &mut rng);
+        assert_eq!(x.sigmas.len(), 1);
+        assert!(x.sigmas[0] != 1.0 && x.sigmas[0] > 0.0);
+        let y = mutation.mutate(&per_gene, &mut rng);
+        assert_eq!(y.sigmas.len(), 4);
+        assert!(y.iter().all(|v| *v != 0.0));
+
+        let children = SelfAdaptiveCrossover(Arithmetic::default()).cross(&y, &per_gene, &mut rng);
+        assert_eq!(children[0].sigmas, y.sigmas.iter().map(|s| (s + 1.0) / 2.0).collect::<Vec<f64>>());
+    }
+
+    #[test]
+    fn sphere() {
+        let test = Genetic::builder()
+            .population(10)
+            .max_generation(200)
+            .pm(1.0)
+            .random_agent(|rng| SelfAdaptive::per_gene((0..5).map(|_| rng.gen_range(-5.0, 5.0)).collect::<Vec<f64>>(), 1.0))
+            .fitness(|a: &SelfAdaptive<Vec<f64>>| a.iter().map(|x| x * x).sum::<f64>())
+            .minimize()
+            .crossover(SelfAdaptiveCrossover(Arithmetic::default()))
+            .mutation(SelfAdaptiveMutation::default())
+            .evolution_strategy(EvolutionStrategy::comma(10, 70))
+            .build()
+            .unwrap();
+
+        let result = test.run_with_seed(1);
+        assert!(result.best_fitness < 1e-6);
+        // the step sizes shrink with the distance to the optimum
+        assert!(result.best.sigmas.iter().all(|s| *s < 0.01));
+    }
+}
@@ -0,0 +1,123 @@
+use rand::prelude::*;
+
+use crate::operators::{Crossover, Mutation};
+use crate::AgentFn;
+
+/// Cut-and-splice crossover for genomes whose length can change: both parents are cut at their own
+/// random point and the heads are joined with the other parent's tail (creates two children,
+/// their lengths add up to the lengths of the parents)
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct CutAndSplice;
+
+impl<G: Clone> Crossover<Vec<G>> for CutAndSplice {
+    fn cross(&self, a: &Vec<G>, b: &Vec<G>, rng: &mut dyn RngCore) -> Vec<Vec<G>> {
+        let i = rng.gen_range(0, a.len() + 1);
+        let j = rng.gen_range(0, b.len() + 1);
+
+        vec![
+            a[..i].iter().chain(b[j..].iter()).cloned().collect(),
+            b[..j].iter().chain(a[i..].iter()).cloned().collect(),
+        ]
+    }
+}
+
+/// A new gene is inserted at a random position (the genome grows by one)
+///
+/// # Examples:
+/// ```
+/// use rand::prelude::*;
+/// use bbte_optim_tzim1773_genetic::genome::{DeleteGene, InsertGene};
+/// use bbte_optim_tzim1773_genetic::operators::Mutation;
+///
+/// fn main() {
+///     let insert = InsertGene::new(|rng| rng.gen_range(0, 10)).max_len(20);
+///     let delete = DeleteGene { min_len: 1 };
+///     // the genome grows or shrinks with equal probability
+///     let mutate = move |a: &Vec<u8>, rng: &mut dyn RngCore| {
+///         if rng.gen::<bool>() { insert.mutate(a, rng) } else { delete.mutate(a, rng) }
+///     };
+///
+///     println!("{:?}", mutate(&vec![1, 2, 3], &mut thread_rng()));
+/// }
+/// ```
+pub struct InsertGene<'a, G> {
+    gene: AgentFn<'a, G>,
+    max_len: Option<usize>,
+}
+
+impl<'a, G> InsertGene<'a, G> {
+    /// Inserts genes created by the given function
+    pub fn new(gene: impl Fn(&mut dyn RngCore) -> G + Send + Sync + 'a) -> Self {
+        InsertGene { gene: Box::new(gene), max_len: None }
+    }
+
+    /// Genomes of this length are not extended
+    pub fn max_len(mut self, max_len: usize) -> Self {
+        self.max_len = Some(max_len);
+        self
+    }
+}
+
+impl<'a, G: Clone> Mutation<Vec<G>> for InsertGene<'a, G> {
+    fn mutate(&self, agent: &Vec<G>, rng: &mut dyn RngCore) -> Vec<G> {
+        let mut x = agent.clone();
+        if self.max_len.is_none_or(|max_len| x.len() < max_len) {
+            let i = rng.gen_range(0, x.len() + 1);
+            x.insert(i, (self.gene)(rng));
+        }
+        x
+    }
+}
+
+/// A random gene is removed (the genome shrinks by one)
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct DeleteGene {
+    /// Genomes of this length are not shortened
+    pub min_len: usize,
+}
+
+impl<G: Clone> Mutation<Vec<G>> for DeleteGene {
+    fn mutate(&self, agent: &Vec<G>, rng: &mut dyn RngCore) -> Vec<G> {
+        let mut x = agent.clone();
+        if !x.is_empty() && x.len() > self.min_len {
+            let i = rng.gen_range(0, x.len());
+            x.remove(i);
+        }
+        x
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cut_and_splice() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let a = vec![0; 5];
+        let b = vec![1; 8];
+
+        for _ in 0..20 {
+            let children = CutAndSplice.cross(&a, &b, &mut rng);
+            assert_eq!(children[0].len() + children[1].len(), 13);
+            let ones = children.iter().flatten().filter(|x| **x == 1).count();
+            assert_eq!(ones, 8);
+        }
+    }
+
+    #[test]
+    fn insert_delete() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let insert = InsertGene::new(|_| 7).max_len(4);
+        let delete = DeleteGene { min_len: 2 };
+
+        let x = insert.mutate(&vec![1, 2, 3], &mut rng);
+        assert_eq!(x.len(), 4);
+        assert!(x.contains(&7));
+        assert_eq!(insert.mutate(&x, &mut rng).len(), 4);
+
+        let y = delete.mutate(&vec![1, 2, 3], &mut rng);
+        assert_eq!(y.len(), 2);
+        assert_eq!(delete.mutate(&y, &mut rng).len(), 2);
+    }
+}
@@ -0,0 +1,312 @@
+use rand::prelude::*;
+use std::error::Error;
+use std::fmt;
+use std::sync::Arc;
+
+use crate::operators::Mutation;
+
+/// Error returned when a BNF grammar can't be parsed (contains the reason)
+#[derive(Debug, Clone, PartialEq)]
+pub struct GrammarError(pub String);
+
+impl fmt::Display for GrammarError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid grammar: {}", self.0)
+    }
+}
+
+impl Error for GrammarError {}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Symbol {
+    Terminal(String),
+    NonTerminal(usize),
+}
+
+/// Context-free grammar in BNF, used to map integer genomes (codons) to programs
+///
+/// Every rule is on its own line: `<name> ::= alternative | alternative ...`, the symbols of an alternative are
+/// separated by whitespace, `<...>` symbols are non-terminals, everything else is a terminal.
+/// The first rule is the start symbol.
+///
+/// # Examples:
+/// ```
+/// use bbte_optim_tzim1773_genetic::gp::Grammar;
+///
+/// fn main() {
+///     let grammar = Grammar::parse("
+///         <expr> ::= <expr> <op> <expr> | x | 1
+///         <op>   ::= + | *
+///     ").unwrap();
+///
+///     // 0: <expr> <op> <expr>, 1: x, 1: *, 2: 1
+///     assert_eq!(grammar.map(&[0, 1, 1, 2], 0), Some("x * 1".to_string()));
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Grammar {
+    names: Vec<String>,
+    rules: Vec<Vec<Vec<Symbol>>>,
+}
+
+/// Maximal number of non-terminal expansions of one mapping (stops grammars that never terminate)
+const MAX_EXPANSIONS: usize = 100_000;
+
+impl Grammar {
+    /// Parses a grammar in BNF
+    pub fn parse(bnf: &str) -> Result<Grammar, GrammarError> {
+        let lines = bnf.lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(|line| {
+                let mut parts = line.splitn(2, "::=");
+                let name = parts.next().unwrap_or("").trim();
+                let body = parts.next().ok_or_else(|| GrammarError(format!("missing ::= in \"{}\"", line)))?;
+                if !(name.starts_with('<') && name.ends_with('>')) {
+                    return Err(GrammarError(format!("the left side must be a non-terminal, got \"{}\"", name)));
+                }
+                Ok((name.to_string(), body))
+            })
+            .collect::<Result<Vec<(String, &str)>, GrammarError>>()?;
+        if lines.is_empty() {
+            return Err(GrammarError("there are no rules".to_string()));
+        }
+
+        let names = lines.iter().map(|(name, _)| name.clone()).collect::<Vec<String>>();
+        let mut rules = Vec::with_capacity(lines.len());
+        for (_, body) in lines.iter() {
+            let mut alternatives = Vec::new();
+            for alternative in body.split('|') {
+                let symbols = alternative.split_whitespace()
+                    .map(|token| {
+                        if token.starts_with('<') && token.ends_with('>') && token.len() > 2 {
+                            return names.iter()
+                                .position(|name| name == token)
+                                .map(Symbol::NonTerminal)
+                                .ok_or_else(|| GrammarError(format!("{} has no rule", token)));
+                        }
+                        Ok(Symbol::Terminal(token.to_string()))
+                    })
+                    .collect::<Result<Vec<Symbol>, GrammarError>>()?;
+                alternatives.push(symbols);
+            }
+            rules.push(alternatives);
+        }
+
+        Ok(Grammar { names, rules })
+    }
+
+    /// Maps the codons to the terminals of a program, None if the mapping doesn't finish
+    ///
+    /// Every expansion of a non-terminal with more than one alternative uses the next codon (codon % alternatives).
+    ///
+    /// # Arguments:
+    ///
+    /// * `codons` the genome
+    /// * `max_wraps` how many times the codons can be reused from the beginning when they run out
+    pub fn map_tokens(&self, codons: &[u32], max_wraps: usize) -> Option<Vec<String>> {
+        let mut tokens = Vec::new();
+        let mut stack = vec![Symbol::NonTerminal(0)];
+        let (mut next, mut wraps, mut expansions) = (0, 0, 0);
+
+        while let Some(symbol) = stack.pop() {
+            let rule = match symbol {
+                Symbol::Terminal(token) => {
+                    tokens.push(token);
+                    continue;
+                }
+                Symbol::NonTerminal(rule) => rule,
+            };
+            expansions += 1;
+            if expansions > MAX_EXPANSIONS {
+                return None;
+            }
+
+            let alternatives = &self.rules[rule];
+            let choice = if alternatives.len() == 1 {
+                0
+            } else {
+                if next == codons.len() {
+                    if wraps == max_wraps || codons.is_empty() {
+                        return None;
+                    }
+                    wraps += 1;
+                    next = 0;
+                }
+                next += 1;
+                codons[next - 1] as usize % alternatives.len()
+            };
+            // the leftmost symbol is expanded first
+            stack.extend(alternatives[choice].iter().rev().cloned());
+        }
+
+        Some(tokens)
+    }
+
+    /// Maps the codons to a program, the terminals are separated by spaces (see map_tokens())
+    pub fn map(&self, codons: &[u32], max_wraps: usize) -> Option<String> {
+        self.map_tokens(codons, max_wraps).map(|tokens| tokens.join(" "))
+    }
+
+    /// Names of the non-terminals (the first one is the start symbol)
+    pub fn non_terminals(&self) -> &[String] {
+        &self.names
+    }
+}
+
+/// Every codon is replaced with the given probability by a random one from [0, max_codon]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CodonMutation {
+    /// Probability of replacing a codon (usually 1 / genome length)
+    pub probability: f64,
+    /// The biggest codon value
+    pub max_codon: u32,
+}
+
+impl Mutation<Vec<u32>> for CodonMutation {
+    fn mutate(&self, agent: &Vec<u32>, rng: &mut dyn RngCore) -> Vec<u32> {
+        agent.iter()
+            .map(|c| if rng.gen_range(0.0, 1.0) < self.probability { rng.gen_range(0, self.max_codon + 1) } else { *c })
+            .collect()
+    }
+}
+
+/// Grammatical evolution: integer genomes (Vec<u32>) are mapped to programs through a grammar
+///
+/// It holds the settings of the representation with sensible defaults (100 codons from [0, 255], 2 wraps),
+/// the genomes can be evolved with Genetic using random_genome(), mutation() and any Vec crossover
+/// (e.g. OnePoint, or CutAndSplice for variable length).
+///
+/// # Examples:
+/// ```
+/// use rand::prelude::*;
+/// use bbte_optim_tzim1773_genetic::Genetic;
+/// use bbte_optim_tzim1773_genetic::gp::{Grammar, GrammaticalEvolution};
+/// use bbte_optim_tzim1773_genetic::operators::OnePoint;
+///
+/// fn main() {
+///     let grammar = Grammar::parse("<bits> ::= <bit> <bits> | <bit>\n<bit> ::= 0 | 1").unwrap();
+///     let ge = GrammaticalEvolution::new(grammar);
+///     let (agent_ge, fitness_ge) = (ge.clone(), ge.clone());
+///
+///     let test = Genetic::builder()
+///         .population(50)
+///         .max_generation(30)
+///         .random_agent(move |rng| agent_ge.random_genome(rng))
+///         // as many ones as possible, invalid genomes get 0
+///         .fitness(move |a: &Vec<u32>| fitness_ge.map(a).map_or(0, |p| p.matches('1').count()))
+///         .crossover(OnePoint)
+///         .mutation(ge.mutation())
+///         .build()
+///         .unwrap();
+///
+///     println!("{}", ge.map(&test.run().best).unwrap());
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct GrammaticalEvolution {
+    /// The grammar the genomes are mapped through
+    pub grammar: Arc<Grammar>,
+    /// Number of codons of the random genomes
+    pub genome_length: usize,
+    /// The biggest codon value
+    pub max_codon: u32,
+    /// How many times the codons can be reused when they run out
+    pub max_wraps: usize,
+}
+
+impl GrammaticalEvolution {
+    /// Grammatical evolution with the default settings (100 codons from [0, 255], 2 wraps)
+    pub fn new(grammar: Grammar) -> Self {
+        GrammaticalEvolution { grammar: Arc::new(grammar), genome_length: 100, max_codon: 255, max_wraps: 2 }
+    }
+
+    /// Returns a genome of random codons
+    pub fn random_genome(&self, rng: &mut dyn RngCore) -> Vec<u32> {
+        (0..self.genome_length).map(|_| rng.gen_range(0, self.max_codon + 1)).collect()
+    }
+
+    /// Maps a genome to a program, None if the mapping doesn't finish
+    pub fn map(&self, codons: &[u32]) -> Option<String> {
+        self.grammar.map(codons, self.max_wraps)
+    }
+
+    /// Codon mutation changing one codon on average
+    pub fn mutation(&self) -> CodonMutation {
+        CodonMutation { probability: 1.0 / self.genome_length.max(1) as f64, max_codon: self.max_codon }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::operators::OnePoint;
+    use crate::{Genetic, Selection};
+
+    fn prefix() -> Grammar {
+        Grammar::parse("
+            <e> ::= add <e> <e> | mul <e> <e> | <t>
+            <t> ::= x | 1
+        ").unwrap()
+    }
+
+    fn evaluate(tokens: &[String], x: f64) -> (f64, &[String]) {
+        match tokens[0].as_str() {
+            "add" | "mul" => {
+                let (a, rest) = evaluate(&tokens[1..], x);
+                let (b, rest) = evaluate(rest, x);
+                (if tokens[0] == "add" { a + b } else { a * b }, rest)
+            }
+            "x" => (x, &tokens[1..]),
+            _ => (1.0, &tokens[1..]),
+        }
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(prefix().non_terminals(), &["<e>".to_string(), "<t>".to_string()]);
+        assert!(Grammar::parse("<e> ::= <f>").is_err());
+        assert!(Grammar::parse("e ::= x").is_err());
+        assert!(Grammar::parse("<e> x").is_err());
+        assert!(Grammar::parse("").is_err());
+    }
+
+    #[test]
+    fn mapping() {
+        let grammar = prefix();
+        // add, <t> x, <t> 1
+        assert_eq!(grammar.map(&[0, 2, 0, 2, 1], 0), Some("add x 1".to_string()));
+        // after the codons run out they are reused
+        // add, <t>, (wrap) 1, <t>, (wrap) 1
+        assert_eq!(grammar.map(&[3, 2], 2), Some("add 1 1".to_string()));
+        assert_eq!(grammar.map(&[3, 2], 1), None);
+        // add forever
+        assert_eq!(grammar.map(&[0], 5), None);
+    }
+
+    #[test]
+    fn grammatical_evolution() {
+        let ge = GrammaticalEvolution { genome_length: 30, ..GrammaticalEvolution::new(prefix()) };
+        let (agent_ge, fitness_ge) = (ge.clone(), ge.clone());
+
+        let test = Genetic::builder()
+            .population(200)
+            .max_generation(40)
+            .random_agent(move |rng| agent_ge.random_genome(rng))
+            // the error on x^2 + x
+            .fitness(move |a: &Vec<u32>| match fitness_ge.grammar.map_tokens(a, fitness_ge.max_wraps) {
+                Some(tokens) => (0..5).map(|x| x as f64).map(|x| (evaluate(&tokens, x).0 - (x * x + x)).abs()).sum::<f64>(),
+                None => f64::INFINITY,
+            })
+            .minimize()
+            .crossover(OnePoint)
+            .mutation(ge.mutation())
+            .selection(Selection::tournament(3))
+            .elitism(1)
+            .build()
+            .unwrap();
+
+        let result = test.run_with_seed(1);
+        assert_eq!(result.best_fitness, 0.0);
+    }
+}
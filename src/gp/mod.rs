@@ -0,0 +1,51 @@
+//! Tree-based genetic programming: expression trees built from a user-given set of functions and terminals
+//! and grammatical evolution: integer genomes mapped to programs through a BNF grammar (see GrammaticalEvolution)
+//!
+//! For the most common use, fitting an expression to data, SymbolicRegression sets up and runs the whole algorithm.
+//!
+//! The trees are ordinary agents, they can be evolved with Genetic using the operators of this module.
+//!
+//! # Examples:
+//! ```
+//! use rand::prelude::*;
+//! use std::sync::Arc;
+//! use bbte_optim_tzim1773_genetic::{Genetic, Selection};
+//! use bbte_optim_tzim1773_genetic::gp::{Limits, PrimitiveSet, SubtreeCrossover, SubtreeMutation, Tree};
+//!
+//! fn main() {
+//!     let set = Arc::new(PrimitiveSet::new()
+//!         .function("add", 2, |a: &[f64]| a[0] + a[1])
+//!         .function("mul", 2, |a: &[f64]| a[0] * a[1])
+//!         .variable("x")
+//!         .constant(1.0));
+//!     let (agent_set, fitness_set) = (Arc::clone(&set), Arc::clone(&set));
+//!
+//!     let test = Genetic::builder()
+//!         .population(200)
+//!         .max_generation(30)
+//!         .random_agent(move |rng| agent_set.ramped_half_and_half(1, 4, rng))
+//!         // the error on 2x^2 + 1
+//!         .fitness(move |a: &Tree<f64>| (-5..=5)
+//!             .map(|x| x as f64)
+//!             .map(|x| (a.evaluate(&fitness_set, &[x]) - (2.0 * x * x + 1.0)).abs())
+//!             .sum::<f64>())
+//!         .minimize()
+//!         .crossover(SubtreeCrossover::default())
+//!         .mutation(SubtreeMutation { set: Arc::clone(&set), max_depth: 2, limits: Limits::default() })
+//!         .selection(Selection::tournament(3))
+//!         .build()
+//!         .unwrap();
+//!
+//!     println!("{}", test.run().best.format(&set));
+//! }
+//! ```
+
+mod grammar;
+mod operators;
+mod regression;
+mod tree;
+
+pub use grammar::{CodonMutation, Grammar, GrammarError, GrammaticalEvolution};
+pub use operators::{HoistMutation, Limits, PointMutation, SubtreeCrossover, SubtreeMutation};
+pub use regression::{ErrorMetric, Expression, RegressionResult, SymbolicFunction, SymbolicRegression};
+pub use tree::{Node, PrimitiveFn, PrimitiveSet, Tree};
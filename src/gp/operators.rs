@@ -0,0 +1,172 @@
+use rand::prelude::*;
+use std::sync::Arc;
+
+use super::{Node, PrimitiveSet, Tree};
+use crate::operators::{Crossover, Mutation};
+
+/// Size limits of the trees, offspring that break them are replaced by their parent
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Limits {
+    /// Maximal depth of a tree
+    pub max_depth: usize,
+    /// Maximal number of nodes of a tree
+    pub max_nodes: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits { max_depth: 17, max_nodes: 500 }
+    }
+}
+
+impl Limits {
+    /// Returns true if the tree is within the limits
+    pub fn allows<V: Clone>(&self, tree: &Tree<V>) -> bool {
+        tree.len() <= self.max_nodes && tree.depth() <= self.max_depth
+    }
+
+    fn or_parent<V: Clone>(&self, child: Tree<V>, parent: &Tree<V>) -> Tree<V> {
+        if self.allows(&child) {
+            return child;
+        }
+        parent.clone()
+    }
+}
+
+/// Random subtrees of the parents are swapped (creates two children)
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct SubtreeCrossover {
+    /// Size limits of the children
+    pub limits: Limits,
+}
+
+impl<V: Clone> Crossover<Tree<V>> for SubtreeCrossover {
+    fn cross(&self, a: &Tree<V>, b: &Tree<V>, rng: &mut dyn RngCore) -> Vec<Tree<V>> {
+        let i = rng.gen_range(0, a.len());
+        let j = rng.gen_range(0, b.len());
+        let x = a.replace(i, &b.nodes[j..b.subtree_end(j)]);
+        let y = b.replace(j, &a.nodes[i..a.subtree_end(i)]);
+
+        vec![self.limits.or_parent(x, a), self.limits.or_parent(y, b)]
+    }
+}
+
+/// Every node is replaced with the given probability by a random primitive of the same arity
+/// (the shape of the tree doesn't change)
+pub struct PointMutation<'a, V> {
+    /// The primitive set the trees are built from
+    pub set: Arc<PrimitiveSet<'a, V>>,
+    /// Probability of replacing a node (usually 1 / size of the tree)
+    pub probability: f64,
+}
+
+impl<'a, V: Clone> Mutation<Tree<V>> for PointMutation<'a, V> {
+    fn mutate(&self, agent: &Tree<V>, rng: &mut dyn RngCore) -> Tree<V> {
+        let nodes = agent.nodes.iter()
+            .map(|node| {
+                if rng.gen_range(0.0, 1.0) >= self.probability {
+                    return node.clone();
+                }
+                match node {
+                    Node::Function(_, arity) => self.set.random_function(Some(*arity), rng).unwrap_or_else(|| node.clone()),
+                    _ => self.set.random_terminal(rng),
+                }
+            })
+            .collect();
+
+        Tree { nodes }
+    }
+}
+
+/// A random subtree is replaced by a new random tree (grown up to max_depth)
+pub struct SubtreeMutation<'a, V> {
+    /// The primitive set the trees are built from
+    pub set: Arc<PrimitiveSet<'a, V>>,
+    /// Maximal depth of the new subtree
+    pub max_depth: usize,
+    /// Size limits of the mutated tree
+    pub limits: Limits,
+}
+
+impl<'a, V: Clone> Mutation<Tree<V>> for SubtreeMutation<'a, V> {
+    fn mutate(&self, agent: &Tree<V>, rng: &mut dyn RngCore) -> Tree<V> {
+        let i = rng.gen_range(0, agent.len());
+        let subtree = self.set.random_tree(self.max_depth, false, rng);
+
+        self.limits.or_parent(agent.replace(i, &subtree.nodes), agent)
+    }
+}
+
+/// The tree is replaced by one of its own random subtrees (shrinks the tree, fights bloat)
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct HoistMutation;
+
+impl<V: Clone> Mutation<Tree<V>> for HoistMutation {
+    fn mutate(&self, agent: &Tree<V>, rng: &mut dyn RngCore) -> Tree<V> {
+        let i = rng.gen_range(0, agent.len());
+        agent.subtree(i)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Genetic, Selection};
+
+    fn arithmetic() -> Arc<PrimitiveSet<'static, f64>> {
+        Arc::new(PrimitiveSet::new()
+            .function("add", 2, |a: &[f64]| a[0] + a[1])
+            .function("sub", 2, |a: &[f64]| a[0] - a[1])
+            .function("mul", 2, |a: &[f64]| a[0] * a[1])
+            .variable("x")
+            .constant(1.0))
+    }
+
+    #[test]
+    fn limits() {
+        let set = arithmetic();
+        let mut rng = StdRng::seed_from_u64(1);
+        let limits = Limits { max_depth: 4, max_nodes: 20 };
+        let crossover = SubtreeCrossover { limits };
+        let subtree = SubtreeMutation { set: Arc::clone(&set), max_depth: 3, limits };
+
+        for _ in 0..100 {
+            let a = set.ramped_half_and_half(1, 4, &mut rng);
+            let b = set.ramped_half_and_half(1, 4, &mut rng);
+            for child in crossover.cross(&a, &b, &mut rng).iter() {
+                assert!(limits.allows(child) || *child == a || *child == b);
+            }
+            let mutated = subtree.mutate(&a, &mut rng);
+            assert!(limits.allows(&mutated) || mutated == a);
+            assert!(HoistMutation.mutate(&a, &mut rng).len() <= a.len());
+            let point = PointMutation { set: Arc::clone(&set), probability: 0.5 }.mutate(&a, &mut rng);
+            assert_eq!(point.len(), a.len());
+        }
+    }
+
+    #[test]
+    fn symbolic_regression() {
+        let set = arithmetic();
+        let (agent_set, fitness_set) = (Arc::clone(&set), Arc::clone(&set));
+        let points = (-10..=10).map(|x| x as f64 / 5.0).collect::<Vec<f64>>();
+
+        let test = Genetic::builder()
+            .population(300)
+            .max_generation(40)
+            .pc(0.9)
+            .pm(0.2)
+            .random_agent(move |rng| agent_set.ramped_half_and_half(1, 4, rng))
+            // the error on x^2 + x
+            .fitness(move |a: &Tree<f64>| points.iter().map(|x| (a.evaluate(&fitness_set, &[*x]) - (x * x + x)).abs()).sum::<f64>())
+            .minimize()
+            .crossover(SubtreeCrossover { limits: Limits { max_depth: 8, max_nodes: 60 } })
+            .mutation(SubtreeMutation { set, max_depth: 2, limits: Limits { max_depth: 8, max_nodes: 60 } })
+            .selection(Selection::tournament(3))
+            .elitism(1)
+            .build()
+            .unwrap();
+
+        let result = test.run_with_seed(1);
+        assert!(result.best_fitness < 1e-9);
+    }
+}
@@ -0,0 +1,596 @@
+use rand::prelude::*;
+use std::fmt;
+use std::sync::Arc;
+
+use super::{Limits, Node, PrimitiveSet, SubtreeCrossover, SubtreeMutation, Tree};
+use crate::{ConfigError, Genetic, Selection};
+
+/// A function the expressions of a symbolic regression can be built from
+///
+/// The partial functions are protected, so every expression can be evaluated everywhere: the division by (nearly) 0
+/// gives 1, the logarithm is taken of the absolute value (and gives 0 at 0), the square root is taken of the
+/// absolute value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SymbolicFunction {
+    /// a + b
+    Add,
+    /// a - b
+    Sub,
+    /// a * b
+    Mul,
+    /// a / b (protected)
+    Div,
+    /// -a
+    Neg,
+    /// a^2
+    Square,
+    /// sqrt(|a|)
+    Sqrt,
+    /// sin(a)
+    Sin,
+    /// cos(a)
+    Cos,
+    /// exp(a)
+    Exp,
+    /// ln(|a|) (protected)
+    Log,
+}
+
+impl SymbolicFunction {
+    /// The four arithmetic operations
+    pub const ARITHMETIC: [SymbolicFunction; 4] = [SymbolicFunction::Add, SymbolicFunction::Sub, SymbolicFunction::Mul, SymbolicFunction::Div];
+
+    /// Returns the name of the function
+    pub fn name(&self) -> &'static str {
+        match self {
+            SymbolicFunction::Add => "add",
+            SymbolicFunction::Sub => "sub",
+            SymbolicFunction::Mul => "mul",
+            SymbolicFunction::Div => "div",
+            SymbolicFunction::Neg => "neg",
+            SymbolicFunction::Square => "square",
+            SymbolicFunction::Sqrt => "sqrt",
+            SymbolicFunction::Sin => "sin",
+            SymbolicFunction::Cos => "cos",
+            SymbolicFunction::Exp => "exp",
+            SymbolicFunction::Log => "log",
+        }
+    }
+
+    /// Returns the number of arguments of the function
+    pub fn arity(&self) -> usize {
+        match self {
+            SymbolicFunction::Add | SymbolicFunction::Sub | SymbolicFunction::Mul | SymbolicFunction::Div => 2,
+            _ => 1,
+        }
+    }
+
+    /// Returns the value of the function
+    pub fn apply(&self, arguments: &[f64]) -> f64 {
+        let a = arguments[0];
+        match self {
+            SymbolicFunction::Add => a + arguments[1],
+            SymbolicFunction::Sub => a - arguments[1],
+            SymbolicFunction::Mul => a * arguments[1],
+            SymbolicFunction::Div => if arguments[1].abs() < 1e-12 { 1.0 } else { a / arguments[1] },
+            SymbolicFunction::Neg => -a,
+            SymbolicFunction::Square => a * a,
+            SymbolicFunction::Sqrt => a.abs().sqrt(),
+            SymbolicFunction::Sin => a.sin(),
+            SymbolicFunction::Cos => a.cos(),
+            SymbolicFunction::Exp => a.exp(),
+            SymbolicFunction::Log => if a.abs() < 1e-12 { 0.0 } else { a.abs().ln() },
+        }
+    }
+
+    /// The binding strength of the function in the infix form (the terminals and the function calls bind the most)
+    fn precedence(&self) -> u8 {
+        match self {
+            SymbolicFunction::Add | SymbolicFunction::Sub => 1,
+            SymbolicFunction::Mul | SymbolicFunction::Div => 2,
+            SymbolicFunction::Neg => 3,
+            SymbolicFunction::Square => 4,
+            _ => 5,
+        }
+    }
+}
+
+/// The error measure of the predictions of an expression, minimised by the symbolic regression
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ErrorMetric {
+    /// Mean squared error
+    Mse,
+    /// Root mean squared error
+    Rmse,
+    /// Mean absolute error
+    Mae,
+}
+
+impl ErrorMetric {
+    /// Returns the error of the predictions, or infinity if a prediction is not a number
+    pub fn error(&self, predictions: &[f64], targets: &[f64]) -> f64 {
+        let n = targets.len() as f64;
+        let error = match self {
+            ErrorMetric::Mse => predictions.iter().zip(targets).map(|(p, y)| (p - y).powi(2)).sum::<f64>() / n,
+            ErrorMetric::Rmse => (predictions.iter().zip(targets).map(|(p, y)| (p - y).powi(2)).sum::<f64>() / n).sqrt(),
+            ErrorMetric::Mae => predictions.iter().zip(targets).map(|(p, y)| (p - y).abs()).sum::<f64>() / n,
+        };
+        if error.is_nan() { f64::INFINITY } else { error }
+    }
+}
+
+/// An expression tree of a symbolic regression, together with the names of its functions and variables, so it can
+/// be evaluated, simplified and printed in infix form (e.g. `x^2 + sin(x) * 0.5`)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expression {
+    tree: Tree<f64>,
+    functions: Vec<SymbolicFunction>,
+    variables: Vec<String>,
+}
+
+impl Expression {
+    /// Returns the expression tree (the function nodes are indices of functions())
+    pub fn tree(&self) -> &Tree<f64> {
+        &self.tree
+    }
+
+    /// Returns the functions the expression was built from
+    pub fn functions(&self) -> &[SymbolicFunction] {
+        &self.functions
+    }
+
+    /// Returns the number of nodes of the expression
+    pub fn len(&self) -> usize {
+        self.tree.len()
+    }
+
+    /// Returns true if the expression has no nodes
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
+
+    /// Computes the value of the expression at the given values of the variables
+    ///
+    /// # Panics:
+    ///  If the expression is empty or a variable is missing
+    pub fn evaluate(&self, inputs: &[f64]) -> f64 {
+        let mut stack: Vec<f64> = Vec::with_capacity(self.len());
+        for node in self.tree.nodes.iter().rev() {
+            let value = match node {
+                Node::Function(f, arity) => {
+                    // the first argument is on the top of the stack
+                    let arguments = (0..*arity).map(|_| stack.pop().unwrap()).collect::<Vec<f64>>();
+                    self.functions[*f].apply(&arguments)
+                }
+                Node::Variable(v) => inputs[*v],
+                Node::Constant(c) => *c,
+            };
+            stack.push(value);
+        }
+        stack.pop().expect("the expression is empty")
+    }
+
+    /// Returns the simplified expression: the subexpressions without variables are computed, and the neutral and
+    /// absorbing elements (x + 0, x * 1, x * 0, x - x, x / x, -(-x), ...) are removed
+    pub fn simplify(&self) -> Expression {
+        let nodes = if self.is_empty() { Vec::new() } else { self.simplify_node(0).0 };
+        Expression { tree: Tree { nodes }, functions: self.functions.clone(), variables: self.variables.clone() }
+    }
+
+    /// Returns the simplified nodes of the subtree starting at i and the index after the subtree
+    fn simplify_node(&self, i: usize) -> (Vec<Node<f64>>, usize) {
+        let (f, arity) = match &self.tree.nodes[i] {
+            Node::Function(f, arity) => (*f, *arity),
+            node => return (vec![node.clone()], i + 1),
+        };
+        let mut children = Vec::with_capacity(arity);
+        let mut next = i + 1;
+        for _ in 0..arity {
+            let (child, end) = self.simplify_node(next);
+            children.push(child);
+            next = end;
+        }
+
+        let constant = |child: &[Node<f64>], value: f64| child == [Node::Constant(value)];
+        let function = self.functions[f];
+        let values = children.iter()
+            .map(|child| match child.as_slice() {
+                [Node::Constant(c)] => Some(*c),
+                _ => None,
+            })
+            .collect::<Option<Vec<f64>>>();
+        let simplified = match (function, values) {
+            (_, Some(values)) if function.apply(&values).is_finite() => Some(vec![Node::Constant(function.apply(&values))]),
+            (SymbolicFunction::Add, _) if constant(&children[0], 0.0) => Some(children[1].clone()),
+            (SymbolicFunction::Add | SymbolicFunction::Sub, _) if constant(&children[1], 0.0) => Some(children[0].clone()),
+            (SymbolicFunction::Sub, _) if children[0] == children[1] => Some(vec![Node::Constant(0.0)]),
+            (SymbolicFunction::Mul, _) if constant(&children[0], 0.0) || constant(&children[1], 0.0) => Some(vec![Node::Constant(0.0)]),
+            (SymbolicFunction::Mul, _) if constant(&children[0], 1.0) => Some(children[1].clone()),
+            (SymbolicFunction::Mul | SymbolicFunction::Div, _) if constant(&children[1], 1.0) => Some(children[0].clone()),
+            // the protected division gives 1 at 0 too
+            (SymbolicFunction::Div, _) if children[0] == children[1] || constant(&children[1], 0.0) => Some(vec![Node::Constant(1.0)]),
+            (SymbolicFunction::Neg, _) => match children[0].first() {
+                Some(Node::Function(g, _)) if self.functions[*g] == SymbolicFunction::Neg => Some(children[0][1..].to_vec()),
+                _ => None,
+            },
+            _ => None,
+        };
+
+        let nodes = simplified.unwrap_or_else(|| {
+            let mut nodes = vec![Node::Function(f, arity)];
+            for child in children {
+                nodes.extend(child);
+            }
+            nodes
+        });
+        (nodes, next)
+    }
+
+    /// Writes the subtree starting at i in infix form, returns the index after the subtree and its precedence
+    fn format_node(&self, i: usize, text: &mut String) -> (usize, u8) {
+        match &self.tree.nodes[i] {
+            Node::Function(f, arity) => {
+                let function = self.functions[*f];
+                let precedence = function.precedence();
+                let mut arguments = Vec::with_capacity(*arity);
+                let mut next = i + 1;
+                for _ in 0..*arity {
+                    let mut argument = String::new();
+                    let (end, inner) = self.format_node(next, &mut argument);
+                    arguments.push((argument, inner));
+                    next = end;
+                }
+                let wrapped = |(argument, inner): &(String, u8), needed: u8| {
+                    if *inner < needed { format!("({})", argument) } else { argument.clone() }
+                };
+
+                match function {
+                    SymbolicFunction::Add | SymbolicFunction::Sub | SymbolicFunction::Mul | SymbolicFunction::Div => {
+                        let symbol = match function {
+                            SymbolicFunction::Add => "+",
+                            SymbolicFunction::Sub => "-",
+                            SymbolicFunction::Mul => "*",
+                            _ => "/",
+                        };
+                        // a - (b - c) and a / (b * c) need the parentheses on the right
+                        let right = if function == SymbolicFunction::Sub || function == SymbolicFunction::Div { precedence + 1 } else { precedence };
+                        text.push_str(&format!("{} {} {}", wrapped(&arguments[0], precedence), symbol, wrapped(&arguments[1], right)));
+                    }
+                    SymbolicFunction::Neg => text.push_str(&format!("-{}", wrapped(&arguments[0], precedence + 1))),
+                    SymbolicFunction::Square => text.push_str(&format!("{}^2", wrapped(&arguments[0], precedence + 1))),
+                    _ => text.push_str(&format!("{}({})", function.name(), arguments[0].0)),
+                }
+                (next, precedence)
+            }
+            Node::Variable(v) => {
+                text.push_str(&self.variables[*v]);
+                (i + 1, 5)
+            }
+            // a negative constant binds like a negation
+            Node::Constant(c) => {
+                text.push_str(&c.to_string());
+                (i + 1, if *c < 0.0 { 3 } else { 5 })
+            }
+        }
+    }
+}
+
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut text = String::new();
+        if !self.is_empty() {
+            self.format_node(0, &mut text);
+        }
+        write!(f, "{}", text)
+    }
+}
+
+/// The outcome of a symbolic regression
+#[derive(Debug, Clone, PartialEq)]
+pub struct RegressionResult {
+    /// The best expression found (simplified)
+    pub expression: Expression,
+    /// The error of the expression on the data
+    pub error: f64,
+    /// The number of times the fitness function was called
+    pub evaluations: usize,
+}
+
+/// Symbolic regression: finds an expression of the variables that fits the given data, by genetic programming with
+/// the trees of the gp module
+///
+/// The expressions are built from the given functions, the variables and random constants. The fitness of an
+/// expression is its error on the data plus a small penalty for every node (so the smaller of two equally good
+/// expressions wins, and the trees don't grow without a reason). The run uses ramped half-and-half initialisation (up
+/// to init_depth), subtree crossover and subtree mutation with size limits, tournament selection and keeps the best
+/// expression.
+///
+/// # Examples:
+/// ```
+/// use bbte_optim_tzim1773_genetic::gp::{ErrorMetric, SymbolicFunction, SymbolicRegression};
+///
+/// fn main() {
+///     let data = (-10..=10).map(|i| i as f64 / 5.0).map(|x| (x, x * x + x)).collect::<Vec<(f64, f64)>>();
+///     let regression = SymbolicRegression::from_pairs(&data)
+///         .functions(SymbolicFunction::ARITHMETIC.to_vec())
+///         .metric(ErrorMetric::Mae)
+///         .generations(30);
+///
+///     let result = regression.run_with_seed(1);
+///     println!("{} (error: {})", result.expression, result.error);
+///     println!("{}", result.expression.evaluate(&[3.0]));
+/// }
+/// ```
+pub struct SymbolicRegression {
+    /// The functions the expressions are built from
+    /// Suggested value: the arithmetic operations, with sin, cos, exp, log if the data needs them
+    pub functions: Vec<SymbolicFunction>,
+    /// The names of the variables (in the order of the inputs)
+    pub variables: Vec<String>,
+    /// The interval of the random constants
+    /// Suggested value: (-1.0, 1.0)
+    pub constants: (f64, f64),
+    /// The error measure that is minimised
+    /// Suggested value: Mse
+    pub metric: ErrorMetric,
+    /// The penalty added to the error for every node of the expression
+    /// Suggested value: 0.001 (0 turns it off)
+    pub parsimony: f64,
+    /// The number of expressions in a generation
+    /// Suggested value: 200
+    pub population: usize,
+    /// The number of generations
+    /// Suggested value: 50
+    pub generations: usize,
+    /// The size limits of the expressions
+    /// Suggested value: depth 8, 100 nodes
+    pub limits: Limits,
+    /// The largest depth of the random expressions of the first generation (they are not deeper than
+    /// limits.max_depth either), the later generations grow by the crossover and the mutation
+    /// Suggested value: 6
+    pub init_depth: usize,
+    inputs: Arc<Vec<Vec<f64>>>,
+    targets: Arc<Vec<f64>>,
+}
+
+impl SymbolicRegression {
+    /// Creates a symbolic regression of the data with the suggested settings, the variables are named x0, x1, ...
+    ///
+    /// # Arguments:
+    ///
+    /// * `inputs` the values of the variables at every data point
+    /// * `targets` the values the expression should give at the data points
+    pub fn new(inputs: Vec<Vec<f64>>, targets: Vec<f64>) -> Self {
+        let variables = (0..inputs.first().map_or(0, Vec::len)).map(|i| format!("x{}", i)).collect();
+        SymbolicRegression {
+            functions: SymbolicFunction::ARITHMETIC.to_vec(),
+            variables,
+            constants: (-1.0, 1.0),
+            metric: ErrorMetric::Mse,
+            parsimony: 0.001,
+            population: 200,
+            generations: 50,
+            limits: Limits { max_depth: 8, max_nodes: 100 },
+            init_depth: 6,
+            inputs: Arc::new(inputs),
+            targets: Arc::new(targets),
+        }
+    }
+
+    /// Creates a symbolic regression of (x, y) points with one variable named x
+    pub fn from_pairs(data: &[(f64, f64)]) -> Self {
+        SymbolicRegression::new(data.iter().map(|(x, _)| vec![*x]).collect(), data.iter().map(|(_, y)| *y).collect())
+            .variables(&["x"])
+    }
+
+    /// Sets the functions the expressions are built from
+    pub fn functions(mut self, functions: Vec<SymbolicFunction>) -> Self {
+        self.functions = functions;
+        self
+    }
+
+    /// Sets the names of the variables
+    pub fn variables(mut self, names: &[&str]) -> Self {
+        self.variables = names.iter().map(|name| name.to_string()).collect();
+        self
+    }
+
+    /// Sets the interval of the random constants
+    pub fn constants(mut self, min: f64, max: f64) -> Self {
+        self.constants = (min, max);
+        self
+    }
+
+    /// Sets the error measure that is minimised
+    pub fn metric(mut self, metric: ErrorMetric) -> Self {
+        self.metric = metric;
+        self
+    }
+
+    /// Sets the penalty added to the error for every node of the expression
+    pub fn parsimony(mut self, parsimony: f64) -> Self {
+        self.parsimony = parsimony;
+        self
+    }
+
+    /// Sets the number of expressions in a generation
+    pub fn population(mut self, population: usize) -> Self {
+        self.population = population;
+        self
+    }
+
+    /// Sets the number of generations
+    pub fn generations(mut self, generations: usize) -> Self {
+        self.generations = generations;
+        self
+    }
+
+    /// Sets the size limits of the expressions
+    pub fn limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Sets the largest depth of the random expressions of the first generation
+    pub fn init_depth(mut self, init_depth: usize) -> Self {
+        self.init_depth = init_depth;
+        self
+    }
+
+    /// Checks whether the data and the settings are usable
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.targets.is_empty() || self.inputs.len() != self.targets.len() {
+            return Err(ConfigError::InvalidRegression(format!("every one of the data points needs a target, got {} inputs and {} targets", self.inputs.len(), self.targets.len())));
+        }
+        if self.variables.is_empty() || self.inputs.iter().any(|x| x.len() != self.variables.len()) {
+            return Err(ConfigError::InvalidRegression(format!("every data point needs a value for the {} variables", self.variables.len())));
+        }
+        if self.functions.is_empty() {
+            return Err(ConfigError::InvalidRegression("at least one function is needed".to_string()));
+        }
+        if self.constants.0.is_nan() || self.constants.1.is_nan() || self.constants.0 > self.constants.1 {
+            return Err(ConfigError::InvalidRegression(format!("the interval of the constants is empty: {:?}", self.constants)));
+        }
+        if self.parsimony.is_nan() || self.parsimony < 0.0 {
+            return Err(ConfigError::InvalidRegression(format!("the parsimony can't be negative, got {}", self.parsimony)));
+        }
+        if self.limits.max_depth == 0 || self.limits.max_nodes == 0 {
+            return Err(ConfigError::InvalidRegression("the limits must allow at least one node".to_string()));
+        }
+        if self.init_depth == 0 {
+            return Err(ConfigError::InvalidRegression("the expressions of the first generation need at least one node".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Returns the primitive set of the functions, the variables and the random constants (the function nodes of
+    /// the trees are the indices of the functions)
+    pub fn primitive_set(&self) -> PrimitiveSet<'static, f64> {
+        let mut set = PrimitiveSet::new();
+        for function in &self.functions {
+            let function = *function;
+            set = set.function(function.name(), function.arity(), move |arguments: &[f64]| function.apply(arguments));
+        }
+        for name in &self.variables {
+            set = set.variable(name);
+        }
+        let (min, max) = self.constants;
+        set.ephemeral(move |rng| if min < max { rng.gen_range(min, max) } else { min })
+    }
+
+    /// Returns the expression of a tree built from the primitive set of this regression
+    pub fn expression(&self, tree: Tree<f64>) -> Expression {
+        Expression { tree, functions: self.functions.clone(), variables: self.variables.clone() }
+    }
+
+    /// Returns the error of the expression on the data
+    pub fn error(&self, expression: &Expression) -> f64 {
+        let predictions = self.inputs.iter().map(|x| expression.evaluate(x)).collect::<Vec<f64>>();
+        self.metric.error(&predictions, &self.targets)
+    }
+
+    /// Evolves the expression
+    ///
+    /// # Panics:
+    ///  If the regression is not valid (see validate())
+    pub fn run(&self) -> RegressionResult {
+        self.run_with_rng(&mut thread_rng())
+    }
+
+    /// Same as run(), but uses a random number generator seeded with the given seed, so the run can be reproduced
+    pub fn run_with_seed(&self, seed: u64) -> RegressionResult {
+        self.run_with_rng(&mut StdRng::seed_from_u64(seed))
+    }
+
+    /// Same as run(), but uses the given random number generator
+    pub fn run_with_rng(&self, mut rng: &mut dyn RngCore) -> RegressionResult {
+        if let Err(error) = self.validate() {
+            panic!("{}", error);
+        }
+        let set = Arc::new(self.primitive_set());
+        let (agent_set, fitness_set) = (Arc::clone(&set), Arc::clone(&set));
+        let max_depth = self.init_depth.min(self.limits.max_depth);
+        let (metric, parsimony) = (self.metric, self.parsimony);
+        let (inputs, targets) = (Arc::clone(&self.inputs), Arc::clone(&self.targets));
+
+        let genetic = Genetic::builder()
+            .population(self.population)
+            .max_generation(self.generations)
+            .elitism(1)
+            .random_agent(move |rng| agent_set.ramped_half_and_half(1, max_depth, rng))
+            .fitness(move |tree: &Tree<f64>| {
+                let predictions = inputs.iter().map(|x| tree.evaluate(&fitness_set, x)).collect::<Vec<f64>>();
+                metric.error(&predictions, &targets) + parsimony * tree.len() as f64
+            })
+            .minimize()
+            .crossover(SubtreeCrossover { limits: self.limits })
+            .mutation(SubtreeMutation { set, max_depth: 2, limits: self.limits })
+            .selection(Selection::tournament(5))
+            .build()
+            .expect("the settings of the symbolic regression are valid");
+        let result = genetic.run_with_rng(&mut rng);
+
+        let expression = self.expression(result.best).simplify();
+        RegressionResult { error: self.error(&expression), expression, evaluations: result.evaluations }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expression(nodes: Vec<Node<f64>>) -> Expression {
+        SymbolicRegression::from_pairs(&[(0.0, 0.0)])
+            .functions(vec![SymbolicFunction::Add, SymbolicFunction::Sub, SymbolicFunction::Mul, SymbolicFunction::Div, SymbolicFunction::Neg, SymbolicFunction::Square, SymbolicFunction::Sin])
+            .expression(Tree { nodes })
+    }
+
+    #[test]
+    fn display() {
+        use Node::{Constant as C, Function as F, Variable as X};
+
+        // (x + 1) * (x - (x - 2))
+        let e = expression(vec![F(2, 2), F(0, 2), X(0), C(1.0), F(1, 2), X(0), F(1, 2), X(0), C(2.0)]);
+        assert_eq!(e.to_string(), "(x + 1) * (x - (x - 2))");
+        assert_eq!(e.evaluate(&[3.0]), 8.0);
+        // sin(x)^2 + -(-x) / -2
+        let e = expression(vec![F(0, 2), F(5, 1), F(6, 1), X(0), F(3, 2), F(4, 1), F(4, 1), X(0), C(-2.0)]);
+        assert_eq!(e.to_string(), "sin(x)^2 + -(-x) / -2");
+        assert_eq!(e.simplify().to_string(), "sin(x)^2 + x / -2");
+    }
+
+    #[test]
+    fn simplify() {
+        use Node::{Constant as C, Function as F, Variable as X};
+
+        let simplified = |nodes| expression(nodes).simplify().to_string();
+        assert_eq!(simplified(vec![F(0, 2), X(0), F(2, 2), C(2.0), C(3.0)]), "x + 6");
+        assert_eq!(simplified(vec![F(0, 2), C(0.0), F(2, 2), X(0), C(1.0)]), "x");
+        assert_eq!(simplified(vec![F(2, 2), F(1, 2), X(0), X(0), F(5, 1), X(0)]), "0");
+        assert_eq!(simplified(vec![F(3, 2), F(6, 1), X(0), F(6, 1), X(0)]), "1");
+        // the protected division by 0 is 1, whatever the dividend is
+        assert_eq!(simplified(vec![F(3, 2), X(0), F(1, 2), C(2.0), C(2.0)]), "1");
+        assert_eq!(expression(vec![F(3, 2), X(0), C(0.0)]).evaluate(&[5.0]), 1.0);
+        assert_eq!(simplified(vec![X(0)]), "x");
+    }
+
+    #[test]
+    fn regression() {
+        assert!(SymbolicRegression::new(vec![vec![1.0]], vec![1.0, 2.0]).validate().is_err());
+        assert!(SymbolicRegression::new(vec![vec![1.0], vec![1.0, 2.0]], vec![1.0, 2.0]).validate().is_err());
+        assert!(SymbolicRegression::from_pairs(&[(1.0, 1.0)]).functions(Vec::new()).validate().is_err());
+        assert!(SymbolicRegression::from_pairs(&[(1.0, 1.0)]).constants(1.0, -1.0).validate().is_err());
+        assert!(SymbolicRegression::from_pairs(&[(1.0, 1.0)]).init_depth(0).validate().is_err());
+        assert_eq!(SymbolicRegression::new(vec![vec![1.0, 2.0]], vec![3.0]).variables, vec!["x0", "x1"]);
+
+        let data = (-10..=10).map(|i| i as f64 / 5.0).map(|x| (x, x * x + x)).collect::<Vec<(f64, f64)>>();
+        let regression = SymbolicRegression::from_pairs(&data).functions(vec![SymbolicFunction::Add, SymbolicFunction::Mul]).generations(30);
+        let result = regression.run_with_seed(1);
+        assert!(result.error < 1e-9, "{} {}", result.expression, result.error);
+        assert_eq!(result.error, regression.error(&result.expression));
+        assert!((result.expression.evaluate(&[3.0]) - 12.0).abs() < 1e-6);
+        assert!(result.evaluations >= 200 * 30);
+    }
+}
@@ -0,0 +1,334 @@
+use rand::prelude::*;
+use std::fmt::Write;
+
+use crate::AgentFn;
+
+/// Function that computes the value of a GP function node from the values of its children
+pub type PrimitiveFn<'a, V> = Box<dyn Fn(&[V]) -> V + Send + Sync + 'a>;
+
+/// One node of an expression tree
+#[derive(Debug, Clone, PartialEq)]
+pub enum Node<V> {
+    /// A function of the primitive set: (index of the function, number of children)
+    Function(usize, usize),
+    /// An input variable: index of the input
+    Variable(usize),
+    /// A constant value
+    Constant(V),
+}
+
+impl<V> Node<V> {
+    /// Number of children of the node
+    pub fn arity(&self) -> usize {
+        match self {
+            Node::Function(_, arity) => *arity,
+            _ => 0,
+        }
+    }
+}
+
+/// The functions and terminals (variables, constants) expression trees are built from
+///
+/// # Examples:
+/// ```
+/// use rand::prelude::*;
+/// use bbte_optim_tzim1773_genetic::gp::PrimitiveSet;
+///
+/// fn main() {
+///     let set = PrimitiveSet::new()
+///         .function("add", 2, |a: &[f64]| a[0] + a[1])
+///         .function("mul", 2, |a: &[f64]| a[0] * a[1])
+///         .variable("x")
+///         .constant(1.0)
+///         .ephemeral(|rng| rng.gen_range(-1.0, 1.0));
+///
+///     let tree = set.ramped_half_and_half(1, 3, &mut thread_rng());
+///     println!("{} = {}", tree.format(&set), tree.evaluate(&set, &[2.0]));
+/// }
+/// ```
+pub struct PrimitiveSet<'a, V> {
+    functions: Vec<(String, usize, PrimitiveFn<'a, V>)>,
+    variables: Vec<String>,
+    constants: Vec<V>,
+    ephemerals: Vec<AgentFn<'a, V>>,
+}
+
+impl<'a, V> Default for PrimitiveSet<'a, V> {
+    fn default() -> Self {
+        PrimitiveSet {
+            functions: Vec::new(),
+            variables: Vec::new(),
+            constants: Vec::new(),
+            ephemerals: Vec::new(),
+        }
+    }
+}
+
+impl<'a, V: Clone> PrimitiveSet<'a, V> {
+    /// Creates an empty primitive set
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a function with the given number of arguments (it gets the values of the children in order)
+    pub fn function(mut self, name: &str, arity: usize, f: impl Fn(&[V]) -> V + Send + Sync + 'a) -> Self {
+        self.functions.push((name.to_string(), arity, Box::new(f)));
+        self
+    }
+
+    /// Adds an input variable, the variables get the inputs of evaluate() in the order they were added
+    pub fn variable(mut self, name: &str) -> Self {
+        self.variables.push(name.to_string());
+        self
+    }
+
+    /// Adds a constant terminal
+    pub fn constant(mut self, value: V) -> Self {
+        self.constants.push(value);
+        self
+    }
+
+    /// Adds an ephemeral random constant: every time it is put into a tree, a new value is generated
+    pub fn ephemeral(mut self, f: impl Fn(&mut dyn RngCore) -> V + Send + Sync + 'a) -> Self {
+        self.ephemerals.push(Box::new(f));
+        self
+    }
+
+    /// Number of functions
+    pub fn function_count(&self) -> usize {
+        self.functions.len()
+    }
+
+    fn terminal_count(&self) -> usize {
+        self.variables.len() + self.constants.len() + self.ephemerals.len()
+    }
+
+    /// Returns a random terminal node
+    ///
+    /// # Panics:
+    ///  If there are no terminals
+    pub fn random_terminal(&self, rng: &mut dyn RngCore) -> Node<V> {
+        assert!(self.terminal_count() > 0, "the primitive set has no terminals");
+        let mut i = rng.gen_range(0, self.terminal_count());
+        if i < self.variables.len() {
+            return Node::Variable(i);
+        }
+        i -= self.variables.len();
+        if i < self.constants.len() {
+            return Node::Constant(self.constants[i].clone());
+        }
+        Node::Constant((self.ephemerals[i - self.constants.len()])(rng))
+    }
+
+    /// Returns a random function node (with the given arity, if it's given), None if there is no such function
+    pub fn random_function(&self, arity: Option<usize>, rng: &mut dyn RngCore) -> Option<Node<V>> {
+        let candidates = self.functions.iter()
+            .enumerate()
+            .filter(|(_, (_, a, _))| arity.is_none_or(|arity| arity == *a))
+            .map(|(i, (_, a, _))| Node::Function(i, *a))
+            .collect::<Vec<Node<V>>>();
+        if candidates.is_empty() {
+            return None;
+        }
+        let i = rng.gen_range(0, candidates.len());
+        Some(candidates[i].clone())
+    }
+
+    /// Creates a random tree of at most the given depth
+    ///
+    /// # Arguments:
+    ///
+    /// * `depth` maximal depth (a single terminal has depth 0)
+    /// * `full` if it's true every branch reaches the maximal depth, otherwise the branches can stop earlier (grow)
+    /// * `rng` random number generator
+    pub fn random_tree(&self, depth: usize, full: bool, rng: &mut dyn RngCore) -> Tree<V> {
+        let mut nodes = Vec::new();
+        self.grow(&mut nodes, depth, full, rng);
+        Tree { nodes }
+    }
+
+    fn grow(&self, nodes: &mut Vec<Node<V>>, depth: usize, full: bool, rng: &mut dyn RngCore) {
+        let use_function = depth > 0 && !self.functions.is_empty() && (full || {
+            let all = self.functions.len() + self.terminal_count();
+            rng.gen_range(0, all) < self.functions.len()
+        });
+        if !use_function {
+            nodes.push(self.random_terminal(rng));
+            return;
+        }
+
+        let node = self.random_function(None, rng).unwrap();
+        let arity = node.arity();
+        nodes.push(node);
+        for _ in 0..arity {
+            self.grow(nodes, depth - 1, full, rng);
+        }
+    }
+
+    /// Creates a random tree with the ramped half-and-half method: the depth is chosen uniformly
+    /// from [min_depth, max_depth] and half of the trees are full, the other half is grown
+    pub fn ramped_half_and_half(&self, min_depth: usize, max_depth: usize, rng: &mut dyn RngCore) -> Tree<V> {
+        let depth = rng.gen_range(min_depth, max_depth.max(min_depth) + 1);
+        let full = rng.gen::<bool>();
+        self.random_tree(depth, full, rng)
+    }
+}
+
+/// Expression tree stored in prefix order (every function is followed by its children)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tree<V> {
+    /// The nodes in prefix order
+    pub nodes: Vec<Node<V>>,
+}
+
+impl<V: Clone> Tree<V> {
+    /// Number of nodes
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns true if the tree has no nodes
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Returns the index after the last node of the subtree starting at i
+    pub fn subtree_end(&self, i: usize) -> usize {
+        let mut missing = 1;
+        let mut j = i;
+        while missing > 0 {
+            missing = missing + self.nodes[j].arity() - 1;
+            j += 1;
+        }
+        j
+    }
+
+    /// Depth of the tree (a single terminal has depth 0)
+    pub fn depth(&self) -> usize {
+        let mut stack: Vec<usize> = Vec::new();
+        for node in self.nodes.iter().rev() {
+            let arity = node.arity();
+            let deepest = (0..arity).map(|_| stack.pop().unwrap_or(0)).max();
+            stack.push(deepest.map_or(0, |d| d + 1));
+        }
+        stack.pop().unwrap_or(0)
+    }
+
+    /// Returns the tree where the subtree starting at i is replaced by the given nodes
+    pub fn replace(&self, i: usize, subtree: &[Node<V>]) -> Tree<V> {
+        let end = self.subtree_end(i);
+        let mut nodes = Vec::with_capacity(self.len() - (end - i) + subtree.len());
+        nodes.extend_from_slice(&self.nodes[..i]);
+        nodes.extend_from_slice(subtree);
+        nodes.extend_from_slice(&self.nodes[end..]);
+        Tree { nodes }
+    }
+
+    /// Returns the subtree starting at i
+    pub fn subtree(&self, i: usize) -> Tree<V> {
+        Tree { nodes: self.nodes[i..self.subtree_end(i)].to_vec() }
+    }
+
+    /// Computes the value of the tree
+    ///
+    /// # Arguments:
+    ///
+    /// * `set` the primitive set the tree was built from
+    /// * `inputs` the values of the variables (in the order they were added to the set)
+    ///
+    /// # Panics:
+    ///  If the tree is empty or an input is missing
+    pub fn evaluate(&self, set: &PrimitiveSet<V>, inputs: &[V]) -> V {
+        let mut stack: Vec<V> = Vec::with_capacity(self.len());
+        for node in self.nodes.iter().rev() {
+            let value = match node {
+                Node::Function(f, arity) => {
+                    // the first child is on the top of the stack
+                    let arguments = (0..*arity).map(|_| stack.pop().unwrap()).collect::<Vec<V>>();
+                    (set.functions[*f].2)(&arguments)
+                }
+                Node::Variable(v) => inputs[*v].clone(),
+                Node::Constant(c) => c.clone(),
+            };
+            stack.push(value);
+        }
+        stack.pop().expect("the tree is empty")
+    }
+
+    /// Returns the expression as text, e.g. add(x, mul(x, 2))
+    pub fn format(&self, set: &PrimitiveSet<V>) -> String
+        where V: std::fmt::Display {
+        let mut text = String::new();
+        if !self.is_empty() {
+            self.format_node(set, 0, &mut text);
+        }
+        text
+    }
+
+    fn format_node(&self, set: &PrimitiveSet<V>, i: usize, text: &mut String) -> usize
+        where V: std::fmt::Display {
+        match &self.nodes[i] {
+            Node::Function(f, arity) => {
+                text.push_str(&set.functions[*f].0);
+                text.push('(');
+                let mut next = i + 1;
+                for child in 0..*arity {
+                    if child > 0 {
+                        text.push_str(", ");
+                    }
+                    next = self.format_node(set, next, text);
+                }
+                text.push(')');
+                next
+            }
+            Node::Variable(v) => {
+                text.push_str(&set.variables[*v]);
+                i + 1
+            }
+            Node::Constant(c) => {
+                let _ = write!(text, "{}", c);
+                i + 1
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn arithmetic<'a>() -> PrimitiveSet<'a, f64> {
+        PrimitiveSet::new()
+            .function("add", 2, |a: &[f64]| a[0] + a[1])
+            .function("neg", 1, |a: &[f64]| -a[0])
+            .variable("x")
+            .constant(2.0)
+    }
+
+    #[test]
+    fn evaluate() {
+        let set = arithmetic();
+        // add(x, neg(2))
+        let tree = Tree { nodes: vec![Node::Function(0, 2), Node::Variable(0), Node::Function(1, 1), Node::Constant(2.0)] };
+
+        assert_eq!(tree.evaluate(&set, &[5.0]), 3.0);
+        assert_eq!(tree.depth(), 2);
+        assert_eq!(tree.subtree_end(2), 4);
+        assert_eq!(tree.format(&set), "add(x, neg(2))");
+        assert_eq!(tree.replace(2, &[Node::Variable(0)]).format(&set), "add(x, x)");
+        assert_eq!(tree.subtree(2).format(&set), "neg(2)");
+    }
+
+    #[test]
+    fn random_trees() {
+        let set = arithmetic();
+        let mut rng = StdRng::seed_from_u64(1);
+
+        for depth in 0..6 {
+            let full = set.random_tree(depth, true, &mut rng);
+            assert_eq!(full.depth(), depth);
+            assert_eq!(full.subtree_end(0), full.len());
+            assert!(set.random_tree(depth, false, &mut rng).depth() <= depth);
+        }
+    }
+}
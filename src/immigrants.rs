@@ -0,0 +1,41 @@
+use rand::RngCore;
+
+use crate::{Evaluated, Fitness, Genetic, MaybeSync, Offspring};
+
+impl<'a, T, F> Genetic<'a, T, F>
+    where T: Clone + MaybeSync, F: Fitness + MaybeSync {
+    /// The number of random immigrants in a generation (the best agent and the elites are never replaced)
+    pub(crate) fn immigrant_count(&self) -> usize {
+        let count = (self.random_immigrants * self.population as f64).round() as usize;
+        count.min(self.population.saturating_sub(self.elitism.max(1)))
+    }
+
+    /// Adds the random immigrants of the next generation to the offspring, so they are evaluated with them
+    pub(crate) fn add_immigrants(&self, offspring: &mut Vec<Offspring<T, F>>, rng: &mut dyn RngCore) {
+        for _ in 0..self.immigrant_count() {
+            offspring.push(((self.get_random_agent)(rng), None));
+        }
+    }
+
+    /// Replaces the worst agents of the next generation with the evaluated immigrants
+    pub(crate) fn replace_worst(&self, xg: &mut [Evaluated<T, F>], immigrants: Vec<Evaluated<T, F>>) {
+        let mut order = (0..xg.len()).collect::<Vec<usize>>();
+        order.sort_by(|a, b| self.objective.compare(&xg[*a].fitness, &xg[*b].fitness));
+        for (i, immigrant) in order.into_iter().rev().zip(immigrants) {
+            xg[i] = immigrant;
+        }
+    }
+
+    /// Replaces the worst agents of the generation with the given ones, as long as they are fitter
+    pub(crate) fn replace_fitter(&self, xg: &mut [Evaluated<T, F>], mut agents: Vec<Evaluated<T, F>>) {
+        let mut order = (0..xg.len()).collect::<Vec<usize>>();
+        order.sort_by(|a, b| self.objective.compare(&xg[*a].fitness, &xg[*b].fitness));
+        agents.sort_by(|a, b| self.objective.compare(&a.fitness, &b.fitness));
+        for (i, x) in order.into_iter().rev().zip(agents) {
+            if !self.is_fitter(&x, &xg[i]) {
+                break;
+            }
+            xg[i] = x;
+        }
+    }
+}
@@ -0,0 +1,411 @@
+use rand::prelude::*;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+
+use crate::{ConfigError, Evaluated, Fitness, Genetic, GeneticState, MaybeSync, RunError, RunResult};
+
+/// The islands the migrants of an island are sent to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Topology {
+    /// Every island sends its migrants to the next one (the last one to the first)
+    #[default]
+    Ring,
+    /// Every island sends its migrants to every other island
+    FullyConnected,
+    /// Every island sends its migrants to a randomly chosen other island at every migration
+    Random,
+}
+
+/// Settings of the migration between the islands: every `interval` generations the `count` best agents of
+/// every island are copied to the islands given by the topology, where they replace the worst agents
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Migration {
+    /// Number of generations between two migrations (at least 1)
+    pub interval: usize,
+    /// Number of the best agents sent by an island (at least 1, less than the population of every island)
+    pub count: usize,
+    /// The islands the migrants are sent to
+    pub topology: Topology,
+}
+
+impl Topology {
+    /// Returns the islands the migrants of island `i` are sent to (out of `n` islands)
+    pub fn destinations(&self, i: usize, n: usize, rng: &mut dyn RngCore) -> Vec<usize> {
+        if n < 2 {
+            return Vec::new();
+        }
+        match self {
+            Topology::Ring => vec![(i + 1) % n],
+            Topology::FullyConnected => (0..n).filter(|j| *j != i).collect(),
+            Topology::Random => vec![(i + rng.gen_range(1, n)) % n],
+        }
+    }
+}
+
+impl Migration {
+    /// Migration on a ring: every `interval` generations the `count` best agents move to the next island
+    pub fn ring(interval: usize, count: usize) -> Self {
+        Migration { interval, count, topology: Topology::Ring }
+    }
+
+    /// Checks whether the parameters are usable with the given population size
+    pub fn validate(&self, population: usize) -> Result<(), ConfigError> {
+        if self.interval == 0 {
+            return Err(ConfigError::InvalidMigration("interval must be at least 1".to_string()));
+        }
+        if self.count == 0 || self.count >= population {
+            return Err(ConfigError::InvalidMigration(format!("count must be between 1 and the population - 1, got {}", self.count)));
+        }
+
+        Ok(())
+    }
+}
+
+/// Evolves several populations (islands) side by side, the islands exchange their best agents from time to time
+///
+/// The islands are Genetics, so they can have different parameters (population, probabilities, operators,
+/// selection, ...), but they should share the fitness function: the migrants keep their fitness.
+/// Every island stops according to its own stopping criteria, the run is over when all of them stopped.
+/// Keeping the populations apart preserves diversity, which helps on multimodal problems.
+///
+/// # Examples:
+/// ```
+/// use rand::prelude::*;
+/// use bbte_optim_tzim1773_genetic::{Genetic, IslandModel, Migration, Topology};
+///
+/// fn main() {
+///     let island = |pm: f64| Genetic::builder()
+///         .population(20)
+///         .max_generation(50)
+///         .pm(pm)
+///         .random_agent(|rng| rng.gen_range(-5.0, 5.0))
+///         .fitness(|a: &f64| 5.0 - a * a)
+///         .mutate(|a: &f64, rng| *a + rng.gen_range(-0.1, 0.1))
+///         .offspring(|a: &f64, b: &f64, _rng| (*a + *b) / 2.0)
+///         .build()
+///         .unwrap();
+///
+///     let model = IslandModel::new(
+///         vec![island(0.2), island(0.4), island(0.8)],
+///         Migration { interval: 5, count: 2, topology: Topology::Ring },
+///     ).unwrap();
+///
+///     let result = model.run();
+///     println!("{} on island {}", result.best, result.best_island); // should be a number close to 0
+/// }
+/// ```
+pub struct IslandModel<'a, T, F = f64> {
+    /// The islands (at least one)
+    pub islands: Vec<Genetic<'a, T, F>>,
+    /// The way the agents move between the islands
+    pub migration: Migration,
+}
+
+/// The outcome of an island model run
+#[derive(Debug, Clone, PartialEq)]
+pub struct IslandResult<T, F = f64> {
+    /// The best agent of all islands
+    pub best: T,
+    /// The fitness of the best agent
+    pub best_fitness: F,
+    /// The index of the island of the best agent
+    pub best_island: usize,
+    /// The number of times the fitness functions were called on all islands
+    pub evaluations: usize,
+    /// The number of migrations
+    pub migrations: usize,
+    /// The results of the islands
+    pub islands: Vec<RunResult<T, F>>,
+}
+
+impl<'a, T, F> IslandModel<'a, T, F>
+    where T: Clone + MaybeSync, F: Fitness + MaybeSync {
+    /// Creates an island model, checking the parameters of the migration
+    pub fn new(islands: Vec<Genetic<'a, T, F>>, migration: Migration) -> Result<Self, ConfigError> {
+        let model = IslandModel { islands, migration };
+        model.validate()?;
+
+        Ok(model)
+    }
+
+    /// Checks whether there is an island and the migration fits every island
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.islands.is_empty() {
+            return Err(ConfigError::InvalidMigration("at least one island is needed".to_string()));
+        }
+        self.islands.iter().try_for_each(|island| self.migration.validate(island.population))
+    }
+
+    /// Runs the islands until all of them stop
+    ///
+    /// # Panics:
+    ///  If the fitness function of an island fails and its fitness_error_policy gives up on the agent
+    ///  (use try_run() to get the error instead)
+    pub fn run(&self) -> IslandResult<T, F> {
+        self.run_with_rng(&mut thread_rng())
+    }
+
+    /// Same as run(), but returns the error of the fitness function instead of panicking
+    pub fn try_run(&self) -> Result<IslandResult<T, F>, RunError> {
+        self.try_run_with_rng(&mut thread_rng())
+    }
+
+    /// Same as run(), but the random number generators of the islands are seeded from the given one
+    ///
+    /// # Arguments:
+    ///
+    /// * `rng` the random number generator seeding the islands and choosing the destinations of Random migration
+    pub fn run_with_rng<R: Rng>(&self, rng: &mut R) -> IslandResult<T, F> {
+        match self.try_run_with_rng(rng) {
+            Ok(result) => result,
+            Err(error) => panic!("{}", error),
+        }
+    }
+
+    /// Same as run(), but the random number generators of the islands are seeded from the given seed
+    pub fn run_with_seed(&self, seed: u64) -> IslandResult<T, F> {
+        self.run_with_rng(&mut StdRng::seed_from_u64(seed))
+    }
+
+    /// Same as run_with_rng(), but returns the error of the fitness function instead of panicking
+    ///
+    /// # Arguments:
+    ///
+    /// * `rng` the random number generator seeding the islands and choosing the destinations of Random migration
+    pub fn try_run_with_rng<R: Rng>(&self, rng: &mut R) -> Result<IslandResult<T, F>, RunError> {
+        let mut states = self.islands.iter()
+            .map(|island| island.start_with_rng(StdRng::seed_from_u64(rng.gen())))
+            .collect::<Result<Vec<GeneticState<T, F>>, RunError>>()?;
+        let mut migrations = 0;
+
+        let mut generation = 0;
+        loop {
+            let mut running = false;
+            for (island, state) in self.islands.iter().zip(states.iter_mut()) {
+                running |= island.step(state)?.is_none();
+            }
+            generation += 1;
+            if !running {
+                break;
+            }
+            if generation % self.migration.interval == 0 {
+                self.migrate(&mut states, rng);
+                migrations += 1;
+            }
+        }
+
+        Ok(self.finish(states, migrations))
+    }
+
+    /// Same as run(), but every island is evolved on its own thread
+    ///
+    /// The islands send their migrants through channels without waiting for each other: the migrants that
+    /// arrived until an island's migration are put into its population. The runs are faster, but they depend on
+    /// the speed of the threads, so seeded runs can't be reproduced (and they differ from run_with_seed()).
+    ///
+    /// # Panics:
+    ///  Same as run(), or if an island panics
+    pub fn run_parallel(&self) -> IslandResult<T, F>
+        where T: Send + Sync, F: Send + Sync {
+        match self.try_run_parallel_with_rng(&mut thread_rng()) {
+            Ok(result) => result,
+            Err(error) => panic!("{}", error),
+        }
+    }
+
+    /// Same as run_parallel(), but returns the error of the fitness function instead of panicking
+    /// (the first error of the islands)
+    ///
+    /// # Arguments:
+    ///
+    /// * `rng` the random number generator seeding the islands
+    pub fn try_run_parallel_with_rng<R: Rng>(&self, rng: &mut R) -> Result<IslandResult<T, F>, RunError>
+        where T: Send + Sync, F: Send + Sync {
+        let seeds = self.islands.iter().map(|_| rng.gen()).collect::<Vec<u64>>();
+        let (senders, receivers): (Vec<_>, Vec<_>) = self.islands.iter()
+            .map(|_| mpsc::channel::<Vec<Evaluated<T, F>>>())
+            .unzip();
+
+        let outcomes = thread::scope(|scope| {
+            let handles = receivers.into_iter()
+                .zip(seeds)
+                .enumerate()
+                .map(|(i, (receiver, seed))| {
+                    let senders = senders.clone();
+                    scope.spawn(move || self.run_island(i, seed, &senders, &receiver))
+                })
+                .collect::<Vec<_>>();
+
+            handles.into_iter()
+                .map(|handle| handle.join().unwrap_or_else(|panic| std::panic::resume_unwind(panic)))
+                .collect::<Vec<_>>()
+        });
+
+        let mut states = Vec::with_capacity(outcomes.len());
+        let mut migrations = 0;
+        for outcome in outcomes {
+            let (state, island_migrations) = outcome?;
+            states.push(state);
+            migrations = migrations.max(island_migrations);
+        }
+
+        Ok(self.finish(states, migrations))
+    }
+
+    /// Evolves island `i` until it stops, sending its migrants to the other islands and taking in the ones that
+    /// arrived at every migration, returns its state and the number of its migrations
+    fn run_island(&self, i: usize, seed: u64, senders: &[Sender<Vec<Evaluated<T, F>>>], receiver: &Receiver<Vec<Evaluated<T, F>>>) -> Result<(GeneticState<T, F>, usize), RunError> {
+        let island = &self.islands[i];
+        let mut state = island.start_with_rng(StdRng::seed_from_u64(seed))?;
+        let mut migrations = 0;
+
+        while island.step(&mut state)?.is_none() {
+            if state.generation % self.migration.interval != 0 {
+                continue;
+            }
+            let migrants = self.emigrants(island, &state.population);
+            for j in self.migration.topology.destinations(i, senders.len(), &mut state.rng) {
+                // the islands that stopped don't take in migrants
+                let _ = senders[j].send(migrants.clone());
+            }
+            let arrived = receiver.try_iter().flatten().collect::<Vec<Evaluated<T, F>>>();
+            if !arrived.is_empty() {
+                Self::immigrate(island, &mut state.population, arrived);
+            }
+            migrations += 1;
+        }
+
+        Ok((state, migrations))
+    }
+
+    /// Copies the best agents of the running islands to their destinations, in place of the worst agents
+    pub(crate) fn migrate<R>(&self, states: &mut [GeneticState<T, F, R>], rng: &mut dyn RngCore) {
+        let running = (0..states.len())
+            .filter(|i| states[*i].termination.is_none())
+            .collect::<Vec<usize>>();
+        if running.len() < 2 {
+            return;
+        }
+
+        // every island sends its migrants before it receives any
+        let mut arrivals = vec![Vec::new(); states.len()];
+        for (k, &i) in running.iter().enumerate() {
+            let migrants = self.emigrants(&self.islands[i], &states[i].population);
+            for j in self.migration.topology.destinations(k, running.len(), rng) {
+                arrivals[running[j]].extend(migrants.iter().cloned());
+            }
+        }
+
+        for (i, migrants) in arrivals.into_iter().enumerate() {
+            if !migrants.is_empty() {
+                Self::immigrate(&self.islands[i], &mut states[i].population, migrants);
+            }
+        }
+    }
+
+    /// Returns the best `count` agents of an island
+    fn emigrants(&self, island: &Genetic<'a, T, F>, xg: &[Evaluated<T, F>]) -> Vec<Evaluated<T, F>> {
+        let mut order = (0..xg.len()).collect::<Vec<usize>>();
+        order.sort_by(|a, b| island.objective.compare(&xg[*a].fitness, &xg[*b].fitness));
+
+        order.into_iter()
+            .take(self.migration.count)
+            .map(|i| xg[i].clone())
+            .collect()
+    }
+
+    /// Puts the migrants in place of the worst agents of an island (the best agent first again)
+    fn immigrate(island: &Genetic<'a, T, F>, xg: &mut Vec<Evaluated<T, F>>, migrants: Vec<Evaluated<T, F>>) {
+        let arriving = migrants.len().min(xg.len());
+        xg.sort_by(|a, b| island.objective.compare(&a.fitness, &b.fitness));
+        xg.truncate(xg.len() - arriving);
+        xg.extend(migrants.into_iter().take(arriving));
+        xg.sort_by(|a, b| island.objective.compare(&a.fitness, &b.fitness));
+    }
+
+    /// Returns the results of the islands and the best agent of them
+    fn finish<R>(&self, states: Vec<GeneticState<T, F, R>>, migrations: usize) -> IslandResult<T, F> {
+        let islands = self.islands.iter()
+            .zip(states)
+            .map(|(island, state)| island.finish(state))
+            .collect::<Vec<RunResult<T, F>>>();
+        let objective = self.islands[0].objective;
+        let mut best_island = 0;
+        for (i, result) in islands.iter().enumerate().skip(1) {
+            if objective.is_better(&result.best_fitness, &islands[best_island].best_fitness) {
+                best_island = i;
+            }
+        }
+
+        IslandResult {
+            best: islands[best_island].best.clone(),
+            best_fitness: islands[best_island].best_fitness.clone(),
+            best_island,
+            evaluations: islands.iter().map(|result| result.evaluations).sum(),
+            migrations,
+            islands,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn island<'a>(population: usize, pm: f64) -> Genetic<'a, f64> {
+        Genetic::builder()
+            .population(population)
+            .max_generation(30)
+            .pm(pm)
+            .random_agent(|rng| rng.gen_range(-5.0, 5.0))
+            .fitness(|a: &f64| 5.0 - a * a)
+            .mutate(|a: &f64, rng| *a + rng.gen_range(-0.1, 0.1))
+            .offspring(|a: &f64, b: &f64, _rng| (*a + *b) / 2.0)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn island_model() {
+        for topology in [Topology::Ring, Topology::FullyConnected, Topology::Random] {
+            let model = IslandModel::new(vec![island(10, 0.2), island(20, 0.4), island(10, 0.8)], Migration { interval: 5, count: 2, topology }).unwrap();
+
+            let result = model.run_with_seed(7);
+            assert_eq!(result, model.run_with_seed(7));
+            assert!(result.best.abs() < 0.1);
+            assert_eq!(result.islands[result.best_island].best, result.best);
+            assert_eq!(result.migrations, 5);
+            assert_eq!(result.islands.iter().map(|x| x.population.len()).collect::<Vec<usize>>(), vec![10, 20, 10]);
+            assert_eq!(result.evaluations, result.islands.iter().map(|x| x.evaluations).sum::<usize>());
+        }
+
+        let model = IslandModel::new(vec![island(10, 0.2), island(20, 0.4), island(10, 0.8), island(10, 0.4)], Migration { interval: 5, count: 2, topology: Topology::Random }).unwrap();
+        let result = model.run_parallel();
+        assert!(result.best.abs() < 0.1);
+        assert_eq!(result.islands.len(), 4);
+        assert!(result.islands.iter().all(|x| x.generations == 30));
+        assert_eq!(result.migrations, 5);
+        assert_eq!(result.islands[result.best_island].best, result.best);
+
+        assert!(IslandModel::new(vec![island(10, 0.2)], Migration::ring(0, 2)).is_err());
+        assert!(IslandModel::new(vec![island(10, 0.2), island(3, 0.2)], Migration::ring(5, 3)).is_err());
+        assert!(IslandModel::<f64>::new(Vec::new(), Migration::ring(5, 1)).is_err());
+    }
+
+    #[test]
+    fn migration() {
+        let model = IslandModel::new(vec![island(4, 0.2), island(4, 0.2)], Migration::ring(1, 2)).unwrap();
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut states = model.islands.iter()
+            .map(|island| island.start_with_rng(StdRng::seed_from_u64(rng.gen())).unwrap())
+            .collect::<Vec<_>>();
+        for (i, state) in states.iter_mut().enumerate() {
+            state.population = (0..4).map(|k| Evaluated { agent: (10 * i + k) as f64, fitness: (10 * i + k) as f64 }).collect();
+        }
+
+        model.migrate(&mut states, &mut rng);
+        let fitness = |state: &GeneticState<f64>| state.population.iter().map(|x| x.fitness).collect::<Vec<f64>>();
+        assert_eq!(fitness(&states[0]), vec![13.0, 12.0, 3.0, 2.0]);
+        assert_eq!(fitness(&states[1]), vec![13.0, 12.0, 3.0, 2.0]);
+    }
+}
@@ -1,7 +1,206 @@
 #![crate_name = "bbte_optim_tzim1773_genetic"]
 use rand::prelude::*;
+use std::cmp::Ordering;
+#[cfg(feature = "rayon")]
+use std::sync::Arc;
+use std::time::Duration;
 
-pub struct Genetic<'a, T> {
+use adaptation::Rates;
+use operator_selection::Bandit;
+use trace::phase;
+
+mod adaptation;
+mod annealing;
+mod ant_colony;
+mod archive;
+mod baseline;
+mod bee_colony;
+pub mod benchmarks;
+#[cfg(feature = "async")]
+mod asynchronous;
+mod builder;
+mod cache;
+mod cellular;
+mod clearing;
+mod callback;
+#[cfg(feature = "serde")]
+mod checkpoint;
+mod coevolution;
+#[cfg(feature = "serde")]
+mod config;
+mod constraints;
+mod decoder;
+mod dedup;
+mod differential_evolution;
+mod engine;
+#[cfg(feature = "distributed")]
+pub mod distributed;
+mod dynamic;
+pub mod eda;
+mod error;
+mod evaluated;
+mod evolution_strategy;
+pub mod experiment;
+mod external;
+mod fitness;
+pub mod genome;
+pub mod gp;
+mod immigrants;
+mod island;
+mod map_elites;
+mod memetic;
+mod moead;
+mod multi_objective;
+mod noise;
+mod novelty;
+mod nsga2;
+mod objective;
+mod operator_selection;
+mod opposition;
+mod optimizer;
+mod parallel;
+mod particle_swarm;
+mod portfolio;
+pub mod operators;
+pub mod pareto;
+pub mod scalarization;
+mod problem;
+mod restart;
+mod result;
+mod run_log;
+mod schedule;
+pub mod scheduling;
+mod selection;
+mod sharing;
+mod spea2;
+mod speciation;
+mod state;
+mod steady_state;
+mod surrogate;
+mod tabu;
+mod termination;
+mod trace;
+mod tuning;
+
+pub use adaptation::{DiversityAdaptation, Hypermutation, LandscapeChange, OneFifthRule};
+pub use annealing::{AnnealingResult, Cooling, SimulatedAnnealing};
+pub use ant_colony::{AcoVariant, AntColony};
+pub use archive::{Archive, SameFn};
+pub use baseline::{Ascent, BaselineResult, HillClimber, RandomSearch};
+pub use bee_colony::BeeColony;
+pub use builder::GeneticBuilder;
+pub use cache::{CacheStats, FitnessCache, LruCache};
+pub use cellular::{Cellular, Neighborhood};
+pub use clearing::Clearing;
+pub use callback::{GenerationFn, GenerationInfo, ProgressFn};
+#[cfg(feature = "serde")]
+pub use checkpoint::Checkpointing;
+#[cfg(feature = "serde")]
+pub use config::GeneticConfig;
+pub use coevolution::{Coevolution, CoevolutionResult, CompetitionFn, HallOfFame, Interaction, Sampling, TeamFn};
+pub use constraints::{ConstraintHandling, ConstraintsFn, Penalty};
+pub use decoder::{DecodeFn, Decoder};
+pub use dynamic::DynamicEnvironment;
+pub use dedup::DuplicatesFn;
+pub use differential_evolution::{DeStrategy, DifferentialEvolution};
+pub use error::{ConfigError, ExternalError, FitnessError, FitnessErrorPolicy, RunError, TsplibError};
+#[cfg(feature = "serde")]
+pub use error::{CheckpointError, ConfigFileError};
+#[cfg(feature = "distributed")]
+pub use error::DistributedError;
+use error::EvaluationBudget;
+pub use evaluated::Evaluated;
+pub use evolution_strategy::EvolutionStrategy;
+#[cfg(feature = "distributed")]
+pub use distributed::DistributedFitness;
+pub use external::{ExternalFitness, LineEncode, LineFormat};
+pub use fitness::Fitness;
+pub use island::{IslandModel, IslandResult, Migration, Topology};
+pub use map_elites::{Dimension, MapElites, MapElitesResult};
+pub use memetic::{LocalSearch, LocalSearchFn};
+pub use moead::Decomposition;
+pub use multi_objective::{MultiObjective, MultiObjectiveAlgorithm, MultiObjectiveResult};
+pub use noise::{Resampling, SampleStatistic, Sampled};
+pub use novelty::{BehaviorFn, Novelty};
+pub use nsga2::{Nsga2, Nsga2Result};
+pub use objective::Objective;
+pub use operator_selection::{OperatorSelection, OperatorStats};
+pub use opposition::{OppositeFn, Opposition};
+pub use optimizer::Optimizer;
+pub use parallel::MaybeSync;
+pub use particle_swarm::{ParticleSwarm, SwarmTopology, VelocityUpdate};
+pub use portfolio::{Portfolio, PortfolioResult, PortfolioStats};
+pub use problem::Problem;
+pub use restart::Restart;
+pub use result::{GenerationStats, RunResult};
+pub use run_log::RunLog;
+pub use schedule::{Schedule, ScheduleFn};
+pub use selection::Selection;
+pub use sharing::Sharing;
+pub use speciation::{Speciation, Species};
+pub use state::{GenerationSnapshot, Generations, GeneticState};
+pub use steady_state::{Replacement, SteadyState};
+pub use surrogate::{FeaturesFn, Surrogate, SurrogateModel};
+pub use tabu::{Aspiration, NeighborhoodFn, TabuSearch};
+pub use termination::{CancellationToken, RunStatus, Stagnation, StopCondition, StopFn, Termination};
+pub use tuning::{Hyperparameters, Tuner, TuningResult};
+
+/// Function that returns a new agent (using the given random number generator)
+pub type AgentFn<'a, T> = Box<dyn Fn(&mut dyn RngCore) -> T + Send + Sync + 'a>;
+/// Function that returns the fitness of an agent (or the reason why it couldn't be evaluated)
+pub type FitnessFn<'a, T, F = f64> = Box<dyn Fn(&T) -> Result<F, FitnessError> + Send + Sync + 'a>;
+/// Function that returns the future fitness of an agent (e.g. the answer of a remote service)
+#[cfg(feature = "async")]
+pub type AsyncFitnessFn<'a, T, F = f64> = Box<dyn Fn(&T) -> futures::future::BoxFuture<'a, F> + Send + Sync + 'a>;
+/// Function that returns the fitness of an agent on every test case
+pub type CaseFitnessFn<'a, T> = Box<dyn Fn(&T) -> Vec<f64> + Send + Sync + 'a>;
+/// Function that returns the mutated version of an agent (using the given random number generator)
+pub type MutateFn<'a, T> = Box<dyn Fn(&T, &mut dyn RngCore) -> T + Send + Sync + 'a>;
+/// Function that returns the distance of two agents (0.0 if they are the same)
+pub type DistanceFn<'a, T> = Box<dyn Fn(&T, &T) -> f64 + Send + Sync + 'a>;
+/// Function that adjusts the fitness of an agent before selection (e.g. penalises long genomes)
+pub type PenaltyFn<'a, T, F = f64> = Box<dyn Fn(&T, F) -> F + Send + Sync + 'a>;
+/// Function that fixes an invalid agent (e.g. a broken permutation or an out-of-bounds value)
+pub type RepairFn<'a, T> = Box<dyn Fn(&T) -> T + Send + Sync + 'a>;
+/// Function that creates offspring (one or more) from two agents (using the given random number generator)
+pub type OffspringFn<'a, T> = Box<dyn Fn(&T, &T, &mut dyn RngCore) -> Vec<T> + Send + Sync + 'a>;
+
+/// A new agent and its fitness if it was already measured
+pub(crate) type Offspring<T, F> = (T, Option<F>);
+
+/// A Genetic that doesn't borrow anything: it can be returned from functions, stored in structs
+/// and moved to other threads (all functions are Send + Sync)
+///
+/// # Examples:
+/// ```
+/// use rand::prelude::*;
+/// use bbte_optim_tzim1773_genetic::{Genetic, OwnedGenetic};
+///
+/// fn parabola(offset: f64) -> OwnedGenetic<f64> {
+///     Genetic::builder()
+///         .max_generation(20)
+///         .random_agent(|rng| rng.gen_range(-5.0, 5.0))
+///         .fitness(move |a: &f64| 5.0 - (a - offset) * (a - offset))
+///         .mutate(|a: &f64, rng| *a + rng.gen_range(-0.01, 0.01))
+///         .offspring(|a: &f64, b: &f64, _rng| (*a + *b) / 2.0)
+///         .build()
+///         .unwrap()
+/// }
+///
+/// fn main() {
+///     let test = parabola(1.0);
+///     let handle = std::thread::spawn(move || test.run());
+///
+///     println!("{}", handle.join().unwrap().best); // should be a number close to 1
+/// }
+/// ```
+pub type OwnedGenetic<T, F = f64> = Genetic<'static, T, F>;
+
+/// Genetic algorithm maximising (or minimising) the fitness of agents of type T
+///
+/// The fitness is f64 by default, but it can be any type implementing Fitness (integers, tuples compared
+/// lexicographically, your own struct, ...)
+pub struct Genetic<'a, T, F = f64> {
     /// Population size: with increased size comes increased accuracy but decreased speed
     /// Suggested value: 100
     pub population: usize,
@@ -14,29 +213,489 @@ pub struct Genetic<'a, T> {
     /// Probability of mutation ((never) 0.0 <= pm <= 1.0 (always))
     pub pm: f64,
     /// Function that returns one agent which is used in the 0th generation
-    /// You can start from a given point, or use a random generator like the rand crate
-    pub get_random_agent: &'a dyn Fn()->T,
-    /// Function that evaluates an agent and returns it's fitness (this algorithm maximises this function)
-    pub f_fitness: &'a dyn Fn(&T) -> f64,
+    /// You can start from a given point, or use the given random number generator
+    /// (Borrowed closures can be given too: Box::new(&agent), but then the Genetic can't outlive them)
+    pub get_random_agent: AgentFn<'a, T>,
+    /// Known agents put into the 0th generation (e.g. the results of a heuristic or of a previous run), the rest
+    /// of the generation is created with get_random_agent (there can't be more of them than the population)
+    /// Suggested value: Vec::new()
+    pub initial_population: Vec<T>,
+    /// If it's given, the opposites of the agents of the 0th generation are evaluated too and the fitter one of
+    /// every pair is kept, and after a generation the agents can jump to their opposites (if those are fitter)
+    /// Suggested value: None (or Some(Opposition::new(RealVector::opposite).jumping_rate(0.3)) for real vectors)
+    pub opposition: Option<Opposition<'a, T>>,
+    /// Function that evaluates an agent and returns it's fitness (this algorithm maximises this function,
+    /// unless the objective is Minimize)
+    /// If the evaluation can fail, return the error, it is handled according to fitness_error_policy
+    pub f_fitness: FitnessFn<'a, T, F>,
     /// Function that mutates an agent and returns the mutated version of it
-    pub f_mutate: &'a dyn Fn(&T) -> T,
-    /// Function that crossovers two agents and creates an offspring
-    pub f_offspring: &'a dyn Fn(&T, &T) -> T,
+    /// Use the given random number generator, so that seeded runs can be reproduced
+    pub f_mutate: MutateFn<'a, T>,
+    /// Function that crossovers two agents and creates offspring
+    /// It can return more than one (e.g. both children of a two-point crossover), they are all added to the population
+    /// (the builder's offspring() wraps functions that return a single child)
+    pub f_offspring: OffspringFn<'a, T>,
+    /// What happens when f_fitness returns an error (abort the run, assign the worst fitness or retry)
+    /// Suggested value: FitnessErrorPolicy::Abort
+    pub fitness_error_policy: FitnessErrorPolicy,
+    /// Whether the fitness function should be maximised or minimised
+    /// Suggested value: Objective::Maximize
+    pub objective: Objective,
+    /// Number of the best agents that are copied unchanged (without mutation) into the next generation
+    /// Suggested value: 0 (or a few percent of the population)
+    pub elitism: usize,
+    /// The fraction of the population replaced with new random agents in every generation (the worst agents are
+    /// replaced, the elites and the best agent are kept), it helps on dynamic and deceptive problems
+    /// Suggested value: 0.0 (or 0.05 - 0.2 if the population converges too fast)
+    pub random_immigrants: f64,
+    /// The way the agents of the next generation are chosen from the agents and their offspring (survivor selection)
+    /// Suggested value: Selection::Truncation (or Selection::tournament(2) if the population converges too fast)
+    pub selection: Selection,
+    /// The way the parents are chosen, if it's None every agent is paired with a random partner
+    /// (Truncation can't be used here, since it can't choose an agent twice)
+    /// Suggested value: None (or Some(Selection::tournament(2)))
+    pub parent_selection: Option<Selection>,
+    /// If it's given, every generation only creates a few offspring which replace existing agents
+    /// (instead of rebuilding the whole generation), every agent is evaluated only once
+    /// The elites are protected from the replacement, selection is not used.
+    /// Suggested value: None (or Some(SteadyState::new(1)) if the fitness function is expensive)
+    pub steady_state: Option<SteadyState>,
+    /// If it's given, the mu best agents create lambda offspring in every generation and the next generation
+    /// is chosen from the offspring, (mu, lambda), or from the parents and the offspring, (mu + lambda)
+    /// (it can't be used together with steady_state)
+    /// Suggested value: None (or Some(EvolutionStrategy::comma(mu, 7 * mu)) with population = mu)
+    pub evolution_strategy: Option<EvolutionStrategy>,
+    /// If it's given, the agents live on a grid and mate only with their neighbours, the offspring replace their
+    /// parents if they are not worse (it can't be used together with steady_state and evolution_strategy)
+    /// Suggested value: None (or Some(Cellular::new(10, 10)) with population = 100 on deceptive problems)
+    pub cellular: Option<Cellular>,
+    /// Function that adjusts the fitness of an agent before the selections compare it, e.g. to control the bloat of
+    /// variable-length genomes: |a, f| f - 0.01 * a.len() as f64 (the reported fitness is not adjusted)
+    /// Suggested value: None
+    pub f_length_penalty: Option<PenaltyFn<'a, T, F>>,
+    /// Function that returns how much an agent violates each constraint (0.0 or less if it's satisfied), with it
+    /// the selections, the elitism and the best agent follow Deb's feasibility rules: a feasible agent beats an
+    /// infeasible one, a smaller total violation beats a bigger one, and the fitness only decides between feasible
+    /// agents (the fitness function doesn't need penalties)
+    /// Suggested value: None
+    pub f_constraints: Option<ConstraintsFn<'a, T>>,
+    /// The way the violations of f_constraints are handled by the selections: Deb's feasibility rules, penalties
+    /// subtracted from the fitness (static, dynamic or adaptive ones, per constraint) or stochastic ranking, the
+    /// elitism and the best agent follow the feasibility rules in every case
+    /// Suggested value: ConstraintHandling::Feasibility
+    pub constraint_handling: ConstraintHandling,
+    /// Function that fixes every offspring and mutant before it's evaluated, so the operators don't have to keep the
+    /// agents valid and the invalid agents don't need penalties: |a| a.clamp(0.0, 1.0)
+    /// Suggested value: None
+    pub f_repair: Option<RepairFn<'a, T>>,
+    /// If it's given, the best candidates are improved by a local search (in every interval-th generation) before
+    /// the next generation is selected (memetic algorithm)
+    /// Suggested value: None
+    pub local_search: Option<LocalSearch<'a, T>>,
+    /// Function that evaluates an agent on every test case (bigger is better, unless the objective is Minimize)
+    /// Only needed by the lexicase selections, the other parts of the algorithm use f_fitness
+    pub f_case_fitness: Option<CaseFitnessFn<'a, T>>,
+    /// If it's given, pm is adapted after every generation with the 1/5 success rule (pm is the starting value)
+    /// Suggested value: None (or Some(OneFifthRule::default()))
+    pub pm_adaptation: Option<OneFifthRule>,
+    /// If it's given, pc and pm are set after every generation from the diversity of the population
+    /// (more mutation and less crossover when the population converges)
+    /// Suggested value: None (or Some(DiversityAdaptation::default()))
+    pub diversity_adaptation: Option<DiversityAdaptation>,
+    /// If it's given, pm is multiplied for a few generations when the best fitness stagnates or the fitness
+    /// landscape changes (the multiplier decays back to 1.0)
+    /// Suggested value: None (or Some(Hypermutation::new(10.0, 10).on_stagnation(Stagnation::new(20))))
+    pub hypermutation: Option<Hypermutation>,
+    /// If it's given, the fitness function can change during the run: the changes are detected with sentinel agents
+    /// (or signalled), and the population is re-evaluated and partly replaced after them
+    /// Suggested value: None (or Some(DynamicEnvironment::new(3).randomize(0.3)) for time-varying problems)
+    pub dynamic_environment: Option<DynamicEnvironment>,
+    /// Function that returns the distance of two agents (genotype distance), used to measure diversity and by the
+    /// niching methods (sharing and clearing)
+    /// If it's None the diversity is measured by the standard deviation of the fitness
+    /// Suggested value: None
+    pub f_distance: Option<DistanceFn<'a, T>>,
+    /// If it's given, the selection strategies use the fitness shared between the agents closer than its radius
+    /// (measured by f_distance), so several optima of a multimodal function can be kept in the population
+    /// Suggested value: None
+    pub sharing: Option<Sharing>,
+    /// If it's given, only the best agents of every niche (the agents closer than its radius by f_distance) keep
+    /// their fitness for the selection strategies, the others get the worst fitness
+    /// Suggested value: None
+    pub clearing: Option<Clearing>,
+    /// If it's given, the population is partitioned into species by f_distance, and every species creates its
+    /// part of the next generation from its own members (NEAT), the selection strategies are not used
+    /// Suggested value: None
+    pub speciation: Option<Speciation>,
+    /// If it's given, the next generation is selected by the novelty of the behaviour of the agents (blended with
+    /// their fitness if its weight is less than 1.0) instead of their fitness
+    /// Suggested value: None
+    pub novelty: Option<Novelty<'a, T>>,
+    /// If it's given, a model learned from the evaluated agents pre-screens the offspring, and only the most promising
+    /// ones are evaluated with f_fitness (for expensive fitness functions)
+    /// Suggested value: None (or Some(Surrogate::new(features).ratio(0.5)))
+    pub surrogate: Option<Surrogate<'a, T>>,
+    /// If it's given, pc follows this schedule across the generations (instead of being constant)
+    /// Suggested value: None
+    pub pc_schedule: Option<Schedule<'a>>,
+    /// If it's given, pm follows this schedule across the generations (instead of being constant)
+    /// Suggested value: None (or Some(Schedule::Linear { start: 0.5, end: 0.05 }))
+    pub pm_schedule: Option<Schedule<'a>>,
+    /// Named mutation operators, if there are any, one of them is chosen for every mutation instead of f_mutate
+    /// (randomly, or learning which of them create improvements if operator_selection is given)
+    /// Suggested value: empty
+    pub mutation_operators: Vec<(String, MutateFn<'a, T>)>,
+    /// Named crossover operators, if there are any, one of them is chosen for every crossover instead of f_offspring
+    /// Suggested value: empty
+    pub crossover_operators: Vec<(String, OffspringFn<'a, T>)>,
+    /// If it's given, the operators are chosen with a multi-armed bandit learning from their successes
+    /// (only matters if more than one operator is registered)
+    /// Suggested value: None (or Some(OperatorSelection::ucb()))
+    pub operator_selection: Option<OperatorSelection>,
+    /// If it's given, the run stops early when the best fitness doesn't improve for a number of generations
+    /// Suggested value: None (or Some(Stagnation::new(max_generation / 10)))
+    pub stagnation: Option<Stagnation>,
+    /// If it's given, the population is created again (keeping its best agents) when the best fitness doesn't
+    /// improve for a number of generations, at most max_restarts times in a run
+    /// Suggested value: None (or Some(Restart::new(Stagnation::new(max_generation / 20))))
+    pub restart: Option<Restart>,
+    /// If it's given, the run stops as soon as the best agent of a generation reaches this fitness
+    /// (or goes beyond it)
+    /// Suggested value: None (or the fitness of a known optimum)
+    pub target_fitness: Option<F>,
+    /// If it's given, the run stops when the time budget is used up, returning the last generation
+    /// (it's checked after every generation, so the last one can exceed it)
+    /// Suggested value: None
+    pub max_duration: Option<Duration>,
+    /// If it's given, the run stops when f_fitness was called this many times (the agents of the generation that
+    /// were evaluated before it was used up replace the worst agents if they are fitter, the first generation is
+    /// cut short if it's bigger), the standard way to compare algorithms
+    /// Suggested value: None
+    pub max_evaluations: Option<usize>,
+    /// If it's given, the run goes on until this (combination of) stopping criteria is met, max_generation is not used
+    /// (the other stopping criteria still stop the run)
+    /// Suggested value: None
+    pub stop_condition: Option<StopCondition<'a, F>>,
+    /// Function called after every generation (for logging or showing the progress), the run stops if it
+    /// returns ControlFlow::Break
+    /// Suggested value: None
+    pub on_generation: Option<GenerationFn<'a, T, F>>,
+    /// Function called after every generation with the progress of the run (generations executed, max_generation,
+    /// best fitness), e.g. to drive a progress bar
+    /// Suggested value: None
+    pub on_progress: Option<ProgressFn<'a, F>>,
+    /// If it's given, the run stops (returning the last generation) when the token is cancelled from another thread
+    /// Suggested value: None
+    pub cancellation: Option<CancellationToken>,
+    /// If it's given, the statistics of every generation are written to it (a CSV or JSON lines file) during the run
+    /// Suggested value: None
+    pub run_log: Option<RunLog<T>>,
+    /// If it's given, the best agents ever evaluated during the run are kept and returned in RunResult::archive
+    /// Suggested value: None
+    pub archive: Option<Archive<'a, T>>,
+    /// If it's given, the copies among the agents the next generation is selected from are replaced with new random
+    /// agents, so the population can't collapse into copies of one agent
+    /// Suggested value: None
+    pub f_duplicates: Option<DuplicatesFn<'a, T>>,
+    /// If it's given, the state of the run is written to a file every few generations, so it can be resumed
+    /// with resume_from()
+    /// Suggested value: None
+    #[cfg(feature = "serde")]
+    pub checkpoint: Option<Checkpointing<T, F>>,
+    /// If it's given, the fitness of the evaluated agents is stored in it and identical agents are not evaluated
+    /// again (useful for expensive fitness functions, the agents found in it don't count as evaluations)
+    /// Suggested value: None
+    pub fitness_cache: Option<Box<dyn FitnessCache<T, F> + 'a>>,
+    /// The thread pool the parallel parts of the run are executed in (the global pool of rayon if it's None)
+    /// Suggested value: None
+    #[cfg(feature = "rayon")]
+    pub thread_pool: Option<Arc<rayon::ThreadPool>>,
+    /// The smallest number of agents a thread works on at once (bigger values keep small populations and cheap
+    /// fitness functions from being split into too many jobs)
+    /// Suggested value: 1 (more if the fitness function is cheap)
+    #[cfg(feature = "rayon")]
+    pub chunk_size: usize,
+    /// Function that evaluates an agent asynchronously, run_async() awaits it for the new agents of every generation
+    /// (f_fitness is still called where an agent is measured while its generation is created, e.g. by pm_adaptation)
+    /// Suggested value: None
+    #[cfg(feature = "async")]
+    pub f_async_fitness: Option<AsyncFitnessFn<'a, T, F>>,
+    /// The number of evaluations run_async() awaits at the same time
+    /// Suggested value: 16 (or the number of requests your service can handle at once)
+    #[cfg(feature = "async")]
+    pub concurrency: usize,
+}
+
+impl<'a, T, F> Genetic<'a, T, F>
+    where T: Clone, F: Fitness {
+    /// Creates a Genetic whose functions are provided by a Problem
+    ///
+    /// # Arguments:
+    ///
+    /// * `population` population size
+    /// * `max_generation` number of generations
+    /// * `pc` probability of crossover
+    /// * `pm` probability of mutation
+    /// * `problem` the problem that generates, evaluates, mutates and crossovers the agents
+    ///
+    /// # Panics:
+    ///  If the parameters are not usable (the same checks as GeneticBuilder::build())
+    ///
+    /// # Examples:
+    /// ```
+    /// use rand::RngCore;
+    /// use bbte_optim_tzim1773_genetic::{Genetic, Problem};
+    ///
+    /// struct Target {
+    ///     value: i64,
+    /// }
+    ///
+    /// impl Problem<i64> for Target {
+    ///     fn random_agent(&self, _rng: &mut dyn RngCore) -> i64 { 0 }
+    ///     fn fitness(&self, a: &i64) -> f64 { -((self.value - *a).abs() as f64) }
+    ///     fn mutate(&self, a: &i64, _rng: &mut dyn RngCore) -> i64 { *a + 1 }
+    ///     fn crossover(&self, a: &i64, b: &i64, _rng: &mut dyn RngCore) -> i64 { (*a + *b) / 2 }
+    /// }
+    ///
+    /// fn main() {
+    ///     let test = Genetic::from_problem(10, 5, 0.5, 1.0, Target { value: 100 });
+    ///
+    ///     let result = test.run();
+    ///
+    ///     println!("{}", result.best); // every generation gets one step closer to 100
+    /// }
+    /// ```
+    pub fn from_problem<P>(population: usize, max_generation: usize, pc: f64, pm: f64, problem: P) -> Genetic<'a, T, F>
+        where P: Problem<T, F> + Send + Sync + 'a {
+        match Genetic::builder()
+            .population(population)
+            .max_generation(max_generation)
+            .pc(pc)
+            .pm(pm)
+            .problem(problem)
+            .build()
+        {
+            Ok(genetic) => genetic,
+            Err(error) => panic!("{}", error),
+        }
+    }
+
+    /// Returns a GeneticBuilder which validates the parameters before creating the Genetic
+    ///
+    /// # Examples:
+    /// ```
+    /// use rand::prelude::*;
+    /// use bbte_optim_tzim1773_genetic::Genetic;
+    ///
+    /// fn main() {
+    ///     let test = Genetic::builder()
+    ///         .population(100)
+    ///         .max_generation(20)
+    ///         .pc(0.5)
+    ///         .pm(0.4)
+    ///         .random_agent(|rng| rng.gen_range(-5.0, 5.0))
+    ///         .fitness(|a: &f64| 5.0 - a * a)
+    ///         .mutate(|a: &f64, rng| *a + rng.gen_range(-0.01, 0.01))
+    ///         .offspring(|a: &f64, b: &f64, _rng| (*a + *b) / 2.0)
+    ///         .build()
+    ///         .unwrap();
+    ///
+    ///     let result = test.run();
+    ///
+    ///     println!("{} {}", result.best, result.best_fitness); // should be a number close to 0 and 5
+    /// }
+    /// ```
+    pub fn builder() -> GeneticBuilder<'a, T, F> {
+        GeneticBuilder::new()
+    }
+
+    /// Checks whether the parameters are usable by the algorithm
+    ///
+    /// The population has to hold at least two agents (so that parents can be paired), there has to be
+    /// at least one generation, both probabilities have to be between 0.0 and 1.0 and
+    /// there can't be more elites than agents (and the selection strategy has to be valid)
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.population < 2 {
+            return Err(ConfigError::PopulationTooSmall(self.population));
+        }
+        if self.max_generation == 0 {
+            return Err(ConfigError::ZeroGenerations);
+        }
+        if self.max_evaluations == Some(0) {
+            return Err(ConfigError::ZeroEvaluations);
+        }
+        if !(0.0..=1.0).contains(&self.pc) {
+            return Err(ConfigError::InvalidCrossoverProbability(self.pc));
+        }
+        if !(0.0..=1.0).contains(&self.pm) {
+            return Err(ConfigError::InvalidMutationProbability(self.pm));
+        }
+        if self.elitism > self.population {
+            return Err(ConfigError::TooManyElites(self.elitism));
+        }
+        if self.initial_population.len() > self.population {
+            return Err(ConfigError::TooManyInitialAgents(self.initial_population.len()));
+        }
+        if let Some(opposition) = &self.opposition {
+            opposition.validate()?;
+        }
+        if let Some(local_search) = &self.local_search {
+            local_search.validate()?;
+        }
+        if !(0.0..1.0).contains(&self.random_immigrants) {
+            return Err(ConfigError::InvalidImmigrants(self.random_immigrants));
+        }
+        self.selection.validate()?;
+        if let Some(parent_selection) = &self.parent_selection {
+            if *parent_selection == Selection::Truncation {
+                return Err(ConfigError::InvalidSelection("truncation can't be used for parent selection".to_string()));
+            }
+            parent_selection.validate()?;
+        }
+        if let Some(steady_state) = &self.steady_state {
+            steady_state.validate(self.population)?;
+        }
+        if let Some(evolution_strategy) = &self.evolution_strategy {
+            if self.steady_state.is_some() {
+                return Err(ConfigError::InvalidEvolutionStrategy("it can't be used together with steady_state".to_string()));
+            }
+            evolution_strategy.validate(self.population)?;
+        }
+        if let Some(cellular) = &self.cellular {
+            if self.steady_state.is_some() || self.evolution_strategy.is_some() {
+                return Err(ConfigError::InvalidCellular("it can't be used together with steady_state or evolution_strategy".to_string()));
+            }
+            cellular.validate(self.population)?;
+        }
+        if let Some(pm_adaptation) = &self.pm_adaptation {
+            if self.diversity_adaptation.is_some() {
+                return Err(ConfigError::InvalidAdaptation("pm_adaptation and diversity_adaptation can't be used together".to_string()));
+            }
+            pm_adaptation.validate()?;
+        }
+        if let Some(diversity_adaptation) = &self.diversity_adaptation {
+            diversity_adaptation.validate()?;
+        }
+        if let Some(hypermutation) = &self.hypermutation {
+            hypermutation.validate()?;
+        }
+        if let Some(dynamic_environment) = &self.dynamic_environment {
+            dynamic_environment.validate(self.hypermutation.is_some())?;
+        }
+        let adapted = self.pm_adaptation.is_some() || self.diversity_adaptation.is_some();
+        if adapted && (self.pc_schedule.is_some() || self.pm_schedule.is_some()) {
+            return Err(ConfigError::InvalidSchedule("schedules can't be used together with adaptation".to_string()));
+        }
+        for schedule in self.pc_schedule.iter().chain(self.pm_schedule.iter()) {
+            schedule.validate()?;
+        }
+        if let Some(stagnation) = &self.stagnation {
+            stagnation.validate()?;
+        }
+        if let Some(restart) = &self.restart {
+            restart.validate(self.population)?;
+        }
+        if let Some(stop_condition) = &self.stop_condition {
+            stop_condition.validate()?;
+        }
+        if let Some(sharing) = &self.sharing {
+            if self.f_distance.is_none() {
+                return Err(ConfigError::MissingFunction("f_distance"));
+            }
+            sharing.validate()?;
+        }
+        if let Some(clearing) = &self.clearing {
+            if self.sharing.is_some() {
+                return Err(ConfigError::InvalidClearing("it can't be used together with sharing".to_string()));
+            }
+            if self.f_distance.is_none() {
+                return Err(ConfigError::MissingFunction("f_distance"));
+            }
+            clearing.validate()?;
+        }
+        if let Some(speciation) = &self.speciation {
+            if self.steady_state.is_some() || self.evolution_strategy.is_some() || self.cellular.is_some() {
+                return Err(ConfigError::InvalidSpeciation("it can't be used together with steady_state, evolution_strategy or cellular".to_string()));
+            }
+            if self.f_distance.is_none() {
+                return Err(ConfigError::MissingFunction("f_distance"));
+            }
+            speciation.validate()?;
+        }
+        if let Some(novelty) = &self.novelty {
+            if self.steady_state.is_some() || self.cellular.is_some() || self.speciation.is_some() {
+                return Err(ConfigError::InvalidNovelty("it can't be used together with steady_state, cellular or speciation".to_string()));
+            }
+            novelty.validate()?;
+        }
+        if let Some(surrogate) = &self.surrogate {
+            if self.steady_state.is_some() || self.cellular.is_some() || self.speciation.is_some() {
+                return Err(ConfigError::InvalidSurrogate("it can't be used together with steady_state, cellular or speciation".to_string()));
+            }
+            surrogate.validate()?;
+        }
+        if let Some(archive) = &self.archive {
+            archive.validate()?;
+        }
+        if self.constraint_handling != ConstraintHandling::Feasibility && self.f_constraints.is_none() {
+            return Err(ConfigError::MissingFunction("f_constraints"));
+        }
+        self.constraint_handling.validate()?;
+        #[cfg(feature = "serde")]
+        {
+            if let Some(checkpoint) = &self.checkpoint {
+                checkpoint.validate()?;
+            }
+        }
+        #[cfg(feature = "rayon")]
+        {
+            if self.chunk_size == 0 {
+                return Err(ConfigError::InvalidParallelism("chunk_size must be at least 1".to_string()));
+            }
+        }
+        #[cfg(feature = "async")]
+        {
+            if self.concurrency == 0 {
+                return Err(ConfigError::InvalidParallelism("concurrency must be at least 1".to_string()));
+            }
+        }
+        if let Some(operator_selection) = &self.operator_selection {
+            operator_selection.validate(self.mutation_operators.len().max(self.crossover_operators.len()).max(1))?;
+        }
+        let uses_cases = self.selection.uses_cases() || self.parent_selection.as_ref().is_some_and(Selection::uses_cases);
+        if uses_cases && self.f_case_fitness.is_none() {
+            return Err(ConfigError::MissingFunction("f_case_fitness"));
+        }
 
+        Ok(())
+    }
 }
 
-impl<'a, T> Genetic<'a, T>
-    where T: Clone {
-    fn get_population(&self) -> Vec<T> {
-        vec![0; self.population]
+impl<'a, T, F> Genetic<'a, T, F>
+    where T: Clone + MaybeSync, F: Fitness + MaybeSync {
+    /// Returns the agents of the 0th generation: the initial_population and random agents
+    fn get_population(&self, rng: &mut dyn RngCore) -> Vec<T> {
+        let random = self.population.saturating_sub(self.initial_population.len());
+        self.initial_population
             .iter()
-            .map(|&_| (self.get_random_agent)())
+            .take(self.population)
+            .cloned()
+            .chain((0..random).map(|_| (self.get_random_agent)(rng)))
             .collect::<Vec<T>>()
     }
 
-    fn generate_parents(&self, xg: &'a Vec<T>) -> Vec<(&T, &T)> {
+    #[allow(clippy::type_complexity)]
+    fn generate_parents<'b>(&self, xg: &'b [Evaluated<T, F>], penalties: &[f64], evaluations: &mut usize, rng: &mut dyn RngCore) -> Vec<(&'b Evaluated<T, F>, &'b Evaluated<T, F>)> {
+        if let Some(parent_selection) = &self.parent_selection {
+            let cases = self.case_fitness(xg, parent_selection, evaluations);
+            let chosen = self.select_from(parent_selection, xg, cases.as_deref(), penalties, 2 * xg.len(), rng);
+
+            return chosen.chunks(2)
+                .map(|p| (&xg[p[0]], &xg[p[1]]))
+                .collect();
+        }
+
         let l = xg.len();
-        let mut rng = thread_rng();
 
         let p = xg.iter()
             .map(|_| {
@@ -47,12 +706,12 @@ impl<'a, T> Genetic<'a, T>
                 }
                 &xg[y]
             })
-        .collect::<Vec<&T>>();
+        .collect::<Vec<&Evaluated<T, F>>>();
 
         xg.iter()
             .zip(p.iter())
             .map(|(a,b)| (a, *b))
-            .collect::<Vec<(&T, &T)>>()
+            .collect()
     }
 
     /// Returns the index of the best agent from a vector of agents
@@ -61,76 +720,418 @@ impl<'a, T> Genetic<'a, T>
     ///
     /// * `u` a vector of agents
     /// 
+    /// # Panics:
+    ///  If the fitness function fails and the fitness_error_policy gives up on the agent
+    ///
     /// # Examples:
     ///  See at run()
+    pub fn get_best(&self, u: &[T]) -> usize {
+        self.find_best(u, &mut 0)
+            .expect("the fitness function failed")
+            .0
+    }
+
+    /// Returns the hits and misses of the fitness_cache (None if there is no cache)
+    pub fn cache_stats(&self) -> Option<CacheStats> {
+        self.fitness_cache.as_ref().map(|cache| cache.stats())
+    }
+
+    /// Evaluates an agent, fails with EvaluationBudget if max_evaluations is used up
+    fn evaluate(&self, x: &T, evaluations: &mut usize) -> Result<F, FitnessError> {
+        if let Some(f_x) = self.fitness_cache.as_ref().and_then(|cache| cache.get(x)) {
+            return Ok(f_x);
+        }
+        if self.max_evaluations.is_some_and(|max_evaluations| *evaluations >= max_evaluations) {
+            return Err(Box::new(EvaluationBudget));
+        }
+        self.call_fitness(x, evaluations)
+    }
+
+    fn evaluate_unlimited(&self, x: &T, evaluations: &mut usize) -> Result<F, FitnessError> {
+        if let Some(f_x) = self.fitness_cache.as_ref().and_then(|cache| cache.get(x)) {
+            return Ok(f_x);
+        }
+        self.call_fitness(x, evaluations)
+    }
+
+    /// Calls the fitness function (retrying according to the fitness_error_policy), successful results are cached
+    fn call_fitness(&self, x: &T, evaluations: &mut usize) -> Result<F, FitnessError> {
+        let mut retries = 0;
+
+        loop {
+            *evaluations += 1;
+            let error = match (self.f_fitness)(x) {
+                Ok(f_x) => {
+                    if let Some(cache) = &self.fitness_cache {
+                        cache.insert(x, f_x.clone());
+                    }
+                    return Ok(f_x);
+                }
+                Err(error) => error,
+            };
+            match self.fitness_error_policy {
+                FitnessErrorPolicy::AssignWorst => return Ok(self.objective.worst()),
+                FitnessErrorPolicy::Retry(max_retries) if retries < max_retries => retries += 1,
+                _ => return Err(error),
+            }
+        }
+    }
 
-    pub fn get_best(&self, u: &Vec<T>) -> usize {
+    fn find_best(&self, u: &[T], evaluations: &mut usize) -> Result<(usize, F), FitnessError> {
         let mut best_i = 0;
-        let mut f_best = (self.f_fitness)(&u[0]);
+        let mut f_best = self.evaluate_unlimited(&u[0], evaluations)?;
 
-        for (i, x) in u.iter().enumerate() {
-            let f_x = (self.f_fitness)(&x);
-            if f_x > f_best {
+        for (i, x) in u.iter().enumerate().skip(1) {
+            let f_x = self.evaluate_unlimited(x, evaluations)?;
+            if self.objective.is_better(&f_x, &f_best) {
                 best_i = i;
                 f_best = f_x;
             }
         }
 
-        best_i
+        Ok((best_i, f_best))
     }
 
-    fn mutate(&self, xg: &Vec<T>) -> Vec<T> {
-        let mut rng = thread_rng();
+    /// Returns the probabilities and operator choosers used by a run at its beginning
+    fn rates(&self) -> Rates {
+        let names = |operators: Vec<&String>, default: &str| {
+            if operators.is_empty() {
+                return vec![default.to_string()];
+            }
+            operators.into_iter().cloned().collect()
+        };
+        let mutations = names(self.mutation_operators.iter().map(|(name, _)| name).collect(), "f_mutate");
+        let crossovers = names(self.crossover_operators.iter().map(|(name, _)| name).collect(), "f_offspring");
 
-        xg.iter()
-            .map(|x| {
-                if rng.gen_range(0.0, 1.0) >= self.pm {
-                    return x.clone();
+        Rates::new(self.pc, self.pm, Bandit::new(mutations, self.operator_selection), Bandit::new(crossovers, self.operator_selection))
+    }
+
+    /// Mutates every agent with probability rates.mutation_probability() (and counts the successful mutations if pm is adapted
+    /// or the operators are learned), the fitness of the agents is kept if it's known
+    fn mutate(&self, xg: Vec<Offspring<T, F>>, rates: &mut Rates, evaluations: &mut usize, rng: &mut dyn RngCore) -> Result<Vec<Offspring<T, F>>, FitnessError> {
+        #[cfg(feature = "rayon")]
+        {
+            if self.pm_adaptation.is_none() && !rates.mutation_bandit.learns() {
+                return Ok(self.par_mutate(xg, rates, rng));
+            }
+        }
+        let mut mutated = Vec::with_capacity(xg.len());
+        for (x, f_x) in xg {
+            if rng.gen_range(0.0, 1.0) >= rates.mutation_probability() {
+                mutated.push((x, f_x));
+                continue;
+            }
+            let operator = rates.mutation_bandit.choose(rng);
+            let y = self.apply_mutation(operator, &x, rng);
+            let mut f_y = None;
+            let mut success = None;
+            if self.pm_adaptation.is_some() || rates.mutation_bandit.learns() {
+                let f_new = self.evaluate(&y, evaluations)?;
+                let f_old = match f_x {
+                    Some(f_x) => f_x,
+                    None => self.evaluate(&x, evaluations)?,
+                };
+                success = Some(self.objective.is_better(&f_new, &f_old));
+                f_y = Some(f_new);
+            }
+            if self.pm_adaptation.is_some() {
+                rates.mutations += 1;
+                if success == Some(true) {
+                    rates.successes += 1;
                 }
-                (self.f_mutate)(x)
-            })
-        .collect::<Vec<T>>()
+            }
+            rates.mutation_bandit.record(operator, success);
+            mutated.push((y, f_y));
+        }
+
+        Ok(mutated)
     }
 
-    fn selection(&self, xg: &mut Vec<T>) -> Vec<T> {
-        let mut new_generation = Vec::new();
+    /// Creates the offspring of two agents with one of the crossover operators
+    /// (and measures whether they are better than the parents if the operators are learned)
+    fn cross(&self, a: &Evaluated<T, F>, b: &Evaluated<T, F>, rates: &mut Rates, evaluations: &mut usize, rng: &mut dyn RngCore) -> Result<Vec<Offspring<T, F>>, FitnessError> {
+        let operator = rates.crossover_bandit.choose(rng);
+        let children = self.apply_crossover(operator, &a.agent, &b.agent, rng);
 
-        for _i in 0..self.population {
-            let best_i = self.get_best(xg);
-            new_generation.push(xg.remove(best_i));
+        if !rates.crossover_bandit.learns() {
+            rates.crossover_bandit.record(operator, None);
+            return Ok(children.into_iter().map(|child| (child, None)).collect());
         }
+        let f_parent = if self.objective.is_better(&b.fitness, &a.fitness) { &b.fitness } else { &a.fitness };
+        let mut improved = false;
+        let mut evaluated = Vec::with_capacity(children.len());
+        for child in children {
+            let f_child = self.evaluate(&child, evaluations)?;
+            improved |= self.objective.is_better(&f_child, f_parent);
+            evaluated.push((child, Some(f_child)));
+        }
+        rates.crossover_bandit.record(operator, Some(improved));
 
-        new_generation
+        Ok(evaluated)
+    }
+
+    /// Applies the mutation operator with the given index (f_mutate if there are no registered operators),
+    /// the mutant is repaired
+    fn apply_mutation(&self, operator: usize, x: &T, rng: &mut dyn RngCore) -> T {
+        let y = match self.mutation_operators.get(operator) {
+            Some((_, f_mutate)) => f_mutate(x, rng),
+            None => (self.f_mutate)(x, rng),
+        };
+        self.repair(y)
+    }
+
+    /// Applies the crossover operator with the given index (f_offspring if there are no registered operators),
+    /// the children are repaired
+    fn apply_crossover(&self, operator: usize, a: &T, b: &T, rng: &mut dyn RngCore) -> Vec<T> {
+        let children = match self.crossover_operators.get(operator) {
+            Some((_, f_offspring)) => f_offspring(a, b, rng),
+            None => (self.f_offspring)(a, b, rng),
+        };
+        children.into_iter().map(|child| self.repair(child)).collect()
+    }
+
+    /// Returns the agent fixed by f_repair (or the agent itself if there's no repair function)
+    fn repair(&self, x: T) -> T {
+        match &self.f_repair {
+            Some(f_repair) => f_repair(&x),
+            None => x,
+        }
+    }
+
+    /// Crosses every parent pair with probability rates.pc
+    #[allow(clippy::type_complexity)]
+    fn cross_all(&self, parents: &[(&Evaluated<T, F>, &Evaluated<T, F>)], rates: &mut Rates, evaluations: &mut usize, rng: &mut dyn RngCore) -> Result<Vec<Offspring<T, F>>, FitnessError> {
+        #[cfg(feature = "rayon")]
+        {
+            if !rates.crossover_bandit.learns() {
+                return Ok(self.par_cross(parents, rates, rng));
+            }
+        }
+        let mut children = Vec::new();
+        for (a, b) in parents {
+            if rng.gen_range(0.0, 1.0) < rates.pc {
+                children.extend(self.cross(a, b, rates, evaluations, rng)?);
+            }
+        }
+
+        Ok(children)
+    }
+
+    /// Evaluates the offspring whose fitness isn't known yet (in parallel with the rayon feature, if they fit
+    /// into max_evaluations), fails with EvaluationBudget if they don't fit
+    fn evaluate_offspring(&self, offspring: Vec<Offspring<T, F>>, evaluations: &mut usize) -> Result<Vec<Evaluated<T, F>>, FitnessError> {
+        match self.evaluate_in_budget(offspring, evaluations)? {
+            (evaluated, false) => Ok(evaluated),
+            (_, true) => Err(Box::new(EvaluationBudget)),
+        }
+    }
+
+    /// Evaluates the offspring whose fitness isn't known yet (in parallel with the rayon feature, if they fit
+    /// into max_evaluations), returns the ones that were evaluated before max_evaluations was used up and whether
+    /// it was used up before every offspring was evaluated
+    fn evaluate_in_budget(&self, offspring: Vec<Offspring<T, F>>, evaluations: &mut usize) -> Result<(Vec<Evaluated<T, F>>, bool), FitnessError> {
+        let unknown = offspring.iter().filter(|(_, f_x)| f_x.is_none()).count();
+        if self.max_evaluations.is_none_or(|max_evaluations| *evaluations + unknown <= max_evaluations) {
+            return Ok((self.evaluate_unlimited_all(offspring, evaluations)?, false));
+        }
+        let mut evaluated = Vec::with_capacity(offspring.len());
+        for (agent, fitness) in offspring {
+            let fitness = match fitness {
+                Some(f_x) => f_x,
+                None => match self.evaluate(&agent, evaluations) {
+                    Ok(f_x) => f_x,
+                    Err(error) if error.is::<EvaluationBudget>() => return Ok((evaluated, true)),
+                    Err(error) => return Err(error),
+                },
+            };
+            evaluated.push(Evaluated { agent, fitness });
+        }
+
+        Ok((evaluated, false))
+    }
+
+    /// Calls on_generation with the state of the generation, returns true if it asks to stop
+    fn notify(&self, generation: usize, xg: &[Evaluated<T, F>], evaluations: usize) -> bool {
+        if xg.is_empty() {
+            return false;
+        }
+        if let Some(on_progress) = &self.on_progress {
+            on_progress(generation + 1, self.max_generation, &xg[self.best_index(xg)].fitness);
+        }
+        match &self.on_generation {
+            Some(on_generation) => on_generation(&GenerationInfo::new(generation, xg, evaluations, self.objective)).is_break(),
+            None => false,
+        }
+    }
+
+    /// Returns the number of generations the loop of a run can execute
+    fn generation_bound(&self) -> usize {
+        if self.stop_condition.is_some() {
+            return usize::MAX;
+        }
+        self.max_generation
+    }
+
+    /// Sets the probabilities of the generation from the schedules
+    fn apply_schedules(&self, rates: &mut Rates, generation: usize) {
+        if let Some(pc_schedule) = &self.pc_schedule {
+            rates.pc = pc_schedule.value(generation, self.max_generation);
+        }
+        if let Some(pm_schedule) = &self.pm_schedule {
+            rates.pm = pm_schedule.value(generation, self.max_generation);
+        }
+    }
+
+    /// Adapts the probabilities at the end of a generation
+    fn adapt(&self, rates: &mut Rates, xg: &[Evaluated<T, F>]) {
+        if let Some(pm_adaptation) = &self.pm_adaptation {
+            rates.pm = pm_adaptation.adapt(rates.pm, rates.successes, rates.mutations);
+        }
+        if let Some(diversity_adaptation) = &self.diversity_adaptation {
+            let diversity = self.diversity(xg);
+            let initial = *rates.initial_diversity.get_or_insert(diversity);
+            let (pc, pm) = diversity_adaptation.adapt(diversity, initial);
+            rates.pc = pc;
+            rates.pm = pm;
+        }
+        rates.mutations = 0;
+        rates.successes = 0;
+    }
+
+    /// Starts a burst of hypermutation if it's triggered (`changed`: the dynamic environment changed and asks for
+    /// one), or lets the current one decay (at the end of a generation)
+    fn hypermutate(&self, rates: &mut Rates, history: &[GenerationStats], generation: usize, changed: bool) {
+        let hypermutation = match &self.hypermutation {
+            Some(hypermutation) => hypermutation,
+            None => return,
+        };
+        rates.burst = rates.burst.saturating_sub(1);
+        let changed = changed || hypermutation.change.as_ref().is_some_and(LandscapeChange::take);
+        let stagnant = rates.burst == 0 && hypermutation.stagnation.is_some_and(|stagnation| stagnation.is_reached(&history[rates.burst_start..], self.objective));
+        if changed || stagnant {
+            rates.burst = hypermutation.generations;
+            rates.burst_start = generation;
+        }
+        rates.pm_multiplier = hypermutation.multiplier(rates.burst);
+    }
+
+    /// Returns the diversity of the agents: their average distance (every agent is compared to the one half a
+    /// population away) if f_distance is given, the standard deviation of their fitness otherwise
+    pub fn diversity(&self, xg: &[Evaluated<T, F>]) -> f64 {
+        if xg.len() < 2 {
+            return 0.0;
+        }
+        if let Some(f_distance) = &self.f_distance {
+            let half = xg.len() / 2;
+            return (0..xg.len())
+                .map(|i| f_distance(&xg[i].agent, &xg[(i + half) % xg.len()].agent))
+                .sum::<f64>() / xg.len() as f64;
+        }
+
+        let values = xg.iter()
+            .map(|x| x.fitness.to_f64())
+            .filter(|f_x| f_x.is_finite())
+            .collect::<Vec<f64>>();
+        if values.is_empty() {
+            return 0.0;
+        }
+        let mean = values.iter().sum::<f64>() / values.len() as f64;
+        (values.iter().map(|f_x| (f_x - mean) * (f_x - mean)).sum::<f64>() / values.len() as f64).sqrt()
+    }
+
+    fn get_elites(&self, xg: &[Evaluated<T, F>]) -> Vec<Evaluated<T, F>> {
+        if self.elitism == 0 {
+            return Vec::new();
+        }
+
+        self.best_first(xg)
+            .into_iter()
+            .take(self.elitism)
+            .map(|i| xg[i].clone())
+            .collect()
+    }
+
+    /// Returns the per-case fitness of every agent if the strategy needs it
+    fn case_fitness(&self, xg: &[Evaluated<T, F>], strategy: &Selection, evaluations: &mut usize) -> Option<Vec<Vec<f64>>> {
+        match (&self.f_case_fitness, strategy.uses_cases()) {
+            (Some(f_case_fitness), true) => {
+                *evaluations += xg.len();
+                Some(xg.iter().map(|x| f_case_fitness(&x.agent)).collect())
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the fitness adjusted by f_length_penalty (or the fitness itself if there's no penalty)
+    fn penalized(&self, xg: &[Evaluated<T, F>]) -> Vec<F> {
+        match &self.f_length_penalty {
+            Some(f_length_penalty) => xg.iter()
+                .map(|x| f_length_penalty(&x.agent, x.fitness.clone()))
+                .collect(),
+            None => xg.iter().map(|x| x.fitness.clone()).collect(),
+        }
+    }
+
+    /// Returns the indices chosen by the selection strategy, using the penalized fitness, shared or cleared between
+    /// the close agents if sharing or clearing is given (and the constraint handling if there are constraints)
+    fn select_from(&self, selection: &Selection, xg: &[Evaluated<T, F>], cases: Option<&[Vec<f64>]>, penalties: &[f64], count: usize, rng: &mut dyn RngCore) -> Vec<usize> {
+        let mut fitness = self.penalized(xg);
+        let distance = |i: usize, j: usize| self.f_distance.as_ref().map_or(0.0, |f_distance| f_distance(&xg[i].agent, &xg[j].agent));
+        if let Some(sharing) = &self.sharing {
+            let shared = sharing.share(&fitness, distance, self.objective);
+            return self.select_feasible(selection, xg, shared, penalties, cases, count, rng);
+        }
+        if let Some(clearing) = &self.clearing {
+            clearing.clear(&mut fitness, distance, self.objective);
+        }
+
+        self.select_feasible(selection, xg, fitness, penalties, cases, count, rng)
+    }
+
+    /// Returns the chosen agents (the best one first), they are chosen by the scores if they are given (novelty search)
+    fn selection(&self, xg: &[Evaluated<T, F>], count: usize, scores: Option<&[f64]>, penalties: &[f64], evaluations: &mut usize, rng: &mut dyn RngCore) -> Vec<Evaluated<T, F>> {
+        let cases = self.case_fitness(xg, &self.selection, evaluations);
+
+        let mut chosen = match scores {
+            Some(scores) => self.selection.select(scores, cases.as_deref(), count, Objective::Maximize, rng),
+            None => self.select_from(&self.selection, xg, cases.as_deref(), penalties, count, rng),
+        };
+        // the best agent is put first
+        let violations = self.violations(xg);
+        chosen.sort_by(|a, b| self.compare_feasible(&violations, xg, *a, *b));
+
+        chosen.into_iter().map(|i| xg[i].clone()).collect()
     }
 
     /// Returns agents from the given generation.
     ///
-    /// # Arguments:
+    /// # Panics:
+    ///  If the fitness function fails and the fitness_error_policy gives up on the agent
+    ///  (use try_run() to get the error instead)
     ///
-    /// * `u` a vector of agents
-    /// 
     /// # Examples:
     /// ```
     /// use rand::prelude::*;
     /// use bbte_optim_tzim1773_genetic::Genetic;
     ///
     /// fn main() {
-    ///    let agent = || 123;
+    ///    let agent = |_: &mut dyn RngCore| 123;
     ///    let fit = |_a: &usize| 1.0;
-    ///    let muta = |a: &usize| *a + 1;
-    ///    let off = |a: &usize, b: &usize| (*a + *b) / 2;
-    ///    let test: Genetic<usize> = Genetic {
-    ///        population: 10,
-    ///        max_generation: 1,
-    ///        pc: 0.5,
-    ///        pm: 1.0,
-    ///        get_random_agent: &agent,
-    ///        f_fitness: &fit,
-    ///        f_mutate: &muta,
-    ///        f_offspring: &off,
-    ///    };
+    ///    let muta = |a: &usize, _: &mut dyn RngCore| *a + 1;
+    ///    let off = |a: &usize, b: &usize, _: &mut dyn RngCore| (*a + *b) / 2;
+    ///    let test: Genetic<usize> = Genetic::builder()
+    ///        .population(10)
+    ///        .max_generation(1)
+    ///        .pc(0.5)
+    ///        .pm(1.0)
+    ///        .random_agent(agent)
+    ///        .fitness(fit)
+    ///        .mutate(muta)
+    ///        .offspring(off)
+    ///        .build()
+    ///        .unwrap();
     ///    
-    ///    let pop = test.run();
+    ///    let pop = test.run().population;
     ///    println!("{}", pop[0]); // since all agents are mutated (pm = 1.0)
     ///                             // all agents should hold the value 124
     /// }
@@ -142,146 +1143,1351 @@ impl<'a, T> Genetic<'a, T>
     /// use bbte_optim_tzim1773_genetic::Genetic;
     ///
     /// fn main() {
-    ///     let agent = || {
-    ///         let mut rng = thread_rng();
-    ///         rng.gen_range(-5.0, 5.0)
-    ///     };
+    ///     let agent = |rng: &mut dyn RngCore| rng.gen_range(-5.0, 5.0);
     ///     let fit = |a: &f64| 5.0 - a * a;
-    ///     let muta = |a: &f64| {
-    ///         let mut rng = thread_rng();
-    ///         *a + rng.gen_range(-0.01, 0.01)
-    ///     };
-    ///     let off = |a: &f64, b: &f64| (*a + *b) / 2.0;
-    ///     let test: Genetic<f64> = Genetic {
-    ///         population: 100,
-    ///         max_generation: 20,
-    ///         pc: 0.5,
-    ///         pm: 0.4,
-    ///         get_random_agent: &agent,
-    ///         f_fitness: &fit,
-    ///         f_mutate: &muta,
-    ///         f_offspring: &off,
-    ///     };
-    ///
-    ///     let simul = test.run();
-    ///     let best = test.get_best(&simul);
-    ///
-    ///     println!("{}", simul[best]); // should be a number close to 0
+    ///     let muta = |a: &f64, rng: &mut dyn RngCore| *a + rng.gen_range(-0.01, 0.01);
+    ///     let off = |a: &f64, b: &f64, _: &mut dyn RngCore| (*a + *b) / 2.0;
+    ///     let test: Genetic<f64> = Genetic::builder()
+    ///         .population(100)
+    ///         .max_generation(20)
+    ///         .pc(0.5)
+    ///         .pm(0.4)
+    ///         .random_agent(agent)
+    ///         .fitness(fit)
+    ///         .mutate(muta)
+    ///         .offspring(off)
+    ///         .build()
+    ///         .unwrap();
+    ///
+    ///     let result = test.run();
+    ///
+    ///     println!("{} {}", result.best, result.best_fitness); // should be a number close to 0 and 5
     /// }
     /// ```
-    pub fn run(&self) -> Vec<T> {
-        let mut xg:Vec<T> = self.get_population(); 
-        let mut rng = thread_rng();
-
-        for _g in 0..self.max_generation {
-            let parents = &self.generate_parents(&xg);
-            let mut population = xg.clone();
-            for (a, b) in parents {
-                if rng.gen_range(0.0, 1.0) < self.pc {
-                    population.push((self.f_offspring)(&a, &b));
-                }
-            }
-            let mut mutated = self.mutate(&population);
+    pub fn run(&self) -> RunResult<T, F> {
+        self.run_with_rng(&mut thread_rng())
+    }
 
-            xg = self.selection(&mut mutated);
-        }
+    /// Same as run(), but returns the error of the fitness function instead of panicking
+    /// when the fitness_error_policy gives up on an agent
+    ///
+    /// # Examples:
+    /// ```
+    /// use rand::prelude::*;
+    /// use bbte_optim_tzim1773_genetic::{FitnessErrorPolicy, Genetic};
+    ///
+    /// fn main() {
+    ///     let test = Genetic::builder()
+    ///         .random_agent(|rng| rng.gen_range(-5.0, 5.0))
+    ///         .try_fitness(|a: &f64| {
+    ///             if a.abs() > 4.0 {
+    ///                 return Err("the simulator only works between -4 and 4");
+    ///             }
+    ///             Ok(5.0 - a * a)
+    ///         })
+    ///         .mutate(|a: &f64, rng| *a + rng.gen_range(-0.01, 0.01))
+    ///         .offspring(|a: &f64, b: &f64, _rng| (*a + *b) / 2.0)
+    ///         .max_generation(20)
+    ///         .fitness_error_policy(FitnessErrorPolicy::AssignWorst)
+    ///         .build()
+    ///         .unwrap();
+    ///
+    ///     match test.try_run() {
+    ///         Ok(result) => println!("{}", result.best), // should be a number close to 0
+    ///         Err(error) => println!("{}", error),
+    ///     }
+    /// }
+    /// ```
+    pub fn try_run(&self) -> Result<RunResult<T, F>, RunError> {
+        self.try_run_with_rng(&mut thread_rng())
+    }
 
-        xg
+    /// Same as run(), but every random decision (initial population, parent pairing,
+    /// crossover and mutation) is made with the given random number generator,
+    /// so seeded generators give reproducible runs
+    ///
+    /// # Arguments:
+    ///
+    /// * `rng` the random number generator used throughout the run
+    ///
+    /// # Panics:
+    ///  Same as run()
+    ///
+    /// # Examples:
+    /// ```
+    /// use rand::prelude::*;
+    /// use bbte_optim_tzim1773_genetic::Genetic;
+    ///
+    /// fn main() {
+    ///     let test = Genetic::builder()
+    ///         .random_agent(|rng| rng.gen_range(-5.0, 5.0))
+    ///         .fitness(|a: &f64| 5.0 - a * a)
+    ///         .mutate(|a: &f64, rng| *a + rng.gen_range(-0.01, 0.01))
+    ///         .offspring(|a: &f64, b: &f64, _rng| (*a + *b) / 2.0)
+    ///         .max_generation(20)
+    ///         .build()
+    ///         .unwrap();
+    ///
+    ///     let first = test.run_with_rng(&mut StdRng::seed_from_u64(42));
+    ///     let second = test.run_with_rng(&mut StdRng::seed_from_u64(42));
+    ///
+    ///     assert_eq!(first, second);
+    /// }
+    /// ```
+    pub fn run_with_rng<R: Rng>(&self, rng: &mut R) -> RunResult<T, F> {
+        match self.try_run_with_rng(rng) {
+            Ok(result) => result,
+            Err(error) => panic!("{}", error),
+        }
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// Same as run_with_rng(), but returns the error of the fitness function instead of panicking
+    ///
+    /// # Arguments:
+    ///
+    /// * `rng` the random number generator used throughout the run
+    pub fn try_run_with_rng<R: Rng>(&self, rng: &mut R) -> Result<RunResult<T, F>, RunError> {
+        let mut state = self.start_with_rng(rng)?;
+        while self.step(&mut state)?.is_none() {}
 
-    #[test]
-    fn correct_agent() {
-        let agent = || 123;
-        let fit = |_a: &usize| 1.0;
-        let muta = |a: &usize| *a + 1;
-        let off = |a: &usize, b: &usize| (*a + *b) / 2;
-        let test: Genetic<usize> = Genetic {
-            population: 10,
-            max_generation: 10,
-            pc: 0.5,
-            pm: 0.5,
-            get_random_agent: &agent,
-            f_fitness: &fit,
-            f_mutate: &muta,
-            f_offspring: &off,
-        };
-        
-        let pop = test.get_population();
-        assert_eq!(pop[0], 123);
+        Ok(self.finish(state))
     }
-    
-    #[test]
-    fn correct_mutation() {
-        let agent = || 123;
-        let fit = |_a: &usize| 1.0;
-        let muta = |a: &usize| *a + 1;
-        let off = |a: &usize, b: &usize| (*a + *b) / 2;
-        let test: Genetic<usize> = Genetic {
-            population: 10,
-            max_generation: 1,
-            pc: 0.5,
-            pm: 1.0,
-            get_random_agent: &agent,
-            f_fitness: &fit,
-            f_mutate: &muta,
-            f_offspring: &off,
+
+    /// Creates the agents the next generation is selected from, only the new ones are unevaluated
+    fn variation(&self, xg: &[Evaluated<T, F>], rates: &mut Rates, evaluations: &mut usize, rng: &mut dyn RngCore) -> Result<Vec<Offspring<T, F>>, FitnessError> {
+        let offspring = match &self.evolution_strategy {
+            Some(evolution_strategy) => self.es_offspring(evolution_strategy, xg, rates, evaluations, rng)?,
+            None => {
+                let parents = self.generate_parents(xg, &rates.penalties, evaluations, rng);
+                let mut population = xg.iter()
+                    .map(|x| (x.agent.clone(), Some(x.fitness.clone())))
+                    .collect::<Vec<Offspring<T, F>>>();
+                population.extend(phase!("crossover", self.cross_all(&parents, rates, evaluations, rng))?);
+                phase!("mutation", self.mutate(population, rates, evaluations, rng))?
+            }
         };
-        
-        let pop = test.run();
-        assert_eq!(pop[0], 124);    
+
+        Ok(offspring)
+    }
+
+    /// Selects the next generation from the evaluated candidates (by their scores if they are given) and the elites
+    /// of xg (the best agent first)
+    #[allow(clippy::too_many_arguments)]
+    fn survivors(&self, xg: &[Evaluated<T, F>], candidates: Vec<Evaluated<T, F>>, scores: Option<&[f64]>, penalties: &[f64], evaluations: &mut usize, rng: &mut dyn RngCore) -> Vec<Evaluated<T, F>> {
+        let elites = self.get_elites(xg);
+        let count = self.population - elites.len();
+        let mut new_generation = self.selection(&candidates, count, scores, penalties, evaluations, rng);
+
+        // the elites are added unchanged, keeping the best agent first
+        if let Some(elite) = elites.first() {
+            let elites_first = new_generation.first().is_none_or(|x| !self.objective.is_better(&x.fitness, &elite.fitness));
+            if elites_first {
+                new_generation.splice(0..0, elites);
+            } else {
+                new_generation.extend(elites);
+            }
+        }
+
+        new_generation
+    }
+
+    /// Returns the index pairs of `count` parents chosen from the agents
+    fn choose_parents(&self, xg: &[Evaluated<T, F>], count: usize, penalties: &[f64], evaluations: &mut usize, rng: &mut dyn RngCore) -> Vec<(usize, usize)> {
+        let l = xg.len();
+
+        match &self.parent_selection {
+            Some(parent_selection) => {
+                let cases = self.case_fitness(xg, parent_selection, evaluations);
+                self.select_from(parent_selection, xg, cases.as_deref(), penalties, 2 * count, rng)
+                    .chunks(2)
+                    .map(|p| (p[0], p[1]))
+                    .collect()
+            }
+            None => (0..count)
+                .map(|_| {
+                    let x = rng.gen_range(0, l);
+                    let mut y = rng.gen_range(0, l);
+                    while y == x && l > 1 {
+                        y = rng.gen_range(0, l);
+                    }
+                    (x, y)
+                })
+                .collect(),
+        }
+    }
+
+    /// Creates offspring: the crossover of the parents with probability pc (otherwise a copy of `a`),
+    /// each of them mutated with probability pm
+    fn breed(&self, a: &Evaluated<T, F>, b: &Evaluated<T, F>, rates: &mut Rates, evaluations: &mut usize, rng: &mut dyn RngCore) -> Result<Vec<Offspring<T, F>>, FitnessError> {
+        let children = if rng.gen_range(0.0, 1.0) < rates.pc {
+            self.cross(a, b, rates, evaluations, rng)?
+        } else {
+            vec![(a.agent.clone(), Some(a.fitness.clone()))]
+        };
+        self.mutate(children, rates, evaluations, rng)
+    }
+
+    /// Creates `count` offspring from the given parent pairs
+    fn breed_all(&self, xg: &[Evaluated<T, F>], parents: &[(usize, usize)], count: usize, rates: &mut Rates, evaluations: &mut usize, rng: &mut dyn RngCore) -> Result<Vec<Offspring<T, F>>, FitnessError> {
+        let mut offspring = Vec::with_capacity(count);
+        for (a, b) in parents {
+            if offspring.len() >= count {
+                break;
+            }
+            offspring.extend(self.breed(&xg[*a], &xg[*b], rates, evaluations, rng)?);
+        }
+        offspring.truncate(count);
+
+        Ok(offspring)
+    }
+
+    /// Returns the agents the next generation is chosen from in the evolution strategy mode
+    fn es_offspring(&self, evolution_strategy: &EvolutionStrategy, xg: &[Evaluated<T, F>], rates: &mut Rates, evaluations: &mut usize, rng: &mut dyn RngCore) -> Result<Vec<Offspring<T, F>>, FitnessError> {
+        let parents = self.best_first(xg)
+            .into_iter()
+            .take(evolution_strategy.mu)
+            .map(|i| xg[i].clone())
+            .collect::<Vec<Evaluated<T, F>>>();
+
+        let pairs = self.choose_parents(&parents, evolution_strategy.lambda, &rates.penalties, evaluations, rng);
+        let offspring = self.breed_all(&parents, &pairs, evolution_strategy.lambda, rates, evaluations, rng)?;
+
+        if evolution_strategy.plus {
+            return Ok(parents.into_iter()
+                .map(|x| (x.agent, Some(x.fitness)))
+                .chain(offspring)
+                .collect());
+        }
+        Ok(offspring)
+    }
+
+    /// Returns the index of the best agent (the best feasible one if there are constraints)
+    fn best_index(&self, xg: &[Evaluated<T, F>]) -> usize {
+        let violations = self.violations(xg);
+        let mut best_i = 0;
+        for i in 1..xg.len() {
+            if self.compare_feasible(&violations, xg, i, best_i) == Ordering::Less {
+                best_i = i;
+            }
+        }
+        best_i
+    }
+
+    /// Creates the offspring of a steady-state generation
+    fn steady_offspring(&self, steady_state: &SteadyState, xg: &[Evaluated<T, F>], rates: &mut Rates, evaluations: &mut usize, rng: &mut dyn RngCore) -> Result<Vec<Offspring<T, F>>, FitnessError> {
+        let parents = self.choose_parents(xg, steady_state.offspring, &rates.penalties, evaluations, rng);
+        self.breed_all(xg, &parents, steady_state.offspring, rates, evaluations, rng)
+    }
+
+    /// Puts the evaluated offspring of a steady-state generation in place of the replaced agents
+    fn replace(&self, steady_state: &SteadyState, xg: &mut [Evaluated<T, F>], children: Vec<Evaluated<T, F>>, rng: &mut dyn RngCore) {
+        for child in children {
+            if let Some(victim) = steady_state.replacement.choose(&self.penalized(xg), self.elitism, self.objective, rng) {
+                xg[victim] = child;
+            }
+        }
+    }
+
+    /// Creates the offspring of a cellular generation: one for every cell, with a mate from its neighbours
+    fn cell_offspring(&self, cellular: &Cellular, xg: &[Evaluated<T, F>], rates: &mut Rates, evaluations: &mut usize, rng: &mut dyn RngCore) -> Result<Vec<Offspring<T, F>>, FitnessError> {
+        let fitness = self.penalized(xg);
+        let cases = self.parent_selection.as_ref().and_then(|parent_selection| self.case_fitness(xg, parent_selection, evaluations));
+
+        let mut offspring = Vec::with_capacity(xg.len());
+        for i in 0..xg.len() {
+            let neighbors = cellular.neighbors(i);
+            let mate = match &self.parent_selection {
+                Some(parent_selection) => {
+                    let neighbor_fitness = neighbors.iter().map(|j| fitness[*j].clone()).collect::<Vec<F>>();
+                    let neighbor_cases = cases.as_ref().map(|cases| neighbors.iter().map(|j| cases[*j].clone()).collect::<Vec<Vec<f64>>>());
+                    neighbors[parent_selection.select(&neighbor_fitness, neighbor_cases.as_deref(), 1, self.objective, rng)[0]]
+                }
+                // binary tournament
+                None => {
+                    let a = neighbors[rng.gen_range(0, neighbors.len())];
+                    let b = neighbors[rng.gen_range(0, neighbors.len())];
+                    if self.objective.is_better(&fitness[b], &fitness[a]) { b } else { a }
+                }
+            };
+            let child = self.breed(&xg[i], &xg[mate], rates, evaluations, rng)?.swap_remove(0);
+            offspring.push(child);
+        }
+
+        Ok(offspring)
+    }
+
+    /// Puts the evaluated offspring of a cellular generation in the place of their parents if they are not worse
+    fn replace_cells(&self, xg: &mut [Evaluated<T, F>], children: Vec<Evaluated<T, F>>) {
+        let fitness = self.penalized(xg);
+        let children_fitness = self.penalized(&children);
+
+        for ((x, child), (f_x, f_child)) in xg.iter_mut().zip(children).zip(fitness.iter().zip(children_fitness.iter())) {
+            if !self.objective.is_better(f_x, f_child) {
+                *x = child;
+            }
+        }
+    }
+
+    /// Same as run(), but the random number generator is seeded with the given seed
+    ///
+    /// # Arguments:
+    ///
+    /// * `seed` the seed of the random number generator, the same seed gives the same result
+    ///
+    /// # Panics:
+    ///  Same as run()
+    pub fn run_with_seed(&self, seed: u64) -> RunResult<T, F> {
+        self.run_with_rng(&mut StdRng::seed_from_u64(seed))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn correct_agent() {
+        let agent = |_: &mut dyn RngCore| 123;
+        let fit = |_a: &usize| 1.0;
+        let muta = |a: &usize, _: &mut dyn RngCore| *a + 1;
+        let off = |a: &usize, b: &usize, _: &mut dyn RngCore| (*a + *b) / 2;
+        let test: Genetic<usize> = Genetic::builder()
+            .population(10)
+            .max_generation(10)
+            .pc(0.5)
+            .pm(0.5)
+            .random_agent(agent)
+            .fitness(fit)
+            .mutate(muta)
+            .offspring(off)
+            .build()
+            .unwrap();
+        
+        let pop = test.get_population(&mut thread_rng());
+        assert_eq!(pop[0], 123);
+    }
+    
+    #[test]
+    fn correct_mutation() {
+        let agent = |_: &mut dyn RngCore| 123;
+        let fit = |_a: &usize| 1.0;
+        let muta = |a: &usize, _: &mut dyn RngCore| *a + 1;
+        let off = |a: &usize, b: &usize, _: &mut dyn RngCore| (*a + *b) / 2;
+        let test: Genetic<usize> = Genetic::builder()
+            .population(10)
+            .max_generation(1)
+            .pc(0.5)
+            .pm(1.0)
+            .random_agent(agent)
+            .fitness(fit)
+            .mutate(muta)
+            .offspring(off)
+            .build()
+            .unwrap();
+        
+        let pop = test.run().population;
+        assert_eq!(pop[0], 124);    
     }
     
     #[test]
     fn correct_crossover() {
-        let agent = || 121;
+        let agent = |_: &mut dyn RngCore| 121;
         let fit = |a: &usize| 10.0 - (*a as f64 - 244.0).abs();
-        let muta = |a: &usize| *a + 2;
-        let off = |a: &usize, b: &usize| (*a + *b);
-        let test: Genetic<usize> = Genetic {
-            population: 2,
-            max_generation: 1,
-            pc: 1.0,
-            pm: 1.0,
-            get_random_agent: &agent,
-            f_fitness: &fit,
-            f_mutate: &muta,
-            f_offspring: &off,
-        };
+        let muta = |a: &usize, _: &mut dyn RngCore| *a + 2;
+        let off = |a: &usize, b: &usize, _: &mut dyn RngCore| *a + *b;
+        let test: Genetic<usize> = Genetic::builder()
+            .population(2)
+            .max_generation(1)
+            .pc(1.0)
+            .pm(1.0)
+            .random_agent(agent)
+            .fitness(fit)
+            .mutate(muta)
+            .offspring(off)
+            .build()
+            .unwrap();
         
-        let pop = test.run();
+        let pop = test.run().population;
         assert_eq!(pop[0], 244);
     }
     
     #[test]
     fn convergence() {
-        let agent = || {
-            let mut rng = thread_rng();
-            rng.gen_range(-5.0, 5.0)
-        };
+        let agent = |rng: &mut dyn RngCore| rng.gen_range(-5.0, 5.0);
         let fit = |a: &f64| 5.0 - a * a;
-        let muta = |a: &f64| {
-            let mut rng = thread_rng();
-            *a + rng.gen_range(-0.01, 0.01)
-        };
-        let off = |a: &f64, b: &f64| (*a + *b) / 2.0;
-        let test: Genetic<f64> = Genetic {
-            population: 100,
-            max_generation: 20,
-            pc: 0.5,
-            pm: 0.4,
-            get_random_agent: &agent,
-            f_fitness: &fit,
-            f_mutate: &muta,
-            f_offspring: &off,
-        };
+        let muta = |a: &f64, rng: &mut dyn RngCore| *a + rng.gen_range(-0.01, 0.01);
+        let off = |a: &f64, b: &f64, _: &mut dyn RngCore| (*a + *b) / 2.0;
+        let test: Genetic<f64> = Genetic::builder()
+            .population(100)
+            .max_generation(20)
+            .pc(0.5)
+            .pm(0.4)
+            .random_agent(agent)
+            .fitness(fit)
+            .mutate(muta)
+            .offspring(off)
+            .build()
+            .unwrap();
         
-        let simul = test.run();
+        let simul = test.run().population;
         let best = test.get_best(&simul);
         assert!((simul[best]).abs() < 1.0);
     }
+
+    struct Parabola {
+        offset: f64,
+    }
+
+    impl Problem<f64> for Parabola {
+        fn random_agent(&self, rng: &mut dyn RngCore) -> f64 {
+            rng.gen_range(-5.0, 5.0)
+        }
+
+        fn fitness(&self, a: &f64) -> f64 {
+            5.0 - (a - self.offset) * (a - self.offset)
+        }
+
+        fn mutate(&self, a: &f64, rng: &mut dyn RngCore) -> f64 {
+            *a + rng.gen_range(-0.01, 0.01)
+        }
+
+        fn crossover(&self, a: &f64, b: &f64, _rng: &mut dyn RngCore) -> f64 {
+            (*a + *b) / 2.0
+        }
+    }
+
+    #[test]
+    fn problem_convergence() {
+        let test = Genetic::from_problem(100, 20, 0.5, 0.4, Parabola { offset: 2.0 });
+
+        let simul = test.run().population;
+        let best = test.get_best(&simul);
+        assert!((simul[best] - 2.0).abs() < 1.0);
+    }
+
+    #[test]
+    fn reproducible_run() {
+        let test = Genetic::from_problem(20, 10, 0.5, 0.4, Parabola { offset: 2.0 });
+
+        assert_eq!(test.run_with_seed(7), test.run_with_seed(7));
+        assert_ne!(test.run_with_seed(7).population, test.run_with_seed(8).population);
+    }
+
+    #[test]
+    fn run_result() {
+        let test = Genetic::from_problem(10, 3, 0.0, 0.0, Parabola { offset: 2.0 });
+
+        let result = test.run();
+        let best = test.get_best(&result.population);
+        assert_eq!(result.best, result.population[best]);
+        assert_eq!(result.best_fitness, (test.f_fitness)(&result.best).unwrap());
+        assert_eq!(result.generations, 3);
+        // without crossover and mutation there are no new agents, only the first generation is evaluated
+        assert_eq!(result.evaluations, 10);
+    }
+
+    #[test]
+    #[should_panic(expected = "pc must be between 0.0 and 1.0")]
+    fn invalid_problem_parameters() {
+        Genetic::from_problem(10, 5, 7.0, -3.0, Parabola { offset: 2.0 });
+    }
+
+    fn failing_builder<'a>(policy: FitnessErrorPolicy) -> GeneticBuilder<'a, i32> {
+        // the agents are 0, 1, 2, ..., but agents above 5 can't be evaluated
+        let next = std::sync::atomic::AtomicI32::new(0);
+        Genetic::builder()
+            .population(10)
+            .max_generation(1)
+            .pc(0.0)
+            .pm(0.0)
+            .random_agent(move |_| next.fetch_add(1, std::sync::atomic::Ordering::SeqCst))
+            .try_fitness(|a: &i32| if *a > 5 { Err("too big") } else { Ok(*a as f64) })
+            .mutate(|a: &i32, _| *a)
+            .offspring(|a: &i32, _b: &i32, _| *a)
+            .fitness_error_policy(policy)
+    }
+
+    #[test]
+    fn fitness_error_abort() {
+        let test = failing_builder(FitnessErrorPolicy::Abort).build().unwrap();
+
+        let error = test.try_run().err().unwrap();
+        assert_eq!(error.to_string(), "fitness function failed: too big");
+    }
+
+    #[test]
+    fn fitness_error_worst() {
+        let test = failing_builder(FitnessErrorPolicy::AssignWorst).build().unwrap();
+
+        let result = test.try_run().unwrap();
+        assert_eq!(result.best, 5);
+        assert_eq!(result.best_fitness, 5.0);
+    }
+
+    #[test]
+    fn fitness_error_retry() {
+        let calls = std::sync::atomic::AtomicUsize::new(0);
+        let test = Genetic::builder()
+            .population(2)
+            .max_generation(1)
+            .random_agent(|_| 0)
+            .try_fitness(|_a: &i32| {
+                // every second call fails
+                match calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst) % 2 {
+                    0 => Err("flaky"),
+                    _ => Ok(1.0),
+                }
+            })
+            .mutate(|a: &i32, _| *a)
+            .offspring(|a: &i32, _b: &i32, _| *a)
+            .fitness_error_policy(FitnessErrorPolicy::Retry(1))
+            .build()
+            .unwrap();
+
+        let result = test.try_run().unwrap();
+        assert_eq!(result.best_fitness, 1.0);
+        assert_eq!(result.evaluations, calls.load(std::sync::atomic::Ordering::SeqCst));
+    }
+
+    #[test]
+    fn minimization() {
+        let test = Genetic::builder()
+            .population(100)
+            .max_generation(20)
+            .random_agent(|rng| rng.gen_range(-5.0, 5.0))
+            .fitness(|a: &f64| (a - 3.0) * (a - 3.0))
+            .mutate(|a: &f64, rng| *a + rng.gen_range(-0.01, 0.01))
+            .offspring(|a: &f64, b: &f64, _| (*a + *b) / 2.0)
+            .minimize()
+            .build()
+            .unwrap();
+
+        let result = test.run();
+        assert!((result.best - 3.0).abs() < 1.0);
+        assert_eq!(test.get_best(&result.population), 0);
+        assert!(result.best_fitness < 1.0);
+    }
+
+    #[test]
+    fn integer_fitness() {
+        let test = Genetic::builder()
+            .population(20)
+            .max_generation(50)
+            .pm(1.0)
+            .random_agent(|rng| rng.gen_range(-100, 100))
+            .fitness(|a: &i64| -(a - 42).abs())
+            .mutate(|a: &i64, rng| *a + rng.gen_range(-1, 2))
+            .offspring(|a: &i64, b: &i64, _| (*a + *b) / 2)
+            .build()
+            .unwrap();
+
+        let result = test.run();
+        assert_eq!(result.best, 42);
+        assert_eq!(result.best_fitness, 0);
+    }
+
+    #[test]
+    fn lexicographic_fitness() {
+        // first the number of even digits, then the smaller number is better
+        let test = Genetic::builder()
+            .population(20)
+            .max_generation(30)
+            .random_agent(|rng| rng.gen_range(0, 1000))
+            .fitness(|a: &u32| {
+                let even = a.to_string().chars().filter(|c| c.to_digit(10).unwrap() % 2 == 0).count();
+                (even, -(*a as i64))
+            })
+            .mutate(|_a: &u32, rng| rng.gen_range(0, 1000))
+            .offspring(|a: &u32, _b: &u32, _| *a)
+            .build()
+            .unwrap();
+
+        let result = test.run();
+        assert_eq!(result.best_fitness.0, 3);
+        assert_eq!(result.best_fitness.1, -(result.best as i64));
+    }
+
+    #[test]
+    fn elitism() {
+        // the agents are 0, 1, 2, ..., but every mutation destroys them
+        let next = std::sync::atomic::AtomicI32::new(0);
+        let test = Genetic::builder()
+            .population(10)
+            .max_generation(5)
+            .pc(0.0)
+            .pm(1.0)
+            .random_agent(move |_| next.fetch_add(1, std::sync::atomic::Ordering::SeqCst))
+            .fitness(|a: &i32| *a)
+            .mutate(|_a: &i32, _| -1)
+            .offspring(|a: &i32, _b: &i32, _| *a)
+            .elitism(2)
+            .build()
+            .unwrap();
+
+        let result = test.run();
+        assert_eq!(result.best, 9);
+        assert_eq!(&result.population[..2], &[9, 8]);
+        assert!(result.population[2..].iter().all(|x| *x == -1));
+    }
+
+    #[test]
+    fn random_immigrants() {
+        // the agents are 0, 1, 2, ..., the smaller ones are better
+        let next = std::sync::atomic::AtomicI32::new(0);
+        let test = Genetic::builder()
+            .population(10)
+            .max_generation(5)
+            .random_agent(move |_| next.fetch_add(1, std::sync::atomic::Ordering::SeqCst))
+            .fitness(|a: &i32| -*a)
+            .mutate(|a: &i32, _| *a)
+            .offspring(|a: &i32, _b: &i32, _| *a)
+            .random_immigrants(0.3)
+            .build()
+            .unwrap();
+
+        // 3 immigrants are created in every generation, the ones of the last generation replace the worst agents
+        let result = test.run();
+        assert_eq!(result.best, 0);
+        let mut immigrants = result.population[7..].to_vec();
+        immigrants.sort();
+        assert_eq!(immigrants, vec![22, 23, 24]);
+
+        let test = failing_builder(FitnessErrorPolicy::Abort).random_immigrants(1.0).build();
+        assert_eq!(test.err(), Some(ConfigError::InvalidImmigrants(1.0)));
+    }
+
+    #[test]
+    fn initial_population() {
+        let test = Genetic::builder()
+            .population(10)
+            .max_generation(1)
+            .pm(0.0)
+            .pc(0.0)
+            .elitism(1)
+            .random_agent(|rng| rng.gen_range(0.0, 1.0))
+            .fitness(|a: &f64| *a)
+            .mutate(|a: &f64, _rng| *a)
+            .offspring(|a: &f64, _b: &f64, _rng| *a)
+            .initial_population(vec![5.0, 3.0])
+            .build()
+            .unwrap();
+
+        let population = test.get_population(&mut thread_rng());
+        assert_eq!(population[..2], [5.0, 3.0]);
+        assert!(population[2..].iter().all(|a| *a < 1.0));
+        assert_eq!(population.len(), 10);
+        assert_eq!(test.run().best, 5.0);
+
+        let seeded = failing_builder(FitnessErrorPolicy::Abort).population(2).initial_population(vec![1, 2, 3]).build();
+        assert_eq!(seeded.err(), Some(ConfigError::TooManyInitialAgents(3)));
+    }
+
+    #[test]
+    fn too_many_elites() {
+        let test = failing_builder(FitnessErrorPolicy::Abort).elitism(11).build();
+        assert_eq!(test.err(), Some(ConfigError::TooManyElites(11)));
+    }
+
+    #[test]
+    fn roulette_convergence() {
+        let test = Genetic::builder()
+            .population(100)
+            .max_generation(20)
+            .random_agent(|rng| rng.gen_range(-5.0, 5.0))
+            .fitness(|a: &f64| 5.0 - a * a)
+            .mutate(|a: &f64, rng| *a + rng.gen_range(-0.01, 0.01))
+            .offspring(|a: &f64, b: &f64, _| (*a + *b) / 2.0)
+            .selection(Selection::Roulette)
+            .elitism(1)
+            .build()
+            .unwrap();
+
+        let result = test.run();
+        assert!(result.best.abs() < 1.0);
+    }
+
+    #[test]
+    fn tournament_convergence() {
+        let test = Genetic::builder()
+            .population(100)
+            .max_generation(30)
+            .random_agent(|rng| rng.gen_range(-5.0, 5.0))
+            .fitness(|a: &f64| 5.0 - a * a)
+            .mutate(|a: &f64, rng| *a + rng.gen_range(-0.01, 0.01))
+            .offspring(|a: &f64, b: &f64, _| (*a + *b) / 2.0)
+            .selection(Selection::Tournament { size: 3, probability: 0.9 })
+            .build()
+            .unwrap();
+
+        let result = test.run();
+        assert!(result.best.abs() < 1.0);
+    }
+
+    #[test]
+    fn lexicase_convergence() {
+        // every bit of the target is a test case
+        let target = 0b1011_0110u8;
+        let test = Genetic::builder()
+            .population(20)
+            .max_generation(50)
+            .random_agent(|rng| rng.gen::<u8>())
+            .fitness(move |a: &u8| 8 - (a ^ target).count_ones())
+            .case_fitness(move |a: &u8| (0..8).map(|i| ((!(a ^ target) >> i) & 1) as f64).collect())
+            .mutate(|a: &u8, rng| a ^ (1 << rng.gen_range(0, 8)))
+            .offspring(|a: &u8, b: &u8, rng| {
+                let mask = rng.gen::<u8>();
+                (a & mask) | (b & !mask)
+            })
+            .selection(Selection::Lexicase)
+            .elitism(1)
+            .build()
+            .unwrap();
+
+        let result = test.run();
+        assert_eq!(result.best, target);
+    }
+
+    #[test]
+    fn parent_selection() {
+        let test = Genetic::builder()
+            .population(50)
+            .max_generation(30)
+            .pc(1.0)
+            .random_agent(|rng| rng.gen_range(-5.0, 5.0))
+            .fitness(|a: &f64| 5.0 - a * a)
+            .mutate(|a: &f64, rng| *a + rng.gen_range(-0.01, 0.01))
+            .offspring(|a: &f64, b: &f64, _rng| (*a + *b) / 2.0)
+            .parent_selection(Selection::tournament(3))
+            .selection(Selection::tournament(2))
+            .build()
+            .unwrap();
+
+        let result = test.run();
+        assert!(result.best.abs() < 0.1);
+
+        let truncation = failing_builder(FitnessErrorPolicy::Abort).parent_selection(Selection::Truncation).build();
+        assert!(matches!(truncation, Err(ConfigError::InvalidSelection(_))));
+    }
+
+    #[test]
+    fn steady_state() {
+        let test = Genetic::builder()
+            .population(20)
+            .max_generation(300)
+            .random_agent(|rng| rng.gen_range(-5.0, 5.0))
+            .fitness(|a: &f64| 5.0 - a * a)
+            .mutate(|a: &f64, rng| *a + rng.gen_range(-0.1, 0.1))
+            .offspring(|a: &f64, b: &f64, _rng| (*a + *b) / 2.0)
+            .steady_state(SteadyState::new(2))
+            .build()
+            .unwrap();
+
+        let result = test.run();
+        assert!(result.best.abs() < 0.1);
+        // every agent is evaluated once (the unchanged copies of the parents keep their fitness)
+        assert!(result.evaluations <= 20 + 2 * 300);
+        assert_eq!(5.0 - result.best * result.best, result.best_fitness);
+
+        let test = Genetic::builder()
+            .population(20)
+            .max_generation(300)
+            .pc(1.0)
+            .pm(1.0)
+            .random_agent(|rng| rng.gen_range(-5.0, 5.0))
+            .fitness(|a: &f64| 5.0 - a * a)
+            .mutate(|a: &f64, rng| *a + rng.gen_range(-0.1, 0.1))
+            .offspring(|a: &f64, b: &f64, _rng| (*a + *b) / 2.0)
+            .steady_state(SteadyState::new(2))
+            .build()
+            .unwrap();
+        assert_eq!(test.run().evaluations, 20 + 2 * 300);
+    }
+
+    #[test]
+    fn cellular() {
+        for neighborhood in [Neighborhood::VonNeumann(1), Neighborhood::Moore(1), Neighborhood::Custom(vec![(1, 0), (0, 1)])] {
+            let test = Genetic::builder()
+                .population(30)
+                .max_generation(50)
+                .pc(1.0)
+                .pm(1.0)
+                .random_agent(|rng| rng.gen_range(-5.0, 5.0))
+                .fitness(|a: &f64| 5.0 - a * a)
+                .mutate(|a: &f64, rng| *a + rng.gen_range(-0.1, 0.1))
+                .offspring(|a: &f64, b: &f64, _rng| (*a + *b) / 2.0)
+                .cellular(Cellular { width: 6, height: 5, neighborhood })
+                .build()
+                .unwrap();
+
+            let result = test.run_with_seed(2);
+            assert!(result.best.abs() < 0.1);
+            assert_eq!(result.population.len(), 30);
+            // one offspring per cell
+            assert_eq!(result.evaluations, 30 + 30 * 50);
+            // the cells are never replaced by worse offspring
+            assert!(result.history.windows(2).all(|h| h[1].worst >= h[0].worst));
+        }
+
+        let both = Genetic::builder()
+            .random_agent(|rng| rng.gen_range(-5.0, 5.0))
+            .fitness(|a: &f64| 5.0 - a * a)
+            .mutate(|a: &f64, rng| *a + rng.gen_range(-0.1, 0.1))
+            .offspring(|a: &f64, b: &f64, _rng| (*a + *b) / 2.0)
+            .cellular(Cellular::new(4, 4))
+            .steady_state(SteadyState::new(1))
+            .build();
+        assert!(matches!(both, Err(ConfigError::InvalidCellular(_))));
+    }
+
+    #[test]
+    fn evolution_strategy() {
+        for evolution_strategy in [EvolutionStrategy::comma(5, 35), EvolutionStrategy::plus(5, 35)].iter() {
+            let test = Genetic::builder()
+                .population(5)
+                .max_generation(50)
+                .pm(1.0)
+                .random_agent(|rng| rng.gen_range(-5.0, 5.0))
+                .fitness(|a: &f64| 5.0 - a * a)
+                .mutate(|a: &f64, rng| *a + rng.gen_range(-0.1, 0.1))
+                .offspring(|a: &f64, b: &f64, _rng| (*a + *b) / 2.0)
+                .evolution_strategy(*evolution_strategy)
+                .build()
+                .unwrap();
+
+            let result = test.run();
+            assert_eq!(result.population.len(), 5);
+            assert!(result.best.abs() < 0.1);
+        }
+
+        let both = failing_builder(FitnessErrorPolicy::Abort)
+            .steady_state(SteadyState::new(1))
+            .evolution_strategy(EvolutionStrategy::plus(2, 2))
+            .build();
+        assert!(matches!(both, Err(ConfigError::InvalidEvolutionStrategy(_))));
+
+        // the population is kept in any order, (10, 70) needs at most 70 agents
+        let before = failing_builder(FitnessErrorPolicy::Abort).population(100).evolution_strategy(EvolutionStrategy::comma(10, 70)).build();
+        let after = failing_builder(FitnessErrorPolicy::Abort).evolution_strategy(EvolutionStrategy::comma(10, 70)).population(100).build();
+        assert!(matches!(before, Err(ConfigError::InvalidEvolutionStrategy(_))));
+        assert!(matches!(after, Err(ConfigError::InvalidEvolutionStrategy(_))));
+    }
+
+    #[test]
+    fn multi_offspring() {
+        let test = Genetic::builder()
+            .population(10)
+            .max_generation(1)
+            .pc(1.0)
+            .pm(0.0)
+            .random_agent(|_rng| 1)
+            .fitness(|a: &i32| *a)
+            .mutate(|a: &i32, _rng| *a)
+            .multi_offspring(|a: &i32, b: &i32, _rng| vec![a + b, a * b, a - b])
+            .build()
+            .unwrap();
+
+        // every agent has three children: 2, 1 and 0
+        let result = test.run();
+        assert_eq!(result.evaluations, 10 + 3 * 10);
+        assert_eq!(result.population, vec![2; 10]);
+    }
+
+    #[test]
+    fn pm_adaptation() {
+        let test = Genetic::builder()
+            .population(20)
+            .max_generation(100)
+            .pm(0.2)
+            .random_agent(|rng| rng.gen_range(-5.0, 5.0))
+            .fitness(|a: &f64| 5.0 - a * a)
+            .mutate(|a: &f64, rng| *a + rng.gen_range(-0.1, 0.1))
+            .offspring(|a: &f64, b: &f64, _rng| (*a + *b) / 2.0)
+            .pm_adaptation(OneFifthRule::default())
+            .build()
+            .unwrap();
+
+        let result = test.run();
+        assert!(result.best.abs() < 0.1);
+        assert!(Genetic::builder()
+            .random_agent(|_| 0.0)
+            .fitness(|a: &f64| *a)
+            .mutate(|a: &f64, _rng| *a)
+            .offspring(|a: &f64, _b: &f64, _rng| *a)
+            .pm_adaptation(OneFifthRule { factor: 2.0, ..OneFifthRule::default() })
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn diversity_adaptation() {
+        let test = Genetic::builder()
+            .population(20)
+            .max_generation(50)
+            .random_agent(|rng| rng.gen_range(-5.0, 5.0))
+            .fitness(|a: &f64| 5.0 - a * a)
+            .mutate(|a: &f64, rng| *a + rng.gen_range(-0.1, 0.1))
+            .offspring(|a: &f64, b: &f64, _rng| (*a + *b) / 2.0)
+            .distance(|a: &f64, b: &f64| (a - b).abs())
+            .diversity_adaptation(DiversityAdaptation::default())
+            .build()
+            .unwrap();
+
+        let result = test.run();
+        assert!(result.best.abs() < 0.1);
+        assert_eq!(test.diversity(&[Evaluated::new(1.0, 0.0), Evaluated::new(3.0, 0.0)]), 2.0);
+        assert!(Genetic::builder()
+            .random_agent(|_| 0.0)
+            .fitness(|a: &f64| *a)
+            .mutate(|a: &f64, _rng| *a)
+            .offspring(|a: &f64, _b: &f64, _rng| *a)
+            .pm_adaptation(OneFifthRule::default())
+            .diversity_adaptation(DiversityAdaptation::default())
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn schedules() {
+        let test = Genetic::builder()
+            .population(20)
+            .max_generation(50)
+            .random_agent(|rng| rng.gen_range(-5.0, 5.0))
+            .fitness(|a: &f64| 5.0 - a * a)
+            .mutate(|a: &f64, rng| *a + rng.gen_range(-0.1, 0.1))
+            .offspring(|a: &f64, b: &f64, _rng| (*a + *b) / 2.0)
+            .pc_schedule(Schedule::Cosine { start: 0.9, end: 0.3 })
+            .pm_schedule(Schedule::Exponential { start: 0.8, rate: 0.95 })
+            .build()
+            .unwrap();
+        assert!(test.run().best.abs() < 0.1);
+
+        // without mutation and crossover nothing changes
+        let test = Genetic::builder()
+            .population(10)
+            .max_generation(5)
+            .random_agent(|_| 1)
+            .fitness(|a: &i32| *a)
+            .mutate(|a: &i32, _rng| *a + 1)
+            .offspring(|a: &i32, b: &i32, _rng| *a + *b)
+            .pc_schedule(Schedule::Custom(Box::new(|_| 0.0)))
+            .pm_schedule(Schedule::Linear { start: 0.0, end: 0.0 })
+            .build()
+            .unwrap();
+        assert_eq!(test.run().best, 1);
+    }
+
+    #[test]
+    fn operator_selection() {
+        for operator_selection in [OperatorSelection::ucb(), OperatorSelection::probability_matching()].iter() {
+            let test = Genetic::builder()
+                .population(20)
+                .max_generation(50)
+                .random_agent(|rng| rng.gen_range(-5.0, 5.0))
+                .fitness(|a: &f64| 5.0 - a * a)
+                .offspring(|a: &f64, b: &f64, _rng| (*a + *b) / 2.0)
+                // moves away from 0, never helps when the agents are already close to it
+                .mutation_operator("away", |a: &f64, _rng: &mut dyn RngCore| *a * 2.0)
+                .mutation_operator("closer", |a: &f64, _rng: &mut dyn RngCore| *a * 0.9)
+                .operator_selection(*operator_selection)
+                .build()
+                .unwrap();
+
+            let result = test.run_with_seed(1);
+            assert!(result.best.abs() < 0.01);
+            assert_eq!(result.mutation_stats.len(), 2);
+            assert_eq!(result.mutation_stats[0].successes, 0);
+            assert!(result.mutation_stats[1].uses > 3 * result.mutation_stats[0].uses);
+            assert_eq!(result.crossover_stats[0].name, "f_offspring");
+        }
+    }
+
+    #[test]
+    fn stagnation() {
+        let test = Genetic::builder()
+            .population(10)
+            .max_generation(1000)
+            .random_agent(|_| 1)
+            .fitness(|a: &i32| (*a).min(5))
+            .mutate(|a: &i32, _rng| *a + 1)
+            .offspring(|a: &i32, b: &i32, _rng| (*a).max(*b))
+            .pm(1.0)
+            .stagnation(Stagnation::new(3))
+            .build()
+            .unwrap();
+
+        // the fitness grows until the 4th generation, then 3 generations pass without improvement
+        let result = test.run();
+        assert_eq!(result.termination, Termination::Stagnation);
+        assert_eq!(result.generations, 7);
+        assert_eq!(result.best_fitness, 5);
+
+        let steady = Genetic::builder()
+            .population(10)
+            .max_generation(1000)
+            .random_agent(|_| 1)
+            .fitness(|a: &i32| (*a).min(5))
+            .mutate(|a: &i32, _rng| *a + 1)
+            .offspring(|a: &i32, b: &i32, _rng| (*a).max(*b))
+            .steady_state(SteadyState::new(1))
+            .stagnation(Stagnation::new(50))
+            .build()
+            .unwrap();
+        let result = steady.run();
+        assert_eq!(result.termination, Termination::Stagnation);
+        assert!(result.generations < 1000);
+    }
+
+    #[test]
+    fn target_fitness() {
+        let test = Genetic::builder()
+            .population(10)
+            .max_generation(1000)
+            .random_agent(|_| 1)
+            .fitness(|a: &i32| *a)
+            .mutate(|a: &i32, _rng| *a + 1)
+            .offspring(|a: &i32, b: &i32, _rng| (*a).max(*b))
+            .pm(1.0)
+            .target_fitness(10)
+            .build()
+            .unwrap();
+
+        // every generation adds one
+        let result = test.run();
+        assert_eq!(result.termination, Termination::TargetReached);
+        assert_eq!(result.generations, 9);
+        assert_eq!(result.best_fitness, 10);
+    }
+
+    #[test]
+    fn max_duration() {
+        let test = Genetic::builder()
+            .population(10)
+            .max_generation(100000)
+            .random_agent(|rng| rng.gen_range(-5.0, 5.0))
+            .fitness(|a: &f64| {
+                std::thread::sleep(Duration::from_micros(100));
+                5.0 - a * a
+            })
+            .mutate(|a: &f64, rng| *a + rng.gen_range(-0.1, 0.1))
+            .offspring(|a: &f64, b: &f64, _rng| (*a + *b) / 2.0)
+            .max_duration(Duration::from_millis(50))
+            .build()
+            .unwrap();
+
+        let result = test.run();
+        assert_eq!(result.termination, Termination::TimeLimit);
+        assert!(result.generations < 100000);
+        assert_eq!(result.population.len(), 10);
+
+        // the time of creating and evaluating the first generation counts too
+        let slow_start = Genetic::builder()
+            .population(10)
+            .max_generation(100000)
+            .random_agent(|rng| {
+                std::thread::sleep(Duration::from_millis(10));
+                rng.gen_range(-5.0, 5.0)
+            })
+            .fitness(|a: &f64| 5.0 - a * a)
+            .mutate(|a: &f64, rng| *a + rng.gen_range(-0.1, 0.1))
+            .offspring(|a: &f64, b: &f64, _rng| (*a + *b) / 2.0)
+            .max_duration(Duration::from_millis(50))
+            .build()
+            .unwrap();
+
+        let result = slow_start.run();
+        assert_eq!(result.termination, Termination::TimeLimit);
+        assert_eq!(result.generations, 1);
+    }
+
+    #[test]
+    fn cancellation() {
+        let token = CancellationToken::new();
+        let canceller = token.clone();
+        let test = Genetic::builder()
+            .population(10)
+            .max_generation(100000)
+            .random_agent(|rng| rng.gen_range(-5.0, 5.0))
+            .fitness(|a: &f64| 5.0 - a * a)
+            .mutate(|a: &f64, rng| *a + rng.gen_range(-0.1, 0.1))
+            .offspring(|a: &f64, b: &f64, _rng| (*a + *b) / 2.0)
+            .cancellation(token.clone())
+            .on_generation(move |info| {
+                if info.generation == 4 {
+                    canceller.cancel();
+                }
+                std::ops::ControlFlow::Continue(())
+            })
+            .build()
+            .unwrap();
+
+        let result = test.run();
+        assert_eq!(result.termination, Termination::Cancelled);
+        assert_eq!(result.generations, 5);
+        assert_eq!(result.population.len(), 10);
+        // a cancelled token stops the next runs before their first generation
+        assert!(token.is_cancelled());
+        assert_eq!(test.run().generations, 0);
+    }
+
+    #[test]
+    fn max_evaluations() {
+        let build = |steady_state: bool| {
+            let builder = Genetic::builder()
+                .population(10)
+                .max_generation(100000)
+                .random_agent(|rng| rng.gen_range(-5.0, 5.0))
+                .fitness(|a: &f64| 5.0 - a * a)
+                .mutate(|a: &f64, rng| *a + rng.gen_range(-0.1, 0.1))
+                .offspring(|a: &f64, b: &f64, _rng| (*a + *b) / 2.0)
+                .max_evaluations(1000);
+            if steady_state {
+                return builder.steady_state(SteadyState::new(2)).build().unwrap();
+            }
+            builder.build().unwrap()
+        };
+
+        for steady_state in [false, true].iter() {
+            let result = build(*steady_state).run();
+            assert_eq!(result.termination, Termination::EvaluationLimit);
+            assert_eq!(result.evaluations, 1000);
+            assert!(result.generations > 0);
+            assert_eq!(result.population.len(), 10);
+        }
+
+        // the budget is never exceeded: the first generation is cut short (with its opposites too)
+        let small = |max_evaluations: usize, opposition: bool| {
+            let builder = Genetic::builder()
+                .population(10)
+                .max_generation(100)
+                .random_agent(|rng| rng.gen_range(-5.0, 5.0))
+                .fitness(|a: &f64| 5.0 - a * a)
+                .mutate(|a: &f64, rng| *a + rng.gen_range(-0.1, 0.1))
+                .offspring(|a: &f64, b: &f64, _rng| (*a + *b) / 2.0)
+                .max_evaluations(max_evaluations);
+            if opposition {
+                return builder.opposition(Opposition::new(|a: &f64| -a)).build().unwrap();
+            }
+            builder.build().unwrap()
+        };
+        for (max_evaluations, opposition, agents) in [(5, false, 5), (5, true, 5), (15, true, 10)].iter() {
+            let result = small(*max_evaluations, *opposition).run_with_seed(1);
+            assert_eq!((result.termination, result.evaluations, result.generations), (Termination::EvaluationLimit, *max_evaluations, 0));
+            assert_eq!(result.population.len(), *agents);
+        }
+
+        // the evaluated part of the unfinished generation is kept
+        let test = small(15, false);
+        let mut state = test.start_with_rng(StdRng::seed_from_u64(1)).unwrap();
+        let worst = state.population().iter().map(|x| x.fitness).fold(f64::INFINITY, f64::min);
+        assert_eq!(test.step(&mut state).unwrap(), Some(Termination::EvaluationLimit));
+        assert_eq!((state.evaluations(), state.generation()), (15, 0));
+        assert!(state.population().iter().map(|x| x.fitness).fold(f64::INFINITY, f64::min) >= worst);
+        assert!(test.finish(state).best_fitness > 4.0);
+    }
+
+    #[test]
+    fn stop_condition() {
+        let test = Genetic::builder()
+            .population(10)
+            .max_generation(5)
+            .random_agent(|_| 1)
+            .fitness(|a: &i32| *a)
+            .mutate(|a: &i32, _rng| *a + 1)
+            .offspring(|a: &i32, b: &i32, _rng| (*a).max(*b))
+            .pm(1.0)
+            // max_generation is replaced
+            .stop_condition(StopCondition::MaxGeneration(20).or(StopCondition::TargetFitness(10)))
+            .build()
+            .unwrap();
+
+        let result = test.run();
+        assert_eq!(result.termination, Termination::StopCondition);
+        assert_eq!(result.generations, 9);
+    }
+
+    #[test]
+    fn on_generation() {
+        use std::ops::ControlFlow;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        let calls = AtomicUsize::new(0);
+        for steady_state in [None, Some(SteadyState::new(2))].iter() {
+            calls.store(0, Ordering::SeqCst);
+            let mut builder = Genetic::builder()
+                .population(10)
+                .max_generation(50)
+                .random_agent(|_| 1)
+                .fitness(|a: &i32| *a)
+                .mutate(|a: &i32, _rng| *a + 1)
+                .offspring(|a: &i32, b: &i32, _rng| (*a).max(*b))
+                .pm(1.0)
+                .on_generation(|info| {
+                    assert_eq!(info.generation, calls.fetch_add(1, Ordering::SeqCst));
+                    assert!(info.best_fitness >= info.worst_fitness);
+                    if *info.best_fitness >= 5 { ControlFlow::Break(()) } else { ControlFlow::Continue(()) }
+                });
+            if let Some(steady_state) = steady_state {
+                builder = builder.steady_state(steady_state.clone());
+            }
+
+            let result = builder.build().unwrap().run();
+            assert_eq!(result.termination, Termination::Callback);
+            assert_eq!(result.generations, calls.load(Ordering::SeqCst));
+            assert!(result.best_fitness >= 5);
+        }
+    }
+
+    #[test]
+    fn archive() {
+        let test = Genetic::builder()
+            .population(10)
+            .max_generation(30)
+            .random_agent(|rng| rng.gen_range(-50, 50))
+            .fitness(|a: &i32| 2500 - a * a)
+            .mutate(|a: &i32, rng| *a + rng.gen_range(-20, 21))
+            .offspring(|a: &i32, b: &i32, _rng| (*a + *b) / 2)
+            .pm(1.0)
+            .selection(Selection::Roulette)
+            .archive(5)
+            .build()
+            .unwrap();
+
+        let result = test.run_with_seed(7);
+        assert_eq!(result.archive.len(), 5);
+        assert!(result.archive[0].fitness >= result.best_fitness);
+        assert!(result.archive.windows(2).all(|pair| pair[0].fitness >= pair[1].fitness && pair[0].agent != pair[1].agent));
+        assert!(result.archive.iter().all(|x| x.fitness == 2500 - x.agent * x.agent));
+    }
+
+    #[test]
+    fn on_progress() {
+        use std::sync::Mutex;
+        let calls = Mutex::new(Vec::new());
+        let test = Genetic::builder()
+            .population(10)
+            .max_generation(5)
+            .random_agent(|_| 1)
+            .fitness(|a: &i32| *a)
+            .mutate(|a: &i32, _rng| *a + 1)
+            .offspring(|a: &i32, b: &i32, _rng| (*a).max(*b))
+            .pm(1.0)
+            .on_progress(|generation, max_generation, best_fitness| calls.lock().unwrap().push((generation, max_generation, *best_fitness)))
+            .build()
+            .unwrap();
+
+        let result = test.run();
+        let calls = calls.lock().unwrap();
+        assert_eq!(calls.iter().map(|call| call.0).collect::<Vec<usize>>(), vec![1, 2, 3, 4, 5]);
+        assert!(calls.iter().all(|call| call.1 == 5));
+        assert_eq!(calls[4].2, result.best_fitness);
+    }
+
+    #[cfg(feature = "indicatif")]
+    #[test]
+    fn progress_bar() {
+        let bar = indicatif::ProgressBar::hidden();
+        let test = Genetic::builder()
+            .population(10)
+            .max_generation(20)
+            .random_agent(|rng| rng.gen_range(-5.0, 5.0))
+            .fitness(|a: &f64| 5.0 - a * a)
+            .mutate(|a: &f64, rng| *a + rng.gen_range(-0.1, 0.1))
+            .offspring(|a: &f64, b: &f64, _rng| (*a + *b) / 2.0)
+            .progress_bar(bar.clone())
+            .build()
+            .unwrap();
+
+        let result = test.run();
+        assert_eq!((bar.position(), bar.length()), (20, Some(20)));
+        assert_eq!(bar.message(), format!("best: {}", result.best_fitness));
+    }
+
+    #[test]
+    fn fitness_cache() {
+        let build = |cache: bool| {
+            let mut builder = Genetic::builder()
+                .population(20)
+                .max_generation(30)
+                .random_agent(|rng| rng.gen_range(-5, 6))
+                .fitness(|a: &i32| -(a * a))
+                .mutate(|a: &i32, rng| *a + rng.gen_range(-1, 2))
+                .offspring(|a: &i32, b: &i32, _rng| (*a + *b) / 2);
+            if cache {
+                builder = builder.cache(100);
+            }
+            builder.build().unwrap()
+        };
+        let (cached, uncached) = (build(true), build(false));
+
+        let result = cached.run_with_seed(1);
+        assert_eq!(result.best_fitness, uncached.run_with_seed(1).best_fitness);
+        let stats = cached.cache_stats().unwrap();
+        // there are only 11 different agents (in parallel the copies of an agent can be evaluated at the same time)
+        #[cfg(not(feature = "rayon"))]
+        assert!(result.evaluations <= 11);
+        assert_eq!(stats.misses, result.evaluations);
+        assert!(stats.hits > 0);
+        assert_eq!(uncached.cache_stats(), None);
+    }
+
+    #[test]
+    fn length_penalty() {
+        let test = Genetic::builder()
+            .population(20)
+            .max_generation(20)
+            .pc(1.0)
+            .pm(0.0)
+            .random_agent(|rng| vec![0u8; rng.gen_range(5, 15)])
+            .fitness(|_a: &Vec<u8>| 1.0)
+            .crossover(genome::CutAndSplice)
+            .mutate(|a: &Vec<u8>, _rng| a.clone())
+            .length_penalty(|a: &Vec<u8>, f| f - a.len() as f64)
+            .build()
+            .unwrap();
+
+        let result = test.run();
+        assert!(result.population.iter().all(|a| a.len() < 5));
+        assert_eq!(result.best_fitness, 1.0);
+    }
+
+    #[test]
+    fn repair() {
+        let test = Genetic::builder()
+            .population(20)
+            .max_generation(20)
+            .random_agent(|rng| rng.gen_range(0.0, 1.0))
+            .fitness(|a: &f64| *a)
+            .mutate(|a: &f64, rng| *a + rng.gen_range(-0.5, 0.5))
+            .offspring(|a: &f64, b: &f64, _rng| 2.0 * a + b)
+            .repair(|a: &f64| a.clamp(0.0, 1.0))
+            .build()
+            .unwrap();
+
+        let result = test.run_with_seed(1);
+        assert!(result.population.iter().all(|a| (0.0..=1.0).contains(a)));
+        assert_eq!(result.best, 1.0);
+    }
+
+    #[test]
+    fn lexicase_needs_cases() {
+        let test = failing_builder(FitnessErrorPolicy::Abort).selection(Selection::Lexicase).build();
+        assert_eq!(test.err(), Some(ConfigError::MissingFunction("f_case_fitness")));
+    }
+
+    fn owned_parabola(offset: f64) -> OwnedGenetic<f64> {
+        Genetic::from_problem(100, 20, 0.5, 0.4, Parabola { offset })
+    }
+
+    #[test]
+    fn owned_in_thread() {
+        let tests = vec![owned_parabola(-2.0), owned_parabola(2.0)];
+
+        let handles = tests.into_iter()
+            .map(|test| std::thread::spawn(move || test.run().best))
+            .collect::<Vec<_>>();
+        let bests = handles.into_iter()
+            .map(|h| h.join().unwrap())
+            .collect::<Vec<f64>>();
+        assert!((bests[0] + 2.0).abs() < 1.0);
+        assert!((bests[1] - 2.0).abs() < 1.0);
+    }
 }
@@ -0,0 +1,287 @@
+use rand::prelude::*;
+use std::collections::BTreeMap;
+
+use crate::error::EvaluationBudget;
+use crate::{BehaviorFn, ConfigError, Evaluated, Fitness, Genetic, MaybeSync, Offspring, RunError};
+
+/// One dimension of the grid of MAP-Elites: the descriptor values between `min` and `max` are divided into `bins`
+/// equal cells (the values outside are put into the first or the last cell)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Dimension {
+    /// The smallest value of the descriptor
+    pub min: f64,
+    /// The biggest value of the descriptor (bigger than min)
+    pub max: f64,
+    /// The number of cells along the dimension (at least 1)
+    pub bins: usize,
+}
+
+impl Dimension {
+    /// Divides min - max into the given number of cells
+    pub fn new(min: f64, max: f64, bins: usize) -> Self {
+        Dimension { min, max, bins }
+    }
+
+    /// Returns the cell of the value along this dimension (None if the value is NaN)
+    pub fn bin(&self, value: f64) -> Option<usize> {
+        if value.is_nan() {
+            return None;
+        }
+        let position = (value - self.min) / (self.max - self.min) * self.bins as f64;
+
+        Some((position.max(0.0) as usize).min(self.bins - 1))
+    }
+}
+
+/// MAP-Elites (multi-dimensional archive of phenotypic elites), a quality-diversity algorithm: instead of one best
+/// agent it finds the best agent of every cell of a grid over the behaviour descriptors of the agents
+///
+/// The agents, the fitness and the operators come from a Genetic: its population gives the number of random agents
+/// the grid is seeded with, then in each of its max_generation iterations `batch` offspring are created by its
+/// operators (crossover with probability pc, mutation with probability pm) from parents chosen uniformly from the
+/// filled cells. An offspring takes the place of the agent of its cell if the cell is empty or it's better. The run
+/// stops early if the max_evaluations of the Genetic is reached.
+///
+/// # Examples:
+/// ```
+/// use rand::prelude::*;
+/// use bbte_optim_tzim1773_genetic::{Dimension, Genetic, MapElites};
+///
+/// fn main() {
+///     let genetic = Genetic::builder()
+///         .population(20)
+///         .max_generation(100)
+///         .pm(1.0)
+///         .random_agent(|rng| vec![rng.gen_range(-5.0, 5.0), rng.gen_range(-5.0, 5.0)])
+///         .fitness(|a: &Vec<f64>| -(a[0] * a[0] + a[1] * a[1]))
+///         .mutate(|a: &Vec<f64>, rng| a.iter().map(|x| x + rng.gen_range(-0.5, 0.5)).collect())
+///         .offspring(|a: &Vec<f64>, b: &Vec<f64>, _rng| vec![a[0], b[1]])
+///         .build()
+///         .unwrap();
+///
+///     // the descriptors are the coordinates, the grid is 10 x 10
+///     let grid = vec![Dimension::new(-5.0, 5.0, 10), Dimension::new(-5.0, 5.0, 10)];
+///     let map_elites = MapElites::new(genetic, |a: &Vec<f64>| a.clone(), grid).unwrap();
+///
+///     let result = map_elites.run();
+///     println!("{} cells, the best: {:?}", result.cells.len(), result.best.unwrap().agent);
+/// }
+/// ```
+pub struct MapElites<'a, T, F = f64> {
+    /// The Genetic giving the agents, the fitness, the operators and the number of iterations
+    pub genetic: Genetic<'a, T, F>,
+    /// The dimensions of the grid (one for every descriptor)
+    pub grid: Vec<Dimension>,
+    /// The number of offspring created in an iteration (at least 1)
+    /// Suggested value: the population of the Genetic
+    pub batch: usize,
+    f_descriptor: BehaviorFn<'a, T>,
+}
+
+/// The outcome of a MAP-Elites run
+#[derive(Debug, Clone, PartialEq)]
+pub struct MapElitesResult<T, F = f64> {
+    /// The best agent of every filled cell (indexed by the position of the cell along the dimensions)
+    pub cells: BTreeMap<Vec<usize>, Evaluated<T, F>>,
+    /// The best agent of all cells with its fitness (None if no agent had valid descriptors)
+    pub best: Option<Evaluated<T, F>>,
+    /// The ratio of the filled cells (0.0 - 1.0)
+    pub coverage: f64,
+    /// The number of iterations executed
+    pub iterations: usize,
+    /// The number of times the fitness function was called
+    pub evaluations: usize,
+}
+
+impl<'a, T, F> MapElites<'a, T, F>
+    where T: Clone + MaybeSync, F: Fitness + MaybeSync {
+    /// Creates a MAP-Elites run, checking the grid
+    ///
+    /// # Arguments:
+    ///
+    /// * `genetic` the agents, the fitness, the operators and the number of iterations
+    /// * `f_descriptor` returns the behaviour descriptors of an agent (one value for every dimension)
+    /// * `grid` the dimensions of the grid
+    pub fn new(genetic: Genetic<'a, T, F>, f_descriptor: impl Fn(&T) -> Vec<f64> + Send + Sync + 'a, grid: Vec<Dimension>) -> Result<Self, ConfigError> {
+        let batch = genetic.population;
+        let map_elites = MapElites { genetic, grid, batch, f_descriptor: Box::new(f_descriptor) };
+        map_elites.validate()?;
+
+        Ok(map_elites)
+    }
+
+    /// Checks whether the grid and the batch are usable
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.grid.is_empty() {
+            return Err(ConfigError::InvalidMapElites("the grid needs at least one dimension".to_string()));
+        }
+        for dimension in &self.grid {
+            if dimension.bins == 0 {
+                return Err(ConfigError::InvalidMapElites("every dimension needs at least one bin".to_string()));
+            }
+            if !(dimension.min < dimension.max && (dimension.max - dimension.min).is_finite()) {
+                return Err(ConfigError::InvalidMapElites(format!("min must be less than max, got {} and {}", dimension.min, dimension.max)));
+            }
+        }
+        if self.batch == 0 {
+            return Err(ConfigError::InvalidMapElites("batch must be at least 1".to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// Fills the grid until the iterations (or the evaluations) run out
+    ///
+    /// # Panics:
+    ///  If the fitness function fails and the fitness_error_policy gives up on the agent
+    ///  (use try_run() to get the error instead)
+    pub fn run(&self) -> MapElitesResult<T, F> {
+        self.run_with_rng(&mut thread_rng())
+    }
+
+    /// Same as run(), but returns the error of the fitness function instead of panicking
+    pub fn try_run(&self) -> Result<MapElitesResult<T, F>, RunError> {
+        self.try_run_with_rng(&mut thread_rng())
+    }
+
+    /// Same as run(), but uses the given random number generator
+    pub fn run_with_rng(&self, rng: &mut dyn RngCore) -> MapElitesResult<T, F> {
+        match self.try_run_with_rng(rng) {
+            Ok(result) => result,
+            Err(error) => panic!("{}", error),
+        }
+    }
+
+    /// Same as run(), but the random number generator is seeded with the given seed
+    pub fn run_with_seed(&self, seed: u64) -> MapElitesResult<T, F> {
+        self.run_with_rng(&mut StdRng::seed_from_u64(seed))
+    }
+
+    /// Same as run_with_rng(), but returns the error of the fitness function instead of panicking
+    pub fn try_run_with_rng(&self, rng: &mut dyn RngCore) -> Result<MapElitesResult<T, F>, RunError> {
+        let genetic = &self.genetic;
+        let mut rates = genetic.rates();
+        let mut evaluations = 0;
+        let mut cells = BTreeMap::new();
+        // the filled cells in the order they were filled, so a parent can be chosen quickly
+        let mut filled = Vec::new();
+
+        let initial = genetic.get_population(rng).into_iter().map(|x| (x, None)).collect();
+        for x in genetic.evaluate_unlimited_all(initial, &mut evaluations)? {
+            self.insert(&mut cells, &mut filled, x);
+        }
+
+        let mut iterations = 0;
+        while iterations < genetic.max_generation && !filled.is_empty() {
+            let mut offspring: Vec<Offspring<T, F>> = Vec::with_capacity(self.batch);
+            while offspring.len() < self.batch {
+                let a = &cells[&filled[rng.gen_range(0, filled.len())]];
+                let b = &cells[&filled[rng.gen_range(0, filled.len())]];
+                offspring.extend(genetic.breed(a, b, &mut rates, &mut evaluations, rng)?);
+            }
+            offspring.truncate(self.batch);
+            let evaluated = match genetic.evaluate_offspring(offspring, &mut evaluations) {
+                Ok(evaluated) => evaluated,
+                Err(error) if error.is::<EvaluationBudget>() => break,
+                Err(error) => return Err(error.into()),
+            };
+            for x in evaluated {
+                self.insert(&mut cells, &mut filled, x);
+            }
+            iterations += 1;
+        }
+
+        let best = cells.values()
+            .fold(None, |best: Option<&Evaluated<T, F>>, x| match best {
+                Some(best) if !genetic.objective.is_better(&x.fitness, &best.fitness) => Some(best),
+                _ => Some(x),
+            })
+            .cloned();
+        let size = self.grid.iter().map(|dimension| dimension.bins).product::<usize>();
+
+        Ok(MapElitesResult {
+            coverage: cells.len() as f64 / size as f64,
+            cells,
+            best,
+            iterations,
+            evaluations,
+        })
+    }
+
+    /// Returns the cell of an agent (None if one of its descriptors is NaN or missing)
+    pub fn cell(&self, agent: &T) -> Option<Vec<usize>> {
+        let descriptors = (self.f_descriptor)(agent);
+        if descriptors.len() != self.grid.len() {
+            return None;
+        }
+
+        self.grid.iter().zip(descriptors).map(|(dimension, value)| dimension.bin(value)).collect()
+    }
+
+    /// Puts the agent into its cell if the cell is empty or the agent is better than the one there
+    fn insert(&self, cells: &mut BTreeMap<Vec<usize>, Evaluated<T, F>>, filled: &mut Vec<Vec<usize>>, x: Evaluated<T, F>) {
+        let cell = match self.cell(&x.agent) {
+            Some(cell) => cell,
+            None => return,
+        };
+        match cells.get_mut(&cell) {
+            Some(elite) => {
+                if self.genetic.objective.is_better(&x.fitness, &elite.fitness) {
+                    *elite = x;
+                }
+            }
+            None => {
+                filled.push(cell.clone());
+                cells.insert(cell, x);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+
+    #[test]
+    fn bin() {
+        let dimension = Dimension::new(-1.0, 1.0, 4);
+        assert_eq!(dimension.bin(-1.0), Some(0));
+        assert_eq!(dimension.bin(-0.4), Some(1));
+        assert_eq!(dimension.bin(0.6), Some(3));
+        // the values outside are put into the first or the last cell
+        assert_eq!(dimension.bin(-7.0), Some(0));
+        assert_eq!(dimension.bin(1.0), Some(3));
+        assert_eq!(dimension.bin(f64::INFINITY), Some(3));
+        assert_eq!(dimension.bin(f64::NAN), None);
+    }
+
+    #[test]
+    fn map_elites() {
+        let genetic = Genetic::builder()
+            .population(10)
+            .max_generation(100)
+            .pm(1.0)
+            .random_agent(|rng| vec![rng.gen_range(-0.5, 0.5), rng.gen_range(-0.5, 0.5)])
+            .fitness(|a: &Vec<f64>| -(a[0] * a[0] + a[1] * a[1]))
+            .mutate(|a: &Vec<f64>, rng| a.iter().map(|x| x + rng.gen_range(-2.0, 2.0)).collect())
+            .offspring(|a: &Vec<f64>, b: &Vec<f64>, _rng| vec![a[0], b[1]])
+            .build()
+            .unwrap();
+        let grid = vec![Dimension::new(-5.0, 5.0, 5), Dimension::new(-5.0, 5.0, 5)];
+        let map_elites = MapElites::new(genetic, |a: &Vec<f64>| a.clone(), grid).unwrap();
+
+        let result = map_elites.run_with_seed(4);
+        assert_eq!(result.cells.len(), 25);
+        assert_eq!(result.coverage, 1.0);
+        assert_eq!(result.iterations, 100);
+        assert_eq!(result.evaluations, 10 + 100 * 10);
+        // every cell holds an agent of the cell, the best one is in the centre of the grid
+        for (cell, x) in &result.cells {
+            assert_eq!(map_elites.cell(&x.agent).as_ref(), Some(cell));
+        }
+        assert_eq!(result.best.as_ref(), Some(&result.cells[&vec![2, 2]]));
+
+        assert!(MapElites::new(map_elites.genetic, |a: &Vec<f64>| a.clone(), vec![Dimension::new(1.0, 1.0, 3)]).is_err());
+    }
+}
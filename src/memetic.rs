@@ -0,0 +1,146 @@
+use crate::error::EvaluationBudget;
+use crate::{ConfigError, Evaluated, Fitness, FitnessError, Genetic, MaybeSync};
+
+/// Function that improves an agent with a local search (e.g. hill climbing or a problem-specific heuristic)
+pub type LocalSearchFn<'a, T> = Box<dyn Fn(&T) -> T + Send + Sync + 'a>;
+
+/// Memetic algorithm: every `interval` generations the best `portion` of the evaluated candidates is improved by a
+/// local search before the next generation is selected
+///
+/// The improved agents are evaluated with f_fitness and they replace the original ones if they are fitter (the
+/// improvement is inherited, Lamarckian learning). The local search is the most expensive part of a memetic
+/// algorithm, the interval and the portion keep it in budget; the evaluations made inside it are not counted, only
+/// the one of the improved agent. The improved agents are not evaluated if they don't fit into max_evaluations.
+///
+/// # Examples:
+/// ```
+/// use rand::prelude::*;
+/// use bbte_optim_tzim1773_genetic::{Genetic, LocalSearch};
+///
+/// fn main() {
+///     let fitness = |a: &f64| 5.0 - a * a;
+///     // a few steps of hill climbing
+///     let climb = move |a: &f64| {
+///         let mut x = *a;
+///         for step in &[0.1, 0.01, 0.001] {
+///             while fitness(&(x + step)) > fitness(&x) { x += step; }
+///             while fitness(&(x - step)) > fitness(&x) { x -= step; }
+///         }
+///         x
+///     };
+///     let test = Genetic::builder()
+///         .max_generation(20)
+///         .random_agent(|rng| rng.gen_range(-5.0, 5.0))
+///         .fitness(fitness)
+///         .mutate(|a: &f64, rng| *a + rng.gen_range(-0.5, 0.5))
+///         .offspring(|a: &f64, b: &f64, _rng| (*a + *b) / 2.0)
+///         .local_search(LocalSearch::new(climb).interval(5).portion(0.1))
+///         .build()
+///         .unwrap();
+///
+///     println!("{}", test.run().best); // should be a number very close to 0
+/// }
+/// ```
+pub struct LocalSearch<'a, T> {
+    /// The local search is applied in every `interval`-th generation (at least 1)
+    /// Suggested value: 1 (or more for expensive local searches)
+    pub interval: usize,
+    /// The part of the candidates improved (the best ones, 0.0 < portion <= 1.0), at least one is improved
+    /// Suggested value: 0.1
+    pub portion: f64,
+    f_improve: LocalSearchFn<'a, T>,
+}
+
+impl<'a, T> LocalSearch<'a, T> {
+    /// Improves the best 10% of the candidates in every generation with the given local search
+    pub fn new(f_improve: impl Fn(&T) -> T + Send + Sync + 'a) -> Self {
+        LocalSearch { interval: 1, portion: 0.1, f_improve: Box::new(f_improve) }
+    }
+
+    /// Sets the number of generations between two local searches
+    pub fn interval(mut self, interval: usize) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Sets the part of the candidates improved
+    pub fn portion(mut self, portion: f64) -> Self {
+        self.portion = portion;
+        self
+    }
+
+    /// Checks whether the parameters are usable
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.interval == 0 {
+            return Err(ConfigError::InvalidLocalSearch("the interval must be at least 1".to_string()));
+        }
+        if !(self.portion > 0.0 && self.portion <= 1.0) {
+            return Err(ConfigError::InvalidLocalSearch(format!("portion must be bigger than 0.0 and at most 1.0, got {}", self.portion)));
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a, T, F> Genetic<'a, T, F>
+    where T: Clone + MaybeSync, F: Fitness + MaybeSync {
+    /// Improves the best candidates with the local search if it's due in the generation
+    pub(crate) fn improve(&self, candidates: &mut [Evaluated<T, F>], generation: usize, evaluations: &mut usize) -> Result<(), FitnessError> {
+        let local_search = match &self.local_search {
+            Some(local_search) if (generation + 1).is_multiple_of(local_search.interval) && !candidates.is_empty() => local_search,
+            _ => return Ok(()),
+        };
+        let count = ((local_search.portion * candidates.len() as f64).ceil() as usize).clamp(1, candidates.len());
+        let mut best = self.best_first(candidates);
+        best.truncate(count);
+        let improved = best.iter().map(|i| ((local_search.f_improve)(&candidates[*i].agent), None)).collect();
+        let improved = match self.evaluate_offspring(improved, evaluations) {
+            Ok(improved) => improved,
+            Err(error) if error.is::<EvaluationBudget>() => return Ok(()),
+            Err(error) => return Err(error),
+        };
+        for (i, x) in best.into_iter().zip(improved) {
+            if self.is_fitter(&x, &candidates[i]) {
+                candidates[i] = x;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::prelude::*;
+
+    #[test]
+    fn local_search() {
+        assert!(LocalSearch::new(|a: &i32| *a).interval(0).validate().is_err());
+        assert!(LocalSearch::new(|a: &i32| *a).portion(0.0).validate().is_err());
+
+        // the local search moves an agent 10 steps towards the optimum at 100, the mutation only 1
+        let builder = || Genetic::builder()
+            .population(10)
+            .max_generation(10)
+            .random_agent(|rng| rng.gen_range(-10, 10))
+            .fitness(|a: &i32| -(a - 100).abs())
+            .mutate(|a: &i32, rng| *a + rng.gen_range(-1, 2))
+            .offspring(|a: &i32, b: &i32, _rng| (*a + *b) / 2);
+
+        let plain = builder().build().unwrap().run_with_seed(1);
+        let memetic = builder().local_search(LocalSearch::new(|a: &i32| (*a + 10).min(100)).portion(0.1)).build().unwrap().run_with_seed(1);
+        assert!(plain.best < 30);
+        assert!(memetic.best >= 90);
+
+        // a worse result of the local search is not kept, and it runs only in every 2nd generation
+        let test = builder().local_search(LocalSearch::new(|a: &i32| *a - 1).interval(2).portion(1.0)).build().unwrap();
+        let mut xg = vec![Evaluated::new(5, -95), Evaluated::new(6, -94)];
+        let mut evaluations = 0;
+        test.improve(&mut xg, 0, &mut evaluations).unwrap();
+        assert_eq!(evaluations, 0);
+        test.improve(&mut xg, 1, &mut evaluations).unwrap();
+        assert_eq!(xg, vec![Evaluated::new(5, -95), Evaluated::new(6, -94)]);
+        assert_eq!(evaluations, 2);
+    }
+}
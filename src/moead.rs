@@ -0,0 +1,167 @@
+use rand::prelude::*;
+
+use crate::error::EvaluationBudget;
+use crate::multi_objective::{front, values};
+use crate::{Evaluated, MaybeSync, MultiObjective, MultiObjectiveResult, RunError};
+
+/// The function turning the objectives into the value of a subproblem of MOEA/D (smaller is better)
+///
+/// The objectives are measured from the ideal point (the best value of every objective found so far), the weights
+/// are the weight vector of the subproblem.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Decomposition {
+    /// The weighted sum of the objectives (it can only find the convex parts of the Pareto front)
+    WeightedSum,
+    /// The biggest weighted distance from the ideal point along an objective
+    Tchebycheff,
+    /// Penalty-based boundary intersection: the distance from the ideal point along the weight vector plus
+    /// `penalty` times the distance from the weight vector (at least 0.0)
+    /// Suggested penalty: 5.0
+    Pbi {
+        /// The weight of the distance from the weight vector
+        penalty: f64,
+    },
+}
+
+impl Decomposition {
+    /// Returns the value of the objectives (to be minimised) in the subproblem with the given weights
+    fn value(&self, costs: &[f64], weights: &[f64], ideal: &[f64]) -> f64 {
+        let differences = costs.iter().zip(ideal).map(|(cost, ideal)| cost - ideal);
+        let value = match self {
+            Decomposition::WeightedSum => costs.iter().zip(weights).map(|(cost, weight)| weight * cost).sum(),
+            Decomposition::Tchebycheff => differences
+                .zip(weights)
+                .map(|(difference, weight)| weight.max(1e-6) * difference.abs())
+                .fold(0.0, f64::max),
+            Decomposition::Pbi { penalty } => {
+                let norm = weights.iter().map(|weight| weight * weight).sum::<f64>().sqrt();
+                let differences = differences.collect::<Vec<f64>>();
+                let along = differences.iter().zip(weights).map(|(difference, weight)| difference * weight).sum::<f64>() / norm;
+                let away = differences.iter()
+                    .zip(weights)
+                    .map(|(difference, weight)| difference - along * weight / norm)
+                    .map(|d| d * d)
+                    .sum::<f64>()
+                    .sqrt();
+                along.abs() + penalty * away
+            }
+        };
+
+        if value.is_nan() { f64::INFINITY } else { value }
+    }
+}
+
+/// Returns the weight vectors of the subproblems: for 2 objectives they are evenly spaced, for more the first ones
+/// are the corners of the simplex and the others are uniformly random on it
+fn weights(count: usize, objectives: usize, rng: &mut dyn RngCore) -> Vec<Vec<f64>> {
+    (0..count)
+        .map(|i| match objectives {
+            0 | 1 => vec![1.0; objectives],
+            2 => {
+                let first = if count > 1 { i as f64 / (count - 1) as f64 } else { 0.5 };
+                vec![first, 1.0 - first]
+            }
+            _ if i < objectives => (0..objectives).map(|k| if k == i { 1.0 } else { 0.0 }).collect(),
+            _ => {
+                let exponential = (0..objectives).map(|_| -(1.0 - rng.gen::<f64>()).ln()).collect::<Vec<f64>>();
+                let total = exponential.iter().sum::<f64>();
+                exponential.iter().map(|x| x / total).collect()
+            }
+        })
+        .collect()
+}
+
+/// Returns the neighbourhoods of the subproblems: the indices of the `size` nearest weight vectors (the nearest,
+/// the subproblem itself, first)
+fn neighborhoods(weights: &[Vec<f64>], size: usize) -> Vec<Vec<usize>> {
+    let distance = |a: &[f64], b: &[f64]| a.iter().zip(b).map(|(x, y)| (x - y) * (x - y)).sum::<f64>();
+    weights.iter()
+        .map(|a| {
+            let mut order = (0..weights.len()).collect::<Vec<usize>>();
+            order.sort_by(|j, k| distance(a, &weights[*j]).total_cmp(&distance(a, &weights[*k])));
+            order.truncate(size);
+            order
+        })
+        .collect()
+}
+
+impl<'a, T> MultiObjective<'a, T>
+    where T: Clone + MaybeSync {
+    /// Runs MOEA/D: every agent of the population solves the subproblem of its weight vector
+    pub(crate) fn run_decomposed(&self, neighbors: usize, decomposition: Decomposition, rng: &mut dyn RngCore) -> Result<MultiObjectiveResult<T>, RunError> {
+        let genetic = &self.genetic;
+        let mut rates = genetic.rates();
+        let mut evaluations = 0;
+
+        let initial = genetic.get_population(rng).into_iter().map(|x| (x, None)).collect();
+        let mut population = genetic.evaluate_unlimited_all(initial, &mut evaluations)?;
+        let count = population.iter().map(|x| x.fitness.len()).max().unwrap_or(0);
+        // the objective values to be minimised
+        let costs = |x: &Evaluated<T, Vec<f64>>| values(&x.fitness, count, genetic.objective).iter().map(|v| -v).collect::<Vec<f64>>();
+        let mut population_costs = population.iter().map(costs).collect::<Vec<Vec<f64>>>();
+        let mut ideal = vec![f64::INFINITY; count];
+        let update = |ideal: &mut Vec<f64>, costs: &[f64]| {
+            for (z, cost) in ideal.iter_mut().zip(costs) {
+                *z = z.min(*cost);
+            }
+        };
+        for costs in &population_costs {
+            update(&mut ideal, costs);
+        }
+        let weights = weights(population.len(), count, rng);
+        let neighborhoods = neighborhoods(&weights, neighbors);
+
+        let mut generations = 0;
+        'run: while generations < genetic.max_generation {
+            for neighborhood in &neighborhoods {
+                let a = &population[neighborhood[rng.gen_range(0, neighborhood.len())]];
+                let b = &population[neighborhood[rng.gen_range(0, neighborhood.len())]];
+                let child = match genetic.breed(a, b, &mut rates, &mut evaluations, rng)?.into_iter().next() {
+                    Some(child) => child,
+                    None => continue,
+                };
+                let child = match genetic.evaluate_offspring(vec![child], &mut evaluations) {
+                    Ok(mut evaluated) => evaluated.remove(0),
+                    Err(error) if error.is::<EvaluationBudget>() => break 'run,
+                    Err(error) => return Err(error.into()),
+                };
+                let child_costs = costs(&child);
+                update(&mut ideal, &child_costs);
+
+                // the infinite ideal values (no agent had a finite value) are not used
+                let ideal = ideal.iter().map(|z| if z.is_finite() { *z } else { 0.0 }).collect::<Vec<f64>>();
+                for j in neighborhood {
+                    if decomposition.value(&child_costs, &weights[*j], &ideal) <= decomposition.value(&population_costs[*j], &weights[*j], &ideal) {
+                        population[*j] = child.clone();
+                        population_costs[*j] = child_costs.clone();
+                    }
+                }
+            }
+            generations += 1;
+        }
+
+        Ok(MultiObjectiveResult { front: front(&population, genetic.objective), population, generations, evaluations })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decomposition() {
+        let rng = &mut StdRng::seed_from_u64(1);
+        assert_eq!(weights(3, 2, rng), vec![vec![0.0, 1.0], vec![0.5, 0.5], vec![1.0, 0.0]]);
+        let random = weights(5, 3, rng);
+        assert_eq!(random[1], vec![0.0, 1.0, 0.0]);
+        assert!(random.iter().all(|w| (w.iter().sum::<f64>() - 1.0).abs() < 1e-9));
+        assert_eq!(neighborhoods(&weights(5, 2, rng), 3), vec![vec![0, 1, 2], vec![1, 0, 2], vec![2, 1, 3], vec![3, 2, 4], vec![4, 3, 2]]);
+
+        let (costs, ideal) = ([3.0, 1.0], [1.0, 0.0]);
+        assert_eq!(Decomposition::WeightedSum.value(&costs, &[0.5, 0.5], &ideal), 2.0);
+        assert_eq!(Decomposition::Tchebycheff.value(&costs, &[0.5, 0.5], &ideal), 1.0);
+        // 2 along the weight vector (1, 0), 1 away from it
+        assert_eq!(Decomposition::Pbi { penalty: 5.0 }.value(&costs, &[1.0, 0.0], &ideal), 7.0);
+        assert_eq!(Decomposition::Tchebycheff.value(&[f64::INFINITY, 0.0], &[0.5, 0.5], &ideal), f64::INFINITY);
+    }
+}
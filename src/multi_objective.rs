@@ -0,0 +1,314 @@
+use rand::prelude::*;
+
+use crate::error::EvaluationBudget;
+use crate::{nsga2, pareto, spea2};
+use crate::{ConfigError, Decomposition, Evaluated, Genetic, MaybeSync, Objective, Offspring, RunError};
+
+/// The key of an agent in the binary tournaments (smaller is better, compared lexicographically)
+pub(crate) type Key = (f64, f64);
+
+/// The algorithm of a multi-objective optimisation
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MultiObjectiveAlgorithm {
+    /// NSGA-II: the next generation is chosen from the parents and the offspring front by front (by non-dominated
+    /// sorting), the last front that doesn't fit is cut by the crowding distance. In the tournaments the agent in
+    /// the better front wins, in the same front the one in the less crowded region.
+    Nsga2,
+    /// SPEA2: the agents are kept in an external archive of the given size (at least 1), the parents are chosen
+    /// from it. The fitness of an agent is the sum of the strengths (the number of agents they dominate) of the
+    /// agents dominating it, plus a density estimate from the distance to its k-th nearest neighbour. The archive
+    /// holds the non-dominated agents, truncated by removing the agent closest to the others again and again (or
+    /// filled with the best dominated agents). In the tournaments the agent with the smaller fitness wins.
+    /// Suggested archive: the population of the Genetic
+    Spea2 {
+        /// The size of the archive
+        archive: usize,
+    },
+    /// MOEA/D: the problem is decomposed into single-objective subproblems (one for every agent of the population,
+    /// each with a weight vector), and every subproblem is solved by its agent. In a generation every subproblem
+    /// breeds an offspring from two random agents of its `neighbors` nearest subproblems (by the weight vectors),
+    /// and the offspring takes the place of the neighbouring agents it's not worse than in their subproblem.
+    /// Suggested neighbors: 20, suggested decomposition: Tchebycheff
+    MoeaD {
+        /// The number of the nearest subproblems (including itself) a subproblem mates with and replaces (at least 1)
+        neighbors: usize,
+        /// The function turning the objectives into the value of a subproblem
+        decomposition: Decomposition,
+    },
+}
+
+/// Multi-objective optimisation: the fitness of an agent is a vector of objective values, and instead of one best
+/// agent the run finds the Pareto front (the agents that no other agent dominates, i.e. no other agent is at least
+/// as good in every objective and better in one), with NSGA-II, SPEA2 or MOEA/D
+///
+/// The agents, the fitness and the operators come from a Genetic with Vec<f64> fitness, every objective is maximised
+/// or minimised according to its objective. In each of its max_generation generations `population` offspring are
+/// created by its operators (crossover with probability pc, mutation with probability pm) from parents chosen by
+/// binary tournaments, then the algorithm chooses the agents kept from the previous ones and the offspring (MOEA/D
+/// breeds and replaces inside the neighbourhoods of its subproblems instead). The other settings of the Genetic (selection, elitism, modes) are not used, the run stops early if its
+/// max_evaluations is reached.
+///
+/// The missing and NaN objective values count as the worst possible values.
+///
+/// # Examples:
+/// ```
+/// use rand::prelude::*;
+/// use bbte_optim_tzim1773_genetic::{Genetic, MultiObjective, MultiObjectiveAlgorithm, Objective};
+///
+/// fn main() {
+///     // the agents between 0 and 2 are the trade-offs between the two objectives
+///     let genetic = Genetic::builder()
+///         .population(40)
+///         .max_generation(50)
+///         .objective(Objective::Minimize)
+///         .random_agent(|rng| rng.gen_range(-10.0, 10.0))
+///         .fitness(|a: &f64| vec![a * a, (a - 2.0) * (a - 2.0)])
+///         .mutate(|a: &f64, rng| *a + rng.gen_range(-0.5, 0.5))
+///         .offspring(|a: &f64, b: &f64, _rng| (*a + *b) / 2.0)
+///         .build()
+///         .unwrap();
+///
+///     let result = MultiObjective::new(genetic, MultiObjectiveAlgorithm::Nsga2).unwrap().run();
+///     for x in &result.front {
+///         println!("{} {:?}", x.agent, x.fitness);
+///     }
+/// }
+/// ```
+pub struct MultiObjective<'a, T> {
+    /// The Genetic giving the agents, the objectives, the operators, the population and the number of generations
+    pub genetic: Genetic<'a, T, Vec<f64>>,
+    /// The algorithm choosing the agents kept
+    pub algorithm: MultiObjectiveAlgorithm,
+}
+
+/// The outcome of a multi-objective run
+#[derive(Debug, Clone, PartialEq)]
+pub struct MultiObjectiveResult<T> {
+    /// The agents kept at the end that no other kept agent dominates
+    pub front: Vec<Evaluated<T, Vec<f64>>>,
+    /// The agents kept at the end (the last generation of NSGA-II, the archive of SPEA2)
+    pub population: Vec<Evaluated<T, Vec<f64>>>,
+    /// The number of generations executed
+    pub generations: usize,
+    /// The number of times the fitness function was called
+    pub evaluations: usize,
+}
+
+impl MultiObjectiveAlgorithm {
+    /// Checks whether the parameters are usable
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        match self {
+            MultiObjectiveAlgorithm::Spea2 { archive: 0 } => {
+                return Err(ConfigError::InvalidMultiObjective("the archive of SPEA2 must be at least 1".to_string()));
+            }
+            MultiObjectiveAlgorithm::MoeaD { neighbors: 0, .. } => {
+                return Err(ConfigError::InvalidMultiObjective("MOEA/D needs at least 1 neighbor".to_string()));
+            }
+            MultiObjectiveAlgorithm::MoeaD { decomposition: Decomposition::Pbi { penalty }, .. } if !(*penalty >= 0.0 && penalty.is_finite()) => {
+                return Err(ConfigError::InvalidMultiObjective(format!("the penalty of PBI must be a non-negative number, got {}", penalty)));
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    /// The number of agents kept between the generations
+    fn size(&self, population: usize) -> usize {
+        match self {
+            MultiObjectiveAlgorithm::Nsga2 | MultiObjectiveAlgorithm::MoeaD { .. } => population,
+            MultiObjectiveAlgorithm::Spea2 { archive } => *archive,
+        }
+    }
+
+    /// Chooses the agents kept, returns their indices with their tournament keys
+    fn select(&self, values: &[Vec<f64>], size: usize) -> (Vec<usize>, Vec<Key>) {
+        match self {
+            MultiObjectiveAlgorithm::Nsga2 => nsga2::select(values, size),
+            MultiObjectiveAlgorithm::Spea2 { .. } => spea2::select(values, size),
+            MultiObjectiveAlgorithm::MoeaD { .. } => unreachable!("MOEA/D replaces the agents of its subproblems"),
+        }
+    }
+}
+
+impl<'a, T> MultiObjective<'a, T>
+    where T: Clone + MaybeSync {
+    /// Creates a multi-objective run, checking the algorithm
+    ///
+    /// # Arguments:
+    ///
+    /// * `genetic` the agents, the objectives, the operators, the population and the number of generations
+    /// * `algorithm` NSGA-II, SPEA2 or MOEA/D
+    pub fn new(genetic: Genetic<'a, T, Vec<f64>>, algorithm: MultiObjectiveAlgorithm) -> Result<Self, ConfigError> {
+        algorithm.validate()?;
+
+        Ok(MultiObjective { genetic, algorithm })
+    }
+
+    /// Runs the optimisation until the generations (or the evaluations) run out
+    ///
+    /// # Panics:
+    ///  If the fitness function fails and the fitness_error_policy gives up on the agent
+    ///  (use try_run() to get the error instead)
+    pub fn run(&self) -> MultiObjectiveResult<T> {
+        self.run_with_rng(&mut thread_rng())
+    }
+
+    /// Same as run(), but returns the error of the fitness function instead of panicking
+    pub fn try_run(&self) -> Result<MultiObjectiveResult<T>, RunError> {
+        self.try_run_with_rng(&mut thread_rng())
+    }
+
+    /// Same as run(), but uses the given random number generator
+    pub fn run_with_rng(&self, rng: &mut dyn RngCore) -> MultiObjectiveResult<T> {
+        match self.try_run_with_rng(rng) {
+            Ok(result) => result,
+            Err(error) => panic!("{}", error),
+        }
+    }
+
+    /// Same as run(), but the random number generator is seeded with the given seed
+    pub fn run_with_seed(&self, seed: u64) -> MultiObjectiveResult<T> {
+        self.run_with_rng(&mut StdRng::seed_from_u64(seed))
+    }
+
+    /// Same as run_with_rng(), but returns the error of the fitness function instead of panicking
+    pub fn try_run_with_rng(&self, rng: &mut dyn RngCore) -> Result<MultiObjectiveResult<T>, RunError> {
+        if let MultiObjectiveAlgorithm::MoeaD { neighbors, decomposition } = self.algorithm {
+            return self.run_decomposed(neighbors, decomposition, rng);
+        }
+        let genetic = &self.genetic;
+        let size = self.algorithm.size(genetic.population);
+        let mut rates = genetic.rates();
+        let mut evaluations = 0;
+
+        let initial = genetic.get_population(rng).into_iter().map(|x| (x, None)).collect();
+        let initial = genetic.evaluate_unlimited_all(initial, &mut evaluations)?;
+        let (chosen, mut keys) = self.algorithm.select(&objectives(&initial, genetic.objective), size);
+        let mut kept = keep(initial, &chosen);
+
+        let mut generations = 0;
+        while generations < genetic.max_generation {
+            let mut offspring: Vec<Offspring<T, Vec<f64>>> = Vec::with_capacity(genetic.population);
+            while offspring.len() < genetic.population {
+                let a = &kept[tournament(&keys, rng)];
+                let b = &kept[tournament(&keys, rng)];
+                offspring.extend(genetic.breed(a, b, &mut rates, &mut evaluations, rng)?);
+            }
+            offspring.truncate(genetic.population);
+            let evaluated = match genetic.evaluate_offspring(offspring, &mut evaluations) {
+                Ok(evaluated) => evaluated,
+                Err(error) if error.is::<EvaluationBudget>() => break,
+                Err(error) => return Err(error.into()),
+            };
+            kept.extend(evaluated);
+
+            let (chosen, chosen_keys) = self.algorithm.select(&objectives(&kept, genetic.objective), size);
+            kept = keep(kept, &chosen);
+            keys = chosen_keys;
+            generations += 1;
+        }
+
+        Ok(MultiObjectiveResult { front: front(&kept, genetic.objective), population: kept, generations, evaluations })
+    }
+}
+
+/// Returns the agents no other agent dominates
+pub(crate) fn front<T: Clone>(xg: &[Evaluated<T, Vec<f64>>], objective: Objective) -> Vec<Evaluated<T, Vec<f64>>> {
+    nsga2::non_dominated_sort(&objectives(xg, objective))
+        .into_iter()
+        .next()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|i| xg[i].clone())
+        .collect()
+}
+
+/// Returns the objective values of the agents turned so that bigger is better in every objective (the missing
+/// and NaN values become -inf)
+fn objectives<T>(xg: &[Evaluated<T, Vec<f64>>], objective: Objective) -> Vec<Vec<f64>> {
+    let count = xg.iter().map(|x| x.fitness.len()).max().unwrap_or(0);
+    xg.iter().map(|x| values(&x.fitness, count, objective)).collect()
+}
+
+/// Returns the first `count` objective values turned so that bigger is better (the missing and NaN values
+/// become -inf)
+pub(crate) fn values(fitness: &[f64], count: usize, objective: Objective) -> Vec<f64> {
+    (0..count)
+        .map(|i| match (fitness.get(i), objective) {
+            (Some(value), _) if value.is_nan() => f64::NEG_INFINITY,
+            (Some(value), Objective::Maximize) => *value,
+            (Some(value), Objective::Minimize) => -value,
+            (None, _) => f64::NEG_INFINITY,
+        })
+        .collect()
+}
+
+/// Tells whether a dominates b (it's at least as big in every objective and bigger in one)
+pub(crate) fn dominates(a: &[f64], b: &[f64]) -> bool {
+    pareto::dominates(a, b, Objective::Maximize)
+}
+
+/// Returns the agents at the given indices (in the order of the indices)
+fn keep<T>(xg: Vec<Evaluated<T, Vec<f64>>>, indices: &[usize]) -> Vec<Evaluated<T, Vec<f64>>> {
+    let mut xg = xg.into_iter().map(Some).collect::<Vec<_>>();
+    indices.iter().map(|i| xg[*i].take().expect("the agents are kept once")).collect()
+}
+
+/// Binary tournament: the agent with the smaller key wins
+fn tournament(keys: &[Key], rng: &mut dyn RngCore) -> usize {
+    let a = rng.gen_range(0, keys.len());
+    let b = rng.gen_range(0, keys.len());
+    if keys[a] <= keys[b] {
+        a
+    } else {
+        b
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+
+    #[test]
+    fn objective_values() {
+        let xg = vec![Evaluated::new(0, vec![1.0, f64::NAN]), Evaluated::new(1, vec![2.0])];
+        assert_eq!(objectives(&xg, Objective::Minimize), vec![vec![-1.0, f64::NEG_INFINITY], vec![-2.0, f64::NEG_INFINITY]]);
+        assert!(dominates(&[1.0, 2.0], &[1.0, 1.0]));
+        assert!(!dominates(&[1.0, 2.0], &[1.0, 2.0]));
+        assert!(MultiObjective::new(genetic(), MultiObjectiveAlgorithm::Spea2 { archive: 0 }).is_err());
+        let decomposition = Decomposition::Pbi { penalty: -1.0 };
+        assert!(MultiObjective::new(genetic(), MultiObjectiveAlgorithm::MoeaD { neighbors: 5, decomposition }).is_err());
+    }
+
+    fn genetic<'a>() -> Genetic<'a, f64, Vec<f64>> {
+        Genetic::builder()
+            .population(30)
+            .max_generation(40)
+            .objective(Objective::Minimize)
+            .random_agent(|rng| rng.gen_range(-10.0, 10.0))
+            .fitness(|a: &f64| vec![a * a, (a - 2.0) * (a - 2.0)])
+            .mutate(|a: &f64, rng| *a + rng.gen_range(-0.5, 0.5))
+            .offspring(|a: &f64, b: &f64, _rng| (*a + *b) / 2.0)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn algorithms() {
+        let moead = MultiObjectiveAlgorithm::MoeaD { neighbors: 5, decomposition: Decomposition::Tchebycheff };
+        for algorithm in [MultiObjectiveAlgorithm::Nsga2, MultiObjectiveAlgorithm::Spea2 { archive: 20 }, moead] {
+            let result = MultiObjective::new(genetic(), algorithm).unwrap().run_with_seed(2);
+            assert_eq!(result.generations, 40);
+            // the children that are not changed by the operators keep the fitness of their parents
+            assert!(result.evaluations > 30 && result.evaluations <= 30 + 40 * 30);
+            assert_eq!(result.population.len(), algorithm.size(30));
+            // the Pareto front is 0 - 2, the agents are spread over it
+            assert_eq!(result.front.len(), result.population.len());
+            assert!(result.front.iter().all(|x| (-0.05..=2.05).contains(&x.agent)));
+            assert!(result.front.iter().any(|x| x.agent < 0.2));
+            assert!(result.front.iter().any(|x| x.agent > 1.8));
+        }
+    }
+}
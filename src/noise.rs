@@ -0,0 +1,199 @@
+use std::cmp::Ordering;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::Fitness;
+
+/// The statistic of the samples of a noisy fitness that the agents are selected on
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum SampleStatistic {
+    /// The average of the samples
+    Mean,
+    /// The given percentile of the samples (0.0 - 100.0, interpolated between the samples), e.g. 25.0 to prefer
+    /// the agents that are good most of the time in a maximising run
+    Percentile(f64),
+}
+
+/// Resampling of a noisy (stochastic) fitness function: every agent is evaluated `samples` times, and the agents
+/// are selected on the mean or a percentile of the samples
+///
+/// With adaptive() the agents are sampled until the standard error of their mean is at most the tolerance (or
+/// they have max_samples samples), so the agents with little noise cost fewer samples. fitness() turns the noisy
+/// function into a fitness function returning Sampled values, they tell the number of samples of every agent too.
+/// The samples of an agent count as a single evaluation (with a fitness cache an agent is sampled only once).
+///
+/// # Examples:
+/// ```
+/// use rand::prelude::*;
+/// use bbte_optim_tzim1773_genetic::{Genetic, Resampling};
+///
+/// fn main() {
+///     let test = Genetic::builder()
+///         .max_generation(50)
+///         .random_agent(|rng| rng.gen_range(-5.0, 5.0))
+///         .fitness(Resampling::new(5).adaptive(50, 0.05).fitness(|a: &f64| 5.0 - a * a + thread_rng().gen_range(-1.0, 1.0)))
+///         .mutate(|a: &f64, rng| *a + rng.gen_range(-0.1, 0.1))
+///         .offspring(|a: &f64, b: &f64, _rng| (*a + *b) / 2.0)
+///         .build()
+///         .unwrap();
+///
+///     let result = test.run();
+///     println!("{} ({} samples)", result.best, result.best_fitness.samples); // should be a number close to 0
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Resampling {
+    /// The number of samples of every agent (the fewest samples with adaptive sampling), at least 1 is taken
+    /// Suggested value: 5
+    pub samples: usize,
+    /// The most samples of an agent (more samples than `samples` are only taken with adaptive sampling)
+    pub max_samples: usize,
+    /// The standard error of the mean where adaptive sampling stops
+    pub tolerance: f64,
+    /// The statistic the agents are selected on
+    /// Suggested value: SampleStatistic::Mean
+    pub statistic: SampleStatistic,
+}
+
+impl Resampling {
+    /// Takes the given number of samples of every agent and selects on their mean
+    pub fn new(samples: usize) -> Self {
+        Resampling { samples, max_samples: samples, tolerance: 0.0, statistic: SampleStatistic::Mean }
+    }
+
+    /// Takes more samples (at most `max_samples`) until the standard error of the mean is at most `tolerance`
+    pub fn adaptive(mut self, max_samples: usize, tolerance: f64) -> Self {
+        self.max_samples = max_samples;
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Sets the statistic the agents are selected on
+    pub fn statistic(mut self, statistic: SampleStatistic) -> Self {
+        self.statistic = statistic;
+        self
+    }
+
+    /// Samples the noisy function for an agent and returns the statistics of the samples
+    pub fn sample<T>(&self, x: &T, f_noisy: impl Fn(&T) -> f64) -> Sampled {
+        let min = self.samples.max(1);
+        let max = self.max_samples.max(min);
+        let mut samples = (0..min).map(|_| f_noisy(x)).collect::<Vec<f64>>();
+        while samples.len() < max && standard_error(&samples) > self.tolerance {
+            samples.push(f_noisy(x));
+        }
+
+        let mean = samples.iter().sum::<f64>() / samples.len() as f64;
+        let value = match self.statistic {
+            SampleStatistic::Mean => mean,
+            SampleStatistic::Percentile(p) => percentile(&mut samples, p),
+        };
+        Sampled { value, mean, std_error: standard_error(&samples), samples: samples.len() }
+    }
+
+    /// Returns a fitness function that samples the noisy function
+    pub fn fitness<'a, T>(self, f_noisy: impl Fn(&T) -> f64 + Send + Sync + 'a) -> impl Fn(&T) -> Sampled + Send + Sync + 'a {
+        move |x| self.sample(x, &f_noisy)
+    }
+}
+
+/// The standard error of the mean of the samples (infinite with fewer than 2 samples)
+fn standard_error(samples: &[f64]) -> f64 {
+    let n = samples.len() as f64;
+    if samples.len() < 2 {
+        return f64::INFINITY;
+    }
+    let mean = samples.iter().sum::<f64>() / n;
+    let variance = samples.iter().map(|x| (x - mean) * (x - mean)).sum::<f64>() / (n - 1.0);
+    (variance / n).sqrt()
+}
+
+/// The p-th percentile of the samples, interpolated linearly between the closest ones (the samples are sorted)
+fn percentile(samples: &mut [f64], p: f64) -> f64 {
+    samples.sort_by(f64::total_cmp);
+    let position = p.clamp(0.0, 100.0) / 100.0 * (samples.len() - 1) as f64;
+    let (below, above) = (position.floor() as usize, position.ceil() as usize);
+    samples[below] + (samples[above] - samples[below]) * (position - below as f64)
+}
+
+/// Fitness of a noisy agent: the statistic of its samples (they are compared by this value) with the mean, its
+/// standard error and the number of samples
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Sampled {
+    /// The statistic the agents are selected on
+    pub value: f64,
+    /// The mean of the samples
+    pub mean: f64,
+    /// The standard error of the mean (infinite with a single sample)
+    pub std_error: f64,
+    /// The number of samples
+    pub samples: usize,
+}
+
+impl PartialEq for Sampled {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl PartialOrd for Sampled {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.value.partial_cmp(&other.value)
+    }
+}
+
+impl Fitness for Sampled {
+    fn lowest() -> Self {
+        Sampled { value: f64::NEG_INFINITY, mean: f64::NEG_INFINITY, std_error: f64::INFINITY, samples: 0 }
+    }
+
+    fn highest() -> Self {
+        Sampled { value: f64::INFINITY, mean: f64::INFINITY, std_error: f64::INFINITY, samples: 0 }
+    }
+
+    fn to_f64(&self) -> f64 {
+        self.value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Genetic;
+    use rand::prelude::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn resampling() {
+        // the samples go around 1, 2, 3, 4
+        let calls = AtomicUsize::new(0);
+        let noisy = |a: &f64| a + (calls.fetch_add(1, Ordering::Relaxed) % 4 + 1) as f64;
+
+        let sampled = Resampling::new(4).sample(&0.0, noisy);
+        assert_eq!((sampled.value, sampled.samples), (2.5, 4));
+        assert!((sampled.std_error - (5.0f64 / 3.0 / 4.0).sqrt()).abs() < 1e-12);
+        let sampled = Resampling::new(4).statistic(SampleStatistic::Percentile(25.0)).sample(&10.0, noisy);
+        assert_eq!((sampled.value, sampled.mean), (11.75, 12.5));
+        // the noise doesn't go below the tolerance, every sample is taken
+        assert_eq!(Resampling::new(2).adaptive(10, 0.1).sample(&0.0, noisy).samples, 10);
+        assert_eq!(Resampling::new(2).adaptive(10, 0.1).sample(&0.0, |a: &f64| *a).samples, 2);
+        assert_eq!(Resampling::new(0).sample(&0.0, |a: &f64| *a).samples, 1);
+
+        let test = Genetic::builder()
+            .population(20)
+            .max_generation(50)
+            .random_agent(|rng| rng.gen_range(-5.0, 5.0))
+            .fitness(Resampling::new(20).fitness(|a: &f64| 5.0 - a * a + thread_rng().gen_range(-1.0, 1.0)))
+            .mutate(|a: &f64, rng| *a + rng.gen_range(-0.1, 0.1))
+            .offspring(|a: &f64, b: &f64, _rng| (*a + *b) / 2.0)
+            .build()
+            .unwrap();
+
+        let result = test.run_with_seed(1);
+        assert!(result.best.abs() < 1.0);
+        assert_eq!(result.best_fitness.samples, 20);
+    }
+}
@@ -0,0 +1,199 @@
+use rand::prelude::*;
+
+use crate::{ConfigError, Evaluated, Fitness, Genetic, MaybeSync, Objective};
+
+/// Function that returns the behaviour of an agent (a point in the behaviour space, e.g. the final position of a robot)
+pub type BehaviorFn<'a, T> = Box<dyn Fn(&T) -> Vec<f64> + Send + Sync + 'a>;
+
+/// Novelty search: the next generation is selected by the novelty of the agents (the mean Euclidean distance of
+/// their behaviour to the `k` nearest behaviours of the other candidates and of the novelty archive) instead of,
+/// or blended with, their fitness, so the run keeps exploring on deceptive problems
+///
+/// With a `weight` of 1.0 only the novelty counts, with smaller weights the score is
+/// weight * novelty + (1 - weight) * fitness, both scaled to 0.0 - 1.0 among the candidates. Every candidate gets
+/// into the archive with `archive_probability` (the oldest ones are dropped above `archive_capacity`). The behaviour
+/// function is called for every candidate in every generation. Only the survivor selection of the generational
+/// mode uses the novelty (the elites and the result are still chosen by the fitness).
+///
+/// # Examples:
+/// ```
+/// use rand::prelude::*;
+/// use bbte_optim_tzim1773_genetic::{Genetic, Novelty};
+///
+/// fn main() {
+///     // the fitness is deceptive: it leads to the local optimum at 0, the global one is above 5
+///     let test = Genetic::builder()
+///         .population(30)
+///         .max_generation(60)
+///         .random_agent(|rng| rng.gen_range(-1.0, 1.0))
+///         .fitness(|a: &f64| if *a > 5.0 { 10.0 } else { -a.abs() })
+///         .mutate(|a: &f64, rng| *a + rng.gen_range(-0.5, 0.5))
+///         .offspring(|a: &f64, _b: &f64, _rng| *a)
+///         .novelty(Novelty::new(|a: &f64| vec![*a]))
+///         .elitism(1)
+///         .build()
+///         .unwrap();
+///
+///     println!("{}", test.run().best); // probably bigger than 5
+/// }
+/// ```
+pub struct Novelty<'a, T> {
+    /// The number of the nearest behaviours the novelty is measured from (at least 1)
+    /// Suggested value: 15
+    pub k: usize,
+    /// The weight of the novelty in the score (0.0 - 1.0), the rest is the weight of the fitness
+    /// Suggested value: 1.0
+    pub weight: f64,
+    /// The probability that a candidate gets into the archive (0.0 - 1.0)
+    /// Suggested value: 0.05
+    pub archive_probability: f64,
+    /// The maximal number of behaviours in the archive
+    /// Suggested value: 1000
+    pub archive_capacity: usize,
+    f_behavior: BehaviorFn<'a, T>,
+}
+
+impl<'a, T> Novelty<'a, T> {
+    /// Pure novelty search with the given behaviour function and the suggested values
+    pub fn new(f_behavior: impl Fn(&T) -> Vec<f64> + Send + Sync + 'a) -> Self {
+        Novelty { k: 15, weight: 1.0, archive_probability: 0.05, archive_capacity: 1000, f_behavior: Box::new(f_behavior) }
+    }
+
+    /// Blends the novelty with the fitness: the score is weight * novelty + (1 - weight) * fitness
+    pub fn weight(mut self, weight: f64) -> Self {
+        self.weight = weight;
+        self
+    }
+
+    /// Checks whether the parameters are usable
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.k == 0 {
+            return Err(ConfigError::InvalidNovelty("k must be at least 1".to_string()));
+        }
+        if !(0.0..=1.0).contains(&self.weight) {
+            return Err(ConfigError::InvalidNovelty(format!("weight must be between 0.0 and 1.0, got {}", self.weight)));
+        }
+        if !(0.0..=1.0).contains(&self.archive_probability) {
+            return Err(ConfigError::InvalidNovelty(format!("archive_probability must be between 0.0 and 1.0, got {}", self.archive_probability)));
+        }
+
+        Ok(())
+    }
+
+    /// Returns the scores of the candidates (bigger is better) and puts some of their behaviours into the archive
+    pub(crate) fn scores<F: Fitness>(&self, candidates: &[Evaluated<T, F>], archive: &mut Vec<Vec<f64>>, objective: Objective, rng: &mut dyn RngCore) -> Vec<f64> {
+        let behaviors = candidates.iter().map(|x| (self.f_behavior)(&x.agent)).collect::<Vec<Vec<f64>>>();
+        let novelty = novelty(&behaviors, archive, self.k);
+        let scores = if self.weight < 1.0 {
+            let fitness = candidates.iter()
+                .map(|x| match objective {
+                    Objective::Maximize => x.fitness.to_f64(),
+                    Objective::Minimize => -x.fitness.to_f64(),
+                })
+                .collect::<Vec<f64>>();
+            let (novelty, fitness) = (normalized(&novelty), normalized(&fitness));
+            novelty.iter().zip(fitness).map(|(n, f)| self.weight * n + (1.0 - self.weight) * f).collect()
+        } else {
+            novelty
+        };
+
+        for behavior in behaviors {
+            if rng.gen_range(0.0, 1.0) < self.archive_probability {
+                archive.push(behavior);
+            }
+        }
+        let excess = archive.len().saturating_sub(self.archive_capacity);
+        archive.drain(..excess);
+
+        scores
+    }
+}
+
+/// Returns the mean Euclidean distance of every behaviour to its k nearest neighbours (among the other
+/// behaviours and the archive)
+fn novelty(behaviors: &[Vec<f64>], archive: &[Vec<f64>], k: usize) -> Vec<f64> {
+    let distance = |a: &[f64], b: &[f64]| a.iter().zip(b).map(|(x, y)| (x - y) * (x - y)).sum::<f64>().sqrt();
+    (0..behaviors.len())
+        .map(|i| {
+            let mut distances = behaviors.iter()
+                .enumerate()
+                .filter(|(j, _)| *j != i)
+                .map(|(_, b)| b)
+                .chain(archive)
+                .map(|b| distance(&behaviors[i], b))
+                .collect::<Vec<f64>>();
+            if distances.is_empty() {
+                return 0.0;
+            }
+            distances.sort_by(f64::total_cmp);
+            distances.truncate(k);
+            distances.iter().sum::<f64>() / distances.len() as f64
+        })
+        .collect()
+}
+
+/// Scales the values to 0.0 - 1.0 (the values that can't be scaled become 0.0)
+fn normalized(values: &[f64]) -> Vec<f64> {
+    let finite = values.iter().cloned().filter(|v| v.is_finite());
+    let min = finite.clone().fold(f64::INFINITY, f64::min);
+    let max = finite.fold(f64::NEG_INFINITY, f64::max);
+    values.iter()
+        .map(|v| if v.is_finite() && max > min { (v - min) / (max - min) } else { 0.0 })
+        .collect()
+}
+
+impl<'a, T, F> Genetic<'a, T, F>
+    where T: Clone + MaybeSync, F: Fitness + MaybeSync {
+    /// Returns the novelty scores of the candidates if it's a novelty search
+    pub(crate) fn novelty_scores(&self, candidates: &[Evaluated<T, F>], archive: &mut Vec<Vec<f64>>, rng: &mut dyn RngCore) -> Option<Vec<f64>> {
+        self.novelty.as_ref().map(|novelty| novelty.scores(candidates, archive, self.objective, rng))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+
+    #[test]
+    fn scores() {
+        let behaviors = vec![vec![0.0, 0.0], vec![3.0, 4.0], vec![0.0, 1.0]];
+        assert_eq!(novelty(&behaviors, &[], 1), vec![1.0, 4.242640687119285, 1.0]);
+        assert_eq!(novelty(&behaviors, &[vec![3.0, 5.0]], 2), vec![3.0, 2.6213203435596424, 2.6213203435596424]);
+        assert_eq!(normalized(&[1.0, 3.0, f64::NAN, 2.0]), vec![0.0, 1.0, 0.0, 0.5]);
+
+        let novelty = Novelty { k: 1, archive_probability: 1.0, archive_capacity: 2, ..Novelty::new(|a: &f64| vec![*a]) };
+        let candidates = [0.0, 1.0, 5.0].iter().map(|x| Evaluated::new(*x, -x)).collect::<Vec<_>>();
+        let mut archive = Vec::new();
+        let rng = &mut StdRng::seed_from_u64(1);
+        assert_eq!(novelty.scores(&candidates, &mut archive, Objective::Maximize, rng), vec![1.0, 1.0, 4.0]);
+        assert_eq!(archive, vec![vec![1.0], vec![5.0]]);
+        // the last two candidates are at distance 0 from the archived behaviours, the fitness decides between them
+        let blended = novelty.weight(0.5).scores(&candidates, &mut archive, Objective::Maximize, rng);
+        assert_eq!(blended, vec![1.0, 0.4, 0.0]);
+        assert!(Novelty { k: 0, ..Novelty::new(|a: &f64| vec![*a]) }.validate().is_err());
+    }
+
+    #[test]
+    fn deceptive() {
+        // the local optimum is at 0, the global one is above 5
+        let run = |novelty: Option<Novelty<'static, f64>>| {
+            let builder = Genetic::builder()
+                .population(30)
+                .max_generation(60)
+                .random_agent(|rng| rng.gen_range(-1.0, 1.0))
+                .fitness(|a: &f64| if *a > 5.0 { 10.0 } else { -a.abs() })
+                .mutate(|a: &f64, rng| *a + rng.gen_range(-0.5, 0.5))
+                .offspring(|a: &f64, _b: &f64, _rng| *a)
+                .elitism(1);
+            match novelty {
+                Some(novelty) => builder.novelty(novelty),
+                None => builder,
+            }.build().unwrap().run_with_seed(1).best_fitness
+        };
+
+        // the fitness leads to the local optimum, the novelty leads away from it
+        assert!(run(None) < 0.0);
+        assert_eq!(run(Some(Novelty::new(|a: &f64| vec![*a]))), 10.0);
+    }
+}
@@ -0,0 +1,205 @@
+use rand::prelude::*;
+
+use crate::multi_objective::{dominates, Key};
+use crate::{Genetic, MaybeSync, MultiObjective, MultiObjectiveAlgorithm, MultiObjectiveResult, RunError};
+
+/// NSGA-II (non-dominated sorting genetic algorithm), a shorthand for a MultiObjective run with
+/// MultiObjectiveAlgorithm::Nsga2: the run finds the Pareto front of the objective values returned by the fitness
+///
+/// # Examples:
+/// ```
+/// use rand::prelude::*;
+/// use bbte_optim_tzim1773_genetic::{Genetic, Nsga2, Objective};
+///
+/// fn main() {
+///     // the agents between 0 and 2 are the trade-offs between the two objectives
+///     let genetic = Genetic::builder()
+///         .population(40)
+///         .max_generation(50)
+///         .objective(Objective::Minimize)
+///         .random_agent(|rng| rng.gen_range(-10.0, 10.0))
+///         .fitness(|a: &f64| vec![a * a, (a - 2.0) * (a - 2.0)])
+///         .mutate(|a: &f64, rng| *a + rng.gen_range(-0.5, 0.5))
+///         .offspring(|a: &f64, b: &f64, _rng| (*a + *b) / 2.0)
+///         .build()
+///         .unwrap();
+///
+///     let result = Nsga2::new(genetic).run();
+///     for x in &result.front {
+///         println!("{} {:?}", x.agent, x.fitness);
+///     }
+/// }
+/// ```
+pub struct Nsga2<'a, T> {
+    /// The multi-objective run with the NSGA-II algorithm
+    pub multi_objective: MultiObjective<'a, T>,
+}
+
+/// The outcome of an NSGA-II run
+pub type Nsga2Result<T> = MultiObjectiveResult<T>;
+
+impl<'a, T> Nsga2<'a, T>
+    where T: Clone + MaybeSync {
+    /// Creates an NSGA-II run from the Genetic giving the agents, the objectives, the operators, the population and
+    /// the number of generations
+    pub fn new(genetic: Genetic<'a, T, Vec<f64>>) -> Self {
+        Nsga2 { multi_objective: MultiObjective { genetic, algorithm: MultiObjectiveAlgorithm::Nsga2 } }
+    }
+
+    /// Runs the optimisation until the generations (or the evaluations) run out
+    ///
+    /// # Panics:
+    ///  If the fitness function fails and the fitness_error_policy gives up on the agent
+    ///  (use try_run() to get the error instead)
+    pub fn run(&self) -> Nsga2Result<T> {
+        self.multi_objective.run()
+    }
+
+    /// Same as run(), but returns the error of the fitness function instead of panicking
+    pub fn try_run(&self) -> Result<Nsga2Result<T>, RunError> {
+        self.multi_objective.try_run()
+    }
+
+    /// Same as run(), but uses the given random number generator
+    pub fn run_with_rng(&self, rng: &mut dyn RngCore) -> Nsga2Result<T> {
+        self.multi_objective.run_with_rng(rng)
+    }
+
+    /// Same as run(), but the random number generator is seeded with the given seed
+    pub fn run_with_seed(&self, seed: u64) -> Nsga2Result<T> {
+        self.multi_objective.run_with_seed(seed)
+    }
+
+    /// Same as run_with_rng(), but returns the error of the fitness function instead of panicking
+    pub fn try_run_with_rng(&self, rng: &mut dyn RngCore) -> Result<Nsga2Result<T>, RunError> {
+        self.multi_objective.try_run_with_rng(rng)
+    }
+}
+
+/// Returns the fronts of the non-dominated sorting: the first front holds the agents no one dominates, the next
+/// one the agents only the previous fronts dominate, and so on
+pub(crate) fn non_dominated_sort(values: &[Vec<f64>]) -> Vec<Vec<usize>> {
+    // the agents every agent dominates and the number of agents dominating it
+    let mut dominated = vec![Vec::new(); values.len()];
+    let mut counts = vec![0; values.len()];
+    for i in 0..values.len() {
+        for j in i + 1..values.len() {
+            if dominates(&values[i], &values[j]) {
+                dominated[i].push(j);
+                counts[j] += 1;
+            } else if dominates(&values[j], &values[i]) {
+                dominated[j].push(i);
+                counts[i] += 1;
+            }
+        }
+    }
+
+    let mut fronts = Vec::new();
+    let mut front = (0..values.len()).filter(|i| counts[*i] == 0).collect::<Vec<usize>>();
+    while !front.is_empty() {
+        let mut next = Vec::new();
+        for i in &front {
+            for j in &dominated[*i] {
+                counts[*j] -= 1;
+                if counts[*j] == 0 {
+                    next.push(*j);
+                }
+            }
+        }
+        fronts.push(front);
+        front = next;
+    }
+
+    fronts
+}
+
+/// Returns the crowding distances of the agents of a front: the sum of the distances of their neighbours along
+/// every objective, scaled by the range of the objective (the agents at the ends get infinity)
+pub(crate) fn crowding_distance(values: &[Vec<f64>], front: &[usize]) -> Vec<f64> {
+    let mut distances = vec![0.0; front.len()];
+    let count = front.first().map_or(0, |i| values[*i].len());
+    let mut order = (0..front.len()).collect::<Vec<usize>>();
+    let columns = (0..count).map(|objective| front.iter().map(|i| values[*i][objective]).collect::<Vec<f64>>());
+    for column in columns {
+        order.sort_by(|a, b| column[*a].total_cmp(&column[*b]));
+        let (first, last) = (order[0], order[front.len() - 1]);
+        distances[first] = f64::INFINITY;
+        distances[last] = f64::INFINITY;
+        let range = column[last] - column[first];
+        if !(range > 0.0 && range.is_finite()) {
+            continue;
+        }
+        for k in 1..front.len() - 1 {
+            distances[order[k]] += (column[order[k + 1]] - column[order[k - 1]]) / range;
+        }
+    }
+
+    distances
+}
+
+/// The environmental selection of NSGA-II: the fronts are taken while they fit, the last one is cut by the
+/// crowding distance (the less crowded agents are kept). Returns the indices of the kept agents with their keys
+/// (the rank of their front and their negated crowding distance)
+pub(crate) fn select(values: &[Vec<f64>], size: usize) -> (Vec<usize>, Vec<Key>) {
+    let (mut chosen, mut keys) = (Vec::with_capacity(size), Vec::with_capacity(size));
+    for (rank, front) in non_dominated_sort(values).into_iter().enumerate() {
+        let distances = crowding_distance(values, &front);
+        let mut order = (0..front.len()).collect::<Vec<usize>>();
+        if chosen.len() + front.len() > size {
+            order.sort_by(|a, b| distances[*b].total_cmp(&distances[*a]));
+            order.truncate(size - chosen.len());
+        }
+        for k in order {
+            chosen.push(front[k]);
+            keys.push((rank as f64, -distances[k]));
+        }
+        if chosen.len() == size {
+            break;
+        }
+    }
+
+    (chosen, keys)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fronts() {
+        let values = vec![vec![1.0, 5.0], vec![2.0, 4.0], vec![1.0, 4.0], vec![3.0, 1.0], vec![0.0, 0.0], vec![2.0, 2.0]];
+        assert_eq!(non_dominated_sort(&values), vec![vec![0, 1, 3], vec![2, 5], vec![4]]);
+        assert_eq!(crowding_distance(&values, &[0, 1, 3]), vec![f64::INFINITY, 2.0, f64::INFINITY]);
+        assert_eq!(crowding_distance(&values, &[4]), vec![f64::INFINITY]);
+
+        let (chosen, keys) = select(&values, 4);
+        assert_eq!(chosen.len(), 4);
+        assert_eq!(chosen[..3], [0, 1, 3]);
+        assert_eq!(keys, vec![(0.0, f64::NEG_INFINITY), (0.0, -2.0), (0.0, f64::NEG_INFINITY), (1.0, f64::NEG_INFINITY)]);
+    }
+
+    #[test]
+    fn nsga2() {
+        let genetic = Genetic::builder()
+            .population(30)
+            .max_generation(40)
+            .objective(crate::Objective::Minimize)
+            .random_agent(|rng| rng.gen_range(-10.0, 10.0))
+            .fitness(|a: &f64| vec![a * a, (a - 2.0) * (a - 2.0)])
+            .mutate(|a: &f64, rng| *a + rng.gen_range(-0.5, 0.5))
+            .offspring(|a: &f64, b: &f64, _rng| (*a + *b) / 2.0)
+            .build()
+            .unwrap();
+
+        let result = Nsga2::new(genetic).run_with_seed(2);
+        assert_eq!(result.generations, 40);
+        // the children that are not changed by the operators keep the fitness of their parents
+        assert!(result.evaluations > 30 && result.evaluations <= 30 + 40 * 30);
+        assert_eq!(result.population.len(), 30);
+        // the Pareto front is 0 - 2, the crowding distance spreads the agents over it
+        assert_eq!(result.front.len(), 30);
+        assert!(result.front.iter().all(|x| (-0.05..=2.05).contains(&x.agent)));
+        assert!(result.front.iter().any(|x| x.agent < 0.2));
+        assert!(result.front.iter().any(|x| x.agent > 1.8));
+    }
+}
@@ -0,0 +1,66 @@
+use std::cmp::Ordering;
+
+use crate::Fitness;
+
+/// The direction of the optimization
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Objective {
+    /// The agents with bigger fitness are better
+    #[default]
+    Maximize,
+    /// The agents with smaller fitness are better
+    Minimize,
+}
+
+impl Objective {
+    /// Returns true if fitness `a` is strictly better than fitness `b`
+    pub fn is_better<F: PartialOrd>(&self, a: &F, b: &F) -> bool {
+        match self {
+            Objective::Maximize => a > b,
+            Objective::Minimize => a < b,
+        }
+    }
+
+    /// Orders two fitness values so that the better one comes first (for sorting)
+    /// Values that can't be compared (like NaN) are treated as equal
+    pub fn compare<F: PartialOrd>(&self, a: &F, b: &F) -> Ordering {
+        let ordering = match self {
+            Objective::Maximize => b.partial_cmp(a),
+            Objective::Minimize => a.partial_cmp(b),
+        };
+        ordering.unwrap_or(Ordering::Equal)
+    }
+
+    /// Returns the worst possible fitness
+    pub fn worst<F: Fitness>(&self) -> F {
+        match self {
+            Objective::Maximize => F::lowest(),
+            Objective::Minimize => F::highest(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn comparison() {
+        assert!(Objective::Maximize.is_better(&2.0, &1.0));
+        assert!(Objective::Minimize.is_better(&1, &2));
+        assert!(!Objective::Minimize.is_better(&1.0, &1.0));
+        assert!(Objective::Minimize.is_better(&0.0, &Objective::Minimize.worst()));
+        assert!(Objective::Maximize.is_better(&(1, 0), &(0, 5)));
+    }
+
+    #[test]
+    fn sorting() {
+        let mut fitness = vec![2.0, 3.0, 1.0];
+        fitness.sort_by(|a, b| Objective::Maximize.compare(a, b));
+        assert_eq!(fitness, vec![3.0, 2.0, 1.0]);
+        fitness.sort_by(|a, b| Objective::Minimize.compare(a, b));
+        assert_eq!(fitness, vec![1.0, 2.0, 3.0]);
+    }
+}
@@ -0,0 +1,200 @@
+use rand::prelude::*;
+
+use crate::ConfigError;
+
+/// The way one of the registered crossover or mutation operators is chosen, learning from which of them
+/// create improvements (multi-armed bandit)
+///
+/// An application of an operator is successful if one of its results is better than its parents
+/// (the agent before the mutation, the better parent of the crossover). Measuring it needs the fitness
+/// of the parents and the results, these evaluations are counted in the evaluations of the result.
+///
+/// # Examples:
+/// ```
+/// use rand::prelude::*;
+/// use bbte_optim_tzim1773_genetic::{Genetic, OperatorSelection};
+/// use bbte_optim_tzim1773_genetic::operators::{Gaussian, OnePoint, Uniform};
+///
+/// fn main() {
+///     let test = Genetic::builder()
+///         .max_generation(50)
+///         .random_agent(|rng| (0..5).map(|_| rng.gen_range(-5.0, 5.0)).collect::<Vec<f64>>())
+///         .fitness(|a: &Vec<f64>| a.iter().map(|x| x * x).sum::<f64>())
+///         .minimize()
+///         .crossover_operator("one point", OnePoint)
+///         .crossover_operator("uniform", Uniform::default())
+///         .mutation_operator("big steps", Gaussian::new(1.0))
+///         .mutation_operator("small steps", Gaussian::new(0.01))
+///         .operator_selection(OperatorSelection::ucb())
+///         .build()
+///         .unwrap();
+///
+///     let result = test.run();
+///     for stats in result.mutation_stats.iter() {
+///         println!("{}: {} of {}", stats.name, stats.successes, stats.uses);
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum OperatorSelection {
+    /// Upper confidence bound: the operator with the best success rate + c * sqrt(ln(uses of all) / uses of it)
+    /// is chosen (every operator is tried once first), bigger c explores more
+    Ucb {
+        /// Weight of the exploration (at least 0.0)
+        c: f64,
+    },
+    /// The operators are chosen randomly, with probabilities proportional to their quality (the exponential
+    /// average of their success with rate alpha), but every operator has at least p_min probability
+    ProbabilityMatching {
+        /// Smallest probability of an operator (0.0 <= p_min * number of operators <= 1.0)
+        p_min: f64,
+        /// How fast the quality follows the recent successes (0.0 < alpha <= 1.0)
+        alpha: f64,
+    },
+}
+
+impl OperatorSelection {
+    /// UCB with the usual exploration weight, sqrt(2)
+    pub fn ucb() -> Self {
+        OperatorSelection::Ucb { c: std::f64::consts::SQRT_2 }
+    }
+
+    /// Probability matching with p_min = 0.05 and alpha = 0.3
+    pub fn probability_matching() -> Self {
+        OperatorSelection::ProbabilityMatching { p_min: 0.05, alpha: 0.3 }
+    }
+
+    /// Checks whether the parameters are usable with the given number of operators
+    pub fn validate(&self, operators: usize) -> Result<(), ConfigError> {
+        match self {
+            OperatorSelection::Ucb { c } if c.is_nan() || *c < 0.0 => {
+                Err(ConfigError::InvalidOperatorSelection(format!("c can't be negative, got {}", c)))
+            }
+            OperatorSelection::ProbabilityMatching { p_min, alpha } => {
+                if !(*p_min >= 0.0 && p_min * operators as f64 <= 1.0) {
+                    return Err(ConfigError::InvalidOperatorSelection(format!("p_min must be between 0.0 and 1 / operators, got {}", p_min)));
+                }
+                if !(*alpha > 0.0 && *alpha <= 1.0) {
+                    return Err(ConfigError::InvalidOperatorSelection(format!("alpha must be between 0.0 and 1.0, got {}", alpha)));
+                }
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// How many times an operator was used during a run and how many of them were successful
+/// (successes are only counted if an OperatorSelection is given)
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OperatorStats {
+    /// The name the operator was registered with
+    pub name: String,
+    /// Number of applications
+    pub uses: usize,
+    /// Number of applications that created an improvement
+    pub successes: usize,
+}
+
+/// Chooses the operators of a run and collects their statistics
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct Bandit {
+    strategy: Option<OperatorSelection>,
+    stats: Vec<OperatorStats>,
+    quality: Vec<f64>,
+}
+
+impl Bandit {
+    pub fn new(names: Vec<String>, strategy: Option<OperatorSelection>) -> Self {
+        let quality = vec![1.0; names.len()];
+        let stats = names.into_iter()
+            .map(|name| OperatorStats { name, uses: 0, successes: 0 })
+            .collect();
+        Bandit { strategy, stats, quality }
+    }
+
+    /// Whether the successes of the operators have to be measured
+    pub fn learns(&self) -> bool {
+        self.strategy.is_some()
+    }
+
+    /// Returns the index of the operator to apply
+    pub fn choose(&self, rng: &mut dyn RngCore) -> usize {
+        let k = self.stats.len();
+        if k <= 1 {
+            return 0;
+        }
+        match self.strategy {
+            None => rng.gen_range(0, k),
+            Some(OperatorSelection::Ucb { c }) => {
+                if let Some(untried) = self.stats.iter().position(|s| s.uses == 0) {
+                    return untried;
+                }
+                let total = self.stats.iter().map(|s| s.uses).sum::<usize>() as f64;
+                let score = |s: &OperatorStats| s.successes as f64 / s.uses as f64 + c * (total.ln() / s.uses as f64).sqrt();
+                (1..k).fold(0, |best, i| if score(&self.stats[i]) > score(&self.stats[best]) { i } else { best })
+            }
+            Some(OperatorSelection::ProbabilityMatching { p_min, .. }) => {
+                let sum = self.quality.iter().sum::<f64>();
+                let mut r = rng.gen_range(0.0, 1.0);
+                for (i, q) in self.quality.iter().enumerate() {
+                    let p = if sum > 0.0 { p_min + (1.0 - k as f64 * p_min) * q / sum } else { 1.0 / k as f64 };
+                    if r < p {
+                        return i;
+                    }
+                    r -= p;
+                }
+                k - 1
+            }
+        }
+    }
+
+    /// Records an application of the i-th operator (success is None if it wasn't measured)
+    pub fn record(&mut self, i: usize, success: Option<bool>) {
+        self.stats[i].uses += 1;
+        if let Some(success) = success {
+            if success {
+                self.stats[i].successes += 1;
+            }
+            if let Some(OperatorSelection::ProbabilityMatching { alpha, .. }) = self.strategy {
+                let reward = if success { 1.0 } else { 0.0 };
+                self.quality[i] += alpha * (reward - self.quality[i]);
+            }
+        }
+    }
+
+    pub fn into_stats(self) -> Vec<OperatorStats> {
+        self.stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn learn(strategy: OperatorSelection) -> Vec<OperatorStats> {
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut bandit = Bandit::new(vec!["bad".to_string(), "good".to_string()], Some(strategy));
+        for _ in 0..1000 {
+            let i = bandit.choose(&mut rng);
+            let success = rng.gen_range(0.0, 1.0) < if i == 0 { 0.1 } else { 0.6 };
+            bandit.record(i, Some(success));
+        }
+        bandit.into_stats()
+    }
+
+    #[test]
+    fn bandits() {
+        for strategy in [OperatorSelection::ucb(), OperatorSelection::probability_matching()].iter() {
+            let stats = learn(*strategy);
+            assert!(stats[1].uses > 3 * stats[0].uses);
+            assert!(stats[0].uses > 0);
+        }
+        assert!(OperatorSelection::Ucb { c: -1.0 }.validate(2).is_err());
+        assert!(OperatorSelection::ProbabilityMatching { p_min: 0.6, alpha: 0.3 }.validate(2).is_err());
+        assert!(OperatorSelection::probability_matching().validate(2).is_ok());
+    }
+}
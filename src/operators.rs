@@ -0,0 +1,428 @@
+//! Crossover and mutation operators for the common genome types
+//!
+//! They can be given to the builder with crossover() and mutation() instead of hand-written functions.
+//!
+//! # Examples:
+//! ```
+//! use rand::prelude::*;
+//! use bbte_optim_tzim1773_genetic::Genetic;
+//! use bbte_optim_tzim1773_genetic::operators::{BitFlip, TwoPoint};
+//!
+//! fn main() {
+//!     let test = Genetic::builder()
+//!         .population(20)
+//!         .max_generation(50)
+//!         .random_agent(|rng| (0..20).map(|_| rng.gen::<bool>()).collect::<Vec<bool>>())
+//!         .fitness(|a: &Vec<bool>| a.iter().filter(|b| **b).count())
+//!         .crossover(TwoPoint)
+//!         .mutation(BitFlip::new(0.05))
+//!         .build()
+//!         .unwrap();
+//!
+//!     println!("{}", test.run().best_fitness); // should be close to 20
+//! }
+//! ```
+use rand::prelude::*;
+use std::marker::PhantomData;
+
+use crate::MutateFn;
+
+/// Operator that crossovers two agents and creates offspring
+pub trait Crossover<T> {
+    /// Creates offspring (one or more) from the two parents
+    fn cross(&self, a: &T, b: &T, rng: &mut dyn RngCore) -> Vec<T>;
+}
+
+/// Operator that mutates an agent
+pub trait Mutation<T> {
+    /// Returns the mutated version of the agent
+    fn mutate(&self, agent: &T, rng: &mut dyn RngCore) -> T;
+}
+
+/// Functions with the signature of a crossover can be used as operators
+impl<T, C> Crossover<T> for C
+    where C: Fn(&T, &T, &mut dyn RngCore) -> Vec<T> {
+    fn cross(&self, a: &T, b: &T, rng: &mut dyn RngCore) -> Vec<T> {
+        self(a, b, rng)
+    }
+}
+
+/// Functions with the signature of a mutation can be used as operators
+impl<T, M> Mutation<T> for M
+    where M: Fn(&T, &mut dyn RngCore) -> T {
+    fn mutate(&self, agent: &T, rng: &mut dyn RngCore) -> T {
+        self(agent, rng)
+    }
+}
+
+/// Returns a number from the standard normal distribution (Box-Muller transform)
+pub(crate) fn standard_normal(rng: &mut dyn RngCore) -> f64 {
+    let u1 = 1.0 - rng.gen::<f64>();
+    let u2 = rng.gen::<f64>();
+    (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+}
+
+/// The genes after a random cut point are swapped (creates two children)
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct OnePoint;
+
+impl<G: Clone> Crossover<Vec<G>> for OnePoint {
+    fn cross(&self, a: &Vec<G>, b: &Vec<G>, rng: &mut dyn RngCore) -> Vec<Vec<G>> {
+        let len = a.len().min(b.len());
+        let cut = rng.gen_range(0, len + 1);
+
+        vec![
+            a[..cut].iter().chain(b[cut..].iter()).cloned().collect(),
+            b[..cut].iter().chain(a[cut..].iter()).cloned().collect(),
+        ]
+    }
+}
+
+/// The genes between two random cut points are swapped (creates two children)
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct TwoPoint;
+
+impl<G: Clone> Crossover<Vec<G>> for TwoPoint {
+    fn cross(&self, a: &Vec<G>, b: &Vec<G>, rng: &mut dyn RngCore) -> Vec<Vec<G>> {
+        let len = a.len().min(b.len());
+        let mut first = rng.gen_range(0, len + 1);
+        let mut second = rng.gen_range(0, len + 1);
+        if first > second {
+            std::mem::swap(&mut first, &mut second);
+        }
+
+        let (mut x, mut y) = (a.clone(), b.clone());
+        x[first..second].clone_from_slice(&b[first..second]);
+        y[first..second].clone_from_slice(&a[first..second]);
+        vec![x, y]
+    }
+}
+
+/// Every gene is swapped with the given probability (creates two children)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Uniform {
+    /// Probability of swapping a gene (0.5 mixes the parents evenly)
+    pub probability: f64,
+}
+
+impl Default for Uniform {
+    fn default() -> Self {
+        Uniform { probability: 0.5 }
+    }
+}
+
+impl<G: Clone> Crossover<Vec<G>> for Uniform {
+    fn cross(&self, a: &Vec<G>, b: &Vec<G>, rng: &mut dyn RngCore) -> Vec<Vec<G>> {
+        let (mut x, mut y) = (a.clone(), b.clone());
+        for i in 0..a.len().min(b.len()) {
+            if rng.gen_range(0.0, 1.0) < self.probability {
+                x[i] = b[i].clone();
+                y[i] = a[i].clone();
+            }
+        }
+        vec![x, y]
+    }
+}
+
+/// The children are weighted averages of the parents: alpha * a + (1 - alpha) * b and (1 - alpha) * a + alpha * b
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Arithmetic {
+    /// The weight of the first parent, if it's None a random weight is chosen for every crossover
+    pub alpha: Option<f64>,
+}
+
+impl Crossover<Vec<f64>> for Arithmetic {
+    fn cross(&self, a: &Vec<f64>, b: &Vec<f64>, rng: &mut dyn RngCore) -> Vec<Vec<f64>> {
+        let alpha = self.alpha.unwrap_or_else(|| rng.gen_range(0.0, 1.0));
+        let mix = |alpha: f64| a.iter()
+            .zip(b.iter())
+            .map(|(x, y)| alpha * x + (1.0 - alpha) * y)
+            .collect::<Vec<f64>>();
+
+        vec![mix(alpha), mix(1.0 - alpha)]
+    }
+}
+
+/// Every bit is flipped with the given probability
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BitFlip {
+    /// Probability of flipping a bit (usually 1 / length)
+    pub probability: f64,
+}
+
+impl BitFlip {
+    /// Flips every bit with the given probability
+    pub fn new(probability: f64) -> Self {
+        BitFlip { probability }
+    }
+
+    /// Flips one bit on average in a genome of `len` bits (probability = 1 / len)
+    pub fn per_bit_of(len: usize) -> Self {
+        BitFlip { probability: 1.0 / len.max(1) as f64 }
+    }
+}
+
+impl Mutation<Vec<bool>> for BitFlip {
+    fn mutate(&self, agent: &Vec<bool>, rng: &mut dyn RngCore) -> Vec<bool> {
+        agent.iter()
+            .map(|b| if rng.gen_range(0.0, 1.0) < self.probability { !b } else { *b })
+            .collect()
+    }
+}
+
+/// Normally distributed noise is added to the genes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Gaussian {
+    /// Standard deviation of the noise
+    pub sigma: f64,
+    /// Probability of changing a gene (1.0 changes every gene)
+    pub probability: f64,
+}
+
+impl Gaussian {
+    /// Adds noise with the given standard deviation to every gene
+    pub fn new(sigma: f64) -> Self {
+        Gaussian { sigma, probability: 1.0 }
+    }
+}
+
+impl Mutation<Vec<f64>> for Gaussian {
+    fn mutate(&self, agent: &Vec<f64>, rng: &mut dyn RngCore) -> Vec<f64> {
+        agent.iter()
+            .map(|x| {
+                if rng.gen_range(0.0, 1.0) < self.probability {
+                    return x + self.sigma * standard_normal(rng);
+                }
+                *x
+            })
+            .collect()
+    }
+}
+
+impl Mutation<f64> for Gaussian {
+    fn mutate(&self, agent: &f64, rng: &mut dyn RngCore) -> f64 {
+        agent + self.sigma * standard_normal(rng)
+    }
+}
+
+/// Two random genes are swapped (keeps permutations valid)
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Swap;
+
+impl<G: Clone> Mutation<Vec<G>> for Swap {
+    fn mutate(&self, agent: &Vec<G>, rng: &mut dyn RngCore) -> Vec<G> {
+        let mut x = agent.clone();
+        if x.len() > 1 {
+            let i = rng.gen_range(0, x.len());
+            let j = rng.gen_range(0, x.len());
+            x.swap(i, j);
+        }
+        x
+    }
+}
+
+/// k-point crossover for any slice-like genome (Vec, fixed size arrays, ...): the genome is cut at k random
+/// points and every second segment is swapped (creates two children)
+///
+/// # Examples:
+/// ```
+/// use rand::prelude::*;
+/// use bbte_optim_tzim1773_genetic::operators::{Crossover, KPoint};
+///
+/// fn main() {
+///     let children = KPoint::new(3).cross(&[0; 8], &[1; 8], &mut thread_rng());
+///     println!("{:?}", children);
+/// }
+/// ```
+#[derive(Debug)]
+pub struct KPoint<G> {
+    /// Number of cut points
+    pub k: usize,
+    gene: PhantomData<fn() -> G>,
+}
+
+impl<G> KPoint<G> {
+    /// Crossover with k cut points
+    pub fn new(k: usize) -> Self {
+        KPoint { k, gene: PhantomData }
+    }
+}
+
+impl<G> Clone for KPoint<G> {
+    fn clone(&self) -> Self {
+        KPoint::new(self.k)
+    }
+}
+
+impl<T, G> Crossover<T> for KPoint<G>
+    where T: AsRef<[G]> + AsMut<[G]> + Clone, G: Clone {
+    fn cross(&self, a: &T, b: &T, rng: &mut dyn RngCore) -> Vec<T> {
+        let len = a.as_ref().len().min(b.as_ref().len());
+        let mut cuts = (0..self.k)
+            .map(|_| rng.gen_range(0, len + 1))
+            .collect::<Vec<usize>>();
+        cuts.sort_unstable();
+        cuts.push(len);
+
+        let (mut x, mut y) = (a.clone(), b.clone());
+        let mut start = 0;
+        for (i, end) in cuts.into_iter().enumerate() {
+            if i % 2 == 1 {
+                x.as_mut()[start..end].clone_from_slice(&b.as_ref()[start..end]);
+                y.as_mut()[start..end].clone_from_slice(&a.as_ref()[start..end]);
+            }
+            start = end;
+        }
+        vec![x, y]
+    }
+}
+
+/// Uniform crossover for any slice-like genome (Vec, fixed size arrays, ...): every gene is swapped
+/// with the given probability (creates two children)
+#[derive(Debug)]
+pub struct SliceUniform<G> {
+    /// Probability of swapping a gene (0.5 mixes the parents evenly)
+    pub probability: f64,
+    gene: PhantomData<fn() -> G>,
+}
+
+impl<G> SliceUniform<G> {
+    /// Crossover swapping every gene with the given probability
+    pub fn new(probability: f64) -> Self {
+        SliceUniform { probability, gene: PhantomData }
+    }
+}
+
+impl<G> Clone for SliceUniform<G> {
+    fn clone(&self) -> Self {
+        SliceUniform::new(self.probability)
+    }
+}
+
+impl<T, G> Crossover<T> for SliceUniform<G>
+    where T: AsRef<[G]> + AsMut<[G]> + Clone, G: Clone {
+    fn cross(&self, a: &T, b: &T, rng: &mut dyn RngCore) -> Vec<T> {
+        let (mut x, mut y) = (a.clone(), b.clone());
+        let len = a.as_ref().len().min(b.as_ref().len());
+        for i in 0..len {
+            if rng.gen_range(0.0, 1.0) < self.probability {
+                x.as_mut()[i] = b.as_ref()[i].clone();
+                y.as_mut()[i] = a.as_ref()[i].clone();
+            }
+        }
+        vec![x, y]
+    }
+}
+
+/// Mutation of any slice-like genome (Vec, fixed size arrays, ...) that changes every gene
+/// with the given probability using a mutation of a single gene
+///
+/// # Examples:
+/// ```
+/// use rand::prelude::*;
+/// use bbte_optim_tzim1773_genetic::operators::{Gaussian, Mutation, PerGene};
+///
+/// fn main() {
+///     let add_one = PerGene::new(0.5, |g: &i32, _rng: &mut dyn RngCore| g + 1);
+///     let noise = PerGene::from_operator(1.0, Gaussian::new(0.1));
+///
+///     println!("{:?}", add_one.mutate(&vec![0; 5], &mut thread_rng()));
+///     println!("{:?}", noise.mutate(&[0.0; 3], &mut thread_rng()));
+/// }
+/// ```
+pub struct PerGene<'a, G> {
+    /// Probability of mutating a gene
+    pub probability: f64,
+    f_mutate: MutateFn<'a, G>,
+}
+
+impl<'a, G> PerGene<'a, G> {
+    /// Mutates every gene with the given probability using the given function
+    pub fn new(probability: f64, f: impl Fn(&G, &mut dyn RngCore) -> G + Send + Sync + 'a) -> Self {
+        PerGene { probability, f_mutate: Box::new(f) }
+    }
+
+    /// Mutates every gene with the given probability using a mutation operator of genes
+    pub fn from_operator<M>(probability: f64, mutation: M) -> Self
+        where M: Mutation<G> + Send + Sync + 'a {
+        PerGene::new(probability, move |g, rng| mutation.mutate(g, rng))
+    }
+}
+
+impl<'a, T, G> Mutation<T> for PerGene<'a, G>
+    where T: AsRef<[G]> + AsMut<[G]> + Clone {
+    fn mutate(&self, agent: &T, rng: &mut dyn RngCore) -> T {
+        let mut x = agent.clone();
+        for g in x.as_mut().iter_mut() {
+            if rng.gen_range(0.0, 1.0) < self.probability {
+                *g = (self.f_mutate)(g, rng);
+            }
+        }
+        x
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crossovers_keep_genes() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let a = vec![0; 10];
+        let b = vec![1; 10];
+        let operators: Vec<Box<dyn Crossover<Vec<i32>>>> = vec![Box::new(OnePoint), Box::new(TwoPoint), Box::new(Uniform::default())];
+
+        for operator in operators.iter() {
+            for _ in 0..20 {
+                let children = operator.cross(&a, &b, &mut rng);
+                assert_eq!(children.len(), 2);
+                // every gene comes from one parent, the other child gets the other one
+                assert!(children[0].iter().zip(children[1].iter()).all(|(x, y)| x + y == 1));
+            }
+        }
+    }
+
+    #[test]
+    fn slice_operators() {
+        let mut rng = StdRng::seed_from_u64(1);
+
+        for k in 0..5 {
+            let children = KPoint::new(k).cross(&[0; 10], &[1; 10], &mut rng);
+            assert!(children[0].iter().zip(children[1].iter()).all(|(x, y)| x + y == 1));
+            // a cut at the same place twice can remove segments, but there are at most k + 1 of them
+            let segments = 1 + children[0].windows(2).filter(|w| w[0] != w[1]).count();
+            assert!(segments <= k + 1);
+        }
+        let children = SliceUniform::new(0.5).cross(&vec![0; 10], &vec![1; 10], &mut rng);
+        assert!(children[0].iter().zip(children[1].iter()).all(|(x, y)| x + y == 1));
+
+        assert_eq!(PerGene::new(1.0, |g: &i32, _rng| g + 1).mutate(&[1, 2, 3], &mut rng), [2, 3, 4]);
+        assert_eq!(PerGene::new(0.0, |g: &i32, _rng| g + 1).mutate(&vec![1, 2, 3], &mut rng), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn arithmetic() {
+        let children = Arithmetic { alpha: Some(0.25) }.cross(&vec![0.0, 4.0], &vec![4.0, 0.0], &mut thread_rng());
+        assert_eq!(children, vec![vec![3.0, 1.0], vec![1.0, 3.0]]);
+    }
+
+    #[test]
+    fn mutations() {
+        let mut rng = StdRng::seed_from_u64(1);
+        assert_eq!(BitFlip::new(1.0).mutate(&vec![true, false], &mut rng), vec![false, true]);
+        assert_eq!(BitFlip::new(0.0).mutate(&vec![true, false], &mut rng), vec![true, false]);
+
+        let mut swapped = Swap.mutate(&vec![1, 2, 3, 4], &mut rng);
+        swapped.sort();
+        assert_eq!(swapped, vec![1, 2, 3, 4]);
+
+        let noise = (0..1000)
+            .map(|_| Mutation::<f64>::mutate(&Gaussian::new(2.0), &0.0, &mut rng))
+            .collect::<Vec<f64>>();
+        let mean = noise.iter().sum::<f64>() / 1000.0;
+        let variance = noise.iter().map(|x| (x - mean) * (x - mean)).sum::<f64>() / 1000.0;
+        assert!(mean.abs() < 0.3);
+        assert!((variance.sqrt() - 2.0).abs() < 0.3);
+    }
+}
@@ -0,0 +1,151 @@
+use rand::prelude::*;
+
+use crate::error::EvaluationBudget;
+use crate::{ConfigError, Evaluated, Fitness, FitnessError, Genetic, MaybeSync};
+
+/// Function that returns the opposite of an agent (e.g. lower + upper - x for every value of a real vector)
+pub type OppositeFn<'a, T> = Box<dyn Fn(&T) -> T + Send + Sync + 'a>;
+
+/// Opposition-based learning: the opposites of the agents of the first generation are evaluated too and the fitter
+/// agent of every pair is kept, and after a generation (with `jumping_rate` probability) every agent is replaced by
+/// its opposite if the opposite is fitter (generation jumping)
+///
+/// A random guess is as likely to be worse than its opposite as to be better, so looking at both is a cheap way to
+/// start closer to the optimum. The opposites are evaluated with f_fitness and count as evaluations (the ones of the
+/// first generation as well), and the agents are compared by the feasibility rules if there are constraints.
+///
+/// # Examples:
+/// ```
+/// use rand::prelude::*;
+/// use bbte_optim_tzim1773_genetic::{Genetic, Opposition};
+/// use bbte_optim_tzim1773_genetic::genome::{Bounds, PolynomialMutation, RealVector, Sbx};
+///
+/// fn main() {
+///     let bounds = Bounds::uniform(10, -5.0, 5.0);
+///     let test = Genetic::builder()
+///         .population(50)
+///         .max_generation(100)
+///         .random_agent(move |rng| RealVector::random(&bounds, rng))
+///         .fitness(|a: &RealVector| a.iter().map(|x| (x - 4.0) * (x - 4.0)).sum::<f64>())
+///         .minimize()
+///         .crossover(Sbx::new(15.0))
+///         .mutation(PolynomialMutation::new(20.0))
+///         .opposition(Opposition::new(RealVector::opposite).jumping_rate(0.3))
+///         .build()
+///         .unwrap();
+///
+///     println!("{:?}", test.run().best.values); // should be close to 4.0 in every dimension
+/// }
+/// ```
+pub struct Opposition<'a, T> {
+    /// The probability of generation jumping after a generation (0.0 - 1.0), with 0.0 only the first generation is
+    /// opposed
+    /// Suggested value: 0.3
+    pub jumping_rate: f64,
+    f_opposite: OppositeFn<'a, T>,
+}
+
+impl<'a, T> Opposition<'a, T> {
+    /// Opposition-based initialization with the given opposite function (without generation jumping)
+    pub fn new(f_opposite: impl Fn(&T) -> T + Send + Sync + 'a) -> Self {
+        Opposition { jumping_rate: 0.0, f_opposite: Box::new(f_opposite) }
+    }
+
+    /// Sets the probability of generation jumping
+    pub fn jumping_rate(mut self, jumping_rate: f64) -> Self {
+        self.jumping_rate = jumping_rate;
+        self
+    }
+
+    /// Checks whether the parameters are usable
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if !(0.0..=1.0).contains(&self.jumping_rate) {
+            return Err(ConfigError::InvalidOpposition(format!("jumping_rate must be between 0.0 and 1.0, got {}", self.jumping_rate)));
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a, T, F> Genetic<'a, T, F>
+    where T: Clone + MaybeSync, F: Fitness + MaybeSync {
+    /// Returns the agents of the first generation followed by their opposites (if opposition-based learning is used)
+    pub(crate) fn with_opposites(&self, mut xg: Vec<T>) -> Vec<T> {
+        if let Some(opposition) = &self.opposition {
+            let opposites = xg.iter().map(|x| (opposition.f_opposite)(x)).collect::<Vec<T>>();
+            xg.extend(opposites);
+        }
+        xg
+    }
+
+    /// Keeps the fitter agent of every agent and opposite pair of the first generation (returned by with_opposites(),
+    /// the agents and opposites that didn't fit into max_evaluations are missing from its end)
+    pub(crate) fn fitter_of_pairs(&self, mut xg: Vec<Evaluated<T, F>>) -> Vec<Evaluated<T, F>> {
+        if self.opposition.is_none() {
+            return xg;
+        }
+        let opposites = xg.split_off(self.population.min(xg.len()));
+        for (x, opposite) in xg.iter_mut().zip(opposites) {
+            if self.is_fitter(&opposite, x) {
+                *x = opposite;
+            }
+        }
+        xg
+    }
+
+    /// Generation jumping: with jumping_rate probability every agent is replaced by its opposite if it's fitter (the
+    /// agents are kept if the opposites don't fit into max_evaluations)
+    pub(crate) fn jump(&self, xg: &mut [Evaluated<T, F>], evaluations: &mut usize, rng: &mut dyn RngCore) -> Result<(), FitnessError> {
+        let opposition = match &self.opposition {
+            Some(opposition) if rng.gen::<f64>() < opposition.jumping_rate => opposition,
+            _ => return Ok(()),
+        };
+        let opposites = xg.iter().map(|x| ((opposition.f_opposite)(&x.agent), None)).collect();
+        let opposites = match self.evaluate_offspring(opposites, evaluations) {
+            Ok(opposites) => opposites,
+            Err(error) if error.is::<EvaluationBudget>() => return Ok(()),
+            Err(error) => return Err(error),
+        };
+        for (x, opposite) in xg.iter_mut().zip(opposites) {
+            if self.is_fitter(&opposite, x) {
+                *x = opposite;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn opposition() {
+        assert!(Opposition::new(|a: &f64| -a).jumping_rate(1.5).validate().is_err());
+
+        // the random agents are all on the wrong side, their opposites are close to the optimum
+        let builder = || Genetic::builder()
+            .population(10)
+            .max_generation(1)
+            .pc(0.0)
+            .pm(0.0)
+            .random_agent(|rng| rng.gen_range(-10.0, -6.0))
+            .fitness(|a: &f64| -(a - 8.0) * (a - 8.0))
+            .mutate(|a: &f64, _rng| *a)
+            .offspring(|a: &f64, _b: &f64, _rng| *a);
+
+        let plain = builder().build().unwrap().run_with_seed(1);
+        assert!(plain.best < 0.0);
+        let opposed = builder().opposition(Opposition::new(|a: &f64| -a)).build().unwrap().run_with_seed(1);
+        assert!(opposed.population.iter().all(|a| *a > 0.0));
+        assert_eq!(opposed.evaluations, 20);
+
+        let test = builder().opposition(Opposition::new(|a: &f64| -a).jumping_rate(1.0)).build().unwrap();
+        let mut xg = vec![Evaluated::new(-7.0, -225.0), Evaluated::new(9.0, -1.0)];
+        let mut evaluations = 0;
+        test.jump(&mut xg, &mut evaluations, &mut thread_rng()).unwrap();
+        assert_eq!(xg, vec![Evaluated::new(7.0, -1.0), Evaluated::new(9.0, -1.0)]);
+        assert_eq!(evaluations, 2);
+    }
+}
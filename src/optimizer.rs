@@ -0,0 +1,408 @@
+use rand::prelude::*;
+use std::mem;
+use std::ops::Deref;
+
+use crate::eda::{Eda, Model};
+use crate::genome::Permutation;
+use crate::problem;
+use crate::{
+    AgentFn, AntColony, AnnealingResult, BaselineResult, BeeColony, DifferentialEvolution, Fitness, FitnessFn, Genetic, HillClimber, MaybeSync, MutateFn, Objective,
+    OffspringFn, ParticleSwarm, Problem, RandomSearch, RunError, RunResult, SimulatedAnnealing, TabuSearch, Termination,
+};
+
+/// The common interface of the algorithms (the genetic algorithm itself, simulated annealing, hill climbing, random
+/// search, tabu search, differential evolution, particle swarm, estimation of distribution, ant colony and bee colony),
+/// so benchmarks and applications can swap them as generics or as `Box<dyn Optimizer<T, F>>`, and run one optimizer
+/// on several problems
+///
+/// optimize() runs the algorithm on the given problem: for the run the Genetic of the algorithm gets the four
+/// functions and the objective of the problem and max_evaluations is set to the budget, afterwards (also if the run
+/// panics) the Genetic gets its own functions, objective and max_evaluations back (the fitness cache is cleared before and after the run, the
+/// cached values belong to another problem). The other settings of the Genetic stay in effect (e.g. repair, the
+/// registered operators, the bounds of differential evolution or the costs of the ant colony), so max_generation
+/// should be high enough for the budget. The algorithms returning their own result types are converted to a
+/// RunResult: the population is their last agent, the generations are the agents created divided by the population,
+/// the history is empty and the termination is the one of their result.
+///
+/// # Examples:
+/// ```
+/// use bbte_optim_tzim1773_genetic::benchmarks::{Benchmark, Function};
+/// use bbte_optim_tzim1773_genetic::{Ascent, DeStrategy, DifferentialEvolution, Genetic, HillClimber, Optimizer};
+///
+/// fn main() {
+///     let (sphere, rastrigin) = (Benchmark::new(Function::Sphere, 3), Benchmark::new(Function::Rastrigin, 3));
+///     let genetic = || Genetic::from_problem(20, 1000, 0.7, 0.3, Benchmark::new(Function::Sphere, 3));
+///
+///     let mut optimizers: Vec<Box<dyn Optimizer<Vec<f64>>>> = vec![
+///         Box::new(genetic()),
+///         Box::new(HillClimber::new(genetic(), Ascent::First { tries: 20 }, usize::MAX).unwrap()),
+///         Box::new(DifferentialEvolution::new(genetic(), DeStrategy::Rand1Bin, 0.5, 0.9).unwrap()),
+///     ];
+///     for problem in [&sphere, &rastrigin] {
+///         for optimizer in &mut optimizers {
+///             let result = optimizer.optimize_with_seed(problem, 2000, 1).unwrap();
+///             println!("{} on {:?}: {}", optimizer.name(), problem.function, result.best_fitness);
+///         }
+///     }
+/// }
+/// ```
+pub trait Optimizer<'a, T, F = f64> {
+    /// Returns the name of the algorithm (e.g. for the tables of benchmarks)
+    fn name(&self) -> &'static str;
+    /// Runs the algorithm on the problem with at most `budget` evaluations of the fitness function and the given
+    /// random number generator
+    fn optimize(&mut self, problem: &'a (dyn Problem<T, F> + Sync), budget: usize, rng: &mut dyn RngCore) -> Result<RunResult<T, F>, RunError>;
+    /// Same as optimize(), but uses a random number generator seeded with the given seed, so the runs can be
+    /// reproduced
+    fn optimize_with_seed(&mut self, problem: &'a (dyn Problem<T, F> + Sync), budget: usize, seed: u64) -> Result<RunResult<T, F>, RunError> {
+        self.optimize(problem, budget, &mut StdRng::seed_from_u64(seed))
+    }
+}
+
+/// The functions, the objective and max_evaluations of a Genetic, replaced by the problem and the budget of optimize()
+struct Replaced<'a, T, F> {
+    get_random_agent: AgentFn<'a, T>,
+    f_fitness: FitnessFn<'a, T, F>,
+    f_mutate: MutateFn<'a, T>,
+    f_offspring: OffspringFn<'a, T>,
+    objective: Objective,
+    max_evaluations: Option<usize>,
+}
+
+impl<'a, T, F> Replaced<'a, T, F> {
+    /// Gives the problem and the budget to the Genetic and returns what they replaced
+    fn install(genetic: &mut Genetic<'a, T, F>, problem: &'a (dyn Problem<T, F> + Sync), budget: usize) -> Self {
+        let (get_random_agent, f_fitness, f_mutate, f_offspring) = problem::into_functions(problem);
+        if let Some(cache) = &genetic.fitness_cache {
+            cache.clear();
+        }
+
+        Replaced {
+            get_random_agent: mem::replace(&mut genetic.get_random_agent, get_random_agent),
+            f_fitness: mem::replace(&mut genetic.f_fitness, f_fitness),
+            f_mutate: mem::replace(&mut genetic.f_mutate, f_mutate),
+            f_offspring: mem::replace(&mut genetic.f_offspring, f_offspring),
+            objective: mem::replace(&mut genetic.objective, problem.objective()),
+            max_evaluations: genetic.max_evaluations.replace(budget),
+        }
+    }
+
+    /// Gives the Genetic its own functions, objective and max_evaluations back
+    fn restore(self, genetic: &mut Genetic<'a, T, F>) {
+        if let Some(cache) = &genetic.fitness_cache {
+            cache.clear();
+        }
+        genetic.get_random_agent = self.get_random_agent;
+        genetic.f_fitness = self.f_fitness;
+        genetic.f_mutate = self.f_mutate;
+        genetic.f_offspring = self.f_offspring;
+        genetic.objective = self.objective;
+        genetic.max_evaluations = self.max_evaluations;
+    }
+}
+
+/// Gives the problem and the budget to the Genetic of an optimizer for a run, the Genetic gets its own functions,
+/// objective and max_evaluations back when the guard is dropped (so also if the run panics)
+struct Installed<'o, 'a, O, T, F> {
+    optimizer: &'o mut O,
+    genetic: fn(&mut O) -> &mut Genetic<'a, T, F>,
+    replaced: Option<Replaced<'a, T, F>>,
+}
+
+impl<'o, 'a, O, T, F> Installed<'o, 'a, O, T, F> {
+    fn new(optimizer: &'o mut O, genetic: fn(&mut O) -> &mut Genetic<'a, T, F>, problem: &'a (dyn Problem<T, F> + Sync), budget: usize) -> Self {
+        let replaced = Replaced::install(genetic(optimizer), problem, budget);
+        Installed { optimizer, genetic, replaced: Some(replaced) }
+    }
+}
+
+impl<O, T, F> Deref for Installed<'_, '_, O, T, F> {
+    type Target = O;
+
+    fn deref(&self) -> &O {
+        self.optimizer
+    }
+}
+
+impl<O, T, F> Drop for Installed<'_, '_, O, T, F> {
+    fn drop(&mut self) {
+        if let Some(replaced) = self.replaced.take() {
+            replaced.restore((self.genetic)(self.optimizer));
+        }
+    }
+}
+
+impl<'a, T, F> Optimizer<'a, T, F> for Genetic<'a, T, F>
+    where T: Clone + MaybeSync, F: Fitness + MaybeSync {
+    fn name(&self) -> &'static str {
+        "genetic algorithm"
+    }
+
+    fn optimize(&mut self, problem: &'a (dyn Problem<T, F> + Sync), budget: usize, mut rng: &mut dyn RngCore) -> Result<RunResult<T, F>, RunError> {
+        Installed::new(self, |genetic| genetic, problem, budget).try_run_with_rng(&mut rng)
+    }
+}
+
+impl<'a, T, F> Optimizer<'a, T, F> for SimulatedAnnealing<'a, T, F>
+    where T: Clone + MaybeSync, F: Fitness + MaybeSync {
+    fn name(&self) -> &'static str {
+        "simulated annealing"
+    }
+
+    fn optimize(&mut self, problem: &'a (dyn Problem<T, F> + Sync), budget: usize, rng: &mut dyn RngCore) -> Result<RunResult<T, F>, RunError> {
+        let result = Installed::new(self, |optimizer| &mut optimizer.genetic, problem, budget).try_run_with_rng(rng);
+        let AnnealingResult { best, best_fitness, current, iterations, evaluations, termination, .. } = result?;
+        Ok(converted(&self.genetic, best, best_fitness, current, iterations, evaluations, 0, termination))
+    }
+}
+
+impl<'a, T, F> Optimizer<'a, T, F> for HillClimber<'a, T, F>
+    where T: Clone + MaybeSync, F: Fitness + MaybeSync {
+    fn name(&self) -> &'static str {
+        "hill climbing"
+    }
+
+    fn optimize(&mut self, problem: &'a (dyn Problem<T, F> + Sync), budget: usize, rng: &mut dyn RngCore) -> Result<RunResult<T, F>, RunError> {
+        let result = Installed::new(self, |optimizer| &mut optimizer.genetic, problem, budget).try_run_with_rng(rng);
+        let BaselineResult { best, best_fitness, samples, evaluations, restarts, termination, .. } = result?;
+        Ok(converted(&self.genetic, best.clone(), best_fitness, best, samples, evaluations, restarts, termination))
+    }
+}
+
+impl<'a, T, F> Optimizer<'a, T, F> for RandomSearch<'a, T, F>
+    where T: Clone + MaybeSync, F: Fitness + MaybeSync {
+    fn name(&self) -> &'static str {
+        "random search"
+    }
+
+    fn optimize(&mut self, problem: &'a (dyn Problem<T, F> + Sync), budget: usize, rng: &mut dyn RngCore) -> Result<RunResult<T, F>, RunError> {
+        let result = Installed::new(self, |optimizer| &mut optimizer.genetic, problem, budget).try_run_with_rng(rng);
+        let BaselineResult { best, best_fitness, samples, evaluations, termination, .. } = result?;
+        Ok(converted(&self.genetic, best.clone(), best_fitness, best, samples, evaluations, 0, termination))
+    }
+}
+
+impl<'a, T, M, F> Optimizer<'a, T, F> for TabuSearch<'a, T, M, F>
+    where T: Clone + MaybeSync, M: PartialEq, F: Fitness + MaybeSync {
+    fn name(&self) -> &'static str {
+        "tabu search"
+    }
+
+    fn optimize(&mut self, problem: &'a (dyn Problem<T, F> + Sync), budget: usize, rng: &mut dyn RngCore) -> Result<RunResult<T, F>, RunError> {
+        let result = Installed::new(self, |optimizer| &mut optimizer.genetic, problem, budget).try_run_with_rng(rng);
+        let BaselineResult { best, best_fitness, samples, evaluations, termination, .. } = result?;
+        Ok(converted(&self.genetic, best.clone(), best_fitness, best, samples, evaluations, 0, termination))
+    }
+}
+
+impl<'a, F> Optimizer<'a, Vec<f64>, F> for DifferentialEvolution<'a, F>
+    where F: Fitness + MaybeSync {
+    fn name(&self) -> &'static str {
+        "differential evolution"
+    }
+
+    fn optimize(&mut self, problem: &'a (dyn Problem<Vec<f64>, F> + Sync), budget: usize, rng: &mut dyn RngCore) -> Result<RunResult<Vec<f64>, F>, RunError> {
+        Installed::new(self, |optimizer| &mut optimizer.genetic, problem, budget).try_run_with_rng(rng)
+    }
+}
+
+impl<'a, F> Optimizer<'a, Vec<f64>, F> for ParticleSwarm<'a, F>
+    where F: Fitness + MaybeSync {
+    fn name(&self) -> &'static str {
+        "particle swarm"
+    }
+
+    fn optimize(&mut self, problem: &'a (dyn Problem<Vec<f64>, F> + Sync), budget: usize, rng: &mut dyn RngCore) -> Result<RunResult<Vec<f64>, F>, RunError> {
+        Installed::new(self, |optimizer| &mut optimizer.genetic, problem, budget).try_run_with_rng(rng)
+    }
+}
+
+impl<'a, T, M, F> Optimizer<'a, T, F> for Eda<'a, T, M, F>
+    where T: Clone + MaybeSync, M: Model<T>, F: Fitness + MaybeSync {
+    fn name(&self) -> &'static str {
+        "estimation of distribution"
+    }
+
+    fn optimize(&mut self, problem: &'a (dyn Problem<T, F> + Sync), budget: usize, rng: &mut dyn RngCore) -> Result<RunResult<T, F>, RunError> {
+        Installed::new(self, |optimizer| &mut optimizer.genetic, problem, budget).try_run_with_rng(rng)
+    }
+}
+
+impl<'a, F> Optimizer<'a, Permutation, F> for AntColony<'a, F>
+    where F: Fitness + MaybeSync {
+    fn name(&self) -> &'static str {
+        "ant colony"
+    }
+
+    fn optimize(&mut self, problem: &'a (dyn Problem<Permutation, F> + Sync), budget: usize, rng: &mut dyn RngCore) -> Result<RunResult<Permutation, F>, RunError> {
+        Installed::new(self, |optimizer| &mut optimizer.genetic, problem, budget).try_run_with_rng(rng)
+    }
+}
+
+impl<'a, T, F> Optimizer<'a, T, F> for BeeColony<'a, T, F>
+    where T: Clone + MaybeSync, F: Fitness + MaybeSync {
+    fn name(&self) -> &'static str {
+        "bee colony"
+    }
+
+    fn optimize(&mut self, problem: &'a (dyn Problem<T, F> + Sync), budget: usize, rng: &mut dyn RngCore) -> Result<RunResult<T, F>, RunError> {
+        Installed::new(self, |optimizer| &mut optimizer.genetic, problem, budget).try_run_with_rng(rng)
+    }
+}
+
+/// Creates the RunResult of an algorithm working with a single agent
+#[allow(clippy::too_many_arguments)]
+fn converted<T, F>(genetic: &Genetic<T, F>, best: T, best_fitness: F, last: T, samples: usize, evaluations: usize, restarts: usize, termination: Termination) -> RunResult<T, F> {
+    RunResult {
+        population: vec![last],
+        best,
+        best_fitness,
+        generations: samples / genetic.population.max(1),
+        evaluations,
+        mutation_stats: Vec::new(),
+        crossover_stats: Vec::new(),
+        termination,
+        history: Vec::new(),
+        archive: Vec::new(),
+        restarts,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::panic::{self, AssertUnwindSafe};
+    use std::sync::Arc;
+
+    use crate::benchmarks::{Benchmark, Function, Tsp};
+    use crate::eda::GaussianUmda;
+    use crate::genome::Bounds;
+    use crate::{AcoVariant, Ascent, Cooling, DeStrategy, SwarmTopology, VelocityUpdate};
+
+    fn genetic<'a>() -> Genetic<'a, Vec<f64>> {
+        Genetic::builder()
+            .population(10)
+            .max_generation(1000)
+            .random_agent(|rng| (0..3).map(|_| rng.gen_range(-5.0, 5.0)).collect::<Vec<f64>>())
+            .fitness(|a: &Vec<f64>| a.iter().map(|x| x * x).sum::<f64>())
+            .minimize()
+            .mutate(|a: &Vec<f64>, rng| a.iter().map(|x| x + rng.gen_range(-0.1, 0.1)).collect())
+            .offspring(|a: &Vec<f64>, b: &Vec<f64>, _rng| a.iter().zip(b).map(|(x, y)| (x + y) / 2.0).collect())
+            .build()
+            .unwrap()
+    }
+
+    fn best<'a, T, F: Fitness>(optimizer: &mut dyn Optimizer<'a, T, F>, problem: &'a (dyn Problem<T, F> + Sync), budget: usize) -> RunResult<T, F> {
+        optimizer.optimize_with_seed(problem, budget, 1).unwrap()
+    }
+
+    /// Runs the optimizer on the problem with the budget, and checks that the budget was kept and that the Genetic of
+    /// the optimizer got its own max_evaluations and objective back
+    fn checked<'a, T, O>(mut optimizer: O, genetic: fn(&mut O) -> &mut Genetic<'a, T>, problem: &'a (dyn Problem<T> + Sync), budget: usize) -> (&'static str, RunResult<T>)
+        where O: Optimizer<'a, T> {
+        genetic(&mut optimizer).max_evaluations = Some(7);
+        genetic(&mut optimizer).objective = Objective::Maximize;
+        let name = optimizer.name();
+        let result = best(&mut optimizer, problem, budget);
+        assert!(result.evaluations <= budget, "{}: {}", name, result.evaluations);
+        assert_eq!(result.termination, Termination::EvaluationLimit, "{}", name);
+        let genetic = genetic(&mut optimizer);
+        assert_eq!((genetic.max_evaluations, genetic.objective), (Some(7), Objective::Maximize), "{}", name);
+        (name, result)
+    }
+
+    #[test]
+    fn optimizer() {
+        let sphere = Benchmark::new(Function::Sphere, 3);
+        let bounds = Bounds::uniform(3, -5.12, 5.12);
+        let results = vec![
+            checked(genetic(), |genetic| genetic, &sphere, 500),
+            checked(SimulatedAnnealing::new(genetic(), 1.0, Cooling::Exponential { rate: 0.99 }).unwrap(), |o| &mut o.genetic, &sphere, 500),
+            checked(HillClimber::new(genetic(), Ascent::First { tries: 20 }, usize::MAX).unwrap(), |o| &mut o.genetic, &sphere, 500),
+            checked(RandomSearch::new(genetic()), |o| &mut o.genetic, &sphere, 500),
+            checked(DifferentialEvolution::new(genetic(), DeStrategy::Rand1Bin, 0.5, 0.9).unwrap(), |o| &mut o.genetic, &sphere, 500),
+            checked(
+                ParticleSwarm::new(genetic(), Arc::clone(&bounds), SwarmTopology::Global, VelocityUpdate::Constriction { c1: 2.05, c2: 2.05 }).unwrap(),
+                |o| &mut o.genetic,
+                &sphere,
+                500,
+            ),
+            checked(Eda::new(genetic(), GaussianUmda::new(bounds, 0.3)).unwrap(), |o| &mut o.genetic, &sphere, 500),
+            checked(BeeColony::new(genetic(), 20).unwrap(), |o| &mut o.genetic, &sphere, 500),
+        ];
+        for (name, result) in &results {
+            assert!(result.best_fitness < 75.0 && result.best_fitness == sphere.evaluate(&result.best), "{}", name);
+        }
+        let names = results.iter().map(|(name, _)| *name).collect::<Vec<&str>>();
+        assert_eq!(
+            names,
+            ["genetic algorithm", "simulated annealing", "hill climbing", "random search", "differential evolution", "particle swarm", "estimation of distribution", "bee colony"]
+        );
+
+        // the ant colony builds the tours of the problem
+        let tsp = Tsp::from_coordinates(&[(0.0, 0.0), (1.0, 0.0), (2.0, 1.0), (1.0, 2.0), (0.0, 2.0), (-1.0, 1.0)]).unwrap();
+        let colony = AntColony::new(Genetic::from_problem(10, 1000, 0.7, 0.3, tsp.clone()), tsp.distances().to_vec(), AcoVariant::MaxMin { evaporation: 0.02 }).unwrap();
+        let (name, result) = checked(colony, |o| &mut o.genetic, &tsp, 300);
+        assert_eq!((name, result.best_fitness), ("ant colony", tsp.fitness(&result.best)));
+
+        // the seeded runs are reproducible through the trait too
+        let mut climber = HillClimber::new(genetic(), Ascent::Steepest { neighbours: 5 }, 0).unwrap();
+        assert_eq!(best(&mut climber, &sphere, 300), best(&mut climber, &sphere, 300));
+
+        // the algorithms working with a single agent report why they stopped
+        let mut search = RandomSearch::new(genetic());
+        assert_eq!(best(&mut search, &sphere, 1_000_000).termination, Termination::MaxGeneration);
+    }
+
+    #[test]
+    fn problems() {
+        let (sphere, rastrigin) = (Benchmark::new(Function::Sphere, 3), Benchmark::new(Function::Rastrigin, 3));
+        let mut genetic = genetic();
+        let own = genetic.run_with_seed(1);
+
+        // one optimizer runs on several problems
+        let on_sphere = best(&mut genetic, &sphere, 2000);
+        let on_rastrigin = best(&mut genetic, &rastrigin, 2000);
+        assert_eq!(on_sphere.best_fitness, sphere.evaluate(&on_sphere.best));
+        assert_eq!(on_rastrigin.best_fitness, rastrigin.evaluate(&on_rastrigin.best));
+        assert_ne!(on_sphere.best_fitness, on_rastrigin.best_fitness);
+
+        // and the Genetic gets its own problem and budget back
+        assert_eq!(genetic.max_evaluations, None);
+        assert_eq!(genetic.objective, Objective::Minimize);
+        assert_eq!(genetic.run_with_seed(1), own);
+    }
+
+    /// A problem whose fitness function panics
+    struct Failing;
+
+    impl Problem<Vec<f64>> for Failing {
+        fn random_agent(&self, _rng: &mut dyn RngCore) -> Vec<f64> {
+            vec![0.0; 3]
+        }
+
+        fn fitness(&self, _agent: &Vec<f64>) -> f64 {
+            panic!("the fitness function failed")
+        }
+
+        fn mutate(&self, agent: &Vec<f64>, _rng: &mut dyn RngCore) -> Vec<f64> {
+            agent.clone()
+        }
+
+        fn crossover(&self, a: &Vec<f64>, _b: &Vec<f64>, _rng: &mut dyn RngCore) -> Vec<f64> {
+            a.clone()
+        }
+    }
+
+    #[test]
+    fn panic_restores() {
+        let failing = Failing;
+        let mut genetic = genetic();
+        let own = genetic.run_with_seed(1);
+
+        // the Genetic gets its own problem and budget back even if the run panics
+        assert!(panic::catch_unwind(AssertUnwindSafe(|| best(&mut genetic, &failing, 100))).is_err());
+        assert_eq!(genetic.max_evaluations, None);
+        assert_eq!(genetic.run_with_seed(1), own);
+    }
+}
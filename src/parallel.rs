@@ -0,0 +1,178 @@
+#[cfg(feature = "rayon")]
+use rand::prelude::*;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use crate::{Evaluated, Fitness, FitnessError, Genetic, Offspring};
+#[cfg(feature = "rayon")]
+use crate::Rates;
+
+/// Send + Sync if the rayon feature is enabled (the agents and their fitness are shared by the threads),
+/// every type implements it otherwise
+#[cfg(feature = "rayon")]
+pub trait MaybeSync: Send + Sync {}
+#[cfg(feature = "rayon")]
+impl<X: Send + Sync> MaybeSync for X {}
+
+/// Send + Sync if the rayon feature is enabled (the agents and their fitness are shared by the threads),
+/// every type implements it otherwise
+#[cfg(not(feature = "rayon"))]
+pub trait MaybeSync {}
+#[cfg(not(feature = "rayon"))]
+impl<X> MaybeSync for X {}
+
+impl<'a, T, F> Genetic<'a, T, F>
+    where T: Clone + MaybeSync, F: Fitness + MaybeSync {
+    /// Evaluates the agents whose fitness isn't known yet, without checking max_evaluations
+    #[cfg(not(feature = "rayon"))]
+    pub(crate) fn evaluate_unlimited_all(&self, offspring: Vec<Offspring<T, F>>, evaluations: &mut usize) -> Result<Vec<Evaluated<T, F>>, FitnessError> {
+        let mut evaluated = Vec::with_capacity(offspring.len());
+        for (agent, fitness) in offspring {
+            let fitness = match fitness {
+                Some(f_x) => f_x,
+                None => self.evaluate_unlimited(&agent, evaluations)?,
+            };
+            evaluated.push(Evaluated { agent, fitness });
+        }
+
+        Ok(evaluated)
+    }
+
+    /// Executes the function in the thread_pool (or in the global pool of rayon if there's none)
+    #[cfg(feature = "rayon")]
+    fn in_pool<R: Send>(&self, f: impl FnOnce() -> R + Send) -> R {
+        match &self.thread_pool {
+            Some(thread_pool) => thread_pool.install(f),
+            None => f(),
+        }
+    }
+
+    /// Evaluates the agents whose fitness isn't known yet in parallel, without checking max_evaluations
+    #[cfg(feature = "rayon")]
+    pub(crate) fn evaluate_unlimited_all(&self, offspring: Vec<Offspring<T, F>>, evaluations: &mut usize) -> Result<Vec<Evaluated<T, F>>, FitnessError> {
+        let evaluated = self.in_pool(|| {
+            offspring.into_par_iter()
+                .with_min_len(self.chunk_size)
+                .map(|(agent, fitness)| {
+                    let mut count = 0;
+                    let fitness = match fitness {
+                        Some(f_x) => f_x,
+                        None => self.evaluate_unlimited(&agent, &mut count)?,
+                    };
+                    Ok((Evaluated { agent, fitness }, count))
+                })
+                .collect::<Result<Vec<(Evaluated<T, F>, usize)>, FitnessError>>()
+        })?;
+
+        *evaluations += evaluated.iter().map(|(_, count)| count).sum::<usize>();
+        Ok(evaluated.into_iter().map(|(x, _)| x).collect())
+    }
+
+    /// Mutates every agent with probability rates.mutation_probability() in parallel (the random decisions are made in order,
+    /// every mutation gets its own random number generator seeded from the one of the run)
+    #[cfg(feature = "rayon")]
+    pub(crate) fn par_mutate(&self, xg: Vec<Offspring<T, F>>, rates: &mut Rates, rng: &mut dyn RngCore) -> Vec<Offspring<T, F>> {
+        let plan = xg.iter()
+            .map(|_| {
+                if rng.gen_range(0.0, 1.0) >= rates.mutation_probability() {
+                    return None;
+                }
+                let operator = rates.mutation_bandit.choose(rng);
+                rates.mutation_bandit.record(operator, None);
+                Some((operator, rng.next_u64()))
+            })
+            .collect::<Vec<Option<(usize, u64)>>>();
+
+        self.in_pool(|| {
+            xg.into_par_iter()
+                .zip(plan)
+                .with_min_len(self.chunk_size)
+                .map(|((x, f_x), plan)| match plan {
+                    Some((operator, seed)) => (self.apply_mutation(operator, &x, &mut StdRng::seed_from_u64(seed)), None),
+                    None => (x, f_x),
+                })
+                .collect()
+        })
+    }
+
+    /// Crosses the parent pairs with probability rates.pc in parallel (the random decisions are made in order,
+    /// every crossover gets its own random number generator seeded from the one of the run)
+    #[cfg(feature = "rayon")]
+    #[allow(clippy::type_complexity)]
+    pub(crate) fn par_cross(&self, parents: &[(&Evaluated<T, F>, &Evaluated<T, F>)], rates: &mut Rates, rng: &mut dyn RngCore) -> Vec<Offspring<T, F>> {
+        let mut plan = Vec::new();
+        for (a, b) in parents {
+            if rng.gen_range(0.0, 1.0) < rates.pc {
+                let operator = rates.crossover_bandit.choose(rng);
+                rates.crossover_bandit.record(operator, None);
+                plan.push((*a, *b, operator, rng.next_u64()));
+            }
+        }
+
+        self.in_pool(|| {
+            plan.into_par_iter()
+                .with_min_len(self.chunk_size)
+                .flat_map_iter(|(a, b, operator, seed)| {
+                    self.apply_crossover(operator, &a.agent, &b.agent, &mut StdRng::seed_from_u64(seed))
+                        .into_iter()
+                        .map(|child| (child, None))
+                })
+                .collect()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn evaluations() {
+        let calls = AtomicUsize::new(0);
+        let test = Genetic::builder()
+            .population(30)
+            .max_generation(20)
+            .random_agent(|rng| rng.gen_range(-5.0, 5.0))
+            .fitness(|a: &f64| {
+                calls.fetch_add(1, Ordering::SeqCst);
+                5.0 - a * a
+            })
+            .mutate(|a: &f64, rng| *a + rng.gen_range(-0.1, 0.1))
+            .offspring(|a: &f64, b: &f64, _rng| (*a + *b) / 2.0)
+            .build()
+            .unwrap();
+
+        let result = test.run_with_seed(5);
+        assert_eq!(result.evaluations, calls.load(Ordering::SeqCst));
+        // the parallel runs are reproducible too
+        assert_eq!(result, test.run_with_seed(5));
+        assert!(result.best.abs() < 0.1);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn thread_pool() {
+        use crate::ConfigError;
+        use std::sync::Arc;
+
+        let builder = || Genetic::builder()
+            .population(30)
+            .max_generation(20)
+            .random_agent(|rng| rng.gen_range(-5.0, 5.0))
+            .fitness(|a: &f64| 5.0 - a * a)
+            .mutate(|a: &f64, rng| *a + rng.gen_range(-0.1, 0.1))
+            .offspring(|a: &f64, b: &f64, _rng| (*a + *b) / 2.0);
+
+        let pool = Arc::new(rayon::ThreadPoolBuilder::new().num_threads(2).build().unwrap());
+        let pooled = builder().thread_pool(pool).chunk_size(8).build().unwrap();
+        let threads = builder().threads(3).build().unwrap();
+        // the result doesn't depend on the threads
+        let result = builder().build().unwrap().run_with_seed(5);
+        assert_eq!(pooled.run_with_seed(5), result);
+        assert_eq!(threads.run_with_seed(5), result);
+
+        assert!(matches!(builder().chunk_size(0).build(), Err(ConfigError::InvalidParallelism(_))));
+    }
+}
@@ -0,0 +1,228 @@
+//! Pareto front utilities: dominance, front extraction, a bounded archive of non-dominated agents and quality
+//! indicators of fronts (hypervolume and IGD)
+//!
+//! They work with every Fitness (the objectives are the values of Fitness::objectives(), e.g. the elements of a
+//! tuple or of a Vec<f64>), so they can be used with the single-objective runs too. Every objective is maximised or
+//! minimised according to the given Objective.
+//!
+//! # Examples:
+//! ```
+//! use rand::prelude::*;
+//! use bbte_optim_tzim1773_genetic::{Genetic, MultiObjective, MultiObjectiveAlgorithm, Objective};
+//! use bbte_optim_tzim1773_genetic::pareto;
+//!
+//! fn main() {
+//!     let genetic = Genetic::builder()
+//!         .population(30)
+//!         .max_generation(30)
+//!         .objective(Objective::Minimize)
+//!         .random_agent(|rng| rng.gen_range(-10.0, 10.0))
+//!         .fitness(|a: &f64| vec![a * a, (a - 2.0) * (a - 2.0)])
+//!         .mutate(|a: &f64, rng| *a + rng.gen_range(-0.5, 0.5))
+//!         .offspring(|a: &f64, b: &f64, _rng| (*a + *b) / 2.0)
+//!         .build()
+//!         .unwrap();
+//!
+//!     let result = MultiObjective::new(genetic, MultiObjectiveAlgorithm::Nsga2).unwrap().run();
+//!     let front = result.front.iter().map(|x| x.fitness.clone()).collect::<Vec<_>>();
+//!     println!("{}", pareto::hypervolume(&front, &[4.0, 4.0], Objective::Minimize)); // close to 16 - 8 / 3
+//! }
+//! ```
+use crate::multi_objective::values;
+use crate::nsga2::crowding_distance;
+use crate::{ConfigError, Evaluated, Fitness, Objective};
+
+/// Tells whether the objectives a dominate the objectives b: a is at least as good in every objective and better
+/// in one (NaN is never as good as anything)
+pub fn dominates(a: &[f64], b: &[f64], objective: Objective) -> bool {
+    let as_good = |x: &f64, y: &f64| match objective {
+        Objective::Maximize => x >= y,
+        Objective::Minimize => x <= y,
+    };
+    a.iter().zip(b).all(|(x, y)| as_good(x, y)) && a.iter().zip(b).any(|(x, y)| objective.is_better(x, y))
+}
+
+/// Returns the agents of the population that no other agent dominates (in the order of the population)
+pub fn front<T: Clone, F: Fitness>(xg: &[Evaluated<T, F>], objective: Objective) -> Vec<Evaluated<T, F>> {
+    let objectives = xg.iter().map(|x| x.fitness.objectives()).collect::<Vec<Vec<f64>>>();
+    xg.iter()
+        .zip(&objectives)
+        .filter(|(_, a)| !objectives.iter().any(|b| dominates(b, a, objective)))
+        .map(|(x, _)| x.clone())
+        .collect()
+}
+
+/// Returns the hypervolume of a front: the volume of the objective space the points dominate, bounded by the
+/// reference point (the points not better than the reference point in every objective are ignored)
+pub fn hypervolume(front: &[Vec<f64>], reference: &[f64], objective: Objective) -> f64 {
+    // the points turned so that smaller is better
+    let turn = |x: f64| match objective {
+        Objective::Maximize => -x,
+        Objective::Minimize => x,
+    };
+    let reference = reference.iter().map(|x| turn(*x)).collect::<Vec<f64>>();
+    let points = front.iter()
+        .filter(|point| point.len() >= reference.len())
+        .map(|point| point.iter().take(reference.len()).map(|x| turn(*x)).collect::<Vec<f64>>())
+        .filter(|point| point.iter().zip(&reference).all(|(x, r)| x.is_finite() && x < r))
+        .collect::<Vec<Vec<f64>>>();
+
+    sliced_volume(&points.iter().map(|point| &point[..]).collect::<Vec<&[f64]>>(), &reference)
+}
+
+/// The hypervolume of the points (smaller is better), cut into slices along the last objective
+fn sliced_volume(points: &[&[f64]], reference: &[f64]) -> f64 {
+    let m = reference.len();
+    if points.is_empty() || m == 0 {
+        return 0.0;
+    }
+    if m == 1 {
+        return reference[0] - points.iter().map(|point| point[0]).fold(f64::INFINITY, f64::min);
+    }
+
+    let mut sorted = points.to_vec();
+    sorted.sort_by(|a, b| a[m - 1].total_cmp(&b[m - 1]));
+    let mut volume = 0.0;
+    for k in 0..sorted.len() {
+        let depth = sorted.get(k + 1).map_or(reference[m - 1], |point| point[m - 1]) - sorted[k][m - 1];
+        if depth > 0.0 {
+            let slice = sorted[..=k].iter().map(|point| &point[..m - 1]).collect::<Vec<&[f64]>>();
+            volume += sliced_volume(&slice, &reference[..m - 1]) * depth;
+        }
+    }
+
+    volume
+}
+
+/// Returns the inverted generational distance of a front: the mean Euclidean distance of the points of the
+/// reference front (e.g. the true Pareto front) to their nearest point in the front (smaller is better)
+pub fn igd(front: &[Vec<f64>], reference: &[Vec<f64>]) -> f64 {
+    let distance = |a: &[f64], b: &[f64]| a.iter().zip(b).map(|(x, y)| (x - y) * (x - y)).sum::<f64>().sqrt();
+    reference.iter()
+        .map(|r| front.iter().map(|point| distance(point, r)).fold(f64::INFINITY, f64::min))
+        .sum::<f64>() / reference.len() as f64
+}
+
+/// A bounded archive of non-dominated agents: an agent gets in if no agent of the archive dominates it (or has the
+/// same objectives), and it removes the agents it dominates. Above the capacity the agent in the most crowded
+/// region (with the smallest crowding distance) is removed, so the archive stays spread over the front.
+///
+/// # Examples:
+/// ```
+/// use bbte_optim_tzim1773_genetic::{Evaluated, Objective};
+/// use bbte_optim_tzim1773_genetic::pareto::ParetoArchive;
+///
+/// fn main() {
+///     let mut archive = ParetoArchive::new(10, Objective::Maximize);
+///     archive.insert(Evaluated::new("a", vec![1.0, 5.0]));
+///     archive.insert(Evaluated::new("b", vec![2.0, 4.0]));
+///     archive.insert(Evaluated::new("c", vec![3.0, 5.0])); // dominates both
+///     assert_eq!(archive.members().len(), 1);
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParetoArchive<T, F = Vec<f64>> {
+    /// The maximal number of agents kept (at least 1)
+    pub capacity: usize,
+    /// Whether the objectives are maximised or minimised
+    pub objective: Objective,
+    members: Vec<Evaluated<T, F>>,
+}
+
+impl<T, F: Fitness> ParetoArchive<T, F> {
+    /// An empty archive keeping at most `capacity` agents
+    pub fn new(capacity: usize, objective: Objective) -> Self {
+        ParetoArchive { capacity, objective, members: Vec::new() }
+    }
+
+    /// Checks whether the capacity is usable
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.capacity == 0 {
+            return Err(ConfigError::InvalidArchive("the capacity must be at least 1".to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// Puts the agent into the archive if no agent of the archive dominates it, returns whether it's kept
+    pub fn insert(&mut self, x: Evaluated<T, F>) -> bool {
+        let objectives = x.fitness.objectives();
+        if self.members.iter().any(|member| {
+            let other = member.fitness.objectives();
+            other == objectives || dominates(&other, &objectives, self.objective)
+        }) {
+            return false;
+        }
+        let objective = self.objective;
+        self.members.retain(|member| !dominates(&objectives, &member.fitness.objectives(), objective));
+        self.members.push(x);
+
+        if self.members.len() <= self.capacity {
+            return true;
+        }
+        let all = self.members.iter().map(|member| member.fitness.objectives()).collect::<Vec<Vec<f64>>>();
+        let count = all.iter().map(|a| a.len()).max().unwrap_or(0);
+        let turned = all.iter().map(|a| values(a, count, self.objective)).collect::<Vec<Vec<f64>>>();
+        let crowding = crowding_distance(&turned, &(0..turned.len()).collect::<Vec<usize>>());
+        let crowded = (0..crowding.len())
+            .min_by(|a, b| crowding[*a].total_cmp(&crowding[*b]))
+            .expect("the archive is not empty");
+        self.members.remove(crowded);
+
+        crowded != self.members.len()
+    }
+
+    /// Puts the agents into the archive one by one
+    pub fn extend(&mut self, xs: impl IntoIterator<Item = Evaluated<T, F>>) {
+        for x in xs {
+            self.insert(x);
+        }
+    }
+
+    /// The agents in the archive
+    pub fn members(&self) -> &[Evaluated<T, F>] {
+        &self.members
+    }
+
+    /// Returns the agents in the archive
+    pub fn into_members(self) -> Vec<Evaluated<T, F>> {
+        self.members
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn indicators() {
+        let points = vec![vec![1.0, 3.0], vec![2.0, 2.0], vec![3.0, 1.0]];
+        assert_eq!(hypervolume(&points, &[4.0, 4.0], Objective::Minimize), 6.0);
+        // the points outside the reference point don't count
+        assert_eq!(hypervolume(&[vec![5.0, 0.0], vec![2.0, 2.0]], &[4.0, 4.0], Objective::Minimize), 4.0);
+        assert_eq!(hypervolume(&[vec![1.0, 2.0, 3.0]], &[0.0, 0.0, 0.0], Objective::Maximize), 6.0);
+        assert_eq!(igd(&[vec![1.0, 3.0], vec![3.0, 1.0]], &points), 2f64.sqrt() / 3.0);
+
+        assert!(dominates(&[1.0, 2.0], &[1.0, 3.0], Objective::Minimize));
+        assert!(!dominates(&[1.0, f64::NAN], &[1.0, 3.0], Objective::Minimize));
+        let xg = vec![Evaluated::new('a', (1, 5)), Evaluated::new('b', (2, 2)), Evaluated::new('c', (1, 4))];
+        assert_eq!(front(&xg, Objective::Maximize), xg[..2].to_vec());
+    }
+
+    #[test]
+    fn archive() {
+        let mut archive = ParetoArchive::new(3, Objective::Maximize);
+        assert!(archive.insert(Evaluated::new(0, vec![1.0, 5.0])));
+        assert!(archive.insert(Evaluated::new(1, vec![2.0, 4.0])));
+        assert!(!archive.insert(Evaluated::new(2, vec![1.0, 4.0])));
+        assert!(!archive.insert(Evaluated::new(3, vec![2.0, 4.0])));
+        assert!(archive.insert(Evaluated::new(4, vec![3.0, 1.0])));
+        // the fourth agent of the front doesn't fit, the most crowded one is removed
+        assert!(archive.insert(Evaluated::new(5, vec![2.5, 3.5])));
+        assert_eq!(archive.members().iter().map(|x| x.agent).collect::<Vec<_>>(), vec![0, 4, 5]);
+
+        archive.extend(vec![Evaluated::new(6, vec![0.0, 0.0]), Evaluated::new(7, vec![3.0, 6.0])]);
+        assert_eq!(archive.into_members(), vec![Evaluated::new(7, vec![3.0, 6.0])]);
+        assert!(ParetoArchive::<i32>::new(0, Objective::Maximize).validate().is_err());
+    }
+}
@@ -0,0 +1,325 @@
+use rand::prelude::*;
+use std::sync::Arc;
+use std::time::Instant;
+
+use crate::engine::Engine;
+use crate::genome::{BoundHandling, Bounds};
+use crate::{ConfigError, Evaluated, Fitness, Genetic, MaybeSync, RunError, RunResult, Termination};
+
+/// The neighbourhood a particle learns from (besides its own best position)
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SwarmTopology {
+    /// Every particle follows the best position of the whole swarm (fast, but it can converge prematurely)
+    Global,
+    /// The particles are on a ring, and every particle follows the best position of the `neighbors` particles on
+    /// both of its sides (slower, keeps more diversity)
+    Ring {
+        /// The number of neighbours on each side (at least 1)
+        neighbors: usize,
+    },
+}
+
+/// How the velocity of a particle is updated from its own best position (p) and the best position of its
+/// neighbourhood (g), r1 and r2 are uniform random numbers in [0, 1) for every dimension
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum VelocityUpdate {
+    /// v = w * v + c1 * r1 * (p - x) + c2 * r2 * (g - x), the inertia weight w changes linearly from start to end
+    /// Suggested values: start 0.9, end 0.4, c1 = c2 = 2.0
+    Inertia {
+        /// The inertia weight in the first generation
+        start: f64,
+        /// The inertia weight in the last generation
+        end: f64,
+        /// The cognitive coefficient (the pull of the own best position)
+        c1: f64,
+        /// The social coefficient (the pull of the best position of the neighbourhood)
+        c2: f64,
+    },
+    /// Clerc's constriction: v = chi * (v + c1 * r1 * (p - x) + c2 * r2 * (g - x)), where chi is computed from
+    /// phi = c1 + c2 (it has to be bigger than 4)
+    /// Suggested values: c1 = c2 = 2.05 (chi is about 0.73)
+    Constriction {
+        /// The cognitive coefficient
+        c1: f64,
+        /// The social coefficient
+        c2: f64,
+    },
+}
+
+impl VelocityUpdate {
+    /// Returns (the multiplier of the old velocity, the multiplier of the whole new velocity, c1, c2) in the given
+    /// generation
+    fn coefficients(&self, generation: usize, max_generation: usize) -> (f64, f64, f64, f64) {
+        match *self {
+            VelocityUpdate::Inertia { start, end, c1, c2 } => {
+                let progress = if max_generation > 1 { (generation as f64 / (max_generation - 1) as f64).min(1.0) } else { 0.0 };
+                (start + (end - start) * progress, 1.0, c1, c2)
+            }
+            VelocityUpdate::Constriction { c1, c2 } => {
+                let phi = c1 + c2;
+                (1.0, 2.0 / (2.0 - phi - (phi * phi - 4.0 * phi).sqrt()).abs(), c1, c2)
+            }
+        }
+    }
+}
+
+/// Particle swarm optimisation on bounded real vector (Vec<f64>) genomes: every particle moves through the search
+/// space with a velocity pulled towards its own best position and the best position of its neighbourhood
+///
+/// The starting positions, the fitness, the number of particles (the population) and the stopping criteria come
+/// from the Genetic: the run stops at max_generation, max_evaluations, the target fitness, the stagnation, the time
+/// limit, the stop condition, the cancellation or the callbacks, and the result is a RunResult with the statistics
+/// of every generation (the run log and the archive are kept too). The population of the result and the statistics
+/// are the best positions of the particles. The positions are evaluated like the offspring of the Genetic (in
+/// parallel with the rayon feature), its operators, selection and modes are not used.
+///
+/// Every component of the velocity is clamped to `max_velocity` part of the interval of its dimension, and the
+/// positions that leave the bounds are moved back by the bound handling (the velocity becomes the actual move).
+///
+/// # Panics:
+///  When the run starts, if an agent doesn't have a value for every dimension of the bounds
+///
+/// # Examples:
+/// ```
+/// use rand::prelude::*;
+/// use bbte_optim_tzim1773_genetic::{Genetic, ParticleSwarm, SwarmTopology, VelocityUpdate};
+/// use bbte_optim_tzim1773_genetic::genome::Bounds;
+///
+/// fn main() {
+///     let genetic = Genetic::builder()
+///         .population(30)
+///         .max_generation(200)
+///         .random_agent(|rng| (0..5).map(|_| rng.gen_range(-5.0, 5.0)).collect())
+///         .fitness(|a: &Vec<f64>| a.iter().map(|x| x * x).sum::<f64>())
+///         .minimize()
+///         .mutate(|a: &Vec<f64>, _rng| a.clone())
+///         .offspring(|a: &Vec<f64>, _b: &Vec<f64>, _rng| a.clone())
+///         .build()
+///         .unwrap();
+///
+///     let swarm = ParticleSwarm::new(genetic, Bounds::uniform(5, -5.0, 5.0), SwarmTopology::Ring { neighbors: 1 }, VelocityUpdate::Constriction { c1: 2.05, c2: 2.05 })
+///         .unwrap();
+///     println!("{:?}", swarm.run().best); // should be close to [0, 0, 0, 0, 0]
+/// }
+/// ```
+pub struct ParticleSwarm<'a, F = f64> {
+    /// The Genetic giving the starting positions, the fitness, the number of particles and the stopping criteria
+    pub genetic: Genetic<'a, Vec<f64>, F>,
+    /// The bounds of the positions
+    pub bounds: Arc<Bounds>,
+    /// The neighbourhood of the particles
+    /// Suggested value: SwarmTopology::Global (Ring { neighbors: 1 } for multimodal problems)
+    pub topology: SwarmTopology,
+    /// How the velocities are updated
+    /// Suggested value: VelocityUpdate::Constriction { c1: 2.05, c2: 2.05 }
+    pub update: VelocityUpdate,
+    /// The largest velocity component relative to the interval of its dimension (bigger than 0.0)
+    /// Suggested value: 0.2
+    pub max_velocity: f64,
+    /// What happens with the positions that leave their bounds
+    pub handling: BoundHandling,
+}
+
+impl<'a, F> ParticleSwarm<'a, F>
+    where F: Fitness + MaybeSync {
+    /// Creates a particle swarm on the problem of the Genetic, checking the parameters (the velocity is clamped to
+    /// 20% of the intervals and the positions are clamped to the bounds)
+    ///
+    /// # Arguments:
+    ///
+    /// * `genetic` the starting positions, the fitness, the number of particles (at least 2) and the stopping criteria
+    /// * `bounds` the bounds of the positions
+    /// * `topology` the neighbourhood of the particles
+    /// * `update` how the velocities are updated
+    pub fn new(genetic: Genetic<'a, Vec<f64>, F>, bounds: Arc<Bounds>, topology: SwarmTopology, update: VelocityUpdate) -> Result<Self, ConfigError> {
+        if genetic.population < 2 {
+            return Err(ConfigError::InvalidParticleSwarm(format!("the swarm needs at least 2 particles, got {}", genetic.population)));
+        }
+        if let SwarmTopology::Ring { neighbors: 0 } = topology {
+            return Err(ConfigError::InvalidParticleSwarm("the ring needs at least 1 neighbour on each side".to_string()));
+        }
+        match update {
+            VelocityUpdate::Inertia { start, end, c1, c2 } if !(start >= 0.0 && end >= 0.0 && c1 >= 0.0 && c2 >= 0.0) => {
+                return Err(ConfigError::InvalidParticleSwarm("the inertia weights and the coefficients can't be negative".to_string()));
+            }
+            VelocityUpdate::Constriction { c1, c2 } if !(c1 >= 0.0 && c2 >= 0.0 && c1 + c2 > 4.0) => {
+                return Err(ConfigError::InvalidParticleSwarm(format!("the constriction needs c1 + c2 > 4.0, got {}", c1 + c2)));
+            }
+            _ => {}
+        }
+
+        Ok(ParticleSwarm { genetic, bounds, topology, update, max_velocity: 0.2, handling: BoundHandling::Clamp })
+    }
+
+    /// Sets the largest velocity component relative to the interval of its dimension (values that are not bigger
+    /// than 0.0 are ignored)
+    pub fn max_velocity(mut self, max_velocity: f64) -> Self {
+        if max_velocity > 0.0 {
+            self.max_velocity = max_velocity;
+        }
+        self
+    }
+
+    /// Sets what happens with the positions that leave their bounds
+    pub fn handling(mut self, handling: BoundHandling) -> Self {
+        self.handling = handling;
+        self
+    }
+
+    /// Runs the optimisation until one of the stopping criteria of the Genetic is met
+    ///
+    /// # Panics:
+    ///  If the fitness function fails and the fitness_error_policy gives up on the agent
+    ///  (use try_run() to get the error instead)
+    pub fn run(&self) -> RunResult<Vec<f64>, F> {
+        self.run_with_rng(&mut thread_rng())
+    }
+
+    /// Same as run(), but returns the error instead of panicking
+    pub fn try_run(&self) -> Result<RunResult<Vec<f64>, F>, RunError> {
+        self.try_run_with_rng(&mut thread_rng())
+    }
+
+    /// Same as run(), but uses the given random number generator
+    pub fn run_with_rng(&self, rng: &mut dyn RngCore) -> RunResult<Vec<f64>, F> {
+        match self.try_run_with_rng(rng) {
+            Ok(result) => result,
+            Err(error) => panic!("{}", error),
+        }
+    }
+
+    /// Same as run(), but uses a random number generator seeded with the given seed, so the runs can be reproduced
+    pub fn run_with_seed(&self, seed: u64) -> RunResult<Vec<f64>, F> {
+        self.run_with_rng(&mut StdRng::seed_from_u64(seed))
+    }
+
+    /// Same as run_with_rng(), but returns the error instead of panicking
+    pub fn try_run_with_rng(&self, rng: &mut dyn RngCore) -> Result<RunResult<Vec<f64>, F>, RunError> {
+        let start = Instant::now();
+        let genetic = &self.genetic;
+        let bounds = &self.bounds;
+        let limits = (0..bounds.len()).map(|j| self.max_velocity * bounds.range(j)).collect::<Vec<f64>>();
+        let initial = genetic.get_population(rng)
+            .into_iter()
+            .map(|x| {
+                assert_eq!(x.len(), bounds.len(), "the agents must have a value for every dimension of the bounds");
+                (self.bounded(x), None)
+            })
+            .collect();
+        let (mut engine, mut positions) = Engine::start(genetic, start, initial)?;
+        let mut velocities = positions.iter()
+            .map(|_| limits.iter().map(|limit| limit * rng.gen_range(-1.0, 1.0)).collect::<Vec<f64>>())
+            .collect::<Vec<_>>();
+        let mut best = positions.clone();
+
+        let termination = loop {
+            if let Some(termination) = engine.check_bound() {
+                break termination;
+            }
+
+            let (inertia, chi, c1, c2) = self.update.coefficients(engine.generation, genetic.max_generation);
+            let moved = (0..positions.len())
+                .map(|i| {
+                    let (x, v, p, g) = (&positions[i].agent, &mut velocities[i], &best[i].agent, &best[self.leader(&best, i)].agent);
+                    for j in 0..x.len() {
+                        let pull = c1 * rng.gen::<f64>() * (p[j] - x[j]) + c2 * rng.gen::<f64>() * (g[j] - x[j]);
+                        v[j] = (chi * (inertia * v[j] + pull)).clamp(-limits[j], limits[j]);
+                    }
+                    let y = self.bounded(x.iter().zip(v.iter()).map(|(x, v)| x + v).collect());
+                    // the velocity becomes the move that was actually made
+                    for ((v, x), y) in v.iter_mut().zip(x).zip(&y) {
+                        *v = y - x;
+                    }
+                    (y, None)
+                })
+                .collect();
+            positions = match engine.evaluate(moved)? {
+                Some(positions) => positions,
+                None => break Termination::EvaluationLimit,
+            };
+            for (p, x) in best.iter_mut().zip(&positions) {
+                if genetic.objective.is_better(&x.fitness, &p.fitness) {
+                    *p = x.clone();
+                }
+            }
+            if let Some(termination) = engine.finish_generation(&best)? {
+                break termination;
+            }
+        };
+
+        let leader = best[genetic.best_index(&best)].clone();
+        Ok(engine.result(leader, best.into_iter().map(|x| x.agent).collect(), termination))
+    }
+
+    /// Returns the index of the best of the best positions in the neighbourhood of the i-th particle
+    fn leader(&self, best: &[Evaluated<Vec<f64>, F>], i: usize) -> usize {
+        let n = best.len();
+        let neighbourhood: Box<dyn Iterator<Item = usize>> = match self.topology {
+            SwarmTopology::Global => Box::new(0..n),
+            SwarmTopology::Ring { neighbors } => {
+                let k = neighbors.min((n - 1) / 2).max(1);
+                Box::new((0..=2 * k).map(move |d| (i + n + d - k) % n))
+            }
+        };
+        neighbourhood.fold(i, |leader, j| if self.genetic.objective.is_better(&best[j].fitness, &best[leader].fitness) { j } else { leader })
+    }
+
+    /// Moves the position into its bounds and repairs it
+    fn bounded(&self, mut x: Vec<f64>) -> Vec<f64> {
+        for (j, value) in x.iter_mut().enumerate().take(self.bounds.len()) {
+            *value = self.handling.apply(*value, self.bounds.lower[j], self.bounds.upper[j]);
+        }
+        self.genetic.repair(x)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Stagnation;
+
+    fn genetic<'a>() -> crate::GeneticBuilder<'a, Vec<f64>> {
+        // a sphere with its minimum at [1, 1, 1]
+        Genetic::builder()
+            .population(20)
+            .max_generation(300)
+            .random_agent(|rng| (0..3).map(|_| rng.gen_range(-10.0, 10.0)).collect())
+            .fitness(|a: &Vec<f64>| a.iter().map(|x| (x - 1.0) * (x - 1.0)).sum::<f64>())
+            .minimize()
+            .mutate(|a: &Vec<f64>, _rng| a.clone())
+            .offspring(|a: &Vec<f64>, _b: &Vec<f64>, _rng| a.clone())
+    }
+
+    #[test]
+    fn particle_swarm() {
+        let bounds = || Bounds::uniform(3, -10.0, 10.0);
+        let constriction = VelocityUpdate::Constriction { c1: 2.05, c2: 2.05 };
+        assert!(ParticleSwarm::new(genetic().build().unwrap(), bounds(), SwarmTopology::Ring { neighbors: 0 }, constriction).is_err());
+        assert!(ParticleSwarm::new(genetic().build().unwrap(), bounds(), SwarmTopology::Global, VelocityUpdate::Constriction { c1: 1.0, c2: 1.0 }).is_err());
+        assert!((VelocityUpdate::Constriction { c1: 2.05, c2: 2.05 }.coefficients(0, 10).1 - 0.7298).abs() < 1e-4);
+        assert_eq!(VelocityUpdate::Inertia { start: 0.9, end: 0.4, c1: 2.0, c2: 2.0 }.coefficients(10, 11).0, 0.4);
+
+        // the smallest fitness is the best on the ring
+        let swarm = ParticleSwarm::new(genetic().build().unwrap(), bounds(), SwarmTopology::Ring { neighbors: 1 }, constriction).unwrap();
+        let best = (0..20).map(|j| Evaluated::new(vec![], j as f64)).collect::<Vec<_>>();
+        assert_eq!((0..4).map(|i| swarm.leader(&best, i)).collect::<Vec<usize>>(), vec![0, 0, 1, 2]);
+        assert!(swarm.run_with_seed(1).best.iter().all(|x| (x - 1.0).abs() < 1e-3));
+
+        let inertia = VelocityUpdate::Inertia { start: 0.9, end: 0.4, c1: 2.0, c2: 2.0 };
+        for (topology, update) in &[(SwarmTopology::Global, constriction), (SwarmTopology::Global, inertia), (SwarmTopology::Ring { neighbors: 2 }, inertia)] {
+            let result = ParticleSwarm::new(genetic().build().unwrap(), bounds(), *topology, *update).unwrap().run_with_seed(1);
+            assert!(result.best.iter().all(|x| (x - 1.0).abs() < 1e-2), "{:?} {:?} {:?}", topology, update, result.best);
+            assert!(result.history.windows(2).all(|w| w[1].best <= w[0].best));
+        }
+
+        // the stopping criteria of the Genetic are used, and the particles stay in the bounds
+        let result = ParticleSwarm::new(genetic().max_evaluations(200).build().unwrap(), bounds(), SwarmTopology::Global, constriction).unwrap().run_with_seed(1);
+        assert_eq!((result.termination, result.evaluations, result.generations), (Termination::EvaluationLimit, 200, 9));
+        let test = genetic().stagnation(Stagnation::new(5)).build().unwrap();
+        let result = ParticleSwarm::new(test, Bounds::uniform(3, 2.0, 3.0), SwarmTopology::Global, constriction).unwrap().handling(BoundHandling::Reflect).run_with_seed(1);
+        assert_eq!(result.termination, Termination::Stagnation);
+        assert!(result.population.iter().all(|x| x.iter().all(|v| (2.0..=3.0).contains(v))));
+    }
+}
@@ -0,0 +1,261 @@
+use rand::prelude::*;
+
+use crate::{ConfigError, Fitness, Optimizer, Problem, RunError};
+
+/// The statistics of an optimizer of a portfolio
+#[derive(Debug, Clone, PartialEq)]
+pub struct PortfolioStats<F = f64> {
+    /// The name of the optimizer
+    pub name: &'static str,
+    /// The number of rounds the optimizer ran in (it was eliminated after the last one, unless it's the winner)
+    pub rounds: usize,
+    /// The number of times the fitness function was called by the optimizer in all of its rounds
+    pub evaluations: usize,
+    /// The best fitness the optimizer found in all of its rounds
+    pub best_fitness: F,
+    /// The best fitness of its last round (the optimizers of a round are ranked by it)
+    pub last_fitness: F,
+}
+
+/// The outcome of a portfolio run
+#[derive(Debug, Clone, PartialEq)]
+pub struct PortfolioResult<T, F = f64> {
+    /// The best agent found by any of the optimizers
+    pub best: T,
+    /// The fitness of the best agent
+    pub best_fitness: F,
+    /// The index of the optimizer that won the last round (not necessarily the one that found the best agent)
+    pub winner: usize,
+    /// The number of times the fitness function was called by all of the optimizers
+    pub evaluations: usize,
+    /// The statistics of the optimizers, in the order they were given
+    pub stats: Vec<PortfolioStats<F>>,
+}
+
+/// Races several optimizers (different algorithms, or Genetics with different settings) on a problem with a shared
+/// evaluation budget, by successive halving: the budget is divided equally between the rounds, in every round
+/// the remaining optimizers run from scratch with an equal part of the budget of the round, and the worse half of them
+/// (by the best fitness of the round) is eliminated, until one optimizer is left. So the better optimizers get
+/// longer runs, and the last round gives all of its budget to the winner. The optimizers are ranked by the objective
+/// of the problem.
+///
+/// # Examples:
+/// ```
+/// use bbte_optim_tzim1773_genetic::benchmarks::{Benchmark, Function};
+/// use bbte_optim_tzim1773_genetic::{Ascent, DeStrategy, DifferentialEvolution, Genetic, HillClimber, Portfolio, RandomSearch};
+///
+/// fn main() {
+///     let problem = Benchmark::new(Function::Sphere, 3);
+///     let genetic = || Genetic::from_problem(20, 1000, 0.7, 0.3, Benchmark::new(Function::Sphere, 3));
+///
+///     let mut portfolio = Portfolio::new(6000)
+///         .optimizer(genetic())
+///         .optimizer(RandomSearch::new(genetic()))
+///         .optimizer(HillClimber::new(genetic(), Ascent::First { tries: 20 }, usize::MAX).unwrap())
+///         .optimizer(DifferentialEvolution::new(genetic(), DeStrategy::Rand1Bin, 0.5, 0.9).unwrap());
+///     let result = portfolio.run(&problem);
+///     println!("{:?} {}", result.best, result.stats[result.winner].name);
+/// }
+/// ```
+pub struct Portfolio<'a, T, F = f64> {
+    /// The number of evaluations shared by the optimizers
+    pub budget: usize,
+    optimizers: Vec<Box<dyn Optimizer<'a, T, F> + 'a>>,
+}
+
+impl<'a, T, F> Portfolio<'a, T, F>
+    where T: Clone, F: Fitness {
+    /// Creates an empty portfolio with the budget shared by its optimizers
+    pub fn new(budget: usize) -> Self {
+        Portfolio { budget, optimizers: Vec::new() }
+    }
+
+    /// Adds an optimizer to the portfolio
+    pub fn optimizer(mut self, optimizer: impl Optimizer<'a, T, F> + 'a) -> Self {
+        self.optimizers.push(Box::new(optimizer));
+        self
+    }
+
+    /// Adds an optimizer that is already boxed (e.g. one chosen at runtime)
+    pub fn optimizer_boxed(mut self, optimizer: Box<dyn Optimizer<'a, T, F> + 'a>) -> Self {
+        self.optimizers.push(optimizer);
+        self
+    }
+
+    /// Checks that the portfolio can be run: it needs at least one optimizer and one evaluation for every run of the
+    /// first round
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.optimizers.is_empty() {
+            return Err(ConfigError::InvalidPortfolio("the portfolio has no optimizers".to_string()));
+        }
+        if self.budget / self.rounds() < self.optimizers.len() {
+            return Err(ConfigError::InvalidPortfolio(format!("a budget of {} is too small for {} optimizers", self.budget, self.optimizers.len())));
+        }
+        Ok(())
+    }
+
+    /// Returns the number of rounds: the optimizers are halved until one is left
+    pub fn rounds(&self) -> usize {
+        let mut remaining = self.optimizers.len();
+        let mut rounds = 1;
+        while remaining > 1 {
+            remaining = remaining.div_ceil(2);
+            rounds += 1;
+        }
+        rounds
+    }
+
+    /// Races the optimizers on the problem
+    ///
+    /// # Panics:
+    ///  If the portfolio is not valid (see validate()), or an optimizer fails (use try_run() to get the error instead)
+    pub fn run(&mut self, problem: &'a (dyn Problem<T, F> + Sync)) -> PortfolioResult<T, F> {
+        self.run_with_rng(problem, &mut thread_rng())
+    }
+
+    /// Same as run(), but returns the error of the failed optimizer (or RunError::Config if the portfolio is not
+    /// valid) instead of panicking
+    pub fn try_run(&mut self, problem: &'a (dyn Problem<T, F> + Sync)) -> Result<PortfolioResult<T, F>, RunError> {
+        self.try_run_with_rng(problem, &mut thread_rng())
+    }
+
+    /// Same as run(), but uses the given random number generator
+    pub fn run_with_rng(&mut self, problem: &'a (dyn Problem<T, F> + Sync), rng: &mut dyn RngCore) -> PortfolioResult<T, F> {
+        match self.try_run_with_rng(problem, rng) {
+            Ok(result) => result,
+            Err(error) => panic!("{}", error),
+        }
+    }
+
+    /// Same as run(), but uses a random number generator seeded with the given seed, so the runs can be reproduced
+    pub fn run_with_seed(&mut self, problem: &'a (dyn Problem<T, F> + Sync), seed: u64) -> PortfolioResult<T, F> {
+        self.run_with_rng(problem, &mut StdRng::seed_from_u64(seed))
+    }
+
+    /// Same as run_with_rng(), but returns the error of the failed optimizer (or RunError::Config if the portfolio is
+    /// not valid) instead of panicking
+    pub fn try_run_with_rng(&mut self, problem: &'a (dyn Problem<T, F> + Sync), rng: &mut dyn RngCore) -> Result<PortfolioResult<T, F>, RunError> {
+        self.validate()?;
+        let objective = problem.objective();
+        let rounds = self.rounds();
+        let round_budget = self.budget / rounds;
+        let mut remaining = (0..self.optimizers.len()).collect::<Vec<usize>>();
+        let mut stats: Vec<Option<PortfolioStats<F>>> = vec![None; self.optimizers.len()];
+        let mut best: Option<(T, F)> = None;
+
+        for _ in 0..rounds {
+            let budget = round_budget / remaining.len();
+            for i in &remaining {
+                let optimizer = &mut self.optimizers[*i];
+                let result = optimizer.optimize(problem, budget, rng)?;
+                if best.as_ref().is_none_or(|(_, f_best)| objective.is_better(&result.best_fitness, f_best)) {
+                    best = Some((result.best.clone(), result.best_fitness.clone()));
+                }
+                let name = optimizer.name();
+                let entry = stats[*i].get_or_insert_with(|| PortfolioStats {
+                    name,
+                    rounds: 0,
+                    evaluations: 0,
+                    best_fitness: result.best_fitness.clone(),
+                    last_fitness: result.best_fitness.clone(),
+                });
+                entry.rounds += 1;
+                entry.evaluations += result.evaluations;
+                if objective.is_better(&result.best_fitness, &entry.best_fitness) {
+                    entry.best_fitness = result.best_fitness.clone();
+                }
+                entry.last_fitness = result.best_fitness;
+            }
+
+            // the better half goes on (the earlier optimizer wins a tie)
+            let last = |i: &usize| &stats[*i].as_ref().expect("every remaining optimizer ran in the round").last_fitness;
+            remaining.sort_by(|a, b| objective.compare(last(a), last(b)));
+            remaining.truncate(remaining.len().div_ceil(2));
+        }
+
+        let stats = stats.into_iter().map(|entry| entry.expect("every optimizer ran in the first round")).collect::<Vec<_>>();
+        let (best, best_fitness) = best.expect("the portfolio has at least one optimizer");
+        Ok(PortfolioResult { best, best_fitness, winner: remaining[0], evaluations: stats.iter().map(|entry| entry.evaluations).sum(), stats })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::benchmarks::{Benchmark, Function};
+    use crate::{Ascent, DeStrategy, DifferentialEvolution, Genetic, HillClimber, RandomSearch};
+
+    fn genetic<'a>() -> Genetic<'a, Vec<f64>> {
+        Genetic::builder()
+            .population(10)
+            .max_generation(1000)
+            .random_agent(|rng| (0..3).map(|_| rng.gen_range(-5.0, 5.0)).collect::<Vec<f64>>())
+            .fitness(|a: &Vec<f64>| a.iter().map(|x| x * x).sum::<f64>())
+            .minimize()
+            .mutate(|a: &Vec<f64>, rng| a.iter().map(|x| x + rng.gen_range(-0.1, 0.1)).collect())
+            .offspring(|a: &Vec<f64>, b: &Vec<f64>, _rng| a.iter().zip(b).map(|(x, y)| (x + y) / 2.0).collect())
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn portfolio() {
+        let sphere = Benchmark::new(Function::Sphere, 3);
+        assert!(Portfolio::<Vec<f64>>::new(1000).validate().is_err());
+        assert!(Portfolio::new(3).optimizer(genetic()).optimizer(genetic()).validate().is_err());
+
+        let mut portfolio = Portfolio::new(3000)
+            .optimizer(RandomSearch::new(genetic()))
+            .optimizer(HillClimber::new(genetic(), Ascent::First { tries: 20 }, usize::MAX).unwrap())
+            .optimizer(DifferentialEvolution::new(genetic(), DeStrategy::Rand1Bin, 0.5, 0.9).unwrap());
+        assert_eq!(portfolio.rounds(), 3);
+        let result = portfolio.run_with_seed(&sphere, 1);
+
+        // 3 runs of 333, 2 runs of 500 and 1 run of 1000 evaluations
+        assert!(result.evaluations <= 3000);
+        let rounds = result.stats.iter().map(|entry| entry.rounds).collect::<Vec<usize>>();
+        assert_eq!(rounds.iter().sum::<usize>(), 3 + 2 + 1);
+        assert_eq!(rounds[result.winner], 3);
+        assert_eq!(result.stats[0].rounds, 1, "random search is eliminated first");
+        assert_eq!(result.stats[0].name, "random search");
+        assert!(result.stats.iter().all(|entry| entry.best_fitness >= result.best_fitness));
+        assert!(result.best_fitness < 1e-3);
+
+        // an invalid portfolio is an error of the run
+        let mut small = Portfolio::new(3).optimizer(genetic()).optimizer(genetic());
+        assert!(matches!(small.try_run_with_rng(&sphere, &mut StdRng::seed_from_u64(1)), Err(RunError::Config(ConfigError::InvalidPortfolio(_)))));
+    }
+
+    /// The negated sphere, maximised
+    struct Peak(Benchmark);
+
+    impl Problem<Vec<f64>> for Peak {
+        fn random_agent(&self, rng: &mut dyn RngCore) -> Vec<f64> {
+            self.0.random_agent(rng)
+        }
+
+        fn fitness(&self, agent: &Vec<f64>) -> f64 {
+            -self.0.fitness(agent)
+        }
+
+        fn mutate(&self, agent: &Vec<f64>, rng: &mut dyn RngCore) -> Vec<f64> {
+            self.0.mutate(agent, rng)
+        }
+
+        fn crossover(&self, a: &Vec<f64>, b: &Vec<f64>, rng: &mut dyn RngCore) -> Vec<f64> {
+            self.0.crossover(a, b, rng)
+        }
+    }
+
+    #[test]
+    fn objective() {
+        // the optimizers minimise by their Genetics, but the problem is maximised
+        let peak = Peak(Benchmark::new(Function::Sphere, 3));
+        let mut portfolio = Portfolio::new(2000)
+            .optimizer(HillClimber::new(genetic(), Ascent::First { tries: 20 }, usize::MAX).unwrap())
+            .optimizer(DifferentialEvolution::new(genetic(), DeStrategy::Rand1Bin, 0.5, 0.9).unwrap());
+        let result = portfolio.run_with_seed(&peak, 1);
+        assert!(result.best_fitness > -1e-2);
+        assert!(result.stats.iter().all(|entry| entry.best_fitness <= result.best_fitness));
+    }
+}
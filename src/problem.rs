@@ -0,0 +1,68 @@
+use rand::RngCore;
+use std::sync::Arc;
+
+use crate::{AgentFn, FitnessFn, MutateFn, Objective, OffspringFn};
+
+/// A problem that the genetic algorithm can solve
+///
+/// It is an alternative to giving the four functions of Genetic one by one.
+/// Since the implementing struct is owned by the Genetic it can carry its own state
+/// (it has to be Send + Sync, so that the Genetic can be moved to other threads)
+/// (bounds, data tables, ...)
+/// The random decisions should be made with the given random number generator, so seeded runs can be reproduced
+///
+/// # Examples:
+///  See at Genetic::from_problem()
+pub trait Problem<T, F = f64> {
+    /// Returns one agent which is used in the 0th generation
+    fn random_agent(&self, rng: &mut dyn RngCore) -> T;
+    /// Evaluates an agent and returns it's fitness (this algorithm maximises this function,
+    /// unless objective() returns Minimize)
+    fn fitness(&self, agent: &T) -> F;
+    /// Mutates an agent and returns the mutated version of it
+    fn mutate(&self, agent: &T, rng: &mut dyn RngCore) -> T;
+    /// Crossovers two agents and creates an offspring
+    fn crossover(&self, a: &T, b: &T, rng: &mut dyn RngCore) -> T;
+    /// Whether the fitness should be maximised or minimised
+    fn objective(&self) -> Objective {
+        Objective::Maximize
+    }
+}
+
+/// A borrowed problem is a problem too, so one problem can be shared by several runs (see Optimizer)
+impl<T, F, P> Problem<T, F> for &P
+    where P: Problem<T, F> + ?Sized {
+    fn random_agent(&self, rng: &mut dyn RngCore) -> T {
+        (**self).random_agent(rng)
+    }
+
+    fn fitness(&self, agent: &T) -> F {
+        (**self).fitness(agent)
+    }
+
+    fn mutate(&self, agent: &T, rng: &mut dyn RngCore) -> T {
+        (**self).mutate(agent, rng)
+    }
+
+    fn crossover(&self, a: &T, b: &T, rng: &mut dyn RngCore) -> T {
+        (**self).crossover(a, b, rng)
+    }
+
+    fn objective(&self) -> Objective {
+        (**self).objective()
+    }
+}
+
+/// Splits a problem into the four functions used by Genetic
+pub(crate) fn into_functions<'a, T, F, P>(problem: P) -> (AgentFn<'a, T>, FitnessFn<'a, T, F>, MutateFn<'a, T>, OffspringFn<'a, T>)
+    where P: Problem<T, F> + Send + Sync + 'a {
+    let problem = Arc::new(problem);
+    let (p_agent, p_fitness, p_mutate) = (Arc::clone(&problem), Arc::clone(&problem), Arc::clone(&problem));
+
+    (
+        Box::new(move |rng| p_agent.random_agent(rng)),
+        Box::new(move |a| Ok(p_fitness.fitness(a))),
+        Box::new(move |a, rng| p_mutate.mutate(a, rng)),
+        Box::new(move |a, b, rng| vec![problem.crossover(a, b, rng)]),
+    )
+}
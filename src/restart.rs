@@ -0,0 +1,167 @@
+use rand::prelude::*;
+
+use crate::error::EvaluationBudget;
+use crate::{ConfigError, Fitness, FitnessError, Genetic, GeneticState, MaybeSync, Stagnation};
+
+/// Warm restarts: when the best fitness stagnates, the population is created again with get_random_agent, only
+/// the best `elites` agents are kept (the hall of fame of archive() is kept too), at most `max_restarts` times
+///
+/// The stagnation is measured from the last restart, so the new population has the same number of generations to
+/// improve. The new agents count as evaluations, and the restarts happen inside a single run (the stagnation
+/// stopping criterion, if it's given, should be longer than the one of the restarts, otherwise it stops the run
+/// first).
+///
+/// # Examples:
+/// ```
+/// use rand::prelude::*;
+/// use bbte_optim_tzim1773_genetic::{Genetic, Restart, Stagnation};
+///
+/// fn main() {
+///     // many local optima, a stuck population starts again around the best agent
+///     let test = Genetic::builder()
+///         .max_generation(200)
+///         .random_agent(|rng| rng.gen_range(-50.0, 50.0))
+///         .fitness(|a: &f64| (3.0 * a).cos() - 0.01 * a * a)
+///         .mutate(|a: &f64, rng| *a + rng.gen_range(-0.1, 0.1))
+///         .offspring(|a: &f64, b: &f64, _rng| (*a + *b) / 2.0)
+///         .restart(Restart::new(Stagnation::new(20)).elites(2).max_restarts(5))
+///         .build()
+///         .unwrap();
+///
+///     let result = test.run();
+///     println!("{} after {} restarts", result.best, result.restarts); // probably close to 0.0
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Restart {
+    /// The number of generations without improvement (and the smallest improvement) that triggers a restart
+    pub stagnation: Stagnation,
+    /// The number of best agents carried over into the new population (less than the population)
+    /// Suggested value: 1
+    pub elites: usize,
+    /// The maximal number of restarts in a run
+    /// Suggested value: 10
+    pub max_restarts: usize,
+}
+
+impl Restart {
+    /// Restarts on the given stagnation, keeping the best agent, at most 10 times
+    pub fn new(stagnation: Stagnation) -> Self {
+        Restart { stagnation, elites: 1, max_restarts: 10 }
+    }
+
+    /// Sets the number of best agents kept
+    pub fn elites(mut self, elites: usize) -> Self {
+        self.elites = elites;
+        self
+    }
+
+    /// Sets the maximal number of restarts
+    pub fn max_restarts(mut self, max_restarts: usize) -> Self {
+        self.max_restarts = max_restarts;
+        self
+    }
+
+    /// Checks whether the parameters are usable with the given population size
+    pub fn validate(&self, population: usize) -> Result<(), ConfigError> {
+        if self.stagnation.generations == 0 {
+            return Err(ConfigError::InvalidRestart("stagnation needs at least 1 generation".to_string()));
+        }
+        if self.stagnation.epsilon.is_nan() || self.stagnation.epsilon < 0.0 {
+            return Err(ConfigError::InvalidRestart(format!("epsilon can't be negative, got {}", self.stagnation.epsilon)));
+        }
+        if self.elites >= population {
+            return Err(ConfigError::InvalidRestart(format!("{} elites leave no room for new agents in a population of {}", self.elites, population)));
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a, T, F> Genetic<'a, T, F>
+    where T: Clone + MaybeSync, F: Fitness + MaybeSync {
+    /// Restarts the population if it's stuck (the new agents are not created if they don't fit into
+    /// max_evaluations, the run stops anyway)
+    pub(crate) fn restart<R: RngCore>(&self, state: &mut GeneticState<T, F, R>) -> Result<(), FitnessError> {
+        let restart = match &self.restart {
+            Some(restart) if state.restarts < restart.max_restarts && restart.stagnation.is_reached(&state.history[state.last_restart..], self.objective) => restart,
+            _ => return Ok(()),
+        };
+        let rng: &mut dyn RngCore = &mut state.rng;
+        let new = (restart.elites..self.population).map(|_| ((self.get_random_agent)(rng), None)).collect();
+        let new = match self.evaluate_offspring(new, &mut state.evaluations) {
+            Ok(new) => new,
+            Err(error) if error.is::<EvaluationBudget>() => return Ok(()),
+            Err(error) => return Err(error),
+        };
+        let mut elites = self.best_first(&state.population);
+        elites.truncate(restart.elites);
+        state.population = elites.into_iter().map(|i| state.population[i].clone()).chain(new).collect();
+        state.restarts += 1;
+        state.last_restart = state.generation;
+        #[cfg(feature = "tracing")]
+        tracing::info!(generation = state.generation, restarts = state.restarts, "population restarted");
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Objective;
+
+    #[test]
+    fn restarts() {
+        assert!(Restart::new(Stagnation::new(0)).validate(10).is_err());
+        assert!(Restart::new(Stagnation::new(5)).elites(10).validate(10).is_err());
+
+        // nothing ever improves, so the population is restarted after every 4th generation
+        let test = Genetic::builder()
+            .population(10)
+            .max_generation(20)
+            .pc(0.0)
+            .pm(0.0)
+            .random_agent(|rng| rng.gen_range(0, 1000))
+            .fitness(|_a: &i32| 1.0)
+            .mutate(|a: &i32, _rng| *a)
+            .offspring(|a: &i32, _b: &i32, _rng| *a)
+            .restart(Restart::new(Stagnation::new(3)).elites(2).max_restarts(2))
+            .build()
+            .unwrap();
+
+        let mut state = test.start_with_rng(StdRng::seed_from_u64(1)).unwrap();
+        for _ in 0..3 {
+            test.step(&mut state).unwrap();
+        }
+        let kept = state.population()[..2].to_vec();
+        assert_eq!(state.restarts(), 0);
+        test.step(&mut state).unwrap();
+        assert_eq!(state.restarts(), 1);
+        assert_eq!(&state.population()[..2], &kept[..]);
+
+        let result = test.finish({
+            while test.step(&mut state).unwrap().is_none() {}
+            state
+        });
+        assert_eq!(result.restarts, 2);
+        assert_eq!(result.evaluations, 10 + 2 * 8);
+        assert_eq!(result.population.len(), 10);
+
+        // an improving run is not restarted
+        let improving = Genetic::builder()
+            .population(10)
+            .max_generation(20)
+            .pm(1.0)
+            .objective(Objective::Minimize)
+            .random_agent(|rng| rng.gen_range(100.0, 200.0))
+            .fitness(|a: &f64| *a)
+            .mutate(|a: &f64, _rng| *a - 1.0)
+            .offspring(|a: &f64, _b: &f64, _rng| *a)
+            .restart(Restart::new(Stagnation::new(3)))
+            .build()
+            .unwrap();
+        assert_eq!(improving.run_with_seed(1).restarts, 0);
+    }
+}
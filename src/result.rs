@@ -0,0 +1,85 @@
+use crate::{Evaluated, Fitness, Objective, OperatorStats, Termination};
+
+/// The outcome of a run
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunResult<T, F = f64> {
+    /// The population of the last generation
+    pub population: Vec<T>,
+    /// The best agent of the last generation
+    pub best: T,
+    /// The fitness of the best agent
+    pub best_fitness: F,
+    /// The number of generations executed (less than max_generation if the run stopped early)
+    pub generations: usize,
+    /// The number of times the fitness function was called
+    pub evaluations: usize,
+    /// How many times the mutation operators were used and how many times they created improvements
+    /// (f_mutate is a single operator if there are no registered ones)
+    pub mutation_stats: Vec<OperatorStats>,
+    /// How many times the crossover operators were used and how many times they created improvements
+    pub crossover_stats: Vec<OperatorStats>,
+    /// The reason the run stopped
+    pub termination: Termination,
+    /// The statistics of the fitness in every generation (for plotting the convergence)
+    pub history: Vec<GenerationStats>,
+    /// The best agents evaluated during the run with their fitness (the best one first), empty if there's no archive
+    pub archive: Vec<Evaluated<T, F>>,
+    /// The number of times the population was restarted (see restart())
+    pub restarts: usize,
+}
+
+/// Statistics of the fitness of a generation (using Fitness::to_f64())
+///
+/// Values that are not finite (like the worst fitness assigned to failed evaluations) are left out,
+/// if there are none left every statistic is NaN.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GenerationStats {
+    /// The fitness of the best agent
+    pub best: f64,
+    /// The average fitness
+    pub mean: f64,
+    /// The middle value of the fitness (the average of the two middle values for an even number of agents)
+    pub median: f64,
+    /// The fitness of the worst agent
+    pub worst: f64,
+    /// The standard deviation of the fitness
+    pub std_dev: f64,
+}
+
+impl GenerationStats {
+    /// Computes the statistics of the fitness of a generation
+    pub fn new<'f, F: Fitness + 'f>(fitness: impl Iterator<Item = &'f F>, objective: Objective) -> Self {
+        let mut values = fitness.map(Fitness::to_f64).filter(|x| x.is_finite()).collect::<Vec<f64>>();
+        if values.is_empty() {
+            return GenerationStats { best: f64::NAN, mean: f64::NAN, median: f64::NAN, worst: f64::NAN, std_dev: f64::NAN };
+        }
+        values.sort_by(|a, b| objective.compare(a, b));
+        let n = values.len();
+        let mean = values.iter().sum::<f64>() / n as f64;
+        let median = if n % 2 == 0 { (values[n / 2 - 1] + values[n / 2]) / 2.0 } else { values[n / 2] };
+
+        GenerationStats {
+            best: values[0],
+            mean,
+            median,
+            worst: values[n - 1],
+            std_dev: (values.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / n as f64).sqrt(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generation_stats() {
+        let stats = GenerationStats::new([2.0, f64::NEG_INFINITY, 4.0, 1.0, 5.0].iter(), Objective::Maximize);
+        assert_eq!(stats, GenerationStats { best: 5.0, mean: 3.0, median: 3.0, worst: 1.0, std_dev: 2.5f64.sqrt() });
+
+        let stats = GenerationStats::new([3, 1, 2].iter(), Objective::Minimize);
+        assert_eq!((stats.best, stats.median, stats.worst), (1.0, 2.0, 3.0));
+        assert!(GenerationStats::new(Vec::<f64>::new().iter(), Objective::Maximize).mean.is_nan());
+    }
+}
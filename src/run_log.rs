@@ -0,0 +1,172 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::sync::Mutex;
+
+use crate::external::EncodeFn;
+use crate::{GenerationStats, LineEncode, LineFormat};
+
+/// Writes the statistics of every generation to a file (or any other writer) while the run goes on,
+/// one line per generation, so the file can be followed (tail -f) and plotted live
+///
+/// In CSV format the first line is the header: generation, evaluations, best, mean, median, worst,
+/// std_dev (and best_agent if it's written). In JSON format every line is an object with the same fields
+/// (JSON lines). Every line is flushed right away. If the same Genetic is run again, the lines of the new run
+/// are appended.
+///
+/// # Examples:
+/// ```no_run
+/// use rand::prelude::*;
+/// use bbte_optim_tzim1773_genetic::{Genetic, RunLog};
+///
+/// fn main() {
+///     let test = Genetic::builder()
+///         .max_generation(1000)
+///         .random_agent(|rng| rng.gen_range(-5.0, 5.0))
+///         .fitness(|a: &f64| 5.0 - a * a)
+///         .mutate(|a: &f64, rng| *a + rng.gen_range(-0.1, 0.1))
+///         .offspring(|a: &f64, b: &f64, _rng| (*a + *b) / 2.0)
+///         .run_log(RunLog::create("run.jsonl").unwrap().with_best())
+///         .build()
+///         .unwrap();
+///
+///     println!("{}", test.run().best);
+/// }
+/// ```
+pub struct RunLog<T> {
+    format: LineFormat,
+    encode: Option<EncodeFn<T>>,
+    sink: Mutex<Sink>,
+}
+
+/// The writer of the lines and whether the CSV header was written already
+struct Sink {
+    writer: Box<dyn Write + Send>,
+    header: bool,
+}
+
+impl<T> RunLog<T> {
+    /// Writes the lines in the given format
+    pub fn new(writer: impl Write + Send + 'static, format: LineFormat) -> Self {
+        RunLog { format, encode: None, sink: Mutex::new(Sink { writer: Box::new(writer), header: false }) }
+    }
+
+    /// Creates the file (replacing the old one), the format is JSON lines if its extension is .jsonl or .json,
+    /// CSV otherwise
+    pub fn create(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref();
+        let format = match path.extension().and_then(|extension| extension.to_str()) {
+            Some("jsonl") | Some("json") => LineFormat::Json,
+            _ => LineFormat::Csv,
+        };
+
+        Ok(Self::new(BufWriter::new(File::create(path)?), format))
+    }
+
+    /// The best agent of every generation is written too (as one CSV field in quotes, or as a JSON value)
+    pub fn with_best(self) -> Self
+        where T: LineEncode {
+        self.with_best_encoder(|agent: &T, format: LineFormat| format.encode(agent))
+    }
+
+    /// The best agent of every generation is written with the given function (it gets the agent and the format,
+    /// its result shouldn't contain line breaks, and quotes in CSV format)
+    pub fn with_best_encoder(mut self, encode: impl Fn(&T, LineFormat) -> String + Send + Sync + 'static) -> Self {
+        self.encode = Some(Box::new(encode));
+        self
+    }
+
+    /// Writes the line of a generation
+    pub(crate) fn record(&self, generation: usize, evaluations: usize, stats: &GenerationStats, best: &T) -> io::Result<()> {
+        let fields = [("best", stats.best), ("mean", stats.mean), ("median", stats.median), ("worst", stats.worst), ("std_dev", stats.std_dev)];
+        let best = self.encode.as_ref().map(|encode| encode(best, self.format));
+        let mut line = String::new();
+        match self.format {
+            LineFormat::Csv => {
+                line.push_str(&format!("{},{}", generation, evaluations));
+                for (_, value) in &fields {
+                    line.push(',');
+                    value.write_csv(&mut line);
+                }
+                if let Some(best) = best {
+                    line.push_str(&format!(",\"{}\"", best));
+                }
+            }
+            LineFormat::Json => {
+                line.push_str(&format!("{{\"generation\":{},\"evaluations\":{}", generation, evaluations));
+                for (name, value) in &fields {
+                    line.push_str(&format!(",\"{}\":", name));
+                    value.write_json(&mut line);
+                }
+                if let Some(best) = best {
+                    line.push_str(&format!(",\"best_agent\":{}", best));
+                }
+                line.push('}');
+            }
+        }
+
+        let mut sink = self.sink.lock().expect("a thread panicked while writing the run log");
+        if self.format == LineFormat::Csv && !sink.header {
+            let header = if self.encode.is_some() { ",best_agent" } else { "" };
+            writeln!(sink.writer, "generation,evaluations,best,mean,median,worst,std_dev{}", header)?;
+        }
+        sink.header = true;
+        writeln!(sink.writer, "{}", line)?;
+        sink.writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Genetic;
+    use rand::Rng;
+    use std::sync::Arc;
+
+    /// Writer whose content can be read after it's given to the RunLog
+    #[derive(Clone, Default)]
+    struct Shared(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn lines(format: LineFormat) -> Vec<String> {
+        let output = Shared::default();
+        let test = Genetic::builder()
+            .population(10)
+            .max_generation(5)
+            .random_agent(|rng| vec![rng.gen_range(-5.0, 5.0); 2])
+            .fitness(|a: &Vec<f64>| -a[0] * a[0] - a[1] * a[1])
+            .mutate(|a: &Vec<f64>, rng| a.iter().map(|x| x + rng.gen_range(-0.1, 0.1)).collect())
+            .offspring(|a: &Vec<f64>, _b: &Vec<f64>, _rng| a.clone())
+            .run_log(RunLog::new(output.clone(), format).with_best())
+            .build()
+            .unwrap();
+        test.run();
+
+        let content = String::from_utf8(output.0.lock().unwrap().clone()).unwrap();
+        content.lines().map(String::from).collect()
+    }
+
+    #[test]
+    fn run_log() {
+        let csv = lines(LineFormat::Csv);
+        assert_eq!(csv.len(), 6);
+        assert_eq!(csv[0], "generation,evaluations,best,mean,median,worst,std_dev,best_agent");
+        assert!(csv[1].starts_with("0,"));
+        assert_eq!(csv[5].matches(',').count(), 8);
+
+        let json = lines(LineFormat::Json);
+        assert_eq!(json.len(), 5);
+        assert!(json[4].starts_with("{\"generation\":4,\"evaluations\":"));
+        assert!(json[4].contains("\"best_agent\":["));
+        assert!(json.iter().all(|line| line.ends_with("]}")));
+    }
+}
@@ -0,0 +1,139 @@
+//! Helpers turning several criteria into the fitness of a single-objective run, for the problems that don't need
+//! a whole Pareto front (see MultiObjective for those)
+//!
+//! weighted_sum() adds up the criteria with weights, lexicographic() compares them one after the other, the values
+//! rounded down to the same multiple of a tolerance counting as a tie. Both wrap a function returning the criteria,
+//! the result can be given to the builder with fitness().
+//!
+//! # Examples:
+//! ```
+//! use rand::prelude::*;
+//! use bbte_optim_tzim1773_genetic::{Genetic, Objective};
+//! use bbte_optim_tzim1773_genetic::scalarization;
+//!
+//! fn main() {
+//!     // the distance from 3 matters first (in steps of 0.1), then the agent should be small
+//!     let test = Genetic::builder()
+//!         .max_generation(100)
+//!         .objective(Objective::Minimize)
+//!         .random_agent(|rng| rng.gen_range(-10.0, 10.0))
+//!         .fitness(scalarization::lexicographic(vec![0.1, 0.0], |a: &f64| vec![(a - 3.0).abs(), *a]))
+//!         .mutate(|a: &f64, rng| *a + rng.gen_range(-0.1, 0.1))
+//!         .offspring(|a: &f64, b: &f64, _rng| (*a + *b) / 2.0)
+//!         .build()
+//!         .unwrap();
+//!
+//!     println!("{}", test.run().best); // probably a bit more than 2.9
+//! }
+//! ```
+use std::cmp::Ordering;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::Fitness;
+
+/// Returns a fitness function that is the weighted sum of the criteria (a negative weight turns a criterion to
+/// be minimised into one to be maximised and the other way around, the missing criteria count as 0.0)
+pub fn weighted_sum<T>(weights: Vec<f64>, f_criteria: impl Fn(&T) -> Vec<f64>) -> impl Fn(&T) -> f64 {
+    move |x| f_criteria(x).iter().zip(&weights).map(|(value, weight)| value * weight).sum()
+}
+
+/// Returns a fitness function comparing the criteria lexicographically: the first criterion decides, if the
+/// values are the same multiple of its tolerance (rounded down) the second one, and so on (the criteria without
+/// a tolerance have 0.0, their values have to be equal for a tie)
+pub fn lexicographic<T>(tolerances: Vec<f64>, f_criteria: impl Fn(&T) -> Vec<f64>) -> impl Fn(&T) -> Lexicographic {
+    move |x| Lexicographic { values: f_criteria(x), tolerances: tolerances.clone() }
+}
+
+/// Fitness of criteria compared lexicographically with tolerances: the values of a criterion are rounded down to
+/// a multiple of its tolerance, if they are the same it's a tie and the next criterion decides
+///
+/// The values are rounded instead of checking whether they differ less than the tolerance, because that wouldn't
+/// be transitive (a ~ b and b ~ c doesn't mean a ~ c), and the agents couldn't be sorted. So two values closer than
+/// the tolerance can differ if a multiple of the tolerance is between them.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone)]
+pub struct Lexicographic {
+    /// The values of the criteria, the most important one first
+    pub values: Vec<f64>,
+    /// The tolerances of the criteria
+    pub tolerances: Vec<f64>,
+}
+
+impl PartialEq for Lexicographic {
+    fn eq(&self, other: &Self) -> bool {
+        self.partial_cmp(other) == Some(Ordering::Equal)
+    }
+}
+
+impl PartialOrd for Lexicographic {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        for (i, (a, b)) in self.values.iter().zip(&other.values).enumerate() {
+            let tolerance = self.tolerances.get(i).or_else(|| other.tolerances.get(i)).cloned().unwrap_or(0.0);
+            let (a, b) = if tolerance > 0.0 { ((a / tolerance).floor(), (b / tolerance).floor()) } else { (*a, *b) };
+            match a.partial_cmp(&b) {
+                Some(Ordering::Equal) => {}
+                ordering => return ordering,
+            }
+        }
+
+        self.values.len().partial_cmp(&other.values.len())
+    }
+}
+
+impl Fitness for Lexicographic {
+    fn lowest() -> Self {
+        Lexicographic { values: vec![f64::NEG_INFINITY], tolerances: Vec::new() }
+    }
+
+    fn highest() -> Self {
+        Lexicographic { values: vec![f64::INFINITY], tolerances: Vec::new() }
+    }
+
+    fn to_f64(&self) -> f64 {
+        self.values.first().cloned().unwrap_or(f64::NEG_INFINITY)
+    }
+
+    fn objectives(&self) -> Vec<f64> {
+        self.values.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Genetic, Objective};
+    use rand::Rng;
+
+    #[test]
+    fn compare() {
+        let criteria = |a: &(f64, f64)| vec![a.0, a.1];
+        assert_eq!(weighted_sum(vec![2.0, -1.0], criteria)(&(3.0, 1.0)), 5.0);
+
+        let fitness = lexicographic(vec![0.5], criteria);
+        // the first criterion is a tie, the second one decides
+        assert!(fitness(&(1.0, 5.0)) > fitness(&(1.4, 2.0)));
+        assert!(fitness(&(1.0, 5.0)) < fitness(&(1.6, 2.0)));
+        assert!(fitness(&(1.0, 2.0)) == fitness(&(1.2, 2.0)));
+        assert!(fitness(&(0.9, 2.0)) < fitness(&(1.0, 2.0)));
+        assert_eq!(fitness(&(1.0, f64::NAN)).partial_cmp(&fitness(&(1.0, 2.0))), None);
+        assert!(Lexicographic::lowest() < fitness(&(-1e300, 0.0)));
+    }
+
+    #[test]
+    fn lexicographic_run() {
+        let test = Genetic::builder()
+            .max_generation(100)
+            .objective(Objective::Minimize)
+            .random_agent(|rng| rng.gen_range(-10.0, 10.0))
+            .fitness(lexicographic(vec![0.1, 0.0], |a: &f64| vec![(a - 3.0).abs(), *a]))
+            .mutate(|a: &f64, rng| *a + rng.gen_range(-0.1, 0.1))
+            .offspring(|a: &f64, b: &f64, _rng| (*a + *b) / 2.0)
+            .build()
+            .unwrap();
+
+        // the distance from 3 is less than 0.1, the agent is the smallest of them
+        let best = test.run_with_seed(1).best;
+        assert!((2.9..3.0).contains(&best));
+    }
+}
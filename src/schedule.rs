@@ -0,0 +1,117 @@
+use crate::ConfigError;
+
+/// Function that returns the value of a parameter in the given generation
+pub type ScheduleFn<'a> = Box<dyn Fn(usize) -> f64 + Send + Sync + 'a>;
+
+/// Value of a probability (pc or pm) that changes across the generations
+///
+/// The values are clamped between 0.0 and 1.0.
+///
+/// # Examples:
+/// ```
+/// use rand::prelude::*;
+/// use bbte_optim_tzim1773_genetic::{Genetic, Schedule};
+///
+/// fn main() {
+///     let test = Genetic::builder()
+///         .max_generation(50)
+///         .random_agent(|rng| rng.gen_range(-5.0, 5.0))
+///         .fitness(|a: &f64| 5.0 - a * a)
+///         .mutate(|a: &f64, rng| *a + rng.gen_range(-0.1, 0.1))
+///         .offspring(|a: &f64, b: &f64, _rng| (*a + *b) / 2.0)
+///         // lots of mutation at the beginning, little at the end
+///         .pm_schedule(Schedule::Linear { start: 0.8, end: 0.1 })
+///         .pc_schedule(Schedule::Custom(Box::new(|g| if g < 10 { 0.2 } else { 0.7 })))
+///         .build()
+///         .unwrap();
+///
+///     println!("{}", test.run().best); // should be a number close to 0
+/// }
+/// ```
+pub enum Schedule<'a> {
+    /// Changes linearly from start (first generation) to end (last generation)
+    Linear {
+        /// Value in the first generation
+        start: f64,
+        /// Value in the last generation
+        end: f64,
+    },
+    /// Multiplied by rate in every generation: start * rate^generation
+    Exponential {
+        /// Value in the first generation
+        start: f64,
+        /// Multiplier of a generation (usually a bit smaller than 1.0, e.g. 0.99)
+        rate: f64,
+    },
+    /// Changes from start to end along half a cosine wave (slowly at the beginning and the end)
+    Cosine {
+        /// Value in the first generation
+        start: f64,
+        /// Value in the last generation
+        end: f64,
+    },
+    /// Your own function of the generation (starting from 0)
+    Custom(ScheduleFn<'a>),
+}
+
+impl<'a> Schedule<'a> {
+    /// Returns the value in the given generation
+    ///
+    /// # Arguments:
+    ///
+    /// * `generation` the index of the generation (starting from 0)
+    /// * `max_generation` the number of generations of the run
+    pub fn value(&self, generation: usize, max_generation: usize) -> f64 {
+        let progress = if max_generation > 1 { generation as f64 / (max_generation - 1) as f64 } else { 0.0 };
+        let value = match self {
+            Schedule::Linear { start, end } => start + (end - start) * progress,
+            Schedule::Exponential { start, rate } => start * rate.powi(generation as i32),
+            Schedule::Cosine { start, end } => end + (start - end) * (1.0 + (std::f64::consts::PI * progress).cos()) / 2.0,
+            Schedule::Custom(f) => f(generation),
+        };
+        if value.is_nan() {
+            return 0.0;
+        }
+        value.clamp(0.0, 1.0)
+    }
+
+    /// Checks whether the parameters are usable (the values of Custom can't be checked, they are clamped)
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let probability = |x: &f64| (0.0..=1.0).contains(x);
+        let valid = match self {
+            Schedule::Linear { start, end } | Schedule::Cosine { start, end } => probability(start) && probability(end),
+            Schedule::Exponential { start, rate } => probability(start) && *rate >= 0.0,
+            Schedule::Custom(_) => true,
+        };
+        if !valid {
+            return Err(ConfigError::InvalidSchedule("the values must be between 0.0 and 1.0 and the rate can't be negative".to_string()));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn values() {
+        let linear = Schedule::Linear { start: 1.0, end: 0.0 };
+        assert_eq!(linear.value(0, 11), 1.0);
+        assert!((linear.value(5, 11) - 0.5).abs() < 1e-12);
+        assert_eq!(linear.value(10, 11), 0.0);
+
+        let cosine = Schedule::Cosine { start: 0.2, end: 0.8 };
+        assert!((cosine.value(0, 11) - 0.2).abs() < 1e-12);
+        assert!((cosine.value(5, 11) - 0.5).abs() < 1e-12);
+        assert!((cosine.value(10, 11) - 0.8).abs() < 1e-12);
+
+        let exponential = Schedule::Exponential { start: 0.8, rate: 0.5 };
+        assert!((exponential.value(2, 11) - 0.2).abs() < 1e-12);
+
+        assert_eq!(Schedule::Custom(Box::new(|g| g as f64)).value(3, 11), 1.0);
+        assert!(Schedule::Linear { start: 1.5, end: 0.0 }.validate().is_err());
+        assert!(Schedule::Exponential { start: 0.5, rate: -1.0 }.validate().is_err());
+    }
+}
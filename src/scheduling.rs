@@ -0,0 +1,462 @@
+//! Shop scheduling problems: the job shop (every job visits the machines in its own order) and the permutation flow
+//! shop (every job visits the machines in the same order), with the makespan (the end of the last operation) as the
+//! fitness, minimised
+//!
+//! A JobShop is a Problem of operation-based genomes: a Vec<usize> in which job j appears as many times as it has
+//! operations, and its k-th appearance stands for its k-th operation. Every such sequence decodes into a feasible
+//! schedule, so the operators can't create invalid agents. A FlowShop is a Problem of Permutation agents, the order
+//! the jobs are processed in on every machine. Both decode the agents into a ShopSchedule with the start and end of
+//! every operation.
+//!
+//! # Examples:
+//! ```
+//! use bbte_optim_tzim1773_genetic::Genetic;
+//! use bbte_optim_tzim1773_genetic::scheduling::JobShop;
+//!
+//! fn main() {
+//!     // 3 jobs on 3 machines, every line is a job: (machine, processing time) pairs
+//!     let problem = JobShop::parse("3 3\n0 3 1 2 2 2\n0 2 2 1 1 4\n1 4 2 3").unwrap();
+//!     let result = Genetic::from_problem(30, 50, 0.8, 0.3, problem.clone()).run();
+//!
+//!     let schedule = problem.decode(&result.best);
+//!     assert_eq!(schedule.makespan, result.best_fitness);
+//!     for operation in schedule.operations {
+//!         println!("job {} on machine {}: {} - {}", operation.job, operation.machine, operation.start, operation.end);
+//!     }
+//! }
+//! ```
+
+use rand::prelude::*;
+
+use crate::genome::{InsertMutation, OrderCrossover, Permutation};
+use crate::operators::{Crossover, Mutation, Swap};
+use crate::{ConfigError, Objective, Problem};
+
+/// An operation placed into a schedule
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScheduledOperation {
+    /// The job of the operation
+    pub job: usize,
+    /// The index of the operation in its job
+    pub operation: usize,
+    /// The machine the operation is processed on
+    pub machine: usize,
+    /// The start of the processing
+    pub start: f64,
+    /// The end of the processing
+    pub end: f64,
+}
+
+/// A decoded schedule: when every operation is processed
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShopSchedule {
+    /// The operations in the order they were placed
+    pub operations: Vec<ScheduledOperation>,
+    /// The end of the last operation
+    pub makespan: f64,
+}
+
+impl ShopSchedule {
+    fn new(operations: Vec<ScheduledOperation>) -> Self {
+        let makespan = operations.iter().map(|operation| operation.end).fold(0.0, f64::max);
+        ShopSchedule { operations, makespan }
+    }
+
+    /// Returns the operations of a machine in the order they are processed
+    pub fn machine(&self, machine: usize) -> Vec<ScheduledOperation> {
+        let mut operations = self.operations.iter().filter(|operation| operation.machine == machine).copied().collect::<Vec<_>>();
+        operations.sort_by(|a, b| a.start.partial_cmp(&b.start).unwrap_or(std::cmp::Ordering::Equal));
+        operations
+    }
+}
+
+/// Checks that a processing time is usable
+fn check_duration(duration: f64) -> Result<(), ConfigError> {
+    if !duration.is_finite() || duration < 0.0 {
+        return Err(ConfigError::InvalidScheduling(format!("the processing times must be finite and not negative, got {}", duration)));
+    }
+    Ok(())
+}
+
+/// Reads the numbers of jobs and machines of an instance
+fn sizes(jobs: &str, machines: &str) -> Result<(usize, usize), ConfigError> {
+    let (jobs, machines) = (whole(jobs)?, whole(machines)?);
+    if jobs == 0 || machines == 0 {
+        return Err(ConfigError::InvalidScheduling("there has to be at least one job and one machine".to_string()));
+    }
+    Ok((jobs, machines))
+}
+
+/// Reads the machine of an operation from an instance numbering the machines from `first`, numbered from 0
+fn machine(token: &str, first: usize, machines: usize) -> Result<usize, ConfigError> {
+    let machine = whole(token)?;
+    if machine < first || machine - first >= machines {
+        return Err(ConfigError::InvalidScheduling(format!("the machines are numbered from {} to {}, got {}", first, first + machines - 1, machine)));
+    }
+    Ok(machine - first)
+}
+
+/// Reads a count or a machine of an instance
+fn whole(token: &str) -> Result<usize, ConfigError> {
+    token.parse::<usize>().map_err(|_| ConfigError::InvalidScheduling(format!("not a whole number: {}", token)))
+}
+
+/// Reads a processing time of an instance
+fn duration(token: &str) -> Result<f64, ConfigError> {
+    token.parse::<f64>().map_err(|_| ConfigError::InvalidScheduling(format!("not a number: {}", token)))
+}
+
+/// A job shop scheduling problem: every job is a sequence of operations, each processed on a given machine for a given
+/// time, a machine processes one operation at a time, and the operations of a job are processed in order
+///
+/// The genome is operation-based (see the module documentation). A sequence is decoded by taking the operations in
+/// its order and placing each of them into the earliest gap of its machine that starts after the previous operation
+/// of its job (so the schedules are semi-active, and idle time is filled when possible). The mutation swaps two genes,
+/// the crossover is the precedence preserving order-based crossover (POX): the genes of a random set of jobs stay at
+/// their place in the first parent, and the rest come in the order of the second one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JobShop {
+    jobs: Vec<Vec<(usize, f64)>>,
+    machines: usize,
+}
+
+impl JobShop {
+    /// Creates the problem from the operations of the jobs: (machine, processing time) pairs in the order they have to
+    /// be processed (the machines are numbered from 0)
+    pub fn new(jobs: Vec<Vec<(usize, f64)>>) -> Result<Self, ConfigError> {
+        if jobs.is_empty() || jobs.iter().any(Vec::is_empty) {
+            return Err(ConfigError::InvalidScheduling("there has to be at least one job, and every job needs an operation".to_string()));
+        }
+        for (_, duration) in jobs.iter().flatten() {
+            check_duration(*duration)?;
+        }
+        let machines = jobs.iter().flatten().map(|(machine, _)| machine + 1).max().unwrap_or(0);
+        Ok(JobShop { jobs, machines })
+    }
+
+    /// Reads an instance in the standard format of the OR-Library: the numbers of jobs and machines, then a line for
+    /// every job with (machine, processing time) pairs, the machines numbered from 0 (lines starting with # are
+    /// comments)
+    pub fn parse(text: &str) -> Result<Self, ConfigError> {
+        let mut lines = text.lines().map(str::trim).filter(|line| !line.is_empty() && !line.starts_with('#'));
+
+        let header = lines.next().unwrap_or("").split_whitespace().collect::<Vec<&str>>();
+        if header.len() != 2 {
+            return Err(ConfigError::InvalidScheduling("the first line should be the number of jobs and machines".to_string()));
+        }
+        let (n_jobs, n_machines) = sizes(header[0], header[1])?;
+        let mut jobs = Vec::with_capacity(n_jobs);
+        for line in lines.by_ref().take(n_jobs) {
+            let tokens = line.split_whitespace().collect::<Vec<&str>>();
+            if tokens.len() % 2 != 0 {
+                return Err(ConfigError::InvalidScheduling(format!("a job should be (machine, processing time) pairs: {}", line)));
+            }
+            jobs.push(tokens.chunks(2).map(|pair| Ok((machine(pair[0], 0, n_machines)?, duration(pair[1])?))).collect::<Result<Vec<_>, ConfigError>>()?);
+        }
+        if jobs.len() != n_jobs {
+            return Err(ConfigError::InvalidScheduling(format!("{} jobs are needed, got {}", n_jobs, jobs.len())));
+        }
+
+        let mut problem = JobShop::new(jobs)?;
+        problem.machines = n_machines;
+        Ok(problem)
+    }
+
+    /// Reads an instance in the format of Taillard's benchmarks: the numbers of jobs and machines (the seeds and the
+    /// bounds after them are ignored), then the processing times of the operations as a line for every job, then the
+    /// machines of the operations (numbered from 1) as a line for every job; the lines not starting with a number
+    /// (the labels, e.g. "Times" and "Machines", and the comments) are skipped
+    pub fn parse_taillard(text: &str) -> Result<Self, ConfigError> {
+        let mut lines = text.lines().filter(|line| line.split_whitespace().next().is_some_and(|token| token.parse::<f64>().is_ok()));
+
+        let header = lines.next().unwrap_or("").split_whitespace().collect::<Vec<&str>>();
+        if header.len() < 2 {
+            return Err(ConfigError::InvalidScheduling("the first line should start with the number of jobs and machines".to_string()));
+        }
+        let (n_jobs, n_machines) = sizes(header[0], header[1])?;
+        let rows = lines.take(2 * n_jobs).map(|line| line.split_whitespace().collect::<Vec<&str>>()).collect::<Vec<_>>();
+        if rows.len() != 2 * n_jobs || rows.iter().any(|row| row.len() != n_machines) {
+            return Err(ConfigError::InvalidScheduling(format!("{} lines of processing times and {} lines of machines are needed, with {} numbers in each", n_jobs, n_jobs, n_machines)));
+        }
+        let (times, machines) = rows.split_at(n_jobs);
+        let jobs = times
+            .iter()
+            .zip(machines)
+            .map(|(times, machines)| times.iter().zip(machines).map(|(time, m)| Ok((machine(m, 1, n_machines)?, duration(time)?))).collect::<Result<Vec<_>, ConfigError>>())
+            .collect::<Result<Vec<_>, ConfigError>>()?;
+
+        let mut problem = JobShop::new(jobs)?;
+        problem.machines = n_machines;
+        Ok(problem)
+    }
+
+    /// Returns the operations of the jobs
+    pub fn jobs(&self) -> &[Vec<(usize, f64)>] {
+        &self.jobs
+    }
+
+    /// Returns the number of machines
+    pub fn machines(&self) -> usize {
+        self.machines
+    }
+
+    /// Returns a lower bound of the makespan: the longest job, or the most loaded machine
+    pub fn lower_bound(&self) -> f64 {
+        let mut loads = vec![0.0; self.machines];
+        for (machine, duration) in self.jobs.iter().flatten() {
+            loads[*machine] += duration;
+        }
+        self.jobs.iter()
+            .map(|job| job.iter().map(|(_, duration)| duration).sum::<f64>())
+            .chain(loads)
+            .fold(0.0, f64::max)
+    }
+
+    /// Decodes an operation-based sequence into a schedule
+    ///
+    /// The genes of the jobs without remaining operations are skipped, and the operations missing from the sequence
+    /// are placed after the others, so every sequence gives a complete schedule.
+    pub fn decode(&self, sequence: &[usize]) -> ShopSchedule {
+        let mut next = vec![0; self.jobs.len()];
+        let mut ready = vec![0.0; self.jobs.len()];
+        // the busy intervals of the machines, ordered by their start
+        let mut busy: Vec<Vec<(f64, f64)>> = vec![Vec::new(); self.machines];
+        let mut operations = Vec::with_capacity(self.jobs.iter().map(Vec::len).sum());
+
+        let missing = (0..self.jobs.len()).flat_map(|job| std::iter::repeat_n(job, self.jobs[job].len()));
+        for job in sequence.iter().copied().chain(missing) {
+            let (machine, duration) = match self.jobs.get(job).and_then(|operations| operations.get(next[job])) {
+                Some(operation) => *operation,
+                None => continue,
+            };
+            let intervals = &mut busy[machine];
+            let mut start = ready[job];
+            let mut position = intervals.len();
+            for (k, (begin, end)) in intervals.iter().enumerate() {
+                if start + duration <= *begin {
+                    position = k;
+                    break;
+                }
+                start = start.max(*end);
+            }
+            intervals.insert(position, (start, start + duration));
+
+            operations.push(ScheduledOperation { job, operation: next[job], machine, start, end: start + duration });
+            next[job] += 1;
+            ready[job] = start + duration;
+        }
+        ShopSchedule::new(operations)
+    }
+
+    /// Returns the makespan of the decoded sequence
+    pub fn makespan(&self, sequence: &[usize]) -> f64 {
+        self.decode(sequence).makespan
+    }
+}
+
+impl Problem<Vec<usize>> for JobShop {
+    fn random_agent(&self, rng: &mut dyn RngCore) -> Vec<usize> {
+        let mut genes = (0..self.jobs.len())
+            .flat_map(|job| std::iter::repeat_n(job, self.jobs[job].len()))
+            .collect::<Vec<usize>>();
+        genes.shuffle(rng);
+        genes
+    }
+
+    fn fitness(&self, agent: &Vec<usize>) -> f64 {
+        self.makespan(agent)
+    }
+
+    fn mutate(&self, agent: &Vec<usize>, rng: &mut dyn RngCore) -> Vec<usize> {
+        Swap.mutate(agent, rng)
+    }
+
+    fn crossover(&self, a: &Vec<usize>, b: &Vec<usize>, rng: &mut dyn RngCore) -> Vec<usize> {
+        let kept = (0..self.jobs.len()).map(|_| rng.gen::<bool>()).collect::<Vec<bool>>();
+        let mut rest = b.iter().filter(|job| !kept[**job]);
+        a.iter()
+            .map(|job| if kept[*job] { *job } else { rest.next().copied().unwrap_or(*job) })
+            .collect()
+    }
+
+    fn objective(&self) -> Objective {
+        Objective::Minimize
+    }
+}
+
+/// A permutation flow shop scheduling problem: every job is processed on every machine in the same order (machine 0,
+/// then 1, ...), and the jobs are processed in the same order on every machine
+///
+/// The agents are the orders of the jobs, every operation starts as soon as its machine and its job are free. The
+/// mutation moves a job to another place, the crossover is the order crossover.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlowShop {
+    processing: Vec<Vec<f64>>,
+}
+
+impl FlowShop {
+    /// Creates the problem from the processing times (processing[j][m] is the time of job j on machine m)
+    pub fn new(processing: Vec<Vec<f64>>) -> Result<Self, ConfigError> {
+        let machines = processing.first().map_or(0, Vec::len);
+        if processing.is_empty() || machines == 0 || processing.iter().any(|job| job.len() != machines) {
+            return Err(ConfigError::InvalidScheduling("there has to be at least one job, and every job needs a time on every machine".to_string()));
+        }
+        for duration in processing.iter().flatten() {
+            check_duration(*duration)?;
+        }
+        Ok(FlowShop { processing })
+    }
+
+    /// Returns the number of jobs
+    pub fn jobs(&self) -> usize {
+        self.processing.len()
+    }
+
+    /// Returns the number of machines
+    pub fn machines(&self) -> usize {
+        self.processing[0].len()
+    }
+
+    /// Decodes the order of the jobs into a schedule
+    pub fn decode(&self, order: &[usize]) -> ShopSchedule {
+        // the end of the last operation on every machine
+        let mut free = vec![0.0; self.machines()];
+        let mut operations = Vec::with_capacity(order.len() * self.machines());
+        for job in order {
+            let mut ready = 0.0;
+            for (machine, duration) in self.processing[*job].iter().enumerate() {
+                let start = f64::max(ready, free[machine]);
+                ready = start + duration;
+                free[machine] = ready;
+                operations.push(ScheduledOperation { job: *job, operation: machine, machine, start, end: ready });
+            }
+        }
+        ShopSchedule::new(operations)
+    }
+
+    /// Returns the makespan of the order of the jobs
+    pub fn makespan(&self, order: &[usize]) -> f64 {
+        self.decode(order).makespan
+    }
+}
+
+impl Problem<Permutation> for FlowShop {
+    fn random_agent(&self, rng: &mut dyn RngCore) -> Permutation {
+        Permutation::random(self.jobs(), rng)
+    }
+
+    fn fitness(&self, agent: &Permutation) -> f64 {
+        self.makespan(agent)
+    }
+
+    fn mutate(&self, agent: &Permutation, rng: &mut dyn RngCore) -> Permutation {
+        InsertMutation.mutate(agent, rng)
+    }
+
+    fn crossover(&self, a: &Permutation, b: &Permutation, rng: &mut dyn RngCore) -> Permutation {
+        OrderCrossover.cross(a, b, rng).swap_remove(0)
+    }
+
+    fn objective(&self) -> Objective {
+        Objective::Minimize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Genetic;
+
+    fn job_shop() -> JobShop {
+        JobShop::parse("# 3 jobs, 3 machines\n3 3\n0 3 1 2 2 2\n0 2 2 1 1 4\n1 4 2 3\n").unwrap()
+    }
+
+    #[test]
+    fn job_shop_decoding() {
+        let problem = job_shop();
+        assert_eq!(problem.machines(), 3);
+        assert_eq!(problem.jobs()[2], vec![(1, 4.0), (2, 3.0)]);
+        assert_eq!(problem.lower_bound(), 10.0);
+
+        let mut rng = StdRng::seed_from_u64(1);
+        for _ in 0..50 {
+            let sequence = problem.random_agent(&mut rng);
+            let schedule = problem.decode(&sequence);
+            assert_eq!(schedule.operations.len(), 8);
+            // the machines process one operation at a time
+            for machine in 0..3 {
+                let operations = schedule.machine(machine);
+                assert!(operations.windows(2).all(|w| w[0].end <= w[1].start));
+            }
+            // the operations of a job follow each other
+            for job in 0..3 {
+                let mut operations = schedule.operations.iter().filter(|o| o.job == job).collect::<Vec<_>>();
+                operations.sort_by_key(|o| o.operation);
+                assert!(operations.windows(2).all(|w| w[0].end <= w[1].start));
+            }
+            assert!(schedule.makespan >= problem.lower_bound());
+        }
+
+        // the first operation of job 2 fills the idle time of machine 1 before job 0
+        let schedule = problem.decode(&[1, 1, 0, 0, 2, 0, 1, 2]);
+        assert_eq!(schedule.operations[3], ScheduledOperation { job: 0, operation: 1, machine: 1, start: 5.0, end: 7.0 });
+        assert_eq!(schedule.operations[4], ScheduledOperation { job: 2, operation: 0, machine: 1, start: 0.0, end: 4.0 });
+        // the missing operations are placed at the end
+        assert_eq!(problem.decode(&[2, 9]).operations.len(), 8);
+
+        assert!(JobShop::parse("2 2\n0 1 1 1").is_err());
+        assert!(JobShop::parse("1 2\n0 1 2 1").is_err());
+        assert!(JobShop::parse("2.7 3\n0 1\n0 1\n0 1").is_err());
+        assert!(JobShop::parse("1 2\n0.5 1 1 1").is_err());
+        assert!(JobShop::parse("1 0\n").is_err());
+        assert!(JobShop::new(vec![vec![(0, -1.0)]]).is_err());
+        assert!(JobShop::new(vec![]).is_err());
+    }
+
+    #[test]
+    fn taillard() {
+        let text = "Nb of jobs, Nb of Machines, Time seed, Machine seed, Upper bound, Lower bound :\n\
+                    3 3 840612802 398197754 11 10\n\
+                    Times\n3 2 2\n2 1 4\n4 3 0\n\
+                    Machines\n1 2 3\n1 3 2\n2 3 1\n";
+        let problem = JobShop::parse_taillard(text).unwrap();
+        assert_eq!(problem.machines(), 3);
+        assert_eq!(problem.jobs()[0], job_shop().jobs()[0]);
+        assert_eq!(problem.jobs()[2], vec![(1, 4.0), (2, 3.0), (0, 0.0)]);
+
+        // the machines are numbered from 1, and the matrices must be complete
+        assert!(JobShop::parse_taillard("1 2\n1 1\n0 1").is_err());
+        assert!(JobShop::parse_taillard("1 2\n1 1\n1.5 2").is_err());
+        assert!(JobShop::parse_taillard("2 2\n1 1\n1 1\n1 2").is_err());
+        assert!(JobShop::parse_taillard("1 2\n1 1 1\n1 2").is_err());
+    }
+
+    #[test]
+    fn job_shop_optimum() {
+        let problem = job_shop();
+        let mut rng = StdRng::seed_from_u64(1);
+        let (a, b) = (problem.random_agent(&mut rng), problem.random_agent(&mut rng));
+        let mut child = problem.crossover(&a, &b, &mut rng);
+        child.sort();
+        assert_eq!(child, vec![0, 0, 0, 1, 1, 1, 2, 2]);
+
+        let result = Genetic::from_problem(30, 50, 0.8, 0.3, problem).run_with_seed(1);
+        assert_eq!(result.best_fitness, 11.0);
+    }
+
+    #[test]
+    fn flow_shop() {
+        // the optimum of two machines is given by Johnson's rule: C, A, D, E, B
+        let problem = FlowShop::new(vec![vec![3.0, 6.0], vec![5.0, 2.0], vec![1.0, 2.0], vec![6.0, 6.0], vec![7.0, 5.0]]).unwrap();
+        assert_eq!(problem.makespan(&[2, 0, 3, 4, 1]), 24.0);
+        assert_eq!(problem.makespan(&[0, 1, 2, 3, 4]), 27.0);
+        let schedule = problem.decode(&[2, 0, 3, 4, 1]);
+        assert_eq!(schedule.machine(1)[1], ScheduledOperation { job: 0, operation: 1, machine: 1, start: 4.0, end: 10.0 });
+
+        let result = Genetic::from_problem(20, 50, 0.8, 0.3, problem).run_with_seed(1);
+        assert_eq!(result.best_fitness, 24.0);
+
+        assert!(FlowShop::new(vec![vec![1.0, 2.0], vec![1.0]]).is_err());
+        assert!(FlowShop::new(vec![vec![f64::NAN]]).is_err());
+    }
+}
@@ -0,0 +1,363 @@
+use rand::prelude::*;
+
+use crate::{ConfigError, Fitness, Objective};
+
+/// The way the agents of the next generation are chosen from the current agents and their offspring
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Selection {
+    /// The best agents are kept (every agent can be chosen at most once)
+    #[default]
+    Truncation,
+    /// Fitness proportional selection: every agent is chosen with a probability proportional to
+    /// how much better it is than the worst agent (agents can be chosen more than once)
+    Roulette,
+    /// `size` random agents compete, the best one wins with `probability`, if it doesn't,
+    /// the second one wins with `probability`, and so on (agents can be chosen more than once)
+    /// With probability 1.0 the best one always wins, smaller sizes and probabilities keep more diversity
+    Tournament {
+        /// Number of agents competing in a tournament (at least 1)
+        size: usize,
+        /// Probability of the best competitor winning (0.0 < probability <= 1.0)
+        probability: f64,
+    },
+    /// The probability of choosing an agent grows linearly with its rank (not its fitness),
+    /// so badly scaled fitness functions don't dominate the selection (agents can be chosen more than once)
+    LinearRank {
+        /// The expected number of times the best agent is chosen out of n (1.0 <= selection_pressure <= 2.0),
+        /// 1.0 is random selection, with 2.0 the worst agent is never chosen
+        selection_pressure: f64,
+    },
+    /// The probability of choosing an agent decreases exponentially with its rank (not its fitness)
+    /// (agents can be chosen more than once)
+    ExponentialRank {
+        /// The best agent is e^selection_pressure times more likely to be chosen than the worst one
+        /// (0.0 <= selection_pressure, 0.0 is random selection)
+        selection_pressure: f64,
+    },
+    /// Every choice goes through the test cases in random order, keeping only the agents that are the best
+    /// on the current case, until one agent remains (or the cases run out and a random one is chosen)
+    /// Needs the per-case fitness function (f_case_fitness) (agents can be chosen more than once)
+    Lexicase,
+    /// Same as Lexicase, but agents within epsilon of the best one on a case are kept too (for real valued cases)
+    EpsilonLexicase {
+        /// The tolerance used on every case, if it's None the median absolute deviation
+        /// of the population on each case is used
+        epsilon: Option<f64>,
+    },
+}
+
+impl Selection {
+    /// Tournament selection where the best competitor always wins
+    pub fn tournament(size: usize) -> Self {
+        Selection::Tournament { size, probability: 1.0 }
+    }
+
+    /// Checks whether the parameters of the strategy are usable
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if let Selection::Tournament { size, probability } = self {
+            if *size == 0 {
+                return Err(ConfigError::InvalidSelection("tournament size must be at least 1".to_string()));
+            }
+            if !(*probability > 0.0 && *probability <= 1.0) {
+                return Err(ConfigError::InvalidSelection(format!("tournament probability must be in (0, 1], got {}", probability)));
+            }
+        }
+        if let Selection::LinearRank { selection_pressure } = self {
+            if !(1.0..=2.0).contains(selection_pressure) {
+                return Err(ConfigError::InvalidSelection(format!("linear rank selection pressure must be in [1, 2], got {}", selection_pressure)));
+            }
+        }
+        if let Selection::ExponentialRank { selection_pressure } = self {
+            if !(*selection_pressure >= 0.0 && selection_pressure.is_finite()) {
+                return Err(ConfigError::InvalidSelection(format!("exponential rank selection pressure must be at least 0, got {}", selection_pressure)));
+            }
+        }
+        if let Selection::EpsilonLexicase { epsilon: Some(epsilon) } = self {
+            if epsilon.is_nan() || *epsilon < 0.0 {
+                return Err(ConfigError::InvalidSelection(format!("lexicase epsilon must be at least 0, got {}", epsilon)));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns true if the strategy needs the per-case fitness of the agents
+    pub fn uses_cases(&self) -> bool {
+        matches!(self, Selection::Lexicase | Selection::EpsilonLexicase { .. })
+    }
+
+    /// Returns the indices of the chosen agents
+    ///
+    /// # Arguments:
+    ///
+    /// * `fitness` the fitness of every agent
+    /// * `cases` the per-case fitness of every agent (only needed if uses_cases() is true)
+    /// * `count` the number of agents to choose
+    /// * `objective` whether bigger or smaller fitness (and per-case fitness) is better
+    /// * `rng` random number generator
+    pub fn select<F: Fitness>(&self, fitness: &[F], cases: Option<&[Vec<f64>]>, count: usize, objective: Objective, rng: &mut dyn RngCore) -> Vec<usize> {
+        match self {
+            Selection::Truncation => {
+                let mut indices = (0..fitness.len()).collect::<Vec<usize>>();
+                indices.sort_by(|a, b| objective.compare(&fitness[*a], &fitness[*b]));
+                indices.truncate(count);
+                indices
+            }
+            Selection::Roulette => sample_weighted(&roulette_weights(fitness, objective), count, rng),
+            Selection::Tournament { size, probability } => {
+                (0..count)
+                    .map(|_| {
+                        let mut competitors = (0..*size)
+                            .map(|_| rng.gen_range(0, fitness.len()))
+                            .collect::<Vec<usize>>();
+                        competitors.sort_by(|a, b| objective.compare(&fitness[*a], &fitness[*b]));
+                        let last = competitors.len() - 1;
+                        competitors.into_iter()
+                            .enumerate()
+                            .find(|(i, _)| *i == last || rng.gen_range(0.0, 1.0) < *probability)
+                            .map(|(_, c)| c)
+                            .unwrap()
+                    })
+                    .collect()
+            }
+            Selection::LinearRank { selection_pressure } => {
+                let n = fitness.len() as f64;
+                let weights = rank_weights(fitness, objective, |rank| {
+                    // rank 0 is the best, it gets selection_pressure / n, the worst gets (2 - selection_pressure) / n
+                    let from_worst = n - 1.0 - rank as f64;
+                    let slope = if n > 1.0 { 2.0 * (selection_pressure - 1.0) / (n - 1.0) } else { 0.0 };
+                    (2.0 - selection_pressure + slope * from_worst) / n
+                });
+                sample_weighted(&weights, count, rng)
+            }
+            Selection::ExponentialRank { selection_pressure } => {
+                let last = (fitness.len().max(2) - 1) as f64;
+                let weights = rank_weights(fitness, objective, |rank| (-selection_pressure * rank as f64 / last).exp());
+                sample_weighted(&weights, count, rng)
+            }
+            Selection::Lexicase => {
+                let cases = cases.expect("lexicase selection needs the per-case fitness");
+                let epsilons = vec![0.0; case_count(cases)];
+                lexicase(cases, &epsilons, count, objective, rng)
+            }
+            Selection::EpsilonLexicase { epsilon } => {
+                let cases = cases.expect("lexicase selection needs the per-case fitness");
+                let epsilons = match epsilon {
+                    Some(epsilon) => vec![*epsilon; case_count(cases)],
+                    None => median_absolute_deviations(cases),
+                };
+                lexicase(cases, &epsilons, count, objective, rng)
+            }
+        }
+    }
+}
+
+fn case_count(cases: &[Vec<f64>]) -> usize {
+    cases.iter()
+        .map(|c| c.len())
+        .min()
+        .unwrap_or(0)
+}
+
+fn median(values: &mut [f64]) -> f64 {
+    values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    values[values.len() / 2]
+}
+
+/// The median of |x - median(x)| for every case
+fn median_absolute_deviations(cases: &[Vec<f64>]) -> Vec<f64> {
+    (0..case_count(cases))
+        .map(|c| {
+            let mut values = cases.iter().map(|x| x[c]).collect::<Vec<f64>>();
+            let m = median(&mut values);
+            let mut deviations = values.iter().map(|v| (v - m).abs()).collect::<Vec<f64>>();
+            median(&mut deviations)
+        })
+        .collect()
+}
+
+fn lexicase(cases: &[Vec<f64>], epsilons: &[f64], count: usize, objective: Objective, rng: &mut dyn RngCore) -> Vec<usize> {
+    let mut order = (0..epsilons.len()).collect::<Vec<usize>>();
+
+    (0..count)
+        .map(|_| {
+            let mut candidates = (0..cases.len()).collect::<Vec<usize>>();
+            order.shuffle(rng);
+            for c in order.iter() {
+                if candidates.len() == 1 {
+                    break;
+                }
+                let best = candidates.iter()
+                    .map(|i| cases[*i][*c])
+                    .fold(objective.worst::<f64>(), |best, v| if objective.is_better(&v, &best) { v } else { best });
+                candidates.retain(|i| (cases[*i][*c] - best).abs() <= epsilons[*c]);
+                if candidates.is_empty() {
+                    // every value was NaN on this case
+                    candidates = (0..cases.len()).collect();
+                }
+            }
+            candidates[rng.gen_range(0, candidates.len())]
+        })
+        .collect()
+}
+
+/// Chooses `count` indices, each with a probability proportional to its weight
+/// (if every weight is 0 the indices are chosen uniformly)
+fn sample_weighted(weights: &[f64], count: usize, rng: &mut dyn RngCore) -> Vec<usize> {
+    let total: f64 = weights.iter().sum();
+
+    (0..count)
+        .map(|_| {
+            if total <= 0.0 {
+                return rng.gen_range(0, weights.len());
+            }
+            let mut r = rng.gen_range(0.0, total);
+            for (i, w) in weights.iter().enumerate() {
+                if r < *w {
+                    return i;
+                }
+                r -= w;
+            }
+            weights.len() - 1
+        })
+        .collect()
+}
+
+/// Gives every agent the weight belonging to its rank (0 is the best agent)
+fn rank_weights<F: Fitness>(fitness: &[F], objective: Objective, weight: impl Fn(usize) -> f64) -> Vec<f64> {
+    let mut indices = (0..fitness.len()).collect::<Vec<usize>>();
+    indices.sort_by(|a, b| objective.compare(&fitness[*a], &fitness[*b]));
+
+    let mut weights = vec![0.0; fitness.len()];
+    for (rank, i) in indices.into_iter().enumerate() {
+        weights[i] = weight(rank);
+    }
+    weights
+}
+
+/// How much better every agent is than the worst one
+/// (agents with infinite or NaN fitness get 0)
+fn roulette_weights<F: Fitness>(fitness: &[F], objective: Objective) -> Vec<f64> {
+    let values = fitness.iter()
+        .map(|f| f.to_f64())
+        .collect::<Vec<f64>>();
+    let finite = values.iter().cloned().filter(|v| v.is_finite());
+    let worst = match objective {
+        Objective::Maximize => finite.fold(f64::INFINITY, f64::min),
+        Objective::Minimize => finite.fold(f64::NEG_INFINITY, f64::max),
+    };
+
+    values.iter()
+        .map(|v| {
+            if !v.is_finite() {
+                return 0.0;
+            }
+            match objective {
+                Objective::Maximize => v - worst,
+                Objective::Minimize => worst - v,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn truncation() {
+        let fitness = vec![1.0, 5.0, 3.0, 4.0];
+        let chosen = Selection::Truncation.select(&fitness, None, 2, Objective::Maximize, &mut thread_rng());
+        assert_eq!(chosen, vec![1, 3]);
+        let chosen = Selection::Truncation.select(&fitness, None, 2, Objective::Minimize, &mut thread_rng());
+        assert_eq!(chosen, vec![0, 2]);
+    }
+
+    #[test]
+    fn roulette() {
+        // the worst agent never gets chosen, the best one is chosen twice as often as the middle one
+        let fitness = vec![1.0, 3.0, 2.0, f64::NEG_INFINITY];
+        let chosen = Selection::Roulette.select(&fitness, None, 3000, Objective::Maximize, &mut StdRng::seed_from_u64(1));
+        let counts = (0..4)
+            .map(|i| chosen.iter().filter(|c| **c == i).count())
+            .collect::<Vec<usize>>();
+        assert_eq!(counts[0], 0);
+        assert_eq!(counts[3], 0);
+        assert!(counts[1] > 1800 && counts[2] > 900);
+    }
+
+    #[test]
+    fn tournament() {
+        let fitness = vec![1, 2, 3, 4];
+        // the worst agent only wins if it plays against itself, the best one wins whenever it plays
+        let chosen = Selection::tournament(4).select(&fitness, None, 1000, Objective::Maximize, &mut StdRng::seed_from_u64(1));
+        assert!(chosen.iter().filter(|c| **c == 0).count() < 20);
+        assert!(chosen.iter().filter(|c| **c == 3).count() > 600);
+        // size 1 is random selection
+        let chosen = Selection::tournament(1).select(&fitness, None, 1000, Objective::Minimize, &mut StdRng::seed_from_u64(1));
+        assert!(chosen.contains(&3));
+        // with a small probability the worse competitors win too
+        let weak = Selection::Tournament { size: 4, probability: 0.1 };
+        let chosen = weak.select(&fitness, None, 1000, Objective::Maximize, &mut StdRng::seed_from_u64(1));
+        assert!(chosen.contains(&0));
+    }
+
+    #[test]
+    fn rank() {
+        // the ranks matter, not the scale of the fitness
+        let fitness = vec![1e9, 1.0, 2.0, 3.0];
+        let linear = Selection::LinearRank { selection_pressure: 2.0 };
+        let chosen = linear.select(&fitness, None, 3000, Objective::Maximize, &mut StdRng::seed_from_u64(1));
+        let counts = (0..4)
+            .map(|i| chosen.iter().filter(|c| **c == i).count())
+            .collect::<Vec<usize>>();
+        // expected: 1500, 0, 500, 1000
+        assert_eq!(counts[1], 0);
+        assert!(counts[0] > 1300 && counts[0] < 1700);
+        assert!(counts[3] > 800 && counts[3] < 1200);
+
+        let exponential = Selection::ExponentialRank { selection_pressure: 3.0 };
+        let chosen = exponential.select(&fitness, None, 3000, Objective::Minimize, &mut StdRng::seed_from_u64(1));
+        let best = chosen.iter().filter(|c| **c == 1).count();
+        let worst = chosen.iter().filter(|c| **c == 0).count();
+        // e^3 is about 20
+        assert!(best > 10 * worst && worst > 0);
+    }
+
+    #[test]
+    fn lexicase() {
+        // the generalist is never the best on any case
+        let cases = vec![vec![1.0, 0.0], vec![0.0, 1.0], vec![0.6, 0.6]];
+        let fitness = cases.iter().map(|c| c.iter().sum()).collect::<Vec<f64>>();
+        let chosen = Selection::Lexicase.select(&fitness, Some(&cases), 100, Objective::Maximize, &mut StdRng::seed_from_u64(1));
+        assert!(!chosen.contains(&2));
+        assert!(chosen.contains(&0) && chosen.contains(&1));
+
+        // but it's close enough with a big epsilon
+        let epsilon = Selection::EpsilonLexicase { epsilon: Some(0.5) };
+        let chosen = epsilon.select(&fitness, Some(&cases), 100, Objective::Maximize, &mut StdRng::seed_from_u64(1));
+        assert!(chosen.contains(&2));
+    }
+
+    #[test]
+    fn epsilon_from_deviation() {
+        let cases = vec![vec![1.0], vec![2.0], vec![4.0], vec![8.0], vec![16.0]];
+        assert_eq!(median_absolute_deviations(&cases), vec![3.0]);
+    }
+
+    #[test]
+    fn invalid_rank() {
+        assert!(Selection::LinearRank { selection_pressure: 2.5 }.validate().is_err());
+        assert!(Selection::ExponentialRank { selection_pressure: -1.0 }.validate().is_err());
+        assert!(Selection::LinearRank { selection_pressure: 1.5 }.validate().is_ok());
+    }
+
+    #[test]
+    fn invalid_tournament() {
+        assert!(Selection::tournament(0).validate().is_err());
+        assert!(Selection::Tournament { size: 2, probability: 0.0 }.validate().is_err());
+        assert!(Selection::Tournament { size: 2, probability: 0.7 }.validate().is_ok());
+    }
+}
@@ -0,0 +1,124 @@
+use crate::{ConfigError, Fitness, Objective};
+
+/// Fitness sharing (niching): the fitness of an agent is divided by the number of agents around it (closer than
+/// `radius` by f_distance), so the agents of a crowded optimum compete with each other, and several optima (niches)
+/// of a multimodal function can be kept in the population instead of all agents converging on one of them
+///
+/// The niche count of an agent is the sum of 1 - (d / radius)^alpha for the agents closer than radius (the agent
+/// itself included, so it's at least 1). The shared fitness is fitness / niche count if the fitness is maximised and
+/// fitness * niche count if it's minimised, so the fitness should not be negative. It's used by the selection
+/// strategies (the result still holds the raw fitness), it needs f_distance and n * n distances per selection.
+///
+/// # Examples:
+/// ```
+/// use rand::prelude::*;
+/// use bbte_optim_tzim1773_genetic::{Genetic, Selection, Sharing};
+///
+/// fn main() {
+///     // two peaks of the same height at -2 and 2
+///     let test = Genetic::builder()
+///         .max_generation(100)
+///         .random_agent(|rng| rng.gen_range(-3.0, 3.0))
+///         .fitness(|a: &f64| (4.0 - (a * a - 4.0).abs()).max(0.0))
+///         .mutate(|a: &f64, rng| *a + rng.gen_range(-0.05, 0.05))
+///         .offspring(|a: &f64, _b: &f64, _rng| *a)
+///         .selection(Selection::tournament(2))
+///         .distance(|a: &f64, b: &f64| (a - b).abs())
+///         .sharing(Sharing::new(1.0))
+///         .build()
+///         .unwrap();
+///
+///     let result = test.run();
+///     println!("{:?}", result.population); // agents around both peaks
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sharing {
+    /// The agents closer than this share their fitness (the radius of a niche, bigger than 0.0)
+    pub radius: f64,
+    /// The shape of the sharing: 1.0 is linear, bigger values share less between close agents
+    /// Suggested value: 1.0
+    pub alpha: f64,
+}
+
+impl Sharing {
+    /// Linear sharing between the agents closer than radius
+    pub fn new(radius: f64) -> Self {
+        Sharing { radius, alpha: 1.0 }
+    }
+
+    /// Checks whether the parameters are usable
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if !(self.radius > 0.0 && self.radius.is_finite()) {
+            return Err(ConfigError::InvalidSharing(format!("radius must be a positive number, got {}", self.radius)));
+        }
+        if !(self.alpha > 0.0 && self.alpha.is_finite()) {
+            return Err(ConfigError::InvalidSharing(format!("alpha must be a positive number, got {}", self.alpha)));
+        }
+
+        Ok(())
+    }
+
+    /// Returns the shared fitness of the agents from their (penalized) fitness and their distances
+    pub(crate) fn share<F: Fitness>(&self, fitness: &[F], distance: impl Fn(usize, usize) -> f64, objective: Objective) -> Vec<f64> {
+        (0..fitness.len())
+            .map(|i| {
+                let niche_count = 1.0 + (0..fitness.len())
+                    .filter(|j| *j != i)
+                    .map(|j| distance(i, j))
+                    .filter(|d| *d < self.radius)
+                    .map(|d| 1.0 - (d / self.radius).powf(self.alpha))
+                    .sum::<f64>();
+                match objective {
+                    Objective::Maximize => fitness[i].to_f64() / niche_count,
+                    Objective::Minimize => fitness[i].to_f64() * niche_count,
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Genetic, Selection};
+    use rand::Rng;
+
+    fn peaks(sharing: Option<Sharing>) -> Vec<f64> {
+        // two peaks of the same height at -2 and 2
+        let builder = Genetic::builder()
+            .population(40)
+            .max_generation(100)
+            .random_agent(|rng| rng.gen_range(-3.0, 3.0))
+            .fitness(|a: &f64| (4.0 - (a * a - 4.0).abs()).max(0.0))
+            .mutate(|a: &f64, rng| *a + rng.gen_range(-0.05, 0.05))
+            .offspring(|a: &f64, _b: &f64, _rng| *a)
+            .selection(Selection::tournament(2))
+            .distance(|a: &f64, b: &f64| (a - b).abs());
+        match sharing {
+            Some(sharing) => builder.sharing(sharing),
+            None => builder,
+        }.build().unwrap().run_with_seed(1).population
+    }
+
+    #[test]
+    fn share() {
+        let sharing = Sharing::new(2.0);
+        let positions = [0.0, 1.0, 5.0];
+        let distance = |i: usize, j: usize| f64::abs(positions[i] - positions[j]);
+
+        // the first two agents are in the same niche: 1 + (1 - 1 / 2)
+        assert_eq!(sharing.share(&[3.0, 3.0, 2.0], distance, Objective::Maximize), vec![2.0, 2.0, 2.0]);
+        assert_eq!(sharing.share(&[3, 3, 2], distance, Objective::Minimize), vec![4.5, 4.5, 2.0]);
+        assert!(Sharing::new(0.0).validate().is_err());
+        assert!(Sharing { radius: 1.0, alpha: f64::NAN }.validate().is_err());
+    }
+
+    #[test]
+    fn niches() {
+        let left = |population: &[f64]| population.iter().filter(|a| **a < 0.0).count();
+        // without sharing the whole population converges on one of the peaks
+        assert!([0, 40].contains(&left(&peaks(None))));
+        assert!((10..=30).contains(&left(&peaks(Some(Sharing::new(1.0))))));
+    }
+}
@@ -0,0 +1,104 @@
+use std::cmp::Ordering;
+
+use crate::multi_objective::{dominates, Key};
+
+/// Returns the SPEA2 fitness of the agents (smaller is better): the raw fitness (the sum of the strengths of the
+/// agents dominating it, the strength is the number of agents an agent dominates) plus the density (1 / (the
+/// distance to the k-th nearest agent + 2), k is the square root of the number of agents)
+fn fitness(values: &[Vec<f64>]) -> Vec<f64> {
+    let strength = values.iter().map(|a| values.iter().filter(|b| dominates(a, b)).count()).collect::<Vec<usize>>();
+    let k = (values.len() as f64).sqrt() as usize;
+    values.iter()
+        .map(|a| {
+            let raw = values.iter().zip(&strength).filter(|(b, _)| dominates(b, a)).map(|(_, s)| *s).sum::<usize>();
+            // the first distance is the distance to itself
+            let distances = sorted_distances(a, values.iter());
+            let density = 1.0 / (distances.get(k).cloned().unwrap_or(0.0) + 2.0);
+            raw as f64 + density
+        })
+        .collect()
+}
+
+/// The Euclidean distance of two points in the objective space (the infinite differences are ignored)
+fn distance(a: &[f64], b: &[f64]) -> f64 {
+    a.iter()
+        .zip(b)
+        .map(|(x, y)| x - y)
+        .filter(|d| d.is_finite())
+        .map(|d| d * d)
+        .sum::<f64>()
+        .sqrt()
+}
+
+/// Returns the distances of a point to the others in increasing order
+fn sorted_distances<'b>(a: &[f64], others: impl Iterator<Item = &'b Vec<f64>>) -> Vec<f64> {
+    let mut distances = others.map(|b| distance(a, b)).collect::<Vec<f64>>();
+    distances.sort_by(f64::total_cmp);
+    distances
+}
+
+/// Removes the agents from the chosen ones until there are `size` of them: always the one closest to the others
+/// (the distance to its nearest neighbour decides, on a tie the distance to the second one, and so on)
+fn truncate(values: &[Vec<f64>], chosen: &mut Vec<usize>, size: usize) {
+    while chosen.len() > size {
+        let distances = chosen.iter()
+            .map(|i| sorted_distances(&values[*i], chosen.iter().filter(|j| *j != i).map(|j| &values[*j])))
+            .collect::<Vec<Vec<f64>>>();
+        let closest = (0..chosen.len())
+            .min_by(|a, b| distances[*a].iter()
+                .zip(&distances[*b])
+                .map(|(x, y)| x.total_cmp(y))
+                .find(|ordering| *ordering != Ordering::Equal)
+                .unwrap_or(Ordering::Equal))
+            .expect("there are more agents than the size");
+        chosen.remove(closest);
+    }
+}
+
+/// The environmental selection of SPEA2: the new archive holds the non-dominated agents (the ones with a fitness
+/// less than 1), if they are too many they are truncated, if they are too few the best dominated agents are added.
+/// Returns the indices of the kept agents with their keys (their fitness)
+pub(crate) fn select(values: &[Vec<f64>], size: usize) -> (Vec<usize>, Vec<Key>) {
+    let fitness = fitness(values);
+    let mut chosen = (0..values.len()).filter(|i| fitness[*i] < 1.0).collect::<Vec<usize>>();
+    if chosen.len() > size {
+        truncate(values, &mut chosen, size);
+    } else {
+        let mut dominated = (0..values.len()).filter(|i| fitness[*i] >= 1.0).collect::<Vec<usize>>();
+        dominated.sort_by(|a, b| fitness[*a].total_cmp(&fitness[*b]));
+        let missing = size - chosen.len();
+        chosen.extend(dominated.into_iter().take(missing));
+    }
+    let keys = chosen.iter().map(|i| (fitness[*i], 0.0)).collect();
+
+    (chosen, keys)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strength() {
+        let values = vec![vec![1.0, 5.0], vec![2.0, 4.0], vec![1.0, 4.0], vec![0.0, 0.0]];
+        // strengths: 2, 2, 1, 0 - the raw fitness of [1, 4] is 2 + 2, of [0, 0] is 2 + 2 + 1
+        let raw = fitness(&values).iter().map(|f| f.floor()).collect::<Vec<f64>>();
+        assert_eq!(raw, vec![0.0, 0.0, 4.0, 5.0]);
+        assert!(fitness(&values).iter().all(|f| f - f.floor() <= 0.5));
+
+        let (chosen, keys) = select(&values, 3);
+        assert_eq!(chosen, vec![0, 1, 2]);
+        assert_eq!(keys[2].0.floor(), 4.0);
+    }
+
+    #[test]
+    fn truncation() {
+        // the agents in the middle of the cluster go first, the ends of the front stay
+        let values = vec![vec![0.0, 4.0], vec![1.0, 3.0], vec![1.1, 2.9], vec![1.2, 2.8], vec![4.0, 0.0]];
+        let mut chosen = (0..5).collect::<Vec<usize>>();
+        truncate(&values, &mut chosen, 3);
+        assert_eq!(chosen.len(), 3);
+        assert!(chosen.contains(&0) && chosen.contains(&4));
+        assert_eq!(select(&values, 2).0, vec![0, 4]);
+    }
+}
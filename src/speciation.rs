@@ -0,0 +1,250 @@
+use rand::prelude::*;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::adaptation::Rates;
+use crate::{ConfigError, Evaluated, Fitness, FitnessError, Genetic, MaybeSync, Objective, Offspring};
+
+/// Speciation as in NEAT: the population is partitioned into species by f_distance (an agent joins the first
+/// species whose representative is closer than `threshold`, otherwise it founds a new one), and the agents only
+/// compete and mate inside their species, so new structures have time to be optimised before they have to compete
+/// with the whole population
+///
+/// Every species gets a part of the next generation proportional to the mean fitness of its members (the fitness
+/// shared inside the species), the worst species gets a small part too. The best `elitism` agents of a species are
+/// copied unchanged, the others are the offspring of two random parents from the best `survival` fraction of the
+/// species. A species whose best fitness didn't improve in `stagnation` generations is removed (except the one
+/// holding the best agent). The selection strategies are not used in this mode.
+///
+/// # Examples:
+/// ```
+/// use rand::prelude::*;
+/// use bbte_optim_tzim1773_genetic::{Genetic, Speciation};
+///
+/// fn main() {
+///     // two peaks of the same height at -2 and 2
+///     let test = Genetic::builder()
+///         .max_generation(100)
+///         .random_agent(|rng| rng.gen_range(-3.0, 3.0))
+///         .fitness(|a: &f64| (4.0 - (a * a - 4.0).abs()).max(0.0))
+///         .mutate(|a: &f64, rng| *a + rng.gen_range(-0.05, 0.05))
+///         .offspring(|a: &f64, b: &f64, _rng| (*a + *b) / 2.0)
+///         .distance(|a: &f64, b: &f64| (a - b).abs())
+///         .speciation(Speciation::new(1.0))
+///         .build()
+///         .unwrap();
+///
+///     let mut state = test.start().unwrap();
+///     while test.step(&mut state).unwrap().is_none() {}
+///     println!("{} species", state.species().len());
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Speciation {
+    /// The agents closer than this to the representative of a species belong to it (bigger than 0.0)
+    pub threshold: f64,
+    /// A species is removed if its best fitness didn't improve in this many generations (at least 1)
+    /// Suggested value: 15
+    pub stagnation: usize,
+    /// The fraction of the best agents of a species that can be parents (more than 0.0, at most 1.0)
+    /// Suggested value: 0.2
+    pub survival: f64,
+    /// The number of the best agents of every species copied unchanged into the next generation
+    /// Suggested value: 1
+    pub elitism: usize,
+}
+
+/// A species of the population
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Species<T, F = f64> {
+    /// The agent the other agents are compared to (a random member of the previous generation)
+    pub representative: T,
+    /// The indices of the members in the population (the agents the species created for the current generation)
+    pub members: Vec<usize>,
+    /// The best fitness the species ever had
+    pub best_fitness: F,
+    /// The generation its best fitness improved last
+    pub last_improvement: usize,
+}
+
+impl Speciation {
+    /// Speciation with the given compatibility threshold and the suggested values
+    pub fn new(threshold: f64) -> Self {
+        Speciation { threshold, stagnation: 15, survival: 0.2, elitism: 1 }
+    }
+
+    /// Checks whether the parameters are usable
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if !(self.threshold > 0.0 && self.threshold.is_finite()) {
+            return Err(ConfigError::InvalidSpeciation(format!("threshold must be a positive number, got {}", self.threshold)));
+        }
+        if self.stagnation == 0 {
+            return Err(ConfigError::InvalidSpeciation("stagnation must be at least 1".to_string()));
+        }
+        if !(self.survival > 0.0 && self.survival <= 1.0) {
+            return Err(ConfigError::InvalidSpeciation(format!("survival must be more than 0.0 and at most 1.0, got {}", self.survival)));
+        }
+
+        Ok(())
+    }
+
+    /// Puts the agents into the species (founding new ones), chooses the new representatives and removes the
+    /// empty and the stagnant species
+    pub(crate) fn speciate<T: Clone, F: Fitness>(&self, species: &mut Vec<Species<T, F>>, xg: &[Evaluated<T, F>], distance: impl Fn(&T, &T) -> f64, generation: usize, objective: Objective, rng: &mut dyn RngCore) {
+        for s in species.iter_mut() {
+            s.members.clear();
+        }
+        for (i, x) in xg.iter().enumerate() {
+            match species.iter_mut().find(|s| distance(&s.representative, &x.agent) < self.threshold) {
+                Some(s) => s.members.push(i),
+                None => species.push(Species { representative: x.agent.clone(), members: vec![i], best_fitness: x.fitness.clone(), last_improvement: generation }),
+            }
+        }
+        species.retain(|s| !s.members.is_empty());
+
+        for s in species.iter_mut() {
+            let best = s.members.iter().map(|i| &xg[*i].fitness).fold(&s.best_fitness, |a, b| if objective.is_better(b, a) { b } else { a });
+            if objective.is_better(best, &s.best_fitness) {
+                s.best_fitness = best.clone();
+                s.last_improvement = generation;
+            }
+            s.representative = xg[s.members[rng.gen_range(0, s.members.len())]].agent.clone();
+        }
+
+        let best_species = (0..species.len())
+            .fold(0, |a, b| if objective.is_better(&species[b].best_fitness, &species[a].best_fitness) { b } else { a });
+        let mut index = 0;
+        species.retain(|s| {
+            index += 1;
+            index - 1 == best_species || generation - s.last_improvement < self.stagnation
+        });
+    }
+
+    /// Returns the number of offspring of every species: proportional to the mean fitness of their members
+    /// (shifted so that the worst species gets a small part too), the sum is the population
+    pub(crate) fn allocate<T, F: Fitness>(&self, species: &[Species<T, F>], xg: &[Evaluated<T, F>], population: usize, objective: Objective) -> Vec<usize> {
+        let means = species.iter()
+            .map(|s| s.members.iter().map(|i| xg[*i].fitness.to_f64()).sum::<f64>() / s.members.len() as f64)
+            .map(|mean| match objective {
+                Objective::Maximize => mean,
+                Objective::Minimize => -mean,
+            })
+            .collect::<Vec<f64>>();
+        let finite = means.iter().cloned().filter(|mean| mean.is_finite());
+        let worst = finite.clone().fold(f64::INFINITY, f64::min);
+        let best = finite.fold(f64::NEG_INFINITY, f64::max);
+        let least = (best - worst) / species.len() as f64;
+        let weights = means.iter()
+            .map(|mean| match (best > worst, mean.is_finite()) {
+                (true, true) => mean - worst + least,
+                (true, false) => least,
+                // every species has the same mean fitness
+                (false, _) => 1.0,
+            })
+            .collect::<Vec<f64>>();
+        let total = weights.iter().sum::<f64>();
+
+        // largest remainder method
+        let quotas = weights.iter().map(|weight| weight / total * population as f64).collect::<Vec<f64>>();
+        let mut counts = quotas.iter().map(|quota| quota.floor() as usize).collect::<Vec<usize>>();
+        let mut order = (0..quotas.len()).collect::<Vec<usize>>();
+        order.sort_by(|a, b| (quotas[*b] - quotas[*b].floor()).total_cmp(&(quotas[*a] - quotas[*a].floor())));
+        let missing = population - counts.iter().sum::<usize>();
+        for i in order.into_iter().cycle().take(missing) {
+            counts[i] += 1;
+        }
+
+        counts
+    }
+}
+
+impl<'a, T, F> Genetic<'a, T, F>
+    where T: Clone + MaybeSync, F: Fitness + MaybeSync {
+    /// Creates the next generation in the speciation mode: the species are updated, then every species creates
+    /// its part of the generation from its own members (they become its members, the species without offspring
+    /// are removed)
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn species_offspring(&self, speciation: &Speciation, species: &mut Vec<Species<T, F>>, xg: &[Evaluated<T, F>], generation: usize, rates: &mut Rates, evaluations: &mut usize, rng: &mut dyn RngCore) -> Result<Vec<Offspring<T, F>>, FitnessError> {
+        let f_distance = self.f_distance.as_ref().expect("speciation needs f_distance");
+        speciation.speciate(species, xg, |a, b| f_distance(a, b), generation, self.objective, rng);
+        let counts = speciation.allocate(species, xg, self.population, self.objective);
+
+        let mut offspring = Vec::with_capacity(self.population);
+        for (s, count) in species.iter_mut().zip(counts) {
+            let mut members = std::mem::take(&mut s.members);
+            members.sort_by(|a, b| self.objective.compare(&xg[*a].fitness, &xg[*b].fitness));
+            let end = offspring.len() + count;
+            for i in members.iter().take(speciation.elitism.min(count)) {
+                offspring.push((xg[*i].agent.clone(), Some(xg[*i].fitness.clone())));
+            }
+            let parents = ((members.len() as f64 * speciation.survival).ceil() as usize).max(1);
+            while offspring.len() < end {
+                let a = &xg[members[rng.gen_range(0, parents)]];
+                let b = &xg[members[rng.gen_range(0, parents)]];
+                let children = self.breed(a, b, rates, evaluations, rng)?;
+                let missing = end - offspring.len();
+                offspring.extend(children.into_iter().take(missing));
+            }
+            s.members = (end - count..end).collect();
+        }
+        species.retain(|s| !s.members.is_empty());
+
+        Ok(offspring)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn speciate() {
+        let speciation = Speciation { stagnation: 2, ..Speciation::new(1.0) };
+        let evaluated = |xs: &[f64]| xs.iter().map(|x| Evaluated::new(*x, -x.abs())).collect::<Vec<_>>();
+        let distance = |a: &f64, b: &f64| (a - b).abs();
+        let rng = &mut StdRng::seed_from_u64(1);
+
+        let mut species = Vec::new();
+        let xg = evaluated(&[0.1, 5.0, 0.5, 5.5, 9.0]);
+        speciation.speciate(&mut species, &xg, distance, 0, Objective::Maximize, rng);
+        let members = species.iter().map(|s| s.members.clone()).collect::<Vec<_>>();
+        assert_eq!(members, vec![vec![0, 2], vec![1, 3], vec![4]]);
+        assert_eq!(speciation.allocate(&species, &xg, 10, Objective::Maximize), vec![6, 3, 1]);
+
+        // the species far from 0 don't improve, they are removed after 2 generations
+        for generation in 1..=2 {
+            let xg = evaluated(&[0.05 / generation as f64, 5.2, 9.2]);
+            speciation.speciate(&mut species, &xg, distance, generation, Objective::Maximize, rng);
+        }
+        assert_eq!(species.len(), 1);
+        assert_eq!(species[0].members, vec![0]);
+        assert_eq!(species[0].last_improvement, 2);
+        assert!(Speciation { survival: 0.0, ..Speciation::new(1.0) }.validate().is_err());
+    }
+
+    #[test]
+    fn species_offspring() {
+        let test = Genetic::builder()
+            .population(30)
+            .max_generation(50)
+            .random_agent(|rng| rng.gen_range(-3.0, 3.0))
+            .fitness(|a: &f64| (4.0 - (a * a - 4.0).abs()).max(0.0))
+            .mutate(|a: &f64, rng| *a + rng.gen_range(-0.05, 0.05))
+            .offspring(|a: &f64, b: &f64, _rng| (*a + *b) / 2.0)
+            .distance(|a: &f64, b: &f64| (a - b).abs())
+            .speciation(Speciation { stagnation: 50, ..Speciation::new(1.0) })
+            .build()
+            .unwrap();
+
+        let mut state = test.start_with_rng(StdRng::seed_from_u64(3)).unwrap();
+        while test.step(&mut state).unwrap().is_none() {
+            assert_eq!(state.population().len(), 30);
+        }
+        // both peaks are kept (the first member of a species is its elite)
+        let best = |s: &Species<f64>| state.population()[s.members[0]].agent;
+        assert!(state.species().iter().any(|s| (best(s) + 2.0).abs() < 0.5));
+        assert!(state.species().iter().any(|s| (best(s) - 2.0).abs() < 0.5));
+        assert!(state.species().iter().all(|s| s.members.iter().all(|i| (state.population()[*i].agent - s.representative).abs() < 2.0)));
+    }
+}
@@ -0,0 +1,553 @@
+use rand::prelude::*;
+use std::time::Instant;
+
+use crate::adaptation::Rates;
+#[cfg(feature = "serde")]
+use crate::checkpoint::SavedRng;
+use crate::error::EvaluationBudget;
+use crate::termination::Progress;
+use crate::trace::phase;
+use crate::{CancellationToken, Evaluated, Fitness, FitnessError, GenerationStats, Genetic, MaybeSync, Offspring, RunError, RunResult, Species, Termination};
+
+/// A run that is executed one generation at a time, so it can be paused, inspected and resumed
+///
+/// It's created by Genetic::start() (or start_with_rng()), advanced by Genetic::step() and turned into the
+/// result of the run by Genetic::finish(). It has to be used with the Genetic that started it.
+///
+/// # Examples:
+/// ```
+/// use rand::prelude::*;
+/// use bbte_optim_tzim1773_genetic::Genetic;
+///
+/// fn main() {
+///     let test = Genetic::builder()
+///         .max_generation(50)
+///         .random_agent(|rng| rng.gen_range(-5.0, 5.0))
+///         .fitness(|a: &f64| 5.0 - a * a)
+///         .mutate(|a: &f64, rng| *a + rng.gen_range(-0.01, 0.01))
+///         .offspring(|a: &f64, b: &f64, _rng| (*a + *b) / 2.0)
+///         .build()
+///         .unwrap();
+///
+///     let mut state = test.start_with_rng(StdRng::seed_from_u64(42)).unwrap();
+///     while test.step(&mut state).unwrap().is_none() {
+///         if state.generation() % 10 == 0 {
+///             println!("{}: {}", state.generation(), state.population()[0].fitness);
+///         }
+///     }
+///
+///     let result = test.finish(state);
+///     println!("{}", result.best); // should be a number close to 0
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct GeneticState<T, F = f64, R = StdRng> {
+    pub(crate) population: Vec<Evaluated<T, F>>,
+    pub(crate) generation: usize,
+    pub(crate) evaluations: usize,
+    pub(crate) rates: Rates,
+    pub(crate) progress: Progress,
+    pub(crate) termination: Option<Termination>,
+    pub(crate) history: Vec<GenerationStats>,
+    pub(crate) archive: Vec<Evaluated<T, F>>,
+    pub(crate) species: Vec<Species<T, F>>,
+    pub(crate) novelty_archive: Vec<Vec<f64>>,
+    pub(crate) restarts: usize,
+    pub(crate) last_restart: usize,
+    pub(crate) sentinels: Vec<Evaluated<T, F>>,
+    pub(crate) optima: Vec<T>,
+    pub(crate) changes: usize,
+    pub(crate) surrogate_points: Vec<(Vec<f64>, f64)>,
+    pub(crate) rng: RunRng<R>,
+}
+
+/// The random number generator of a run: the one given to the run, or with checkpoints a ChaCha20 generator (the
+/// algorithm of StdRng) seeded from it once, whose position is saved in the checkpoints
+#[derive(Debug, Clone)]
+pub(crate) struct RunRng<R> {
+    pub(crate) rng: R,
+    #[cfg(feature = "serde")]
+    pub(crate) saved: Option<SavedRng>,
+}
+
+impl<R: RngCore> RunRng<R> {
+    fn active(&mut self) -> &mut dyn RngCore {
+        #[cfg(feature = "serde")]
+        {
+            if let Some(saved) = &mut self.saved {
+                return &mut saved.rng;
+            }
+        }
+        &mut self.rng
+    }
+}
+
+impl<R: RngCore> RngCore for RunRng<R> {
+    fn next_u32(&mut self) -> u32 {
+        self.active().next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.active().next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.active().fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.active().try_fill_bytes(dest)
+    }
+}
+
+impl<T, F, R> GeneticState<T, F, R> {
+    /// The agents of the current generation with their fitness (the best one first, except in the
+    /// first generation, in the steady-state, in the cellular and in the speciation mode)
+    pub fn population(&self) -> &[Evaluated<T, F>] {
+        &self.population
+    }
+
+    /// The number of generations executed
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    /// The number of times the fitness function was called
+    pub fn evaluations(&self) -> usize {
+        self.evaluations
+    }
+
+    /// The reason the run stopped, None if it can go on
+    pub fn termination(&self) -> Option<Termination> {
+        self.termination
+    }
+
+    /// The statistics of the fitness of the generations executed
+    pub fn history(&self) -> &[GenerationStats] {
+        &self.history
+    }
+
+    /// The number of times the population was restarted
+    pub fn restarts(&self) -> usize {
+        self.restarts
+    }
+
+    /// The number of changes of the environment detected (with a dynamic environment)
+    pub fn changes(&self) -> usize {
+        self.changes
+    }
+
+    /// The probability of mutation used by the next generation (raised during a burst of hypermutation, before
+    /// the schedules are applied)
+    pub fn mutation_probability(&self) -> f64 {
+        self.rates.mutation_probability()
+    }
+
+    /// The best agents evaluated during the run (the best one first), empty if there's no archive
+    pub fn archive(&self) -> &[Evaluated<T, F>] {
+        &self.archive
+    }
+
+    /// The species of the population in the speciation mode (their members are the agents they created for the
+    /// current generation), empty otherwise
+    pub fn species(&self) -> &[Species<T, F>] {
+        &self.species
+    }
+
+    /// The behaviours in the archive of the novelty search (the oldest one first)
+    pub fn novelty_archive(&self) -> &[Vec<f64>] {
+        &self.novelty_archive
+    }
+
+    /// The random number generator of the run (with checkpoints the generations draw from a generator seeded from it)
+    pub fn rng(&mut self) -> &mut R {
+        &mut self.rng.rng
+    }
+}
+
+impl<'a, T, F> Genetic<'a, T, F>
+    where T: Clone + MaybeSync, F: Fitness + MaybeSync {
+    /// Creates and evaluates the first generation of a run that is executed with step(), using a random number
+    /// generator seeded from the operating system
+    pub fn start(&self) -> Result<GeneticState<T, F>, RunError> {
+        self.start_with_rng(StdRng::from_entropy())
+    }
+
+    /// Same as start(), but the run uses the given random number generator
+    ///
+    /// # Arguments:
+    ///
+    /// * `rng` the random number generator used throughout the run (it's owned by the state)
+    pub fn start_with_rng<R: RngCore>(&self, mut rng: R) -> Result<GeneticState<T, F, R>, RunError> {
+        let start = Instant::now();
+        let xg = self.with_opposites(self.get_population(&mut rng));
+        let mut evaluations = 0;
+        let (population, exhausted) = self.evaluate_in_budget(xg.into_iter().map(|x| (x, None)).collect(), &mut evaluations)?;
+        let mut state = self.initial_state(self.fitter_of_pairs(population), evaluations, start, rng);
+        // the first generation was cut short by max_evaluations, the run is over
+        if exhausted {
+            state.termination = Some(Termination::EvaluationLimit);
+        }
+
+        Ok(state)
+    }
+
+    /// Creates the state of a run from its evaluated first generation (the time of the run is measured from `start`)
+    pub(crate) fn initial_state<R: RngCore>(&self, population: Vec<Evaluated<T, F>>, evaluations: usize, start: Instant, rng: R) -> GeneticState<T, F, R> {
+        let mut archive = Vec::new();
+        if let Some(best) = &self.archive {
+            best.update(&mut archive, &population, self.objective);
+        }
+
+        let mut surrogate_points = Vec::new();
+        self.learn(&mut surrogate_points, &population);
+
+        #[cfg(feature = "serde")]
+        let rng = {
+            let mut rng = rng;
+            let saved = self.checkpoint.as_ref().map(|_| SavedRng::new(&mut rng));
+            RunRng { rng, saved }
+        };
+        #[cfg(not(feature = "serde"))]
+        let rng = RunRng { rng };
+
+        GeneticState {
+            population,
+            generation: 0,
+            evaluations,
+            rates: self.rates(),
+            progress: Progress::new(start),
+            termination: None,
+            history: Vec::new(),
+            archive,
+            species: Vec::new(),
+            novelty_archive: Vec::new(),
+            restarts: 0,
+            last_restart: 0,
+            sentinels: Vec::new(),
+            optima: Vec::new(),
+            changes: 0,
+            surrogate_points,
+            rng,
+        }
+    }
+
+    /// Executes the next generation of the run, returns the reason of stopping if the run is over
+    /// (calling it again doesn't change the state)
+    ///
+    /// # Arguments:
+    ///
+    /// * `state` the state of the run, created by start() of this Genetic
+    pub fn step<R: RngCore>(&self, state: &mut GeneticState<T, F, R>) -> Result<Option<Termination>, RunError> {
+        if let Some(termination) = self.check_bound(state) {
+            return Ok(Some(termination));
+        }
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("generation", generation = state.generation).entered();
+        let candidates = self.offspring(state).and_then(|offspring| phase!("evaluation", self.evaluate_in_budget(offspring, &mut state.evaluations)));
+        let termination = self.advance(state, candidates)?;
+        #[cfg(feature = "serde")]
+        self.checkpoint(state)?;
+
+        Ok(termination)
+    }
+
+    /// Stops the run if it reached the last generation or it was cancelled, returns the reason of stopping
+    /// if the run is over
+    pub(crate) fn check_bound<R>(&self, state: &mut GeneticState<T, F, R>) -> Option<Termination> {
+        if state.termination.is_none() && self.cancellation.as_ref().is_some_and(CancellationToken::is_cancelled) {
+            state.termination = Some(Termination::Cancelled);
+        }
+        if state.termination.is_none() && state.generation >= self.generation_bound() {
+            state.termination = Some(Termination::MaxGeneration);
+        }
+        state.termination
+    }
+
+    /// Creates the offspring of the next generation (the first part of a step, before they are evaluated)
+    pub(crate) fn offspring<R: RngCore>(&self, state: &mut GeneticState<T, F, R>) -> Result<Vec<Offspring<T, F>>, FitnessError> {
+        let rng: &mut dyn RngCore = &mut state.rng;
+        self.apply_schedules(&mut state.rates, state.generation);
+        self.update_penalties(&mut state.rates, state.generation, &state.population);
+
+        let mut offspring = phase!("variation", match (&self.steady_state, &self.cellular, &self.speciation) {
+            (Some(steady_state), _, _) => self.steady_offspring(steady_state, &state.population, &mut state.rates, &mut state.evaluations, rng),
+            (None, Some(cellular), _) => self.cell_offspring(cellular, &state.population, &mut state.rates, &mut state.evaluations, rng),
+            (None, None, Some(speciation)) => self.species_offspring(speciation, &mut state.species, &state.population, state.generation, &mut state.rates, &mut state.evaluations, rng),
+            (None, None, None) => self.variation(&state.population, &mut state.rates, &mut state.evaluations, rng),
+        })?;
+        if let Some(f_duplicates) = &self.f_duplicates {
+            self.replace_duplicates(f_duplicates, &mut offspring, rng);
+        }
+        self.screen(&mut offspring, &state.surrogate_points);
+        self.add_immigrants(&mut offspring, rng);
+
+        Ok(offspring)
+    }
+
+    /// Finishes a step with the evaluated offspring (and whether max_evaluations was used up before every offspring
+    /// was evaluated): creates the next generation from them and updates the statistics and the termination of the run
+    pub(crate) fn advance<R: RngCore>(&self, state: &mut GeneticState<T, F, R>, candidates: Result<(Vec<Evaluated<T, F>>, bool), FitnessError>) -> Result<Option<Termination>, RunError> {
+        let mut candidates = match candidates {
+            Ok((candidates, false)) => candidates,
+            // the evaluated part of the unfinished generation is kept if it's fitter than the worst agents
+            Ok((candidates, true)) => {
+                if let Some(archive) = &self.archive {
+                    archive.update(&mut state.archive, &candidates, self.objective);
+                }
+                self.replace_fitter(&mut state.population, candidates);
+                state.termination = Some(Termination::EvaluationLimit);
+                return Ok(state.termination);
+            }
+            Err(error) if error.is::<EvaluationBudget>() => {
+                state.termination = Some(Termination::EvaluationLimit);
+                return Ok(state.termination);
+            }
+            Err(error) => return Err(error.into()),
+        };
+        self.improve(&mut candidates, state.generation, &mut state.evaluations)?;
+        if let Some(archive) = &self.archive {
+            archive.update(&mut state.archive, &candidates, self.objective);
+        }
+        self.learn(&mut state.surrogate_points, &candidates);
+        let immigrants = candidates.split_off(candidates.len() - self.immigrant_count());
+        let g = state.generation;
+        let rng: &mut dyn RngCore = &mut state.rng;
+
+        phase!("selection", match (&self.steady_state, &self.cellular, &self.speciation) {
+            (Some(steady_state), _, _) => self.replace(steady_state, &mut state.population, candidates, rng),
+            (None, Some(_), _) => self.replace_cells(&mut state.population, candidates),
+            (None, None, Some(_)) => state.population = candidates,
+            (None, None, None) => {
+                let scores = self.novelty_scores(&candidates, &mut state.novelty_archive, rng);
+                state.population = self.survivors(&state.population, candidates, scores.as_deref(), &state.rates.penalties, &mut state.evaluations, rng);
+            }
+        });
+        self.replace_worst(&mut state.population, immigrants);
+        self.jump(&mut state.population, &mut state.evaluations, rng)?;
+        let changed = self.track_environment(state)?;
+        state.generation = g + 1;
+
+        let stats = GenerationStats::new(state.population.iter().map(|x| &x.fitness), self.objective);
+        #[cfg(feature = "tracing")]
+        tracing::info!(generation = g, best_fitness = stats.best, mean_fitness = stats.mean, worst_fitness = stats.worst, evaluations = state.evaluations, "generation finished");
+        state.history.push(stats);
+        if let (Some(run_log), Some(stats)) = (&self.run_log, state.history.last()) {
+            if !state.population.is_empty() {
+                let best = &state.population[self.best_index(&state.population)].agent;
+                run_log.record(g, state.evaluations, stats, best).map_err(RunError::Log)?;
+            }
+        }
+        self.adapt(&mut state.rates, &state.population);
+        let burst = changed && self.dynamic_environment.as_ref().is_some_and(|dynamic| dynamic.hypermutation);
+        self.hypermutate(&mut state.rates, &state.history, state.generation, burst);
+        self.restart(state)?;
+        state.termination = if self.notify(g, &state.population, state.evaluations) {
+            Some(Termination::Callback)
+        } else if state.population.is_empty() {
+            None
+        } else {
+            state.progress.update(self, &state.population[self.best_index(&state.population)].fitness, state.evaluations)
+        };
+
+        Ok(self.check_bound(state))
+    }
+
+    /// Returns the result of the run (if the run didn't stop yet, its termination is MaxGeneration)
+    ///
+    /// # Arguments:
+    ///
+    /// * `state` the state of the run, created by start() of this Genetic
+    pub fn finish<R>(&self, state: GeneticState<T, F, R>) -> RunResult<T, F> {
+        let GeneticState { population, generation, evaluations, rates, termination, history, archive, restarts, .. } = state;
+        let best = population[self.best_index(&population)].clone();
+
+        RunResult {
+            best: best.agent,
+            best_fitness: best.fitness,
+            population: population.into_iter().map(|x| x.agent).collect(),
+            generations: generation,
+            evaluations,
+            mutation_stats: rates.mutation_bandit.into_stats(),
+            crossover_stats: rates.crossover_bandit.into_stats(),
+            termination: termination.unwrap_or(Termination::MaxGeneration),
+            history,
+            archive,
+            restarts,
+        }
+    }
+}
+
+/// A generation of a run, yielded by the Generations iterator
+#[derive(Debug, Clone, PartialEq)]
+pub struct GenerationSnapshot<T, F = f64> {
+    /// The index of the generation (starting from 0)
+    pub generation: usize,
+    /// The agents of the generation with their fitness
+    pub population: Vec<Evaluated<T, F>>,
+    /// The best agent of the generation
+    pub best: T,
+    /// The fitness of the best agent
+    pub best_fitness: F,
+    /// The number of times the fitness function was called until now
+    pub evaluations: usize,
+    /// The reason the run stopped after this generation, None if it goes on
+    pub termination: Option<Termination>,
+}
+
+/// Iterator over the generations of a run, created by Genetic::generations()
+///
+/// It ends when the run stops (the last generation is still yielded), but it can be stopped earlier with the
+/// usual iterator adapters. The state of the run can be taken back with into_state() to finish it.
+///
+/// # Panics:
+///  Same as run(), if the fitness function fails (also when it's created)
+///
+/// # Examples:
+/// ```
+/// use rand::prelude::*;
+/// use bbte_optim_tzim1773_genetic::Genetic;
+///
+/// fn main() {
+///     let test = Genetic::builder()
+///         .max_generation(100)
+///         .random_agent(|rng| rng.gen_range(-5.0, 5.0))
+///         .fitness(|a: &f64| 5.0 - a * a)
+///         .mutate(|a: &f64, rng| *a + rng.gen_range(-0.01, 0.01))
+///         .offspring(|a: &f64, b: &f64, _rng| (*a + *b) / 2.0)
+///         .build()
+///         .unwrap();
+///
+///     let good_enough = test.generations()
+///         .inspect(|s| println!("{}: {}", s.generation, s.best_fitness))
+///         .find(|s| s.best_fitness > 4.99);
+///     if let Some(snapshot) = good_enough {
+///         println!("{} in generation {}", snapshot.best, snapshot.generation);
+///     }
+/// }
+/// ```
+pub struct Generations<'g, 'a, T, F = f64, R = StdRng> {
+    genetic: &'g Genetic<'a, T, F>,
+    state: GeneticState<T, F, R>,
+}
+
+impl<'g, 'a, T, F, R> Generations<'g, 'a, T, F, R> {
+    /// The state of the run (after the last yielded generation)
+    pub fn state(&self) -> &GeneticState<T, F, R> {
+        &self.state
+    }
+
+    /// Returns the state of the run, it can be continued with step() or turned into a result with finish()
+    pub fn into_state(self) -> GeneticState<T, F, R> {
+        self.state
+    }
+}
+
+impl<'g, 'a, T, F, R> Iterator for Generations<'g, 'a, T, F, R>
+    where T: Clone + MaybeSync, F: Fitness + MaybeSync, R: RngCore {
+    type Item = GenerationSnapshot<T, F>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let generation = self.state.generation;
+        if let Err(error) = self.genetic.step(&mut self.state) {
+            panic!("{}", error);
+        }
+        // the run stopped before executing a generation
+        if self.state.generation == generation {
+            return None;
+        }
+        let best = self.state.population.get(self.genetic.best_index(&self.state.population))?.clone();
+
+        Some(GenerationSnapshot {
+            generation,
+            population: self.state.population.clone(),
+            best: best.agent,
+            best_fitness: best.fitness,
+            evaluations: self.state.evaluations,
+            termination: self.state.termination,
+        })
+    }
+}
+
+impl<'a, T, F> Genetic<'a, T, F>
+    where T: Clone + MaybeSync, F: Fitness + MaybeSync {
+    /// Returns an iterator over the generations of a new run
+    pub fn generations(&self) -> Generations<'_, 'a, T, F> {
+        self.generations_with_rng(StdRng::from_entropy())
+    }
+
+    /// Same as generations(), but the run uses the given random number generator
+    ///
+    /// # Arguments:
+    ///
+    /// * `rng` the random number generator used throughout the run
+    pub fn generations_with_rng<R: RngCore>(&self, rng: R) -> Generations<'_, 'a, T, F, R> {
+        match self.start_with_rng(rng) {
+            Ok(state) => Generations { genetic: self, state },
+            Err(error) => panic!("{}", error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resume() {
+        let test = Genetic::builder()
+            .population(10)
+            .max_generation(20)
+            .random_agent(|rng| rng.gen_range(-5.0, 5.0))
+            .fitness(|a: &f64| 5.0 - a * a)
+            .mutate(|a: &f64, rng| *a + rng.gen_range(-0.1, 0.1))
+            .offspring(|a: &f64, b: &f64, _rng| (*a + *b) / 2.0)
+            .build()
+            .unwrap();
+
+        let mut state = test.start_with_rng(StdRng::seed_from_u64(3)).unwrap();
+        for g in 1..=10 {
+            assert_eq!(test.step(&mut state).unwrap(), None);
+            assert_eq!(state.generation(), g);
+        }
+        // pausing and resuming from a copy gives the same run
+        let mut copy = state.clone();
+        while test.step(&mut state).unwrap().is_none() {}
+        while test.step(&mut copy).unwrap().is_none() {}
+        assert_eq!(test.step(&mut state).unwrap(), Some(Termination::MaxGeneration));
+        assert_eq!(state.generation(), 20);
+        assert_eq!(state.history().len(), 20);
+
+        let result = test.finish(state);
+        assert_eq!(result, test.finish(copy));
+        assert_eq!(result, test.run_with_seed(3));
+    }
+
+    #[test]
+    fn generations() {
+        let test = Genetic::builder()
+            .population(10)
+            .max_generation(20)
+            .random_agent(|rng| rng.gen_range(-5.0, 5.0))
+            .fitness(|a: &f64| 5.0 - a * a)
+            .mutate(|a: &f64, rng| *a + rng.gen_range(-0.1, 0.1))
+            .offspring(|a: &f64, b: &f64, _rng| (*a + *b) / 2.0)
+            .build()
+            .unwrap();
+
+        let snapshots = test.generations_with_rng(StdRng::seed_from_u64(3)).collect::<Vec<_>>();
+        assert_eq!(snapshots.len(), 20);
+        assert!(snapshots.iter().enumerate().all(|(g, s)| s.generation == g));
+        assert_eq!(snapshots[19].termination, Some(Termination::MaxGeneration));
+        assert!(snapshots[..19].iter().all(|s| s.termination.is_none()));
+
+        let result = test.run_with_seed(3);
+        assert_eq!((&snapshots[19].best, snapshots[19].best_fitness), (&result.best, result.best_fitness));
+
+        let mut generations = test.generations_with_rng(StdRng::seed_from_u64(3));
+        assert_eq!(generations.by_ref().take(5).count(), 5);
+        let mut state = generations.into_state();
+        while test.step(&mut state).unwrap().is_none() {}
+        assert_eq!(test.finish(state), result);
+    }
+}
@@ -0,0 +1,104 @@
+use rand::prelude::*;
+
+use crate::{ConfigError, Fitness, Objective};
+
+/// Settings of the steady-state mode: instead of rebuilding the whole generation, every iteration
+/// only creates `offspring` new agents, which take the place of existing ones
+///
+/// Every agent is evaluated only once (when it's created), so it's useful when the fitness function is expensive.
+/// One iteration counts as one generation (max_generation is the number of iterations).
+#[derive(Debug, Clone, PartialEq)]
+pub struct SteadyState {
+    /// Number of offspring created in each iteration (1 <= offspring <= population)
+    pub offspring: usize,
+    /// The way the agents that are replaced by the offspring are chosen
+    pub replacement: Replacement,
+}
+
+/// The way the agent replaced by a new offspring is chosen in the steady-state mode
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Replacement {
+    /// The worst agent is replaced (the best agents are never lost)
+    #[default]
+    Worst,
+    /// `size` random agents compete and the worst of them is replaced
+    /// (smaller sizes keep more diversity, but good agents can be lost, use elitism to protect them)
+    Tournament(usize),
+}
+
+impl SteadyState {
+    /// Steady-state mode where the offspring replace the worst agents
+    pub fn new(offspring: usize) -> Self {
+        SteadyState { offspring, replacement: Replacement::Worst }
+    }
+
+    /// Checks whether the parameters are usable with the given population size
+    pub fn validate(&self, population: usize) -> Result<(), ConfigError> {
+        if self.offspring == 0 || self.offspring > population {
+            return Err(ConfigError::InvalidSteadyState(format!("offspring must be between 1 and the population, got {}", self.offspring)));
+        }
+        if self.replacement == Replacement::Tournament(0) {
+            return Err(ConfigError::InvalidSteadyState("replacement tournament size must be at least 1".to_string()));
+        }
+
+        Ok(())
+    }
+}
+
+impl Replacement {
+    /// Returns the index of the agent that should be replaced, or None if every agent is protected
+    ///
+    /// # Arguments:
+    ///
+    /// * `fitness` the fitness of every agent
+    /// * `protected` the number of the best agents that can't be replaced
+    /// * `objective` whether bigger or smaller fitness is better
+    /// * `rng` random number generator
+    pub fn choose<F: Fitness>(&self, fitness: &[F], protected: usize, objective: Objective, rng: &mut dyn RngCore) -> Option<usize> {
+        let mut candidates = (0..fitness.len()).collect::<Vec<usize>>();
+        // the worst agent is put first
+        candidates.sort_by(|a, b| objective.compare(&fitness[*b], &fitness[*a]));
+        candidates.truncate(fitness.len().saturating_sub(protected));
+        if candidates.is_empty() {
+            return None;
+        }
+
+        match self {
+            Replacement::Worst => Some(candidates[0]),
+            Replacement::Tournament(size) => {
+                (0..*size)
+                    .map(|_| rng.gen_range(0, candidates.len()))
+                    .min()
+                    .map(|c| candidates[c])
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replacement() {
+        let fitness = vec![3.0, 1.0, 4.0, 2.0];
+        let mut rng = StdRng::seed_from_u64(1);
+        assert_eq!(Replacement::Worst.choose(&fitness, 0, Objective::Maximize, &mut rng), Some(1));
+        assert_eq!(Replacement::Worst.choose(&fitness, 0, Objective::Minimize, &mut rng), Some(2));
+        assert_eq!(Replacement::Worst.choose(&fitness, 4, Objective::Maximize, &mut rng), None);
+
+        // the two best agents are protected
+        for _ in 0..100 {
+            let victim = Replacement::Tournament(1).choose(&fitness, 2, Objective::Maximize, &mut rng);
+            assert!(victim == Some(1) || victim == Some(3));
+        }
+    }
+
+    #[test]
+    fn invalid_steady_state() {
+        assert!(SteadyState::new(0).validate(10).is_err());
+        assert!(SteadyState::new(11).validate(10).is_err());
+        assert!(SteadyState { offspring: 1, replacement: Replacement::Tournament(0) }.validate(10).is_err());
+        assert!(SteadyState::new(10).validate(10).is_ok());
+    }
+}
@@ -0,0 +1,273 @@
+use crate::{ConfigError, Evaluated, Fitness, Genetic, MaybeSync, Objective, Offspring};
+
+/// Function that returns the features of an agent (a point in the space the surrogate model interpolates in)
+pub type FeaturesFn<'a, T> = Box<dyn Fn(&T) -> Vec<f64> + Send + Sync + 'a>;
+
+/// The model of a surrogate, predicting the fitness of a point from the evaluated points
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SurrogateModel {
+    /// The mean of the fitness of the `k` nearest evaluated points, weighted by the inverse of their distance
+    NearestNeighbors {
+        /// The number of neighbours (at least 1)
+        k: usize,
+    },
+    /// Interpolation with Gaussian radial basis functions exp(-(distance / width)^2) centred on the evaluated
+    /// points, it solves a linear system of all the points every generation
+    Rbf {
+        /// The width of the basis functions (bigger than 0.0), about the distance of the neighbouring points
+        width: f64,
+    },
+}
+
+impl SurrogateModel {
+    /// Checks whether the parameters are usable
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        match self {
+            SurrogateModel::NearestNeighbors { k } if *k == 0 => Err(ConfigError::InvalidSurrogate("k must be at least 1".to_string())),
+            SurrogateModel::Rbf { width } if !(*width > 0.0 && width.is_finite()) => Err(ConfigError::InvalidSurrogate(format!("width must be bigger than 0.0, got {}", width))),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the predicted fitness of the points from the evaluated points (their features and fitness)
+    pub fn predict(&self, evaluated: &[(Vec<f64>, f64)], xs: &[Vec<f64>]) -> Vec<f64> {
+        if evaluated.is_empty() {
+            return vec![f64::NAN; xs.len()];
+        }
+        match self {
+            SurrogateModel::NearestNeighbors { k } => xs.iter().map(|x| nearest_neighbors(evaluated, x, *k)).collect(),
+            SurrogateModel::Rbf { width } => {
+                let basis = |a: &[f64], b: &[f64]| (-(distance(a, b) / width).powi(2)).exp();
+                let mean = evaluated.iter().map(|(_, f)| f).sum::<f64>() / evaluated.len() as f64;
+                let matrix = evaluated.iter()
+                    .map(|(a, _)| evaluated.iter().map(|(b, _)| basis(a, b)).collect())
+                    .collect();
+                let weights = solve(matrix, evaluated.iter().map(|(_, f)| f - mean).collect());
+                xs.iter()
+                    .map(|x| mean + evaluated.iter().zip(&weights).map(|((a, _), w)| w * basis(a, x)).sum::<f64>())
+                    .collect()
+            }
+        }
+    }
+}
+
+fn distance(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| (x - y) * (x - y)).sum::<f64>().sqrt()
+}
+
+/// The inverse distance weighted mean of the fitness of the k nearest points (the fitness of the point if it
+/// was evaluated)
+fn nearest_neighbors(evaluated: &[(Vec<f64>, f64)], x: &[f64], k: usize) -> f64 {
+    let mut neighbors = evaluated.iter().map(|(a, f)| (distance(a, x), *f)).collect::<Vec<(f64, f64)>>();
+    neighbors.sort_by(|a, b| a.0.total_cmp(&b.0));
+    neighbors.truncate(k);
+    if let Some((_, f)) = neighbors.iter().find(|(d, _)| *d == 0.0) {
+        return *f;
+    }
+    let weights = neighbors.iter().map(|(d, _)| 1.0 / d).sum::<f64>();
+    neighbors.iter().map(|(d, f)| f / d).sum::<f64>() / weights
+}
+
+/// Solves the linear system with Gaussian elimination (with partial pivoting, the matrix is regularised a bit
+/// so that points too close to each other don't make it singular)
+fn solve(mut matrix: Vec<Vec<f64>>, mut values: Vec<f64>) -> Vec<f64> {
+    let n = values.len();
+    for (i, row) in matrix.iter_mut().enumerate() {
+        row[i] += 1e-10;
+    }
+    for column in 0..n {
+        let pivot = (column..n).max_by(|a, b| matrix[*a][column].abs().total_cmp(&matrix[*b][column].abs())).unwrap_or(column);
+        matrix.swap(column, pivot);
+        values.swap(column, pivot);
+        if matrix[column][column] == 0.0 {
+            continue;
+        }
+        for row in column + 1..n {
+            let (above, below) = matrix.split_at_mut(row);
+            let (pivot_row, current) = (&above[column], &mut below[0]);
+            let factor = current[column] / pivot_row[column];
+            for (x, p) in current[column..].iter_mut().zip(&pivot_row[column..]) {
+                *x -= factor * p;
+            }
+            values[row] -= factor * values[column];
+        }
+    }
+    let mut solution = vec![0.0; n];
+    for row in (0..n).rev() {
+        let rest = (row + 1..n).map(|j| matrix[row][j] * solution[j]).sum::<f64>();
+        if matrix[row][row] != 0.0 {
+            solution[row] = (values[row] - rest) / matrix[row][row];
+        }
+    }
+    solution
+}
+
+/// Surrogate-assisted evaluation: a cheap model learned from the evaluated agents pre-screens the offspring, and
+/// only the most promising `ratio` part of the offspring with unknown fitness is evaluated with f_fitness, the
+/// rest is dropped
+///
+/// The model works on the features of the agents (e.g. the values of a real vector) and on Fitness::to_f64(), it
+/// learns from every evaluated agent (at most `capacity` of them, the oldest ones are dropped). Until it knows a
+/// population of agents every offspring is evaluated, and enough of them are always evaluated to fill the next
+/// generation. Only the generational mode uses it.
+///
+/// # Examples:
+/// ```
+/// use rand::prelude::*;
+/// use bbte_optim_tzim1773_genetic::{Genetic, Surrogate, SurrogateModel};
+///
+/// fn main() {
+///     let test = Genetic::builder()
+///         .population(20)
+///         .max_generation(50)
+///         .random_agent(|rng| rng.gen_range(-5.0, 5.0))
+///         .fitness(|a: &f64| 5.0 - a * a) // an expensive simulation
+///         .mutate(|a: &f64, rng| *a + rng.gen_range(-0.1, 0.1))
+///         .offspring(|a: &f64, b: &f64, _rng| (*a + *b) / 2.0)
+///         .surrogate(Surrogate::new(|a: &f64| vec![*a]).model(SurrogateModel::Rbf { width: 1.0 }).ratio(0.3))
+///         .build()
+///         .unwrap();
+///
+///     let result = test.run();
+///     println!("{} with {} evaluations", result.best, result.evaluations); // should be a number close to 0
+/// }
+/// ```
+pub struct Surrogate<'a, T> {
+    /// The model predicting the fitness
+    /// Suggested value: SurrogateModel::NearestNeighbors { k: 5 }
+    pub model: SurrogateModel,
+    /// The part of the offspring with unknown fitness evaluated with f_fitness (0.0 < ratio <= 1.0)
+    /// Suggested value: 0.5
+    pub ratio: f64,
+    /// The maximal number of evaluated agents the model learns from
+    /// Suggested value: 300
+    pub capacity: usize,
+    f_features: FeaturesFn<'a, T>,
+}
+
+impl<'a, T> Surrogate<'a, T> {
+    /// A k-nearest-neighbours surrogate with the given features function and the suggested values
+    pub fn new(f_features: impl Fn(&T) -> Vec<f64> + Send + Sync + 'a) -> Self {
+        Surrogate { model: SurrogateModel::NearestNeighbors { k: 5 }, ratio: 0.5, capacity: 300, f_features: Box::new(f_features) }
+    }
+
+    /// Sets the model predicting the fitness
+    pub fn model(mut self, model: SurrogateModel) -> Self {
+        self.model = model;
+        self
+    }
+
+    /// Sets the part of the offspring evaluated with f_fitness
+    pub fn ratio(mut self, ratio: f64) -> Self {
+        self.ratio = ratio;
+        self
+    }
+
+    /// Sets the maximal number of evaluated agents the model learns from
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self
+    }
+
+    /// Checks whether the parameters are usable
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if !(self.ratio > 0.0 && self.ratio <= 1.0) {
+            return Err(ConfigError::InvalidSurrogate(format!("ratio must be bigger than 0.0 and at most 1.0, got {}", self.ratio)));
+        }
+        if self.capacity == 0 {
+            return Err(ConfigError::InvalidSurrogate("the capacity must be at least 1".to_string()));
+        }
+
+        self.model.validate()
+    }
+}
+
+impl<'a, T, F> Genetic<'a, T, F>
+    where T: Clone + MaybeSync, F: Fitness + MaybeSync {
+    /// Drops the offspring with unknown fitness that the surrogate finds the least promising
+    pub(crate) fn screen(&self, offspring: &mut Vec<Offspring<T, F>>, evaluated: &[(Vec<f64>, f64)]) {
+        let surrogate = match &self.surrogate {
+            Some(surrogate) if evaluated.len() >= self.population => surrogate,
+            _ => return,
+        };
+        let unknown = (0..offspring.len()).filter(|i| offspring[*i].1.is_none()).collect::<Vec<usize>>();
+        let known = offspring.len() - unknown.len();
+        let kept = ((surrogate.ratio * unknown.len() as f64).ceil() as usize).max(self.population.saturating_sub(known));
+        if kept >= unknown.len() {
+            return;
+        }
+
+        let features = unknown.iter().map(|i| (surrogate.f_features)(&offspring[*i].0)).collect::<Vec<Vec<f64>>>();
+        let predicted = surrogate.model.predict(evaluated, &features);
+        let mut order = (0..unknown.len()).collect::<Vec<usize>>();
+        order.sort_by(|a, b| match self.objective {
+            Objective::Maximize => predicted[*b].total_cmp(&predicted[*a]),
+            Objective::Minimize => predicted[*a].total_cmp(&predicted[*b]),
+        });
+        let mut dropped = vec![false; offspring.len()];
+        for i in &order[kept..] {
+            dropped[unknown[*i]] = true;
+        }
+        let mut i = 0;
+        offspring.retain(|_| {
+            i += 1;
+            !dropped[i - 1]
+        });
+    }
+
+    /// Puts the evaluated agents the surrogate doesn't know yet among the points it learns from
+    pub(crate) fn learn(&self, evaluated: &mut Vec<(Vec<f64>, f64)>, xg: &[Evaluated<T, F>]) {
+        let surrogate = match &self.surrogate {
+            Some(surrogate) => surrogate,
+            None => return,
+        };
+        for x in xg {
+            let features = (surrogate.f_features)(&x.agent);
+            let fitness = x.fitness.to_f64();
+            if fitness.is_finite() && !evaluated.iter().any(|(known, _)| *known == features) {
+                evaluated.push((features, fitness));
+            }
+        }
+        let excess = evaluated.len().saturating_sub(surrogate.capacity);
+        evaluated.drain(..excess);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::prelude::*;
+
+    #[test]
+    fn models() {
+        let evaluated = vec![(vec![0.0], 0.0), (vec![1.0], 1.0), (vec![2.0], 4.0), (vec![3.0], 9.0)];
+        let nearest = SurrogateModel::NearestNeighbors { k: 2 }.predict(&evaluated, &[vec![1.0], vec![1.25]]);
+        assert_eq!(nearest, vec![1.0, (1.0 / 0.25 + 4.0 / 0.75) / (1.0 / 0.25 + 1.0 / 0.75)]);
+        // the interpolation goes through the evaluated points
+        let rbf = SurrogateModel::Rbf { width: 1.0 }.predict(&evaluated, &[vec![2.0], vec![1.5]]);
+        assert!((rbf[0] - 4.0).abs() < 1e-6);
+        assert!(rbf[1] > 1.0 && rbf[1] < 4.0);
+
+        assert!(SurrogateModel::Rbf { width: 0.0 }.validate().is_err());
+        assert!(Surrogate::new(|a: &f64| vec![*a]).ratio(0.0).validate().is_err());
+    }
+
+    #[test]
+    fn screening() {
+        let builder = || Genetic::builder()
+            .population(20)
+            .max_generation(30)
+            .random_agent(|rng| rng.gen_range(-5.0, 5.0))
+            .fitness(|a: &f64| 5.0 - a * a)
+            .mutate(|a: &f64, rng| *a + rng.gen_range(-0.1, 0.1))
+            .offspring(|a: &f64, b: &f64, _rng| (*a + *b) / 2.0)
+            .pm(0.5);
+
+        let plain = builder().build().unwrap().run_with_seed(1);
+        let screened = builder().surrogate(Surrogate::new(|a: &f64| vec![*a]).ratio(0.3)).build().unwrap().run_with_seed(1);
+        assert!(screened.evaluations < plain.evaluations);
+        assert!(screened.best.abs() < 0.5);
+        assert_eq!(screened.population.len(), 20);
+    }
+}
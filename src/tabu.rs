@@ -0,0 +1,221 @@
+use rand::prelude::*;
+use std::collections::VecDeque;
+
+use crate::baseline::{BaselineResult, Tracker};
+use crate::{ConfigError, Fitness, Genetic, MaybeSync, RunError};
+
+/// Creates the neighbours of an agent, each with the attribute of the move leading to it
+pub type NeighborhoodFn<'a, T, M> = Box<dyn Fn(&T, &mut dyn RngCore) -> Vec<(T, M)> + Send + Sync + 'a>;
+
+/// When a tabu move is allowed anyway
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Aspiration {
+    /// The tabu moves are never made
+    Never,
+    /// A tabu move is made if it leads to a better agent than the best one found so far
+    BestEver,
+}
+
+/// Tabu search on the problem of a Genetic: in every step the search moves to the best neighbour of the current agent,
+/// even if it's worse than the current one, but the moves whose attribute was used in the last `tenure` steps are
+/// tabu, so the search doesn't go back to the local optimum it just left
+///
+/// The neighbours come from the neighborhood function with the attributes of their moves, which identify the moves
+/// that would undo each other (e.g. the index of the flipped bit, or the pair of the swapped elements). A neighbour is
+/// tabu if the attribute of its move is on the tabu list, unless the aspiration allows it; the search stops when
+/// every neighbour is tabu or there are none. The starting agent and the fitness come from the Genetic, the neighbours
+/// are repaired with its repair(), and the run creates as many agents as the Genetic evaluates
+/// (max_generation * population), stopping early if max_evaluations is reached. With a deterministic neighborhood
+/// the search is deterministic after the starting agent, so run_from() can improve the best agent of a genetic
+/// algorithm.
+///
+/// # Examples:
+/// ```
+/// use rand::prelude::*;
+/// use bbte_optim_tzim1773_genetic::{Genetic, TabuSearch};
+///
+/// fn main() {
+///     // flips the bits of a string, the index of the bit is the attribute of the move
+///     let genetic = Genetic::builder()
+///         .max_generation(20)
+///         .random_agent(|rng| (0..20).map(|_| rng.gen::<bool>()).collect::<Vec<bool>>())
+///         .fitness(|a: &Vec<bool>| a.iter().filter(|bit| **bit).count() as f64)
+///         .mutate(|a: &Vec<bool>, _rng| a.clone())
+///         .offspring(|a: &Vec<bool>, _b: &Vec<bool>, _rng| a.clone())
+///         .build()
+///         .unwrap();
+///     let flips = |a: &Vec<bool>, _rng: &mut dyn RngCore| (0..a.len()).map(|i| {
+///         let mut neighbour = a.clone();
+///         neighbour[i] = !neighbour[i];
+///         (neighbour, i)
+///     }).collect();
+///
+///     let tabu = TabuSearch::new(genetic, 5, flips).unwrap();
+///     println!("{:?}", tabu.run().best); // should be all true
+/// }
+/// ```
+pub struct TabuSearch<'a, T, M, F = f64> {
+    /// The Genetic giving the starting agent, the fitness and the number of agents created
+    pub genetic: Genetic<'a, T, F>,
+    /// The number of steps a used move attribute stays tabu
+    /// Suggested value: 5 - 20 (smaller than the number of neighbours)
+    pub tenure: usize,
+    /// When a tabu move is allowed anyway
+    /// Suggested value: Aspiration::BestEver
+    pub aspiration: Aspiration,
+    f_neighborhood: NeighborhoodFn<'a, T, M>,
+}
+
+impl<'a, T, M, F> TabuSearch<'a, T, M, F>
+    where T: Clone + MaybeSync, M: PartialEq, F: Fitness + MaybeSync {
+    /// Creates a tabu search on the problem of the Genetic (with the BestEver aspiration), checking the tenure
+    ///
+    /// # Arguments:
+    ///
+    /// * `genetic` the starting agent, the fitness and the number of agents created
+    /// * `tenure` the number of steps a used move attribute stays tabu (at least 1)
+    /// * `f_neighborhood` creates the neighbours of an agent, each with the attribute of the move leading to it
+    pub fn new(genetic: Genetic<'a, T, F>, tenure: usize, f_neighborhood: impl Fn(&T, &mut dyn RngCore) -> Vec<(T, M)> + Send + Sync + 'a) -> Result<Self, ConfigError> {
+        if tenure == 0 {
+            return Err(ConfigError::InvalidTabuSearch("the tenure must be at least 1".to_string()));
+        }
+
+        Ok(TabuSearch { genetic, tenure, aspiration: Aspiration::BestEver, f_neighborhood: Box::new(f_neighborhood) })
+    }
+
+    /// Sets when a tabu move is allowed anyway
+    pub fn aspiration(mut self, aspiration: Aspiration) -> Self {
+        self.aspiration = aspiration;
+        self
+    }
+
+    /// Searches from a random agent until the agents (or the evaluations) run out or every neighbour is tabu
+    ///
+    /// # Panics:
+    ///  If the fitness function fails and the fitness_error_policy gives up on the agent
+    ///  (use try_run() to get the error instead)
+    pub fn run(&self) -> BaselineResult<T, F> {
+        self.run_with_rng(&mut thread_rng())
+    }
+
+    /// Same as run(), but returns the error of the fitness function instead of panicking
+    pub fn try_run(&self) -> Result<BaselineResult<T, F>, RunError> {
+        self.try_run_with_rng(&mut thread_rng())
+    }
+
+    /// Same as run(), but uses the given random number generator
+    pub fn run_with_rng(&self, rng: &mut dyn RngCore) -> BaselineResult<T, F> {
+        match self.try_run_with_rng(rng) {
+            Ok(result) => result,
+            Err(error) => panic!("{}", error),
+        }
+    }
+
+    /// Same as run(), but uses a random number generator seeded with the given seed, so the runs can be reproduced
+    pub fn run_with_seed(&self, seed: u64) -> BaselineResult<T, F> {
+        self.run_with_rng(&mut StdRng::seed_from_u64(seed))
+    }
+
+    /// Same as run_with_rng(), but returns the error of the fitness function instead of panicking
+    pub fn try_run_with_rng(&self, rng: &mut dyn RngCore) -> Result<BaselineResult<T, F>, RunError> {
+        self.try_run_from((self.genetic.get_random_agent)(rng), rng)
+    }
+
+    /// Same as run_with_rng(), but starts from the given agent (e.g. the best agent of a genetic algorithm)
+    pub fn run_from(&self, start: T, rng: &mut dyn RngCore) -> BaselineResult<T, F> {
+        match self.try_run_from(start, rng) {
+            Ok(result) => result,
+            Err(error) => panic!("{}", error),
+        }
+    }
+
+    /// Same as run_from(), but returns the error of the fitness function instead of panicking
+    pub fn try_run_from(&self, start: T, rng: &mut dyn RngCore) -> Result<BaselineResult<T, F>, RunError> {
+        let genetic = &self.genetic;
+        let mut tracker = Tracker::new(genetic);
+        let mut tabu = VecDeque::with_capacity(self.tenure + 1);
+
+        let mut current = genetic.repair(start);
+        let f_current = genetic.evaluate_unlimited(&current, &mut tracker.evaluations)?;
+        tracker.record(&f_current);
+        let mut best = (current.clone(), f_current);
+
+        'search: while !tracker.is_done() {
+            let mut next: Option<(T, M, F)> = None;
+            for (neighbour, attribute) in (self.f_neighborhood)(&current, rng) {
+                if tracker.is_done() {
+                    break;
+                }
+                let neighbour = genetic.repair(neighbour);
+                let f_neighbour = match tracker.evaluate(&neighbour)? {
+                    Some(f_neighbour) => f_neighbour,
+                    None => break 'search,
+                };
+                let allowed = !tabu.contains(&attribute) || match self.aspiration {
+                    Aspiration::Never => false,
+                    Aspiration::BestEver => genetic.objective.is_better(&f_neighbour, &best.1),
+                };
+                if allowed && next.as_ref().is_none_or(|(_, _, f_next)| genetic.objective.is_better(&f_neighbour, f_next)) {
+                    next = Some((neighbour, attribute, f_neighbour));
+                }
+            }
+
+            // every neighbour is tabu (or there are none)
+            let (x, attribute, f_x) = match next {
+                Some(next) => next,
+                None => break,
+            };
+            tabu.push_back(attribute);
+            if tabu.len() > self.tenure {
+                tabu.pop_front();
+            }
+            if genetic.objective.is_better(&f_x, &best.1) {
+                best = (x.clone(), f_x);
+            }
+            current = x;
+        }
+
+        Ok(tracker.finish(best))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn genetic<'a>() -> Genetic<'a, i32> {
+        // a local optimum at -50 (fitness 0) and the global one at 50 (fitness 10), with steps of 1
+        Genetic::builder()
+            .population(10)
+            .max_generation(100)
+            .random_agent(|rng| rng.gen_range(-100, 100))
+            .fitness(|a: &i32| (10 - (a - 50).abs()).max(-(a + 50).abs()) as f64)
+            .mutate(|a: &i32, _rng| *a)
+            .offspring(|a: &i32, _b: &i32, _rng| *a)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn tabu_search() {
+        // the attribute of a step is the edge between the two numbers, so the step back is tabu
+        let steps = |a: &i32, _rng: &mut dyn RngCore| vec![(a + 1, *a), (a - 1, a - 1)];
+        assert!(TabuSearch::new(genetic(), 0, steps).is_err());
+
+        // the search leaves the local optimum through the valley instead of stepping back into it
+        let tabu = TabuSearch::new(genetic(), 2, steps).unwrap();
+        let mut rng = StdRng::seed_from_u64(1);
+        let result = tabu.run_from(-50, &mut rng);
+        assert_eq!((result.best, result.samples, result.restarts), (50, 1000, 0));
+        assert!(result.history.windows(2).all(|w| w[1] >= w[0]));
+
+        // when every move has the same attribute, only the aspiration lets the search go on
+        let same = |a: &i32, _rng: &mut dyn RngCore| vec![(a - 1, ()), (a + 1, ())];
+        let never = TabuSearch::new(genetic(), 1, same).unwrap().aspiration(Aspiration::Never);
+        assert_eq!(never.run_from(20, &mut rng).samples, 5);
+        let best_ever = TabuSearch::new(genetic(), 1, same).unwrap();
+        let result = best_ever.run_from(20, &mut rng);
+        assert_eq!((result.best, result.samples), (50, 1 + 2 * 31));
+    }
+}
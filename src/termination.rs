@@ -0,0 +1,449 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::{ConfigError, Fitness, GenerationStats, Genetic, Objective};
+
+/// The reason a run stopped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Termination {
+    /// Every generation (max_generation) was executed
+    MaxGeneration,
+    /// The best fitness didn't improve for the given number of generations
+    Stagnation,
+    /// An agent reached the target fitness
+    TargetReached,
+    /// The time budget (max_duration) was used up
+    TimeLimit,
+    /// The fitness evaluation budget (max_evaluations) was used up
+    EvaluationLimit,
+    /// The stop_condition was met
+    StopCondition,
+    /// The on_generation callback returned ControlFlow::Break
+    Callback,
+    /// The run was cancelled with its CancellationToken
+    Cancelled,
+}
+
+/// Stops a run from another thread (e.g. a Ctrl-C handler or the cancel button of a GUI), the run returns
+/// the result of the last finished generation
+///
+/// The clones of a token share its state. It's checked before and after every generation.
+///
+/// # Examples:
+/// ```
+/// use rand::prelude::*;
+/// use std::time::Duration;
+/// use bbte_optim_tzim1773_genetic::{CancellationToken, Genetic, Termination};
+///
+/// fn main() {
+///     let token = CancellationToken::new();
+///     let test = Genetic::builder()
+///         .max_generation(usize::MAX)
+///         .random_agent(|rng| rng.gen_range(-5.0, 5.0))
+///         .fitness(|a: &f64| 5.0 - a * a)
+///         .mutate(|a: &f64, rng| *a + rng.gen_range(-0.01, 0.01))
+///         .offspring(|a: &f64, b: &f64, _rng| (*a + *b) / 2.0)
+///         .cancellation(token.clone())
+///         .build()
+///         .unwrap();
+///
+///     let handle = std::thread::spawn(move || test.run());
+///     std::thread::sleep(Duration::from_millis(100));
+///     token.cancel();
+///
+///     let result = handle.join().unwrap();
+///     assert_eq!(result.termination, Termination::Cancelled);
+///     println!("{} after {} generations", result.best, result.generations);
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    /// Creates a token that isn't cancelled
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Asks the runs using this token to stop
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    /// Returns true if cancel() was called
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+impl From<Arc<AtomicBool>> for CancellationToken {
+    /// The run is cancelled when the flag is set to true
+    fn from(flag: Arc<AtomicBool>) -> Self {
+        CancellationToken(flag)
+    }
+}
+
+/// Stops the run when the best fitness doesn't improve by more than epsilon for the given number of generations
+///
+/// # Examples:
+/// ```
+/// use rand::prelude::*;
+/// use bbte_optim_tzim1773_genetic::{Genetic, Stagnation, Termination};
+///
+/// fn main() {
+///     let test = Genetic::builder()
+///         .max_generation(10000)
+///         .random_agent(|rng| rng.gen_range(-5.0, 5.0))
+///         .fitness(|a: &f64| 5.0 - a * a)
+///         .mutate(|a: &f64, rng| *a + rng.gen_range(-0.01, 0.01))
+///         .offspring(|a: &f64, b: &f64, _rng| (*a + *b) / 2.0)
+///         .stagnation(Stagnation { generations: 20, epsilon: 1e-9 })
+///         .build()
+///         .unwrap();
+///
+///     let result = test.run();
+///     assert_eq!(result.termination, Termination::Stagnation);
+///     println!("{} after {} generations", result.best, result.generations);
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Stagnation {
+    /// Number of generations without improvement (at least 1)
+    pub generations: usize,
+    /// Smallest change of the best fitness that counts as an improvement (at least 0.0)
+    pub epsilon: f64,
+}
+
+impl Stagnation {
+    /// Stops after the given number of generations without any improvement
+    pub fn new(generations: usize) -> Self {
+        Stagnation { generations, epsilon: 0.0 }
+    }
+
+    /// Checks whether the parameters are usable
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.generations == 0 {
+            return Err(ConfigError::InvalidStopCondition("stagnation needs at least 1 generation".to_string()));
+        }
+        if self.epsilon.is_nan() || self.epsilon < 0.0 {
+            return Err(ConfigError::InvalidStopCondition(format!("epsilon can't be negative, got {}", self.epsilon)));
+        }
+
+        Ok(())
+    }
+
+    /// Returns true if the best fitness found in the generations of the history didn't improve by more than
+    /// epsilon in its last `generations` generations
+    pub(crate) fn is_reached(&self, history: &[GenerationStats], objective: Objective) -> bool {
+        if history.len() <= self.generations {
+            return false;
+        }
+        // the best fitness found until every generation
+        let bests = history.iter()
+            .scan(f64::NAN, |best, stats| {
+                if best.is_nan() || objective.is_better(&stats.best, best) {
+                    *best = stats.best;
+                }
+                Some(*best)
+            })
+            .collect::<Vec<f64>>();
+        let now = bests[bests.len() - 1];
+        let before = bests[bests.len() - 1 - self.generations];
+        let improvement = match objective {
+            Objective::Maximize => now - before,
+            Objective::Minimize => before - now,
+        };
+
+        improvement <= self.epsilon
+    }
+}
+
+/// Function that decides from the state of a run whether it should stop
+pub type StopFn<'a, F> = Box<dyn Fn(&RunStatus<F>) -> bool + Send + Sync + 'a>;
+
+/// The state of a run after a generation, the stopping criteria are checked against it
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunStatus<'s, F> {
+    /// Number of generations executed
+    pub generation: usize,
+    /// Number of times the fitness function was called
+    pub evaluations: usize,
+    /// Time since the start of the run
+    pub elapsed: Duration,
+    /// The fitness of the best agent of the last generation
+    pub best_fitness: &'s F,
+    /// Whether the fitness is maximised or minimised
+    pub objective: Objective,
+    history: &'s [f64],
+}
+
+impl<'s, F> RunStatus<'s, F> {
+    /// How much the best fitness found so far improved in the last `generations` generations
+    /// (positive if it got better, using Fitness::to_f64()), None if the run isn't that long yet
+    pub fn improvement(&self, generations: usize) -> Option<f64> {
+        if generations == 0 || self.history.len() <= generations {
+            return None;
+        }
+        let now = self.history[self.history.len() - 1];
+        let before = self.history[self.history.len() - 1 - generations];
+        match self.objective {
+            Objective::Maximize => Some(now - before),
+            Objective::Minimize => Some(before - now),
+        }
+    }
+
+    /// Returns true if the best fitness didn't improve according to the stagnation criterion
+    pub fn is_stagnant(&self, stagnation: &Stagnation) -> bool {
+        self.improvement(stagnation.generations).is_some_and(|improvement| improvement <= stagnation.epsilon)
+    }
+}
+
+/// Stopping criteria that can be combined: the run stops when the condition is met
+/// (max_generation is not used if a stop condition is given, the run goes on until the condition is met)
+///
+/// # Examples:
+/// ```
+/// use rand::prelude::*;
+/// use std::time::Duration;
+/// use bbte_optim_tzim1773_genetic::{Genetic, Stagnation, StopCondition};
+///
+/// fn main() {
+///     // at most 1000 generations or 1 second, but at least 20 generations, unless the target is reached
+///     let stop = StopCondition::MaxGeneration(1000)
+///         .or(StopCondition::MaxDuration(Duration::from_secs(1)))
+///         .or(StopCondition::Stagnation(Stagnation::new(10)).and(StopCondition::Custom(Box::new(|s| s.generation >= 20))))
+///         .or(StopCondition::TargetFitness(5.0));
+///
+///     let test = Genetic::builder()
+///         .random_agent(|rng| rng.gen_range(-5.0, 5.0))
+///         .fitness(|a: &f64| 5.0 - a * a)
+///         .mutate(|a: &f64, rng| *a + rng.gen_range(-0.01, 0.01))
+///         .offspring(|a: &f64, b: &f64, _rng| (*a + *b) / 2.0)
+///         .stop_condition(stop)
+///         .build()
+///         .unwrap();
+///
+///     let result = test.run();
+///     println!("{} after {} generations", result.best, result.generations);
+/// }
+/// ```
+pub enum StopCondition<'a, F = f64> {
+    /// The given number of generations were executed
+    MaxGeneration(usize),
+    /// The best agent reached this fitness (or went beyond it)
+    TargetFitness(F),
+    /// The best fitness didn't improve
+    Stagnation(Stagnation),
+    /// The run took this long
+    MaxDuration(Duration),
+    /// The fitness function was called this many times
+    MaxEvaluations(usize),
+    /// Your own function of the state of the run
+    Custom(StopFn<'a, F>),
+    /// Every condition is met
+    And(Vec<StopCondition<'a, F>>),
+    /// Any of the conditions is met
+    Or(Vec<StopCondition<'a, F>>),
+}
+
+impl<'a, F: Fitness> StopCondition<'a, F> {
+    /// Met when both conditions are met
+    pub fn and(self, other: Self) -> Self {
+        match self {
+            StopCondition::And(mut conditions) => {
+                conditions.push(other);
+                StopCondition::And(conditions)
+            }
+            condition => StopCondition::And(vec![condition, other]),
+        }
+    }
+
+    /// Met when any of the conditions is met
+    pub fn or(self, other: Self) -> Self {
+        match self {
+            StopCondition::Or(mut conditions) => {
+                conditions.push(other);
+                StopCondition::Or(conditions)
+            }
+            condition => StopCondition::Or(vec![condition, other]),
+        }
+    }
+
+    /// Returns true if the run should stop
+    pub fn is_met(&self, status: &RunStatus<F>) -> bool {
+        match self {
+            StopCondition::MaxGeneration(max_generation) => status.generation >= *max_generation,
+            StopCondition::TargetFitness(target) => !status.objective.is_better(target, status.best_fitness),
+            StopCondition::Stagnation(stagnation) => status.is_stagnant(stagnation),
+            StopCondition::MaxDuration(max_duration) => status.elapsed >= *max_duration,
+            StopCondition::MaxEvaluations(max_evaluations) => status.evaluations >= *max_evaluations,
+            StopCondition::Custom(f) => f(status),
+            StopCondition::And(conditions) => conditions.iter().all(|condition| condition.is_met(status)),
+            StopCondition::Or(conditions) => conditions.iter().any(|condition| condition.is_met(status)),
+        }
+    }
+
+    /// Checks whether the parameters are usable
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        match self {
+            StopCondition::Stagnation(stagnation) => stagnation.validate(),
+            StopCondition::And(conditions) | StopCondition::Or(conditions) => {
+                if conditions.is_empty() {
+                    return Err(ConfigError::InvalidStopCondition("And and Or need at least one condition".to_string()));
+                }
+                conditions.iter().try_for_each(StopCondition::validate)
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// The state of a run the stopping criteria are checked against
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Progress {
+    start: Instant,
+    /// The best fitness found until the end of every generation
+    history: Vec<f64>,
+}
+
+impl Progress {
+    /// Starts measuring the time of a run that started at `start` (before its first generation was created)
+    pub fn new(start: Instant) -> Self {
+        Progress { start, history: Vec::new() }
+    }
+
+    /// Continues measuring a run that already took `elapsed` time and found the given best fitness values
+    #[cfg(feature = "serde")]
+    pub fn resume(history: Vec<f64>, elapsed: Duration) -> Self {
+        Progress { start: Instant::now().checked_sub(elapsed).unwrap_or_else(Instant::now), history }
+    }
+
+    /// The time since the start of the run
+    #[cfg(feature = "serde")]
+    pub fn elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    /// The best fitness found until the end of every generation
+    #[cfg(feature = "serde")]
+    pub fn history(&self) -> &[f64] {
+        &self.history
+    }
+
+    /// Records the best fitness of a generation, returns the reason of stopping if the run has to stop
+    pub fn update<T, F: Fitness>(&mut self, genetic: &Genetic<T, F>, f_best: &F, evaluations: usize) -> Option<Termination> {
+        let objective = genetic.objective;
+        let f_x = f_best.to_f64();
+        let best = match self.history.last() {
+            Some(best) if !objective.is_better(&f_x, best) => *best,
+            _ => f_x,
+        };
+        self.history.push(best);
+
+        let status = RunStatus {
+            generation: self.history.len(),
+            evaluations,
+            elapsed: self.start.elapsed(),
+            best_fitness: f_best,
+            objective,
+            history: &self.history,
+        };
+        if genetic.target_fitness.as_ref().is_some_and(|target| !objective.is_better(target, f_best)) {
+            return Some(Termination::TargetReached);
+        }
+        if genetic.stagnation.as_ref().is_some_and(|stagnation| status.is_stagnant(stagnation)) {
+            return Some(Termination::Stagnation);
+        }
+        if genetic.max_duration.is_some_and(|max_duration| status.elapsed >= max_duration) {
+            return Some(Termination::TimeLimit);
+        }
+        if genetic.stop_condition.as_ref().is_some_and(|stop_condition| stop_condition.is_met(&status)) {
+            return Some(Termination::StopCondition);
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn genetic<'a>() -> Genetic<'a, f64> {
+        Genetic::builder()
+            .random_agent(|_| 0.0)
+            .fitness(|a: &f64| *a)
+            .mutate(|a: &f64, _rng| *a)
+            .offspring(|a: &f64, _b: &f64, _rng| *a)
+            .stagnation(Stagnation { generations: 2, epsilon: 0.5 })
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn stagnation() {
+        let mut genetic = genetic();
+        let mut progress = Progress::new(Instant::now());
+
+        assert_eq!(progress.update(&genetic, &1.0, 0), None);
+        assert_eq!(progress.update(&genetic, &2.0, 0), None);
+        // too small improvements
+        assert_eq!(progress.update(&genetic, &2.4, 0), None);
+        assert_eq!(progress.update(&genetic, &2.3, 0), Some(Termination::Stagnation));
+
+        genetic.objective = Objective::Minimize;
+        let mut progress = Progress::new(Instant::now());
+        assert_eq!(progress.update(&genetic, &2.0, 0), None);
+        assert_eq!(progress.update(&genetic, &1.0, 0), None);
+        assert_eq!(progress.update(&genetic, &3.0, 0), None);
+        assert_eq!(progress.update(&genetic, &0.0, 0), None);
+
+        assert!(Stagnation::new(0).validate().is_err());
+        assert!(Stagnation { generations: 1, epsilon: -1.0 }.validate().is_err());
+    }
+
+    #[test]
+    fn target() {
+        let mut genetic = genetic();
+        genetic.target_fitness = Some(2.0);
+        let mut progress = Progress::new(Instant::now());
+
+        assert_eq!(progress.update(&genetic, &1.0, 0), None);
+        assert_eq!(progress.update(&genetic, &2.0, 0), Some(Termination::TargetReached));
+
+        genetic.objective = Objective::Minimize;
+        let mut progress = Progress::new(Instant::now());
+        assert_eq!(progress.update(&genetic, &2.5, 0), None);
+        assert_eq!(progress.update(&genetic, &1.5, 0), Some(Termination::TargetReached));
+    }
+
+    #[test]
+    fn stop_condition() {
+        let mut genetic = genetic();
+        genetic.stagnation = None;
+        genetic.stop_condition = Some(StopCondition::MaxEvaluations(100).and(StopCondition::Custom(Box::new(|s| *s.best_fitness > 3.0))));
+        let mut progress = Progress::new(Instant::now());
+
+        assert_eq!(progress.update(&genetic, &4.0, 50), None);
+        assert_eq!(progress.update(&genetic, &2.0, 150), None);
+        assert_eq!(progress.update(&genetic, &4.0, 150), Some(Termination::StopCondition));
+
+        let stop = StopCondition::MaxGeneration(10).or(StopCondition::TargetFitness(1.0)).or(StopCondition::MaxGeneration(5));
+        assert!(matches!(&stop, StopCondition::Or(conditions) if conditions.len() == 3));
+        assert!(StopCondition::<f64>::Or(Vec::new()).validate().is_err());
+        assert!(StopCondition::<f64>::Stagnation(Stagnation::new(0)).validate().is_err());
+    }
+
+    #[test]
+    fn time_limit() {
+        let mut genetic = genetic();
+        genetic.max_duration = Some(std::time::Duration::from_millis(20));
+        let mut progress = Progress::new(Instant::now());
+
+        assert_eq!(progress.update(&genetic, &1.0, 0), None);
+        std::thread::sleep(std::time::Duration::from_millis(25));
+        assert_eq!(progress.update(&genetic, &1.0, 0), Some(Termination::TimeLimit));
+    }
+}
@@ -0,0 +1,77 @@
+/// Executes a phase of a generation (variation, crossover, mutation, evaluation, selection) in its own span,
+/// so a tracing subscriber can measure its duration (without the tracing feature it only executes it)
+macro_rules! phase {
+    ($name:literal, $body:expr) => {{
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!($name).entered();
+        $body
+    }};
+}
+
+pub(crate) use phase;
+
+#[cfg(all(test, feature = "tracing"))]
+mod tests {
+    use crate::Genetic;
+    use rand::Rng;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::{Arc, Mutex};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Metadata, Subscriber};
+
+    /// The names of the spans and the number of events
+    #[derive(Default)]
+    struct Records {
+        spans: Mutex<Vec<&'static str>>,
+        events: AtomicU64,
+    }
+
+    /// Subscriber collecting the records
+    #[derive(Clone, Default)]
+    struct Collector(Arc<Records>);
+
+    impl Subscriber for Collector {
+        fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &Attributes<'_>) -> Id {
+            let mut spans = self.0.spans.lock().unwrap();
+            spans.push(span.metadata().name());
+            Id::from_u64(spans.len() as u64)
+        }
+
+        fn record(&self, _span: &Id, _values: &Record<'_>) {}
+
+        fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+        fn event(&self, _event: &Event<'_>) {
+            self.0.events.fetch_add(1, Ordering::Relaxed);
+        }
+
+        fn enter(&self, _span: &Id) {}
+
+        fn exit(&self, _span: &Id) {}
+    }
+
+    #[test]
+    fn spans() {
+        let test = Genetic::builder()
+            .population(10)
+            .max_generation(3)
+            .random_agent(|rng| rng.gen_range(-5.0, 5.0))
+            .fitness(|a: &f64| 5.0 - a * a)
+            .mutate(|a: &f64, rng| *a + rng.gen_range(-0.1, 0.1))
+            .offspring(|a: &f64, b: &f64, _rng| (*a + *b) / 2.0)
+            .build()
+            .unwrap();
+        let collector = Collector::default();
+        tracing::subscriber::with_default(collector.clone(), || test.run());
+
+        let spans = collector.0.spans.lock().unwrap();
+        for name in ["generation", "variation", "crossover", "mutation", "evaluation", "selection"] {
+            assert_eq!(spans.iter().filter(|span| **span == name).count(), 3, "{}", name);
+        }
+        assert_eq!(collector.0.events.load(Ordering::Relaxed), 3);
+    }
+}
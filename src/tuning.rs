@@ -0,0 +1,285 @@
+use rand::prelude::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use crate::operators::standard_normal;
+use crate::{ConfigError, Fitness, FitnessError, FitnessErrorPolicy, Genetic, MaybeSync, Selection};
+
+/// The settings of the genetic algorithm tuned by a Tuner
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Hyperparameters {
+    /// Population size
+    pub population: usize,
+    /// Probability of crossover
+    pub pc: f64,
+    /// Probability of mutation
+    pub pm: f64,
+    /// The selection strategy
+    pub selection: Selection,
+}
+
+impl Hyperparameters {
+    /// Sets the hyperparameters of the Genetic
+    pub fn apply<T, F>(&self, genetic: &mut Genetic<T, F>) {
+        genetic.population = self.population;
+        genetic.pc = self.pc;
+        genetic.pm = self.pm;
+        genetic.selection = self.selection.clone();
+    }
+}
+
+/// The outcome of tuning
+#[derive(Debug, Clone, PartialEq)]
+pub struct TuningResult {
+    /// The best hyperparameters found
+    pub best: Hyperparameters,
+    /// The average of the best fitness of the inner runs with the best hyperparameters (using Fitness::to_f64())
+    pub score: f64,
+    /// The number of hyperparameter settings tried
+    pub settings: usize,
+    /// The number of times the fitness function of the problem was called by all of the inner runs
+    pub evaluations: usize,
+}
+
+/// Tunes the population size, the probabilities of crossover and mutation and the selection strategy of a genetic
+/// algorithm with a meta genetic algorithm: every setting is scored by running short inner genetic algorithms on the
+/// problem, and the outer one evolves the settings
+///
+/// The inner Genetics are created by the given function (with everything else set, e.g. the operators, the elitism
+/// or the stopping criteria), then their hyperparameters are overwritten, and they run until `budget` evaluations
+/// (max_evaluations is set to the budget and max_generation to its value, so every setting gets the same number of
+/// evaluations). A setting is scored by the average of the best fitness of `repeats` runs, seeded with 0, 1, ... so
+/// the settings are compared on the same random numbers. The settings that the inner Genetic can't run with (e.g.
+/// a population smaller than the elitism) get the worst score.
+///
+/// # Examples:
+/// ```
+/// use rand::prelude::*;
+/// use bbte_optim_tzim1773_genetic::{Genetic, Tuner};
+///
+/// fn main() {
+///     let problem = || Genetic::builder()
+///         .random_agent(|rng| rng.gen_range(-100.0, 100.0))
+///         .fitness(|a: &f64| -(a - 7.0).abs())
+///         .mutate(|a: &f64, rng| a + rng.gen_range(-1.0, 1.0))
+///         .offspring(|a: &f64, b: &f64, _rng| (a + b) / 2.0)
+///         .build()
+///         .unwrap();
+///
+///     let result = Tuner::new(problem, 500).repeats(2).meta(6, 3).run_with_seed(1);
+///     let mut genetic = problem();
+///     result.best.apply(&mut genetic);
+///     println!("{:?} {}", result.best, genetic.run().best);
+/// }
+/// ```
+pub struct Tuner<'a, T, F = f64> {
+    /// The number of evaluations of an inner run
+    /// Suggested value: the budget of the real runs, or a part of it
+    pub budget: usize,
+    /// The number of inner runs scoring a setting
+    /// Suggested value: 3
+    pub repeats: usize,
+    /// The smallest and the biggest population tried
+    /// Suggested value: (10, 200)
+    pub population_range: (usize, usize),
+    /// The selection strategies tried
+    /// Suggested value: Truncation, Tournament with 2 and 5 agents, LinearRank and ExponentialRank
+    pub selections: Vec<Selection>,
+    /// The population of the outer genetic algorithm
+    /// Suggested value: 10
+    pub meta_population: usize,
+    /// The number of generations of the outer genetic algorithm
+    /// Suggested value: 10
+    pub meta_generations: usize,
+    f_genetic: Arc<dyn Fn() -> Genetic<'a, T, F> + Send + Sync + 'a>,
+}
+
+impl<'a, T, F> Tuner<'a, T, F>
+    where T: Clone + MaybeSync + 'a, F: Fitness + MaybeSync + 'a {
+    /// Creates a tuner with the suggested settings
+    ///
+    /// # Arguments:
+    ///
+    /// * `f_genetic` creates the inner Genetics solving the problem
+    /// * `budget` the number of evaluations of an inner run
+    pub fn new(f_genetic: impl Fn() -> Genetic<'a, T, F> + Send + Sync + 'a, budget: usize) -> Self {
+        Tuner {
+            budget,
+            repeats: 3,
+            population_range: (10, 200),
+            selections: vec![
+                Selection::Truncation,
+                Selection::tournament(2),
+                Selection::tournament(5),
+                Selection::LinearRank { selection_pressure: 1.5 },
+                Selection::ExponentialRank { selection_pressure: 3.0 },
+            ],
+            meta_population: 10,
+            meta_generations: 10,
+            f_genetic: Arc::new(f_genetic),
+        }
+    }
+
+    /// Sets the number of inner runs scoring a setting
+    pub fn repeats(mut self, repeats: usize) -> Self {
+        self.repeats = repeats;
+        self
+    }
+
+    /// Sets the smallest and the biggest population tried
+    pub fn population_range(mut self, min: usize, max: usize) -> Self {
+        self.population_range = (min, max);
+        self
+    }
+
+    /// Sets the selection strategies tried
+    pub fn selections(mut self, selections: Vec<Selection>) -> Self {
+        self.selections = selections;
+        self
+    }
+
+    /// Sets the population and the number of generations of the outer genetic algorithm
+    pub fn meta(mut self, population: usize, generations: usize) -> Self {
+        self.meta_population = population;
+        self.meta_generations = generations;
+        self
+    }
+
+    /// Checks whether the settings of the tuner are usable
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.budget == 0 || self.repeats == 0 {
+            return Err(ConfigError::InvalidTuning("the budget and the repeats must be at least 1".to_string()));
+        }
+        let (min, max) = self.population_range;
+        if min < 2 || min > max {
+            return Err(ConfigError::InvalidTuning(format!("the population range must be at least 2 and not empty, got {} - {}", min, max)));
+        }
+        if self.selections.is_empty() {
+            return Err(ConfigError::InvalidTuning("at least one selection strategy has to be tried".to_string()));
+        }
+        for selection in &self.selections {
+            selection.validate()?;
+        }
+        if self.meta_population < 2 || self.meta_generations == 0 {
+            return Err(ConfigError::InvalidTuning("the outer genetic algorithm needs at least 2 agents and 1 generation".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Tunes the hyperparameters
+    ///
+    /// # Panics:
+    ///  If the tuner is not valid (see validate())
+    pub fn run(&self) -> TuningResult {
+        self.run_with_rng(&mut thread_rng())
+    }
+
+    /// Same as run(), but uses a random number generator seeded with the given seed, so the tuning can be reproduced
+    pub fn run_with_seed(&self, seed: u64) -> TuningResult {
+        self.run_with_rng(&mut StdRng::seed_from_u64(seed))
+    }
+
+    /// Same as run(), but uses the given random number generator for the outer genetic algorithm
+    pub fn run_with_rng(&self, mut rng: &mut dyn RngCore) -> TuningResult {
+        if let Err(error) = self.validate() {
+            panic!("{}", error);
+        }
+        let evaluations = Arc::new(AtomicUsize::new(0));
+        let objective = (self.f_genetic)().objective;
+        let (min, max) = self.population_range;
+        let (selections, mutated_selections) = (self.selections.clone(), self.selections.clone());
+        let (f_genetic, counter) = (Arc::clone(&self.f_genetic), Arc::clone(&evaluations));
+        let (budget, repeats) = (self.budget, self.repeats);
+
+        let meta = Genetic::builder()
+            .population(self.meta_population)
+            .max_generation(self.meta_generations)
+            .elitism(1)
+            .objective(objective)
+            .fitness_error_policy(FitnessErrorPolicy::AssignWorst)
+            .random_agent(move |rng| Hyperparameters {
+                // the population is uniform on a logarithmic scale
+                population: ((min as f64).ln() + rng.gen::<f64>() * ((max as f64).ln() - (min as f64).ln())).exp().round() as usize,
+                pc: rng.gen::<f64>(),
+                pm: rng.gen::<f64>(),
+                selection: selections[rng.gen_range(0, selections.len())].clone(),
+            })
+            .try_fitness(move |setting: &Hyperparameters| -> Result<f64, FitnessError> {
+                let mut score = 0.0;
+                for seed in 0..repeats {
+                    let mut genetic = f_genetic();
+                    setting.apply(&mut genetic);
+                    genetic.max_evaluations = Some(budget);
+                    genetic.max_generation = budget;
+                    genetic.validate()?;
+                    let result = genetic.try_run_with_rng(&mut StdRng::seed_from_u64(seed as u64))?;
+                    counter.fetch_add(result.evaluations, Ordering::Relaxed);
+                    score += result.best_fitness.to_f64();
+                }
+                Ok(score / repeats as f64)
+            })
+            .mutate(move |setting: &Hyperparameters, rng| Hyperparameters {
+                population: ((setting.population as f64) * (0.2 * standard_normal(rng)).exp()).round().clamp(min as f64, max as f64) as usize,
+                pc: (setting.pc + 0.1 * standard_normal(rng)).clamp(0.0, 1.0),
+                pm: (setting.pm + 0.1 * standard_normal(rng)).clamp(0.0, 1.0),
+                selection: if rng.gen::<f64>() < 0.2 {
+                    mutated_selections[rng.gen_range(0, mutated_selections.len())].clone()
+                } else {
+                    setting.selection.clone()
+                },
+            })
+            .offspring(|a: &Hyperparameters, b: &Hyperparameters, rng| Hyperparameters {
+                population: if rng.gen::<bool>() { a.population } else { b.population },
+                pc: if rng.gen::<bool>() { a.pc } else { b.pc },
+                pm: if rng.gen::<bool>() { a.pm } else { b.pm },
+                selection: if rng.gen::<bool>() { a.selection.clone() } else { b.selection.clone() },
+            })
+            .build()
+            .expect("the outer genetic algorithm is valid");
+        let result = meta.run_with_rng(&mut rng);
+
+        TuningResult { best: result.best, score: result.best_fitness, settings: result.evaluations, evaluations: evaluations.load(Ordering::Relaxed) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn problem<'a>() -> Genetic<'a, f64> {
+        Genetic::builder()
+            .elitism(4)
+            .random_agent(|rng| rng.gen_range(-100.0, 100.0))
+            .fitness(|a: &f64| -(a - 7.0).abs())
+            .mutate(|a: &f64, rng| a + rng.gen_range(-1.0, 1.0))
+            .offspring(|a: &f64, b: &f64, _rng| (a + b) / 2.0)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn tuner() {
+        assert!(Tuner::new(problem, 0).validate().is_err());
+        assert!(Tuner::new(problem, 100).population_range(50, 10).validate().is_err());
+        assert!(Tuner::new(problem, 100).selections(Vec::new()).validate().is_err());
+        assert!(Tuner::new(problem, 100).meta(1, 5).validate().is_err());
+
+        // the populations smaller than the elitism of the problem can't win
+        let tuner = Tuner::new(problem, 300).repeats(2).population_range(2, 40).meta(6, 4);
+        let result = tuner.run_with_seed(1);
+        assert!(result.best.population >= 4 && result.best.population <= 40);
+        assert!(tuner.selections.contains(&result.best.selection));
+        assert!(result.score > -1.0, "{:?}", result);
+        assert!(result.settings >= 6);
+        assert!(result.evaluations <= result.settings * 2 * 300);
+
+        // the score is the average of the seeded inner runs
+        let mut genetic = problem();
+        result.best.apply(&mut genetic);
+        genetic.max_evaluations = Some(300);
+        genetic.max_generation = 300;
+        let runs = (0..2).map(|seed| genetic.run_with_seed(seed).best_fitness).sum::<f64>() / 2.0;
+        assert_eq!(runs, result.score);
+    }
+}